@@ -1,8 +1,10 @@
 //! Module for the symbols representing an element in a `SMILES` string
-use core::{cmp::Ordering, fmt};
+use core::{cmp::Ordering, fmt, str::FromStr};
 
 use elements_rs::Element;
 
+use crate::errors::SmilesError;
+
 #[derive(Copy, Default, Debug, PartialEq, Clone, Eq, Hash)]
 /// Enum to allow for standard elements or the `WildCard` variant, represented
 /// as `*`
@@ -109,6 +111,49 @@ impl fmt::Display for AtomSymbol {
     }
 }
 
+impl TryFrom<&str> for AtomSymbol {
+    type Error = SmilesError;
+
+    /// Parses an element symbol such as `C` or `Fe`, or `*` for the
+    /// wildcard. Aromatic spellings such as `c` are rejected, since the
+    /// symbol does not carry aromaticity.
+    ///
+    /// # Errors
+    /// Returns [`SmilesError::ElementsRs`] when `text` is not an element
+    /// symbol.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use elements_rs::Element;
+    /// use smiles_parser::atom::atom_symbol::AtomSymbol;
+    ///
+    /// assert_eq!(AtomSymbol::try_from("Fe")?, AtomSymbol::Element(Element::Fe));
+    /// assert_eq!(AtomSymbol::try_from("*")?, AtomSymbol::WildCard);
+    /// assert!(AtomSymbol::try_from("c").is_err());
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        if text == "*" {
+            return Ok(Self::WildCard);
+        }
+        Ok(Self::Element(Element::from_str(text)?))
+    }
+}
+
+impl TryFrom<char> for AtomSymbol {
+    type Error = SmilesError;
+
+    /// Parses a one-letter element symbol or `*`.
+    ///
+    /// # Errors
+    /// Returns [`SmilesError::ElementsRs`] when `symbol` is not an element
+    /// symbol.
+    fn try_from(symbol: char) -> Result<Self, Self::Error> {
+        Self::try_from(&*symbol.encode_utf8(&mut [0; 4]))
+    }
+}
+
 impl PartialOrd for AtomSymbol {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -136,6 +181,17 @@ mod tests {
 
     use crate::atom::atom_symbol::AtomSymbol;
 
+    #[test]
+    fn atom_symbols_round_trip_through_text() {
+        for symbol in [AtomSymbol::WildCard, AtomSymbol::Element(Element::Cl)] {
+            assert_eq!(AtomSymbol::try_from(symbol.to_string().as_str()), Ok(symbol));
+        }
+        assert_eq!(AtomSymbol::try_from('N'), Ok(AtomSymbol::Element(Element::N)));
+        assert_eq!(AtomSymbol::try_from('*'), Ok(AtomSymbol::WildCard));
+        assert!(AtomSymbol::try_from("").is_err());
+        assert!(AtomSymbol::try_from("Xx").is_err());
+    }
+
     #[test]
     fn test_atom_symbols_all() {
         let hydrogen = Element::H;
@@ -1,7 +1,7 @@
 //! Module for validating a charge on an atom
 use core::fmt;
 
-use crate::errors::SmilesError;
+use crate::{errors::SmilesError, parser::token_iter::parse_charge};
 
 #[derive(Copy, Default, Debug, PartialEq, Clone, Eq, Hash)]
 /// Wrapper struct for possible charge to limit upper and lower bounds
@@ -60,6 +60,33 @@ impl Charge {
     }
 }
 
+impl TryFrom<&str> for Charge {
+    type Error = SmilesError;
+
+    /// Parses a charge as written in a bracket atom, such as `+`, `--` or
+    /// `-2`; the empty string is no charge, matching the
+    /// [`Display`](fmt::Display) impl.
+    ///
+    /// # Errors
+    /// Returns [`SmilesError::ChargeOverflow`] or
+    /// [`SmilesError::ChargeUnderflow`] for charges beyond `±15` and
+    /// [`SmilesError::UnexpectedCharacter`] for any other text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::atom::bracketed::charge::Charge;
+    ///
+    /// assert_eq!(Charge::try_from("+++")?.get(), 3);
+    /// assert_eq!(Charge::try_from("-2")?.get(), -2);
+    /// assert!(Charge::try_from("+x").is_err());
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        parse_charge(text)
+    }
+}
+
 impl fmt::Display for Charge {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.get() {
@@ -81,6 +108,17 @@ mod tests {
     use super::Charge;
     use crate::errors::SmilesError;
 
+    #[test]
+    fn charges_round_trip_through_text() {
+        for value in -15..=15 {
+            let charge = Charge::try_new(value).unwrap();
+            assert_eq!(Charge::try_from(charge.to_string().as_str()), Ok(charge));
+        }
+        assert_eq!(Charge::try_from("--").map(|c| c.get()), Ok(-2));
+        assert_eq!(Charge::try_from("+16"), Err(SmilesError::ChargeOverflow(16)));
+        assert_eq!(Charge::try_from("+1a"), Err(SmilesError::UnexpectedCharacter('a')));
+    }
+
     #[test]
     fn default_is_zero() {
         assert_eq!(Charge::default().get(), 0);
@@ -1,7 +1,7 @@
 //! Module for parsing, validating, and specifying the chirality of an atom
 use core::fmt;
 
-use crate::errors::SmilesError;
+use crate::{errors::SmilesError, parser::token_iter::parse_chirality};
 
 #[derive(Copy, Debug, PartialEq, Clone, Eq, Hash)]
 /// Specifies the chirality if present
@@ -109,6 +109,45 @@ impl Chirality {
         (1..=30).contains(&num).then_some(Self::OH(num)).ok_or(SmilesError::InvalidChirality)
     }
 
+    /// Returns the shorthand spelling of the same configuration: `@TH1` is
+    /// `@` and `@TH2` is `@@`. Every other tag is returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::atom::bracketed::chirality::Chirality;
+    ///
+    /// assert_eq!(Chirality::TH(1).normalized(), Chirality::At);
+    /// assert_eq!(Chirality::TH(2).normalized(), Chirality::AtAt);
+    /// assert_eq!(Chirality::AL(1).normalized(), Chirality::AL(1));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn normalized(self) -> Self {
+        match self {
+            Self::TH(1) => Self::At,
+            Self::TH(2) => Self::AtAt,
+            other => other,
+        }
+    }
+
+    /// Returns whether both tags spell the same configuration, treating
+    /// `@TH1`/`@` and `@TH2`/`@@` as equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::atom::bracketed::chirality::Chirality;
+    ///
+    /// assert!(Chirality::TH(1).is_equivalent(Chirality::At));
+    /// assert!(!Chirality::TH(1).is_equivalent(Chirality::AtAt));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_equivalent(self, other: Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+
     #[inline]
     #[must_use]
     pub(crate) const fn display_len(self) -> usize {
@@ -122,6 +161,32 @@ impl Chirality {
     }
 }
 
+impl TryFrom<&str> for Chirality {
+    type Error = SmilesError;
+
+    /// Parses a chirality tag as written in a bracket atom, such as `@`,
+    /// `@@`, `@TH2` or `@OH30`.
+    ///
+    /// # Errors
+    /// Returns [`SmilesError::InvalidChirality`] for malformed or out of
+    /// range tags and [`SmilesError::UnexpectedCharacter`] for text after a
+    /// complete tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::atom::bracketed::chirality::Chirality;
+    ///
+    /// assert_eq!(Chirality::try_from("@@")?, Chirality::AtAt);
+    /// assert_eq!(Chirality::try_from("@TB12")?, Chirality::TB(12));
+    /// assert!(Chirality::try_from("@TH3").is_err());
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        parse_chirality(text)
+    }
+}
+
 impl fmt::Display for Chirality {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -151,7 +216,25 @@ mod tests {
     use alloc::string::ToString;
 
     use super::{Chirality, decimal_len_u8};
-    use crate::errors::SmilesError;
+    use crate::{errors::SmilesError, parser::token_iter::TokenIter, token::Token};
+
+    #[test]
+    fn chirality_tags_round_trip_through_text() {
+        for chirality in [
+            Chirality::At,
+            Chirality::AtAt,
+            Chirality::TH(2),
+            Chirality::AL(1),
+            Chirality::SP(3),
+            Chirality::TB(20),
+            Chirality::OH(30),
+        ] {
+            assert_eq!(Chirality::try_from(chirality.to_string().as_str()), Ok(chirality));
+        }
+        assert_eq!(Chirality::try_from(""), Err(SmilesError::InvalidChirality));
+        assert_eq!(Chirality::try_from("@OH31"), Err(SmilesError::InvalidChirality));
+        assert_eq!(Chirality::try_from("@@@"), Err(SmilesError::UnexpectedCharacter('@')));
+    }
 
     #[test]
     fn try_th_accepts_valid_values() {
@@ -279,4 +362,48 @@ mod tests {
         assert_eq!(decimal_len_u8(10), 2);
         assert_eq!(decimal_len_u8(100), 3);
     }
+
+    #[test]
+    fn normalized_only_rewrites_the_tetrahedral_spellings() {
+        assert_eq!(Chirality::TH(1).normalized(), Chirality::At);
+        assert_eq!(Chirality::TH(2).normalized(), Chirality::AtAt);
+        for unchanged in [
+            Chirality::At,
+            Chirality::AtAt,
+            Chirality::AL(2),
+            Chirality::SP(1),
+            Chirality::TB(1),
+            Chirality::OH(2),
+        ] {
+            assert_eq!(unchanged.normalized(), unchanged);
+        }
+
+        assert!(Chirality::TH(2).is_equivalent(Chirality::AtAt));
+        assert!(Chirality::At.is_equivalent(Chirality::TH(1)));
+        assert!(!Chirality::TB(1).is_equivalent(Chirality::At));
+        assert!(!Chirality::AL(1).is_equivalent(Chirality::At));
+    }
+
+    #[test]
+    fn display_round_trips_through_the_tokenizer() {
+        let ranges: [(u8, fn(u8) -> Chirality); 5] = [
+            (2, Chirality::TH),
+            (2, Chirality::AL),
+            (3, Chirality::SP),
+            (20, Chirality::TB),
+            (30, Chirality::OH),
+        ];
+        let chiralities = [Chirality::At, Chirality::AtAt]
+            .into_iter()
+            .chain(ranges.into_iter().flat_map(|(max, variant)| (1..=max).map(variant)));
+        for chirality in chiralities {
+            let input = format!("[C{chirality}H]");
+            let token = TokenIter::from(input.as_str()).next().unwrap().unwrap();
+            assert!(
+                matches!(token.token(), Token::Atom(atom) if atom.chirality() == Some(chirality)),
+                "{input}"
+            );
+            assert_eq!(token.token().to_string(), input);
+        }
+    }
 }
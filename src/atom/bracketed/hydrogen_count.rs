@@ -0,0 +1,119 @@
+//! Module for validating the explicit hydrogen count of a bracket atom
+use core::fmt;
+
+use crate::errors::SmilesError;
+
+#[derive(Copy, Default, Debug, PartialEq, Clone, Eq, Hash, PartialOrd, Ord)]
+/// Wrapper struct for the hydrogen count of a bracket atom, limited to the
+/// single digit of the OpenSMILES `hcount` rule
+pub struct HydrogenCount(u8);
+
+impl HydrogenCount {
+    /// Largest count the OpenSMILES grammar can write, `H` followed by one
+    /// digit.
+    pub const MAX: u8 = 9;
+
+    /// Largest count accepted with
+    /// [`ParserOptions::multi_digit_hydrogen_counts`](crate::ParserOptions::multi_digit_hydrogen_counts).
+    ///
+    /// Chosen to mirror the magnitude cap on bracket-atom charges; bounding
+    /// the value keeps downstream `u8` valence math from overflowing.
+    pub const MAX_MULTI_DIGIT: u8 = 15;
+
+    /// Attempts to set the `HydrogenCount`, if above [`HydrogenCount::MAX`]
+    /// returns [`SmilesError::HydrogenCountOverflow`]
+    ///
+    /// # Errors
+    /// - Returns [`SmilesError::HydrogenCountOverflow`] if `u8` is greater than
+    ///   `9`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{SmilesError, atom::bracketed::hydrogen_count::HydrogenCount};
+    ///
+    /// assert_eq!(HydrogenCount::try_new(4)?.get(), 4);
+    /// assert_eq!(HydrogenCount::try_new(12), Err(SmilesError::HydrogenCountOverflow(12)));
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    pub fn try_new(count: u8) -> Result<Self, SmilesError> {
+        (count <= Self::MAX).then_some(Self(count)).ok_or(SmilesError::HydrogenCountOverflow(count))
+    }
+
+    /// Attempts to set a `HydrogenCount` written with several digits, if
+    /// above [`HydrogenCount::MAX_MULTI_DIGIT`] returns
+    /// [`SmilesError::HydrogenCountOverflow`]
+    ///
+    /// # Errors
+    /// - Returns [`SmilesError::HydrogenCountOverflow`] if `u8` is greater than
+    ///   `15`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::atom::bracketed::hydrogen_count::HydrogenCount;
+    ///
+    /// assert_eq!(HydrogenCount::try_new_multi_digit(12)?.get(), 12);
+    /// assert!(HydrogenCount::try_new_multi_digit(16).is_err());
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    pub fn try_new_multi_digit(count: u8) -> Result<Self, SmilesError> {
+        (count <= Self::MAX_MULTI_DIGIT)
+            .then_some(Self(count))
+            .ok_or(SmilesError::HydrogenCountOverflow(count))
+    }
+
+    /// Returns the `HydrogenCount` value as `u8`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::atom::bracketed::hydrogen_count::HydrogenCount;
+    ///
+    /// assert_eq!(HydrogenCount::try_new(0)?.get(), 0);
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn get(&self) -> u8 {
+        self.0
+    }
+}
+
+impl fmt::Display for HydrogenCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get() {
+            0 => Ok(()),
+            1 => f.write_str("H"),
+            n => write!(f, "H{n}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::HydrogenCount;
+    use crate::errors::SmilesError;
+
+    #[test]
+    fn counts_are_capped_at_one_digit() {
+        for count in 0..=HydrogenCount::MAX {
+            assert_eq!(HydrogenCount::try_new(count).map(|h| h.get()), Ok(count));
+        }
+        assert_eq!(HydrogenCount::try_new(10), Err(SmilesError::HydrogenCountOverflow(10)));
+        assert_eq!(HydrogenCount::try_new_multi_digit(15).map(|h| h.get()), Ok(15));
+        assert_eq!(
+            HydrogenCount::try_new_multi_digit(16),
+            Err(SmilesError::HydrogenCountOverflow(16))
+        );
+    }
+
+    #[test]
+    fn counts_display_as_bracket_fields() {
+        assert_eq!(HydrogenCount::default().to_string(), "");
+        assert_eq!(HydrogenCount::try_new(1).unwrap().to_string(), "H");
+        assert_eq!(HydrogenCount::try_new(3).unwrap().to_string(), "H3");
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! The main [`crate::atom::Atom`] type stores both bracket and organic-subset
 //! atoms directly. This module remains the home of bracket-only helper types
-//! such as charge and chirality.
+//! such as charge, chirality and hydrogen count.
 
 pub mod charge;
 pub mod chirality;
+pub mod hydrogen_count;
@@ -16,6 +16,7 @@ use crate::{
         bracketed::{charge::Charge, chirality::Chirality},
     },
     errors::SmilesError,
+    parser::token_iter::parse_bracket_atom_prefix,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -214,6 +215,36 @@ impl Atom {
         }
     }
 
+    /// Parses the bracket atom at the start of `text`, such as
+    /// `[13C@@H2+:5]`, and returns it with the byte length of its spelling,
+    /// so standalone bracket expressions can be read without a whole SMILES
+    /// string around them.
+    ///
+    /// # Errors
+    /// Returns the error the tokenizer reports for the same bracket atom,
+    /// [`SmilesError::UnexpectedCharacter`] if `text` does not start with
+    /// `[`, and [`SmilesError::EmptyInput`] if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use elements_rs::Element;
+    /// use smiles_parser::atom::Atom;
+    ///
+    /// let (atom, len) = Atom::parse_bracket("[13C@@H2+:5]CC")?;
+    /// assert_eq!(len, 12);
+    /// assert_eq!(atom.element(), Some(Element::C));
+    /// assert_eq!(atom.isotope_mass_number(), Some(13));
+    /// assert_eq!((atom.hydrogen_count(), atom.charge_value(), atom.class()), (2, 1, 5));
+    /// assert_eq!(atom.to_string(), "[13C@@H2+:5]");
+    /// assert!(Atom::parse_bracket("[C").is_err());
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    #[inline]
+    pub fn parse_bracket(text: &str) -> Result<(Self, usize), SmilesError> {
+        parse_bracket_atom_prefix(text)
+    }
+
     /// Returns the syntax category used to parse this atom.
     ///
     /// # Examples
@@ -377,6 +408,52 @@ impl Atom {
         self
     }
 
+    /// Returns a copy with the tetrahedral chirality cleared.
+    #[inline]
+    #[must_use]
+    pub const fn without_chirality(mut self) -> Self {
+        self.chirality = None;
+        self
+    }
+
+    /// Returns a copy with `@TH1`/`@TH2` respelled as `@`/`@@`, see
+    /// [`Chirality::normalized`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{Smiles, atom::bracketed::chirality::Chirality};
+    ///
+    /// let smiles: Smiles = "N[C@TH2H](C)O".parse()?;
+    /// let atom = smiles.nodes()[1].with_normalized_chirality();
+    /// assert_eq!(atom.chirality(), Some(Chirality::AtAt));
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn with_normalized_chirality(mut self) -> Self {
+        if let Some(chirality) = self.chirality {
+            self.chirality = Some(chirality.normalized());
+        }
+        self
+    }
+
+    /// Returns a copy with the isotope label cleared, e.g. `[H]` for `[2H]`.
+    #[inline]
+    #[must_use]
+    pub const fn without_isotope(mut self) -> Self {
+        self.isotope_mass_number = None;
+        self
+    }
+
+    /// Returns a copy with the atom class cleared.
+    #[inline]
+    #[must_use]
+    pub const fn without_class(mut self) -> Self {
+        self.class = 0;
+        self
+    }
+
     /// Returns the explicit hydrogen count written on the atom.
     ///
     /// # Examples
@@ -608,8 +685,88 @@ impl Atom {
             AtomSyntax::Bracket => None,
         }
     }
+
+    /// Returns the fixed-width little-endian encoding used by binary
+    /// containers: symbol, flags, isotope, hydrogens, charge, class and
+    /// chirality.
+    #[must_use]
+    pub(crate) fn to_packed(self) -> [u8; PACKED_ATOM_LEN] {
+        let symbol = match self.symbol {
+            AtomSymbol::Element(element) => u8::from(element),
+            AtomSymbol::WildCard => 0,
+        };
+        let flags = u8::from(self.aromatic)
+            | (u8::from(self.syntax == AtomSyntax::Bracket) << 1)
+            | (u8::from(self.isotope_mass_number.is_some()) << 2);
+        let isotope = self.isotope_mass_number.unwrap_or_default().to_le_bytes();
+        let class = self.class.to_le_bytes();
+        let (chirality_tag, chirality_value) = match self.chirality {
+            None => (0, 0),
+            Some(Chirality::At) => (1, 0),
+            Some(Chirality::AtAt) => (2, 0),
+            Some(Chirality::TH(value)) => (3, value),
+            Some(Chirality::AL(value)) => (4, value),
+            Some(Chirality::SP(value)) => (5, value),
+            Some(Chirality::TB(value)) => (6, value),
+            Some(Chirality::OH(value)) => (7, value),
+        };
+        [
+            symbol,
+            flags,
+            isotope[0],
+            isotope[1],
+            self.hydrogens,
+            self.charge.get().to_le_bytes()[0],
+            class[0],
+            class[1],
+            chirality_tag,
+            chirality_value,
+        ]
+    }
+
+    /// Decodes an atom written by [`Atom::to_packed`], returning `None` for
+    /// bytes no atom encodes to.
+    #[must_use]
+    pub(crate) fn from_packed(bytes: [u8; PACKED_ATOM_LEN]) -> Option<Self> {
+        let symbol = match bytes[0] {
+            0 => AtomSymbol::WildCard,
+            atomic_number => AtomSymbol::Element(Element::try_from(atomic_number).ok()?),
+        };
+        let flags = bytes[1];
+        if flags > 0b111 {
+            return None;
+        }
+        let isotope = u16::from_le_bytes([bytes[2], bytes[3]]);
+        let chirality = match (bytes[8], bytes[9]) {
+            (0, 0) => None,
+            (1, 0) => Some(Chirality::At),
+            (2, 0) => Some(Chirality::AtAt),
+            (3, value) => Chirality::try_th(value).ok(),
+            (4, value) => Chirality::try_al(value).ok(),
+            (5, value) => Chirality::try_sp(value).ok(),
+            (6, value) => Chirality::try_tb(value).ok(),
+            (7, value) => Chirality::try_oh(value).ok(),
+            _ => return None,
+        };
+        if bytes[8] != 0 && chirality.is_none() {
+            return None;
+        }
+        Some(Self {
+            symbol,
+            isotope_mass_number: (flags & 0b100 != 0).then_some(isotope),
+            aromatic: flags & 0b1 != 0,
+            hydrogens: bytes[4],
+            charge: Charge::try_new(i8::from_le_bytes([bytes[5]])).ok()?,
+            class: u16::from_le_bytes([bytes[6], bytes[7]]),
+            chirality,
+            syntax: if flags & 0b10 != 0 { AtomSyntax::Bracket } else { AtomSyntax::OrganicSubset },
+        })
+    }
 }
 
+/// Length of the encoding returned by [`Atom::to_packed`].
+pub(crate) const PACKED_ATOM_LEN: usize = 10;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 /// Builder for bracket atoms.
 pub struct AtomBuilder {
@@ -824,6 +981,67 @@ fn rendered_symbol_len(symbol: AtomSymbol, aromatic: bool, syntax: AtomSyntax) -
     }
 }
 
+/// Returns whether `atom` has to be written as a bracket atom.
+///
+/// `implicit_hydrogens` is the hydrogen count the atom would get from the
+/// default valence rules if written without brackets, which depends on its
+/// bonds and so is computed by the caller. Brackets are mandatory when the
+/// atom has an isotope, a charge, a class or a chirality, when its symbol is
+/// outside the organic subset (including aromatic symbols such as `se`), or
+/// when its hydrogen count differs from `implicit_hydrogens`, as for an
+/// unusual valence. Organic-subset atoms never need brackets, since their
+/// hydrogens are implicit by construction.
+///
+/// # Examples
+///
+/// ```
+/// use elements_rs::Element;
+/// use smiles_parser::atom::{Atom, atom_symbol::AtomSymbol, needs_brackets};
+///
+/// let methane = Atom::builder().with_symbol(AtomSymbol::Element(Element::C)).with_hydrogens(4);
+/// assert!(!needs_brackets(&methane.build(), 4));
+/// assert!(needs_brackets(&methane.build(), 3));
+///
+/// let iron = Atom::builder().with_symbol(AtomSymbol::Element(Element::Fe)).build();
+/// assert!(needs_brackets(&iron, 0));
+/// ```
+#[inline]
+#[must_use]
+pub fn needs_brackets(atom: &Atom, implicit_hydrogens: u8) -> bool {
+    if atom.syntax() == AtomSyntax::OrganicSubset {
+        return false;
+    }
+    atom.isotope_mass_number().is_some()
+        || atom.charge_value() != 0
+        || atom.class() != 0
+        || atom.chirality().is_some()
+        || !is_organic_subset_symbol(atom.symbol())
+        || (atom.aromatic()
+            && atom.element().is_none_or(|element| !can_write_unbracketed_aromatic(element)))
+        || atom.hydrogen_count() != implicit_hydrogens
+}
+
+/// Returns whether `symbol` may be written without brackets.
+#[inline]
+pub(crate) fn is_organic_subset_symbol(symbol: AtomSymbol) -> bool {
+    matches!(
+        symbol,
+        AtomSymbol::WildCard
+            | AtomSymbol::Element(
+                Element::B
+                    | Element::C
+                    | Element::N
+                    | Element::O
+                    | Element::P
+                    | Element::S
+                    | Element::F
+                    | Element::Cl
+                    | Element::Br
+                    | Element::I
+            )
+    )
+}
+
 #[inline]
 pub(crate) fn can_write_unbracketed_aromatic(element: Element) -> bool {
     matches!(element, Element::B | Element::C | Element::N | Element::O | Element::P | Element::S)
@@ -1130,6 +1348,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_bracket_reads_one_bracket_atom() {
+        for source in ["[*]", "[2H]", "[se]", "[Fe+3]", "[C@TB12H]", "[NH4+:12]"] {
+            let (atom, len) = Atom::parse_bracket(source).unwrap();
+            assert_eq!(len, source.len());
+            assert_eq!(atom.to_string(), source);
+        }
+        assert_eq!(Atom::parse_bracket("[OH-]O").map(|(_, len)| len), Ok(5));
+        assert_eq!(Atom::parse_bracket(""), Err(SmilesError::EmptyInput));
+        assert_eq!(Atom::parse_bracket("C"), Err(SmilesError::UnexpectedCharacter('C')));
+        assert_eq!(Atom::parse_bracket("[CH4"), Err(SmilesError::UnclosedBracket));
+        assert_eq!(Atom::parse_bracket("[C+16]"), Err(SmilesError::ChargeOverflow(16)));
+    }
+
+    #[test]
+    fn needs_brackets_covers_every_mandatory_bracket_field() {
+        let carbon =
+            || Atom::builder().with_symbol(AtomSymbol::Element(Element::C)).with_hydrogens(4);
+        assert!(!needs_brackets(&carbon().build(), 4));
+        assert!(needs_brackets(&carbon().with_hydrogens(2).build(), 4));
+        assert!(needs_brackets(&carbon().with_isotope(13).build(), 4));
+        assert!(needs_brackets(&carbon().with_charge(Charge::try_new(-1).unwrap()).build(), 4));
+        assert!(needs_brackets(&carbon().with_class(1).build(), 4));
+        assert!(needs_brackets(&carbon().with_chirality(Chirality::At).build(), 4));
+
+        let aromatic = |element| {
+            Atom::builder().with_symbol(AtomSymbol::Element(element)).with_aromatic(true).build()
+        };
+        assert!(!needs_brackets(&aromatic(Element::S), 0));
+        assert!(needs_brackets(&aromatic(Element::Se), 0));
+        assert!(!needs_brackets(
+            &Atom::new_organic_subset(AtomSymbol::Element(Element::N), false),
+            3
+        ));
+        assert!(!needs_brackets(&Atom::builder().build(), 0));
+    }
+
     #[test]
     fn rendered_len_hint_matches_actual_length_across_bracket_fields() {
         // The capacity hint must equal the rendered length so the render buffer
@@ -1237,4 +1492,31 @@ mod tests {
             assert_eq!(atom.to_string(), expected);
         }
     }
+
+    #[test]
+    fn packed_atoms_round_trip() {
+        let atoms = [
+            Atom::new_organic_subset(AtomSymbol::Element(Element::C), true),
+            Atom::new_organic_subset(AtomSymbol::WildCard, false),
+            Atom::builder()
+                .with_symbol(AtomSymbol::Element(Element::C))
+                .with_isotope(13)
+                .with_chirality(Chirality::TB(17))
+                .with_hydrogens(1)
+                .with_charge(Charge::try_new(-15).unwrap())
+                .with_class(65535)
+                .build(),
+            Atom::builder().with_symbol(AtomSymbol::Element(Element::Og)).with_isotope(0).build(),
+        ];
+        for atom in atoms {
+            assert_eq!(Atom::from_packed(atom.to_packed()), Some(atom));
+        }
+
+        let mut corrupt = atoms[0].to_packed();
+        corrupt[0] = 200;
+        assert_eq!(Atom::from_packed(corrupt), None);
+        let mut corrupt = atoms[2].to_packed();
+        corrupt[9] = 21;
+        assert_eq!(Atom::from_packed(corrupt), None);
+    }
 }
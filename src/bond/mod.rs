@@ -4,6 +4,8 @@ pub mod ring_num;
 
 use core::fmt;
 
+use crate::errors::SmilesError;
+
 #[derive(Copy, Debug, Default, PartialEq, Clone, Eq, Hash)]
 /// Enum used to specify the Bond type, based on SMILES specification
 pub enum Bond {
@@ -29,6 +31,60 @@ impl fmt::Display for Bond {
 }
 
 impl Bond {
+    /// Returns the bond order: `1` for single bonds, including the directional
+    /// `/` and `\`, up to `4` for quadruple bonds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::bond::Bond;
+    ///
+    /// assert_eq!(Bond::Up.order(), 1);
+    /// assert_eq!(Bond::Triple.order(), 3);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn order(self) -> u8 {
+        match self {
+            Self::Single | Self::Up | Self::Down => 1,
+            Self::Double => 2,
+            Self::Triple => 3,
+            Self::Quadruple => 4,
+        }
+    }
+
+    /// Returns the non-directional bond of the given order, or `None` for
+    /// orders outside `1..=4`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::bond::Bond;
+    ///
+    /// assert_eq!(Bond::from_order(2), Some(Bond::Double));
+    /// assert_eq!(Bond::from_order(Bond::Down.order()), Some(Bond::Single));
+    /// assert_eq!(Bond::from_order(5), None);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn from_order(order: u8) -> Option<Self> {
+        match order {
+            1 => Some(Self::Single),
+            2 => Some(Self::Double),
+            3 => Some(Self::Triple),
+            4 => Some(Self::Quadruple),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the bond is a directional `/` or `\` single bond,
+    /// which marks double-bond stereochemistry.
+    #[inline]
+    #[must_use]
+    pub const fn is_stereo_marker(self) -> bool {
+        matches!(self, Self::Up | Self::Down)
+    }
+
     #[inline]
     #[must_use]
     pub(crate) const fn smiles_symbol(self) -> &'static str {
@@ -52,6 +108,64 @@ impl Bond {
     }
 }
 
+impl TryFrom<char> for Bond {
+    type Error = SmilesError;
+
+    /// Parses the bond symbol written by the [`Display`](fmt::Display) impl.
+    ///
+    /// # Errors
+    /// Returns [`SmilesError::UnexpectedCharacter`] for any other character,
+    /// including the aromatic `:`, which [`BondDescriptor`] represents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::bond::Bond;
+    ///
+    /// assert_eq!(Bond::try_from('#'), Ok(Bond::Triple));
+    /// assert!(Bond::try_from(':').is_err());
+    /// ```
+    fn try_from(symbol: char) -> Result<Self, Self::Error> {
+        match symbol {
+            '-' => Ok(Self::Single),
+            '=' => Ok(Self::Double),
+            '#' => Ok(Self::Triple),
+            '$' => Ok(Self::Quadruple),
+            '/' => Ok(Self::Up),
+            '\\' => Ok(Self::Down),
+            _ => Err(SmilesError::UnexpectedCharacter(symbol)),
+        }
+    }
+}
+
+impl TryFrom<&str> for Bond {
+    type Error = SmilesError;
+
+    /// Parses a string holding a single bond symbol, as `TryFrom<char>` does.
+    ///
+    /// # Errors
+    /// Returns [`SmilesError::EmptyInput`] for an empty string and
+    /// [`SmilesError::UnexpectedCharacter`] for anything but a single bond
+    /// symbol.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::bond::Bond;
+    ///
+    /// assert_eq!(Bond::try_from("/"), Ok(Bond::Up));
+    /// assert!(Bond::try_from("==").is_err());
+    /// ```
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        let mut characters = text.chars();
+        let symbol = characters.next().ok_or(SmilesError::EmptyInput)?;
+        match characters.next() {
+            Some(extra) => Err(SmilesError::UnexpectedCharacter(extra)),
+            None => Self::try_from(symbol),
+        }
+    }
+}
+
 /// Parsed or rendered bond syntax with aromaticity carried separately from
 /// the underlying bond order.
 #[derive(Copy, Debug, Default, PartialEq, Clone, Eq, Hash)]
@@ -90,6 +204,23 @@ impl BondDescriptor {
         self.aromatic
     }
 
+    /// Returns the integral bond order, or `None` for aromatic bonds, whose
+    /// order depends on the Kekule form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::bond::{Bond, BondDescriptor};
+    ///
+    /// assert_eq!(BondDescriptor::new(Bond::Double).order(), Some(2));
+    /// assert_eq!(BondDescriptor::aromatic(Bond::Single).order(), None);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn order(self) -> Option<u8> {
+        if self.aromatic { None } else { Some(self.bond.order()) }
+    }
+
     #[inline]
     #[must_use]
     pub(crate) const fn with_bond(mut self, bond: Bond) -> Self {
@@ -119,7 +250,10 @@ impl fmt::Display for BondDescriptor {
 mod tests {
     use alloc::string::ToString;
 
-    use crate::bond::{Bond, BondDescriptor};
+    use crate::{
+        bond::{Bond, BondDescriptor},
+        errors::SmilesError,
+    };
 
     #[test]
     fn test_default() {
@@ -141,6 +275,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn bond_orders_and_symbols_round_trip() {
+        for bond in [Bond::Single, Bond::Double, Bond::Triple, Bond::Quadruple] {
+            assert_eq!(Bond::from_order(bond.order()), Some(bond));
+            assert!(!bond.is_stereo_marker());
+        }
+        for bond in [Bond::Up, Bond::Down] {
+            assert_eq!(bond.order(), 1);
+            assert!(bond.is_stereo_marker());
+        }
+        for bond in
+            [Bond::Single, Bond::Double, Bond::Triple, Bond::Quadruple, Bond::Up, Bond::Down]
+        {
+            let symbol = bond.to_string().chars().next().unwrap();
+            assert_eq!(Bond::try_from(symbol), Ok(bond));
+            assert_eq!(Bond::try_from(bond.to_string().as_str()), Ok(bond));
+        }
+        assert_eq!(Bond::from_order(0), None);
+        assert_eq!(Bond::try_from('a'), Err(SmilesError::UnexpectedCharacter('a')));
+        assert_eq!(Bond::try_from(""), Err(SmilesError::EmptyInput));
+        assert_eq!(Bond::try_from("=#"), Err(SmilesError::UnexpectedCharacter('#')));
+        assert_eq!(BondDescriptor::aromatic(Bond::Double).order(), None);
+    }
+
     #[test]
     fn directional_bonds_collapse_to_single() {
         assert_eq!(Bond::Up.without_direction(), Bond::Single);
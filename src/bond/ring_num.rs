@@ -1,7 +1,7 @@
 //! Module for mapping and validating a ring marker
 use core::fmt;
 
-use crate::errors::SmilesError;
+use crate::{errors::SmilesError, parser::token_iter::single_token, token::Token};
 
 #[derive(Copy, Debug, PartialEq, PartialOrd, Clone, Eq, Ord, Hash)]
 /// Represents a ring marker and implements tighter bounds for the minimal and
@@ -41,11 +41,73 @@ impl RingNum {
     pub fn get(&self) -> u8 {
         self.0
     }
+
+    /// Returns whether the ring number is spelled with `%`.
+    ///
+    /// The tokenizer rejects `%` before numbers below `10`, so the spelling
+    /// follows from the value: `%12` is one closure, while `12` is the two
+    /// closures `1` and `2`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::bond::ring_num::RingNum;
+    ///
+    /// assert!(RingNum::try_new(12)?.uses_percent());
+    /// assert!(!RingNum::try_new(1)?.uses_percent());
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn uses_percent(self) -> bool {
+        self.0 > 9
+    }
+}
+
+impl TryFrom<&str> for RingNum {
+    type Error = SmilesError;
+
+    /// Parses a ring label as written after an atom, such as `7` or `%12`.
+    ///
+    /// # Errors
+    /// Returns the tokenizer error for malformed labels, such as
+    /// [`SmilesError::InvalidRingNumber`] for `%7`, and
+    /// [`SmilesError::UnexpectedCharacter`] when `text` holds more than one
+    /// label.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::bond::ring_num::RingNum;
+    ///
+    /// assert_eq!(RingNum::try_from("%12")?.get(), 12);
+    /// assert!(RingNum::try_from("12").is_err());
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        match single_token(text)? {
+            Token::RingClosure { ring_num, .. } => Ok(ring_num),
+            _ => Err(SmilesError::InvalidRingNumber),
+        }
+    }
+}
+
+impl TryFrom<char> for RingNum {
+    type Error = SmilesError;
+
+    /// Parses a single-digit ring label.
+    ///
+    /// # Errors
+    /// Returns [`SmilesError::InvalidRingNumber`] for anything but a digit.
+    fn try_from(digit: char) -> Result<Self, Self::Error> {
+        let value = digit.to_digit(10).ok_or(SmilesError::InvalidRingNumber)?;
+        Self::try_new(u8::try_from(value).unwrap_or_else(|_| unreachable!("digits are below 10")))
+    }
 }
 
 impl fmt::Display for RingNum {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.get() > 9 { write!(f, "%{}", self.get()) } else { write!(f, "{}", self.get()) }
+        if self.uses_percent() { write!(f, "%{}", self.get()) } else { write!(f, "{}", self.get()) }
     }
 }
 
@@ -54,6 +116,20 @@ mod tests {
     use alloc::string::ToString;
 
     use crate::{bond::ring_num::RingNum, errors::SmilesError};
+    #[test]
+    fn ring_labels_round_trip_through_text() {
+        for value in [0, 7, 10, 99] {
+            let ring_num = RingNum::try_new(value).unwrap();
+            assert_eq!(RingNum::try_from(ring_num.to_string().as_str()), Ok(ring_num));
+        }
+        assert_eq!(RingNum::try_from('3'), RingNum::try_new(3));
+        assert_eq!(RingNum::try_from('x'), Err(SmilesError::InvalidRingNumber));
+        assert_eq!(RingNum::try_from("%7"), Err(SmilesError::InvalidRingNumber));
+        assert_eq!(RingNum::try_from("12"), Err(SmilesError::UnexpectedCharacter('2')));
+        assert_eq!(RingNum::try_from("C"), Err(SmilesError::InvalidRingNumber));
+        assert_eq!(RingNum::try_from(""), Err(SmilesError::EmptyInput));
+    }
+
     #[test]
     fn test_ring_num_try_new_bounds() -> Result<(), SmilesError> {
         assert_eq!(RingNum::try_new(0)?.get(), 0);
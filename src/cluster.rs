@@ -0,0 +1,176 @@
+//! Taylor-Butina clustering over precomputed fingerprints.
+//!
+//! [`butina`] links every pair of molecules whose [`Fingerprint`] Tanimoto
+//! similarity reaches a threshold, then repeatedly picks the unassigned
+//! molecule with the most neighbors as a centroid and groups it with its
+//! unassigned neighbors, following RDKit's `Butina.ClusterData`. Neighbor
+//! counts are computed once, so the result does not depend on the order in
+//! which clusters are formed beyond ties, which go to the lower index.
+//! [`representatives`] renders the centroids as canonical SMILES, giving a
+//! diverse subset of a dataset. The neighbor search compares all pairs; with
+//! the `rayon` feature it runs on all threads.
+//!
+//! # Examples
+//!
+//! ```
+//! use smiles_parser::{Smiles, cluster};
+//!
+//! let molecules = ["CCO", "OCC", "c1ccccc1", "c1ccccc1", "N#N"]
+//!     .into_iter()
+//!     .map(str::parse::<Smiles>)
+//!     .collect::<Result<Vec<_>, _>>()?;
+//! let fingerprints = molecules.iter().map(Smiles::fingerprint).collect::<Vec<_>>();
+//!
+//! let clusters = cluster::butina(&fingerprints, 0.5);
+//! assert_eq!(clusters.len(), 3);
+//! assert_eq!(clusters[2].members(), [4]);
+//! assert_eq!(cluster::representatives(&clusters, &molecules)[2], "N#N");
+//! # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use crate::smiles::{Fingerprint, Smiles, SmilesAtomPolicy};
+
+/// A group of similar molecules formed around a centroid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cluster {
+    members: Vec<usize>,
+}
+
+impl Cluster {
+    /// Returns the index of the centroid.
+    #[inline]
+    #[must_use]
+    pub fn centroid(&self) -> usize {
+        self.members[0]
+    }
+
+    /// Returns the indices of the members, the centroid first and the others
+    /// in increasing order.
+    #[inline]
+    #[must_use]
+    pub fn members(&self) -> &[usize] {
+        &self.members
+    }
+
+    /// Returns the number of members.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns `false`, since a cluster holds at least its centroid.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+/// Clusters `fingerprints` so that every member is at least `threshold`
+/// Tanimoto-similar to its centroid.
+///
+/// Clusters are returned in formation order, starting from the centroid with
+/// the most neighbors, and every index of `fingerprints` belongs to exactly
+/// one cluster. Molecules without neighbors form singleton clusters.
+#[must_use]
+pub fn butina(fingerprints: &[Fingerprint], threshold: f64) -> Vec<Cluster> {
+    let neighbors = |index: usize| {
+        let fingerprint = &fingerprints[index];
+        (0..fingerprints.len())
+            .filter(|&other| {
+                other != index && fingerprint.tanimoto(&fingerprints[other]) >= threshold
+            })
+            .collect::<Vec<_>>()
+    };
+
+    #[cfg(feature = "rayon")]
+    let neighbors: Vec<Vec<usize>> = {
+        use rayon::prelude::*;
+        (0..fingerprints.len()).into_par_iter().map(neighbors).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let neighbors: Vec<Vec<usize>> = (0..fingerprints.len()).map(neighbors).collect();
+
+    let mut order = (0..fingerprints.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&index| core::cmp::Reverse(neighbors[index].len()));
+
+    let mut assigned = vec![false; fingerprints.len()];
+    let mut clusters = Vec::new();
+    for centroid in order {
+        if assigned[centroid] {
+            continue;
+        }
+        assigned[centroid] = true;
+        let mut members = vec![centroid];
+        for &neighbor in &neighbors[centroid] {
+            if !assigned[neighbor] {
+                assigned[neighbor] = true;
+                members.push(neighbor);
+            }
+        }
+        clusters.push(Cluster { members });
+    }
+    clusters
+}
+
+/// Returns the canonical SMILES of the centroid of every cluster, where
+/// `molecules` are the graphs the clustered fingerprints were computed from.
+///
+/// # Panics
+/// Panics if a centroid is not an index of `molecules`.
+#[must_use]
+pub fn representatives<AtomPolicy: SmilesAtomPolicy>(
+    clusters: &[Cluster],
+    molecules: &[Smiles<AtomPolicy>],
+) -> Vec<String> {
+    clusters.iter().map(|cluster| molecules[cluster.centroid()].canonicalize().render()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprints(inputs: &[&str]) -> Vec<Fingerprint> {
+        inputs.iter().map(|input| input.parse::<Smiles>().unwrap().fingerprint()).collect()
+    }
+
+    #[test]
+    fn every_molecule_lands_in_one_cluster() {
+        let fingerprints =
+            fingerprints(&["CCO", "OCC", "CCCO", "c1ccccc1", "Cc1ccccc1", "c1ccccc1", "[Na+]"]);
+        let clusters = butina(&fingerprints, 0.6);
+
+        let mut seen =
+            clusters.iter().flat_map(|cluster| cluster.members()).copied().collect::<Vec<_>>();
+        seen.sort_unstable();
+        assert_eq!(seen, (0..fingerprints.len()).collect::<Vec<_>>());
+        for cluster in &clusters {
+            let centroid = &fingerprints[cluster.centroid()];
+            assert!(cluster.members().iter().all(|&member| {
+                centroid.tanimoto(&fingerprints[member]) >= 0.6 || member == cluster.centroid()
+            }));
+        }
+        assert!(clusters.iter().any(|cluster| cluster.members() == [6]));
+    }
+
+    #[test]
+    fn thresholds_bound_the_cluster_count() {
+        let fingerprints = fingerprints(&["CCO", "CCO", "CCN"]);
+        assert_eq!(butina(&fingerprints, 1.0).len(), 2);
+        assert_eq!(butina(&fingerprints, 0.0).len(), 1);
+        assert_eq!(butina(&fingerprints, 1.0)[0].members(), [0, 1]);
+        assert!(butina(&[], 0.5).is_empty());
+    }
+
+    #[test]
+    fn representatives_are_canonical() {
+        let molecules = ["OCC", "CCO"].map(|input| input.parse::<Smiles>().unwrap());
+        let clusters = butina(&molecules.iter().map(Smiles::fingerprint).collect::<Vec<_>>(), 0.9);
+        let canonical = molecules[1].canonicalize().render();
+        assert_eq!(molecules[0].canonicalize().render(), canonical);
+        assert_eq!(representatives(&clusters, &molecules), [canonical]);
+    }
+}
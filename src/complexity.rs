@@ -0,0 +1,114 @@
+//! Size estimates of SMILES strings taken before parsing them.
+//!
+//! [`estimate_complexity`] makes one pass over the bytes of the input with
+//! [`lex`], without building atoms or a graph, and counts what the full parse
+//! would have to deal with. Services can use the [`ComplexityEstimate`] to send
+//! very large molecules to a slower queue, or to reject them, before paying for
+//! the parse.
+//!
+//! # Examples
+//!
+//! ```
+//! use smiles_parser::complexity::{ComplexityEstimate, estimate_complexity};
+//!
+//! let estimate = estimate_complexity("CC(=O)Oc1ccccc1C(=O)O");
+//! assert_eq!(estimate, ComplexityEstimate { approx_atoms: 13, approx_rings: 1, branches: 2 });
+//!
+//! let too_large = |input: &str| estimate_complexity(input).approx_atoms > 500;
+//! assert!(!too_large("CCO"));
+//! assert!(too_large(&"C".repeat(1_000)));
+//! ```
+
+use crate::token::{TokenKind, lex};
+
+/// Counts taken by [`estimate_complexity`].
+///
+/// The counts are exact for valid input and approximate otherwise, since the
+/// scan does not check the syntax.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ComplexityEstimate {
+    /// Atoms written in the input, bracket atoms included. Implicit hydrogens
+    /// and hydrogen counts inside brackets are not counted.
+    pub approx_atoms: usize,
+    /// Ring bonds, that is half the ring-closure numbers written.
+    pub approx_rings: usize,
+    /// Opened branches.
+    pub branches: usize,
+}
+
+/// Estimates the size of the molecule written in `input` from a single scan
+/// of its bytes.
+///
+/// Input the parser would reject still gets an estimate, so the size check
+/// can run before any validation.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::complexity::estimate_complexity;
+///
+/// let cubane = estimate_complexity("C12C3C4C1C5C2C3C45");
+/// assert_eq!((cubane.approx_atoms, cubane.approx_rings), (8, 5));
+///
+/// let salt = estimate_complexity("[Na+].[Cl-]");
+/// assert_eq!(salt.approx_atoms, 2);
+/// ```
+#[must_use]
+pub fn estimate_complexity(input: &str) -> ComplexityEstimate {
+    let mut estimate = ComplexityEstimate::default();
+    let mut ring_closures = 0;
+    for (kind, _span) in lex(input) {
+        match kind {
+            TokenKind::Atom => estimate.approx_atoms += 1,
+            TokenKind::RingClosure => ring_closures += 1,
+            TokenKind::LeftParentheses => estimate.branches += 1,
+            _ => {}
+        }
+    }
+    estimate.approx_rings = ring_closures / 2;
+    estimate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smiles::Smiles;
+
+    #[test]
+    fn valid_input_estimates_match_the_parsed_graph() {
+        for input in [
+            "CCO",
+            "c1ccccc1Cl",
+            "[13CH3]C(=O)[O-].[Na+]",
+            "C%10CC%10C1CC1",
+            "N[C@@H](C)C(=O)O",
+            "C1=CC2=NC1=CC3=CC=C(N3)C=C4C=CC(=N4)C=C5C=CC(=C2)N5",
+        ] {
+            let smiles: Smiles = input.parse().unwrap();
+            let estimate = estimate_complexity(input);
+            assert_eq!(estimate.approx_atoms, smiles.nodes().len(), "{input}");
+            assert_eq!(
+                estimate.approx_rings,
+                smiles.number_of_bonds() + smiles.connected_components().number_of_components()
+                    - smiles.nodes().len(),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_input_still_gets_an_estimate() {
+        assert_eq!(estimate_complexity(""), ComplexityEstimate::default());
+        assert_eq!(
+            estimate_complexity("C(C(C1"),
+            ComplexityEstimate { approx_atoms: 3, approx_rings: 0, branches: 2 }
+        );
+        assert_eq!(estimate_complexity("C C [Xx").approx_atoms, 2);
+
+        let nested = format!("{}C{}", "C(".repeat(10_000), ")".repeat(10_000));
+        assert_eq!(
+            estimate_complexity(&nested),
+            ComplexityEstimate { approx_atoms: 10_001, approx_rings: 0, branches: 10_000 }
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! Conformance vectors taken from the OpenSMILES specification.
+//!
+//! [`CASES`] lists the example strings of the specification, grouped by the
+//! section they illustrate, together with the outcome the specification
+//! gives them: valid, or explicitly illegal. Cases in the `Grammar` section
+//! are not written examples but strings the formal grammar rules out. Where
+//! this crate departs from the specification under the default
+//! [`ParserOptions`], the case records the
+//! [deviation](ConformanceCase::deviation). [`run`] parses every case as a
+//! [`WildcardSmiles`] with caller-chosen options and reports the cases whose
+//! outcome differs, so integrators can check how a configuration departs from
+//! the specification.
+//!
+//! # Examples
+//!
+//! ```
+//! use smiles_parser::{ElementProfile, ParserOptions, conformance};
+//!
+//! let report = conformance::run(&ParserOptions::default());
+//! assert!(report.failures().iter().all(|failure| failure.case().deviation().is_some()));
+//!
+//! let options = ParserOptions { allow_empty: true, ..ParserOptions::default() };
+//! assert!(conformance::run(&options).is_conforming());
+//!
+//! let options = ParserOptions { profile: ElementProfile::CHNOPS, ..ParserOptions::default() };
+//! let report = conformance::run(&options);
+//! assert!(report.failures().iter().any(|failure| failure.case().smiles() == "[Na+].[Cl-]"));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{
+    errors::SmilesErrorWithSpan,
+    smiles::{ParserOptions, WildcardSmiles},
+};
+
+/// The outcome the specification gives a [`ConformanceCase`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Expectation {
+    /// The string is valid SMILES.
+    Valid,
+    /// The string is illegal and must fail to parse.
+    Invalid,
+}
+
+/// A specification example with its expected outcome.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ConformanceCase {
+    section: &'static str,
+    smiles: &'static str,
+    expectation: Expectation,
+    deviation: Option<&'static str>,
+}
+
+impl ConformanceCase {
+    const fn valid(section: &'static str, smiles: &'static str) -> Self {
+        Self { section, smiles, expectation: Expectation::Valid, deviation: None }
+    }
+
+    const fn invalid(section: &'static str, smiles: &'static str) -> Self {
+        Self { section, smiles, expectation: Expectation::Invalid, deviation: None }
+    }
+
+    const fn deviating(self, deviation: &'static str) -> Self {
+        Self { deviation: Some(deviation), ..self }
+    }
+
+    /// Returns the title of the specification section the case illustrates.
+    #[inline]
+    #[must_use]
+    pub const fn section(&self) -> &'static str {
+        self.section
+    }
+
+    /// Returns the SMILES string.
+    #[inline]
+    #[must_use]
+    pub const fn smiles(&self) -> &'static str {
+        self.smiles
+    }
+
+    /// Returns the outcome given by the specification.
+    #[inline]
+    #[must_use]
+    pub const fn expectation(&self) -> Expectation {
+        self.expectation
+    }
+
+    /// Returns how the default [`ParserOptions`] depart from the
+    /// specification on this case, or `None` when they follow it.
+    #[inline]
+    #[must_use]
+    pub const fn deviation(&self) -> Option<&'static str> {
+        self.deviation
+    }
+
+    /// Returns whether `outcome` matches the expectation of the case.
+    fn is_met_by<T>(&self, outcome: &Result<T, SmilesErrorWithSpan>) -> bool {
+        matches!(
+            (self.expectation, outcome),
+            (Expectation::Valid, Ok(_)) | (Expectation::Invalid, Err(_))
+        )
+    }
+}
+
+/// The specification examples, in the order of the specification.
+pub const CASES: &[ConformanceCase] = &[
+    ConformanceCase::valid("Atoms", "[U]"),
+    ConformanceCase::valid("Atoms", "[Pb]"),
+    ConformanceCase::valid("Atoms", "[He]"),
+    ConformanceCase::valid("Atoms", "[*]"),
+    ConformanceCase::valid("Atoms", "Oc1c(*)cccc1"),
+    ConformanceCase::valid("Atoms", "C"),
+    ConformanceCase::valid("Atoms", "N"),
+    ConformanceCase::valid("Atoms", "Cl"),
+    ConformanceCase::valid("Atoms", "[CH4]"),
+    ConformanceCase::valid("Atoms", "[ClH]"),
+    ConformanceCase::valid("Atoms", "[ClH1]"),
+    ConformanceCase::valid("Atoms", "[Cl-]"),
+    ConformanceCase::valid("Atoms", "[OH1-]"),
+    ConformanceCase::valid("Atoms", "[OH-1]"),
+    ConformanceCase::valid("Atoms", "[Cu+2]"),
+    ConformanceCase::valid("Atoms", "[Cu++]"),
+    ConformanceCase::valid("Atoms", "[13CH4]"),
+    ConformanceCase::valid("Atoms", "[2H+]"),
+    ConformanceCase::valid("Atoms", "[238U]"),
+    ConformanceCase::valid("Atoms", "[CH4:2]"),
+    ConformanceCase::valid("Bonds", "CC"),
+    ConformanceCase::valid("Bonds", "C-C"),
+    ConformanceCase::valid("Bonds", "C=O"),
+    ConformanceCase::valid("Bonds", "C#N"),
+    ConformanceCase::valid("Bonds", "[Ga+]$[As-]"),
+    ConformanceCase::valid("Bonds", "[Rh-](Cl)(Cl)(Cl)(Cl)$[Rh-](Cl)(Cl)(Cl)Cl"),
+    ConformanceCase::valid("Branches", "CCN(CC)CC"),
+    ConformanceCase::valid("Branches", "CC(C)C(=O)C(C)C"),
+    ConformanceCase::valid("Branches", "OCC(CCC)C(C(C)C)CCC"),
+    ConformanceCase::valid("Rings", "C1CCCCC1"),
+    ConformanceCase::valid("Rings", "C1CCCCC1C1CCCCC1"),
+    ConformanceCase::valid("Rings", "C12(CCCCC1)CCCCC2"),
+    ConformanceCase::valid("Rings", "C=1CCCCC1"),
+    ConformanceCase::valid("Rings", "C1CCCCC=1"),
+    ConformanceCase::valid("Rings", "C=1CCCCC=1"),
+    ConformanceCase::valid("Rings", "C0CCCCC0"),
+    ConformanceCase::valid("Rings", "C%10CCCCC%10"),
+    ConformanceCase::invalid("Rings", "C=1CCCCC#1"),
+    ConformanceCase::invalid("Rings", "C11"),
+    ConformanceCase::invalid("Rings", "C12CCCCC12"),
+    ConformanceCase::valid("Disconnected structures", "[Na+].[Cl-]"),
+    ConformanceCase::valid("Disconnected structures", "Oc1ccccc1.NCCO"),
+    ConformanceCase::valid("Disconnected structures", "C1.C1"),
+    ConformanceCase::valid("Hydrogens", "[H+]"),
+    ConformanceCase::valid("Hydrogens", "[H][H]"),
+    ConformanceCase::valid("Hydrogens", "[2H]C"),
+    ConformanceCase::valid("Aromaticity", "c1ccccc1"),
+    ConformanceCase::valid("Aromaticity", "C1=CC=CC=C1"),
+    ConformanceCase::valid("Aromaticity", "[nH]1cccc1"),
+    ConformanceCase::valid("Aromaticity", "c1ccccc1-c2ccccc2"),
+    ConformanceCase::valid("Stereochemistry", "F/C=C/F"),
+    ConformanceCase::valid("Stereochemistry", "F/C=C\\F"),
+    ConformanceCase::valid("Stereochemistry", "F/C=C/C=C/C"),
+    ConformanceCase::valid("Stereochemistry", "N[C@](Br)(O)C"),
+    ConformanceCase::valid("Stereochemistry", "N[C@@](Br)(C)O"),
+    ConformanceCase::valid("Empty strings", "")
+        .deviating("rejected with EmptyInput unless ParserOptions::allow_empty is set"),
+    ConformanceCase::invalid("Grammar", "[CH10]"),
+];
+
+/// A case whose outcome differed from its expectation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceFailure {
+    case: &'static ConformanceCase,
+    error: Option<SmilesErrorWithSpan>,
+}
+
+impl ConformanceFailure {
+    /// Returns the failing case.
+    #[inline]
+    #[must_use]
+    pub fn case(&self) -> &'static ConformanceCase {
+        self.case
+    }
+
+    /// Returns the parse error, or `None` when the string parsed although it
+    /// was expected to fail.
+    #[inline]
+    #[must_use]
+    pub fn error(&self) -> Option<&SmilesErrorWithSpan> {
+        self.error.as_ref()
+    }
+}
+
+/// The outcome of [`run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceReport {
+    passed: usize,
+    failures: Vec<ConformanceFailure>,
+}
+
+impl ConformanceReport {
+    /// Returns the number of cases whose outcome met their expectation.
+    #[inline]
+    #[must_use]
+    pub fn passed(&self) -> usize {
+        self.passed
+    }
+
+    /// Returns the cases whose outcome differed, in the order of [`CASES`].
+    #[inline]
+    #[must_use]
+    pub fn failures(&self) -> &[ConformanceFailure] {
+        &self.failures
+    }
+
+    /// Returns whether every case met its expectation.
+    #[inline]
+    #[must_use]
+    pub fn is_conforming(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Parses every case of [`CASES`] with `options` and compares the outcomes
+/// against their expectations.
+#[must_use]
+pub fn run(options: &ParserOptions) -> ConformanceReport {
+    let mut report = ConformanceReport { passed: 0, failures: Vec::new() };
+    for case in CASES {
+        let outcome = WildcardSmiles::parse_with_options(case.smiles, options);
+        if case.is_met_by(&outcome) {
+            report.passed += 1;
+        } else {
+            report.failures.push(ConformanceFailure { case, error: outcome.err() });
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smiles::ElementProfile;
+
+    #[test]
+    fn default_options_only_fail_documented_deviations() {
+        let report = run(&ParserOptions::default());
+        let failing =
+            report.failures().iter().map(|failure| failure.case().smiles()).collect::<Vec<_>>();
+        let deviating = CASES
+            .iter()
+            .filter(|case| case.deviation().is_some())
+            .map(ConformanceCase::smiles)
+            .collect::<Vec<_>>();
+        assert_eq!(failing, deviating);
+        assert_eq!(report.passed() + failing.len(), CASES.len());
+
+        let report = run(&ParserOptions { allow_empty: true, ..ParserOptions::default() });
+        assert!(report.is_conforming(), "{:?}", report.failures());
+    }
+
+    #[test]
+    fn deviating_options_are_reported() {
+        let options = ParserOptions {
+            allow_empty: true,
+            multi_digit_hydrogen_counts: true,
+            ..ParserOptions::default()
+        };
+        let report = run(&options);
+        assert_eq!(report.failures().len(), 1);
+        assert_eq!(report.failures()[0].case().smiles(), "[CH10]");
+        assert_eq!(report.failures()[0].error(), None);
+
+        let report =
+            run(&ParserOptions { profile: ElementProfile::CHNOPS, ..ParserOptions::default() });
+        let failing =
+            report.failures().iter().map(|failure| failure.case().smiles()).collect::<Vec<_>>();
+        assert!(failing.contains(&"[U]"));
+        assert!(failing.contains(&"[Na+].[Cl-]"));
+        assert!(!failing.contains(&"C11"));
+        let error = report.failures()[0].error().unwrap();
+        assert_eq!(error.smiles_error().category(), "ElementOutsideProfile");
+    }
+}
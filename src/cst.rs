@@ -0,0 +1,350 @@
+//! Lossless concrete syntax trees for SMILES strings.
+//!
+//! A [`ConcreteSyntaxTree`] is built alongside the parsed graph and keeps
+//! every character of the input: explicit bonds, `%` ring numbers, the field
+//! order of bracket atoms and any text around the SMILES, such as an
+//! annotation. Its nodes point at the atoms and bonds of the graph they
+//! spell, so a tool can go from a graph element back to the text that wrote
+//! it and rewrite that text in place.
+//!
+//! # Examples
+//!
+//! ```
+//! use smiles_parser::cst::{ConcreteSyntaxTree, CstNodeKind, GraphRef};
+//!
+//! let (smiles, tree) = ConcreteSyntaxTree::parse("C-1CC%10CC1%10")?;
+//! assert_eq!(tree.source(), "C-1CC%10CC1%10");
+//! assert_eq!(smiles.nodes().len(), 5);
+//!
+//! let bond = tree.nodes().iter().find(|node| tree.text(node) == "-").unwrap();
+//! assert_eq!(bond.graph_ref(), Some(GraphRef::Bond(0, 4)));
+//! let ring = tree.bond_nodes(2, 4).map(|node| tree.text(node)).collect::<Vec<_>>();
+//! assert_eq!(ring, ["%10", "%10"]);
+//! assert!(matches!(tree.atom_node(3).unwrap().kind(), CstNodeKind::Token(_)));
+//! # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use core::{fmt, ops::Range};
+
+use hashbrown::HashMap;
+
+use crate::{
+    errors::SmilesErrorWithSpan,
+    parser::smiles_parser::parse_smiles_with_hook,
+    smiles::{ParserOptions, Smiles, edge_key},
+    token::{Token, TokenWithSpan},
+};
+
+/// What a [`CstNode`] stands for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CstNodeKind {
+    /// The whole input
+    Root,
+    /// A branch, from its `(` through its `)`
+    Branch,
+    /// A single token
+    Token(Token),
+    /// Text that is not part of any token, such as an annotation after the
+    /// SMILES
+    Trivia,
+}
+
+/// The graph element a [`CstNode`] spells.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GraphRef {
+    /// The atom with this id
+    Atom(usize),
+    /// The bond between these atom ids, in ascending order. Both digits of a
+    /// ring closure and the bond symbols written with them refer to the ring
+    /// bond.
+    Bond(usize, usize),
+}
+
+/// A node of a [`ConcreteSyntaxTree`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CstNode {
+    kind: CstNodeKind,
+    span: Range<usize>,
+    children: Vec<usize>,
+    graph_ref: Option<GraphRef>,
+}
+
+impl CstNode {
+    /// Returns what the node stands for.
+    #[inline]
+    #[must_use]
+    pub fn kind(&self) -> CstNodeKind {
+        self.kind.clone()
+    }
+
+    /// Returns the byte span of the node in the input.
+    #[inline]
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the indices of the children of the node in
+    /// [`ConcreteSyntaxTree::nodes`], in input order.
+    #[inline]
+    #[must_use]
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    /// Returns the atom or bond of the graph the node spells, if any.
+    #[inline]
+    #[must_use]
+    pub fn graph_ref(&self) -> Option<GraphRef> {
+        self.graph_ref
+    }
+}
+
+/// A lossless syntax tree of a SMILES string, cross-referenced with the
+/// graph parsed from it.
+///
+/// The root covers the whole input and its children tile it: tokens,
+/// branches, whose children are in turn the tokens and branches between
+/// their parentheses, and trivia for any other text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcreteSyntaxTree {
+    source: String,
+    nodes: Vec<CstNode>,
+    atom_nodes: Vec<usize>,
+}
+
+impl ConcreteSyntaxTree {
+    /// Parses `input` into a graph and its syntax tree.
+    ///
+    /// # Errors
+    /// Returns the parse error if `input` is not valid SMILES.
+    #[inline]
+    pub fn parse(input: &str) -> Result<(Smiles, Self), SmilesErrorWithSpan> {
+        Self::parse_with_options(input, &ParserOptions::default())
+    }
+
+    /// Parses `input` into a graph and its syntax tree with the given
+    /// [`ParserOptions`].
+    ///
+    /// # Errors
+    /// Returns the parse error if `input` is not valid SMILES under
+    /// `options`.
+    pub fn parse_with_options(
+        input: &str,
+        options: &ParserOptions,
+    ) -> Result<(Smiles, Self), SmilesErrorWithSpan> {
+        let mut tokens = Vec::new();
+        let smiles = parse_smiles_with_hook(input, options, |token| tokens.push(token.clone()))?;
+        Ok((smiles, Self::from_tokens(input, &tokens)))
+    }
+
+    /// Returns the input the tree was built from.
+    #[inline]
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the root node, which covers the whole input.
+    #[inline]
+    #[must_use]
+    pub fn root(&self) -> &CstNode {
+        &self.nodes[0]
+    }
+
+    /// Returns every node, the root first and the rest in input order.
+    #[inline]
+    #[must_use]
+    pub fn nodes(&self) -> &[CstNode] {
+        &self.nodes
+    }
+
+    /// Returns the text spelled by `node`.
+    #[inline]
+    #[must_use]
+    pub fn text(&self, node: &CstNode) -> &str {
+        &self.source[node.span()]
+    }
+
+    /// Returns the token node of the atom with id `atom_id`, if present.
+    #[inline]
+    #[must_use]
+    pub fn atom_node(&self, atom_id: usize) -> Option<&CstNode> {
+        self.atom_nodes.get(atom_id).map(|&index| &self.nodes[index])
+    }
+
+    /// Returns the token nodes that spell the bond between `node_a` and
+    /// `node_b`: its bond symbols and, for a ring bond, both ring numbers.
+    /// Implicit bonds have none.
+    pub fn bond_nodes(&self, node_a: usize, node_b: usize) -> impl Iterator<Item = &CstNode> + '_ {
+        let (node_a, node_b) = edge_key(node_a, node_b);
+        self.nodes.iter().filter(move |node| node.graph_ref == Some(GraphRef::Bond(node_a, node_b)))
+    }
+
+    /// Builds the tree of `input` from the tokens the parser read from it.
+    fn from_tokens(input: &str, tokens: &[TokenWithSpan]) -> Self {
+        let mut builder = TreeBuilder {
+            nodes: vec![CstNode {
+                kind: CstNodeKind::Root,
+                span: 0..input.len(),
+                children: Vec::new(),
+                graph_ref: None,
+            }],
+            parents: vec![0],
+            atom_nodes: Vec::new(),
+            anchor: None,
+            anchors: Vec::new(),
+            pending_bond: None,
+            open_rings: HashMap::new(),
+        };
+        let mut cursor = 0;
+        for token in tokens {
+            if token.start() > cursor {
+                builder.push(CstNodeKind::Trivia, cursor..token.start());
+            }
+            builder.add_token(token);
+            cursor = token.end();
+        }
+        if cursor < input.len() {
+            builder.push(CstNodeKind::Trivia, cursor..input.len());
+        }
+        Self { source: input.into(), nodes: builder.nodes, atom_nodes: builder.atom_nodes }
+    }
+}
+
+impl fmt::Display for ConcreteSyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Working state of [`ConcreteSyntaxTree::from_tokens`], which follows the
+/// atoms and bonds the way the parser does.
+struct TreeBuilder {
+    nodes: Vec<CstNode>,
+    /// Open branch nodes, innermost last, above the root.
+    parents: Vec<usize>,
+    atom_nodes: Vec<usize>,
+    /// Atom the next atom or ring number bonds to.
+    anchor: Option<usize>,
+    /// Anchors to restore when the open branches close.
+    anchors: Vec<Option<usize>>,
+    /// Bond symbol node waiting for the atom or ring number it leads to.
+    pending_bond: Option<usize>,
+    /// Ring number node, its atom and its bond symbol node for every open
+    /// ring.
+    open_rings: HashMap<u8, (usize, usize, Option<usize>)>,
+}
+
+impl TreeBuilder {
+    /// Appends a node to the innermost open branch and returns its index.
+    fn push(&mut self, kind: CstNodeKind, span: Range<usize>) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(CstNode { kind, span, children: Vec::new(), graph_ref: None });
+        let parent =
+            *self.parents.last().unwrap_or_else(|| unreachable!("the root is never popped"));
+        self.nodes[parent].children.push(index);
+        index
+    }
+
+    fn add_token(&mut self, token: &TokenWithSpan) {
+        let kind = CstNodeKind::Token(token.token());
+        match token.token() {
+            Token::LeftParentheses => {
+                let branch = self.push(CstNodeKind::Branch, token.span());
+                self.parents.push(branch);
+                self.push(kind, token.span());
+                self.anchors.push(self.anchor);
+            }
+            Token::RightParentheses => {
+                self.push(kind, token.span());
+                let branch =
+                    self.parents.pop().unwrap_or_else(|| unreachable!("branches are balanced"));
+                self.nodes[branch].span.end = token.end();
+                self.anchor =
+                    self.anchors.pop().unwrap_or_else(|| unreachable!("branches are balanced"));
+            }
+            Token::Atom(_) => {
+                let index = self.push(kind, token.span());
+                let atom_id = self.atom_nodes.len();
+                self.atom_nodes.push(index);
+                self.nodes[index].graph_ref = Some(GraphRef::Atom(atom_id));
+                if let (Some(bond), Some(anchor)) = (self.pending_bond.take(), self.anchor) {
+                    let (node_a, node_b) = edge_key(anchor, atom_id);
+                    self.nodes[bond].graph_ref = Some(GraphRef::Bond(node_a, node_b));
+                }
+                self.anchor = Some(atom_id);
+            }
+            Token::Bond(_) => {
+                self.pending_bond = Some(self.push(kind, token.span()));
+            }
+            Token::RingClosure { ring_num, .. } => {
+                let index = self.push(kind, token.span());
+                let atom_id = self.anchor.unwrap_or_else(|| unreachable!("rings follow an atom"));
+                let bond = self.pending_bond.take();
+                let Some((opening, partner, opening_bond)) =
+                    self.open_rings.remove(&ring_num.get())
+                else {
+                    self.open_rings.insert(ring_num.get(), (index, atom_id, bond));
+                    return;
+                };
+                let (node_a, node_b) = edge_key(partner, atom_id);
+                for node in [Some(opening), Some(index), opening_bond, bond].into_iter().flatten() {
+                    self.nodes[node].graph_ref = Some(GraphRef::Bond(node_a, node_b));
+                }
+            }
+            Token::NonBond => {
+                self.push(kind, token.span());
+                self.anchor = None;
+            }
+            Token::UnknownBracket(..) => {
+                unreachable!("the parser never yields unknown bracket atoms")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_keeps_every_character_and_links_the_graph() {
+        let options = ParserOptions { annotations: true, ..ParserOptions::default() };
+        let input = "C-1CC(=O)C1.[NH4+] two parts";
+        let (smiles, tree) = ConcreteSyntaxTree::parse_with_options(input, &options).unwrap();
+
+        assert_eq!(tree.to_string(), input);
+        let root_text = tree.root().children().iter().map(|&index| tree.text(&tree.nodes()[index]));
+        assert_eq!(root_text.collect::<String>(), input);
+        let trivia = &tree.nodes()[*tree.root().children().last().unwrap()];
+        assert_eq!((trivia.kind(), tree.text(trivia)), (CstNodeKind::Trivia, " two parts"));
+
+        let branch = tree.nodes().iter().find(|node| node.kind() == CstNodeKind::Branch).unwrap();
+        assert_eq!(tree.text(branch), "(=O)");
+        assert_eq!(branch.children().len(), 4);
+
+        assert_eq!(tree.bond_nodes(2, 3).map(|node| tree.text(node)).collect::<Vec<_>>(), ["="]);
+        assert_eq!(
+            tree.bond_nodes(4, 0).map(|node| tree.text(node)).collect::<Vec<_>>(),
+            ["-", "1", "1"]
+        );
+        assert_eq!(tree.bond_nodes(0, 1).count(), 0);
+        for atom_id in 0..smiles.nodes().len() {
+            let node = tree.atom_node(atom_id).unwrap();
+            assert_eq!(node.graph_ref(), Some(GraphRef::Atom(atom_id)));
+            assert_eq!(node.kind(), CstNodeKind::Token(Token::Atom(smiles.nodes()[atom_id])));
+        }
+        assert_eq!(tree.text(tree.atom_node(5).unwrap()), "[NH4+]");
+        assert!(tree.atom_node(6).is_none());
+    }
+
+    #[test]
+    fn lenient_bracket_atoms_keep_their_field_order() {
+        let options = ParserOptions { lenient_bracket_order: true, ..ParserOptions::default() };
+        let (_, tree) = ConcreteSyntaxTree::parse_with_options("C[O-H]", &options).unwrap();
+        assert_eq!(tree.text(tree.atom_node(1).unwrap()), "[O-H]");
+        assert!(ConcreteSyntaxTree::parse("C(").is_err());
+    }
+}
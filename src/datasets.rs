@@ -35,7 +35,7 @@ use alloc::{borrow::ToOwned, boxed::Box, string::String, vec::Vec};
 use std::{
     env,
     fs::{self, File},
-    io::{self, BufRead, BufReader, BufWriter, IsTerminal, Read, Write},
+    io::{self, BufRead, BufWriter, IsTerminal, Read, Write},
     path::{Path, PathBuf},
     time::Duration,
 };
@@ -1158,14 +1158,8 @@ fn is_zinc20_smiles_file(path: &Path) -> bool {
 }
 
 fn open_text_reader(path: &Path) -> Result<Box<dyn BufRead + Send>, DatasetError> {
-    let file =
-        File::open(path).map_err(|source| DatasetError::Io { path: path.to_path_buf(), source })?;
-
-    if path.extension().is_some_and(|extension| extension == "gz") {
-        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
-    } else {
-        Ok(Box::new(BufReader::new(file)))
-    }
+    crate::io::open_text_reader(path)
+        .map_err(|source| DatasetError::Io { path: path.to_path_buf(), source })
 }
 
 fn fetch_dataset<D: DatasetSource + ?Sized>(
@@ -0,0 +1,480 @@
+//! Duplicate detection over large SMILES collections.
+//!
+//! [`Deduplicator`] canonicalizes every graph it is given and reduces the
+//! canonical SMILES to a 128-bit hash. It remembers the hash and the position
+//! of the first record that produced it, and the position of every later
+//! duplicate so that [`Deduplicator::clusters`] can report them. Memory
+//! therefore grows with the number of records, one hash per distinct
+//! structure and one position per duplicate, but not with the length of their
+//! strings, so millions of records can be streamed through it. When memory
+//! has to stay fixed whatever the corpus size, [`BoundedDeduplicator`] flags
+//! duplicates with a Bloom filter sized up front, at the cost of a small
+//! false-positive rate and of the clusters. [`DedupLevel`] selects which
+//! differences still count: stereochemistry and isotopes can each be ignored,
+//! and tautomers that only move a mobile hydrogen can be merged.
+//!
+//! # Examples
+//!
+//! ```
+//! use smiles_parser::{
+//!     Smiles,
+//!     dedup::{DedupLevel, Deduplicator},
+//! };
+//!
+//! let mut deduplicator = Deduplicator::new(DedupLevel::IgnoreStereo);
+//! for input in ["OCC", "C[C@H](N)O", "CCO", "C[C@@H](N)O", "CCN"] {
+//!     deduplicator.insert(&input.parse::<Smiles>()?);
+//! }
+//!
+//! assert_eq!(deduplicator.distinct(), 3);
+//! let clusters = deduplicator.clusters();
+//! assert_eq!(clusters[0].records(), [0, 2]);
+//! assert_eq!(clusters[1].records(), [1, 3]);
+//! # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use hashbrown::HashMap;
+
+use crate::smiles::{CanonicalAlgorithmVersion, Smiles, SmilesAtomPolicy, WriterOptions};
+
+/// Which differences between two graphs [`Deduplicator`] ignores.
+///
+/// Atom classes are ignored at every level, since they annotate atoms rather
+/// than change the structure.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum DedupLevel {
+    /// Graphs must match including stereochemistry and isotopes.
+    #[default]
+    Exact,
+    /// Stereoisomers are duplicates of each other.
+    IgnoreStereo,
+    /// Isotopologues are duplicates of each other.
+    IgnoreIsotopes,
+    /// Only the constitution is compared: both stereochemistry and isotopes
+    /// are ignored.
+    IgnoreStereoAndIsotopes,
+    /// As [`DedupLevel::IgnoreStereoAndIsotopes`], with mobile hydrogens
+    /// moved to a fixed position first, so tautomers of carboxylic acids,
+    /// amides, amidines and similar groups are duplicates of each other. See
+    /// [`Smiles::normalize_mobile_hydrogens`].
+    IgnoreTautomers,
+}
+
+impl DedupLevel {
+    /// Returns the canonical SMILES that identifies `smiles` at this level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{Smiles, dedup::DedupLevel};
+    ///
+    /// let labelled: Smiles = "[13CH3]O".parse()?;
+    /// let plain: Smiles = "OC".parse()?;
+    /// assert_ne!(DedupLevel::Exact.key(&labelled), DedupLevel::Exact.key(&plain));
+    /// assert_eq!(DedupLevel::IgnoreIsotopes.key(&labelled), DedupLevel::IgnoreIsotopes.key(&plain));
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn key<AtomPolicy: SmilesAtomPolicy>(self, smiles: &Smiles<AtomPolicy>) -> String {
+        let options = WriterOptions {
+            stereo: matches!(self, Self::Exact | Self::IgnoreIsotopes),
+            isotopes: matches!(self, Self::Exact | Self::IgnoreStereo),
+            atom_classes: false,
+            ..WriterOptions::default()
+        };
+        let smiles = smiles.with_writer_features_removed(&options);
+        if self == Self::IgnoreTautomers {
+            smiles.normalize_mobile_hydrogens().canonicalize().render()
+        } else {
+            smiles.canonicalize().render()
+        }
+    }
+
+    /// Returns [`DedupLevel::key`] prefixed with the major
+    /// [`CanonicalAlgorithmVersion`] that wrote it, as in `v1:CO`.
+    ///
+    /// Stored keys can be compared as strings: keys written by incompatible
+    /// algorithm versions never collide.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{Smiles, dedup::DedupLevel};
+    ///
+    /// let smiles: Smiles = "OC".parse()?;
+    /// assert_eq!(DedupLevel::Exact.versioned_key(&smiles), "v1:CO");
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn versioned_key<AtomPolicy: SmilesAtomPolicy>(
+        self,
+        smiles: &Smiles<AtomPolicy>,
+    ) -> String {
+        format!("v{}:{}", CanonicalAlgorithmVersion::CURRENT.major(), self.key(smiles))
+    }
+
+    /// Returns a 128-bit FNV-1a hash of [`DedupLevel::key`].
+    ///
+    /// The hash does not depend on the process, so it can be compared across
+    /// runs of the same major [`CanonicalAlgorithmVersion`]. It carries no
+    /// version of its own, since [`Deduplicator`] only compares hashes it
+    /// computed itself; hashes that are stored should come from
+    /// [`DedupLevel::versioned_structure_hash`] instead.
+    #[must_use]
+    pub fn structure_hash<AtomPolicy: SmilesAtomPolicy>(self, smiles: &Smiles<AtomPolicy>) -> u128 {
+        fnv1a_128(self.key(smiles).as_bytes())
+    }
+
+    /// Returns a 128-bit FNV-1a hash of [`DedupLevel::versioned_key`].
+    ///
+    /// Hashes written by incompatible algorithm versions differ even for the
+    /// same canonical string, so stored hashes never match across a major
+    /// version bump.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{Smiles, dedup::DedupLevel};
+    ///
+    /// let smiles: Smiles = "OC".parse()?;
+    /// assert_eq!(
+    ///     DedupLevel::Exact.versioned_structure_hash(&smiles),
+    ///     DedupLevel::Exact.versioned_structure_hash(&"CO".parse::<Smiles>()?),
+    /// );
+    /// assert_ne!(
+    ///     DedupLevel::Exact.versioned_structure_hash(&smiles),
+    ///     DedupLevel::Exact.structure_hash(&smiles),
+    /// );
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn versioned_structure_hash<AtomPolicy: SmilesAtomPolicy>(
+        self,
+        smiles: &Smiles<AtomPolicy>,
+    ) -> u128 {
+        fnv1a_128(self.versioned_key(smiles).as_bytes())
+    }
+}
+
+/// The records that [`Deduplicator`] found to share one structure.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DuplicateCluster {
+    records: Vec<usize>,
+}
+
+impl DuplicateCluster {
+    /// Returns the positions of the records in insertion order. The first one
+    /// is the record that was seen first.
+    #[inline]
+    #[must_use]
+    pub fn records(&self) -> &[usize] {
+        &self.records
+    }
+}
+
+/// Streams graphs and groups the ones that are duplicates at a chosen
+/// [`DedupLevel`].
+///
+/// Records are identified by their position in the insertion order, starting
+/// at zero. The deduplicator keeps one hash per distinct structure and one
+/// position per duplicate record, so its memory is not bounded: it grows
+/// linearly with the number of records inserted. See [`BoundedDeduplicator`]
+/// for a fixed memory budget.
+#[derive(Debug, Clone, Default)]
+pub struct Deduplicator {
+    level: DedupLevel,
+    first_records: HashMap<u128, usize>,
+    duplicates: HashMap<usize, Vec<usize>>,
+    records: usize,
+}
+
+impl Deduplicator {
+    /// Creates an empty deduplicator.
+    #[must_use]
+    pub fn new(level: DedupLevel) -> Self {
+        Self { level, ..Self::default() }
+    }
+
+    /// Returns the level the deduplicator compares graphs at.
+    #[inline]
+    #[must_use]
+    pub fn level(&self) -> DedupLevel {
+        self.level
+    }
+
+    /// Adds the next record and returns the position of the first earlier
+    /// record with the same structure, if any.
+    pub fn insert<AtomPolicy: SmilesAtomPolicy>(
+        &mut self,
+        smiles: &Smiles<AtomPolicy>,
+    ) -> Option<usize> {
+        self.insert_hash(self.level.structure_hash(smiles))
+    }
+
+    /// Adds the next record by a hash computed with
+    /// [`DedupLevel::structure_hash`], for callers that hash in parallel
+    /// and insert sequentially.
+    pub fn insert_hash(&mut self, hash: u128) -> Option<usize> {
+        let record = self.records;
+        self.records += 1;
+        let first = *self.first_records.entry(hash).or_insert(record);
+        if first == record {
+            return None;
+        }
+        self.duplicates.entry(first).or_default().push(record);
+        Some(first)
+    }
+
+    /// Returns the number of records inserted so far.
+    #[inline]
+    #[must_use]
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Returns the number of distinct structures inserted so far.
+    #[inline]
+    #[must_use]
+    pub fn distinct(&self) -> usize {
+        self.first_records.len()
+    }
+
+    /// Returns every group of two or more duplicate records, ordered by the
+    /// position of their first record.
+    #[must_use]
+    pub fn clusters(&self) -> Vec<DuplicateCluster> {
+        let mut clusters = self
+            .duplicates
+            .iter()
+            .map(|(&first, duplicates)| {
+                let mut records = Vec::with_capacity(duplicates.len() + 1);
+                records.push(first);
+                records.extend_from_slice(duplicates);
+                DuplicateCluster { records }
+            })
+            .collect::<Vec<_>>();
+        clusters.sort_unstable_by_key(|cluster| cluster.records[0]);
+        clusters
+    }
+}
+
+/// Streams graphs and flags duplicates at a chosen [`DedupLevel`] in memory
+/// fixed at construction.
+///
+/// The deduplicator is a Bloom filter over [`DedupLevel::structure_hash`]
+/// holding [`BITS_PER_RECORD`](Self::BITS_PER_RECORD) bits per expected
+/// distinct structure, each hash setting [`PROBES`](Self::PROBES) of them. A
+/// duplicate is always reported as one. While at most the expected number of
+/// distinct structures has been inserted, a new structure is wrongly reported
+/// as a duplicate with a probability of about 0.82%; past that the rate keeps
+/// growing, reaching about 14% at twice the expected number. No record
+/// positions are kept, so unlike [`Deduplicator`] it cannot report clusters.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::{
+///     Smiles,
+///     dedup::{BoundedDeduplicator, DedupLevel},
+/// };
+///
+/// let mut deduplicator = BoundedDeduplicator::new(DedupLevel::Exact, 1_000);
+/// let memory = deduplicator.memory_bytes();
+/// for input in ["OCC", "CCN", "CCO"] {
+///     deduplicator.insert(&input.parse::<Smiles>()?);
+/// }
+///
+/// assert_eq!(deduplicator.duplicates(), 1);
+/// assert_eq!(deduplicator.memory_bytes(), memory);
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundedDeduplicator {
+    level: DedupLevel,
+    words: Vec<u64>,
+    records: usize,
+    duplicates: usize,
+}
+
+impl BoundedDeduplicator {
+    /// Number of filter bits reserved per expected distinct structure.
+    pub const BITS_PER_RECORD: usize = 10;
+    /// Number of filter bits each structure hash sets and checks.
+    pub const PROBES: u64 = 7;
+
+    /// Creates an empty deduplicator sized for `expected_records` distinct
+    /// structures.
+    #[must_use]
+    pub fn new(level: DedupLevel, expected_records: usize) -> Self {
+        let bits = expected_records.max(1).saturating_mul(Self::BITS_PER_RECORD);
+        Self { level, words: vec![0; bits.div_ceil(64)], records: 0, duplicates: 0 }
+    }
+
+    /// Returns the level the deduplicator compares graphs at.
+    #[inline]
+    #[must_use]
+    pub fn level(&self) -> DedupLevel {
+        self.level
+    }
+
+    /// Adds the next record and returns whether a record with the same
+    /// structure was probably inserted before.
+    pub fn insert<AtomPolicy: SmilesAtomPolicy>(&mut self, smiles: &Smiles<AtomPolicy>) -> bool {
+        self.insert_hash(self.level.structure_hash(smiles))
+    }
+
+    /// Adds the next record by a hash computed with
+    /// [`DedupLevel::structure_hash`], for callers that hash in parallel
+    /// and insert sequentially.
+    pub fn insert_hash(&mut self, hash: u128) -> bool {
+        let bits = u64::try_from(self.words.len() * 64)
+            .unwrap_or_else(|_| unreachable!("filter sizes fit in u64"));
+        // Double hashing over the two halves of the structure hash, mixed so
+        // that the probes do not inherit the weak low bits of FNV-1a.
+        let first = mix64(
+            u64::try_from(hash & u128::from(u64::MAX))
+                .unwrap_or_else(|_| unreachable!("the low half of a u128 fits in u64")),
+        );
+        let step = mix64(
+            u64::try_from(hash >> 64)
+                .unwrap_or_else(|_| unreachable!("the high half of a u128 fits in u64")),
+        ) | 1;
+        let mut seen = true;
+        for probe in 0..Self::PROBES {
+            let bit = usize::try_from(first.wrapping_add(probe.wrapping_mul(step)) % bits)
+                .unwrap_or_else(|_| unreachable!("filter bits are indexable"));
+            let mask = 1 << (bit % 64);
+            seen &= self.words[bit / 64] & mask != 0;
+            self.words[bit / 64] |= mask;
+        }
+        self.records += 1;
+        self.duplicates += usize::from(seen);
+        seen
+    }
+
+    /// Returns the number of records inserted so far.
+    #[inline]
+    #[must_use]
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Returns the number of records reported as duplicates so far.
+    #[inline]
+    #[must_use]
+    pub fn duplicates(&self) -> usize {
+        self.duplicates
+    }
+
+    /// Returns the size of the filter in bytes, which never changes after
+    /// construction.
+    #[inline]
+    #[must_use]
+    pub fn memory_bytes(&self) -> usize {
+        self.words.len() * 8
+    }
+}
+
+/// The `SplitMix64` finalizer.
+fn mix64(mut word: u64) -> u64 {
+    word = (word ^ (word >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    word = (word ^ (word >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    word ^ (word >> 31)
+}
+
+fn fnv1a_128(bytes: &[u8]) -> u128 {
+    const OFFSET_BASIS: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+    const PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| (hash ^ u128::from(byte)).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smiles::WildcardSmiles;
+
+    fn clusters(level: DedupLevel, inputs: &[&str]) -> Vec<Vec<usize>> {
+        let mut deduplicator = Deduplicator::new(level);
+        for input in inputs {
+            deduplicator.insert(&input.parse::<Smiles>().unwrap());
+        }
+        deduplicator.clusters().into_iter().map(|cluster| cluster.records().to_vec()).collect()
+    }
+
+    const INPUTS: [&str; 5] = ["C[C@H](N)O", "C[C@@H](N)O", "[13CH3][C@H](N)O", "CC(N)O", "NC(C)O"];
+
+    #[test]
+    fn each_level_merges_its_own_differences() {
+        assert_eq!(clusters(DedupLevel::Exact, &INPUTS), [vec![3, 4]]);
+        assert_eq!(clusters(DedupLevel::IgnoreStereo, &INPUTS), [vec![0, 1, 3, 4]]);
+        assert_eq!(clusters(DedupLevel::IgnoreIsotopes, &INPUTS), [vec![0, 2], vec![3, 4]]);
+        assert_eq!(clusters(DedupLevel::IgnoreStereoAndIsotopes, &INPUTS), [vec![0, 1, 2, 3, 4]]);
+        assert_eq!(clusters(DedupLevel::IgnoreTautomers, &INPUTS), [vec![0, 1, 2, 3, 4]]);
+    }
+
+    #[test]
+    fn tautomers_are_merged_only_when_asked() {
+        let inputs = ["CNC(C)=NCC", "CN=C(C)NCC", "CC(O)=N", "NC(C)=O", "CC(=O)OC"];
+
+        assert!(clusters(DedupLevel::IgnoreStereoAndIsotopes, &inputs).is_empty());
+        assert_eq!(clusters(DedupLevel::IgnoreTautomers, &inputs), [vec![0, 1], vec![2, 3]]);
+    }
+
+    #[test]
+    fn atom_classes_are_ignored() {
+        assert_eq!(clusters(DedupLevel::Exact, &["[CH3:1]O", "CO"]), [vec![0, 1]]);
+    }
+
+    #[test]
+    fn insert_reports_the_first_record() {
+        let mut deduplicator = Deduplicator::default();
+        let ethanol: Smiles = "CCO".parse().unwrap();
+
+        assert_eq!(deduplicator.insert(&ethanol), None);
+        assert_eq!(deduplicator.insert(&"C".parse::<Smiles>().unwrap()), None);
+        assert_eq!(deduplicator.insert(&"OCC".parse::<Smiles>().unwrap()), Some(0));
+        assert_eq!(deduplicator.insert_hash(DedupLevel::Exact.structure_hash(&ethanol)), Some(0));
+        assert_eq!(deduplicator.records(), 4);
+        assert_eq!(deduplicator.distinct(), 2);
+    }
+
+    #[test]
+    fn wildcard_graphs_are_supported() {
+        let first: WildcardSmiles = "*CC".parse().unwrap();
+        let second: WildcardSmiles = "CC*".parse().unwrap();
+
+        assert_eq!(
+            DedupLevel::Exact.structure_hash(&first),
+            DedupLevel::Exact.structure_hash(&second)
+        );
+    }
+
+    #[test]
+    fn bounded_deduplicator_keeps_its_memory_and_false_positive_rate() {
+        let capacity = 10_000;
+        let mut deduplicator = BoundedDeduplicator::new(DedupLevel::Exact, capacity);
+        let memory = deduplicator.memory_bytes();
+        assert_eq!(memory, capacity * BoundedDeduplicator::BITS_PER_RECORD / 8);
+
+        let hash = |record: usize| fnv1a_128(format!("record {record}").as_bytes());
+        let false_positives =
+            (0..capacity).filter(|&record| deduplicator.insert_hash(hash(record))).count();
+        assert!(false_positives < capacity / 50, "{false_positives} false positives");
+        assert!((0..capacity).all(|record| deduplicator.insert_hash(hash(record))));
+
+        for record in capacity..10 * capacity {
+            deduplicator.insert_hash(hash(record));
+        }
+        assert_eq!(deduplicator.memory_bytes(), memory);
+        assert_eq!(deduplicator.records(), 11 * capacity);
+    }
+
+    #[test]
+    fn fnv_hash_matches_the_reference_vectors() {
+        assert_eq!(fnv1a_128(b""), 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d);
+        assert_eq!(fnv1a_128(b"a"), 0xd228_cb69_6f1a_8caf_7891_2b70_4e4a_8964);
+    }
+}
@@ -1,6 +1,6 @@
 //! Defines errors used in the SMILES parser.
 
-use alloc::{format, string::String};
+use alloc::{borrow::Cow, format, string::String};
 use core::{fmt, num::TryFromIntError, ops::Range};
 
 use elements_rs::Element;
@@ -8,7 +8,7 @@ use thiserror::Error;
 
 use crate::{
     atom::atom_symbol::AtomSymbol,
-    bond::{Bond, BondDescriptor},
+    bond::{Bond, BondDescriptor, ring_num::RingNum},
 };
 
 /// The errors that could occur during SMILES parsing.
@@ -23,21 +23,49 @@ pub enum SmilesError {
     /// A charge is below allowed minimum (-15)
     #[error("Charge underflow: {0}")]
     ChargeUnderflow(i8),
+    /// An atom class is larger than the maximum supported value (65535)
+    #[error("Atom class overflow: classes above {} are not supported", u16::MAX)]
+    ClassOverflow,
+    /// A bracket atom has a second charge after its first one, as in `[C++-]`
+    #[error("Bracket atom has more than one charge")]
+    ConflictingCharge,
+    /// A bond is followed by `.`, `)` or the end of the input instead of an
+    /// atom or ring closure
+    #[error("Bond: {0} is not followed by an atom")]
+    DanglingBond(BondDescriptor),
     /// A duplicate edge between two nodes has been found
     #[error("Node A: {0} has multiple edges with Node B: {1}")]
     DuplicateEdge(usize, usize),
+    /// A bracket atom has a second hydrogen count, as in `[CH2H3]`
+    #[error("Bracket atom has more than one hydrogen count")]
+    DuplicateHydrogenCount,
+    /// A bracket atom has a second isotope after its symbol, as in `[13C13]`
+    #[error("Bracket atom has more than one isotope")]
+    DuplicateIsotope,
+    /// An element is not allowed by the
+    /// [`ElementProfile`](crate::ElementProfile) the input is parsed with
+    #[error("Element outside of the profile: {0}")]
+    ElementOutsideProfile(Element),
     /// A non bare element found outside of brackets
     #[error("Element requires brackets")]
     ElementRequiresBrackets,
     /// Wrapper for `element_rs` errors
     #[error("Error Parsing Element: {0}")]
     ElementsRs(#[from] elements_rs::errors::Error),
+    /// The input is empty or contains only whitespace
+    #[error("Empty input")]
+    EmptyInput,
     /// A branch without any nodes has been parsed
     #[error("A branch without any nodes has been found")]
     EmptyBranch,
     /// A bond was not able to bind two atoms
     #[error("Bond: {0} missing atom index(es)")]
     IncompleteBond(BondDescriptor),
+    /// A quadruple bond ends on an element that cannot form one, reported
+    /// when [`ParserOptions::strict_quadruple_bonds`](crate::ParserOptions::strict_quadruple_bonds)
+    /// is set
+    #[error("Quadruple bond to {0}, which cannot form one")]
+    ImplausibleQuadrupleBond(Element),
     /// Element forbidden to be written as aromatic here
     #[error("Invalid aromatic element: {0}")]
     InvalidAromaticElement(Element),
@@ -57,13 +85,14 @@ pub enum SmilesError {
     /// Error indicating invalid Element name
     #[error("Invalid element name: {0}")]
     InvalidElementName(char),
-    /// A bracket atom's explicit hydrogen count exceeds the maximum supported
-    /// value (15).
+    /// A bracket atom's explicit hydrogen count exceeds the single digit of the
+    /// specification (9), or 15 with
+    /// [`ParserOptions::multi_digit_hydrogen_counts`](crate::ParserOptions::multi_digit_hydrogen_counts).
     ///
-    /// The cap mirrors the magnitude cap on bracket-atom charges and prevents
-    /// downstream `u8` valence math (`explicit_valence + hydrogen_count +
-    /// implicit_hydrogen_count`) from overflowing for adversarial inputs.
-    /// Real chemistry SMILES never need an explicit `H` count above this bound.
+    /// The multi-digit cap mirrors the magnitude cap on bracket-atom charges
+    /// and prevents downstream `u8` valence math (`explicit_valence +
+    /// hydrogen_count + implicit_hydrogen_count`) from overflowing for
+    /// adversarial inputs.
     #[error("Hydrogen count overflow: {0}")]
     HydrogenCountOverflow(u8),
     /// A hydrogen bracket atom has an unsupported explicit hydrogen count
@@ -87,6 +116,12 @@ pub enum SmilesError {
     /// An invalid ring number has been found
     #[error("Invalid ring number")]
     InvalidRingNumber,
+    /// The input starts with a bond, e.g. `=CC`
+    #[error("Bond: {0} appears before the first atom")]
+    LeadingBond(BondDescriptor),
+    /// The input starts with a ring closure, e.g. `1CC1`
+    #[error("Ring closure {0} appears before the first atom")]
+    LeadingRingClosure(RingNum),
     /// found `[..]` that did not contain an element
     #[error("Missing element inside brackets")]
     MissingBracketElement,
@@ -99,6 +134,10 @@ pub enum SmilesError {
     /// Non Bond in Bracket
     #[error("Non-bond '.' in bracket")]
     NonBondInBracket,
+    /// The two ends of a ring closure spell different bond orders, e.g.
+    /// `C=1CCCCC#1`
+    #[error("Ring closure bonds disagree: {0} and {1}")]
+    RingBondMismatch(Bond, Bond),
     /// Ring Number Overflow (greater than 99)
     #[error("Ring number overflow: {0}")]
     RingNumberOverflow(u8),
@@ -117,6 +156,16 @@ pub enum SmilesError {
     /// Error indicating that unexpected unicode input was encountered.
     #[error("Unexpected unicode character")]
     UnexpectedUnicodeCharacter,
+    /// A non-ASCII character that looks like a SMILES character, such as a
+    /// Greek capital omicron for `O`, a fullwidth digit or an en dash, as
+    /// commonly left by copying SMILES out of documents
+    #[error("Non-ASCII character {found:?} looks like {suggestion:?}")]
+    NonAsciiLookalike {
+        /// The character found in the input.
+        found: char,
+        /// The ASCII character it resembles.
+        suggestion: char,
+    },
     /// An unexpected `:` has been found
     #[error("Unexpected ':'")]
     UnexpectedColon,
@@ -141,9 +190,18 @@ pub enum SmilesError {
     /// A wildcard atom was parsed where only concrete atoms are allowed.
     #[error("Wildcard atom not allowed")]
     WildcardAtomNotAllowed,
+    /// An aromatic element outside of the organic subset, such as `se` or
+    /// `as`, was written without brackets
+    #[error("Aromatic {0} must be written in brackets")]
+    UnbracketedAromaticElement(Element),
     /// A closing `]` bracket was not found
     #[error("Unclosed '['")]
     UnclosedBracket,
+    /// Bracket text that is not a valid bracket atom, as produced by
+    /// [`tokenize_permissive`](crate::token::tokenize_permissive), was given
+    /// to the graph builder
+    #[error("Bracket text is not a valid atom")]
+    UnknownBracket,
     /// A branch has not been closed with a `)`
     #[error("Branch not closed")]
     UnclosedBranch,
@@ -152,6 +210,85 @@ pub enum SmilesError {
     UnclosedRing,
 }
 
+impl SmilesError {
+    /// Returns the name of the error variant, without its payload.
+    ///
+    /// Bulk reports use it to count and group failures by category.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::SmilesError;
+    ///
+    /// assert_eq!(SmilesError::UnexpectedCharacter('?').category(), "UnexpectedCharacter");
+    /// assert_eq!(SmilesError::UnclosedRing.category(), "UnclosedRing");
+    /// ```
+    #[must_use]
+    pub const fn category(&self) -> &'static str {
+        match self {
+            Self::BondInBracket(..) => "BondInBracket",
+            Self::ChargeOverflow(..) => "ChargeOverflow",
+            Self::ChargeUnderflow(..) => "ChargeUnderflow",
+            Self::ClassOverflow => "ClassOverflow",
+            Self::ConflictingCharge => "ConflictingCharge",
+            Self::DanglingBond(..) => "DanglingBond",
+            Self::DuplicateEdge(..) => "DuplicateEdge",
+            Self::DuplicateHydrogenCount => "DuplicateHydrogenCount",
+            Self::DuplicateIsotope => "DuplicateIsotope",
+            Self::ElementOutsideProfile(..) => "ElementOutsideProfile",
+            Self::ElementRequiresBrackets => "ElementRequiresBrackets",
+            Self::ElementsRs(..) => "ElementsRs",
+            Self::EmptyBranch => "EmptyBranch",
+            Self::EmptyInput => "EmptyInput",
+            Self::ImplausibleQuadrupleBond(..) => "ImplausibleQuadrupleBond",
+            Self::IncompleteBond(..) => "IncompleteBond",
+            Self::InvalidAromaticElement(..) => "InvalidAromaticElement",
+            Self::InvalidBond => "InvalidBond",
+            Self::InvalidBranch => "InvalidBranch",
+            Self::InvalidChirality => "InvalidChirality",
+            Self::InvalidClass => "InvalidClass",
+            Self::InvalidElementName(..) => "InvalidElementName",
+            Self::HydrogenCountOverflow(..) => "HydrogenCountOverflow",
+            Self::InvalidHydrogenWithExplicitHydrogensFound => {
+                "InvalidHydrogenWithExplicitHydrogensFound"
+            }
+            Self::InvalidIsotope => "InvalidIsotope",
+            Self::InvalidNonBondToken => "InvalidNonBondToken",
+            Self::InvalidNumber => "InvalidNumber",
+            Self::IntegerOverflow => "IntegerOverflow",
+            Self::InvalidUnbracketedAtom(..) => "InvalidUnbracketedAtom",
+            Self::InvalidRingNumber => "InvalidRingNumber",
+            Self::LeadingBond(..) => "LeadingBond",
+            Self::LeadingRingClosure(..) => "LeadingRingClosure",
+            Self::MissingBracketElement => "MissingBracketElement",
+            Self::MissingElement => "MissingElement",
+            Self::NodeIdInvalid(..) => "NodeIdInvalid",
+            Self::NonBondInBracket => "NonBondInBracket",
+            Self::RingBondMismatch(..) => "RingBondMismatch",
+            Self::RingNumberOverflow(..) => "RingNumberOverflow",
+            Self::SelfLoopEdge(..) => "SelfLoopEdge",
+            Self::UnexpectedBracketedState => "UnexpectedBracketedState",
+            Self::UnexpectedEndOfString => "UnexpectedEndOfString",
+            Self::UnexpectedCharacter(..) => "UnexpectedCharacter",
+            Self::UnexpectedUnicodeCharacter => "UnexpectedUnicodeCharacter",
+            Self::NonAsciiLookalike { .. } => "NonAsciiLookalike",
+            Self::UnexpectedColon => "UnexpectedColon",
+            Self::UnexpectedDash => "UnexpectedDash",
+            Self::UnexpectedPercent => "UnexpectedPercent",
+            Self::UnexpectedLeftBracket => "UnexpectedLeftBracket",
+            Self::UnexpectedLeftParentheses => "UnexpectedLeftParentheses",
+            Self::UnexpectedRightBracket => "UnexpectedRightBracket",
+            Self::UnexpectedRightParentheses => "UnexpectedRightParentheses",
+            Self::WildcardAtomNotAllowed => "WildcardAtomNotAllowed",
+            Self::UnbracketedAromaticElement(..) => "UnbracketedAromaticElement",
+            Self::UnclosedBracket => "UnclosedBracket",
+            Self::UnknownBracket => "UnknownBracket",
+            Self::UnclosedBranch => "UnclosedBranch",
+            Self::UnclosedRing => "UnclosedRing",
+        }
+    }
+}
+
 impl From<TryFromIntError> for SmilesError {
     fn from(_: TryFromIntError) -> Self {
         SmilesError::InvalidNumber
@@ -159,7 +296,7 @@ impl From<TryFromIntError> for SmilesError {
 }
 
 /// Wraps the `Smiles` error adding the location of where the error was found
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SmilesErrorWithSpan {
     /// The [`SmilesError`]
     smiles_error: SmilesError,
@@ -246,6 +383,10 @@ impl SmilesErrorWithSpan {
 
     /// Render the error pointing back to location in the original string
     ///
+    /// The underline is aligned by characters rather than bytes, so inputs
+    /// containing multibyte characters (e.g. dashes pasted from a PDF) are
+    /// marked at the right column.
+    ///
     /// # Examples
     ///
     /// ```
@@ -259,14 +400,208 @@ impl SmilesErrorWithSpan {
     /// ```
     #[must_use]
     pub fn render(&self, input: &str) -> String {
+        self.render_with_options(input, &RenderOptions::default())
+    }
+
+    /// Renders the error as [`SmilesErrorWithSpan::render`] does, showing at
+    /// most [`RenderOptions::max_width`] characters of the input around the
+    /// error.
+    ///
+    /// Elided text is replaced by `…`, and when anything was elided the last
+    /// line also gives the absolute byte offsets of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{RenderOptions, Smiles};
+    ///
+    /// let input = format!("{}(", "C".repeat(40));
+    /// let error = input.parse::<Smiles>().unwrap_err();
+    /// let options = RenderOptions { max_width: Some(8) };
+    ///
+    /// assert_eq!(
+    ///     error.render_with_options(&input, &options),
+    ///     "…CCCCCCC(\n        ^\nBranch not closed at 40..41"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn render_with_options(&self, input: &str, options: &RenderOptions) -> String {
         let start = self.start().min(input.len());
         let end = self.end().min(input.len()).max(start + 1).min(input.len());
+        // Columns round the span outwards to whole characters.
+        let start_column =
+            input.char_indices().take_while(|&(index, c)| index + c.len_utf8() <= start).count();
+        let end_column = input.char_indices().take_while(|&(index, _)| index < end).count();
+        let columns = input.chars().count();
+
+        let width = options.max_width.unwrap_or(columns).max(1);
+        if columns <= width {
+            let mut underline = String::new();
+            underline.push_str(&" ".repeat(start_column));
+            underline.push_str(&"^".repeat(end_column.saturating_sub(start_column)));
+            return format!("{input}\n{underline}\n{}", self.smiles_error);
+        }
 
+        let span_columns = end_column.saturating_sub(start_column);
+        let window_end = (start_column.saturating_sub(width.saturating_sub(span_columns) / 2)
+            + width)
+            .min(columns);
+        let window_start = window_end - width;
+
+        let mut line = String::new();
         let mut underline = String::new();
-        underline.push_str(&" ".repeat(start));
-        underline.push_str(&"^".repeat(end - start));
+        if window_start > 0 {
+            line.push('…');
+            underline.push(' ');
+        }
+        line.extend(input.chars().skip(window_start).take(width));
+        if window_end < columns {
+            line.push('…');
+        }
+        let caret_start = start_column.clamp(window_start, window_end);
+        let caret_end = end_column.clamp(caret_start, window_end);
+        underline.push_str(&" ".repeat(caret_start - window_start));
+        underline.push_str(&"^".repeat(caret_end - caret_start));
+
+        format!("{line}\n{underline}\n{self}")
+    }
+
+    /// Returns a rewrite of `input` that resolves this error, for the common
+    /// mistakes where the intended spelling is unambiguous.
+    ///
+    /// Fixes are offered for lowercase or uppercase halogens (`cl`, `CL`,
+    /// `br`), a missing closing `]`, and a positive charge written after an
+    /// unbracketed atom (`N+`, `Fe++`). The charge fix only adds the brackets:
+    /// no hydrogens are added, so the rewritten atom may still need them.
+    ///
+    /// `input` must be the string this error was produced from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::Smiles;
+    ///
+    /// let input = "Ccl";
+    /// let error = input.parse::<Smiles>().unwrap_err();
+    /// let fix = error.suggested_fix(input).unwrap();
+    /// assert_eq!(fix.apply(input), "CCl");
+    ///
+    /// let input = "[NH4+";
+    /// let error = input.parse::<Smiles>().unwrap_err();
+    /// assert_eq!(error.suggested_fix(input).unwrap().apply(input), "[NH4+]");
+    /// ```
+    #[must_use]
+    pub fn suggested_fix(&self, input: &str) -> Option<SuggestedFix> {
+        let start = self.start();
+        let end = self.end();
+        if start > end || end > input.len() {
+            return None;
+        }
+        match self.smiles_error {
+            SmilesError::InvalidElementName(second) => {
+                let first = *input.as_bytes().get(start.checked_sub(1)?)?;
+                let symbol = match (first.to_ascii_uppercase(), second.to_ascii_lowercase()) {
+                    (b'C', 'l') => "Cl",
+                    (b'B', 'r') => "Br",
+                    _ => return None,
+                };
+                Some(SuggestedFix::new(start - 1..start + 1, symbol))
+            }
+            SmilesError::InvalidAromaticElement(element @ (Element::Cl | Element::Br)) => {
+                let symbol = element.symbol();
+                let offset = input.get(start..end)?.find(&*symbol.to_ascii_lowercase())?;
+                Some(SuggestedFix::new(start + offset..start + offset + symbol.len(), symbol))
+            }
+            SmilesError::UnbracketedAromaticElement(_) => {
+                Some(SuggestedFix::new(start..end, format!("[{}]", input.get(start..end)?)))
+            }
+            SmilesError::UnclosedBracket => Some(SuggestedFix::new(end..end, "]")),
+            SmilesError::NonAsciiLookalike { suggestion, .. } => {
+                Some(SuggestedFix::new(start..end, suggestion))
+            }
+            SmilesError::UnexpectedCharacter('+') => unbracketed_charge_fix(input, start),
+            _ => None,
+        }
+    }
+}
 
-        format!("{input}\n{underline}\n{}", self.smiles_error)
+/// Wraps the atom written just before an unbracketed `+` and its charge into a
+/// bracket atom, e.g. `N+` into `[N+]` and `Fe++` into `[Fe+2]`.
+fn unbracketed_charge_fix(input: &str, start: usize) -> Option<SuggestedFix> {
+    let before = input.get(..start)?;
+    let symbol_len = before.bytes().rev().take(2).take_while(u8::is_ascii_alphabetic).count();
+    let symbol_len = match before.as_bytes()[start - symbol_len..] {
+        [first, second] if first.is_ascii_uppercase() && second.is_ascii_lowercase() => 2,
+        [.., last] if last.is_ascii_alphabetic() => 1,
+        _ => return None,
+    };
+    let symbol_start = start - symbol_len;
+    let symbol = &before[symbol_start..];
+
+    let after = &input.as_bytes()[start..];
+    let plus_run = after.iter().take_while(|&&byte| byte == b'+').count();
+    let (magnitude, consumed) = if plus_run == 1 {
+        let digits = after[1..].iter().take(2).take_while(|byte| byte.is_ascii_digit()).count();
+        let magnitude = input[start + 1..start + 1 + digits].parse::<usize>().unwrap_or(1);
+        (magnitude, 1 + digits)
+    } else {
+        (plus_run, plus_run)
+    };
+
+    let replacement =
+        if magnitude == 1 { format!("[{symbol}+]") } else { format!("[{symbol}+{magnitude}]") };
+    Some(SuggestedFix::new(symbol_start..start + consumed, replacement))
+}
+
+/// A machine-applicable rewrite of part of a SMILES string.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::Smiles;
+///
+/// let input = "CN+";
+/// let fix = input.parse::<Smiles>().unwrap_err().suggested_fix(input).unwrap();
+/// assert_eq!(fix.span(), 1..3);
+/// assert_eq!(fix.replacement(), "[N+]");
+/// assert_eq!(fix.apply(input), "C[N+]");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SuggestedFix {
+    span: Range<usize>,
+    replacement: String,
+}
+
+impl SuggestedFix {
+    pub(crate) fn new(span: Range<usize>, replacement: impl Into<String>) -> Self {
+        Self { span, replacement: replacement.into() }
+    }
+
+    /// Returns the byte span of the text to replace.
+    #[inline]
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the text to write in place of [`SuggestedFix::span`].
+    #[inline]
+    #[must_use]
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+
+    /// Returns `input` with this fix applied.
+    ///
+    /// # Panics
+    /// Panics if the span is out of bounds for `input`.
+    #[must_use]
+    pub fn apply(&self, input: &str) -> String {
+        let mut output = String::with_capacity(input.len() + self.replacement.len());
+        output.push_str(&input[..self.span.start]);
+        output.push_str(&self.replacement);
+        output.push_str(&input[self.span.end..]);
+        output
     }
 }
 
@@ -276,6 +611,111 @@ impl fmt::Display for SmilesErrorWithSpan {
     }
 }
 
+impl SmilesErrorWithSpan {
+    /// Attaches the input this error was produced from, so that it can be
+    /// rendered after the caller has dropped its own copy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{Smiles, SmilesErrorWithSource};
+    ///
+    /// let errors: Vec<SmilesErrorWithSource<'static>> = ["CCO", "C1CC"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .filter_map(|line| line.parse::<Smiles>().err().map(|error| error.with_source(line)))
+    ///     .collect();
+    ///
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].input(), "C1CC");
+    /// assert!(errors[0].render().starts_with("C1CC\n"));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_source<'a>(self, input: impl Into<Cow<'a, str>>) -> SmilesErrorWithSource<'a> {
+        SmilesErrorWithSource { error: self, input: input.into() }
+    }
+}
+
+/// Settings for [`SmilesErrorWithSpan::render_with_options`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RenderOptions {
+    /// Maximum number of input characters shown around the error; `None`
+    /// shows the whole input.
+    pub max_width: Option<usize>,
+}
+
+/// A [`SmilesErrorWithSpan`] together with the input it points into.
+///
+/// The input is borrowed when the caller still owns it and owned otherwise,
+/// which lets errors be collected over millions of lines and reported at the
+/// end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmilesErrorWithSource<'a> {
+    error: SmilesErrorWithSpan,
+    input: Cow<'a, str>,
+}
+
+impl SmilesErrorWithSource<'_> {
+    /// Returns the error without its input.
+    #[inline]
+    #[must_use]
+    pub fn error(&self) -> &SmilesErrorWithSpan {
+        &self.error
+    }
+
+    /// Returns the input the error was produced from.
+    #[inline]
+    #[must_use]
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Renders the error as [`SmilesErrorWithSpan::render`] does with the
+    /// retained input.
+    #[inline]
+    #[must_use]
+    pub fn render(&self) -> String {
+        self.error.render(&self.input)
+    }
+
+    /// Renders the error as [`SmilesErrorWithSpan::render_with_options`] does
+    /// with the retained input.
+    #[inline]
+    #[must_use]
+    pub fn render_with_options(&self, options: &RenderOptions) -> String {
+        self.error.render_with_options(&self.input, options)
+    }
+
+    /// Returns the fix of [`SmilesErrorWithSpan::suggested_fix`] for the
+    /// retained input.
+    #[inline]
+    #[must_use]
+    pub fn suggested_fix(&self) -> Option<SuggestedFix> {
+        self.error.suggested_fix(&self.input)
+    }
+
+    /// Copies a borrowed input so that the error no longer borrows from the
+    /// caller.
+    #[must_use]
+    pub fn into_owned(self) -> SmilesErrorWithSource<'static> {
+        SmilesErrorWithSource { error: self.error, input: Cow::Owned(self.input.into_owned()) }
+    }
+
+    /// Drops the input and returns the bare error.
+    #[inline]
+    #[must_use]
+    pub fn into_error(self) -> SmilesErrorWithSpan {
+        self.error
+    }
+}
+
+impl fmt::Display for SmilesErrorWithSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in `{}`", self.error, self.input)
+    }
+}
+
 /// Error returned when carving a [`Fragment`](crate::smiles::Fragment) out of a
 /// parent graph fails.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
@@ -289,6 +729,30 @@ pub enum SubgraphError {
     BondReferencesUnknownAtom(usize),
 }
 
+/// Error returned when an atom order passed to
+/// [`Smiles::renumber`](crate::smiles::Smiles::renumber) is not a permutation,
+/// or when an atom mapping passed to
+/// [`Smiles::diff_with_mapping`](crate::smiles::Smiles::diff_with_mapping) is
+/// not one to one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum RenumberError {
+    /// The order does not list exactly one entry per atom.
+    #[error("Atom order has {actual} entries, but the graph has {expected} atoms")]
+    LengthMismatch {
+        /// Number of atoms in the graph.
+        expected: usize,
+        /// Number of entries in the order.
+        actual: usize,
+    },
+    /// An entry is not a valid atom id of the graph.
+    #[error("Atom id {0} is out of range for the graph")]
+    AtomOutOfRange(usize),
+    /// An atom id appears more than once.
+    #[error("Atom id {0} appears more than once in the order")]
+    DuplicateAtom(usize),
+}
+
 /// Error returned when rendering a fragment anchored at a chosen parent atom.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
 #[non_exhaustive]
@@ -300,17 +764,49 @@ pub enum RootError {
 
 #[cfg(test)]
 mod tests {
-    use alloc::string::ToString;
+    use alloc::{
+        borrow::{Cow, ToOwned},
+        string::ToString,
+    };
     use std::num::TryFromIntError;
 
     use elements_rs::Element;
 
     use crate::{
         atom::atom_symbol::AtomSymbol,
-        bond::{Bond, BondDescriptor},
-        errors::{SmilesError, SmilesErrorWithSpan},
+        bond::{Bond, BondDescriptor, ring_num::RingNum},
+        errors::{RenderOptions, SmilesError, SmilesErrorWithSpan},
+        smiles::Smiles,
     };
 
+    fn fixed(input: &str) -> Option<String> {
+        let error = input.parse::<Smiles>().unwrap_err();
+        error.suggested_fix(input).map(|fix| fix.apply(input))
+    }
+
+    #[test]
+    fn errors_keep_their_source_borrowed_or_owned() {
+        let input = "CC(C";
+        let error = input.parse::<Smiles>().unwrap_err();
+        let borrowed = error.clone().with_source(input);
+
+        assert!(matches!(borrowed.input, Cow::Borrowed(_)));
+        assert_eq!(borrowed.render(), error.render(input));
+        assert_eq!(borrowed.to_string(), format!("{error} in `CC(C`"));
+
+        let owned = borrowed.into_owned();
+        assert!(matches!(owned.input, Cow::Owned(_)));
+        assert_eq!(owned.input(), input);
+        assert_eq!(owned.into_error(), error);
+    }
+
+    #[test]
+    fn sourced_errors_offer_the_same_fix() {
+        let error = "Ccl".parse::<Smiles>().unwrap_err().with_source("Ccl".to_owned());
+
+        assert_eq!(error.suggested_fix().unwrap().apply(error.input()), "CCl");
+    }
+
     #[test]
     fn test_smiles_error_display_monolithic() {
         let elements_rs_error = elements_rs::errors::Error::AtomicNumber(4);
@@ -322,7 +818,21 @@ mod tests {
             ),
             (SmilesError::ChargeOverflow(50), "Charge overflow: 50".to_string()),
             (SmilesError::ChargeUnderflow(-50), "Charge underflow: -50".to_string()),
+            (
+                SmilesError::ClassOverflow,
+                "Atom class overflow: classes above 65535 are not supported".to_string(),
+            ),
+            (SmilesError::ConflictingCharge, "Bracket atom has more than one charge".to_string()),
+            (
+                SmilesError::DanglingBond(Bond::Double.into()),
+                "Bond: = is not followed by an atom".to_string(),
+            ),
+            (
+                SmilesError::ElementOutsideProfile(Element::Na),
+                format!("Element outside of the profile: {}", Element::Na),
+            ),
             (SmilesError::ElementRequiresBrackets, "Element requires brackets".to_string()),
+            (SmilesError::EmptyInput, "Empty input".to_string()),
             (
                 SmilesError::ElementsRs(elements_rs_error),
                 format!("Error Parsing Element: {elements_rs_error}"),
@@ -332,6 +842,10 @@ mod tests {
                 "Bond: : missing atom index(es)".to_string(),
             ),
             (SmilesError::HydrogenCountOverflow(16), "Hydrogen count overflow: 16".to_string()),
+            (
+                SmilesError::ImplausibleQuadrupleBond(Element::C),
+                format!("Quadruple bond to {}, which cannot form one", Element::C),
+            ),
             (
                 SmilesError::InvalidAromaticElement(Element::Ac),
                 format!("Invalid aromatic element: {}", Element::Ac),
@@ -348,10 +862,26 @@ mod tests {
                 format!("Invalid unbracketed atom: {}", AtomSymbol::WildCard),
             ),
             (SmilesError::InvalidRingNumber, "Invalid ring number".to_string()),
+            (
+                SmilesError::UnbracketedAromaticElement(Element::Se),
+                format!("Aromatic {} must be written in brackets", Element::Se),
+            ),
+            (
+                SmilesError::LeadingBond(Bond::Double.into()),
+                "Bond: = appears before the first atom".to_string(),
+            ),
+            (
+                SmilesError::LeadingRingClosure(RingNum::try_new(12).unwrap()),
+                "Ring closure %12 appears before the first atom".to_string(),
+            ),
             (SmilesError::MissingBracketElement, "Missing element inside brackets".to_string()),
             (SmilesError::MissingElement, "Missing element".to_string()),
             (SmilesError::NodeIdInvalid(2), "Invalid atom index: 2".to_string()),
             (SmilesError::NonBondInBracket, "Non-bond '.' in bracket".to_string()),
+            (
+                SmilesError::RingBondMismatch(Bond::Double, Bond::Triple),
+                "Ring closure bonds disagree: = and #".to_string(),
+            ),
             (SmilesError::RingNumberOverflow(100), "Ring number overflow: 100".to_string()),
             (SmilesError::UnexpectedBracketedState, "Unexpected bracketed state".to_string()),
             (SmilesError::UnexpectedEndOfString, "Unexpected end of string".to_string()),
@@ -366,6 +896,7 @@ mod tests {
             (SmilesError::UnexpectedRightParentheses, "Unexpected `)`".to_string()),
             (SmilesError::WildcardAtomNotAllowed, "Wildcard atom not allowed".to_string()),
             (SmilesError::UnclosedBracket, "Unclosed '['".to_string()),
+            (SmilesError::UnknownBracket, "Bracket text is not a valid atom".to_string()),
             (SmilesError::UnclosedBranch, "Branch not closed".to_string()),
             (SmilesError::UnclosedRing, "Ring not closed".to_string()),
             (
@@ -376,6 +907,11 @@ mod tests {
                 SmilesError::DuplicateEdge(0, 1),
                 "Node A: 0 has multiple edges with Node B: 1".to_string(),
             ),
+            (
+                SmilesError::DuplicateHydrogenCount,
+                "Bracket atom has more than one hydrogen count".to_string(),
+            ),
+            (SmilesError::DuplicateIsotope, "Bracket atom has more than one isotope".to_string()),
         ];
 
         for (error, expected) in cases {
@@ -437,4 +973,94 @@ mod tests {
 
         assert_eq!(error.to_string(), "Unexpected unicode character at 2..4");
     }
+
+    #[test]
+    fn windowed_render_elides_long_inputs_around_the_error() {
+        let input = "CCCCCCCCCCCC(CCCCCCCCCCCCCCC";
+        let error = SmilesErrorWithSpan::new(SmilesError::UnclosedBranch, 12, 13);
+        let options = |max_width| RenderOptions { max_width: Some(max_width) };
+
+        assert_eq!(
+            error.render_with_options(input, &options(5)),
+            "…CC(CC…\n   ^\nBranch not closed at 12..13"
+        );
+        assert_eq!(
+            error.render_with_options(input, &options(40)),
+            error.render(input),
+            "inputs that fit are rendered unchanged"
+        );
+
+        let at_start = SmilesErrorWithSpan::new(SmilesError::UnclosedBranch, 0, 1);
+        assert_eq!(
+            at_start.render_with_options(input, &options(4)),
+            "CCCC…\n^\nBranch not closed at 0..1"
+        );
+
+        let at_end = SmilesErrorWithSpan::new(SmilesError::UnexpectedEndOfString, 28, 28);
+        assert_eq!(
+            at_end.render_with_options(input, &options(3)),
+            "…CCC\n \nUnexpected end of string at 28..28"
+        );
+
+        let wide = SmilesErrorWithSpan::new(SmilesError::UnexpectedCharacter('x'), 2, 20);
+        assert_eq!(
+            wide.render_with_options(input, &options(4)),
+            "…CCCC…\n ^^^^\nUnexpected character: x at 2..20"
+        );
+    }
+
+    #[test]
+    fn render_aligns_carets_by_character_after_multibyte_input() {
+        let input = "CC\u{2192}C";
+        let error = crate::smiles::Smiles::from_str(input).unwrap_err();
+        assert_eq!(error.smiles_error(), SmilesError::UnexpectedUnicodeCharacter);
+        assert_eq!(error.span(), 2..5);
+        assert_eq!(error.render(input), "CC\u{2192}C\n  ^\nUnexpected unicode character");
+
+        let input = "C\u{2013}C(C";
+        let after = SmilesErrorWithSpan::new(SmilesError::UnclosedBranch, 5, 6);
+        assert_eq!(after.render(input), "C\u{2013}C(C\n   ^\nBranch not closed");
+
+        let inside = SmilesErrorWithSpan::new(SmilesError::UnexpectedUnicodeCharacter, 2, 3);
+        assert_eq!(inside.render(input), "C\u{2013}C(C\n ^\nUnexpected unicode character");
+    }
+
+    #[test]
+    fn suggested_fix_recapitalizes_halogens() {
+        assert_eq!(fixed("Ccl").as_deref(), Some("CCl"));
+        assert_eq!(fixed("CCL").as_deref(), Some("CCl"));
+        assert_eq!(fixed("brC").as_deref(), Some("BrC"));
+        assert_eq!(fixed("C[cl-]").as_deref(), Some("C[Cl-]"));
+    }
+
+    #[test]
+    fn suggested_fix_brackets_aromatic_selenium_and_arsenic() {
+        assert_eq!(fixed("c1ccsec1").as_deref(), Some("c1cc[se]c1"));
+        assert_eq!(fixed("c1ccasc1").as_deref(), Some("c1cc[as]c1"));
+    }
+
+    #[test]
+    fn suggested_fix_closes_unclosed_bracket() {
+        assert_eq!(fixed("C[NH4+").as_deref(), Some("C[NH4+]"));
+        assert_eq!(fixed("[13CH4").as_deref(), Some("[13CH4]"));
+    }
+
+    #[test]
+    fn suggested_fix_brackets_unbracketed_charges() {
+        assert_eq!(fixed("CN+").as_deref(), Some("C[N+]"));
+        assert_eq!(fixed("CN++C").as_deref(), Some("C[N+2]C"));
+        assert_eq!(fixed("C+2").as_deref(), Some("[C+2]"));
+        assert_eq!(fixed("CCl+").as_deref(), Some("C[Cl+]"));
+        assert_eq!(fixed("(+)"), None);
+    }
+
+    #[test]
+    fn suggested_fix_is_absent_for_other_errors() {
+        assert_eq!(fixed("C("), None);
+        assert_eq!(fixed("Cx"), None);
+        assert_eq!(
+            SmilesErrorWithSpan::new(SmilesError::UnclosedBracket, 4, 9).suggested_fix("C"),
+            None
+        );
+    }
 }
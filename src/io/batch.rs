@@ -0,0 +1,124 @@
+//! One-call parsing of many SMILES with per-record outcomes.
+
+use alloc::vec::Vec;
+use core::time::Duration;
+use std::time::Instant;
+
+use crate::{errors::SmilesErrorWithSpan, smiles::Smiles};
+
+/// The result of parsing one input of [`parse_all`].
+///
+/// Only the counts of the parsed graph are kept, so that summaries over large
+/// datasets do not hold every graph in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    index: usize,
+    elapsed: Duration,
+    result: Result<(usize, usize), SmilesErrorWithSpan>,
+}
+
+impl Outcome {
+    /// Returns the position of the input, starting at zero.
+    #[inline]
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the time spent parsing the input.
+    #[inline]
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns whether the input parsed.
+    #[inline]
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    /// Returns the parse error, if the input failed to parse.
+    #[inline]
+    #[must_use]
+    pub fn error(&self) -> Option<&SmilesErrorWithSpan> {
+        self.result.as_ref().err()
+    }
+
+    /// Returns the number of atoms of the parsed graph.
+    #[inline]
+    #[must_use]
+    pub fn atoms(&self) -> Option<usize> {
+        self.result.as_ref().ok().map(|&(atoms, _)| atoms)
+    }
+
+    /// Returns the number of bonds of the parsed graph.
+    #[inline]
+    #[must_use]
+    pub fn bonds(&self) -> Option<usize> {
+        self.result.as_ref().ok().map(|&(_, bonds)| bonds)
+    }
+}
+
+/// Parses every input as a [`Smiles`] and returns one [`Outcome`] per input,
+/// in input order.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::io::parse_all;
+///
+/// let outcomes = parse_all(["CCO", "C1CC", "c1ccccc1"]);
+///
+/// assert_eq!(outcomes.iter().filter(|outcome| outcome.is_ok()).count(), 2);
+/// assert_eq!(outcomes[1].index(), 1);
+/// assert_eq!(outcomes[1].error().unwrap().smiles_error().category(), "UnclosedRing");
+/// assert_eq!(outcomes[2].atoms(), Some(6));
+/// assert_eq!(outcomes[2].bonds(), Some(6));
+/// ```
+pub fn parse_all<'a>(inputs: impl IntoIterator<Item = &'a str>) -> Vec<Outcome> {
+    inputs
+        .into_iter()
+        .enumerate()
+        .map(|(index, input)| {
+            let started = Instant::now();
+            let result = input
+                .parse::<Smiles>()
+                .map(|smiles| (smiles.nodes().len(), smiles.number_of_bonds()));
+            Outcome { index, elapsed: started.elapsed(), result }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+    use crate::errors::SmilesError;
+
+    #[test]
+    fn outcomes_follow_input_order() {
+        let inputs = [String::from("[Na+].[Cl-]"), String::from("*C"), String::new()];
+        let outcomes = parse_all(inputs.iter().map(String::as_str));
+
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(outcomes.iter().map(Outcome::index).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!((outcomes[0].atoms(), outcomes[0].bonds()), (Some(2), Some(0)));
+        assert_eq!(
+            outcomes[1].error().map(SmilesErrorWithSpan::smiles_error),
+            Some(SmilesError::WildcardAtomNotAllowed)
+        );
+        assert_eq!(outcomes[1].atoms(), None);
+        assert_eq!(
+            outcomes[2].error().map(SmilesErrorWithSpan::smiles_error),
+            Some(SmilesError::EmptyInput)
+        );
+    }
+
+    #[test]
+    fn empty_input_yields_no_outcomes() {
+        assert!(parse_all([]).is_empty());
+    }
+}
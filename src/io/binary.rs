@@ -0,0 +1,332 @@
+//! The `.smb` binary container for collections of compact graphs.
+
+use alloc::vec::Vec;
+use std::io::{self, Write};
+
+use thiserror::Error;
+
+use crate::smiles::{CompactSmiles, CompactSmilesRef};
+
+/// Leading bytes of every `.smb` file.
+const MAGIC: [u8; 8] = *b"SMILESB\0";
+/// Format version written by [`SmbWriter`] and read by [`SmbReader`].
+pub const SMB_VERSION: u32 = 1;
+/// Length of the magic bytes and the version.
+const HEADER_LEN: usize = MAGIC.len() + 4;
+/// Length of the record count and the index offset closing the file.
+const FOOTER_LEN: usize = 16;
+
+/// Errors raised while opening or reading a `.smb` container.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SmbError {
+    /// The input does not start with the `.smb` magic bytes.
+    #[error("not a .smb container")]
+    BadMagic,
+    /// The container was written by an unknown format version.
+    #[error("unsupported .smb version {0}, expected {SMB_VERSION}")]
+    UnsupportedVersion(u32),
+    /// The header, index or footer is cut short or inconsistent.
+    #[error("truncated or inconsistent .smb index")]
+    Truncated,
+    /// The requested record is past the end of the collection.
+    #[error("record {index} is out of range for {len} records")]
+    IndexOutOfRange {
+        /// The requested record.
+        index: usize,
+        /// The number of records.
+        len: usize,
+    },
+    /// A record does not decode to a valid graph.
+    #[error("record {index} is corrupt")]
+    CorruptRecord {
+        /// The zero-based record index.
+        index: usize,
+    },
+}
+
+/// Writes [`CompactSmiles`] graphs into a `.smb` container.
+///
+/// A container starts with eight magic bytes and the `u32` format version,
+/// followed by the records written by [`CompactSmiles::encode`], an index
+/// holding the `u64` byte offset of every record, and a footer with the
+/// record count and the offset of the index. All integers are little-endian.
+/// Since the index comes last, records are streamed to the writer as they are
+/// pushed and only their offsets are kept in memory.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::{
+///     Smiles,
+///     io::{SmbReader, SmbWriter},
+/// };
+///
+/// let mut writer = SmbWriter::new(Vec::new())?;
+/// for smiles in ["CCO", "c1ccccc1", "C[C@H](N)C(=O)O"] {
+///     writer.push(&smiles.parse::<Smiles>()?.freeze())?;
+/// }
+/// let bytes = writer.finish()?;
+///
+/// let reader = SmbReader::new(&bytes)?;
+/// assert_eq!(reader.len(), 3);
+/// assert_eq!(reader.get(1)?.thaw(), "c1ccccc1".parse::<Smiles>()?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct SmbWriter<W> {
+    writer: W,
+    offsets: Vec<u64>,
+    position: u64,
+    record: Vec<u8>,
+}
+
+impl<W: Write> SmbWriter<W> {
+    /// Creates a writer and writes the container header.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised by the underlying writer.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&SMB_VERSION.to_le_bytes())?;
+        Ok(Self { writer, offsets: Vec::new(), position: HEADER_LEN as u64, record: Vec::new() })
+    }
+
+    /// Appends one graph to the container.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised by the underlying writer.
+    pub fn push(&mut self, smiles: &CompactSmiles) -> io::Result<()> {
+        self.record.clear();
+        smiles.encode(&mut self.record);
+        self.writer.write_all(&self.record)?;
+        self.offsets.push(self.position);
+        self.position += self.record.len() as u64;
+        Ok(())
+    }
+
+    /// Returns the number of graphs pushed so far.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Returns whether no graph was pushed yet.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Writes the index and the footer, flushes, and returns the underlying
+    /// writer.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised by the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        for offset in &self.offsets {
+            self.writer.write_all(&offset.to_le_bytes())?;
+        }
+        self.writer.write_all(&(self.offsets.len() as u64).to_le_bytes())?;
+        self.writer.write_all(&self.position.to_le_bytes())?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Random access to the graphs of a `.smb` container held in memory.
+///
+/// The reader borrows the container bytes and only validates the header and
+/// the footer when opened, so opening a memory-mapped file of millions of
+/// graphs is immediate. [`SmbReader::get_ref`] borrows one record as a
+/// [`CompactSmilesRef`] that reads atoms and bonds straight from the
+/// container bytes, while [`SmbReader::get`] copies the record into an owned
+/// [`CompactSmiles`] with an adjacency table for repeated neighbor queries.
+/// Both check the record and report [`SmbError::CorruptRecord`] instead of
+/// panicking on damaged input.
+///
+/// See [`SmbWriter`] for the layout and an example.
+#[derive(Debug, Clone, Copy)]
+pub struct SmbReader<'a> {
+    bytes: &'a [u8],
+    index: &'a [u8],
+    index_offset: usize,
+}
+
+impl<'a> SmbReader<'a> {
+    /// Opens the container stored in `bytes`.
+    ///
+    /// # Errors
+    /// Returns [`SmbError::BadMagic`] or [`SmbError::UnsupportedVersion`] for
+    /// foreign input and [`SmbError::Truncated`] when the index does not fit
+    /// the input.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, SmbError> {
+        if !bytes.starts_with(&MAGIC) {
+            return Err(SmbError::BadMagic);
+        }
+        let version = read_u32(bytes, MAGIC.len()).ok_or(SmbError::Truncated)?;
+        if version != SMB_VERSION {
+            return Err(SmbError::UnsupportedVersion(version));
+        }
+        let footer = bytes.len().checked_sub(FOOTER_LEN).ok_or(SmbError::Truncated)?;
+        let len = read_u64(bytes, footer).ok_or(SmbError::Truncated)?;
+        let index_offset = read_u64(bytes, footer + 8).ok_or(SmbError::Truncated)?;
+        if index_offset < HEADER_LEN || len.checked_mul(8) != footer.checked_sub(index_offset) {
+            return Err(SmbError::Truncated);
+        }
+        Ok(Self { bytes, index: &bytes[index_offset..footer], index_offset })
+    }
+
+    /// Returns the number of graphs in the container.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.index.len() / 8
+    }
+
+    /// Returns whether the container holds no graph.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Decodes the graph at position `index` into an owned graph.
+    ///
+    /// # Errors
+    /// Returns [`SmbError::IndexOutOfRange`] past the last graph and
+    /// [`SmbError::CorruptRecord`] when the record does not decode.
+    pub fn get(&self, index: usize) -> Result<CompactSmiles, SmbError> {
+        let mut record = self.record(index)?;
+        match CompactSmiles::decode(&mut record) {
+            Some(smiles) if record.is_empty() => Ok(smiles),
+            _ => Err(SmbError::CorruptRecord { index }),
+        }
+    }
+
+    /// Borrows the graph at position `index` without copying its record.
+    ///
+    /// # Errors
+    /// Returns [`SmbError::IndexOutOfRange`] past the last graph and
+    /// [`SmbError::CorruptRecord`] when the record does not decode.
+    pub fn get_ref(&self, index: usize) -> Result<CompactSmilesRef<'a>, SmbError> {
+        let mut record = self.record(index)?;
+        match CompactSmilesRef::decode(&mut record) {
+            Some(smiles) if record.is_empty() => Ok(smiles),
+            _ => Err(SmbError::CorruptRecord { index }),
+        }
+    }
+
+    /// Iterates the graphs in order.
+    pub fn iter(&self) -> impl Iterator<Item = Result<CompactSmiles, SmbError>> + '_ {
+        (0..self.len()).map(|index| self.get(index))
+    }
+
+    /// Iterates borrowed views of the graphs in order.
+    pub fn iter_ref(&self) -> impl Iterator<Item = Result<CompactSmilesRef<'a>, SmbError>> + '_ {
+        (0..self.len()).map(|index| self.get_ref(index))
+    }
+
+    /// Returns the bytes of the record at position `index`.
+    fn record(&self, index: usize) -> Result<&'a [u8], SmbError> {
+        let len = self.len();
+        if index >= len {
+            return Err(SmbError::IndexOutOfRange { index, len });
+        }
+        let corrupt = SmbError::CorruptRecord { index };
+        let start = read_u64(self.index, 8 * index).ok_or_else(|| corrupt.clone())?;
+        let end = if index + 1 == len {
+            self.index_offset
+        } else {
+            read_u64(self.index, 8 * (index + 1)).ok_or_else(|| corrupt.clone())?
+        };
+        if start < HEADER_LEN || start > end || end > self.index_offset {
+            return Err(corrupt);
+        }
+        Ok(&self.bytes[start..end])
+    }
+}
+
+/// Reads a little-endian `u32` at `offset`.
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(offset..offset.checked_add(4)?)?.try_into().ok()?))
+}
+
+/// Reads a little-endian `u64` at `offset` as a `usize`.
+fn read_u64(bytes: &[u8], offset: usize) -> Option<usize> {
+    let value = u64::from_le_bytes(bytes.get(offset..offset.checked_add(8)?)?.try_into().ok()?);
+    usize::try_from(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::smiles::Smiles;
+
+    fn container(inputs: &[&str]) -> Vec<u8> {
+        let mut writer = SmbWriter::new(Vec::new()).unwrap();
+        for input in inputs {
+            writer.push(&input.parse::<Smiles>().unwrap().freeze()).unwrap();
+        }
+        assert_eq!(writer.len(), inputs.len());
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn containers_round_trip() {
+        let inputs = ["CCO", "[Na+].[Cl-]", "F/C=C/F", "[2H][C@@](F)(Cl)Br", "c1ccc2[nH]ccc2c1"];
+        let bytes = container(&inputs);
+        let reader = SmbReader::new(&bytes).unwrap();
+
+        assert_eq!(reader.len(), inputs.len());
+        for (input, graph) in inputs.iter().zip(reader.iter()) {
+            let smiles: Smiles = input.parse().unwrap();
+            assert_eq!(graph.unwrap(), smiles.freeze(), "{input}");
+        }
+        for (input, view) in inputs.iter().zip(reader.iter_ref()) {
+            let smiles: Smiles = input.parse().unwrap();
+            let view = view.unwrap();
+            let record = view.implicit_hydrogen_counts().as_ptr_range();
+            assert!(bytes.as_ptr_range().contains(&record.start), "{input}");
+            assert_eq!(view.thaw(), smiles, "{input}");
+        }
+        assert_eq!(reader.get(5), Err(SmbError::IndexOutOfRange { index: 5, len: 5 }));
+        assert_eq!(reader.get_ref(5).unwrap_err(), SmbError::IndexOutOfRange { index: 5, len: 5 });
+
+        let empty = container(&[]);
+        assert!(SmbReader::new(&empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn aromaticized_sources_survive() {
+        let perception = "C1=CC=CC=C1".parse::<Smiles>().unwrap().perceive_aromaticity().unwrap();
+        let mut writer = SmbWriter::new(Vec::new()).unwrap();
+        writer.push(&perception.aromaticized().freeze()).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let graph = SmbReader::new(&bytes).unwrap().get(0).unwrap().thaw();
+        assert_eq!(graph.kekulize().unwrap(), perception.aromaticized().kekulize().unwrap());
+    }
+
+    #[test]
+    fn damaged_input_is_rejected() {
+        let bytes = container(&["CCO", "CCN"]);
+
+        assert_eq!(SmbReader::new(b"not a container").unwrap_err(), SmbError::BadMagic);
+        assert_eq!(SmbReader::new(&bytes[..bytes.len() - 1]).unwrap_err(), SmbError::Truncated);
+        let mut future = bytes.clone();
+        future[8] = 9;
+        assert_eq!(SmbReader::new(&future).unwrap_err(), SmbError::UnsupportedVersion(9));
+
+        let mut corrupt = bytes.clone();
+        corrupt[HEADER_LEN + 13] = 0xEE;
+        let reader = SmbReader::new(&corrupt).unwrap();
+        assert_eq!(reader.get(0).unwrap_err(), SmbError::CorruptRecord { index: 0 });
+        assert_eq!(reader.get_ref(0).unwrap_err(), SmbError::CorruptRecord { index: 0 });
+        assert!(reader.get(1).is_ok());
+        assert!(reader.get_ref(1).is_ok());
+    }
+}
@@ -0,0 +1,162 @@
+//! Transparent decompression of gzip and Zstandard SMILES files.
+
+use alloc::boxed::Box;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use flate2::read::MultiGzDecoder;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression format of a SMILES file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FileCompression {
+    /// Plain text.
+    None,
+    /// Gzip, including multi-member files written by `bgzip` or `cat a.gz
+    /// b.gz`.
+    Gzip,
+    /// Zstandard.
+    Zstd,
+}
+
+impl FileCompression {
+    /// Detects the compression from the first bytes of a file, falling back
+    /// to the extension of `path` when `header` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::io::FileCompression;
+    ///
+    /// assert_eq!(FileCompression::detect("a.smi", &[0x1f, 0x8b, 0x08]), FileCompression::Gzip);
+    /// assert_eq!(FileCompression::detect("a.smi.zst", &[]), FileCompression::Zstd);
+    /// assert_eq!(FileCompression::detect("a.gz", b"CCO\n"), FileCompression::None);
+    /// ```
+    #[must_use]
+    pub fn detect(path: impl AsRef<Path>, header: &[u8]) -> Self {
+        if header.starts_with(&GZIP_MAGIC) {
+            return Self::Gzip;
+        }
+        if header.starts_with(&ZSTD_MAGIC) {
+            return Self::Zstd;
+        }
+        if !header.is_empty() {
+            return Self::None;
+        }
+        match path.as_ref().extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("gz") => Self::Gzip,
+            Some(extension) if extension.eq_ignore_ascii_case("zst") => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Opens `path` as buffered text, transparently decompressing gzip and, with
+/// the `io-zstd` feature, Zstandard input.
+///
+/// # Errors
+/// Returns the I/O error raised while opening or peeking at the file, or an
+/// [`io::ErrorKind::Unsupported`] error for Zstandard input when the `io-zstd`
+/// feature is disabled.
+pub fn open_text_reader(path: impl AsRef<Path>) -> io::Result<Box<dyn BufRead + Send>> {
+    let path = path.as_ref();
+    let mut file = BufReader::new(File::open(path)?);
+    let compression = FileCompression::detect(path, file.fill_buf()?);
+    match compression {
+        FileCompression::None => Ok(Box::new(file)),
+        FileCompression::Gzip => Ok(Box::new(BufReader::new(MultiGzDecoder::new(file)))),
+        FileCompression::Zstd => open_zstd(file),
+    }
+}
+
+#[cfg(feature = "io-zstd")]
+fn open_zstd(file: BufReader<File>) -> io::Result<Box<dyn BufRead + Send>> {
+    let decoder = ruzstd::decoding::StreamingDecoder::new(file)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    Ok(Box::new(BufReader::new(decoder)))
+}
+
+#[cfg(not(feature = "io-zstd"))]
+fn open_zstd(_file: BufReader<File>) -> io::Result<Box<dyn BufRead + Send>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "reading Zstandard input requires the `io-zstd` feature",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io::Write, path::PathBuf, process, string::String, vec::Vec};
+
+    use flate2::{Compression, write::GzEncoder};
+
+    use super::*;
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = env::temp_dir().join(format!("smiles-parser-io-{}-{name}", process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn gzip(contents: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn read_all(path: &Path) -> String {
+        let mut text = String::new();
+        for line in open_text_reader(path).unwrap().lines() {
+            text.push_str(&line.unwrap());
+            text.push('\n');
+        }
+        text
+    }
+
+    #[test]
+    fn plain_and_gzip_files_read_the_same() {
+        let plain = temp_file("plain.smi", b"CCO\nc1ccccc1\n");
+        let compressed = temp_file("compressed.smi.gz", &gzip(b"CCO\nc1ccccc1\n"));
+
+        assert_eq!(read_all(&plain), "CCO\nc1ccccc1\n");
+        assert_eq!(read_all(&compressed), read_all(&plain));
+        fs::remove_file(plain).unwrap();
+        fs::remove_file(compressed).unwrap();
+    }
+
+    #[test]
+    fn gzip_is_detected_without_extension_and_across_members() {
+        let mut contents = gzip(b"CCO\n");
+        contents.extend(gzip(b"CCN\n"));
+        let path = temp_file("members.smi", &contents);
+
+        assert_eq!(read_all(&path), "CCO\nCCN\n");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn empty_files_fall_back_to_the_extension() {
+        assert_eq!(FileCompression::detect("a.GZ", &[]), FileCompression::Gzip);
+        assert_eq!(FileCompression::detect("a.smi", &[]), FileCompression::None);
+        assert_eq!(FileCompression::detect("a.zst", &ZSTD_MAGIC), FileCompression::Zstd);
+
+        let path = temp_file("empty.smi", b"");
+        assert_eq!(read_all(&path), "");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(not(feature = "io-zstd"))]
+    #[test]
+    fn zstd_without_feature_is_unsupported() {
+        let path = temp_file("frame.zst", &ZSTD_MAGIC);
+
+        let error = open_text_reader(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,229 @@
+//! JSON Lines reports of SMILES that failed to parse.
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+};
+use core::fmt::Write as _;
+use std::io::{self, Write};
+
+use hashbrown::HashSet;
+
+use crate::{errors::SmilesErrorWithSpan, shrink::shrink_failure};
+
+/// Writes failing SMILES with their diagnostics as JSON Lines, keeping at most
+/// a fixed number of distinct examples per error category.
+///
+/// Each written line is an object with the fields `id` (or `null`),
+/// `smiles`, `category` (see
+/// [`SmilesError::category`](crate::SmilesError::category)), `message`,
+/// `start`, `end`, `diagnostic` (the output of
+/// [`SmilesErrorWithSpan::render`]), `suggested_fix` (the rewritten
+/// SMILES, or `null`) and `minimal` (the shortest string found by
+/// [`shrink_failure`] that still fails with the same category). Repeated inputs
+/// are written once, and failures past the cap are only counted, so that a
+/// report sent to a dataset provider stays small while still covering every
+/// kind of problem.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::io::{ErrorCorpusWriter, SmilesTableReader, TableColumn, TableOptions};
+///
+/// let table = "id\tsmiles\n1\tCCO\n2\tCcl\n3\tC1CC\n4\tC1CCC\n";
+/// let options =
+///     TableOptions { id_column: Some(TableColumn::Index(0)), ..TableOptions::default() };
+/// let reader: SmilesTableReader<_> = SmilesTableReader::new(table.as_bytes(), &options)?;
+///
+/// let mut corpus = ErrorCorpusWriter::new(Vec::new(), 1);
+/// for record in reader {
+///     let record = record?;
+///     if let Err(error) = record.parsed() {
+///         corpus.record(record.id(), record.smiles(), error)?;
+///     }
+/// }
+///
+/// assert_eq!(corpus.failures()["UnclosedRing"], 2);
+/// let jsonl = String::from_utf8(corpus.into_inner()).unwrap();
+/// assert_eq!(jsonl.lines().count(), 2);
+/// assert!(jsonl.contains(r#""suggested_fix":"CCl""#));
+/// assert!(jsonl.contains(r#""minimal":"C1""#));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct ErrorCorpusWriter<W> {
+    writer: W,
+    per_category_limit: usize,
+    seen: HashSet<String>,
+    written: BTreeMap<&'static str, usize>,
+    failures: BTreeMap<&'static str, usize>,
+    line: String,
+}
+
+impl<W: Write> ErrorCorpusWriter<W> {
+    /// Creates a writer keeping at most `per_category_limit` examples of each
+    /// error category.
+    #[must_use]
+    pub fn new(writer: W, per_category_limit: usize) -> Self {
+        Self {
+            writer,
+            per_category_limit,
+            seen: HashSet::new(),
+            written: BTreeMap::new(),
+            failures: BTreeMap::new(),
+            line: String::new(),
+        }
+    }
+
+    /// Records the failure of `smiles`, writing it unless its category is
+    /// full or the same input was already written.
+    ///
+    /// Returns whether a line was written.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised by the underlying writer.
+    pub fn record(
+        &mut self,
+        id: Option<&str>,
+        smiles: &str,
+        error: &SmilesErrorWithSpan,
+    ) -> io::Result<bool> {
+        let category = error.smiles_error().category();
+        *self.failures.entry(category).or_default() += 1;
+        let written = self.written.entry(category).or_default();
+        if *written >= self.per_category_limit || self.seen.contains(smiles) {
+            return Ok(false);
+        }
+        *written += 1;
+        self.seen.insert(smiles.into());
+
+        self.line.clear();
+        self.line.push_str("{\"id\":");
+        match id {
+            Some(id) => push_json_string(&mut self.line, id),
+            None => self.line.push_str("null"),
+        }
+        self.line.push_str(",\"smiles\":");
+        push_json_string(&mut self.line, smiles);
+        self.line.push_str(",\"category\":");
+        push_json_string(&mut self.line, category);
+        self.line.push_str(",\"message\":");
+        push_json_string(&mut self.line, &error.smiles_error().to_string());
+        write!(self.line, ",\"start\":{},\"end\":{}", error.start(), error.end())
+            .unwrap_or_else(|_| unreachable!("writing to String cannot fail"));
+        self.line.push_str(",\"diagnostic\":");
+        push_json_string(&mut self.line, &error.render(smiles));
+        self.line.push_str(",\"suggested_fix\":");
+        match error.suggested_fix(smiles) {
+            Some(fix) => push_json_string(&mut self.line, &fix.apply(smiles)),
+            None => self.line.push_str("null"),
+        }
+        self.line.push_str(",\"minimal\":");
+        push_json_string(&mut self.line, &shrink_failure(smiles, error));
+        self.line.push_str("}\n");
+        self.writer.write_all(self.line.as_bytes())?;
+        Ok(true)
+    }
+
+    /// Returns the number of failures recorded per category, including those
+    /// that were not written.
+    #[inline]
+    #[must_use]
+    pub fn failures(&self) -> &BTreeMap<&'static str, usize> {
+        &self.failures
+    }
+
+    /// Flushes and returns the underlying writer.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised while flushing.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Returns the underlying writer without flushing it.
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Appends `value` to `out` as a JSON string literal.
+fn push_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            control if u32::from(control) < 0x20 => {
+                write!(out, "\\u{:04x}", u32::from(control))
+                    .unwrap_or_else(|_| unreachable!("writing to String cannot fail"));
+            }
+            other => out.push(other),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::smiles::Smiles;
+
+    fn record(corpus: &mut ErrorCorpusWriter<Vec<u8>>, id: Option<&str>, smiles: &str) -> bool {
+        let error = smiles.parse::<Smiles>().unwrap_err();
+        corpus.record(id, smiles, &error).unwrap()
+    }
+
+    #[test]
+    fn lines_carry_the_rendered_diagnostic() {
+        let mut corpus = ErrorCorpusWriter::new(Vec::new(), 10);
+        assert!(record(&mut corpus, Some("7"), "C1CC"));
+
+        let jsonl = String::from_utf8(corpus.finish().unwrap()).unwrap();
+        let error = "C1CC".parse::<Smiles>().unwrap_err();
+        let mut expected = String::new();
+        push_json_string(&mut expected, &error.render("C1CC"));
+        assert_eq!(
+            jsonl,
+            format!(
+                "{{\"id\":\"7\",\"smiles\":\"C1CC\",\"category\":\"UnclosedRing\",\"message\":\"Ring not closed\",\"start\":{},\"end\":{},\"diagnostic\":{expected},\"suggested_fix\":null,\"minimal\":\"C1\"}}\n",
+                error.start(),
+                error.end()
+            )
+        );
+        assert!(expected.contains("\\n"));
+    }
+
+    #[test]
+    fn categories_are_capped_and_inputs_deduplicated() {
+        let mut corpus = ErrorCorpusWriter::new(Vec::new(), 2);
+
+        assert!(record(&mut corpus, None, "C1CC"));
+        assert!(!record(&mut corpus, None, "C1CC"));
+        assert!(record(&mut corpus, None, "C2CC"));
+        assert!(!record(&mut corpus, None, "C3CC"));
+        assert!(record(&mut corpus, None, "CC(C"));
+
+        assert_eq!(corpus.failures()["UnclosedRing"], 4);
+        assert_eq!(corpus.failures()["UnclosedBranch"], 1);
+        let jsonl = String::from_utf8(corpus.into_inner()).unwrap();
+        assert_eq!(jsonl.lines().count(), 3);
+        assert!(jsonl.lines().all(|line| line.starts_with("{\"id\":null,")));
+    }
+
+    #[test]
+    fn json_strings_are_escaped() {
+        let mut out = String::new();
+        push_json_string(&mut out, "a\"b\\c\u{1}\té");
+
+        assert_eq!(out, "\"a\\\"b\\\\c\\u0001\\té\"");
+    }
+}
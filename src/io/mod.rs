@@ -0,0 +1,64 @@
+//! Readers and writers for SMILES files and tables.
+//!
+//! [`SmilesTableReader`] extracts a SMILES column, an optional identifier
+//! column and any extra columns from CSV or TSV input and parses every row,
+//! with [`TableOptions`] presets for the PubChem, ChEMBL and COCONUT dumps so
+//! regression suites over several databases share one loader. [`BulkValidator`]
+//! runs resumable validations over multi-gigabyte corpora, while [`parse_all`]
+//! summarizes a batch in memory with one [`Outcome`] per input. Failing inputs
+//! can be collected into a JSON Lines report with [`ErrorCorpusWriter`]. With
+//! the `io-compress` feature, `open_text_reader` opens bulk dumps such as
+//! PubChem `CID-SMILES.gz`, ChEMBL or COCONUT, detecting gzip from the leading
+//! magic bytes; Zstandard additionally needs `io-zstd`. Conversions end in
+//! [`SmilesFileWriter`], which streams canonical or as-parsed SMILES with
+//! identifier and extra columns, or [`SdfWriter`], which writes V2000
+//! records with embedded coordinates and data fields; [`SdfReader`] reads
+//! such files back into graphs that keep the name and every data field in
+//! their metadata. Parsed libraries can be stored as frozen graphs in the
+//! indexed `.smb` container written by [`SmbWriter`], which [`SmbReader`]
+//! opens over a byte slice or a memory map and decodes record by record into
+//! owned graphs without re-parsing, and [`SubstructureIndex`] maps
+//! fingerprint bits to record numbers to screen substructure queries against
+//! such a library.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::{fs::File, io::BufReader};
+//!
+//! use smiles_parser::io::{SmilesTableReader, TableOptions};
+//!
+//! let file = BufReader::new(File::open("CID-SMILES")?);
+//! let reader: SmilesTableReader<_> = SmilesTableReader::new(file, &TableOptions::pubchem())?;
+//! for record in reader {
+//!     let record = record?;
+//!     if let Err(error) = record.parsed() {
+//!         eprintln!("line {}: {error}", record.line_number());
+//!     }
+//! }
+//! # Ok::<(), smiles_parser::io::TableError>(())
+//! ```
+
+mod batch;
+mod binary;
+#[cfg(feature = "io-compress")]
+mod compress;
+mod error_corpus;
+mod sdf;
+mod smiles_file;
+mod substructure_index;
+mod table;
+mod validate;
+
+#[cfg(feature = "io-compress")]
+pub use self::compress::{FileCompression, open_text_reader};
+pub use self::{
+    batch::{Outcome, parse_all},
+    binary::{SMB_VERSION, SmbError, SmbReader, SmbWriter},
+    error_corpus::ErrorCorpusWriter,
+    sdf::{SdfError, SdfReader, SdfWriter},
+    smiles_file::{SmilesFileOptions, SmilesFileWriter},
+    substructure_index::SubstructureIndex,
+    table::{SmilesTableReader, SmilesTableRecord, TableColumn, TableError, TableOptions},
+    validate::{BulkValidator, ValidationOptions, ValidationReport},
+};
@@ -0,0 +1,812 @@
+//! Structure-data files (SDF) with V2000 molfile records.
+
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use core::{
+    fmt::{self, Write as _},
+    ops::Range,
+    str::FromStr,
+};
+use std::io::{self, BufRead, BufWriter, Write};
+
+use elements_rs::{ChargedValences, Element};
+use geometric_traits::traits::SparseValuedMatrixRef;
+use thiserror::Error;
+
+use crate::{
+    atom::{Atom, atom_symbol::AtomSymbol, bracketed::charge::Charge},
+    bond::{Bond, BondDescriptor},
+    smiles::{Smiles, build_bond_matrix_from_known_simple_edges},
+};
+
+/// Second line of every written molfile: program name and 3D dimension code.
+const PROGRAM_LINE: &str = "  SMILESPR          3D";
+/// Largest atom or bond count of a V2000 counts line.
+const MAX_V2000_COUNT: usize = 999;
+/// Valence field value marking an atom with valence zero.
+const ZERO_VALENCE: u8 = 15;
+
+/// Errors raised while reading an SD file.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SdfError {
+    /// Reading from the underlying source failed.
+    #[error("failed to read SD file: {0}")]
+    Io(#[from] io::Error),
+    /// A record does not follow the V2000 molfile layout, or uses a feature
+    /// the reader does not support.
+    #[error("line {line_number}: {message}")]
+    Malformed {
+        /// The 1-based line number in the file.
+        line_number: usize,
+        /// What is wrong with the line.
+        message: &'static str,
+    },
+}
+
+/// Streams graphs to an SD file.
+///
+/// Each graph is written as a V2000 molfile with the coordinates of
+/// [`Smiles::embed_3d`], followed by its data fields and the `$$$$`
+/// delimiter. Aromatic graphs are kekulized first, falling back to aromatic
+/// bonds (bond type 4) when no Kekule form exists. Charges and isotopes are
+/// written as `M  CHG` and `M  ISO` properties. Organic-subset atoms leave
+/// their hydrogens to the valence model of the reader, while bracket atoms
+/// such as `[CH2]` or `[nH]` record their bond valence plus hydrogens in the
+/// valence field of the atom block. The embedded coordinates ignore stereo,
+/// so graphs with tetrahedral or double-bond stereo are rejected rather than
+/// written without it; call [`Smiles::strip_stereo`] first to drop it. The
+/// output goes through a [`BufWriter`]; call [`SdfWriter::finish`] to flush
+/// it.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::{Smiles, io::SdfWriter};
+///
+/// let mut writer = SdfWriter::new(Vec::new());
+/// writer.write(&"c1ccccc1O".parse::<Smiles>()?, Some("phenol"), &[("CAS", "108-95-2")])?;
+///
+/// let sdf = String::from_utf8(writer.finish()?).unwrap();
+/// assert!(sdf.starts_with("phenol\n"));
+/// assert!(sdf.contains("  7  7  0  0  0  0  0  0  0  0999 V2000\n"));
+/// assert!(sdf.ends_with("M  END\n>  <CAS>\n108-95-2\n\n$$$$\n"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct SdfWriter<W: Write> {
+    writer: BufWriter<W>,
+    written: usize,
+    record: String,
+}
+
+impl<W: Write> SdfWriter<W> {
+    /// Creates a writer.
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        Self { writer: BufWriter::new(writer), written: 0, record: String::new() }
+    }
+
+    /// Writes one graph with its name and data fields.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised by the underlying writer, or an
+    /// [`io::ErrorKind::InvalidInput`] error when the graph has more than 999
+    /// atoms or bonds, a quadruple bond or a bracket atom with a valence above
+    /// 14, which V2000 cannot represent, when the graph has stereo, when the
+    /// name or a tag spans several lines, or when a value contains an empty
+    /// line.
+    pub fn write(
+        &mut self,
+        smiles: &Smiles,
+        name: Option<&str>,
+        properties: &[(&str, &str)],
+    ) -> io::Result<()> {
+        let name = name.unwrap_or_default();
+        if name.contains(['\n', '\r']) {
+            return Err(invalid_input("molecule names must fit on one line"));
+        }
+        for (tag, value) in properties {
+            if tag.contains(['<', '>', '\n', '\r']) {
+                return Err(invalid_input("data tags must fit on one line without angle brackets"));
+            }
+            if value.lines().any(str::is_empty) {
+                return Err(invalid_input("data values must not contain empty lines"));
+            }
+        }
+        if smiles.number_of_bonds() > MAX_V2000_COUNT || smiles.nodes().len() > MAX_V2000_COUNT {
+            return Err(invalid_input("V2000 molfiles hold at most 999 atoms and bonds"));
+        }
+        let has_stereo = smiles.nodes().iter().any(|atom| atom.chirality().is_some())
+            || smiles
+                .bond_matrix()
+                .sparse_entries()
+                .any(|(_, entry)| matches!(entry.bond(), Bond::Up | Bond::Down));
+        if has_stereo {
+            return Err(invalid_input("stereo cannot be written; strip it from the graph first"));
+        }
+        let kekule = smiles.kekulize();
+        let smiles = kekule.as_ref().unwrap_or(smiles);
+        let bonds = smiles
+            .bond_matrix()
+            .sparse_entries()
+            .filter(|((row, column), _)| row < column)
+            .map(|((row, column), entry)| {
+                let code = match entry.bond() {
+                    _ if entry.aromatic() => 4,
+                    Bond::Single | Bond::Up | Bond::Down => 1,
+                    Bond::Double => 2,
+                    Bond::Triple => 3,
+                    Bond::Quadruple => {
+                        return Err(invalid_input("V2000 molfiles have no quadruple bonds"));
+                    }
+                };
+                Ok((row, column, code))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        let valences = valence_fields(smiles, &bonds)?;
+
+        self.record.clear();
+        write_record(&mut self.record, name, smiles, &bonds, &valences, properties)
+            .unwrap_or_else(|_| unreachable!("writing to String cannot fail"));
+        self.writer.write_all(self.record.as_bytes())?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Writes a graph with the name and all the properties of its metadata,
+    /// in key order, as read by [`SdfReader`].
+    ///
+    /// # Errors
+    /// Returns the errors of [`SdfWriter::write`].
+    pub fn write_record(&mut self, smiles: &Smiles) -> io::Result<()> {
+        let properties = smiles.properties().collect::<Vec<_>>();
+        self.write(smiles, smiles.name(), &properties)
+    }
+
+    /// Returns the number of graphs written so far.
+    #[inline]
+    #[must_use]
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Flushes the buffer and returns the underlying writer.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised while flushing.
+    pub fn finish(self) -> io::Result<W> {
+        self.writer.into_inner().map_err(io::IntoInnerError::into_error)
+    }
+}
+
+/// Reads the records of an SD file, one [`Smiles`] graph per V2000 molfile.
+///
+/// Atoms, bonds of types 1 to 4, and the `M  CHG` and `M  ISO` properties
+/// become the graph. A non-empty header line becomes its
+/// [name](Smiles::name) and every `>  <tag>` data item one of its
+/// [properties](Smiles::property), so identifiers, CAS numbers or measured
+/// activities survive a conversion between formats; multi-line values are
+/// joined with `\n`. Other properties, coordinates and stereo flags are
+/// ignored. Atoms with a valence field become bracket atoms with the
+/// hydrogens completing it, as written by [`SdfWriter`] for bracket atoms.
+/// Otherwise, neutral organic-subset atoms without isotope take their
+/// hydrogens from the SMILES valence model, so a molfile with implicit
+/// hydrogens reads as the molecule it was written from. Charged or labelled
+/// atoms of the organic subset become bracket atoms with the hydrogens of
+/// their lowest fitting valence, and other elements carry none. Aromatic
+/// bonds (type 4) mark their atoms aromatic and count as single bonds plus
+/// one per aromatic atom; without a valence field, aromatic nitrogens
+/// carrying a hydrogen cannot be told apart and are read without one.
+///
+/// A malformed record yields an [`SdfError::Malformed`] and the reader
+/// resumes at the next `$$$$` delimiter.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::{
+///     Smiles,
+///     io::{SdfReader, SdfWriter},
+/// };
+///
+/// let mut writer = SdfWriter::new(Vec::new());
+/// writer.write(&"CC[NH3+]".parse::<Smiles>()?, Some("ethylammonium"), &[("pKa", "10.7")])?;
+/// let sdf = writer.finish()?;
+///
+/// let smiles = SdfReader::new(sdf.as_slice()).next().unwrap()?;
+/// assert_eq!(smiles.name(), Some("ethylammonium"));
+/// assert_eq!(smiles.property("pKa"), Some("10.7"));
+/// assert_eq!(smiles.render(), "CC[NH3+]");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct SdfReader<R> {
+    reader: R,
+    line_number: usize,
+    line_buffer: String,
+}
+
+impl<R: BufRead> SdfReader<R> {
+    /// Creates a reader.
+    #[inline]
+    #[must_use]
+    pub fn new(reader: R) -> Self {
+        Self { reader, line_number: 0, line_buffer: String::new() }
+    }
+}
+
+#[cfg(feature = "io-compress")]
+impl SdfReader<alloc::boxed::Box<dyn BufRead + Send>> {
+    /// Opens the SD file at `path` with
+    /// [`open_text_reader`](super::open_text_reader), so gzip-compressed
+    /// files are decoded transparently.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised while opening the file.
+    pub fn open(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        Ok(Self::new(super::open_text_reader(path)?))
+    }
+}
+
+impl<R: BufRead> Iterator for SdfReader<R> {
+    type Item = Result<Smiles, SdfError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first_line_number = self.line_number + 1;
+        let mut lines = Vec::new();
+        loop {
+            self.line_buffer.clear();
+            match self.reader.read_line(&mut self.line_buffer) {
+                Ok(0) => break,
+                Ok(_) => self.line_number += 1,
+                Err(error) => return Some(Err(error.into())),
+            }
+            let line = self.line_buffer.trim_end_matches(['\r', '\n']);
+            if line.trim_end() == "$$$$" {
+                return Some(parse_record(&lines, first_line_number));
+            }
+            lines.push(line.to_owned());
+        }
+        if lines.iter().all(|line| line.trim().is_empty()) {
+            None
+        } else {
+            Some(parse_record(&lines, first_line_number))
+        }
+    }
+}
+
+/// Returns the valence field of every atom: the bond valence plus the
+/// hydrogens of bracket atoms, with aromatic bonds counted as single bonds
+/// plus one per aromatic atom, and zero for organic-subset atoms.
+fn valence_fields(smiles: &Smiles, bonds: &[(usize, usize, u8)]) -> io::Result<Vec<u8>> {
+    let atoms = smiles.nodes();
+    let mut valences = vec![0_u8; atoms.len()];
+    let mut aromatic = vec![false; atoms.len()];
+    for &(row, column, code) in bonds {
+        let order = if code == 4 { 1 } else { code };
+        valences[row] = valences[row].saturating_add(order);
+        valences[column] = valences[column].saturating_add(order);
+        if code == 4 {
+            aromatic[row] = true;
+            aromatic[column] = true;
+        }
+    }
+    atoms
+        .iter()
+        .enumerate()
+        .map(|(id, atom)| {
+            if !atom.is_bracket_atom() {
+                return Ok(0);
+            }
+            let valence = valences[id]
+                .saturating_add(u8::from(aromatic[id]))
+                .saturating_add(atom.hydrogen_count());
+            match valence {
+                0 => Ok(ZERO_VALENCE),
+                1..ZERO_VALENCE => Ok(valence),
+                _ => Err(invalid_input("V2000 valence fields stop at 14")),
+            }
+        })
+        .collect()
+}
+
+/// Appends the molfile, the data fields and the record delimiter.
+fn write_record(
+    record: &mut String,
+    name: &str,
+    smiles: &Smiles,
+    bonds: &[(usize, usize, u8)],
+    valences: &[u8],
+    properties: &[(&str, &str)],
+) -> fmt::Result {
+    let atoms = smiles.nodes();
+    writeln!(record, "{name}\n{PROGRAM_LINE}\n")?;
+    writeln!(record, "{:>3}{:>3}  0  0  0  0  0  0  0  0999 V2000", atoms.len(), bonds.len())?;
+    let conformer = smiles.embed_3d();
+    for ((atom, [x, y, z]), valence) in atoms.iter().zip(conformer.positions()).zip(valences) {
+        let symbol = atom.element().map_or("*", |element| element.symbol());
+        writeln!(
+            record,
+            "{x:>10.4}{y:>10.4}{z:>10.4} {symbol:<3} 0  0  0  0  0{valence:>3}  0  0  0  0  0  0"
+        )?;
+    }
+    for (row, column, code) in bonds {
+        writeln!(record, "{:>3}{:>3}{code:>3}  0  0  0  0", row + 1, column + 1)?;
+    }
+    let charges = atoms
+        .iter()
+        .enumerate()
+        .filter(|(_, atom)| atom.charge_value() != 0)
+        .map(|(id, atom)| (id, i32::from(atom.charge_value())));
+    write_atom_property(record, "CHG", charges)?;
+    let isotopes = atoms
+        .iter()
+        .enumerate()
+        .filter_map(|(id, atom)| atom.isotope_mass_number().map(|mass| (id, i32::from(mass))));
+    write_atom_property(record, "ISO", isotopes)?;
+    record.push_str("M  END\n");
+
+    for (tag, value) in properties {
+        writeln!(record, ">  <{tag}>")?;
+        if !value.is_empty() {
+            writeln!(record, "{}", value.trim_end_matches(['\n', '\r']))?;
+        }
+        record.push('\n');
+    }
+    record.push_str("$$$$\n");
+    Ok(())
+}
+
+/// Appends `M  <kind>` lines holding up to eight `(atom, value)` pairs each.
+fn write_atom_property(
+    record: &mut String,
+    kind: &str,
+    values: impl Iterator<Item = (usize, i32)>,
+) -> fmt::Result {
+    let values = values.collect::<Vec<_>>();
+    for chunk in values.chunks(8) {
+        write!(record, "M  {kind}{:>3}", chunk.len())?;
+        for (id, value) in chunk {
+            write!(record, " {:>3} {value:>3}", id + 1)?;
+        }
+        record.push('\n');
+    }
+    Ok(())
+}
+
+/// A malformed line of a record: its index in the record and the problem.
+type LineError = (usize, &'static str);
+
+/// Atom block of a molfile, updated by its property block.
+struct AtomBlock {
+    elements: Vec<Element>,
+    isotopes: Vec<Option<u16>>,
+    charges: Vec<i8>,
+    /// Valence fields, as bond valence plus hydrogens.
+    valences: Vec<Option<u8>>,
+}
+
+/// Parses the lines of one record, without its `$$$$` delimiter.
+fn parse_record(lines: &[String], first_line_number: usize) -> Result<Smiles, SdfError> {
+    let malformed = |(index, message): LineError| {
+        SdfError::Malformed { line_number: first_line_number + index, message }
+    };
+    let counts = line(lines, 3).map_err(malformed)?;
+    if counts.contains("V3000") {
+        return Err(malformed((3, "V3000 molfiles are not supported")));
+    }
+    let number_of_atoms =
+        integer(counts, 0..3).ok_or((3, "invalid atom count")).map_err(malformed)?;
+    let number_of_bonds =
+        integer(counts, 3..6).ok_or((3, "invalid bond count")).map_err(malformed)?;
+
+    let mut atoms = parse_atom_block(lines, number_of_atoms).map_err(malformed)?;
+    let bond_lines = 4 + number_of_atoms..4 + number_of_atoms + number_of_bonds;
+    let bonds = parse_bond_block(lines, bond_lines.clone(), number_of_atoms).map_err(malformed)?;
+    let end = parse_property_block(lines, bond_lines.end, &mut atoms).map_err(malformed)?;
+    let mut smiles = build_graph(&atoms, &bonds).map_err(|message| malformed((4, message)))?;
+    let name = lines[0].trim();
+    if !name.is_empty() {
+        smiles.set_name(name);
+    }
+    parse_data_items(&mut smiles, &lines[end + 1..]);
+    Ok(smiles)
+}
+
+/// Returns the line at `index`, or an error when the record ends before it.
+fn line(lines: &[String], index: usize) -> Result<&str, LineError> {
+    lines.get(index).map(String::as_str).ok_or((index, "truncated molfile"))
+}
+
+/// Parses the element, the charge code and the valence field of every atom
+/// line.
+fn parse_atom_block(lines: &[String], number_of_atoms: usize) -> Result<AtomBlock, LineError> {
+    let mut atoms = AtomBlock {
+        elements: Vec::with_capacity(number_of_atoms),
+        isotopes: vec![None; number_of_atoms],
+        charges: Vec::with_capacity(number_of_atoms),
+        valences: Vec::with_capacity(number_of_atoms),
+    };
+    for (id, index) in (4..4 + number_of_atoms).enumerate() {
+        let atom_line = line(lines, index)?;
+        let element = match field(atom_line, 31..34) {
+            "D" => {
+                atoms.isotopes[id] = Some(2);
+                Element::H
+            }
+            "T" => {
+                atoms.isotopes[id] = Some(3);
+                Element::H
+            }
+            symbol => {
+                Element::from_str(symbol)
+                    .map_err(|_| (index, "unsupported or query atom symbol"))?
+            }
+        };
+        let charge = match integer(atom_line, 36..39).unwrap_or_default() {
+            code @ (1..=3 | 5..=7) => {
+                4 - i8::try_from(code).unwrap_or_else(|_| unreachable!("charge codes fit in i8"))
+            }
+            _ => 0,
+        };
+        let valence = match integer(atom_line, 48..51) {
+            Some(15) => Some(0),
+            Some(valence @ 1..=14) => {
+                Some(u8::try_from(valence).unwrap_or_else(|_| unreachable!("valences fit in u8")))
+            }
+            _ => None,
+        };
+        atoms.elements.push(element);
+        atoms.charges.push(charge);
+        atoms.valences.push(valence);
+    }
+    Ok(atoms)
+}
+
+/// Parses the bond lines into zero-based `(smaller, larger, type)` triples.
+fn parse_bond_block(
+    lines: &[String],
+    bond_lines: Range<usize>,
+    number_of_atoms: usize,
+) -> Result<Vec<(usize, usize, usize)>, LineError> {
+    let mut bonds = Vec::with_capacity(bond_lines.len());
+    for index in bond_lines.clone() {
+        let bond_line = line(lines, index)?;
+        let ends = (integer(bond_line, 0..3), integer(bond_line, 3..6));
+        let (Some(first @ 1..), Some(second @ 1..)) = ends else {
+            return Err((index, "invalid bond atom"));
+        };
+        if first == second || first.max(second) > number_of_atoms {
+            return Err((index, "invalid bond atom"));
+        }
+        let code = match integer(bond_line, 6..9) {
+            Some(code @ 1..=4) => code,
+            _ => return Err((index, "unsupported bond type")),
+        };
+        bonds.push((first.min(second) - 1, first.max(second) - 1, code));
+    }
+    let mut ends = bonds.iter().map(|&(first, second, _)| (first, second)).collect::<Vec<_>>();
+    ends.sort_unstable();
+    if ends.windows(2).any(|pair| pair[0] == pair[1]) {
+        return Err((bond_lines.start, "duplicate bond"));
+    }
+    Ok(bonds)
+}
+
+/// Applies the `M  CHG` and `M  ISO` lines up to `M  END` and returns the
+/// index of the `M  END` line. As in the V2000 format, the first `M  CHG`
+/// line discards the charges of the atom block.
+fn parse_property_block(
+    lines: &[String],
+    start: usize,
+    atoms: &mut AtomBlock,
+) -> Result<usize, LineError> {
+    let mut charges_reset = false;
+    for index in start.. {
+        let property_line = line(lines, index)?;
+        if property_line.starts_with("M  END") {
+            return Ok(index);
+        }
+        let kind = property_line.get(3..6).unwrap_or_default();
+        if !property_line.starts_with("M  ") || !matches!(kind, "CHG" | "ISO") {
+            continue;
+        }
+        let entries =
+            integer(property_line, 6..9).ok_or((index, "invalid property entry count"))?;
+        if kind == "CHG" && !charges_reset {
+            atoms.charges.fill(0);
+            charges_reset = true;
+        }
+        for entry in 0..entries {
+            let column = 9 + 8 * entry;
+            let atom = integer(property_line, column..column + 4)
+                .filter(|atom| (1..=atoms.elements.len()).contains(atom))
+                .ok_or((index, "invalid property atom"))?;
+            let value = field(property_line, column + 4..column + 8);
+            if kind == "CHG" {
+                atoms.charges[atom - 1] = value.parse().map_err(|_| (index, "invalid charge"))?;
+            } else {
+                atoms.isotopes[atom - 1] =
+                    Some(value.parse().map_err(|_| (index, "invalid isotope"))?);
+            }
+        }
+    }
+    unreachable!("the line range is unbounded")
+}
+
+/// Sets the `>  <tag>` data items following the molfile as properties of
+/// the graph. Items without an angle-bracketed tag are skipped.
+fn parse_data_items(smiles: &mut Smiles, lines: &[String]) {
+    let mut lines = lines.iter().map(String::as_str);
+    while let Some(header) = lines.next() {
+        if !header.starts_with('>') {
+            continue;
+        }
+        let tag = header
+            .split_once('<')
+            .and_then(|(_, rest)| rest.split_once('>'))
+            .map(|(tag, _)| tag.to_owned());
+        let value =
+            lines.by_ref().take_while(|value| !value.is_empty()).collect::<Vec<_>>().join("\n");
+        if let Some(tag) = tag {
+            smiles.set_property(tag, value);
+        }
+    }
+}
+
+/// Builds the graph of a molfile from its atom and bond blocks.
+fn build_graph(atoms: &AtomBlock, bonds: &[(usize, usize, usize)]) -> Result<Smiles, &'static str> {
+    let AtomBlock { elements, isotopes, charges, valences: valence_fields } = atoms;
+    let mut aromatic = vec![false; elements.len()];
+    let mut valences = vec![0_u8; elements.len()];
+    for &(first, second, code) in bonds {
+        if code == 4 {
+            aromatic[first] = true;
+            aromatic[second] = true;
+        }
+        let order = if code == 4 {
+            1
+        } else {
+            u8::try_from(code).unwrap_or_else(|_| unreachable!("bond types fit in u8"))
+        };
+        valences[first] = valences[first].saturating_add(order);
+        valences[second] = valences[second].saturating_add(order);
+    }
+
+    let mut atoms = Vec::with_capacity(elements.len());
+    for (id, &element) in elements.iter().enumerate() {
+        let symbol = AtomSymbol::Element(element);
+        let organic = matches!(
+            element,
+            Element::B
+                | Element::C
+                | Element::N
+                | Element::O
+                | Element::P
+                | Element::S
+                | Element::F
+                | Element::Cl
+                | Element::Br
+                | Element::I
+        );
+        if aromatic[id]
+            && !matches!(
+                element,
+                Element::B
+                    | Element::C
+                    | Element::N
+                    | Element::O
+                    | Element::P
+                    | Element::S
+                    | Element::Se
+                    | Element::As
+                    | Element::Te
+            )
+        {
+            return Err("aromatic bond on an element that cannot be aromatic");
+        }
+        let valence = valences[id].saturating_add(u8::from(aromatic[id]));
+        let hydrogens = match valence_fields[id] {
+            Some(field) => {
+                field.checked_sub(valence).ok_or("valence field below the bond valence")?
+            }
+            None if organic && charges[id] == 0 && isotopes[id].is_none() => {
+                atoms.push(Atom::new_organic_subset(symbol, aromatic[id]));
+                continue;
+            }
+            None if organic => {
+                element
+                    .valences_at_charge(charges[id])
+                    .iter()
+                    .find(|&&allowed| allowed >= valence)
+                    .map_or(0, |allowed| allowed - valence)
+            }
+            None => 0,
+        };
+        let charge = Charge::try_new(charges[id]).map_err(|_| "charge out of range")?;
+        let mut builder = Atom::builder()
+            .with_symbol(symbol)
+            .with_aromatic(aromatic[id])
+            .with_hydrogens(hydrogens)
+            .with_charge(charge);
+        if let Some(isotope) = isotopes[id] {
+            builder = builder.with_isotope(isotope);
+        }
+        atoms.push(builder.build());
+    }
+
+    let bond_matrix = build_bond_matrix_from_known_simple_edges(
+        elements.len(),
+        bonds.iter().map(|&(first, second, code)| {
+            let descriptor = match code {
+                1 => BondDescriptor::new(Bond::Single),
+                2 => BondDescriptor::new(Bond::Double),
+                3 => BondDescriptor::new(Bond::Triple),
+                _ => BondDescriptor::aromatic(Bond::Single),
+            };
+            (first, second, descriptor, None)
+        }),
+    );
+    Ok(Smiles::from_bond_matrix_parts(atoms, bond_matrix))
+}
+
+/// Returns the trimmed columns `range` of a fixed-width line, empty when the
+/// line is shorter.
+fn field(line: &str, range: Range<usize>) -> &str {
+    let end = range.end.min(line.len());
+    line.get(range.start.min(end)..end).unwrap_or_default().trim()
+}
+
+/// Parses the columns `range` of a fixed-width line as an integer.
+fn integer(line: &str, range: Range<usize>) -> Option<usize> {
+    field(line, range).parse().ok()
+}
+
+fn invalid_input(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{format, string::ToString, vec::Vec};
+
+    use molecular_formulas::ChemicalFormula;
+
+    use super::*;
+
+    fn sdf(smiles: &str, name: Option<&str>, properties: &[(&str, &str)]) -> io::Result<String> {
+        let mut writer = SdfWriter::new(Vec::new());
+        writer.write(&smiles.parse::<Smiles>().unwrap(), name, properties)?;
+        assert_eq!(writer.written(), 1);
+        Ok(String::from_utf8(writer.finish()?).unwrap())
+    }
+
+    #[test]
+    fn molfiles_follow_the_v2000_layout() {
+        let sdf = sdf("C[NH3+]", None, &[]).unwrap();
+        let lines = sdf.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "");
+        assert_eq!(lines[1], PROGRAM_LINE);
+        assert_eq!(lines[3], "  2  1  0  0  0  0  0  0  0  0999 V2000");
+        assert_eq!(&lines[4][30..], " C   0  0  0  0  0  0  0  0  0  0  0  0");
+        assert_eq!(&lines[5][31..34], "N  ");
+        assert_eq!(&lines[5][48..51], "  4");
+        assert_eq!(lines[6], "  1  2  1  0  0  0  0");
+        assert_eq!(lines[7], "M  CHG  1   2   1");
+        assert_eq!(lines[8..], ["M  END", "$$$$"]);
+    }
+
+    #[test]
+    fn aromatic_graphs_are_kekulized_and_isotopes_kept() {
+        let sdf = sdf("[13cH]1ccccc1", Some("benzene-13C"), &[("note", "a\nb")]).unwrap();
+        let bond_types = sdf
+            .lines()
+            .skip(10)
+            .take(6)
+            .map(|line| line[6..9].trim().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(bond_types.iter().filter(|code| *code == "2").count(), 3);
+        assert!(sdf.contains("M  ISO  1   1  13\n"));
+        assert!(sdf.contains(">  <note>\na\nb\n\n$$$$\n"));
+    }
+
+    #[test]
+    fn unrepresentable_records_are_rejected() {
+        for error in [
+            sdf("C$C", None, &[]).unwrap_err(),
+            sdf("C", Some("a\nb"), &[]).unwrap_err(),
+            sdf("C", None, &[("<tag>", "")]).unwrap_err(),
+            sdf("C", None, &[("tag", "a\n\nb")]).unwrap_err(),
+        ] {
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+        let many_atoms = "C".repeat(1000);
+        assert!(sdf(&many_atoms, None, &[]).is_err());
+    }
+
+    fn read(sdf: &str) -> Vec<Result<Smiles, SdfError>> {
+        SdfReader::new(sdf.as_bytes()).collect()
+    }
+
+    #[test]
+    fn written_records_read_back_with_their_data_fields() {
+        let mut writer = SdfWriter::new(Vec::new());
+        let inputs = ["CC(=O)[O-]", "[13CH3]O", "C[NH3+]", "[2H]C([2H])=C", "[Na+].[Cl-]", "N#N"];
+        for (id, input) in inputs.iter().enumerate() {
+            let mut smiles: Smiles = input.parse().unwrap();
+            smiles.set_name(format!("mol{id}"));
+            smiles.set_property("ID", id.to_string());
+            smiles.set_property("note", "first\nsecond");
+            writer.write_record(&smiles).unwrap();
+        }
+        let sdf = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        let records = read(&sdf);
+        assert_eq!(records.len(), inputs.len());
+        for (id, (smiles, input)) in records.into_iter().zip(inputs).enumerate() {
+            let smiles = smiles.unwrap();
+            let expected = input.parse::<Smiles>().unwrap().canonicalize().render();
+            assert_eq!(smiles.canonicalize().render(), expected);
+            assert_eq!(smiles.name(), Some(format!("mol{id}").as_str()));
+            assert_eq!(smiles.property("ID"), Some(id.to_string().as_str()));
+            assert_eq!(smiles.property("note"), Some("first\nsecond"));
+        }
+    }
+
+    #[test]
+    fn bracket_hydrogens_survive_and_stereo_is_rejected() {
+        for input in ["[CH2]C", "[C]", "c1cc[nH]c1", "[NH4+]", "[Fe]", "[SiH4]"] {
+            let smiles: Smiles = input.parse().unwrap();
+            let read_back = read(&sdf(input, None, &[]).unwrap()).pop().unwrap().unwrap();
+            assert_eq!(
+                ChemicalFormula::<u32, i32>::from(&read_back),
+                ChemicalFormula::<u32, i32>::from(&smiles),
+                "{input}"
+            );
+        }
+        for input in ["C[C@H](N)O", "F/C=C/F"] {
+            let error = sdf(input, None, &[]).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            let stripped = input.parse::<Smiles>().unwrap().strip_stereo();
+            assert!(SdfWriter::new(Vec::new()).write(&stripped, None, &[]).is_ok());
+        }
+    }
+
+    #[test]
+    fn aromatic_bonds_mark_aromatic_atoms() {
+        let mut sdf = sdf("C1=CC=CC=C1", None, &[]).unwrap();
+        let lines = sdf
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                if (10..16).contains(&index) {
+                    format!("{}  4{}", &line[..6], &line[9..])
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect::<Vec<_>>();
+        sdf = lines.join("\n");
+
+        let smiles = read(&sdf).pop().unwrap().unwrap();
+        let benzene: Smiles = "c1ccccc1".parse().unwrap();
+        assert_eq!(smiles.canonicalize().render(), benzene.canonicalize().render());
+        assert_eq!(smiles.name(), None);
+        assert_eq!(smiles.properties().count(), 0);
+    }
+
+    #[test]
+    fn malformed_records_are_reported_and_skipped() {
+        let valid = sdf("CO", Some("methanol"), &[]).unwrap();
+        let bad_bond = valid.replace("  1  2  1  0", "  1  1  1  0");
+        let records = read(&format!("{bad_bond}{valid}"));
+        assert_eq!(records.len(), 2);
+        assert!(matches!(records[0], Err(SdfError::Malformed { line_number: 7, .. })));
+        assert_eq!(records[1].as_ref().unwrap().name(), Some("methanol"));
+
+        let v3000 = valid.replace("V2000", "V3000");
+        assert!(matches!(read(&v3000)[0], Err(SdfError::Malformed { line_number: 4, .. })));
+        assert!(matches!(read("name\n\n\n")[0], Err(SdfError::Malformed { .. })));
+        assert!(read("\n\n").is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+//! Buffered writers for `.smi` files.
+
+use alloc::{string::String, vec::Vec};
+use std::io::{self, BufWriter, Write};
+
+use crate::smiles::{Smiles, SmilesAtomPolicy};
+
+/// Layout of the lines written by [`SmilesFileWriter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmilesFileOptions {
+    /// Field separator.
+    pub delimiter: char,
+    /// Whether graphs are written as canonical SMILES rather than rendered
+    /// in their parsed atom order.
+    pub canonical: bool,
+    /// Column names written as a first line, if any.
+    pub header: Option<Vec<String>>,
+}
+
+impl Default for SmilesFileOptions {
+    fn default() -> Self {
+        Self { delimiter: '\t', canonical: true, header: None }
+    }
+}
+
+/// Streams graphs to a `.smi` file, one line per graph.
+///
+/// Each line holds the SMILES string, followed by the identifier and any
+/// extra fields when given, separated by [`SmilesFileOptions::delimiter`].
+/// The output goes through a [`BufWriter`], so writing to a [`std::fs::File`]
+/// needs no extra buffering; call [`SmilesFileWriter::finish`] to flush it.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::{
+///     Smiles,
+///     io::{SmilesFileOptions, SmilesFileWriter},
+/// };
+///
+/// let mut writer = SmilesFileWriter::new(Vec::new(), &SmilesFileOptions::default())?;
+/// writer.write(&"C".parse::<Smiles>()?, Some("methane"), &["16.04"])?;
+/// writer.write(&"N".parse::<Smiles>()?, None, &[])?;
+///
+/// let written = String::from_utf8(writer.finish()?).unwrap();
+/// assert_eq!(written, "C\tmethane\t16.04\nN\n");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct SmilesFileWriter<W: Write> {
+    writer: BufWriter<W>,
+    delimiter: char,
+    canonical: bool,
+    written: usize,
+    line: String,
+}
+
+impl<W: Write> SmilesFileWriter<W> {
+    /// Creates a writer and writes the header line, if any.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised by the underlying writer, or an
+    /// [`io::ErrorKind::InvalidInput`] error when a column name contains the
+    /// delimiter or a line break.
+    pub fn new(writer: W, options: &SmilesFileOptions) -> io::Result<Self> {
+        let mut writer = Self {
+            writer: BufWriter::new(writer),
+            delimiter: options.delimiter,
+            canonical: options.canonical,
+            written: 0,
+            line: String::new(),
+        };
+        if let Some(header) = &options.header {
+            let header = header.iter().map(String::as_str).collect::<Vec<_>>();
+            writer.write_fields(&header)?;
+        }
+        Ok(writer)
+    }
+
+    /// Writes one graph with its identifier and extra fields. An empty
+    /// identifier field is written when `id` is `None` but `extra_fields`
+    /// is not empty.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised by the underlying writer, or an
+    /// [`io::ErrorKind::InvalidInput`] error when a field contains the
+    /// delimiter or a line break.
+    pub fn write<AtomPolicy: SmilesAtomPolicy>(
+        &mut self,
+        smiles: &Smiles<AtomPolicy>,
+        id: Option<&str>,
+        extra_fields: &[&str],
+    ) -> io::Result<()> {
+        let rendered =
+            if self.canonical { smiles.canonicalize().render() } else { smiles.render() };
+        let mut fields = Vec::with_capacity(2 + extra_fields.len());
+        fields.push(rendered.as_str());
+        if id.is_some() || !extra_fields.is_empty() {
+            fields.push(id.unwrap_or_default());
+        }
+        fields.extend_from_slice(extra_fields);
+        self.write_fields(&fields)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Returns the number of graphs written so far.
+    #[inline]
+    #[must_use]
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Flushes the buffer and returns the underlying writer.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised while flushing.
+    pub fn finish(self) -> io::Result<W> {
+        self.writer.into_inner().map_err(io::IntoInnerError::into_error)
+    }
+
+    fn write_fields(&mut self, fields: &[&str]) -> io::Result<()> {
+        self.line.clear();
+        for (index, field) in fields.iter().enumerate() {
+            if field.contains([self.delimiter, '\n', '\r']) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "field contains the delimiter or a line break",
+                ));
+            }
+            if index > 0 {
+                self.line.push(self.delimiter);
+            }
+            self.line.push_str(field);
+        }
+        self.line.push('\n');
+        self.writer.write_all(self.line.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{string::ToString, vec};
+
+    use super::*;
+    use crate::io::{SmilesTableReader, TableColumn, TableOptions};
+
+    #[test]
+    fn written_files_read_back() {
+        let options = SmilesFileOptions {
+            delimiter: ',',
+            canonical: false,
+            header: Some(vec!["smiles".to_string(), "id".to_string()]),
+        };
+        let mut writer = SmilesFileWriter::new(Vec::new(), &options).unwrap();
+        for (id, input) in ["OCC", "c1ccccc1", "[Na+].[Cl-]"].iter().enumerate() {
+            writer.write(&input.parse::<Smiles>().unwrap(), Some(&id.to_string()), &[]).unwrap();
+        }
+        assert_eq!(writer.written(), 3);
+        let bytes = writer.finish().unwrap();
+        assert!(bytes.starts_with(b"smiles,id\nOCC,0\n"));
+
+        let table = TableOptions { id_column: Some(TableColumn::Index(1)), ..TableOptions::csv() };
+        let reader: SmilesTableReader<_> =
+            SmilesTableReader::new(bytes.as_slice(), &table).unwrap();
+        let ids =
+            reader.map(|record| record.unwrap().id().unwrap().to_string()).collect::<Vec<_>>();
+        assert_eq!(ids, ["0", "1", "2"]);
+    }
+
+    #[test]
+    fn fields_breaking_the_layout_are_rejected() {
+        let mut writer = SmilesFileWriter::new(Vec::new(), &SmilesFileOptions::default()).unwrap();
+        let smiles: Smiles = "C".parse().unwrap();
+        let error = writer.write(&smiles, Some("a\tb"), &[]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        writer.write(&smiles, None, &["x"]).unwrap();
+        assert_eq!(writer.finish().unwrap(), b"C\t\tx\n");
+    }
+}
@@ -0,0 +1,279 @@
+//! Inverted fingerprint index for substructure screening.
+
+use alloc::vec::Vec;
+use std::io::{self, Write};
+
+use super::binary::{SMB_VERSION, SmbError, SmbReader};
+use crate::smiles::{FINGERPRINT_BITS, Fingerprint, Smiles, SmilesAtomPolicy};
+
+/// Leading bytes of a persisted [`SubstructureIndex`].
+const MAGIC: [u8; 8] = *b"SMILESX\0";
+/// [`FINGERPRINT_BITS`] as stored in the index header.
+#[allow(clippy::cast_possible_truncation)]
+const BITS: u32 = FINGERPRINT_BITS as u32;
+
+/// An inverted index from [`Fingerprint`] bits to molecule ids.
+///
+/// Molecule ids are the insertion order, so an index built with
+/// [`SubstructureIndex::from_container`] shares the record numbers of its
+/// `.smb` container and is typically persisted next to it. A query graph is
+/// screened by intersecting the molecule lists of its fingerprint bits: every
+/// molecule containing the query as a subgraph is a candidate, while most
+/// others are discarded before any exact match. Queries are parsed SMILES,
+/// the subset of SMARTS this crate reads; queries with wildcard atoms, which
+/// match any atom, are screened by passing the fingerprint of a
+/// [`WildcardSmiles`](crate::smiles::WildcardSmiles) to
+/// [`SubstructureIndex::candidates`].
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::{Smiles, io::SubstructureIndex};
+///
+/// let mut index = SubstructureIndex::new();
+/// for smiles in ["CCO", "Oc1ccccc1", "c1ccccc1C(=O)O", "CCN"] {
+///     index.push(&smiles.parse::<Smiles>()?.fingerprint());
+/// }
+///
+/// let query: Smiles = "c1ccccc1".parse()?;
+/// assert_eq!(index.substructure_candidates(&query), [1, 2]);
+///
+/// let mut persisted = Vec::new();
+/// index.write_to(&mut persisted)?;
+/// assert_eq!(SubstructureIndex::read(&persisted)?, index);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubstructureIndex {
+    postings: Vec<Vec<u32>>,
+    len: usize,
+}
+
+impl Default for SubstructureIndex {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SubstructureIndex {
+    /// Creates an empty index.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { postings: vec![Vec::new(); FINGERPRINT_BITS], len: 0 }
+    }
+
+    /// Indexes the fingerprints of every graph of a container.
+    ///
+    /// # Errors
+    /// Returns the [`SmbError`] of the first record that does not decode.
+    pub fn from_container(reader: &SmbReader<'_>) -> Result<Self, SmbError> {
+        let mut index = Self::new();
+        for graph in reader.iter() {
+            index.push(&graph?.thaw().fingerprint());
+        }
+        Ok(index)
+    }
+
+    /// Adds a molecule and returns its id.
+    ///
+    /// # Panics
+    /// Panics if the index already holds `u32::MAX` molecules.
+    pub fn push(&mut self, fingerprint: &Fingerprint) -> usize {
+        let id = u32::try_from(self.len)
+            .ok()
+            .filter(|&id| id != u32::MAX)
+            .expect("substructure indices hold fewer than u32::MAX molecules");
+        for bit in fingerprint.ones() {
+            self.postings[bit].push(id);
+        }
+        self.len += 1;
+        self.len - 1
+    }
+
+    /// Returns the number of indexed molecules.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether no molecule is indexed.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the ids of the molecules that may contain `query`, in
+    /// increasing order.
+    #[must_use]
+    pub fn substructure_candidates<AtomPolicy: SmilesAtomPolicy>(
+        &self,
+        query: &Smiles<AtomPolicy>,
+    ) -> Vec<usize> {
+        self.candidates(&query.fingerprint())
+    }
+
+    /// Returns the ids of the molecules whose fingerprints are supersets of
+    /// `fingerprint`, in increasing order.
+    #[must_use]
+    pub fn candidates(&self, fingerprint: &Fingerprint) -> Vec<usize> {
+        let mut lists = fingerprint.ones().map(|bit| &self.postings[bit]).collect::<Vec<_>>();
+        lists.sort_unstable_by_key(|list| list.len());
+        let Some((shortest, rest)) = lists.split_first() else {
+            return (0..self.len).collect();
+        };
+        shortest
+            .iter()
+            .filter(|id| rest.iter().all(|list| list.binary_search(id).is_ok()))
+            .map(|&id| id as usize)
+            .collect()
+    }
+
+    /// Writes the index: eight magic bytes, the `u32` format version, the
+    /// `u64` molecule count, the `u32` fingerprint width and, for every bit,
+    /// a `u32` id count followed by the `u32` ids, all little-endian.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised by the writer.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&SMB_VERSION.to_le_bytes())?;
+        writer.write_all(&(self.len as u64).to_le_bytes())?;
+        writer.write_all(&BITS.to_le_bytes())?;
+        for list in &self.postings {
+            let count = u32::try_from(list.len())
+                .unwrap_or_else(|_| unreachable!("molecule lists hold fewer than u32::MAX ids"));
+            writer.write_all(&count.to_le_bytes())?;
+            for id in list {
+                writer.write_all(&id.to_le_bytes())?;
+            }
+        }
+        writer.flush()
+    }
+
+    /// Reads an index written by [`SubstructureIndex::write_to`].
+    ///
+    /// # Errors
+    /// Returns [`SmbError::BadMagic`] or [`SmbError::UnsupportedVersion`] for
+    /// foreign input and [`SmbError::Truncated`] for cut or inconsistent
+    /// molecule lists.
+    pub fn read(mut bytes: &[u8]) -> Result<Self, SmbError> {
+        if !bytes.starts_with(&MAGIC) {
+            return Err(SmbError::BadMagic);
+        }
+        bytes = &bytes[MAGIC.len()..];
+        let version = take_u32(&mut bytes)?;
+        if version != SMB_VERSION {
+            return Err(SmbError::UnsupportedVersion(version));
+        }
+        let len = <[u8; 8]>::try_from(take(&mut bytes, 8)?)
+            .unwrap_or_else(|_| unreachable!("take returns the requested length"));
+        let len = usize::try_from(u64::from_le_bytes(len)).map_err(|_| SmbError::Truncated)?;
+        if take_u32(&mut bytes)? != BITS {
+            return Err(SmbError::Truncated);
+        }
+        let mut postings = Vec::with_capacity(FINGERPRINT_BITS);
+        for _ in 0..FINGERPRINT_BITS {
+            let count = take_u32(&mut bytes)? as usize;
+            let ids = take(&mut bytes, count.checked_mul(4).ok_or(SmbError::Truncated)?)?
+                .chunks_exact(4)
+                .map(|id| u32::from_le_bytes([id[0], id[1], id[2], id[3]]))
+                .collect::<Vec<_>>();
+            let ascending = ids.windows(2).all(|pair| pair[0] < pair[1]);
+            if !ascending || ids.last().is_some_and(|&id| id as usize >= len) {
+                return Err(SmbError::Truncated);
+            }
+            postings.push(ids);
+        }
+        if !bytes.is_empty() {
+            return Err(SmbError::Truncated);
+        }
+        Ok(Self { postings, len })
+    }
+}
+
+/// Splits `len` bytes off the front of `bytes`.
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], SmbError> {
+    if bytes.len() < len {
+        return Err(SmbError::Truncated);
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+/// Splits a little-endian `u32` off the front of `bytes`.
+fn take_u32(bytes: &mut &[u8]) -> Result<u32, SmbError> {
+    let head = take(bytes, 4)?;
+    Ok(u32::from_le_bytes([head[0], head[1], head[2], head[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{io::SmbWriter, smiles::WildcardSmiles};
+
+    const LIBRARY: [&str; 6] =
+        ["CCO", "Oc1ccccc1", "c1ccccc1C(=O)O", "CCN", "CC(=O)Oc1ccccc1C(=O)O", "C1CCCCC1"];
+
+    fn index() -> SubstructureIndex {
+        let mut index = SubstructureIndex::new();
+        for smiles in LIBRARY {
+            index.push(&smiles.parse::<Smiles>().unwrap().fingerprint());
+        }
+        index
+    }
+
+    #[test]
+    fn candidates_include_every_match() {
+        let index = index();
+        let candidates =
+            |query: &str| index.substructure_candidates(&query.parse::<Smiles>().unwrap());
+
+        assert_eq!(candidates("c1ccccc1"), [1, 2, 4]);
+        assert_eq!(candidates("C(=O)O"), [2, 4]);
+        assert!(candidates("CC").contains(&0));
+        assert!(candidates("CC").contains(&5));
+        assert!(candidates("S").is_empty());
+        assert_eq!(index.candidates(&Fingerprint::default()), [0, 1, 2, 3, 4, 5]);
+
+        let wildcard: WildcardSmiles = "*C(=O)O".parse().unwrap();
+        assert_eq!(index.candidates(&wildcard.fingerprint()), [2, 4]);
+    }
+
+    #[test]
+    fn kekule_targets_match_aromatic_queries() {
+        let mut index = SubstructureIndex::new();
+        for smiles in ["OC1=CC=CC=C1", "C1CCCCC1", "OC1=CCCC=C1"] {
+            index.push(&smiles.parse::<Smiles>().unwrap().fingerprint());
+        }
+        let aromatic: Smiles = "c1ccccc1".parse().unwrap();
+        let kekule: Smiles = "C1=CC=CC=C1".parse().unwrap();
+        assert_eq!(index.candidates(&aromatic.fingerprint()), [0]);
+        assert_eq!(index.substructure_candidates(&kekule), [0]);
+    }
+
+    #[test]
+    fn indices_follow_containers_and_persist() {
+        let mut writer = SmbWriter::new(Vec::new()).unwrap();
+        for smiles in LIBRARY {
+            writer.push(&smiles.parse::<Smiles>().unwrap().freeze()).unwrap();
+        }
+        let container = writer.finish().unwrap();
+        let index =
+            SubstructureIndex::from_container(&SmbReader::new(&container).unwrap()).unwrap();
+        assert_eq!(index, self::index());
+        assert_eq!(index.len(), LIBRARY.len());
+
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+        assert_eq!(SubstructureIndex::read(&bytes).unwrap(), index);
+        assert_eq!(SubstructureIndex::read(&bytes[..bytes.len() - 1]), Err(SmbError::Truncated));
+        assert_eq!(SubstructureIndex::read(&container), Err(SmbError::BadMagic));
+        assert!(SubstructureIndex::new().is_empty());
+    }
+}
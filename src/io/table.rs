@@ -0,0 +1,521 @@
+//! Extraction of SMILES and identifier columns from CSV and TSV tables.
+
+use alloc::{
+    borrow::{Cow, ToOwned},
+    string::String,
+    vec::Vec,
+};
+use core::{marker::PhantomData, str::FromStr};
+use std::io::{self, BufRead};
+
+use thiserror::Error;
+
+use crate::{errors::SmilesErrorWithSpan, smiles::Smiles};
+
+/// Selects a column of a SMILES table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TableColumn {
+    /// Zero-based column index.
+    Index(usize),
+    /// Header name, matched ignoring ASCII case and surrounding whitespace.
+    Name(String),
+}
+
+/// Layout of a table read by [`SmilesTableReader`].
+///
+/// The default reads a tab-separated file with a header row and takes the
+/// SMILES from the column named `smiles`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TableOptions {
+    /// Field separator.
+    pub delimiter: char,
+    /// Whether the first line is a header row.
+    pub has_header: bool,
+    /// Column holding the SMILES strings.
+    pub smiles_column: TableColumn,
+    /// Column holding the record identifiers, if any.
+    pub id_column: Option<TableColumn>,
+    /// Further columns carried along with each record, such as expected
+    /// formulas or reference canonical SMILES for regression suites.
+    pub extra_columns: Vec<TableColumn>,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: '\t',
+            has_header: true,
+            smiles_column: TableColumn::Name("smiles".to_owned()),
+            id_column: None,
+            extra_columns: Vec::new(),
+        }
+    }
+}
+
+impl TableOptions {
+    /// Returns the default options with a comma as delimiter.
+    #[must_use]
+    pub fn csv() -> Self {
+        Self { delimiter: ',', ..Self::default() }
+    }
+
+    /// Returns the layout of the PubChem `CID-SMILES` dump: tab-separated,
+    /// without a header, with the CID before the SMILES.
+    #[must_use]
+    pub fn pubchem() -> Self {
+        Self {
+            has_header: false,
+            smiles_column: TableColumn::Index(1),
+            id_column: Some(TableColumn::Index(0)),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the layout of the ChEMBL `chemreps` dump: tab-separated, with
+    /// the `canonical_smiles` and `chembl_id` columns.
+    #[must_use]
+    pub fn chembl() -> Self {
+        Self {
+            smiles_column: TableColumn::Name("canonical_smiles".to_owned()),
+            id_column: Some(TableColumn::Name("chembl_id".to_owned())),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the layout of the COCONUT CSV export, with the
+    /// `canonical_smiles` and `identifier` columns.
+    #[must_use]
+    pub fn coconut() -> Self {
+        Self {
+            smiles_column: TableColumn::Name("canonical_smiles".to_owned()),
+            id_column: Some(TableColumn::Name("identifier".to_owned())),
+            ..Self::csv()
+        }
+    }
+}
+
+/// Errors raised while reading a SMILES table.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TableError {
+    /// Reading from the underlying source failed.
+    #[error("failed to read SMILES table: {0}")]
+    Io(#[from] io::Error),
+    /// The options expect a header row but the input is empty.
+    #[error("expected a header row")]
+    MissingHeader,
+    /// A column selected by name is not in the header, or the table has no
+    /// header.
+    #[error("no column named `{name}` in the table header")]
+    UnknownColumn {
+        /// The requested column name.
+        name: String,
+    },
+    /// A row is shorter than a selected column.
+    #[error("line {line_number} has no field at column {column}")]
+    MissingField {
+        /// The 1-based line number, counting the header row.
+        line_number: usize,
+        /// The zero-based column index.
+        column: usize,
+    },
+}
+
+/// One row of a SMILES table with its parse result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmilesTableRecord<T = Smiles> {
+    line_number: usize,
+    id: Option<String>,
+    smiles: String,
+    extra_fields: Vec<String>,
+    parsed: Result<T, SmilesErrorWithSpan>,
+}
+
+impl<T> SmilesTableRecord<T> {
+    /// Returns the 1-based line number of the row, counting the header row.
+    #[inline]
+    #[must_use]
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Returns the record identifier, when an identifier column is selected.
+    #[inline]
+    #[must_use]
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the SMILES string as read from the table.
+    #[inline]
+    #[must_use]
+    pub fn smiles(&self) -> &str {
+        &self.smiles
+    }
+
+    /// Returns the values of [`TableOptions::extra_columns`], in the same
+    /// order.
+    #[inline]
+    #[must_use]
+    pub fn extra_fields(&self) -> &[String] {
+        &self.extra_fields
+    }
+
+    /// Returns the parse result of the SMILES string.
+    ///
+    /// # Errors
+    /// Returns the parse error of the row.
+    #[inline]
+    pub fn parsed(&self) -> Result<&T, &SmilesErrorWithSpan> {
+        self.parsed.as_ref()
+    }
+
+    /// Consumes the record and returns the parse result.
+    ///
+    /// # Errors
+    /// Returns the parse error of the row.
+    #[inline]
+    pub fn into_parsed(self) -> Result<T, SmilesErrorWithSpan> {
+        self.parsed
+    }
+}
+
+/// Streams the rows of a CSV or TSV table, parsing the SMILES column of each.
+///
+/// Fields may be enclosed in double quotes, with `""` standing for a literal
+/// quote; quoted fields cannot span lines. Blank lines are skipped. Rows whose
+/// SMILES fails to parse are still yielded, with the error available from
+/// [`SmilesTableRecord::parsed`], so that one bad row does not stop a bulk run.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::io::{SmilesTableReader, TableColumn, TableOptions};
+///
+/// let table = "id,name,smiles\n1,ethanol,CCO\n2,broken,C1CC\n";
+/// let options = TableOptions { id_column: Some(TableColumn::Index(0)), ..TableOptions::csv() };
+/// let reader: SmilesTableReader<_> = SmilesTableReader::new(table.as_bytes(), &options)?;
+///
+/// let records = reader.collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(records[0].id(), Some("1"));
+/// assert_eq!(records[0].parsed().unwrap().nodes().len(), 3);
+/// assert_eq!(records[1].line_number(), 3);
+/// assert!(records[1].parsed().is_err());
+/// # Ok::<(), smiles_parser::io::TableError>(())
+/// ```
+pub struct SmilesTableReader<R, T = Smiles> {
+    reader: R,
+    delimiter: char,
+    header: Option<Vec<String>>,
+    smiles_column: usize,
+    id_column: Option<usize>,
+    extra_columns: Vec<usize>,
+    line_number: usize,
+    line_buffer: String,
+    parsed: PhantomData<fn() -> T>,
+}
+
+impl<R: BufRead, T> SmilesTableReader<R, T> {
+    /// Creates a reader over `reader`, consuming the header row when
+    /// `options` declares one.
+    ///
+    /// # Errors
+    /// Returns [`TableError::MissingHeader`] for empty input when a header is
+    /// expected, [`TableError::UnknownColumn`] when a named column cannot be
+    /// resolved, and [`TableError::Io`] when reading the header fails.
+    pub fn new(mut reader: R, options: &TableOptions) -> Result<Self, TableError> {
+        let mut line_buffer = String::new();
+        let mut line_number = 0;
+        let header = if options.has_header {
+            if reader.read_line(&mut line_buffer)? == 0 {
+                return Err(TableError::MissingHeader);
+            }
+            line_number = 1;
+            Some(
+                split_fields(line_buffer.trim_end_matches(['\r', '\n']), options.delimiter)
+                    .into_iter()
+                    .map(|field| field.trim().to_owned())
+                    .collect::<Vec<_>>(),
+            )
+        } else {
+            None
+        };
+        let smiles_column = resolve_column(&options.smiles_column, header.as_deref())?;
+        let id_column = options
+            .id_column
+            .as_ref()
+            .map(|column| resolve_column(column, header.as_deref()))
+            .transpose()?;
+        let extra_columns = options
+            .extra_columns
+            .iter()
+            .map(|column| resolve_column(column, header.as_deref()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            reader,
+            delimiter: options.delimiter,
+            header,
+            smiles_column,
+            id_column,
+            extra_columns,
+            line_number,
+            line_buffer,
+            parsed: PhantomData,
+        })
+    }
+
+    /// Returns the header row, when the table has one.
+    #[inline]
+    #[must_use]
+    pub fn header(&self) -> Option<&[String]> {
+        self.header.as_deref()
+    }
+}
+
+#[cfg(feature = "io-compress")]
+impl<T> SmilesTableReader<alloc::boxed::Box<dyn BufRead + Send>, T> {
+    /// Opens the table at `path` with
+    /// [`open_text_reader`](super::open_text_reader), so gzip-compressed
+    /// tables are decoded transparently.
+    ///
+    /// # Errors
+    /// Returns [`TableError::Io`] when the file cannot be opened, and the
+    /// errors of [`SmilesTableReader::new`] otherwise.
+    pub fn open(
+        path: impl AsRef<std::path::Path>,
+        options: &TableOptions,
+    ) -> Result<Self, TableError> {
+        Self::new(super::open_text_reader(path)?, options)
+    }
+}
+
+impl<R, T> Iterator for SmilesTableReader<R, T>
+where
+    R: BufRead,
+    T: FromStr<Err = SmilesErrorWithSpan>,
+{
+    type Item = Result<SmilesTableRecord<T>, TableError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line_buffer.clear();
+            match self.reader.read_line(&mut self.line_buffer) {
+                Ok(0) => return None,
+                Ok(_) => self.line_number += 1,
+                Err(error) => return Some(Err(error.into())),
+            }
+
+            let line = self.line_buffer.trim_end_matches(['\r', '\n']);
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields = split_fields(line, self.delimiter);
+            let field = |column: usize| {
+                fields
+                    .get(column)
+                    .map(|field| field.trim().to_owned())
+                    .ok_or(TableError::MissingField { line_number: self.line_number, column })
+            };
+            let record = field(self.smiles_column).and_then(|smiles| {
+                let id = self.id_column.map(field).transpose()?;
+                let extra_fields = self
+                    .extra_columns
+                    .iter()
+                    .map(|&column| field(column))
+                    .collect::<Result<_, _>>()?;
+                Ok(SmilesTableRecord {
+                    line_number: self.line_number,
+                    id,
+                    extra_fields,
+                    parsed: smiles.parse(),
+                    smiles,
+                })
+            });
+            return Some(record);
+        }
+    }
+}
+
+fn resolve_column(column: &TableColumn, header: Option<&[String]>) -> Result<usize, TableError> {
+    match column {
+        TableColumn::Index(index) => Ok(*index),
+        TableColumn::Name(name) => {
+            header
+                .and_then(|header| {
+                    header.iter().position(|field| field.eq_ignore_ascii_case(name.trim()))
+                })
+                .ok_or_else(|| TableError::UnknownColumn { name: name.clone() })
+        }
+    }
+}
+
+/// Splits a table row into fields, unquoting double-quoted fields.
+fn split_fields(line: &str, delimiter: char) -> Vec<Cow<'_, str>> {
+    let mut fields = Vec::new();
+    let mut rest = line;
+    loop {
+        let remaining = if let Some(quoted) = rest.strip_prefix('"') {
+            let mut value = String::new();
+            let mut remaining = quoted;
+            let mut closed = false;
+            while let Some(end) = remaining.find('"') {
+                value.push_str(&remaining[..end]);
+                remaining = &remaining[end + 1..];
+                if let Some(after_escape) = remaining.strip_prefix('"') {
+                    value.push('"');
+                    remaining = after_escape;
+                } else {
+                    closed = true;
+                    break;
+                }
+            }
+            if !closed {
+                value.push_str(remaining);
+                remaining = "";
+            }
+            fields.push(Cow::Owned(value));
+            remaining
+        } else {
+            let end = rest.find(delimiter).unwrap_or(rest.len());
+            fields.push(Cow::Borrowed(&rest[..end]));
+            &rest[end..]
+        };
+        match remaining.find(delimiter) {
+            Some(index) => rest = &remaining[index + delimiter.len_utf8()..],
+            None => return fields,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{errors::SmilesError, smiles::WildcardSmiles};
+
+    fn read(table: &str, options: &TableOptions) -> Vec<SmilesTableRecord> {
+        SmilesTableReader::new(table.as_bytes(), options)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn named_columns_are_resolved_from_the_header() {
+        let options = TableOptions {
+            id_column: Some(TableColumn::Name("CID".to_owned())),
+            ..TableOptions::default()
+        };
+        let records = read("cid\tSMILES\n702\tCCO\n\n241\tc1ccccc1\r\n", &options);
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id(), Some("702"));
+        assert_eq!(records[0].smiles(), "CCO");
+        assert_eq!(records[1].line_number(), 4);
+        assert_eq!(records[1].parsed().unwrap().nodes().len(), 6);
+    }
+
+    #[test]
+    fn headerless_tables_use_column_indices() {
+        let options = TableOptions {
+            delimiter: ',',
+            has_header: false,
+            smiles_column: TableColumn::Index(1),
+            id_column: Some(TableColumn::Index(0)),
+            extra_columns: Vec::new(),
+        };
+        let records = read("a, CCO\nb,C(\n", &options);
+
+        assert_eq!(records[0].line_number(), 1);
+        assert_eq!(records[0].smiles(), "CCO");
+        assert_eq!(records[1].id(), Some("b"));
+        assert_eq!(records[1].parsed().unwrap_err().smiles_error(), SmilesError::UnclosedBranch);
+        assert_eq!(records[1].clone(), records[1]);
+        assert_ne!(records[0], records[1]);
+    }
+
+    #[test]
+    fn quoted_fields_are_unescaped() {
+        assert_eq!(
+            split_fields(r#"1,"CC(=O)O","say ""hi""",x"#, ','),
+            ["1", "CC(=O)O", "say \"hi\"", "x"]
+        );
+        assert_eq!(split_fields(r#""a,b""#, ','), ["a,b"]);
+        assert_eq!(split_fields(",", ','), ["", ""]);
+    }
+
+    #[test]
+    fn reader_is_generic_over_the_parsed_type() {
+        let reader: SmilesTableReader<_, WildcardSmiles> =
+            SmilesTableReader::new("smiles\n*CC\n".as_bytes(), &TableOptions::default()).unwrap();
+
+        let record = reader.map(Result::unwrap).next().unwrap();
+        assert_eq!(record.into_parsed().unwrap().to_string(), "CC*");
+    }
+
+    #[test]
+    fn missing_columns_and_fields_are_reported() {
+        assert!(matches!(
+            SmilesTableReader::<_, Smiles>::new("".as_bytes(), &TableOptions::default()),
+            Err(TableError::MissingHeader)
+        ));
+        assert!(matches!(
+            SmilesTableReader::<_, Smiles>::new("id\tname\n".as_bytes(), &TableOptions::default()),
+            Err(TableError::UnknownColumn { name }) if name == "smiles"
+        ));
+
+        let options =
+            TableOptions { id_column: Some(TableColumn::Index(2)), ..TableOptions::default() };
+        let mut reader: SmilesTableReader<_> =
+            SmilesTableReader::new("smiles\tid\nCCO\n".as_bytes(), &options).unwrap();
+        assert!(matches!(
+            reader.next(),
+            Some(Err(TableError::MissingField { line_number: 2, column: 2 }))
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn extra_columns_are_carried_with_each_record() {
+        let options = TableOptions {
+            extra_columns: vec![TableColumn::Name("formula".to_owned()), TableColumn::Index(0)],
+            ..TableOptions::csv()
+        };
+        let records = read("name,smiles,formula\nethanol,CCO,C2H6O\n", &options);
+        assert_eq!(records[0].extra_fields(), ["C2H6O", "ethanol"]);
+
+        assert!(matches!(
+            SmilesTableReader::<_, Smiles>::new(
+                "smiles\n".as_bytes(),
+                &TableOptions {
+                    extra_columns: vec![TableColumn::Name("mass".to_owned())],
+                    ..TableOptions::default()
+                },
+            ),
+            Err(TableError::UnknownColumn { name }) if name == "mass"
+        ));
+    }
+
+    #[test]
+    fn corpus_presets_match_their_dumps() {
+        let pubchem = read("702\tCCO\n241\tc1ccccc1\n", &TableOptions::pubchem());
+        assert_eq!(pubchem[1].id(), Some("241"));
+        assert_eq!(pubchem[1].line_number(), 2);
+
+        let chembl = read(
+            "chembl_id\tcanonical_smiles\tstandard_inchi\tstandard_inchi_key\n\
+             CHEMBL545\tCCO\tInChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3\tLFQSCWFLJHTTHZ-UHFFFAOYSA-N\n",
+            &TableOptions::chembl(),
+        );
+        assert_eq!(chembl[0].id(), Some("CHEMBL545"));
+        assert_eq!(chembl[0].smiles(), "CCO");
+
+        let coconut = read(
+            "identifier,canonical_smiles,name\nCNP0000001.0,OC1=CC=CC=C1,phenol\n",
+            &TableOptions::coconut(),
+        );
+        assert_eq!(coconut[0].id(), Some("CNP0000001.0"));
+        assert_eq!(coconut[0].parsed().unwrap().nodes().len(), 7);
+    }
+}
@@ -0,0 +1,329 @@
+//! Resumable validation of large SMILES files.
+
+use alloc::{collections::BTreeMap, string::String};
+use core::{fmt, marker::PhantomData, str::FromStr};
+use std::io::{self, BufRead, Read};
+
+use crate::{errors::SmilesErrorWithSpan, smiles::Smiles};
+
+/// Settings of a [`BulkValidator`] run.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValidationOptions {
+    /// Zero-based index of the SMILES among the whitespace-separated fields of
+    /// a line: `0` for `.smi` files, `1` for PubChem `CID-SMILES`.
+    pub smiles_field: usize,
+    /// Whether the first line of the input is a header to skip.
+    pub has_header: bool,
+    /// Number of records between two progress callbacks; `0` only reports
+    /// at the end of the run.
+    pub progress_interval: usize,
+    /// Maximum number of records to validate in one run.
+    pub limit: Option<usize>,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        Self { smiles_field: 0, has_header: false, progress_interval: 100_000, limit: None }
+    }
+}
+
+/// Summary of a bulk validation, doubling as its checkpoint.
+///
+/// [`ValidationReport::offset`] is the byte offset, in the decompressed
+/// input, just past the last line processed. Passing the report back to
+/// [`BulkValidator::resume`] continues from there with the counts carried
+/// over.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    offset: u64,
+    lines: usize,
+    records: usize,
+    valid: usize,
+    errors: BTreeMap<&'static str, usize>,
+}
+
+impl ValidationReport {
+    /// Returns an empty report that resumes at byte `offset`, for checkpoints
+    /// where only the offset was persisted.
+    #[inline]
+    #[must_use]
+    pub fn at_offset(offset: u64) -> Self {
+        Self { offset, ..Self::default() }
+    }
+
+    /// Returns the byte offset just past the last processed line.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the number of lines read, including blank lines and the
+    /// header.
+    #[inline]
+    #[must_use]
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// Returns the number of SMILES validated.
+    #[inline]
+    #[must_use]
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Returns the number of SMILES that parsed.
+    #[inline]
+    #[must_use]
+    pub fn valid(&self) -> usize {
+        self.valid
+    }
+
+    /// Returns the number of SMILES that failed to parse.
+    #[inline]
+    #[must_use]
+    pub fn invalid(&self) -> usize {
+        self.records - self.valid
+    }
+
+    /// Returns the number of failures per
+    /// [`SmilesError::category`](crate::SmilesError::category).
+    #[inline]
+    #[must_use]
+    pub fn error_counts(&self) -> &BTreeMap<&'static str, usize> {
+        &self.errors
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} records, {} valid, {} invalid (offset {})",
+            self.records,
+            self.valid,
+            self.invalid(),
+            self.offset
+        )?;
+        for (category, count) in &self.errors {
+            write!(f, "\n  {category}: {count}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses every SMILES of a line-oriented file, counting failures by
+/// category and reporting progress through a callback.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::{
+///     Smiles,
+///     io::{BulkValidator, ValidationOptions},
+/// };
+///
+/// let input = "CCO ethanol\nC1CC broken\nc1ccccc1 benzene\n";
+/// let options = ValidationOptions { limit: Some(2), ..ValidationOptions::default() };
+///
+/// let mut validator = BulkValidator::<Smiles>::new(options.clone());
+/// let checkpoint = validator.run(input.as_bytes(), |_| {})?.clone();
+/// assert_eq!(checkpoint.records(), 2);
+/// assert_eq!(checkpoint.error_counts()["UnclosedRing"], 1);
+///
+/// let mut validator = BulkValidator::<Smiles>::resume(options, checkpoint);
+/// let report = validator.run(input.as_bytes(), |_| {})?;
+/// assert_eq!(report.records(), 3);
+/// assert_eq!(report.valid(), 2);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct BulkValidator<T = Smiles> {
+    options: ValidationOptions,
+    report: ValidationReport,
+    parsed: PhantomData<fn() -> T>,
+}
+
+impl<T> BulkValidator<T> {
+    /// Creates a validator starting at the beginning of the input.
+    #[inline]
+    #[must_use]
+    pub fn new(options: ValidationOptions) -> Self {
+        Self::resume(options, ValidationReport::default())
+    }
+
+    /// Creates a validator continuing from `checkpoint`.
+    #[inline]
+    #[must_use]
+    pub fn resume(options: ValidationOptions, checkpoint: ValidationReport) -> Self {
+        Self { options, report: checkpoint, parsed: PhantomData }
+    }
+
+    /// Returns the report accumulated so far.
+    #[inline]
+    #[must_use]
+    pub fn report(&self) -> &ValidationReport {
+        &self.report
+    }
+}
+
+impl<T: FromStr<Err = SmilesErrorWithSpan>> BulkValidator<T> {
+    /// Validates `reader` from the start of the input, skipping the bytes
+    /// already covered by the checkpoint, until the end of the input or the
+    /// record limit.
+    ///
+    /// `progress` is called every
+    /// [`progress_interval`](ValidationOptions::progress_interval) records
+    /// and once more when the run stops.
+    ///
+    /// # Errors
+    /// Returns the I/O error raised while reading, or an
+    /// [`io::ErrorKind::UnexpectedEof`] error when the input is shorter than
+    /// the checkpoint offset. The report keeps the progress made before the
+    /// error, so the run can be resumed.
+    pub fn run<R, F>(&mut self, mut reader: R, mut progress: F) -> io::Result<&ValidationReport>
+    where
+        R: BufRead,
+        F: FnMut(&ValidationReport),
+    {
+        let skipped = io::copy(&mut reader.by_ref().take(self.report.offset), &mut io::sink())?;
+        if skipped < self.report.offset {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the checkpoint offset is past the end of the input",
+            ));
+        }
+
+        let mut line = String::new();
+        let mut validated = 0;
+        while self.options.limit.is_none_or(|limit| validated < limit) {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 {
+                break;
+            }
+            let is_header = self.options.has_header && self.report.offset == 0;
+            self.report.offset += u64::try_from(read).unwrap_or_else(|_| {
+                unreachable!("usize always fits into u64 on supported targets")
+            });
+            self.report.lines += 1;
+            if is_header {
+                continue;
+            }
+            let Some(smiles) = line.split_ascii_whitespace().nth(self.options.smiles_field) else {
+                continue;
+            };
+
+            validated += 1;
+            self.report.records += 1;
+            match smiles.parse::<T>() {
+                Ok(_) => self.report.valid += 1,
+                Err(error) => {
+                    *self.report.errors.entry(error.smiles_error().category()).or_default() += 1;
+                }
+            }
+            if self.options.progress_interval > 0
+                && self.report.records % self.options.progress_interval == 0
+            {
+                progress(&self.report);
+            }
+        }
+
+        progress(&self.report);
+        Ok(&self.report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::smiles::WildcardSmiles;
+
+    const INPUT: &str = "cid\tsmiles\n1\tCCO\n2\tCC(C\n\n3\t*C\n4\tC1CC\n5\tN\n";
+
+    fn options() -> ValidationOptions {
+        ValidationOptions { smiles_field: 1, has_header: true, ..ValidationOptions::default() }
+    }
+
+    #[test]
+    fn counts_records_and_errors_by_category() {
+        let mut validator = BulkValidator::<Smiles>::new(options());
+        let report = validator.run(INPUT.as_bytes(), |_| {}).unwrap();
+
+        assert_eq!(report.lines(), 7);
+        assert_eq!(report.records(), 5);
+        assert_eq!(report.valid(), 2);
+        assert_eq!(report.invalid(), 3);
+        assert_eq!(report.offset(), u64::try_from(INPUT.len()).unwrap());
+        assert_eq!(report.error_counts()["UnclosedBranch"], 1);
+        assert_eq!(report.error_counts()["UnclosedRing"], 1);
+        assert_eq!(report.error_counts()["WildcardAtomNotAllowed"], 1);
+    }
+
+    #[test]
+    fn wildcard_validation_accepts_wildcards() {
+        let mut validator = BulkValidator::<WildcardSmiles>::new(options());
+        let report = validator.run(INPUT.as_bytes(), |_| {}).unwrap();
+
+        assert_eq!(report.valid(), 3);
+        assert!(!report.error_counts().contains_key("WildcardAtomNotAllowed"));
+    }
+
+    #[test]
+    fn resuming_in_chunks_matches_a_single_run() {
+        let single =
+            BulkValidator::<Smiles>::new(options()).run(INPUT.as_bytes(), |_| {}).unwrap().clone();
+
+        let chunked = ValidationOptions { limit: Some(2), ..options() };
+        let mut checkpoint = ValidationReport::default();
+        let mut runs = 0;
+        loop {
+            let mut validator =
+                BulkValidator::<Smiles>::resume(chunked.clone(), checkpoint.clone());
+            let report = validator.run(INPUT.as_bytes(), |_| {}).unwrap().clone();
+            runs += 1;
+            if report == checkpoint {
+                break;
+            }
+            checkpoint = report;
+        }
+
+        assert_eq!(checkpoint, single);
+        assert_eq!(runs, 4);
+    }
+
+    #[test]
+    fn progress_is_reported_at_each_interval_and_at_the_end() {
+        let mut seen = Vec::new();
+        let options = ValidationOptions { progress_interval: 2, ..options() };
+        BulkValidator::<Smiles>::new(options)
+            .run(INPUT.as_bytes(), |report| seen.push(report.records()))
+            .unwrap();
+
+        assert_eq!(seen, [2, 4, 5]);
+    }
+
+    #[test]
+    fn offsets_past_the_end_are_rejected() {
+        let mut validator =
+            BulkValidator::<Smiles>::resume(options(), ValidationReport::at_offset(1_000));
+        let error = validator.run(INPUT.as_bytes(), |_| {}).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn report_displays_a_summary() {
+        let mut validator = BulkValidator::<Smiles>::new(ValidationOptions::default());
+        let report = validator.run("CCO\nCC(C\n".as_bytes(), |_| {}).unwrap();
+
+        assert_eq!(
+            report.to_string(),
+            "2 records, 1 valid, 1 invalid (offset 9)\n  UnclosedBranch: 1"
+        );
+    }
+}
@@ -7,16 +7,40 @@ extern crate alloc;
 #[cfg(test)]
 #[macro_use]
 extern crate std;
-#[cfg(all(feature = "datasets", not(test)))]
+#[cfg(all(any(feature = "datasets", feature = "io", feature = "stats"), not(test)))]
 extern crate std;
 
+/// Enters a `tracing` span at the given level for the rest of the enclosing
+/// block when the `tracing` feature is enabled, and expands to nothing
+/// otherwise, so the field expressions cost nothing in default builds.
+macro_rules! stage_span {
+    ($level:ident, $name:literal $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::span!(tracing::Level::$level, $name $(, $($fields)*)?).entered();
+    };
+}
+
 pub mod atom;
 pub mod bond;
+pub mod cluster;
+pub mod complexity;
+pub mod conformance;
+pub mod cst;
 #[cfg(feature = "datasets")]
 pub mod datasets;
+pub mod dedup;
 pub mod errors;
+#[cfg(feature = "io")]
+pub mod io;
+pub mod library;
+pub mod lint;
+pub mod mass;
 pub(crate) mod parser;
+pub mod sanitize;
+pub mod shrink;
 pub mod smiles;
+#[cfg(feature = "stats")]
+pub mod stats;
 pub mod token;
 
 #[cfg(feature = "datasets")]
@@ -29,17 +53,25 @@ pub use crate::datasets::{
     default_dataset_cache_dir,
 };
 pub use crate::{
-    errors::{RootError, SmilesError, SmilesErrorWithSpan, SubgraphError},
+    errors::{
+        RenderOptions, RenumberError, RootError, SmilesError, SmilesErrorWithSource,
+        SmilesErrorWithSpan, SubgraphError, SuggestedFix,
+    },
     smiles::{
         AromaticityAssignment, AromaticityAssignmentApplicationError, AromaticityDiagnostic,
         AromaticityModel, AromaticityPerception, AromaticityPolicy, AromaticityRingFamilyKind,
-        AromaticityStatus, AtomEnvironment, DoubleBondStereoConfig, Fragment, GraphSimilarities,
-        InitialProductVertexOrdering, KekulizationError, KekulizationMode, LargestFragmentMetric,
-        McesBuilder, McesResult, McesSearchMode, RdkitDefaultAromaticity, RdkitMdlAromaticity,
-        RdkitSimpleAromaticity, RingAtomMembership, RingAtomMembershipScratch, RingMembership,
-        Smiles, SmilesComponents, SmilesMces, SymmSssrResult, SymmSssrStatus,
-        WildcardAromaticityPerception, WildcardMolecularFormulaConversionError, WildcardSmiles,
-        WildcardSmilesComponents,
+        AromaticityStatus, AtomAttribute, AtomEnvironment, AtomPerception, BondAttribute,
+        CanonicalAlgorithmVersion, CompactSmiles, CompactSmilesRef, Conformer,
+        DoubleBondStereoConfig, ElementProfile, FINGERPRINT_BITS, Fingerprint, Fragment,
+        GraphSimilarities, HighlightStyle, Hybridization, InitialProductVertexOrdering,
+        KekulizationError, KekulizationMode, LargestFragmentMetric, McesBuilder, McesResult,
+        McesSearchMode, MemoryFootprint, ParserOptions, ParserScratch, PhModel,
+        RdkitDefaultAromaticity, RdkitMdlAromaticity, RdkitSimpleAromaticity, RingAtomMembership,
+        RingAtomMembershipScratch, RingMembership, SharedSmiles, Smiles, SmilesComponents,
+        SmilesDiff, SmilesMces, SymmSssrResult, SymmSssrStatus, Transaction, TransactionError,
+        ValenceError, ValenceModel, WildcardAromaticityPerception,
+        WildcardMolecularFormulaConversionError, WildcardSmiles, WildcardSmilesComponents,
+        WriterOptions,
     },
 };
 
@@ -48,13 +80,19 @@ pub mod prelude {
     pub use crate::{
         AromaticityAssignment, AromaticityAssignmentApplicationError, AromaticityDiagnostic,
         AromaticityModel, AromaticityPerception, AromaticityPolicy, AromaticityRingFamilyKind,
-        AromaticityStatus, AtomEnvironment, DoubleBondStereoConfig, Fragment, GraphSimilarities,
-        InitialProductVertexOrdering, KekulizationError, KekulizationMode, LargestFragmentMetric,
-        McesBuilder, McesResult, McesSearchMode, RdkitDefaultAromaticity, RdkitMdlAromaticity,
-        RdkitSimpleAromaticity, RingAtomMembership, RingAtomMembershipScratch, RingMembership,
-        RootError, Smiles, SmilesComponents, SmilesError, SmilesErrorWithSpan, SmilesMces,
-        SubgraphError, SymmSssrResult, SymmSssrStatus, WildcardAromaticityPerception,
-        WildcardMolecularFormulaConversionError, WildcardSmiles, WildcardSmilesComponents,
+        AromaticityStatus, AtomAttribute, AtomEnvironment, AtomPerception, BondAttribute,
+        CanonicalAlgorithmVersion, CompactSmiles, CompactSmilesRef, Conformer,
+        DoubleBondStereoConfig, ElementProfile, FINGERPRINT_BITS, Fingerprint, Fragment,
+        GraphSimilarities, HighlightStyle, Hybridization, InitialProductVertexOrdering,
+        KekulizationError, KekulizationMode, LargestFragmentMetric, McesBuilder, McesResult,
+        McesSearchMode, MemoryFootprint, ParserOptions, ParserScratch, PhModel,
+        RdkitDefaultAromaticity, RdkitMdlAromaticity, RdkitSimpleAromaticity, RenderOptions,
+        RenumberError, RingAtomMembership, RingAtomMembershipScratch, RingMembership, RootError,
+        SharedSmiles, Smiles, SmilesComponents, SmilesDiff, SmilesError, SmilesErrorWithSource,
+        SmilesErrorWithSpan, SmilesMces, SubgraphError, SuggestedFix, SymmSssrResult,
+        SymmSssrStatus, Transaction, TransactionError, ValenceError, ValenceModel,
+        WildcardAromaticityPerception, WildcardMolecularFormulaConversionError, WildcardSmiles,
+        WildcardSmilesComponents, WriterOptions,
     };
     #[cfg(feature = "datasets")]
     pub use crate::{
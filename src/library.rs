@@ -0,0 +1,279 @@
+//! Fingerprint similarity search over in-memory molecule collections.
+//!
+//! A [`Library`] keeps every graph next to its [`Fingerprint`], so a query is
+//! screened by Tanimoto similarity without touching the graphs. Screened hits
+//! can optionally be verified by a maximum common edge subgraph search, which
+//! replaces the fingerprint estimate with the Johnson similarity of the two
+//! graphs; the RASCAL bound of [`SmilesMces::similarity_threshold`] skips the
+//! search for pairs that cannot reach the threshold. With the `rayon` feature
+//! the library is scanned on all threads.
+//!
+//! # Examples
+//!
+//! ```
+//! use smiles_parser::{Smiles, library::Library};
+//!
+//! let library = ["Oc1ccccc1", "Cc1ccccc1", "CCO", "Oc1ccccc1O"]
+//!     .into_iter()
+//!     .map(str::parse::<Smiles>)
+//!     .collect::<Result<Library, _>>()?;
+//!
+//! let query: Smiles = "Oc1ccccc1".parse()?;
+//! let hits = library.similar_to(&query, 0.5);
+//! assert_eq!(hits[0].id(), 0);
+//! assert!((hits[0].similarity() - 1.0).abs() < f64::EPSILON);
+//! assert!(hits.iter().all(|hit| hit.id() != 2));
+//! # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+//! ```
+//!
+//! [`SmilesMces::similarity_threshold`]: crate::smiles::SmilesMces::similarity_threshold
+
+use alloc::vec::Vec;
+
+use crate::smiles::{Fingerprint, GraphSimilarities, Smiles};
+
+/// Settings of a [`Library::similar_to_with`] search.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SimilarityOptions {
+    /// Smallest similarity a hit must reach, between `0.0` and `1.0`.
+    pub threshold: f64,
+    /// Whether hits passing the fingerprint screen are verified by a maximum
+    /// common edge subgraph search, keeping only those whose Johnson
+    /// similarity also reaches the threshold.
+    pub verify: bool,
+    /// Branch-and-bound node budget of each verification search, or `None`
+    /// for an unbounded search. A capped search may under-estimate the
+    /// similarity and drop a hit.
+    pub search_budget: Option<usize>,
+}
+
+impl Default for SimilarityOptions {
+    fn default() -> Self {
+        Self { threshold: 0.7, verify: false, search_budget: None }
+    }
+}
+
+/// A library molecule similar to the query.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SimilarityHit {
+    id: usize,
+    tanimoto: f64,
+    mces_similarity: Option<f64>,
+}
+
+impl SimilarityHit {
+    /// Returns the id of the molecule in the library.
+    #[inline]
+    #[must_use]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the Tanimoto similarity of the fingerprints.
+    #[inline]
+    #[must_use]
+    pub fn tanimoto(&self) -> f64 {
+        self.tanimoto
+    }
+
+    /// Returns the Johnson similarity of the maximum common edge subgraph, or
+    /// `None` when the search was not verified.
+    #[inline]
+    #[must_use]
+    pub fn mces_similarity(&self) -> Option<f64> {
+        self.mces_similarity
+    }
+
+    /// Returns the verified similarity when available and the Tanimoto
+    /// similarity otherwise.
+    #[inline]
+    #[must_use]
+    pub fn similarity(&self) -> f64 {
+        self.mces_similarity.unwrap_or(self.tanimoto)
+    }
+}
+
+/// Molecules with precomputed fingerprints, searchable by similarity.
+///
+/// Molecule ids are the insertion order.
+#[derive(Debug, Clone, Default)]
+pub struct Library {
+    molecules: Vec<Smiles>,
+    fingerprints: Vec<Fingerprint>,
+}
+
+impl FromIterator<Smiles> for Library {
+    fn from_iter<I: IntoIterator<Item = Smiles>>(iter: I) -> Self {
+        let mut library = Self::new();
+        for smiles in iter {
+            library.push(smiles);
+        }
+        library
+    }
+}
+
+impl Library {
+    /// Creates an empty library.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a molecule and returns its id.
+    pub fn push(&mut self, smiles: Smiles) -> usize {
+        self.fingerprints.push(smiles.fingerprint());
+        self.molecules.push(smiles);
+        self.molecules.len() - 1
+    }
+
+    /// Returns the number of molecules.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.molecules.len()
+    }
+
+    /// Returns whether the library holds no molecule.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.molecules.is_empty()
+    }
+
+    /// Returns the molecule with the given id.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, id: usize) -> Option<&Smiles> {
+        self.molecules.get(id)
+    }
+
+    /// Returns the fingerprint of the molecule with the given id.
+    #[inline]
+    #[must_use]
+    pub fn fingerprint(&self, id: usize) -> Option<&Fingerprint> {
+        self.fingerprints.get(id)
+    }
+
+    /// Returns the molecules whose fingerprint Tanimoto similarity to `query`
+    /// is at least `threshold`, most similar first.
+    #[must_use]
+    pub fn similar_to(&self, query: &Smiles, threshold: f64) -> Vec<SimilarityHit> {
+        self.similar_to_with(
+            query,
+            &SimilarityOptions { threshold, ..SimilarityOptions::default() },
+        )
+    }
+
+    /// Returns the molecules similar to `query` under `options`, ordered by
+    /// decreasing [`SimilarityHit::similarity`] and then by id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{
+    ///     Smiles,
+    ///     library::{Library, SimilarityOptions},
+    /// };
+    ///
+    /// let library: Library = ["CCCO", "CCCN"].into_iter().map(|s| s.parse().unwrap()).collect();
+    /// let query: Smiles = "CCCO".parse()?;
+    /// let options =
+    ///     SimilarityOptions { threshold: 0.3, verify: true, ..SimilarityOptions::default() };
+    ///
+    /// let hits = library.similar_to_with(&query, &options);
+    /// assert_eq!(hits[0].mces_similarity(), Some(1.0));
+    /// assert!(hits.iter().all(|hit| hit.mces_similarity().is_some()));
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn similar_to_with(
+        &self,
+        query: &Smiles,
+        options: &SimilarityOptions,
+    ) -> Vec<SimilarityHit> {
+        let fingerprint = query.fingerprint();
+        let score = |id: usize| self.score(id, query, &fingerprint, options);
+
+        #[cfg(feature = "rayon")]
+        let mut hits: Vec<SimilarityHit> = {
+            use rayon::prelude::*;
+            (0..self.len()).into_par_iter().filter_map(score).collect()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let mut hits: Vec<SimilarityHit> = (0..self.len()).filter_map(score).collect();
+
+        hits.sort_by(|a, b| b.similarity().total_cmp(&a.similarity()).then(a.id.cmp(&b.id)));
+        hits
+    }
+
+    /// Screens and optionally verifies one molecule.
+    fn score(
+        &self,
+        id: usize,
+        query: &Smiles,
+        fingerprint: &Fingerprint,
+        options: &SimilarityOptions,
+    ) -> Option<SimilarityHit> {
+        let tanimoto = fingerprint.tanimoto(&self.fingerprints[id]);
+        if tanimoto < options.threshold {
+            return None;
+        }
+        let mces_similarity = if options.verify {
+            let mut search =
+                query.mces_with(&self.molecules[id]).similarity_threshold(options.threshold);
+            if let Some(budget) = options.search_budget {
+                search = search.search_budget(budget);
+            }
+            let similarity = search.compute().johnson_similarity();
+            if similarity < options.threshold {
+                return None;
+            }
+            Some(similarity)
+        } else {
+            None
+        };
+        Some(SimilarityHit { id, tanimoto, mces_similarity })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(inputs: &[&str]) -> Library {
+        inputs.iter().map(|input| input.parse::<Smiles>().unwrap()).collect()
+    }
+
+    #[test]
+    fn hits_are_thresholded_and_ranked() {
+        let library = library(&["CCO", "Oc1ccccc1", "Cc1ccccc1", "Oc1ccccc1", "CCCCCC"]);
+        let query: Smiles = "Oc1ccccc1".parse().unwrap();
+
+        let hits = library.similar_to(&query, 0.4);
+        let ids = hits.iter().map(SimilarityHit::id).collect::<Vec<_>>();
+        assert_eq!(ids[..2], [1, 3]);
+        assert!(ids.contains(&2));
+        assert!(!ids.contains(&4));
+        assert!(hits.windows(2).all(|pair| pair[0].similarity() >= pair[1].similarity()));
+        assert!(hits.iter().all(|hit| hit.mces_similarity().is_none()));
+
+        assert_eq!(library.similar_to(&query, 0.0).len(), library.len());
+        assert!(Library::new().similar_to(&query, 0.0).is_empty());
+    }
+
+    #[test]
+    fn verification_replaces_the_fingerprint_estimate() {
+        let library = library(&["c1ccccc1O", "c1ccccc1N", "C1CCCCC1"]);
+        let query: Smiles = "Oc1ccccc1".parse().unwrap();
+        let options =
+            SimilarityOptions { threshold: 0.0, verify: true, search_budget: Some(10_000) };
+
+        let hits = library.similar_to_with(&query, &options);
+        assert_eq!(hits[0].id(), 0);
+        assert_eq!(hits[0].mces_similarity(), Some(1.0));
+        assert!(hits.iter().all(|hit| hit.mces_similarity().is_some()));
+        assert_eq!(library.get(2).map(Smiles::render), Some("C1CCCCC1".into()));
+        assert_eq!(library.fingerprint(0), Some(&query.fingerprint()));
+    }
+}
@@ -0,0 +1,530 @@
+//! Style checks for SMILES strings that parse but are not written in their
+//! simplest form.
+//!
+//! [`lint`] parses the input, walks its tokens alongside the parsed graph and
+//! reports every spelling that a conventional writer would not produce. Each
+//! [`Lint`] carries the byte span of the offending text and, when the rewrite
+//! is purely local, a [`SuggestedFix`] that [`apply_fixes`] can apply.
+//!
+//! # Examples
+//!
+//! ```
+//! use smiles_parser::lint::{LintKind, apply_fixes, lint};
+//!
+//! let lints = lint("[CH3]-C")?;
+//! assert_eq!(lints.len(), 2);
+//! assert_eq!(lints[0].kind(), LintKind::UnnecessaryBrackets);
+//! assert_eq!(lints[1].kind(), LintKind::RedundantSingleBond);
+//! assert_eq!(apply_fixes("[CH3]-C", &lints), "CC");
+//! # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, ops::Range};
+
+use elements_rs::Element;
+
+use crate::{
+    atom::Atom,
+    bond::{Bond, ring_num::RingNum},
+    errors::{SmilesErrorWithSpan, SuggestedFix},
+    parser::token_iter::TokenIter,
+    smiles::{KekulizationError, ParserOptions, Smiles, WildcardAtoms, WildcardSmiles},
+    token::{Token, TokenWithSpan},
+};
+
+/// The style issue reported by a [`Lint`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// A bracket atom that means exactly the same as its organic-subset
+    /// spelling, e.g. `[CH4]` instead of `C`.
+    UnnecessaryBrackets,
+    /// An explicit `-` where the implied bond is already single, i.e. not
+    /// between two aromatic atoms.
+    RedundantSingleBond,
+    /// An atom written in Kekule form although it belongs to an aromatic ring,
+    /// e.g. the atoms of `C1=CC=CC=C1`.
+    KekuleAromaticAtom,
+    /// A ring-closure digit that is closed and immediately reopened on the
+    /// same atom, e.g. the second `1` in `C1CC11CC1`.
+    RingNumberReusedOnSameAtom,
+    /// A nitro group written with a pentavalent nitrogen, `N(=O)=O`, instead of
+    /// the charge-separated `[N+](=O)[O-]`.
+    UnchargedNitro,
+    /// Two different single-digit ring closures written back to back, e.g.
+    /// `12` in `C12CCCC1CCC2`, which a reader can mistake for ring `%12`.
+    AdjacentRingDigits,
+    /// A bracket atom whose chirality, hydrogen count, charge and class are
+    /// not in the order of the specification, e.g. `[O-H]` for `[OH-]`.
+    BracketFieldOrder,
+    /// A bare aromatic `n` in a ring that has no Kekule form unless that
+    /// nitrogen carries a hydrogen, e.g. `n1cccc1` for pyrrole `[nH]1cccc1`.
+    ///
+    /// A bare `n` shares one electron with its ring, like the nitrogen of
+    /// pyridine, while `[nH]` donates its lone pair, like the nitrogen of
+    /// pyrrole.
+    PyrroleNitrogenWithoutHydrogen,
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::UnnecessaryBrackets => "brackets are not needed for this atom",
+            Self::RedundantSingleBond => "explicit single bond is implied",
+            Self::KekuleAromaticAtom => "atom belongs to an aromatic ring written in Kekule form",
+            Self::RingNumberReusedOnSameAtom => {
+                "ring number is reopened on the atom that just closed it"
+            }
+            Self::UnchargedNitro => "nitro group is written with a pentavalent nitrogen",
+            Self::AdjacentRingDigits => "adjacent ring digits read like a two-digit ring number",
+            Self::BracketFieldOrder => "bracket atom fields are out of order",
+            Self::PyrroleNitrogenWithoutHydrogen => {
+                "aromatic nitrogen needs an explicit hydrogen for its ring to have a Kekule form"
+            }
+        })
+    }
+}
+
+/// A style issue found in a SMILES string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Lint {
+    kind: LintKind,
+    span: Range<usize>,
+    fix: Option<SuggestedFix>,
+}
+
+impl Lint {
+    fn with_fix(kind: LintKind, span: Range<usize>, replacement: String) -> Self {
+        let fix = SuggestedFix::new(span.clone(), replacement);
+        Self { kind, span, fix: Some(fix) }
+    }
+
+    fn without_fix(kind: LintKind, span: Range<usize>) -> Self {
+        Self { kind, span, fix: None }
+    }
+
+    /// Returns the kind of issue.
+    #[inline]
+    #[must_use]
+    pub fn kind(&self) -> LintKind {
+        self.kind
+    }
+
+    /// Returns the byte span of the offending text.
+    #[inline]
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the automatic fix for this issue, if one exists.
+    #[inline]
+    #[must_use]
+    pub fn fix(&self) -> Option<&SuggestedFix> {
+        self.fix.as_ref()
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}..{}", self.kind, self.span.start, self.span.end)
+    }
+}
+
+/// Lints a SMILES string.
+///
+/// Wildcard atoms and bracket atoms with out of order fields are accepted.
+/// Lints are returned in input order.
+///
+/// # Errors
+/// Returns the parse error if `input` is not valid SMILES.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::lint::{LintKind, lint};
+///
+/// let lints = lint("C1=CC=CC=C1")?;
+/// assert_eq!(lints.len(), 6);
+/// assert!(lints.iter().all(|lint| lint.kind() == LintKind::KekuleAromaticAtom));
+/// assert!(lint("c1ccccc1-c1ccccc1")?.is_empty());
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+pub fn lint(input: &str) -> Result<Vec<Lint>, SmilesErrorWithSpan> {
+    let options = ParserOptions { lenient_bracket_order: true, ..ParserOptions::default() };
+    let smiles = WildcardSmiles::parse_with_options(input, &options)?;
+    let smiles = smiles.inner();
+    let aromaticity = smiles.aromaticity_assignment();
+    let tokens = TokenIter::from(input)
+        .with_lenient_bracket_order(true)
+        .map(|token| token.unwrap_or_else(|_| unreachable!("input was already parsed")))
+        .collect::<Vec<_>>();
+
+    let pyrrole_nitrogens = pyrrole_nitrogens_without_hydrogen(input, &options, smiles, &tokens);
+
+    let mut lints = Vec::new();
+    let mut atom_id = 0_usize;
+    let mut current_atom = None;
+    let mut branch_stack = Vec::new();
+    let mut closed_on_current_atom = Vec::new();
+    let mut open_rings = [false; 100];
+
+    for (index, token) in tokens.iter().enumerate() {
+        match token.token() {
+            Token::Atom(atom) => {
+                if atom.is_bracket_atom() && Atom::parse_bracket(&input[token.span()]).is_err() {
+                    lints.push(Lint::with_fix(
+                        LintKind::BracketFieldOrder,
+                        token.span(),
+                        atom.to_string(),
+                    ));
+                }
+                if let Some(collapsed) = smiles.organic_subset_spelling(atom_id) {
+                    lints.push(Lint::with_fix(
+                        LintKind::UnnecessaryBrackets,
+                        token.span(),
+                        collapsed.to_string(),
+                    ));
+                }
+                if !atom.aromatic() && aromaticity.contains_atom(atom_id) {
+                    lints.push(Lint::without_fix(LintKind::KekuleAromaticAtom, token.span()));
+                }
+                if pyrrole_nitrogens.contains(&atom_id) {
+                    lints.push(Lint::with_fix(
+                        LintKind::PyrroleNitrogenWithoutHydrogen,
+                        token.span(),
+                        String::from("[nH]"),
+                    ));
+                }
+                if is_uncharged_nitro(input, token.span().start, atom_id, |id| {
+                    smiles.edge_count_for_node(id)
+                }) {
+                    let start = token.span().start;
+                    lints.push(Lint::with_fix(
+                        LintKind::UnchargedNitro,
+                        start..start + NITRO.len(),
+                        String::from("[N+](=O)[O-]"),
+                    ));
+                }
+                current_atom = Some(atom_id);
+                closed_on_current_atom.clear();
+                atom_id += 1;
+            }
+            Token::Bond(descriptor) => {
+                if descriptor.bond() == Bond::Single
+                    && !descriptor.is_aromatic()
+                    && is_redundant_single_bond(smiles.nodes(), current_atom, &tokens, index)
+                {
+                    lints.push(Lint::with_fix(
+                        LintKind::RedundantSingleBond,
+                        token.span(),
+                        String::new(),
+                    ));
+                }
+            }
+            Token::RingClosure { ring_num, .. } => {
+                if let Some(previous) = adjacent_single_digit_ring(&tokens, index, ring_num) {
+                    lints.push(Lint::without_fix(
+                        LintKind::AdjacentRingDigits,
+                        previous.span().start..token.span().end,
+                    ));
+                }
+                let slot = usize::from(ring_num.get());
+                if open_rings[slot] {
+                    open_rings[slot] = false;
+                    closed_on_current_atom.push(slot);
+                } else {
+                    if closed_on_current_atom.contains(&slot) {
+                        lints.push(Lint::without_fix(
+                            LintKind::RingNumberReusedOnSameAtom,
+                            token.span(),
+                        ));
+                    }
+                    open_rings[slot] = true;
+                }
+            }
+            Token::LeftParentheses => {
+                branch_stack.push(current_atom);
+                closed_on_current_atom.clear();
+            }
+            Token::RightParentheses => {
+                current_atom = branch_stack.pop().flatten();
+                closed_on_current_atom.clear();
+            }
+            Token::NonBond => {
+                current_atom = None;
+                closed_on_current_atom.clear();
+            }
+            Token::UnknownBracket(..) => {
+                unreachable!("the strict tokenizer never yields unknown bracket atoms")
+            }
+        }
+    }
+    Ok(lints)
+}
+
+/// Applies every available fix to `input`.
+///
+/// Fixes whose spans overlap an earlier fix are skipped, so the result is
+/// always well formed even when lints were collected from several passes.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::lint::{apply_fixes, lint};
+///
+/// let input = "[NH3+]-[CH2]C(=O)[OH]";
+/// assert_eq!(apply_fixes(input, &lint(input)?), "[NH3+]CC(=O)O");
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[must_use]
+pub fn apply_fixes(input: &str, lints: &[Lint]) -> String {
+    let mut fixes = lints.iter().filter_map(Lint::fix).collect::<Vec<_>>();
+    fixes.sort_by_key(|fix| (fix.span().start, fix.span().end));
+
+    let mut output = String::with_capacity(input.len());
+    let mut cursor = 0;
+    for fix in fixes {
+        let span = fix.span();
+        if span.start < cursor {
+            continue;
+        }
+        output.push_str(&input[cursor..span.start]);
+        output.push_str(fix.replacement());
+        cursor = span.end;
+    }
+    output.push_str(&input[cursor..]);
+    output
+}
+
+/// The pentavalent nitro spelling reported by [`LintKind::UnchargedNitro`].
+const NITRO: &str = "N(=O)=O";
+
+/// Returns whether the atom token starting at `start` opens a literal
+/// `N(=O)=O` whose two oxygens are terminal.
+fn is_uncharged_nitro(
+    input: &str,
+    start: usize,
+    atom_id: usize,
+    degree: impl Fn(usize) -> usize,
+) -> bool {
+    input[start..].starts_with(NITRO) && degree(atom_id + 1) == 1 && degree(atom_id + 2) == 1
+}
+
+/// Returns the ids of the bare aromatic `n` atoms that need a hydrogen for
+/// their aromatic system to have a Kekule form, at most one per system.
+///
+/// Each bare `n` of a system that cannot be localized is tried in input order
+/// by respelling it `[nH]`; the first one that makes its system localizable
+/// is reported.
+fn pyrrole_nitrogens_without_hydrogen(
+    input: &str,
+    options: &ParserOptions,
+    smiles: &Smiles<WildcardAtoms>,
+    tokens: &[TokenWithSpan],
+) -> Vec<usize> {
+    let Err(KekulizationError::NoPerfectMatching { ring_atoms, .. }) = smiles.kekulize_standalone()
+    else {
+        return Vec::new();
+    };
+
+    let atom_spans = tokens
+        .iter()
+        .filter(|token| matches!(token.token(), Token::Atom(_)))
+        .map(TokenWithSpan::span)
+        .collect::<Vec<_>>();
+    let mut unresolved = ring_atoms;
+    let mut nitrogens = Vec::new();
+    for atom_id in unresolved.clone() {
+        let atom = smiles.nodes()[atom_id];
+        if !unresolved.contains(&atom_id)
+            || atom.is_bracket_atom()
+            || !atom.aromatic()
+            || atom.element() != Some(Element::N)
+        {
+            continue;
+        }
+        let span = atom_spans[atom_id].clone();
+        let respelled = [&input[..span.start], "[nH]", &input[span.end..]].concat();
+        let Ok(protonated) = WildcardSmiles::parse_with_options(&respelled, options) else {
+            continue;
+        };
+        let still_failing = match protonated.inner().kekulize_standalone() {
+            Err(KekulizationError::NoPerfectMatching { ring_atoms, .. }) => ring_atoms,
+            _ => Vec::new(),
+        };
+        if still_failing.contains(&atom_id) {
+            continue;
+        }
+        unresolved.retain(|id| still_failing.contains(id));
+        nitrogens.push(atom_id);
+    }
+    nitrogens
+}
+
+/// Returns the single-digit ring closure written immediately before the one
+/// at `index`, if both are single digits and differ. Equal digits are left to
+/// [`LintKind::RingNumberReusedOnSameAtom`].
+fn adjacent_single_digit_ring(
+    tokens: &[TokenWithSpan],
+    index: usize,
+    ring_num: RingNum,
+) -> Option<&TokenWithSpan> {
+    let previous = tokens.get(index.checked_sub(1)?)?;
+    let Token::RingClosure { ring_num: previous_num, .. } = previous.token() else {
+        return None;
+    };
+    (!ring_num.uses_percent()
+        && !previous_num.uses_percent()
+        && previous_num != ring_num
+        && previous.span().end == tokens[index].span().start)
+        .then_some(previous)
+}
+
+/// Returns whether an explicit `-` only restates the implied bond, which is
+/// the case unless both of its endpoints are aromatic. Ring-closure bonds on
+/// an aromatic atom are left alone because the partner is not known yet.
+fn is_redundant_single_bond(
+    atoms: &[Atom],
+    current_atom: Option<usize>,
+    tokens: &[TokenWithSpan],
+    index: usize,
+) -> bool {
+    if current_atom.is_some_and(|atom_id| !atoms[atom_id].aromatic()) {
+        return true;
+    }
+    matches!(
+        tokens.get(index + 1).map(TokenWithSpan::token),
+        Some(Token::Atom(next)) if !next.aromatic()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<LintKind> {
+        lint(input).unwrap().iter().map(Lint::kind).collect()
+    }
+
+    #[test]
+    fn conventional_smiles_have_no_lints() {
+        for input in ["CCO", "c1ccccc1", "c1ccccc1-c1ccccc1", "[nH]1cccc1", "C[C@H](N)O", "[13CH4]"]
+        {
+            assert!(kinds(input).is_empty(), "{input}");
+        }
+    }
+
+    #[test]
+    fn unnecessary_brackets_are_fixed_to_organic_subset() {
+        let lints = lint("[CH3][OH]").unwrap();
+
+        assert_eq!(lints.len(), 2);
+        assert_eq!(lints[0].span(), 0..5);
+        assert_eq!(lints[1].fix().unwrap().replacement(), "O");
+        assert_eq!(apply_fixes("[CH3][OH]", &lints), "CO");
+    }
+
+    #[test]
+    fn brackets_that_change_hydrogen_count_are_kept() {
+        assert!(kinds("[CH2]C").is_empty());
+        assert!(kinds("[C]").is_empty());
+    }
+
+    #[test]
+    fn redundant_single_bonds_respect_aromatic_endpoints() {
+        assert_eq!(kinds("C-C"), [LintKind::RedundantSingleBond]);
+        assert_eq!(kinds("c1ccccc1-C"), [LintKind::RedundantSingleBond]);
+        assert_eq!(kinds("C(-C)C"), [LintKind::RedundantSingleBond]);
+        assert!(kinds("c1ccccc1-c1ccccc1").is_empty());
+        assert!(kinds("c1ccccc-1").is_empty());
+    }
+
+    #[test]
+    fn kekule_aromatic_atoms_are_reported_per_atom() {
+        let lints = lint("C1=CC=CC=C1O").unwrap();
+
+        assert_eq!(lints.len(), 6);
+        assert!(lints.iter().all(|lint| lint.fix().is_none()));
+        assert_eq!(lints[1].span(), 3..4);
+    }
+
+    #[test]
+    fn ring_number_reopened_on_closing_atom_is_reported() {
+        let lints = lint("C1CC11CC1").unwrap();
+
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind(), LintKind::RingNumberReusedOnSameAtom);
+        assert_eq!(lints[0].span(), 5..6);
+        assert!(kinds("C1CC1C1CC1").is_empty());
+    }
+
+    #[test]
+    fn adjacent_ring_digits_are_reported() {
+        let lints = lint("C12CCCC1CCC2").unwrap();
+
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind(), LintKind::AdjacentRingDigits);
+        assert_eq!(lints[0].span(), 1..3);
+        assert!(lints[0].fix().is_none());
+        assert!(kinds("C%12CCCC%12").is_empty());
+        assert!(kinds("C1%12CCCC1CCC%12").is_empty());
+        assert!(kinds("C1CCCC1C2CCCC2").is_empty());
+    }
+
+    #[test]
+    fn uncharged_nitro_is_rewritten_charge_separated() {
+        let input = "c1ccccc1N(=O)=O";
+        let lints = lint(input).unwrap();
+
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].kind(), LintKind::UnchargedNitro);
+        assert_eq!(lints[0].span(), 8..15);
+        assert_eq!(apply_fixes(input, &lints), "c1ccccc1[N+](=O)[O-]");
+        assert!(kinds("C[N+](=O)[O-]").is_empty());
+        assert!(kinds("CN(=O)=OC").is_empty());
+    }
+
+    #[test]
+    fn out_of_order_bracket_fields_are_reordered() {
+        let input = "C[O-H]C[N+:1H3]";
+        let lints = lint(input).unwrap();
+
+        assert_eq!(lints.len(), 2);
+        assert!(lints.iter().all(|lint| lint.kind() == LintKind::BracketFieldOrder));
+        assert_eq!(lints[0].span(), 1..6);
+        assert_eq!(apply_fixes(input, &lints), "C[OH-]C[NH3+:1]");
+        assert!("C[O-H]".parse::<crate::smiles::Smiles>().is_err());
+        assert!(kinds("[C@@H2+:1]").is_empty());
+    }
+
+    #[test]
+    fn bare_pyrrole_nitrogen_is_given_a_hydrogen() {
+        let input = "n1cccc1.c1nccn1";
+        let lints = lint(input).unwrap();
+
+        assert_eq!(lints.len(), 2);
+        assert!(lints.iter().all(|lint| lint.kind() == LintKind::PyrroleNitrogenWithoutHydrogen));
+        assert_eq!(lints[0].span(), 0..1);
+        assert_eq!(lints[1].span(), 10..11);
+        assert_eq!(apply_fixes(input, &lints), "[nH]1cccc1.c1[nH]ccn1");
+        assert!(kinds("c1ccncc1").is_empty());
+        assert!(kinds("c1cccc1").is_empty());
+        assert!(kinds("[n]1cccc1").is_empty());
+    }
+
+    #[test]
+    fn invalid_input_returns_parse_error() {
+        assert!(lint("C(").is_err());
+    }
+
+    #[test]
+    fn lint_display_names_kind_and_span() {
+        let lints = lint("C-C").unwrap();
+
+        assert_eq!(lints[0].to_string(), "explicit single bond is implied at 1..2");
+    }
+}
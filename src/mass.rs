@@ -0,0 +1,258 @@
+//! Monoisotopic masses and mass-based candidate filtering.
+//!
+//! Untargeted metabolomics annotates a measured mass by looking for the
+//! candidate structures whose monoisotopic mass lies within a tolerance of it.
+//! [`Smiles::monoisotopic_mass`] computes the mass of a graph,
+//! [`Smiles::matches_mass`] and [`Smiles::matching_components`] test a graph
+//! or its disconnected components against a target, and [`rank_by_mass_error`]
+//! orders a candidate list by how close each one comes. [`Adduct`] converts
+//! between neutral masses and the m/z of ions such as `[M+H]+`.
+//!
+//! # Examples
+//!
+//! ```
+//! use smiles_parser::{
+//!     Smiles,
+//!     mass::{MassTolerance, rank_by_mass_error},
+//! };
+//!
+//! let candidates = ["CCO", "COC", "CC=O", "OCC(O)CO"]
+//!     .map(|smiles| smiles.parse::<Smiles>())
+//!     .into_iter()
+//!     .collect::<Result<Vec<_>, _>>()?;
+//!
+//! let matches = rank_by_mass_error(&candidates, 46.0419, MassTolerance::Ppm(10.0));
+//! assert_eq!(matches.iter().map(|candidate| candidate.index()).collect::<Vec<_>>(), [0, 1]);
+//! # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+//! ```
+
+use alloc::vec::Vec;
+
+use elements_rs::{Element, RelativeAtomicMass};
+
+use crate::smiles::Smiles;
+
+mod adduct;
+
+pub use self::adduct::{Adduct, AdductError};
+
+/// Mass of the electron in daltons (CODATA 2018), removed once per positive
+/// charge and added once per negative charge.
+pub const ELECTRON_MASS: f64 = 0.000_548_579_909_065;
+
+/// How far a mass may lie from its target and still match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MassTolerance {
+    /// An absolute window in daltons.
+    Dalton(f64),
+    /// A window in parts per million of the target mass.
+    Ppm(f64),
+}
+
+impl MassTolerance {
+    /// Returns the half-width in daltons of the window around `target`.
+    #[must_use]
+    pub fn window(self, target: f64) -> f64 {
+        match self {
+            Self::Dalton(window) => window,
+            Self::Ppm(ppm) => target.abs() * ppm * 1e-6,
+        }
+    }
+
+    /// Returns whether `mass` lies within the window around `target`.
+    #[must_use]
+    pub fn contains(self, target: f64, mass: f64) -> bool {
+        (mass - target).abs() <= self.window(target)
+    }
+}
+
+/// A candidate that matched a target mass in [`rank_by_mass_error`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MassMatch {
+    index: usize,
+    mass: f64,
+    error: f64,
+}
+
+impl MassMatch {
+    /// Returns the position of the candidate in the input list.
+    #[inline]
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the monoisotopic mass of the candidate.
+    #[inline]
+    #[must_use]
+    pub fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    /// Returns the signed difference between the candidate and the target, in
+    /// daltons.
+    #[inline]
+    #[must_use]
+    pub fn error(&self) -> f64 {
+        self.error
+    }
+
+    /// Returns the signed difference between the candidate and the target, in
+    /// parts per million of the target.
+    #[inline]
+    #[must_use]
+    pub fn error_ppm(&self) -> f64 {
+        self.error / (self.mass - self.error) * 1e6
+    }
+}
+
+impl Smiles {
+    /// Returns the monoisotopic mass of the graph in daltons.
+    ///
+    /// Atoms without an isotope label use the most abundant isotope of their
+    /// element, labelled atoms use their isotope, implicit and bracket
+    /// hydrogens count as protium, and each formal charge removes or adds one
+    /// electron mass.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let ethanol: Smiles = "CCO".parse()?;
+    /// assert!((ethanol.monoisotopic_mass() - 46.041865).abs() < 1e-5);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn monoisotopic_mass(&self) -> f64 {
+        self.atoms_mass(0..self.nodes().len())
+    }
+
+    /// Returns the monoisotopic mass of every connected component, in the
+    /// order of [`Smiles::connected_components`].
+    #[must_use]
+    pub fn component_monoisotopic_masses(&self) -> Vec<f64> {
+        let components = self.connected_components();
+        (0..components.number_of_components())
+            .map(|component| self.atoms_mass(components.node_ids_of_component(component)))
+            .collect()
+    }
+
+    /// Returns whether the monoisotopic mass of the whole graph matches
+    /// `target`.
+    #[must_use]
+    pub fn matches_mass(&self, target: f64, tolerance: MassTolerance) -> bool {
+        tolerance.contains(target, self.monoisotopic_mass())
+    }
+
+    /// Returns the connected components whose monoisotopic mass matches
+    /// `target`, e.g. the parent ion of a salt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{Smiles, mass::MassTolerance};
+    ///
+    /// let salt: Smiles = "[Na+].CC(=O)[O-]".parse()?;
+    /// assert_eq!(salt.matching_components(59.0139, MassTolerance::Dalton(0.001)), [1]);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn matching_components(&self, target: f64, tolerance: MassTolerance) -> Vec<usize> {
+        self.component_monoisotopic_masses()
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, mass)| tolerance.contains(target, mass))
+            .map(|(component, _)| component)
+            .collect()
+    }
+
+    fn atoms_mass(&self, atoms: impl IntoIterator<Item = usize>) -> f64 {
+        let hydrogen = Element::H.most_abundant_isotope().relative_atomic_mass();
+        atoms
+            .into_iter()
+            .map(|atom_id| {
+                let atom = self.nodes()[atom_id];
+                let isotope = atom
+                    .isotope()
+                    .unwrap_or_else(|_| unreachable!("strict atoms have a known isotope"));
+                let hydrogens = f64::from(atom.hydrogen_count())
+                    + f64::from(self.implicit_hydrogen_count(atom_id));
+                isotope.relative_atomic_mass() + hydrogens * hydrogen
+                    - f64::from(atom.charge_value()) * ELECTRON_MASS
+            })
+            .sum()
+    }
+}
+
+/// Returns the candidates whose monoisotopic mass matches `target`, ordered
+/// by increasing absolute error. Ties keep the input order.
+pub fn rank_by_mass_error<'a>(
+    candidates: impl IntoIterator<Item = &'a Smiles>,
+    target: f64,
+    tolerance: MassTolerance,
+) -> Vec<MassMatch> {
+    let mut matches = candidates
+        .into_iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            let mass = candidate.monoisotopic_mass();
+            MassMatch { index, mass, error: mass - target }
+        })
+        .filter(|candidate| tolerance.contains(target, candidate.mass))
+        .collect::<Vec<_>>();
+    matches.sort_by(|left, right| left.error.abs().total_cmp(&right.error.abs()));
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mass(smiles: &str) -> f64 {
+        smiles.parse::<Smiles>().unwrap().monoisotopic_mass()
+    }
+
+    #[test]
+    fn monoisotopic_masses_match_reference_values() {
+        for (smiles, expected) in [
+            ("OCC1OC(O)C(O)C(O)C1O", 180.063_388),
+            ("Cn1cnc2c1c(=O)n(C)c(=O)n2C", 194.080_376),
+            ("[13CH4]", 17.034_655),
+            ("[2H]C([2H])([2H])[2H]", 20.056_407),
+            ("[NH4+]", 18.033_826),
+        ] {
+            assert!((mass(smiles) - expected).abs() < 1e-5, "{smiles}: {}", mass(smiles));
+        }
+    }
+
+    #[test]
+    fn tolerances_cover_daltons_and_ppm() {
+        assert!(MassTolerance::Dalton(0.01).contains(100.0, 100.009));
+        assert!(!MassTolerance::Dalton(0.01).contains(100.0, 99.98));
+        assert!((MassTolerance::Ppm(5.0).window(200.0) - 0.001).abs() < 1e-12);
+        assert!(MassTolerance::Ppm(5.0).contains(200.0, 200.000_9));
+        assert!(!MassTolerance::Ppm(5.0).contains(200.0, 200.002));
+    }
+
+    #[test]
+    fn whole_graphs_and_components_are_matched() {
+        let salt: Smiles = "[Na+].[Cl-]".parse().unwrap();
+        assert!(salt.matches_mass(57.958_622, MassTolerance::Dalton(1e-4)));
+        assert_eq!(salt.component_monoisotopic_masses().len(), 2);
+        assert!(salt.matching_components(57.958_622, MassTolerance::Dalton(1e-4)).is_empty());
+        assert_eq!(salt.matching_components(34.969_402, MassTolerance::Dalton(1e-3)), [1]);
+    }
+
+    #[test]
+    fn candidates_are_ranked_by_absolute_error() {
+        let candidates =
+            ["CC=O", "CCO", "C=CO", "COC"].map(|smiles| smiles.parse::<Smiles>().unwrap());
+        let matches = rank_by_mass_error(&candidates, 46.041_9, MassTolerance::Dalton(0.01));
+
+        assert_eq!(matches.iter().map(MassMatch::index).collect::<Vec<_>>(), [1, 3]);
+        assert!(matches[0].error().abs() < 1e-4);
+        assert!(matches[0].error_ppm().abs() < 1.0);
+        assert!((matches[0].mass() - mass("CCO")).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,281 @@
+//! Mass spectrometry adducts such as `[M+H]+` or `[2M-H]-`.
+
+use alloc::{string::String, vec::Vec};
+use core::{fmt, str::FromStr};
+
+use elements_rs::{Element, RelativeAtomicMass};
+use molecular_formulas::ChemicalFormula;
+use thiserror::Error;
+
+use super::ELECTRON_MASS;
+use crate::smiles::{Smiles, element_counts};
+
+/// Error raised while parsing an [`Adduct`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Error)]
+pub enum AdductError {
+    /// The notation does not have the `[nM±X]z±` shape; the offset points at
+    /// the first unexpected byte.
+    #[error("unexpected character at byte {0} of adduct")]
+    UnexpectedCharacter(usize),
+    /// The group starting at the given byte offset is not a formula.
+    #[error("invalid adduct group at byte {0}")]
+    InvalidGroup(usize),
+    /// The notation ends without a `+` or `-` charge sign.
+    #[error("adduct is missing its charge sign")]
+    MissingCharge,
+    /// The count starting at the given byte offset is zero or does not fit
+    /// in an `i32`.
+    #[error("invalid adduct count at byte {0}")]
+    InvalidCount(usize),
+}
+
+/// A group added to or removed from the molecule, such as `+Na` or `-H2O`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AdductGroup {
+    count: i32,
+    /// The elements of the group formula and their counts.
+    elements: Vec<(Element, u32)>,
+}
+
+impl AdductGroup {
+    /// Returns the monoisotopic mass of one copy of the group, using the most
+    /// abundant isotope of every element.
+    fn mass(&self) -> f64 {
+        self.elements
+            .iter()
+            .map(|&(element, count)| {
+                f64::from(count) * element.most_abundant_isotope().relative_atomic_mass()
+            })
+            .sum()
+    }
+}
+
+/// An ion type written in the common `[nM±X]z±` notation, e.g. `[M+H]+`,
+/// `[M+Na]+`, `[M-H]-`, `[2M+H]+`, `[M+2H]2+` or `[M+H-H2O]+`.
+///
+/// Groups are plain formulas; abbreviations such as `ACN` or `FA` are not
+/// recognised and must be written out (`C2H3N`, `CH2O2`).
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::{Smiles, mass::Adduct};
+///
+/// let protonated: Adduct = "[M+H]+".parse()?;
+/// let glucose: Smiles = "OCC1OC(O)C(O)C(O)C1O".parse()?;
+///
+/// assert_eq!(protonated.charge(), 1);
+/// assert!((glucose.adduct_mz(&protonated) - 181.070_665).abs() < 1e-5);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Adduct {
+    notation: String,
+    multimer: u32,
+    groups: Vec<AdductGroup>,
+    charge: i32,
+}
+
+impl Adduct {
+    /// Returns how many molecules the ion contains, e.g. 2 for `[2M+H]+`.
+    #[inline]
+    #[must_use]
+    pub fn multimer(&self) -> u32 {
+        self.multimer
+    }
+
+    /// Returns the signed charge of the ion.
+    #[inline]
+    #[must_use]
+    pub fn charge(&self) -> i32 {
+        self.charge
+    }
+
+    /// Returns the net mass of the added and removed groups, in daltons.
+    #[must_use]
+    pub fn mass_shift(&self) -> f64 {
+        self.groups.iter().map(|group| f64::from(group.count) * group.mass()).sum()
+    }
+
+    /// Returns the m/z of the ion formed from a neutral molecule of the given
+    /// monoisotopic mass.
+    #[must_use]
+    pub fn mz(&self, neutral_mass: f64) -> f64 {
+        let ion_mass = f64::from(self.multimer) * neutral_mass + self.mass_shift()
+            - f64::from(self.charge) * ELECTRON_MASS;
+        ion_mass / f64::from(self.charge.unsigned_abs())
+    }
+
+    /// Returns the monoisotopic mass of the neutral molecule that forms an
+    /// ion of the given m/z; the inverse of [`Adduct::mz`].
+    #[must_use]
+    pub fn neutral_mass(&self, mz: f64) -> f64 {
+        let ion_mass = mz * f64::from(self.charge.unsigned_abs());
+        (ion_mass + f64::from(self.charge) * ELECTRON_MASS - self.mass_shift())
+            / f64::from(self.multimer)
+    }
+}
+
+impl FromStr for Adduct {
+    type Err = AdductError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+        if bytes.first() != Some(&b'[') {
+            return Err(AdductError::UnexpectedCharacter(0));
+        }
+        let (multimer, mut position) = leading_count(bytes, 1)?;
+        if bytes.get(position) != Some(&b'M') {
+            return Err(AdductError::UnexpectedCharacter(position));
+        }
+        position += 1;
+
+        let mut groups = Vec::new();
+        while let Some(&sign @ (b'+' | b'-')) = bytes.get(position) {
+            let start = position + 1;
+            let end = bytes[start..]
+                .iter()
+                .position(|byte| matches!(byte, b'+' | b'-' | b']'))
+                .map_or(bytes.len(), |offset| start + offset);
+            let (count, formula_start) = leading_count(bytes, start)?;
+            let formula = ChemicalFormula::<u32, i32>::try_from(&s[formula_start..end])
+                .map_err(|_| AdductError::InvalidGroup(start))?;
+            let elements = element_counts(&formula);
+            if elements.is_empty() {
+                return Err(AdductError::InvalidGroup(start));
+            }
+            let count = i32::try_from(count.unwrap_or(1))
+                .unwrap_or_else(|_| unreachable!("counts fit in an i32"));
+            groups.push(AdductGroup { count: if sign == b'+' { count } else { -count }, elements });
+            position = end;
+        }
+        if bytes.get(position) != Some(&b']') {
+            return Err(AdductError::UnexpectedCharacter(position));
+        }
+
+        let (magnitude, sign_position) = leading_count(bytes, position + 1)?;
+        let charge = match bytes.get(sign_position) {
+            Some(b'+') => 1,
+            Some(b'-') => -1,
+            Some(_) => return Err(AdductError::UnexpectedCharacter(sign_position)),
+            None => return Err(AdductError::MissingCharge),
+        };
+        if sign_position + 1 != bytes.len() {
+            return Err(AdductError::UnexpectedCharacter(sign_position + 1));
+        }
+        let magnitude = i32::try_from(magnitude.unwrap_or(1))
+            .unwrap_or_else(|_| unreachable!("counts fit in an i32"));
+
+        Ok(Self {
+            notation: s.into(),
+            multimer: multimer.unwrap_or(1),
+            groups,
+            charge: charge * magnitude,
+        })
+    }
+}
+
+/// Reads the digits starting at `start`, returning `None` when there are none,
+/// together with the offset after them.
+///
+/// # Errors
+/// Returns [`AdductError::InvalidCount`] for a count of zero or one that does
+/// not fit in an `i32`.
+fn leading_count(bytes: &[u8], start: usize) -> Result<(Option<u32>, usize), AdductError> {
+    let end = bytes[start..]
+        .iter()
+        .position(|byte| !byte.is_ascii_digit())
+        .map_or(bytes.len(), |offset| start + offset);
+    if start == end {
+        return Ok((None, end));
+    }
+    let count = core::str::from_utf8(&bytes[start..end])
+        .ok()
+        .and_then(|digits| digits.parse::<u32>().ok())
+        .filter(|&count| count != 0 && i32::try_from(count).is_ok())
+        .ok_or(AdductError::InvalidCount(start))?;
+    Ok((Some(count), end))
+}
+
+impl fmt::Display for Adduct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.notation)
+    }
+}
+
+impl Smiles {
+    /// Returns the m/z of the ion the graph forms with `adduct`, using the
+    /// graph's [monoisotopic mass](Smiles::monoisotopic_mass) as the mass of
+    /// the molecule.
+    #[must_use]
+    pub fn adduct_mz(&self, adduct: &Adduct) -> f64 {
+        adduct.mz(self.monoisotopic_mass())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    fn mz(smiles: &str, adduct: &str) -> f64 {
+        smiles.parse::<Smiles>().unwrap().adduct_mz(&adduct.parse().unwrap())
+    }
+
+    #[test]
+    fn common_adducts_match_reference_mz() {
+        let glucose = "OCC1OC(O)C(O)C(O)C1O";
+        for (adduct, expected) in [
+            ("[M+H]+", 181.070_665),
+            ("[M+Na]+", 203.052_609),
+            ("[M-H]-", 179.056_112),
+            ("[M+NH4]+", 198.097_214),
+            ("[2M+H]+", 361.134_053),
+            ("[M+2H]2+", 91.038_971),
+            ("[M+H-H2O]+", 163.060_100),
+            ("[M]+", 180.062_840),
+        ] {
+            assert!(
+                (mz(glucose, adduct) - expected).abs() < 1e-5,
+                "{adduct}: {}",
+                mz(glucose, adduct)
+            );
+        }
+    }
+
+    #[test]
+    fn neutral_mass_inverts_mz() {
+        let adduct: Adduct = "[2M+Na-H2O]+".parse().unwrap();
+        assert_eq!(adduct.multimer(), 2);
+        assert_eq!(adduct.charge(), 1);
+        assert_eq!(adduct.to_string(), "[2M+Na-H2O]+");
+        assert!((adduct.neutral_mass(adduct.mz(250.0)) - 250.0).abs() < 1e-9);
+
+        let doubly: Adduct = "[M-2H]2-".parse().unwrap();
+        assert_eq!(doubly.charge(), -2);
+        assert!((doubly.neutral_mass(doubly.mz(400.0)) - 400.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn malformed_adducts_are_rejected() {
+        assert_eq!("M+H".parse::<Adduct>(), Err(AdductError::UnexpectedCharacter(0)));
+        assert_eq!("[X+H]+".parse::<Adduct>(), Err(AdductError::UnexpectedCharacter(1)));
+        assert_eq!("[M+H]".parse::<Adduct>(), Err(AdductError::MissingCharge));
+        assert_eq!("[M+ACN]+".parse::<Adduct>(), Err(AdductError::InvalidGroup(3)));
+        assert_eq!("[M+]+".parse::<Adduct>(), Err(AdductError::InvalidGroup(3)));
+        assert_eq!("[M+H]+x".parse::<Adduct>(), Err(AdductError::UnexpectedCharacter(6)));
+        assert_eq!("[M*H]+".parse::<Adduct>(), Err(AdductError::UnexpectedCharacter(2)));
+    }
+
+    #[test]
+    fn zero_and_overflowing_counts_are_rejected() {
+        assert_eq!("[0M+H]+".parse::<Adduct>(), Err(AdductError::InvalidCount(1)));
+        assert_eq!("[M+0H]+".parse::<Adduct>(), Err(AdductError::InvalidCount(3)));
+        assert_eq!("[M+H]0+".parse::<Adduct>(), Err(AdductError::InvalidCount(5)));
+        assert_eq!("[4294967296M+H]+".parse::<Adduct>(), Err(AdductError::InvalidCount(1)));
+        assert_eq!("[M+2147483648H]+".parse::<Adduct>(), Err(AdductError::InvalidCount(3)));
+        assert_eq!("[M+H]99999999999+".parse::<Adduct>(), Err(AdductError::InvalidCount(5)));
+        assert_eq!("[M+2147483647H]+".parse::<Adduct>().map(|adduct| adduct.charge()), Ok(1));
+    }
+}
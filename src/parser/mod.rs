@@ -1,4 +1,5 @@
 //! Submodule for parsing tokens
 
+pub(crate) mod ring_table;
 pub(crate) mod smiles_parser;
 pub(crate) mod token_iter;
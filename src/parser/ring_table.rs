@@ -0,0 +1,118 @@
+//! The ring-closure numbers left open while parsing.
+
+use smallvec::SmallVec;
+
+use crate::bond::{BondDescriptor, ring_num::RingNum};
+
+/// A ring bond opened by a ring-closure number and waiting for the atom that
+/// closes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct OpenRing {
+    /// Atom written before the opening number.
+    pub(crate) atom: usize,
+    /// Bond written before the opening number, if any.
+    pub(crate) bond: Option<BondDescriptor>,
+}
+
+/// Ring-closure numbers the table holds inline before spilling to the heap.
+const INLINE_RING_NUMBERS: usize = 8;
+
+/// The open ring-closure numbers of a parse, keyed by number.
+///
+/// Only numbers that are currently open take space, so checking that every
+/// ring was closed does not scan the whole number range. Cage compounds and
+/// macrocycles with hundreds of rings cycle through a handful of numbers, and
+/// the table counts how many times each number has been closed so far.
+/// Lookups scan the numbers in use, which stay within the first
+/// [`INLINE_RING_NUMBERS`] for almost every molecule, so the table does not
+/// allocate for them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RingTable {
+    open: SmallVec<[(RingNum, OpenRing); INLINE_RING_NUMBERS]>,
+    closures: SmallVec<[(RingNum, usize); INLINE_RING_NUMBERS]>,
+}
+
+impl RingTable {
+    /// Returns the table emptied, keeping its allocations.
+    #[inline]
+    #[must_use]
+    pub(crate) fn cleared(mut self) -> Self {
+        self.open.clear();
+        self.closures.clear();
+        self
+    }
+
+    /// Opens `ring_num` at `ring`.
+    #[inline]
+    pub(crate) fn open(&mut self, ring_num: RingNum, ring: OpenRing) {
+        match self.open.iter_mut().find(|(open, _)| *open == ring_num) {
+            Some((_, slot)) => *slot = ring,
+            None => self.open.push((ring_num, ring)),
+        }
+    }
+
+    /// Closes `ring_num` and returns where it was opened, if it was open.
+    #[inline]
+    pub(crate) fn close(&mut self, ring_num: RingNum) -> Option<OpenRing> {
+        let position = self.open.iter().position(|(open, _)| *open == ring_num)?;
+        let (_, ring) = self.open.swap_remove(position);
+        match self.closures.iter_mut().find(|(closed, _)| *closed == ring_num) {
+            Some((_, closures)) => *closures += 1,
+            None => self.closures.push((ring_num, 1)),
+        }
+        Some(ring)
+    }
+
+    /// Returns whether no ring-closure number is open.
+    #[inline]
+    #[must_use]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.open.is_empty()
+    }
+
+    /// Returns how many times `ring_num` has been closed so far.
+    #[cfg(any(test, feature = "tracing"))]
+    #[inline]
+    #[must_use]
+    pub(crate) fn closures(&self, ring_num: RingNum) -> usize {
+        self.closures
+            .iter()
+            .find_map(|&(closed, closures)| (closed == ring_num).then_some(closures))
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bond::Bond;
+
+    #[test]
+    fn numbers_are_reused_and_their_closures_counted() {
+        let mut table = RingTable::default();
+        let one = RingNum::try_new(1).unwrap();
+        let two = RingNum::try_new(2).unwrap();
+        assert!(table.is_empty());
+        assert_eq!(table.close(one), None);
+
+        for atom in 0..300 {
+            table.open(one, OpenRing { atom, bond: Some(Bond::Double.into()) });
+            assert!(!table.is_empty());
+            assert_eq!(table.close(one), Some(OpenRing { atom, bond: Some(Bond::Double.into()) }));
+        }
+        table.open(two, OpenRing { atom: 0, bond: None });
+        assert_eq!(table.closures(one), 300);
+        assert_eq!(table.closures(two), 0);
+        assert!(!table.open.spilled() && !table.closures.spilled());
+
+        for number in 0..100 {
+            table.open(RingNum::try_new(number).unwrap(), OpenRing { atom: 1, bond: None });
+        }
+        assert_eq!(table.close(two), Some(OpenRing { atom: 1, bond: None }));
+        assert_eq!(table.closures(two), 1);
+
+        let table = table.cleared();
+        assert!(table.is_empty());
+        assert_eq!(table.closures(one), 0);
+    }
+}
@@ -1,21 +1,31 @@
 //! Parser state used while turning tokenized SMILES into a graph.
 
 use alloc::vec::Vec;
-use core::marker::PhantomData;
+use core::{marker::PhantomData, mem};
 
 use elements_rs::{Element, Isotope};
+use smallvec::SmallVec;
 
 use crate::{
     atom::Atom,
     bond::{Bond, BondDescriptor, ring_num::RingNum},
     errors::{SmilesError, SmilesErrorWithSpan},
-    parser::token_iter::TokenIter,
-    smiles::{BondMatrixBuilder, Smiles, SmilesAtomPolicy, StereoNeighbor, WildcardAtoms},
+    parser::{
+        ring_table::{OpenRing, RingTable},
+        token_iter::TokenIter,
+    },
+    smiles::{
+        BondMatrixBuilder, ParserOptions, ParserScratch, Smiles, SmilesAtomPolicy, StereoNeighbor,
+        WildcardAtoms, edge_key,
+    },
     token::{Token, TokenKind, TokenWithSpan},
 };
 
 #[inline]
-fn next_token(tokens: &mut TokenIter<'_>) -> Result<Option<TokenWithSpan>, SmilesErrorWithSpan> {
+fn next_token(
+    tokens: &mut impl Iterator<Item = Result<TokenWithSpan, SmilesErrorWithSpan>>,
+) -> Result<Option<TokenWithSpan>, SmilesErrorWithSpan> {
+    stage_span!(TRACE, "smiles.tokenize");
     match tokens.next() {
         Some(Ok(token)) => Ok(Some(token)),
         Some(Err(error)) => Err(error),
@@ -36,17 +46,128 @@ pub(crate) fn parse_wildcard_smiles(
 pub(crate) fn parse_smiles_with_policy<AtomPolicy: SmilesAtomPolicy>(
     input: &str,
 ) -> Result<Smiles<AtomPolicy>, SmilesErrorWithSpan> {
-    if input.is_empty() {
-        return Err(SmilesErrorWithSpan::new(SmilesError::MissingElement, 0, 0));
+    parse_smiles_with_options(input, &ParserOptions::default())
+}
+
+pub(crate) fn parse_smiles_with_options<AtomPolicy: SmilesAtomPolicy>(
+    input: &str,
+    options: &ParserOptions,
+) -> Result<Smiles<AtomPolicy>, SmilesErrorWithSpan> {
+    parse_smiles_with_hook(input, options, |_| {})
+}
+
+pub(crate) fn parse_smiles_with_scratch<AtomPolicy: SmilesAtomPolicy>(
+    input: &str,
+    options: &ParserOptions,
+    scratch: &mut ParserScratch,
+) -> Result<Smiles<AtomPolicy>, SmilesErrorWithSpan> {
+    parse_smiles_with_scratch_and_hook(input, options, Some(scratch), |_| {})
+}
+
+/// Builds a graph from already tokenized SMILES, with the default options.
+pub(crate) fn parse_smiles_from_tokens<AtomPolicy: SmilesAtomPolicy>(
+    tokens: &[TokenWithSpan],
+) -> Result<Smiles<AtomPolicy>, SmilesErrorWithSpan> {
+    if tokens.is_empty() {
+        return Err(SmilesErrorWithSpan::new(SmilesError::EmptyInput, 0, 0));
+    }
+    // Spans only locate errors, so the buffers are sized from the token count.
+    let mut parser_state = ParserState::<AtomPolicy>::new_for_policy(tokens.len());
+    add_tokens(
+        tokens.iter().cloned().map(Ok),
+        &ParserOptions::default(),
+        &mut parser_state,
+        |_| {},
+    )?;
+    Ok(parser_state.into_smiles())
+}
+
+/// Parses `input`, calling `on_token` on every token before it is validated.
+pub(crate) fn parse_smiles_with_hook<AtomPolicy: SmilesAtomPolicy>(
+    input: &str,
+    options: &ParserOptions,
+    on_token: impl FnMut(&TokenWithSpan),
+) -> Result<Smiles<AtomPolicy>, SmilesErrorWithSpan> {
+    parse_smiles_with_scratch_and_hook(input, options, None, on_token)
+}
+
+/// Parses `input` with the working memory of `scratch`, if any, calling
+/// `on_token` on every token before it is validated.
+fn parse_smiles_with_scratch_and_hook<AtomPolicy: SmilesAtomPolicy>(
+    input: &str,
+    options: &ParserOptions,
+    scratch: Option<&mut ParserScratch>,
+    on_token: impl FnMut(&TokenWithSpan),
+) -> Result<Smiles<AtomPolicy>, SmilesErrorWithSpan> {
+    stage_span!(DEBUG, "smiles.parse", input_len = input.len());
+    let (input, annotation) = options.split_annotation(input);
+    if input.trim_ascii().is_empty() {
+        if options.allow_empty {
+            return Ok(annotated(
+                ParserState::<AtomPolicy>::new_for_policy(0).into_smiles(),
+                annotation,
+            ));
+        }
+        return Err(SmilesErrorWithSpan::new(SmilesError::EmptyInput, 0, input.len()));
+    }
+
+    let (mut parser_state, parsed) = match scratch {
+        Some(scratch) => {
+            let mut parser_state = ParserState::<AtomPolicy>::with_scratch(input.len(), scratch);
+            let parsed = parse_tokens(input, options, &mut parser_state, on_token);
+            parser_state.return_scratch(scratch);
+            (parser_state, parsed)
+        }
+        None => {
+            let mut parser_state = ParserState::<AtomPolicy>::new_for_policy(input.len());
+            let parsed = parse_tokens(input, options, &mut parser_state, on_token);
+            (parser_state, parsed)
+        }
+    };
+    parsed?;
+    let explicit_single_bonds = mem::take(&mut parser_state.explicit_single_bonds);
+    let smiles = parser_state.into_smiles();
+    let mut smiles = if AtomPolicy::ALLOW_WILDCARDS && options.aromatic_wildcards {
+        smiles.with_ring_wildcards_aromatic()
+    } else {
+        smiles
+    };
+    if options.explicit_single_bonds {
+        smiles.set_explicit_single_bonds(explicit_single_bonds);
     }
+    Ok(annotated(smiles, annotation))
+}
 
-    let mut tokens = TokenIter::from(input);
-    let mut parser_state = ParserState::<AtomPolicy>::new_for_policy(input.len());
+/// Feeds the tokens of `input` to `parser_state` and checks that every
+/// branch and ring is closed.
+fn parse_tokens<AtomPolicy: SmilesAtomPolicy>(
+    input: &str,
+    options: &ParserOptions,
+    parser_state: &mut ParserState<AtomPolicy>,
+    on_token: impl FnMut(&TokenWithSpan),
+) -> Result<(), SmilesErrorWithSpan> {
+    let tokens = TokenIter::from(input)
+        .with_unbracketed_se_as(options.unbracketed_se_as)
+        .with_lenient_bracket_order(options.lenient_bracket_order)
+        .with_multi_digit_hydrogen_counts(options.multi_digit_hydrogen_counts);
+    add_tokens(tokens, options, parser_state, on_token)
+}
+
+/// Feeds `tokens` to `parser_state` and checks that every branch and ring
+/// is closed.
+fn add_tokens<AtomPolicy: SmilesAtomPolicy>(
+    mut tokens: impl Iterator<Item = Result<TokenWithSpan, SmilesErrorWithSpan>>,
+    options: &ParserOptions,
+    parser_state: &mut ParserState<AtomPolicy>,
+    mut on_token: impl FnMut(&TokenWithSpan),
+) -> Result<(), SmilesErrorWithSpan> {
+    parser_state.strict_quadruple_bonds = options.strict_quadruple_bonds;
     let mut previous = None;
     let mut current = next_token(&mut tokens)?;
     let mut next = next_token(&mut tokens)?;
 
     while let Some(token_with_span) = current.take() {
+        on_token(&token_with_span);
         let (start, end) = (token_with_span.start(), token_with_span.end());
         let token = token_with_span.token();
         let token_kind = token.kind();
@@ -54,7 +175,13 @@ pub(crate) fn parse_smiles_with_policy<AtomPolicy: SmilesAtomPolicy>(
 
         parser_state.update_last_span((start, end));
         match token {
-            Token::Atom(atom) => parser_state.add_atom(atom, start, end)?,
+            Token::Atom(atom) => {
+                options
+                    .profile
+                    .check_atom(&atom)
+                    .map_err(|error| SmilesErrorWithSpan::new(error, start, end))?;
+                parser_state.add_atom(atom, start, end)?;
+            }
             Token::Bond(bond) => {
                 parser_state.validate_and_add_bond(start, end, bond, next_kind)?;
             }
@@ -65,12 +192,15 @@ pub(crate) fn parse_smiles_with_policy<AtomPolicy: SmilesAtomPolicy>(
                 parser_state.validate_component_boundary()?;
                 ParserState::<AtomPolicy>::validate_non_bond(previous, next_kind, start, end)?;
             }
-            Token::RingClosure(ring_num) => {
+            Token::RingClosure { ring_num, .. } => {
                 parser_state.validate_and_add_ring_num(start, end, ring_num)?;
             }
             Token::RightParentheses => {
                 parser_state.validate_branch_close(start, end)?;
             }
+            Token::UnknownBracket(..) => {
+                return Err(SmilesErrorWithSpan::new(SmilesError::UnknownBracket, start, end));
+            }
         }
 
         previous = Some(token_kind);
@@ -78,10 +208,27 @@ pub(crate) fn parse_smiles_with_policy<AtomPolicy: SmilesAtomPolicy>(
         next = next_token(&mut tokens)?;
     }
 
-    parser_state.validate_all_closed()?;
-    Ok(parser_state.into_smiles())
+    parser_state.validate_all_closed()
+}
+
+/// Attaches a non-empty annotation as the name of the graph.
+fn annotated<AtomPolicy: SmilesAtomPolicy>(
+    mut smiles: Smiles<AtomPolicy>,
+    annotation: &str,
+) -> Smiles<AtomPolicy> {
+    if !annotation.is_empty() {
+        smiles.set_name(annotation);
+    }
+    smiles
 }
 
+/// Branch nesting depth kept on the stack before [`ParserState`] spills to
+/// the heap.
+const INLINE_BRANCH_DEPTH: usize = 32;
+
+/// The stack of branch anchor atoms, inline up to [`INLINE_BRANCH_DEPTH`].
+pub(crate) type BranchStack = SmallVec<[usize; INLINE_BRANCH_DEPTH]>;
+
 /// Structure containing parser state.
 struct ParserState<AtomPolicy = crate::smiles::ConcreteAtoms> {
     /// Nodes accumulated during parsing.
@@ -93,12 +240,19 @@ struct ParserState<AtomPolicy = crate::smiles::ConcreteAtoms> {
     /// A pending bond that needs to be connected to a second atom
     pending_bond: Option<BondDescriptor>,
     /// The stack of branch anchor atoms
-    branch_stack: Vec<usize>,
-    /// Open ring closures indexed by ring label.
-    ring_open: [Option<(usize, Option<BondDescriptor>)>; 100],
+    branch_stack: BranchStack,
+    /// Open ring closures keyed by ring label, with the bond written before
+    /// the opening digit. A `/` or `\` kept here becomes the direction of the
+    /// ring bond unless the closing digit spells its own bond, so alkene
+    /// stereo written across ring closures survives parsing.
+    ring_table: RingTable,
     /// Parsed lexical stereo neighbor order per atom, preserving ring-digit
     /// position.
     parsed_stereo_neighbors: Vec<Vec<PendingStereoNeighbor>>,
+    /// Edge keys of the bonds written as a plain `-`.
+    explicit_single_bonds: Vec<(usize, usize)>,
+    /// Whether quadruple bonds must join two metals able to form them.
+    strict_quadruple_bonds: bool,
     /// The last used span
     last_span: (usize, usize),
     atom_policy: PhantomData<fn() -> AtomPolicy>,
@@ -115,14 +269,50 @@ impl<AtomPolicy: SmilesAtomPolicy> ParserState<AtomPolicy> {
     /// Creates a new initial state for the parser.
     #[must_use]
     fn new_for_policy(input_len: usize) -> Self {
+        Self::with_buffers(
+            input_len,
+            BranchStack::new(),
+            BondMatrixBuilder::with_capacity(input_len),
+            RingTable::default(),
+        )
+    }
+    /// Creates a new initial state that borrows the buffers of `scratch`
+    /// until [`ParserState::return_scratch`].
+    #[must_use]
+    fn with_scratch(input_len: usize, scratch: &mut ParserScratch) -> Self {
+        let mut branch_stack = mem::take(&mut scratch.branch_stack);
+        branch_stack.clear();
+        Self::with_buffers(
+            input_len,
+            branch_stack,
+            BondMatrixBuilder::with_seen_edges(input_len, mem::take(&mut scratch.seen_edges)),
+            mem::take(&mut scratch.ring_table).cleared(),
+        )
+    }
+    /// Hands the buffers borrowed by [`ParserState::with_scratch`] back.
+    fn return_scratch(&mut self, scratch: &mut ParserScratch) {
+        scratch.branch_stack = mem::take(&mut self.branch_stack);
+        scratch.seen_edges = self.bond_matrix.take_seen_edges();
+        scratch.ring_table = mem::take(&mut self.ring_table);
+    }
+    /// Creates a new initial state around the given working buffers.
+    #[must_use]
+    fn with_buffers(
+        input_len: usize,
+        branch_stack: BranchStack,
+        bond_matrix: BondMatrixBuilder,
+        ring_table: RingTable,
+    ) -> Self {
         Self {
             atom_nodes: Vec::with_capacity(input_len),
-            bond_matrix: BondMatrixBuilder::with_capacity(input_len),
+            bond_matrix,
             last_atom: None,
             pending_bond: None,
-            branch_stack: Vec::with_capacity(input_len.min(16)),
-            ring_open: [None; 100],
+            branch_stack,
+            ring_table,
             parsed_stereo_neighbors: Vec::with_capacity(input_len),
+            explicit_single_bonds: Vec::new(),
+            strict_quadruple_bonds: false,
             last_span: (0, 0),
             atom_policy: PhantomData,
         }
@@ -162,18 +352,18 @@ impl<AtomPolicy: SmilesAtomPolicy> ParserState<AtomPolicy> {
     fn stack_empty(&self) -> bool {
         self.branch_stack.is_empty()
     }
-    /// Removes and returns the specified ring open field entry if present.
-    fn remove_ring_open(&mut self, ring_num: RingNum) -> Option<(usize, Option<BondDescriptor>)> {
-        self.ring_open[usize::from(ring_num.get())].take()
+    /// Removes and returns the specified open ring if present.
+    fn remove_ring_open(&mut self, ring_num: RingNum) -> Option<OpenRing> {
+        self.ring_table.close(ring_num)
     }
-    /// Checks if the ring open field is currently empty.
+    /// Checks if no ring is currently open.
     #[must_use]
     fn ring_open_empty(&self) -> bool {
-        self.ring_open.iter().all(Option::is_none)
+        self.ring_table.is_empty()
     }
-    /// Inserts the given ring into the ring open field
-    fn insert_ring(&mut self, ring_num: RingNum, pending: (usize, Option<BondDescriptor>)) {
-        self.ring_open[usize::from(ring_num.get())] = Some(pending);
+    /// Opens the given ring at `pending`.
+    fn insert_ring(&mut self, ring_num: RingNum, pending: OpenRing) {
+        self.ring_table.open(ring_num, pending);
     }
     #[must_use]
     fn nodes(&self) -> &[Atom] {
@@ -183,6 +373,7 @@ impl<AtomPolicy: SmilesAtomPolicy> ParserState<AtomPolicy> {
     #[must_use]
     fn into_smiles(self) -> Smiles<AtomPolicy> {
         let number_of_nodes = self.atom_nodes.len();
+        stage_span!(DEBUG, "smiles.build", atoms = number_of_nodes);
         let parsed_stereo_neighbors = self
             .parsed_stereo_neighbors
             .into_iter()
@@ -244,6 +435,18 @@ impl<AtomPolicy: SmilesAtomPolicy> ParserState<AtomPolicy> {
             .unwrap_or_else(|| unreachable!("ring opening placeholder must exist"));
         *slot = PendingStereoNeighbor::Atom(neighbor);
     }
+    /// Records the edge between `node_a` and `node_b` as written with `-`
+    /// when `written`, the bond spelled in the input, is a plain single bond.
+    fn record_written_bond(
+        &mut self,
+        node_a: usize,
+        node_b: usize,
+        written: Option<BondDescriptor>,
+    ) {
+        if written == Some(Bond::Single.into()) {
+            self.explicit_single_bonds.push(edge_key(node_a, node_b));
+        }
+    }
     #[inline]
     fn push_edge_verified(
         &mut self,
@@ -252,6 +455,15 @@ impl<AtomPolicy: SmilesAtomPolicy> ParserState<AtomPolicy> {
         bond: BondDescriptor,
         ring_num: Option<RingNum>,
     ) -> Result<(), SmilesError> {
+        if self.strict_quadruple_bonds && bond.bond() == Bond::Quadruple {
+            for node in [node_a, node_b] {
+                if let Some(element) = self.atom_nodes[node].element()
+                    && !can_form_quadruple_bond(element)
+                {
+                    return Err(SmilesError::ImplausibleQuadrupleBond(element));
+                }
+            }
+        }
         self.bond_matrix.push_edge_with_descriptor(node_a, node_b, bond, ring_num)
     }
     /// Adds an atom to the SMILES graph, either bracketed or unbracketed.
@@ -298,9 +510,11 @@ impl<AtomPolicy: SmilesAtomPolicy> ParserState<AtomPolicy> {
         *self.parsed_stereo_neighbors.last_mut().unwrap_or_else(|| unreachable!()) =
             stereo_neighbors;
         if let Some(prev) = previous_atom {
-            let bond = self.pending_bond().unwrap_or_else(|| default_bond(self.nodes(), prev, id));
+            let written = self.pending_bond();
+            let bond = written.unwrap_or_else(|| default_bond(self.nodes(), prev, id));
             self.push_edge_verified(prev, id, bond, None)
                 .map_err(|e| SmilesErrorWithSpan::new(e, start, end))?;
+            self.record_written_bond(prev, id, written);
             self.append_stereo_neighbor(prev, PendingStereoNeighbor::Atom(id));
         }
         self.update_last_atom(Some(id));
@@ -361,41 +575,71 @@ impl<AtomPolicy: SmilesAtomPolicy> ParserState<AtomPolicy> {
     /// the graph.
     ///
     /// # Errors
+    /// - Returns [`SmilesError::LeadingRingClosure`] if no atom has been parsed
+    ///   yet.
     /// - Returns [`SmilesError::InvalidRingNumber`] if a previous atom for the
     ///   bond is not found or a relevant edge between the vertices is not
     ///   found.
     /// - Returns [`SmilesError::NodeIdInvalid`] if a node cannot be found in
     ///   the edge list
+    /// - Returns [`SmilesError::RingBondMismatch`] if both ends of the closure
+    ///   spell a bond and their orders differ. Directional bonds count as
+    ///   single bonds.
     fn validate_and_add_ring_num(
         &mut self,
         start: usize,
         end: usize,
         ring_num: RingNum,
     ) -> Result<(), SmilesErrorWithSpan> {
+        stage_span!(
+            TRACE,
+            "smiles.ring_closure",
+            ring = ring_num.get(),
+            closures = self.ring_table.closures(ring_num)
+        );
         let Some(current) = self.last_atom() else {
-            return Err(SmilesErrorWithSpan::new(SmilesError::InvalidRingNumber, start, end));
+            let error = if self.nodes().is_empty() {
+                SmilesError::LeadingRingClosure(ring_num)
+            } else {
+                SmilesError::InvalidRingNumber
+            };
+            return Err(SmilesErrorWithSpan::new(error, start, end));
         };
-        if let Some((other, stored_bond)) = self.remove_ring_open(ring_num) {
+        if let Some(OpenRing { atom: other, bond: stored_bond }) = self.remove_ring_open(ring_num) {
             if current == other {
                 return Err(SmilesErrorWithSpan::new(SmilesError::InvalidRingNumber, start, end));
             }
             if self.edge_for_node_pair_exists((current, other)) {
                 return Err(SmilesErrorWithSpan::new(SmilesError::InvalidRingNumber, start, end));
             }
-            let bond = self
-                .pending_bond()
-                .or(stored_bond)
-                .unwrap_or_else(|| default_bond(self.nodes(), current, other));
+            let written = self.pending_bond().or(stored_bond);
+            let bond = match (stored_bond, self.pending_bond()) {
+                (Some(opening), Some(closing))
+                    if opening.bond().without_direction() != closing.bond().without_direction() =>
+                {
+                    return Err(SmilesErrorWithSpan::new(
+                        SmilesError::RingBondMismatch(opening.bond(), closing.bond()),
+                        start,
+                        end,
+                    ));
+                }
+                (opening, closing) => {
+                    closing
+                        .or(opening)
+                        .unwrap_or_else(|| default_bond(self.nodes(), current, other))
+                }
+            };
 
             self.push_edge_verified(current, other, bond, Some(ring_num))
                 .map_err(|e| SmilesErrorWithSpan::new(e, start, end))?;
+            self.record_written_bond(current, other, written);
             self.append_stereo_neighbor(current, PendingStereoNeighbor::Atom(other));
             self.resolve_ring_label_neighbor(other, ring_num, current);
 
             self.update_pending_bond(None);
         } else {
             self.append_stereo_neighbor(current, PendingStereoNeighbor::RingLabel(ring_num));
-            self.insert_ring(ring_num, (current, self.pending_bond()));
+            self.insert_ring(ring_num, OpenRing { atom: current, bond: self.pending_bond() });
             self.update_pending_bond(None);
         }
 
@@ -462,10 +706,13 @@ impl<AtomPolicy: SmilesAtomPolicy> ParserState<AtomPolicy> {
     /// updates the pending bond field with the bond.
     ///
     /// # Errors
+    /// - Returns [`SmilesError::LeadingBond`] if no atom has been parsed yet.
     /// - Returns [`SmilesError::IncompleteBond`] if a previous atom is not
     ///   found.
     /// - Returns [`SmilesError::InvalidBond`] if bond is not binding two valid
     ///   nodes
+    /// - Returns [`SmilesError::DanglingBond`] if the bond is followed by `.`,
+    ///   `)` or the end of the input.
     fn validate_and_add_bond(
         &mut self,
         start: usize,
@@ -474,12 +721,21 @@ impl<AtomPolicy: SmilesAtomPolicy> ParserState<AtomPolicy> {
         next_token: Option<TokenKind>,
     ) -> Result<(), SmilesErrorWithSpan> {
         if self.last_atom().is_none() {
-            return Err(SmilesErrorWithSpan::new(SmilesError::IncompleteBond(bond), start, end));
+            let error = if self.nodes().is_empty() {
+                SmilesError::LeadingBond(bond)
+            } else {
+                SmilesError::IncompleteBond(bond)
+            };
+            return Err(SmilesErrorWithSpan::new(error, start, end));
         }
-        if let Some(token) = next_token
-            && matches!(token, TokenKind::Bond | TokenKind::LeftParentheses)
-        {
-            return Err(SmilesErrorWithSpan::new(SmilesError::InvalidBond, start, end));
+        match next_token {
+            Some(TokenKind::Bond | TokenKind::LeftParentheses | TokenKind::Invalid) => {
+                return Err(SmilesErrorWithSpan::new(SmilesError::InvalidBond, start, end));
+            }
+            None | Some(TokenKind::NonBond | TokenKind::RightParentheses) => {
+                return Err(SmilesErrorWithSpan::new(SmilesError::DanglingBond(bond), start, end));
+            }
+            Some(TokenKind::Atom | TokenKind::RingClosure | TokenKind::UnknownBracket) => {}
         }
         self.update_pending_bond(Some(bond));
         Ok(())
@@ -541,6 +797,13 @@ fn validate_concrete_isotope(
         .map_err(|_| SmilesErrorWithSpan::new(SmilesError::InvalidIsotope, start, end))
 }
 
+/// Returns whether `element` is a transition metal of groups 5 to 9, the
+/// elements known to form metal-metal quadruple bonds, as in
+/// `[Mo]$[Mo]` or `[Re]$[Re]`.
+fn can_form_quadruple_bond(element: Element) -> bool {
+    matches!(u8::from(element), 23..=27 | 41..=45 | 73..=77)
+}
+
 #[cfg(test)]
 impl ParserState {
     #[must_use]
@@ -569,7 +832,11 @@ mod tests {
         Smiles, SmilesError,
         atom::{Atom, atom_symbol::AtomSymbol},
         bond::{Bond, BondDescriptor, ring_num::RingNum},
-        parser::smiles_parser::{ParserState, default_bond},
+        parser::{
+            ring_table::OpenRing,
+            smiles_parser::{ParserState, default_bond, parse_smiles_with_options},
+        },
+        smiles::{ParserOptions, WildcardAtoms},
         token::TokenKind,
     };
 
@@ -625,7 +892,7 @@ mod tests {
         state.push_stack(3);
 
         assert!(!state.stack_empty());
-        assert_eq!(state.branch_stack, [1, 3]);
+        assert_eq!(state.branch_stack.as_slice(), [1, 3]);
         assert_eq!(state.pop_branch_stack(), Some(3));
         assert_eq!(state.pop_branch_stack(), Some(1));
         assert_eq!(state.pop_branch_stack(), None);
@@ -640,9 +907,12 @@ mod tests {
         assert!(state.ring_open_empty());
         assert_eq!(state.remove_ring_open(ring), None);
 
-        state.insert_ring(ring, (9, Some(Bond::Double.into())));
+        state.insert_ring(ring, OpenRing { atom: 9, bond: Some(Bond::Double.into()) });
         assert!(!state.ring_open_empty());
-        assert_eq!(state.remove_ring_open(ring), Some((9, Some(Bond::Double.into()))));
+        assert_eq!(
+            state.remove_ring_open(ring),
+            Some(OpenRing { atom: 9, bond: Some(Bond::Double.into()) })
+        );
         assert!(state.ring_open_empty());
     }
 
@@ -746,7 +1016,7 @@ mod tests {
     fn parser_state_validate_all_closed_errors_for_unclosed_ring() {
         let mut state = ParserState::new(0);
         state.update_last_span((2, 3));
-        state.insert_ring(RingNum::try_new(1).unwrap(), (0, None));
+        state.insert_ring(RingNum::try_new(1).unwrap(), OpenRing { atom: 0, bond: None });
 
         let err = state.validate_all_closed().expect_err("expected unclosed ring");
 
@@ -759,7 +1029,7 @@ mod tests {
     fn parser_state_validate_component_boundary_allows_open_ring_labels() {
         let mut state = ParserState::new(0);
         state.update_last_span((2, 3));
-        state.insert_ring(RingNum::try_new(1).unwrap(), (0, None));
+        state.insert_ring(RingNum::try_new(1).unwrap(), OpenRing { atom: 0, bond: None });
         state.update_last_atom(Some(0));
 
         state.validate_component_boundary().unwrap();
@@ -836,14 +1106,38 @@ mod tests {
         let mut state = ParserState::new(0);
         state.update_last_atom(Some(0));
 
-        state.validate_and_add_bond(1, 2, BondDescriptor::aromatic(Bond::Single), None).unwrap();
+        state
+            .validate_and_add_bond(
+                1,
+                2,
+                BondDescriptor::aromatic(Bond::Single),
+                Some(TokenKind::Atom),
+            )
+            .unwrap();
 
         assert_eq!(state.pending_bond(), Some(BondDescriptor::aromatic(Bond::Single)));
     }
 
+    #[test]
+    fn bonds_followed_by_a_dot_close_paren_or_end_are_dangling() {
+        for (input, bond, span) in [
+            ("CC=", Bond::Double, 2..3),
+            ("C#.N", Bond::Triple, 1..2),
+            ("CC(C-)O", Bond::Single, 4..5),
+            ("C(=)=O", Bond::Double, 2..3),
+            ("C1CC/", Bond::Up, 4..5),
+        ] {
+            let err = input.parse::<crate::smiles::Smiles>().unwrap_err();
+            assert_eq!(err.smiles_error(), SmilesError::DanglingBond(bond.into()), "{input}");
+            assert_eq!(err.span(), span, "{input}");
+        }
+        assert!("C=1CCCCC1".parse::<crate::smiles::Smiles>().is_ok());
+    }
+
     #[test]
     fn parser_state_validate_and_add_bond_errors_without_left_atom() {
         let mut state = ParserState::new(0);
+        state.push_node(atom(Element::C, false));
 
         let err = state
             .validate_and_add_bond(1, 2, Bond::Single.into(), None)
@@ -866,7 +1160,10 @@ mod tests {
         state.validate_and_add_ring_num(1, 2, ring).unwrap();
 
         assert_eq!(state.pending_bond(), None);
-        assert_eq!(state.remove_ring_open(ring), Some((0, Some(Bond::Double.into()))));
+        assert_eq!(
+            state.remove_ring_open(ring),
+            Some(OpenRing { atom: 0, bond: Some(Bond::Double.into()) })
+        );
     }
 
     #[test]
@@ -876,7 +1173,7 @@ mod tests {
 
         state.push_node(atom(Element::C, false));
         state.push_node(atom(Element::N, false));
-        state.insert_ring(ring, (0, Some(Bond::Triple.into())));
+        state.insert_ring(ring, OpenRing { atom: 0, bond: Some(Bond::Triple.into()) });
         state.update_last_atom(Some(1));
 
         state.validate_and_add_ring_num(2, 3, ring).unwrap();
@@ -891,25 +1188,70 @@ mod tests {
     }
 
     #[test]
-    fn parser_state_validate_and_add_ring_num_prefers_current_pending_bond() {
+    fn parser_state_validate_and_add_ring_num_rejects_mismatched_bond_orders() {
         let mut state = ParserState::new(0);
         let ring = RingNum::try_new(5).unwrap();
 
         state.push_node(atom(Element::C, false));
         state.push_node(atom(Element::O, false));
-        state.insert_ring(ring, (0, Some(Bond::Double.into())));
+        state.insert_ring(ring, OpenRing { atom: 0, bond: Some(Bond::Double.into()) });
         state.update_last_atom(Some(1));
         state.update_pending_bond(Some(Bond::Quadruple.into()));
 
+        let err = state.validate_and_add_ring_num(2, 3, ring).expect_err("expected mismatch");
+
+        assert_eq!(
+            err.smiles_error(),
+            SmilesError::RingBondMismatch(Bond::Double, Bond::Quadruple)
+        );
+        assert_eq!((err.start(), err.end()), (2, 3));
+    }
+
+    #[test]
+    fn parser_state_validate_and_add_ring_num_prefers_current_pending_bond() {
+        let mut state = ParserState::new(0);
+        let ring = RingNum::try_new(5).unwrap();
+
+        state.push_node(atom(Element::C, false));
+        state.push_node(atom(Element::O, false));
+        state.insert_ring(ring, OpenRing { atom: 0, bond: Some(Bond::Single.into()) });
+        state.update_last_atom(Some(1));
+        state.update_pending_bond(Some(Bond::Up.into()));
+
         state.validate_and_add_ring_num(2, 3, ring).unwrap();
 
         let smiles = state.into_smiles();
-        assert_eq!(smiles.edge_for_node_pair((0, 1)).unwrap().bond(), Bond::Quadruple);
+        assert_eq!(smiles.edge_for_node_pair((0, 1)).unwrap().bond(), Bond::Up);
+    }
+
+    #[test]
+    fn ring_closure_bond_orders_must_agree_at_both_ends() {
+        for accepted in ["C=1CCCCC=1", "C=1CCCCC1", "C1CCCCC=1", "C-1CCCCC1", "c:1ccccc:1"] {
+            assert!(accepted.parse::<crate::smiles::Smiles>().is_ok(), "{accepted}");
+        }
+        let err = "C=1CCCCC#1".parse::<crate::smiles::Smiles>().unwrap_err();
+        assert_eq!(err.smiles_error(), SmilesError::RingBondMismatch(Bond::Double, Bond::Triple));
+        assert_eq!(err.span(), 9..10);
+    }
+
+    #[test]
+    fn leading_bonds_ring_closures_and_close_parens_have_dedicated_errors() {
+        for (input, error, span) in [
+            ("=CC", SmilesError::LeadingBond(Bond::Double.into()), 0..1),
+            ("1CC1", SmilesError::LeadingRingClosure(RingNum::try_new(1).unwrap()), 0..1),
+            ("%12CC%12", SmilesError::LeadingRingClosure(RingNum::try_new(12).unwrap()), 0..3),
+            (")CC", SmilesError::UnexpectedRightParentheses, 0..1),
+        ] {
+            let err = input.parse::<crate::smiles::Smiles>().unwrap_err();
+            assert_eq!(err.smiles_error(), error, "{input}");
+            assert_eq!(err.span(), span, "{input}");
+        }
     }
 
     #[test]
     fn parser_state_validate_and_add_ring_num_errors_without_current_atom() {
         let mut state = ParserState::new(0);
+        state.push_node(atom(Element::C, false));
         let ring = RingNum::try_new(1).unwrap();
 
         let err =
@@ -926,7 +1268,7 @@ mod tests {
         let ring = RingNum::try_new(2).unwrap();
 
         state.push_node(atom(Element::C, false));
-        state.insert_ring(ring, (0, None));
+        state.insert_ring(ring, OpenRing { atom: 0, bond: None });
         state.update_last_atom(Some(0));
 
         let err = state
@@ -946,7 +1288,7 @@ mod tests {
         state.push_node(atom(Element::C, false));
         state.push_node(atom(Element::O, false));
         state.push_edge_verified(0, 1, Bond::Single.into(), None).unwrap();
-        state.insert_ring(ring, (0, None));
+        state.insert_ring(ring, OpenRing { atom: 0, bond: None });
         state.update_last_atom(Some(1));
 
         let err = state
@@ -969,6 +1311,28 @@ mod tests {
         assert_eq!(right.number_of_bonds(), 4);
     }
 
+    #[test]
+    fn strict_quadruple_bonds_need_two_capable_metals() {
+        let options = ParserOptions { strict_quadruple_bonds: true, ..ParserOptions::default() };
+        for source in ["[Mo]$[Mo]", "[Cr]$[Cr]", "[Re]1[Re]$1", "[W]$*"] {
+            assert!(
+                parse_smiles_with_options::<WildcardAtoms>(source, &options).is_ok(),
+                "{source}"
+            );
+        }
+        for (source, element, span) in [
+            ("C$C", Element::C, (2, 3)),
+            ("[Mo]$C", Element::C, (5, 6)),
+            ("[Zn]$[Zn]", Element::Zn, (5, 9)),
+            ("C1CC$1", Element::C, (5, 6)),
+        ] {
+            let error = parse_smiles_with_options::<WildcardAtoms>(source, &options).unwrap_err();
+            assert_eq!(error.smiles_error(), SmilesError::ImplausibleQuadrupleBond(element));
+            assert_eq!((error.start(), error.end()), span);
+        }
+        assert!("C$C".parse::<Smiles>().is_ok());
+    }
+
     #[test]
     fn default_bond_is_aromatic_for_two_aromatic_atoms() {
         let nodes = vec![atom(Element::C, true), atom(Element::N, true)];
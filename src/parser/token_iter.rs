@@ -1,7 +1,10 @@
 //! Submodule creating the `TokenIter` struct, which is an iterator over
 //! the `Token`s found in a provided string.
 
-use core::str::{FromStr, from_utf8};
+use core::{
+    ops::Range,
+    str::{FromStr, from_utf8},
+};
 
 use elements_rs::Element;
 
@@ -9,11 +12,11 @@ use crate::{
     atom::{
         Atom,
         atom_symbol::AtomSymbol,
-        bracketed::{charge::Charge, chirality::Chirality},
+        bracketed::{charge::Charge, chirality::Chirality, hydrogen_count::HydrogenCount},
     },
-    bond::{Bond, ring_num::RingNum},
+    bond::{Bond, BondDescriptor, ring_num::RingNum},
     errors::{SmilesError, SmilesErrorWithSpan},
-    token::{Token, TokenWithSpan},
+    token::{BracketFieldSpans, Token, TokenWithSpan},
 };
 
 /// An iterator over the tokens found in a SMILES string.
@@ -26,12 +29,87 @@ pub(crate) struct TokenIter<'a> {
     in_bracket: bool,
     /// The length of the input
     len: usize,
+    /// Whether malformed bracket atoms become [`Token::UnknownBracket`]
+    /// instead of errors.
+    permissive: bool,
+    /// Whether `se` and `as` are accepted outside brackets.
+    unbracketed_se_as: bool,
+    /// Whether the fields after a bracket atom's symbol may come in any
+    /// order.
+    lenient_bracket_order: bool,
+    /// Whether bracket hydrogen counts may have several digits.
+    multi_digit_hydrogen_counts: bool,
+    /// Start of the bracket field an error points at, when it is narrower
+    /// than the whole token.
+    field_error_start: Option<usize>,
+    /// Offset the previous token or error stopped at, where the next token
+    /// must start.
+    previous_end: usize,
+    /// Number of tokens yielded so far, the index of the next one.
+    token_count: usize,
+    /// The bond yielded as the previous token, if it was one.
+    previous_bond: Option<BondDescriptor>,
+    /// Index of the token that opened each ring label still open.
+    open_rings: [Option<usize>; 100],
+    /// The field spans of the bracket atom just read, handed to its token.
+    bracket_field_spans: Option<BracketFieldSpans>,
 }
 
 impl<'a> From<&'a str> for TokenIter<'a> {
     #[inline]
     fn from(s: &'a str) -> Self {
-        TokenIter { bytes: s.as_bytes(), position: 0, in_bracket: false, len: s.len() }
+        TokenIter {
+            bytes: s.as_bytes(),
+            position: 0,
+            in_bracket: false,
+            len: s.len(),
+            permissive: false,
+            unbracketed_se_as: false,
+            lenient_bracket_order: false,
+            multi_digit_hydrogen_counts: false,
+            field_error_start: None,
+            previous_end: 0,
+            token_count: 0,
+            previous_bond: None,
+            open_rings: [None; 100],
+            bracket_field_spans: None,
+        }
+    }
+}
+
+impl<'a> TokenIter<'a> {
+    /// Creates an iterator that captures malformed but closed bracket atoms
+    /// as [`Token::UnknownBracket`].
+    #[inline]
+    pub(crate) fn permissive(s: &'a str) -> Self {
+        TokenIter { permissive: true, ..Self::from(s) }
+    }
+
+    /// Sets whether `se` and `as` written outside brackets are read as the
+    /// bracket atoms `[se]` and `[as]` instead of failing with
+    /// [`SmilesError::UnbracketedAromaticElement`].
+    #[inline]
+    pub(crate) fn with_unbracketed_se_as(self, unbracketed_se_as: bool) -> Self {
+        TokenIter { unbracketed_se_as, ..self }
+    }
+
+    /// Sets whether the chirality, hydrogen count, charge and class of a
+    /// bracket atom are accepted in any order, as in `[O-H]`, instead of only
+    /// in the order of the specification.
+    #[inline]
+    pub(crate) fn with_lenient_bracket_order(self, lenient_bracket_order: bool) -> Self {
+        TokenIter { lenient_bracket_order, ..self }
+    }
+
+    /// Sets whether bracket hydrogen counts up to
+    /// [`HydrogenCount::MAX_MULTI_DIGIT`] are accepted, as in `[CH12]`,
+    /// instead of only the single digit of the specification.
+    #[inline]
+    pub(crate) fn with_multi_digit_hydrogen_counts(
+        self,
+        multi_digit_hydrogen_counts: bool,
+    ) -> Self {
+        TokenIter { multi_digit_hydrogen_counts, ..self }
     }
 }
 
@@ -49,38 +127,28 @@ impl TokenIter<'_> {
                 if self.in_bracket {
                     return Err(SmilesError::UnexpectedLeftBracket);
                 }
-                self.in_bracket = true;
-                let isotope_mass_number = if let Some(isotope) = try_fold_number::<u16, 3>(self) {
-                    Some(isotope?)
-                } else {
-                    None
-                };
-                let (symbol, aromatic) = try_element(self)?;
-                let chirality = try_chirality(self)?;
-                let hydrogens = hydrogen_count(self)?;
-                let charge = try_charge(self)?;
-                let class = try_class(self)?;
-                let atom = Atom::new_bracket(
-                    symbol,
-                    isotope_mass_number,
-                    aromatic,
-                    hydrogens,
-                    charge,
-                    class,
-                    chirality,
-                );
-                if self.peek_byte() == Some(b']') {
-                    self.in_bracket = false;
-                    let _ = self.next_byte();
-                    Token::Atom(atom)
-                } else {
-                    return Err(SmilesError::UnclosedBracket);
-                }
+                let (atom, spans) = self.parse_bracket_atom_with_spans()?;
+                self.bracket_field_spans = Some(spans);
+                Token::Atom(atom)
             }
             c if c.is_ascii_alphabetic() || c == b'*' => {
                 if self.in_bracket {
                     return Err(SmilesError::UnexpectedBracketedState);
                 }
+                if let Some(element) = try_bracket_only_aromatic_from_first(self, c) {
+                    if !self.unbracketed_se_as {
+                        return Err(SmilesError::UnbracketedAromaticElement(element));
+                    }
+                    return Ok(Token::Atom(Atom::new_bracket(
+                        AtomSymbol::Element(element),
+                        None,
+                        true,
+                        0,
+                        Charge::default(),
+                        0,
+                        None,
+                    )));
+                }
                 let (symbol, aromatic) = if let Some(atom) = try_organic_subset_from_first(self, c)
                 {
                     atom?
@@ -105,12 +173,12 @@ impl TokenIter<'_> {
                         if ring_num.get() < 10 {
                             return Err(SmilesError::InvalidRingNumber);
                         }
-                        Token::RingClosure(ring_num)
+                        self.ring_closure(ring_num)
                     } else {
                         return Err(SmilesError::InvalidRingNumber);
                     }
                 } else {
-                    Token::RingClosure(RingNum::try_new(n - b'0')?)
+                    self.ring_closure(RingNum::try_new(n - b'0')?)
                 }
             }
             b'-' | b'=' | b'#' | b'$' | b':' | b'/' | b'\\' => {
@@ -133,6 +201,155 @@ impl TokenIter<'_> {
         Ok(token)
     }
 
+    /// Returns the ring closure token for `ring_num`, pairing it with the
+    /// label that opened the ring if it is open.
+    fn ring_closure(&mut self, ring_num: RingNum) -> Token {
+        let slot = &mut self.open_rings[usize::from(ring_num.get())];
+        let partner = slot.take();
+        if partner.is_none() {
+            *slot = Some(self.token_count);
+        }
+        Token::RingClosure { ring_num, bond: self.previous_bond, partner }
+    }
+
+    /// Parses the fields of the bracket atom whose `[` was just consumed,
+    /// through the closing `]`.
+    fn parse_bracket_atom(&mut self) -> Result<Atom, SmilesError> {
+        self.parse_bracket_atom_with_spans().map(|(atom, _)| atom)
+    }
+
+    /// Parses the bracket atom whose `[` was just consumed, through the
+    /// closing `]`, and the spans of the fields it is written with.
+    fn parse_bracket_atom_with_spans(&mut self) -> Result<(Atom, BracketFieldSpans), SmilesError> {
+        self.in_bracket = true;
+        let mut spans = BracketFieldSpans::default();
+        let isotope_mass_number = self
+            .try_field(&mut spans.isotope, |stream| try_fold_number::<u16, 3>(stream).transpose())?
+            .flatten();
+        let symbol_start = self.position;
+        let (symbol, aromatic) = try_element(self)?;
+        spans.symbol = symbol_start..self.position;
+        let (chirality, hydrogens, charge, class) = if self.lenient_bracket_order {
+            self.parse_bracket_fields_in_any_order(&mut spans)?
+        } else {
+            let chirality = self.try_field(&mut spans.chirality, try_chirality)?.flatten();
+            let hydrogens = self.try_field(&mut spans.hydrogens, hydrogen_count)?;
+            if spans.isotope.is_some() && self.at_unsigned_number() {
+                return Err(self.repeated_field_error(&spans));
+            }
+            let charge = self.try_field(&mut spans.charge, try_charge)?;
+            let class = self.try_field(&mut spans.class, try_class)?;
+            (chirality, hydrogens, charge, class)
+        };
+        if self.peek_byte() != Some(b']') {
+            return Err(self.repeated_field_error(&spans));
+        }
+        let _ = self.next_byte();
+        self.in_bracket = false;
+        let atom = Atom::new_bracket(
+            symbol,
+            isotope_mass_number,
+            aromatic,
+            hydrogens.unwrap_or(0),
+            charge.unwrap_or_default(),
+            class.unwrap_or(0),
+            chirality,
+        );
+        Ok((atom, spans))
+    }
+
+    /// Runs `parse` and returns its value, or `None` when it consumed
+    /// nothing because the field is absent. The consumed span is stored in
+    /// `span`.
+    fn try_field<T>(
+        &mut self,
+        span: &mut Option<Range<usize>>,
+        parse: impl FnOnce(&mut Self) -> Result<T, SmilesError>,
+    ) -> Result<Option<T>, SmilesError> {
+        let start = self.position;
+        let value = parse(self)?;
+        if self.position == start {
+            return Ok(None);
+        }
+        *span = Some(start..self.position);
+        Ok(Some(value))
+    }
+
+    /// Returns whether the next bytes are digits not followed by a charge
+    /// sign, which cannot start a charge such as `2+`.
+    fn at_unsigned_number(&self) -> bool {
+        let digits =
+            self.bytes[self.position..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+        digits > 0 && !matches!(self.bytes.get(self.position + digits), Some(b'+' | b'-'))
+    }
+
+    /// Returns the error for the unexpected byte ending the fields of a
+    /// bracket atom, given the fields read so far.
+    ///
+    /// A second isotope, hydrogen count or charge is consumed and reported
+    /// with a span covering only that field; anything else leaves the
+    /// bracket unclosed.
+    fn repeated_field_error(&mut self, spans: &BracketFieldSpans) -> SmilesError {
+        let start = self.position;
+        let error = match self.peek_byte() {
+            Some(b'0'..=b'9') if spans.isotope.is_some() => {
+                while self.peek_byte().is_some_and(|byte| byte.is_ascii_digit()) {
+                    self.position += 1;
+                }
+                SmilesError::DuplicateIsotope
+            }
+            Some(b'H') if spans.hydrogens.is_some() => {
+                let _ = hydrogen_count(self);
+                SmilesError::DuplicateHydrogenCount
+            }
+            Some(b'+' | b'-') if spans.charge.is_some() => {
+                let _ = try_charge(self);
+                SmilesError::ConflictingCharge
+            }
+            _ => return SmilesError::UnclosedBracket,
+        };
+        self.field_error_start = Some(start);
+        error
+    }
+
+    /// Parses the chirality, hydrogen count, charge and class of a bracket
+    /// atom in whatever order they are written, each at most once.
+    fn parse_bracket_fields_in_any_order(
+        &mut self,
+        spans: &mut BracketFieldSpans,
+    ) -> Result<(Option<Chirality>, Option<u8>, Option<Charge>, Option<u16>), SmilesError> {
+        let (mut chirality, mut hydrogens, mut charge, mut class) = (None, None, None, None);
+        loop {
+            match self.peek_byte() {
+                Some(b'@') if spans.chirality.is_none() => {
+                    chirality = self.try_field(&mut spans.chirality, try_chirality)?.flatten();
+                }
+                Some(b'H') if spans.hydrogens.is_none() => {
+                    hydrogens = self.try_field(&mut spans.hydrogens, hydrogen_count)?;
+                }
+                Some(b'+' | b'-' | b'0'..=b'9')
+                    if spans.charge.is_none() && !self.at_unsigned_number() =>
+                {
+                    charge = self.try_field(&mut spans.charge, try_charge)?;
+                }
+                Some(b':') if spans.class.is_none() => {
+                    class = self.try_field(&mut spans.class, try_class)?;
+                }
+                _ => break,
+            }
+        }
+        Ok((chirality, hydrogens, charge, class))
+    }
+
+    /// Returns the offset just past the `]` closing the bracket opened at
+    /// `start`, or `None` when another `[` or the end of input comes first.
+    fn unknown_bracket_end(&self, start: usize) -> Option<usize> {
+        let offset =
+            self.bytes[start + 1..].iter().position(|&byte| matches!(byte, b'[' | b']'))?;
+        let close = start + 1 + offset;
+        (self.bytes[close] == b']').then_some(close + 1)
+    }
+
     #[inline]
     fn current_end(&self) -> usize {
         self.position
@@ -155,30 +372,134 @@ impl Iterator for TokenIter<'_> {
     type Item = Result<TokenWithSpan, SmilesErrorWithSpan>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let item = self.next_item()?;
+        match &item {
+            Ok(token) => {
+                debug_assert!(
+                    token.start() == self.previous_end
+                        && token.start() < token.end()
+                        && token.end() <= self.len,
+                    "token spans must tile the input"
+                );
+                self.previous_end = token.end();
+                self.token_count += 1;
+                self.previous_bond = match token.token() {
+                    Token::Bond(bond) => Some(bond),
+                    _ => None,
+                };
+            }
+            Err(_) => {
+                self.previous_end = self.position;
+                self.previous_bond = None;
+            }
+        }
+        Some(item)
+    }
+}
+
+impl TokenIter<'_> {
+    /// Reads the token or error starting at the current position.
+    fn next_item(&mut self) -> Option<Result<TokenWithSpan, SmilesErrorWithSpan>> {
         let start = self.position;
         let current_byte = self.next_byte()?;
         if !current_byte.is_ascii() {
             self.position = (start + utf8_char_width(current_byte)).min(self.len);
-            return Some(Err(SmilesErrorWithSpan::new(
-                SmilesError::UnexpectedUnicodeCharacter,
-                start,
-                self.position,
-            )));
+            return Some(Err(self.non_ascii_error(start)));
         }
-        match self.parse_token(current_byte) {
+        let token = self.parse_token(current_byte);
+        let field_error_start = self.field_error_start.take();
+        let bracket_field_spans = self.bracket_field_spans.take();
+        match token {
             Ok(token) => {
-                let end = self.current_end();
-                Some(Ok(TokenWithSpan::new(token, start, end)))
+                Some(Ok(TokenWithSpan::read(token, start, &self.bytes[start..self.current_end()])
+                    .with_bracket_field_spans(bracket_field_spans)))
+            }
+            Err(_) if self.permissive && current_byte == b'[' => {
+                let Some(close) = self.unknown_bracket_end(start) else {
+                    self.position = self.len;
+                    return Some(Err(SmilesErrorWithSpan::new(
+                        SmilesError::UnclosedBracket,
+                        start,
+                        self.len,
+                    )));
+                };
+                self.position = close;
+                self.in_bracket = false;
+                let text = from_utf8(&self.bytes[start..close])
+                    .unwrap_or_else(|_| unreachable!("brackets are ASCII character boundaries"));
+                Some(Ok(TokenWithSpan::read(
+                    Token::UnknownBracket(text.into(), start..close),
+                    start,
+                    text.as_bytes(),
+                )))
             }
             Err(e) => {
                 let mut end = self.current_end();
                 if end <= start {
                     end = (start + 1).min(self.len);
                 }
-                Some(Err(SmilesErrorWithSpan::new(e, start, end)))
+                if current_byte == b'[' {
+                    let bracket_end = self.unknown_bracket_end(start).unwrap_or(self.len);
+                    if let Some(offset) =
+                        self.bytes[start..bracket_end].iter().position(|byte| !byte.is_ascii())
+                    {
+                        let error = self.non_ascii_error(start + offset);
+                        if matches!(error.smiles_error(), SmilesError::NonAsciiLookalike { .. }) {
+                            return Some(Err(error));
+                        }
+                    }
+                }
+                Some(Err(SmilesErrorWithSpan::new(e, field_error_start.unwrap_or(start), end)))
             }
         }
     }
+
+    /// Returns the error for the non-ASCII character starting at `start`,
+    /// naming the ASCII character it resembles when there is one.
+    fn non_ascii_error(&self, start: usize) -> SmilesErrorWithSpan {
+        let end = (start + utf8_char_width(self.bytes[start])).min(self.len);
+        let found = from_utf8(&self.bytes[start..end]).ok().and_then(|text| text.chars().next());
+        let error = match found.and_then(|found| Some((found, ascii_lookalike(found)?))) {
+            Some((found, suggestion)) => SmilesError::NonAsciiLookalike { found, suggestion },
+            None => SmilesError::UnexpectedUnicodeCharacter,
+        };
+        SmilesErrorWithSpan::new(error, start, end)
+    }
+}
+
+/// Returns the ASCII character that `character` is commonly mistaken for:
+/// Greek and Cyrillic letters shaped like Latin ones, fullwidth forms, dashes
+/// and the minus sign, and the `≡` of drawn triple bonds.
+fn ascii_lookalike(character: char) -> Option<char> {
+    let lookalike = match character {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(u32::from(character) - 0xFEE0)?,
+        '\u{2010}'..='\u{2015}' | '\u{2212}' => '-',
+        '\u{2261}' => '#',
+        // Greek and Cyrillic letters.
+        '\u{391}' | '\u{410}' => 'A',
+        '\u{392}' | '\u{412}' => 'B',
+        '\u{421}' => 'C',
+        '\u{395}' | '\u{415}' => 'E',
+        '\u{397}' | '\u{41D}' => 'H',
+        '\u{399}' | '\u{406}' => 'I',
+        '\u{39A}' | '\u{41A}' => 'K',
+        '\u{39C}' | '\u{41C}' => 'M',
+        '\u{39D}' => 'N',
+        '\u{39F}' | '\u{41E}' => 'O',
+        '\u{3A1}' | '\u{420}' => 'P',
+        '\u{3A4}' | '\u{422}' => 'T',
+        '\u{3A7}' | '\u{425}' => 'X',
+        '\u{3A5}' => 'Y',
+        '\u{396}' => 'Z',
+        '\u{430}' => 'a',
+        '\u{441}' => 'c',
+        '\u{435}' => 'e',
+        '\u{3BF}' | '\u{43E}' => 'o',
+        '\u{440}' => 'p',
+        '\u{455}' => 's',
+        _ => return None,
+    };
+    Some(lookalike)
 }
 
 #[inline]
@@ -222,12 +543,80 @@ fn aromatic_from_element(in_bracket: bool, element: Element) -> Result<bool, Smi
     if allowed { Ok(true) } else { Err(SmilesError::InvalidAromaticElement(element)) }
 }
 
+/// Reads the whole of `text` as one token, for the `TryFrom<&str>` impls of
+/// the token payload types.
+pub(crate) fn single_token(text: &str) -> Result<Token, SmilesError> {
+    let token = TokenIter::from(text)
+        .next()
+        .ok_or(SmilesError::EmptyInput)?
+        .map_err(|error| error.smiles_error())?;
+    trailing_character(text, token.end()).map_or(Ok(token.token()), Err)
+}
+
+/// Parses the bracket atom at the start of `text`, returning it with the
+/// length of its spelling.
+pub(crate) fn parse_bracket_atom_prefix(text: &str) -> Result<(Atom, usize), SmilesError> {
+    let mut stream = TokenIter::from(text);
+    match stream.next_byte() {
+        Some(b'[') => {}
+        Some(_) => {
+            let first = text.chars().next().unwrap_or_else(|| unreachable!());
+            return Err(SmilesError::UnexpectedCharacter(first));
+        }
+        None => return Err(SmilesError::EmptyInput),
+    }
+    let atom = stream.parse_bracket_atom()?;
+    Ok((atom, stream.current_end()))
+}
+
+/// Reads the whole of `text` with `parse`, as the part of a bracket atom it
+/// parses.
+fn bracket_component<T>(
+    text: &str,
+    parse: impl FnOnce(&mut TokenIter<'_>) -> Result<T, SmilesError>,
+) -> Result<T, SmilesError> {
+    let mut stream = TokenIter { in_bracket: true, ..TokenIter::from(text) };
+    let value = parse(&mut stream)?;
+    trailing_character(text, stream.current_end()).map_or(Ok(value), Err)
+}
+
+/// Returns the error for the first character of `text` past `end`, if any.
+fn trailing_character(text: &str, end: usize) -> Option<SmilesError> {
+    text.get(end..)?.chars().next().map(SmilesError::UnexpectedCharacter)
+}
+
+/// Parses a chirality tag such as `@`, `@@` or `@TB12`.
+pub(crate) fn parse_chirality(text: &str) -> Result<Chirality, SmilesError> {
+    if text == "@" {
+        return Ok(Chirality::At);
+    }
+    bracket_component(text, |stream| try_chirality(stream)?.ok_or(SmilesError::InvalidChirality))
+}
+
+/// Parses a charge such as `+`, `--` or `-2`; the empty string is no charge.
+pub(crate) fn parse_charge(text: &str) -> Result<Charge, SmilesError> {
+    bracket_component(text, try_charge)
+}
+
 #[inline]
 fn try_element(stream: &mut TokenIter<'_>) -> Result<(AtomSymbol, bool), SmilesError> {
     let first = stream.next_byte().ok_or(SmilesError::MissingElement)?;
     try_element_from_first(stream, first)
 }
 
+/// Consumes the `e` of `se` or the `s` of `as`, the aromatic spellings that
+/// are only valid inside brackets but that some toolkits write without them.
+#[inline]
+fn try_bracket_only_aromatic_from_first(stream: &mut TokenIter<'_>, byte_1: u8) -> Option<Element> {
+    let element = match (byte_1, stream.peek_byte()?) {
+        (b's', b'e') => Element::Se,
+        (b'a', b's') => Element::As,
+        _ => return None,
+    };
+    let _ = stream.next_byte();
+    Some(element)
+}
+
 #[inline]
 fn try_organic_subset_from_first(
     stream: &mut TokenIter<'_>,
@@ -452,13 +841,6 @@ where
     Some(B::try_from(amount).map_err(|_| SmilesError::IntegerOverflow))
 }
 
-/// Maximum bracket-atom explicit hydrogen count accepted by the parser.
-///
-/// Chosen to mirror the magnitude cap on bracket-atom charges. Real chemistry
-/// SMILES do not require explicit hydrogen counts above this bound, and
-/// bounding the value here keeps downstream `u8` valence math from overflowing.
-const MAX_HYDROGEN_COUNT: u8 = 15;
-
 #[inline]
 fn hydrogen_count(stream: &mut TokenIter<'_>) -> Result<u8, SmilesError> {
     if stream.peek_byte() == Some(b'H') {
@@ -467,10 +849,12 @@ fn hydrogen_count(stream: &mut TokenIter<'_>) -> Result<u8, SmilesError> {
             Some(h) => h?,
             None => 1,
         };
-        if count > MAX_HYDROGEN_COUNT {
-            return Err(SmilesError::HydrogenCountOverflow(count));
-        }
-        Ok(count)
+        let count = if stream.multi_digit_hydrogen_counts {
+            HydrogenCount::try_new_multi_digit(count)?
+        } else {
+            HydrogenCount::try_new(count)?
+        };
+        Ok(count.get())
     } else {
         Ok(0)
     }
@@ -585,18 +969,36 @@ fn repeated_sign_charge(
     }
 }
 
+/// Parses an optional `:class` suffix.
+///
+/// The grammar allows any number of digits, so every digit is consumed and
+/// values that do not fit the `u16` storage are reported as
+/// [`SmilesError::ClassOverflow`] instead of stopping mid-number.
 #[inline]
 fn try_class(stream: &mut TokenIter<'_>) -> Result<u16, SmilesError> {
-    match stream.peek_byte() {
-        Some(b':') => {
-            stream.position += 1;
-            if let Some(possible_num) = try_fold_number::<u16, 3>(stream) {
-                possible_num
-            } else {
-                Err(SmilesError::InvalidClass)
-            }
+    if stream.peek_byte() != Some(b':') {
+        return Ok(0);
+    }
+    stream.position += 1;
+
+    let mut class: u16 = 0;
+    let mut overflowed = false;
+    let mut digits_found = false;
+    while let Some(byte) = stream.peek_byte().filter(u8::is_ascii_digit) {
+        stream.position += 1;
+        digits_found = true;
+        match class.checked_mul(10).and_then(|value| value.checked_add(u16::from(byte - b'0'))) {
+            Some(value) => class = value,
+            None => overflowed = true,
         }
-        _ => Ok(0),
+    }
+
+    if !digits_found {
+        Err(SmilesError::InvalidClass)
+    } else if overflowed {
+        Err(SmilesError::ClassOverflow)
+    } else {
+        Ok(class)
     }
 }
 
@@ -664,6 +1066,7 @@ mod tests {
         },
         bond::{Bond, ring_num::RingNum},
         errors::SmilesError,
+        smiles::Smiles,
         token::Token,
     };
 
@@ -753,7 +1156,7 @@ mod tests {
     #[test]
     fn parse_token_single_digit_ring_closure_success() {
         let token = next_ok("1");
-        assert_eq!(token.token(), Token::RingClosure(RingNum::try_new(1).unwrap()));
+        assert_eq!(token.token(), Token::ring_closure(RingNum::try_new(1).unwrap()));
         assert_eq!(token.span(), 0..1);
     }
 
@@ -900,16 +1303,32 @@ mod tests {
         let mut stream = TokenIter::from("C");
         assert_eq!(hydrogen_count(&mut stream), Ok(0));
 
-        let mut stream = TokenIter::from("H15");
+        let mut stream = TokenIter::from("H9");
+        assert_eq!(hydrogen_count(&mut stream), Ok(9));
+
+        let mut stream = TokenIter::from("H10");
+        assert_eq!(hydrogen_count(&mut stream), Err(SmilesError::HydrogenCountOverflow(10)));
+
+        let mut stream = TokenIter::from("H15").with_multi_digit_hydrogen_counts(true);
         assert_eq!(hydrogen_count(&mut stream), Ok(15));
 
-        let mut stream = TokenIter::from("H16");
+        let mut stream = TokenIter::from("H16").with_multi_digit_hydrogen_counts(true);
         assert_eq!(hydrogen_count(&mut stream), Err(SmilesError::HydrogenCountOverflow(16)));
 
         let mut stream = TokenIter::from("H254");
         assert_eq!(hydrogen_count(&mut stream), Err(SmilesError::HydrogenCountOverflow(254)));
     }
 
+    #[test]
+    fn oversized_hydrogen_counts_are_rejected_by_the_parser() {
+        for (input, count) in [("[CH99]", 99), ("[CH10+]", 10), ("C[NH255]C", 255)] {
+            let error = input.parse::<Smiles>().unwrap_err();
+            assert_eq!(error.smiles_error(), SmilesError::HydrogenCountOverflow(count), "{input}");
+        }
+        assert!("[CH1000]".parse::<Smiles>().is_err());
+        assert!("[CH9]".parse::<Smiles>().is_ok());
+    }
+
     #[test]
     fn try_charge_branches() {
         let mut stream = TokenIter::from("-");
@@ -950,6 +1369,40 @@ mod tests {
 
         let mut stream = TokenIter::from("C");
         assert_eq!(try_class(&mut stream), Ok(0));
+
+        let mut stream = TokenIter::from(":65535]");
+        assert_eq!(try_class(&mut stream), Ok(u16::MAX));
+        assert_eq!(stream.peek_byte(), Some(b']'));
+
+        let mut stream = TokenIter::from(":0001");
+        assert_eq!(try_class(&mut stream), Ok(1));
+
+        for overflowing in [":65536", ":99999", ":1000000000000"] {
+            let mut stream = TokenIter::from(overflowing);
+            assert_eq!(try_class(&mut stream), Err(SmilesError::ClassOverflow), "{overflowing}");
+            assert_eq!(stream.peek_byte(), None, "{overflowing}");
+        }
+    }
+
+    #[test]
+    fn atom_classes_survive_writing_and_canonicalization() {
+        for input in ["[CH3:1][OH:65535]", "[NH4+:1000].[Cl-:42]"] {
+            let smiles: Smiles = input.parse().unwrap();
+            let rendered: Smiles = smiles.render().parse().unwrap();
+            let canonical: Smiles = smiles.canonicalize().render().parse().unwrap();
+            for parsed in [&rendered, &canonical] {
+                let mut classes =
+                    parsed.nodes().iter().map(|atom| atom.class()).collect::<Vec<_>>();
+                classes.sort_unstable();
+                let mut expected =
+                    smiles.nodes().iter().map(|atom| atom.class()).collect::<Vec<_>>();
+                expected.sort_unstable();
+                assert_eq!(classes, expected, "{input}");
+            }
+        }
+
+        let error = "C[C:65536]".parse::<Smiles>().unwrap_err();
+        assert_eq!(error.smiles_error(), SmilesError::ClassOverflow);
     }
 
     #[test]
@@ -989,6 +1442,27 @@ mod tests {
         assert_eq!(err.end(), 2);
     }
 
+    #[test]
+    fn repeated_bracket_fields_point_at_the_repeated_field() {
+        for (input, error, span) in [
+            ("[CH2H3]", SmilesError::DuplicateHydrogenCount, (4, 6)),
+            ("[C++-]", SmilesError::ConflictingCharge, (4, 5)),
+            ("[13C13]", SmilesError::DuplicateIsotope, (4, 6)),
+            ("[NH4+:1-]", SmilesError::ConflictingCharge, (7, 8)),
+        ] {
+            let err = next_err(input);
+            assert_eq!(err.smiles_error(), error, "{input}");
+            assert_eq!((err.start(), err.end()), span, "{input}");
+        }
+        assert_eq!(next_err("[O-H]").smiles_error(), SmilesError::UnclosedBracket);
+        assert_eq!(next_err("[C13]").smiles_error(), SmilesError::UnexpectedCharacter('1'));
+
+        let mut tokens = TokenIter::from("[CH2+H]").with_lenient_bracket_order(true);
+        let err = tokens.next().unwrap().unwrap_err();
+        assert_eq!(err.smiles_error(), SmilesError::DuplicateHydrogenCount);
+        assert_eq!((err.start(), err.end()), (5, 6));
+    }
+
     #[test]
     fn try_chirality_th_form_should_parse() {
         let mut stream = TokenIter::from("@TH1");
@@ -1001,6 +1475,121 @@ mod tests {
         assert_eq!(try_chirality(&mut stream), Ok(Some(Chirality::try_al(2).unwrap())));
     }
 
+    #[test]
+    fn every_extended_chirality_form_parses_within_its_range() {
+        let forms: [(&str, u8, fn(u8) -> Chirality); 5] = [
+            ("TH", 2, Chirality::TH),
+            ("AL", 2, Chirality::AL),
+            ("SP", 3, Chirality::SP),
+            ("TB", 20, Chirality::TB),
+            ("OH", 30, Chirality::OH),
+        ];
+        for (name, max, variant) in forms {
+            for number in 1..=max {
+                let input = format!("[C@{name}{number}]");
+                let token = next_ok(&input);
+                assert_eq!(token.end(), input.len(), "{input}");
+                assert!(
+                    matches!(token.token(), Token::Atom(atom) if atom.chirality() == Some(variant(number))),
+                    "{input}"
+                );
+            }
+            for number in [0, max + 1] {
+                let input = format!("[C@{name}{number}]");
+                assert_eq!(
+                    next_err(&input).smiles_error(),
+                    SmilesError::InvalidChirality,
+                    "{input}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn chirality_is_followed_by_hydrogens_charge_class_or_bracket_close() {
+        for (input, chirality, hydrogens, charge, class) in [
+            ("[C@]", Chirality::At, 0, 0, 0),
+            ("[C@@]", Chirality::AtAt, 0, 0, 0),
+            ("[C@H]", Chirality::At, 1, 0, 0),
+            ("[C@@H2]", Chirality::AtAt, 2, 0, 0),
+            ("[N@+]", Chirality::At, 0, 1, 0),
+            ("[C@@-]", Chirality::AtAt, 0, -1, 0),
+            ("[C@:4]", Chirality::At, 0, 0, 4),
+            ("[C@@H+:7]", Chirality::AtAt, 1, 1, 7),
+            ("[C@TH2H-2:1]", Chirality::TH(2), 1, -2, 1),
+        ] {
+            let Token::Atom(atom) = next_ok(input).token() else {
+                panic!("{input} should be an atom token");
+            };
+            assert_eq!(atom.chirality(), Some(chirality), "{input}");
+            assert_eq!(atom.hydrogen_count(), hydrogens, "{input}");
+            assert_eq!(atom.charge_value(), charge, "{input}");
+            assert_eq!(atom.class(), class, "{input}");
+        }
+
+        for invalid in ["[C@1]", "[C@T]", "[C@TX1]", "[C@THX]", "[C@O1]", "[C@a]"] {
+            assert_eq!(
+                next_err(invalid).smiles_error(),
+                SmilesError::InvalidChirality,
+                "{invalid}"
+            );
+        }
+        for truncated in ["[C@", "[C@T", "[C@S"] {
+            assert_eq!(
+                next_err(truncated).smiles_error(),
+                SmilesError::UnexpectedEndOfString,
+                "{truncated}"
+            );
+        }
+    }
+
+    #[test]
+    fn consecutive_ring_digits_are_separate_closures() {
+        let rings = |input: &str| {
+            TokenIter::from(input)
+                .filter_map(|token| {
+                    match token.unwrap().token() {
+                        Token::RingClosure { ring_num, .. } => Some(ring_num.get()),
+                        _ => None,
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rings("C12(CCCC1)CCCC2"), [1, 2, 1, 2]);
+        assert_eq!(rings("C%12CCCC%12"), [12, 12]);
+        assert_eq!(rings("C%123CC%12CC3"), [12, 3, 12, 3]);
+
+        // Spiro: both rings pass through the first atom.
+        let spiro: Smiles = "C12(CCCC1)CCCC2".parse().unwrap();
+        assert_eq!(spiro.number_of_bonds(), 10);
+        assert_eq!(spiro.edge_count_for_node(0), 4);
+        assert_eq!(spiro.edge_count_for_node(4), 2);
+
+        // Fused: the second ring closes on a different atom than the first.
+        let fused: Smiles = "C12CCCCC1CCCC2".parse().unwrap();
+        assert_eq!(fused.number_of_bonds(), 11);
+        assert_eq!(fused.edge_count_for_node(0), 3);
+        assert_eq!(fused.edge_count_for_node(5), 3);
+    }
+
+    #[test]
+    fn allene_stereocenters_keep_their_al_chirality() {
+        for (input, expected) in
+            [("OC(Cl)=[C@AL1]=C(C)F", Chirality::AL(1)), ("OC(Cl)=[C@AL2]=C(C)F", Chirality::AL(2))]
+        {
+            let smiles: Smiles = input.parse().unwrap();
+            assert_eq!(smiles.nodes()[3].chirality(), Some(expected), "{input}");
+        }
+
+        for invalid in ["[C@AL0]", "[C@AL3]", "[C@AL]", "[C@A1]"] {
+            assert_eq!(
+                next_err(invalid).smiles_error(),
+                SmilesError::InvalidChirality,
+                "{invalid}"
+            );
+        }
+    }
+
     #[test]
     fn try_chirality_tb_form_should_parse() {
         let mut stream = TokenIter::from("@TB10");
@@ -1071,6 +1660,25 @@ mod tests {
         assert_eq!(minus_err.smiles_error(), SmilesError::ChargeUnderflow(-16));
     }
 
+    #[test]
+    fn lookalike_characters_name_their_ascii_counterpart() {
+        for (input, found, suggestion, span) in [
+            ("C\u{39F}", '\u{39F}', 'O', 1..3),
+            ("C\u{FF11}CC1", '\u{FF11}', '1', 1..4),
+            ("C\u{2013}C", '\u{2013}', '-', 1..4),
+            ("N\u{2261}C", '\u{2261}', '#', 1..4),
+            ("C[\u{41D}+]", '\u{41D}', 'H', 2..4),
+        ] {
+            let error = TokenIter::from(input).find_map(Result::err).unwrap();
+            assert_eq!(error.smiles_error(), SmilesError::NonAsciiLookalike { found, suggestion });
+            assert_eq!(error.span(), span, "{input}");
+            assert!(error.suggested_fix(input).unwrap().apply(input).is_ascii());
+        }
+        let error = next_err("\u{2192}");
+        assert_eq!(error.smiles_error(), SmilesError::UnexpectedUnicodeCharacter);
+        assert!(error.suggested_fix("\u{2192}").is_none());
+    }
+
     #[test]
     fn test_charge_parsing_number_before_sign() {
         let test_cases = vec![("[C2-]", -2), ("[C2+]", 2), ("[Fe15+]", 15), ("[Fe15-]", -15)];
@@ -0,0 +1,235 @@
+//! Cleanup pass for SMILES strings copied out of spreadsheets and documents.
+//!
+//! Office software tends to decorate plain text: files start with a byte order
+//! mark, hyphens turn into en dashes and straight quotes into curly ones.
+//! [`sanitize`] undoes these substitutions and trims surrounding whitespace.
+//! The returned [`Sanitized`] keeps an offset map, so spans of errors reported
+//! on the cleaned string can be moved back onto the text the user supplied.
+//!
+//! # Examples
+//!
+//! ```
+//! use smiles_parser::{Smiles, sanitize::sanitize};
+//!
+//! let input = "\u{feff} C\u{2013}C ";
+//! let sanitized = sanitize(input);
+//! assert_eq!(sanitized.as_str(), "C-C");
+//!
+//! let smiles: Smiles = sanitized.parse()?;
+//! assert_eq!(smiles.nodes().len(), 2);
+//! # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use core::{ops::Range, str::FromStr};
+
+use crate::errors::SmilesErrorWithSpan;
+
+/// A sanitized SMILES string together with the offsets of its bytes in the
+/// original input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Sanitized {
+    cleaned: String,
+    /// Original byte offset of every cleaned byte, followed by the original
+    /// offset just past the last kept character.
+    offsets: Vec<usize>,
+}
+
+impl Sanitized {
+    /// Returns the cleaned string.
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.cleaned
+    }
+
+    /// Consumes the value and returns the cleaned string.
+    #[inline]
+    #[must_use]
+    pub fn into_string(self) -> String {
+        self.cleaned
+    }
+
+    /// Maps a byte offset in the cleaned string to the corresponding byte
+    /// offset in the original input.
+    ///
+    /// Offsets past the end of the cleaned string map to the end of the
+    /// retained part of the original.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::sanitize::sanitize;
+    ///
+    /// let sanitized = sanitize("  C\u{2212}O");
+    /// assert_eq!(sanitized.as_str(), "C-O");
+    /// assert_eq!(sanitized.original_offset(0), 2);
+    /// assert_eq!(sanitized.original_offset(2), 6);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn original_offset(&self, offset: usize) -> usize {
+        self.offsets[offset.min(self.cleaned.len())]
+    }
+
+    /// Maps a byte span in the cleaned string to the original input.
+    #[inline]
+    #[must_use]
+    pub fn original_span(&self, span: Range<usize>) -> Range<usize> {
+        self.original_offset(span.start)..self.original_offset(span.end)
+    }
+
+    /// Moves the span of an error raised on the cleaned string onto the
+    /// original input, so that [`SmilesErrorWithSpan::render`] can be called
+    /// with the text the user supplied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{Smiles, sanitize::sanitize};
+    ///
+    /// let input = "\u{feff}C\u{2014}C?";
+    /// let sanitized = sanitize(input);
+    /// let error = sanitized.as_str().parse::<Smiles>().unwrap_err();
+    /// let error = sanitized.map_error(error);
+    /// assert_eq!(&input[error.span()], "?");
+    /// ```
+    #[must_use]
+    pub fn map_error(&self, error: SmilesErrorWithSpan) -> SmilesErrorWithSpan {
+        let span = self.original_span(error.span());
+        SmilesErrorWithSpan::new(error.smiles_error(), span.start, span.end)
+    }
+
+    /// Parses the cleaned string, reporting errors against the original input.
+    ///
+    /// # Errors
+    /// Returns the parse error with its span mapped by
+    /// [`Sanitized::map_error`].
+    #[inline]
+    pub fn parse<T>(&self) -> Result<T, SmilesErrorWithSpan>
+    where
+        T: FromStr<Err = SmilesErrorWithSpan>,
+    {
+        self.cleaned.parse().map_err(|error| self.map_error(error))
+    }
+}
+
+/// Cleans up `input` before parsing.
+///
+/// Byte order marks and zero-width characters are removed, unicode hyphens,
+/// dashes and minus signs become `-`, curly single and double quotes become
+/// `'` and `"`, and leading and trailing whitespace is trimmed. Everything
+/// else is copied unchanged, so invalid characters are still reported by the
+/// parser.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::sanitize::sanitize;
+///
+/// assert_eq!(sanitize("\tCC(=O)O\r\n").as_str(), "CC(=O)O");
+/// assert_eq!(sanitize("[O\u{2010}]").as_str(), "[O-]");
+/// assert_eq!(sanitize("CCO").as_str(), "CCO");
+/// ```
+#[must_use]
+pub fn sanitize(input: &str) -> Sanitized {
+    let mut cleaned = String::with_capacity(input.len());
+    let mut offsets = Vec::with_capacity(input.len() + 1);
+    let mut end = 0;
+
+    for (offset, character) in input.char_indices() {
+        let Some(character) = normalize(character) else {
+            continue;
+        };
+        if cleaned.is_empty() && character.is_whitespace() {
+            continue;
+        }
+        cleaned.push(character);
+        offsets.extend(core::iter::repeat_n(offset, character.len_utf8()));
+        if !character.is_whitespace() {
+            end = cleaned.len();
+        }
+    }
+
+    cleaned.truncate(end);
+    offsets.truncate(end);
+    let original_end = offsets
+        .last()
+        .map_or(input.len(), |&last| last + input[last..].chars().next().map_or(0, char::len_utf8));
+    offsets.push(original_end);
+    Sanitized { cleaned, offsets }
+}
+
+/// Returns the ASCII replacement for `character`, or `None` when it should
+/// be dropped.
+fn normalize(character: char) -> Option<char> {
+    match character {
+        '\u{feff}' | '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{00ad}' => None,
+        '\u{2010}'..='\u{2015}' | '\u{2212}' | '\u{fe58}' | '\u{fe63}' | '\u{ff0d}' => Some('-'),
+        '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' | '\u{2032}' => Some('\''),
+        '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' | '\u{2033}' => Some('"'),
+        other => Some(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{errors::SmilesError, smiles::Smiles};
+
+    #[test]
+    fn clean_input_is_unchanged_and_maps_to_itself() {
+        let sanitized = sanitize("C[C@H](N)O");
+
+        assert_eq!(sanitized.as_str(), "C[C@H](N)O");
+        for offset in 0..=sanitized.as_str().len() {
+            assert_eq!(sanitized.original_offset(offset), offset);
+        }
+    }
+
+    #[test]
+    fn byte_order_mark_and_whitespace_are_removed() {
+        let sanitized = sanitize("\u{feff}  CCO \n");
+
+        assert_eq!(sanitized.as_str(), "CCO");
+        assert_eq!(sanitized.original_span(0..3), 5..8);
+        assert_eq!(sanitized.original_offset(10), 8);
+    }
+
+    #[test]
+    fn dashes_and_quotes_become_ascii() {
+        assert_eq!(sanitize("C\u{2013}C\u{2014}C\u{2212}C").as_str(), "C-C-C-C");
+        assert_eq!(sanitize("\u{201c}CC\u{201d}").as_str(), "\"CC\"");
+        assert_eq!(sanitize("\u{2018}CC\u{2019}").as_str(), "'CC'");
+    }
+
+    #[test]
+    fn replaced_characters_map_to_their_full_original_width() {
+        let input = "C\u{2013}C";
+        let sanitized = sanitize(input);
+
+        assert_eq!(sanitized.original_span(1..2), 1..4);
+        assert_eq!(&input[sanitized.original_span(2..3)], "C");
+    }
+
+    #[test]
+    fn inner_whitespace_is_kept_for_the_parser_to_report() {
+        assert_eq!(sanitize(" C C ").as_str(), "C C");
+    }
+
+    #[test]
+    fn empty_and_blank_input_sanitize_to_empty() {
+        assert_eq!(sanitize("").as_str(), "");
+        assert_eq!(sanitize(" \u{feff}\t").as_str(), "");
+        assert_eq!(sanitize("  ").original_offset(0), 2);
+    }
+
+    #[test]
+    fn parse_errors_point_into_the_original_input() {
+        let input = "\u{feff}C\u{2013}C?";
+        let error = sanitize(input).parse::<Smiles>().unwrap_err();
+
+        assert_eq!(error.smiles_error(), SmilesError::UnexpectedCharacter('?'));
+        assert_eq!(&input[error.span()], "?");
+    }
+}
@@ -0,0 +1,141 @@
+//! Minimization of SMILES strings that fail to parse.
+//!
+//! Long natural products rarely fail because of their whole string: a single
+//! unclosed ring or a misplaced bracket is usually enough. [`shrink`] runs
+//! delta debugging over the characters of an input, deleting ever smaller
+//! chunks for as long as a predicate still reports a failure, and
+//! [`shrink_failure`] applies it to parse errors, keeping only candidates that
+//! fail with the same [`SmilesError::category`](crate::SmilesError::category).
+//!
+//! # Examples
+//!
+//! ```
+//! use smiles_parser::{Smiles, shrink::shrink_failure};
+//!
+//! let input = "CC(C)C[C@H]1C(=O)N[C@@H](Cc2ccccc2)C(=O)N1C(C";
+//! let error = input.parse::<Smiles>().unwrap_err();
+//! let minimal = shrink_failure(input, &error);
+//!
+//! assert!(minimal.len() < 5);
+//! let shrunk_error = minimal.parse::<Smiles>().unwrap_err();
+//! assert_eq!(shrunk_error.smiles_error().category(), error.smiles_error().category());
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{errors::SmilesErrorWithSpan, smiles::Smiles};
+
+/// Returns a shortest-found string obtained by deleting characters of `input`
+/// for which `still_fails` holds.
+///
+/// The search is the complement variant of delta debugging: chunks of the
+/// current string are removed while the predicate keeps holding, and the
+/// chunks are halved whenever no removal succeeds. The result is 1-minimal,
+/// i.e. deleting any single further character makes the predicate fail.
+/// Deletions work on characters, so the result is always valid UTF-8.
+///
+/// `still_fails` is not called on `input` itself, which is returned unchanged
+/// when no deletion keeps the predicate true.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::shrink::shrink;
+///
+/// let minimal = shrink("abXcdeXfg", |candidate| candidate.matches('X').count() == 2);
+/// assert_eq!(minimal, "XX");
+/// ```
+#[must_use]
+pub fn shrink(input: &str, mut still_fails: impl FnMut(&str) -> bool) -> String {
+    let mut characters = input.chars().collect::<Vec<_>>();
+    let mut candidate = String::with_capacity(input.len());
+    let mut granularity = 2;
+    while !characters.is_empty() {
+        granularity = granularity.min(characters.len());
+        let chunk = characters.len().div_ceil(granularity);
+        let mut reduced = false;
+        let mut start = 0;
+        while start < characters.len() {
+            let end = (start + chunk).min(characters.len());
+            candidate.clear();
+            candidate.extend(&characters[..start]);
+            candidate.extend(&characters[end..]);
+            if still_fails(&candidate) {
+                characters.drain(start..end);
+                reduced = true;
+            } else {
+                start = end;
+            }
+        }
+        if reduced {
+            granularity = granularity.saturating_sub(1).max(2);
+        } else if chunk == 1 {
+            break;
+        } else {
+            granularity *= 2;
+        }
+    }
+    characters.into_iter().collect()
+}
+
+/// Returns a minimal string, obtained by deleting characters of `smiles`,
+/// that still fails to parse with the category of `error`.
+///
+/// Candidates are parsed as [`Smiles`] with the default options, so failures
+/// that only arise under custom [`ParserOptions`](crate::ParserOptions) are
+/// returned unchanged.
+#[must_use]
+pub fn shrink_failure(smiles: &str, error: &SmilesErrorWithSpan) -> String {
+    let category = error.smiles_error().category();
+    shrink(smiles, |candidate| {
+        candidate
+            .parse::<Smiles>()
+            .is_err_and(|candidate_error| candidate_error.smiles_error().category() == category)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fails_with(candidate: &str, category: &str) -> bool {
+        candidate.parse::<Smiles>().is_err_and(|error| error.smiles_error().category() == category)
+    }
+
+    #[test]
+    fn shrink_returns_a_one_minimal_string() {
+        let mut calls = 0;
+        let minimal = shrink("xxAyyyyyyByyyyyyyyyyyyyyyyC", |candidate| {
+            calls += 1;
+            candidate.contains('A') && candidate.contains('B') && candidate.contains('C')
+        });
+        assert_eq!(minimal, "ABC");
+        assert!(calls < 200, "{calls}");
+
+        assert_eq!(shrink("keep", |_| false), "keep");
+        assert_eq!(shrink("gone", |_| true), "");
+        assert_eq!(shrink("", |_| true), "");
+        assert_eq!(shrink("é→ü", |candidate| candidate.contains('→')), "→");
+    }
+
+    #[test]
+    fn failures_shrink_within_their_category() {
+        for input in [
+            "CC(C)C[C@H]1C(=O)N[C@@H](Cc2ccccc2)C(=O)N1CC1CC",
+            "O=C(O)c1ccccc1OC(=O)C.CCN(CC)CC(C",
+            "CN1CCC23c4c5ccc(O)c4OC2C(O)C=CC3C1C5[Xx]",
+        ] {
+            let error = input.parse::<Smiles>().unwrap_err();
+            let category = error.smiles_error().category();
+            let minimal = shrink_failure(input, &error);
+
+            assert!(minimal.len() < input.len(), "{input} -> {minimal}");
+            assert!(fails_with(&minimal, category), "{input} -> {minimal}");
+            for (offset, character) in minimal.char_indices() {
+                let mut smaller = minimal.clone();
+                smaller.replace_range(offset..offset + character.len_utf8(), "");
+                assert!(!fails_with(&smaller, category), "{minimal} -> {smaller}");
+            }
+        }
+    }
+}
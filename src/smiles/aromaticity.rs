@@ -43,6 +43,20 @@ pub trait AromaticityModel {
 }
 
 /// Named aromaticity-policy presets exposed by the crate.
+///
+/// [`AromaticityPolicy::RdkitDefault`] and [`AromaticityPolicy::RdkitMdl`]
+/// count pi electrons over fused ring systems as well as single rings, so
+/// fused systems such as azulene are judged on their perimeter.
+/// [`AromaticityPolicy::RdkitSimple`] only considers 5- and 6-membered rings
+/// and judges each ring on its own, so it finds no aromatic ring in azulene.
+///
+/// Exocyclic double bonds are handled by their partner atom. Under every
+/// preset, a ring atom doubly bonded to a more electronegative atom outside
+/// the ring contributes no electron, which keeps quinones non-aromatic. A ring
+/// carbon doubly bonded to an exocyclic carbon contributes one electron under
+/// `RdkitDefault` and `RdkitSimple`, so p-xylylene counts six electrons and is
+/// aromatic. `RdkitMdl` never makes an atom with an exocyclic multiple bond
+/// aromatic.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum AromaticityPolicy {
     /// The current `RDKit` default aromaticity model target.
@@ -359,7 +359,7 @@ impl RdkitAtomAromContext {
 
         for edge in edges {
             let bond = edge.bond().without_direction();
-            explicit_valence += bond_valence_contribution(bond);
+            explicit_valence += usize::from(bond.order());
             if edge.is_aromatic() || matches!(bond, Bond::Double | Bond::Triple | Bond::Quadruple) {
                 incident_multiple_bond = true;
                 if ring_membership.contains_edge(atom_id, edge.target()) {
@@ -1578,15 +1578,6 @@ pub(crate) fn rdkit_smarts_total_valence(
         .map_or(raw_total_valence, |default_valence| raw_total_valence.max(default_valence))
 }
 
-fn bond_valence_contribution(bond: Bond) -> usize {
-    match bond {
-        Bond::Single | Bond::Up | Bond::Down => 1,
-        Bond::Double => 2,
-        Bond::Triple => 3,
-        Bond::Quadruple => 4,
-    }
-}
-
 fn more_electronegative(other: Element, current: Element) -> bool {
     let other_outer = rdkit_outer_electrons(other);
     let current_outer = rdkit_outer_electrons(current);
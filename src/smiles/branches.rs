@@ -24,6 +24,29 @@ impl BranchPlan {
         self.ordered_children.get(node_id).map_or(&[], Vec::as_slice)
     }
 
+    /// Reorders every child list by subtree size, smallest first, so the
+    /// largest subtree becomes the continuation and short substituents are
+    /// written as branches. Equal sizes keep their structural order.
+    pub(crate) fn order_children_smallest_first(&mut self) {
+        let mut sizes = vec![0; self.ordered_children.len()];
+        let mut stack = Vec::new();
+        for root in 0..self.ordered_children.len() {
+            stack.push((root, false));
+            while let Some((node_id, children_done)) = stack.pop() {
+                let children = &self.ordered_children[node_id];
+                if children_done {
+                    sizes[node_id] = 1 + children.iter().map(|&child| sizes[child]).sum::<usize>();
+                } else if sizes[node_id] == 0 {
+                    stack.push((node_id, true));
+                    stack.extend(children.iter().map(|&child| (child, false)));
+                }
+            }
+        }
+        for children in &mut self.ordered_children {
+            children.sort_by_key(|&child| sizes[child]);
+        }
+    }
+
     #[cfg(test)]
     #[must_use]
     pub(crate) fn continuation_child(&self, node_id: usize) -> Option<usize> {
@@ -227,21 +250,28 @@ fn node_local_key(
 }
 
 fn forest_postorder(forest: &SpanningForest, node_count: usize) -> Vec<usize> {
-    fn visit(node_id: usize, forest: &SpanningForest, visited: &mut [bool], out: &mut Vec<usize>) {
-        if visited[node_id] {
-            return;
-        }
-        visited[node_id] = true;
-        for &child in forest.children_of(node_id) {
-            visit(child, forest, visited, out);
-        }
-        out.push(node_id);
-    }
-
     let mut visited = vec![false; node_count];
     let mut out = Vec::with_capacity(node_count);
+    let mut stack = Vec::new();
     for &root in forest.roots() {
-        visit(root, forest, &mut visited, &mut out);
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        stack.push((root, 0_usize));
+        while let Some(&(node_id, next_child)) = stack.last() {
+            if let Some(&child) = forest.children_of(node_id).get(next_child) {
+                let top = stack.len() - 1;
+                stack[top].1 += 1;
+                if !visited[child] {
+                    visited[child] = true;
+                    stack.push((child, 0));
+                }
+            } else {
+                out.push(node_id);
+                stack.pop();
+            }
+        }
     }
     out
 }
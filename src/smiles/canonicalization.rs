@@ -11,8 +11,9 @@ use super::{
     implicit_hydrogens::implicit_hydrogens_if_written_unbracketed,
 };
 use crate::{
-    atom::{Atom, AtomSyntax, atom_symbol::AtomSymbol, can_write_unbracketed_aromatic},
+    atom::{Atom, AtomSyntax, needs_brackets},
     bond::Bond,
+    errors::RenumberError,
 };
 
 mod components;
@@ -82,8 +83,80 @@ impl SmilesCanonicalLabeling {
     }
 }
 
+/// Returns the ids that `step`, a list of source ids, refers to in the graph
+/// `provenance` maps into: `composed[i] = provenance[step[i]]`.
+fn compose_provenance(provenance: &[usize], step: &[usize]) -> Vec<usize> {
+    step.iter().map(|&id| provenance[id]).collect()
+}
+
+/// Version of the canonicalization algorithm behind
+/// [`Smiles::canonicalize`] and every output derived from it.
+///
+/// Canonical SMILES are stable within a major version: a release may bump
+/// the minor version for fixes that leave every canonical string unchanged,
+/// but any change to a canonical string bumps the major version. Canonical
+/// SMILES stored as database keys stay valid for as long as
+/// [`CanonicalAlgorithmVersion::is_compatible_with`] holds for the version
+/// they were written with.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::prelude::CanonicalAlgorithmVersion;
+///
+/// let stored = CanonicalAlgorithmVersion::new(1, 0);
+/// assert!(CanonicalAlgorithmVersion::CURRENT.is_compatible_with(stored));
+/// assert_eq!(CanonicalAlgorithmVersion::CURRENT.to_string(), "1.0");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CanonicalAlgorithmVersion {
+    major: u16,
+    minor: u16,
+}
+
+impl CanonicalAlgorithmVersion {
+    /// Version implemented by this build of the crate.
+    pub const CURRENT: Self = Self::new(1, 0);
+
+    /// Creates a version from its major and minor numbers.
+    #[inline]
+    #[must_use]
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+
+    /// Returns the major version, bumped whenever a canonical string changes.
+    #[inline]
+    #[must_use]
+    pub const fn major(self) -> u16 {
+        self.major
+    }
+
+    /// Returns the minor version, bumped for changes that keep every
+    /// canonical string.
+    #[inline]
+    #[must_use]
+    pub const fn minor(self) -> u16 {
+        self.minor
+    }
+
+    /// Returns whether canonical strings written with `other` are the ones
+    /// this version writes, which holds when the major versions match.
+    #[inline]
+    #[must_use]
+    pub const fn is_compatible_with(self, other: Self) -> bool {
+        self.major == other.major
+    }
+}
+
+impl core::fmt::Display for CanonicalAlgorithmVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
 impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
-    fn exact_canonical_labeling(&self) -> SmilesCanonicalLabeling {
+    pub(super) fn exact_canonical_labeling(&self) -> SmilesCanonicalLabeling {
         self.canonical_labeling_with(Self::exact_canonical_labeling_whole_graph)
     }
 
@@ -193,27 +266,43 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
     }
 
     fn canonicalization_step(&self) -> Self {
-        let canonicalized = self
+        self.canonicalization_step_with_provenance().0
+    }
+
+    /// Runs one canonicalization step and returns, for every atom of the
+    /// result, the id of the atom of `self` it comes from.
+    fn canonicalization_step_with_provenance(&self) -> (Self, Vec<usize>) {
+        let (collapsed, kept_nodes) = self
             .canonicalization_normal_form()
-            .collapse_removable_explicit_hydrogens()
-            .canonicalize_from_current_bond_orders();
+            .collapse_removable_explicit_hydrogens_with_provenance();
+        let (canonicalized, order) = collapsed.canonicalize_from_current_bond_orders();
+        let provenance = compose_provenance(&kept_nodes, &order);
         let has_aromatic_bonds = canonicalized
             .bond_matrix()
             .sparse_entries()
             .any(|((_row, _column), entry)| entry.aromatic());
         if !has_aromatic_bonds {
-            return canonicalized;
+            return (canonicalized, provenance);
         }
 
-        canonicalized
-            .kekulize_standalone()
-            .ok()
-            .map(|kekulized| kekulized.canonicalize_from_current_bond_orders())
-            .unwrap_or(canonicalized)
+        match canonicalized.kekulize_standalone() {
+            Ok(kekulized) => {
+                let (canonicalized, order) = kekulized.canonicalize_from_current_bond_orders();
+                (canonicalized, compose_provenance(&provenance, &order))
+            }
+            Err(_) => (canonicalized, provenance),
+        }
     }
 
-    fn canonicalize_from_current_bond_orders(&self) -> Self {
-        self.stereo_normal_form().exact_canonicalize().canonicalization_spelling_normal_form()
+    /// Canonicalizes the atom order under the current bond orders and
+    /// returns the result with the old id of each of its atoms.
+    fn canonicalize_from_current_bond_orders(&self) -> (Self, Vec<usize>) {
+        let normal_form = self.stereo_normal_form();
+        let labeling = normal_form.exact_canonical_labeling();
+        let canonicalized = normal_form
+            .exact_canonicalize_with_labeling(&labeling)
+            .canonicalization_spelling_normal_form();
+        (canonicalized, labeling.order)
     }
 
     pub(super) fn canonicalization_spelling_normal_form(&self) -> Self {
@@ -262,6 +351,7 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
     /// ```
     #[must_use]
     pub fn canonical_labeling(&self) -> SmilesCanonicalLabeling {
+        stage_span!(DEBUG, "smiles.canonical_labeling", atoms = self.atom_nodes.len());
         self.canonicalization_normal_form()
             .collapse_removable_explicit_hydrogens()
             .stereo_normal_form()
@@ -291,6 +381,9 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
 
     /// Returns the graph rewritten into canonical node order.
     ///
+    /// The rendered result only changes between major versions of
+    /// [`CanonicalAlgorithmVersion`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -302,25 +395,141 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
     /// ```
     #[must_use]
     pub fn canonicalize(&self) -> Self {
+        stage_span!(
+            DEBUG,
+            "smiles.canonicalize",
+            atoms = self.atom_nodes.len(),
+            bonds = self.number_of_bonds()
+        );
         self.canonicalize_orbit_min()
     }
 
+    /// Returns the graph rewritten into canonical node order, together with
+    /// the id in `self` of each of its atoms, so per-atom annotations can be
+    /// carried over.
+    ///
+    /// Explicit hydrogens that canonicalization folds into their parent atom
+    /// have no canonical atom, so their ids are missing from the mapping.
+    /// [`ConcreteSyntaxTree::atom_node`](crate::cst::ConcreteSyntaxTree::atom_node)
+    /// leads from an input atom id to the text that wrote it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use elements_rs::Element;
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let smiles: Smiles = "OC([H])N".parse()?;
+    /// let (canonical, provenance) = smiles.canonicalize_with_provenance();
+    /// assert_eq!(canonical, smiles.canonicalize());
+    /// assert_eq!(provenance.len(), 3);
+    /// for (canonical_id, &input_id) in provenance.iter().enumerate() {
+    ///     assert_eq!(canonical.nodes()[canonical_id].element(), smiles.nodes()[input_id].element());
+    /// }
+    /// assert!(!provenance.contains(&2));
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn canonicalize_with_provenance(&self) -> (Self, Vec<usize>) {
+        stage_span!(
+            DEBUG,
+            "smiles.canonicalize",
+            atoms = self.atom_nodes.len(),
+            bonds = self.number_of_bonds()
+        );
+        self.canonicalize_orbit_min_with_provenance()
+    }
+
+    /// Returns a copy with the atoms renumbered so that new atom `i` is old
+    /// atom `order[i]`.
+    ///
+    /// Atoms, bonds, stereo neighbor lists and implicit hydrogen counts are
+    /// carried over unchanged; only the indices move. Parser ring-closure
+    /// numbers are dropped because they refer to the original input order.
+    ///
+    /// # Errors
+    /// Returns a [`RenumberError`] if `order` is not a permutation of the atom
+    /// ids of this graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use elements_rs::Element;
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let smiles: Smiles = "OCN".parse()?;
+    /// let renumbered = smiles.renumber(&[2, 1, 0]).unwrap();
+    ///
+    /// assert_eq!(renumbered.nodes()[0].element(), Some(Element::N));
+    /// assert_eq!(renumbered.render(), smiles.render());
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    pub fn renumber(&self, order: &[usize]) -> Result<Self, RenumberError> {
+        let atom_count = self.atom_nodes.len();
+        if order.len() != atom_count {
+            return Err(RenumberError::LengthMismatch {
+                expected: atom_count,
+                actual: order.len(),
+            });
+        }
+        let mut seen = vec![false; atom_count];
+        for &atom_id in order {
+            let slot = seen.get_mut(atom_id).ok_or(RenumberError::AtomOutOfRange(atom_id))?;
+            if *slot {
+                return Err(RenumberError::DuplicateAtom(atom_id));
+            }
+            *slot = true;
+        }
+        Ok(self.exact_canonicalize_with_labeling(&SmilesCanonicalLabeling::new(order.to_vec())))
+    }
+
+    /// Returns a copy renumbered into canonical atom order.
+    ///
+    /// Unlike [`canonicalize`](Self::canonicalize), the atoms and bonds are
+    /// kept exactly as they are, with no aromaticity, hydrogen or spelling
+    /// normalization, so two inputs get the same numbering only when they are
+    /// written identically up to atom order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let left = "OCC".parse::<Smiles>()?.canonical_renumber();
+    /// let right = "CCO".parse::<Smiles>()?.canonical_renumber();
+    /// assert_eq!(left.nodes(), right.nodes());
+    /// assert_eq!(left.bond_matrix(), right.bond_matrix());
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn canonical_renumber(&self) -> Self {
+        self.exact_canonicalize()
+    }
+
     fn canonicalize_orbit_min(&self) -> Self {
-        let first = self.canonicalization_step();
-        let first_key = canonicalization_state_key(&first);
-        let second = first.canonicalization_step();
-        let second_key = canonicalization_state_key(&second);
+        self.canonicalize_orbit_min_with_provenance().0
+    }
+
+    /// Canonicalizes the graph and returns, for every atom of the result,
+    /// the id of the atom of `self` it comes from.
+    fn canonicalize_orbit_min_with_provenance(&self) -> (Self, Vec<usize>) {
+        let first = self.canonicalization_step_with_provenance();
+        let first_key = canonicalization_state_key(&first.0);
+        let second = first.0.canonicalization_step_with_provenance();
+        let second_key = canonicalization_state_key(&second.0);
 
         if second_key == first_key {
             return first;
         }
 
-        let mut states: Vec<Self> = vec![first, second];
+        let second = (second.0, compose_provenance(&first.1, &second.1));
+        let mut current = second.0.canonicalization_step_with_provenance();
+        current.1 = compose_provenance(&second.1, &current.1);
+        let mut states: Vec<(Self, Vec<usize>)> = vec![first, second];
         let mut keys: Vec<CanonicalizationStateKey> = vec![first_key, second_key];
-        let mut current = states[1].canonicalization_step();
 
         loop {
-            let key = canonicalization_state_key(&current);
+            let key = canonicalization_state_key(&current.0);
             if let Some(cycle_start) = keys.iter().position(|existing| *existing == key) {
                 // Orbit-min chooses the lexicographically smallest state from
                 // the discovered cycle rather than whichever representative we
@@ -330,15 +539,23 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
                     .enumerate()
                     .min_by(|left, right| left.1.cmp(right.1))
                     .map_or_else(|| unreachable!("cycle slice is non-empty"), |(index, _)| index);
-                return states[cycle_start + best_relative_index].clone();
+                return states.swap_remove(cycle_start + best_relative_index);
             }
             keys.push(key);
-            states.push(current.clone());
-            current = current.canonicalization_step();
+            let mut next = current.0.canonicalization_step_with_provenance();
+            next.1 = compose_provenance(&current.1, &next.1);
+            states.push(current);
+            current = next;
         }
     }
 
     fn collapse_removable_explicit_hydrogens(&self) -> Self {
+        self.collapse_removable_explicit_hydrogens_with_provenance().0
+    }
+
+    /// Collapses removable explicit hydrogens into their parents and returns
+    /// the result with the old id of each atom kept.
+    fn collapse_removable_explicit_hydrogens_with_provenance(&self) -> (Self, Vec<usize>) {
         let node_count = self.nodes().len();
         let mut collapsed_parent_of = vec![None; node_count];
         let mut collapsed_count_for_parent = vec![0_u8; node_count];
@@ -353,7 +570,7 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
         }
 
         if collapsed_parent_of.iter().all(Option::is_none) {
-            return self.clone();
+            return (self.clone(), (0..node_count).collect());
         }
 
         let kept_nodes = (0..node_count)
@@ -424,11 +641,12 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
             })
             .collect::<Vec<_>>();
 
-        Self::from_bond_matrix_parts_with_parsed_stereo(
+        let collapsed = Self::from_bond_matrix_parts_with_parsed_stereo(
             atom_nodes,
             builder.finish(kept_nodes.len()),
             parsed_stereo_neighbors,
-        )
+        );
+        (collapsed, kept_nodes)
     }
 
     fn collapsible_explicit_hydrogen_parent(&self, node_id: usize) -> Option<usize> {
@@ -464,6 +682,20 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
     }
 }
 
+impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns the organic-subset spelling of a bracket atom when dropping the
+    /// brackets would not change what the atom means, e.g. `C` for `[CH4]`.
+    #[must_use]
+    pub(crate) fn organic_subset_spelling(&self, node_id: usize) -> Option<Atom> {
+        let atom = self.atom_nodes[node_id];
+        if atom.syntax() != AtomSyntax::Bracket {
+            return None;
+        }
+        let collapsed = maybe_collapse_atom_to_organic_subset(self, node_id, atom);
+        (collapsed.syntax() == AtomSyntax::OrganicSubset).then_some(collapsed)
+    }
+}
+
 #[cfg(feature = "fuzzing")]
 impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
     /// Panics if canonicalization invariants are violated.
@@ -532,15 +764,7 @@ fn maybe_collapse_atom_to_organic_subset<AtomPolicy: crate::smiles::SmilesAtomPo
     atom: Atom,
 ) -> Atom {
     if atom.syntax() != AtomSyntax::Bracket
-        || atom.isotope_mass_number().is_some()
-        || atom.charge_value() != 0
-        || atom.class() != 0
-        || atom.chirality().is_some()
-        || (atom.aromatic()
-            && atom.element().is_none_or(|element| !can_write_unbracketed_aromatic(element)))
-        || !canonicalization_valid_unbracketed(atom.symbol())
-        || implicit_hydrogens_if_written_unbracketed(smiles, node_id, &atom)
-            != atom.hydrogen_count()
+        || needs_brackets(&atom, implicit_hydrogens_if_written_unbracketed(smiles, node_id, &atom))
     {
         return atom;
     }
@@ -548,25 +772,6 @@ fn maybe_collapse_atom_to_organic_subset<AtomPolicy: crate::smiles::SmilesAtomPo
     Atom::new_organic_subset(atom.symbol(), atom.aromatic())
 }
 
-fn canonicalization_valid_unbracketed(symbol: AtomSymbol) -> bool {
-    matches!(
-        symbol,
-        AtomSymbol::WildCard
-            | AtomSymbol::Element(
-                Element::B
-                    | Element::C
-                    | Element::N
-                    | Element::O
-                    | Element::P
-                    | Element::S
-                    | Element::F
-                    | Element::Cl
-                    | Element::Br
-                    | Element::I
-            )
-    )
-}
-
 fn canonicalization_atom_spelling_normal_form<AtomPolicy: crate::smiles::SmilesAtomPolicy>(
     smiles: &Smiles<AtomPolicy>,
     node_id: usize,
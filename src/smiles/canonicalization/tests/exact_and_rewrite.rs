@@ -6,7 +6,9 @@ use super::super::{
     Smiles, remap_parsed_stereo_neighbors_row,
     support::{assert_canonicalization_invariants, permute_smiles, same_canonicalization_state},
 };
-use crate::{parser::smiles_parser::parse_wildcard_smiles, smiles::StereoNeighbor};
+use crate::{
+    errors::RenumberError, parser::smiles_parser::parse_wildcard_smiles, smiles::StereoNeighbor,
+};
 
 #[test]
 fn exact_canonicalize_is_idempotent_for_disconnected_cases() {
@@ -33,6 +35,39 @@ fn canonicalize_converges_permuted_symmetric_cage_graph() {
     assert_eq!(original.canonicalize(), permuted.canonicalize());
 }
 
+/// Reversed, rotated and strided atom orders, which reach the symmetric
+/// atoms of a graph in different orders.
+fn symmetric_tie_permutations(node_count: usize) -> Vec<Vec<usize>> {
+    let stride = (2..node_count).find(|stride| gcd(*stride, node_count) == 1).unwrap_or(1);
+    vec![
+        (0..node_count).rev().collect(),
+        (0..node_count).map(|index| (index + node_count / 2) % node_count).collect(),
+        (0..node_count).map(|index| (index * stride + 1) % node_count).collect(),
+    ]
+}
+
+fn gcd(left: usize, right: usize) -> usize {
+    if right == 0 { left } else { gcd(right, left % right) }
+}
+
+#[test]
+fn canonicalize_breaks_symmetric_ties_independently_of_input_order() {
+    for source in [
+        "C12C3C4C1C5C2C3C45",
+        "C1=CC2=NC1=CC3=CC=C(N3)C=C4C=CC(=N4)C=C5C=CC(=C2)N5",
+        "C12=C3C4=C5C6=C1C7=C8C9=C1C%10=C%11C(=C29)C3=C2C3=C4C4=C5C5=C9C6=C7C6=C7C8=C1C1=C8C%10=C%10C%11=C2C2=C3C3=C4C4=C5C5=C%11C%12=C(C6=C95)C7=C1C1=C%12C5=C%11C4=C3C3=C5C(=C81)C%10=C23",
+    ] {
+        let original = Smiles::from_str(source).unwrap();
+        let canonical = original.canonicalize();
+        for order in symmetric_tie_permutations(original.nodes().len()) {
+            let permuted = permute_smiles(&original, &order);
+            assert_eq!(permuted.canonicalize(), canonical, "{source} {order:?}");
+            assert_eq!(permuted.canonicalize().render(), canonical.render(), "{source}");
+            assert_eq!(permuted.is_isomorphic_to(&original), Some(true), "{source}");
+        }
+    }
+}
+
 #[test]
 fn canonicalize_converges_permuted_bridged_graph() {
     let original = Smiles::from_str("C1CC2CCC1C2").unwrap();
@@ -135,3 +170,78 @@ fn canonicalize_converges_permuted_graph_with_sidecars() {
 
     assert_eq!(original.canonicalize(), permuted.canonicalize());
 }
+
+#[test]
+fn renumber_matches_test_permutation_and_keeps_stereo() {
+    let original = Smiles::from_str("N[C@H](F)C(=O)O").unwrap();
+    let order = [5, 3, 0, 6, 1, 4, 2];
+    let renumbered = original.renumber(&order).unwrap();
+
+    for (new_atom, &old_atom) in order.iter().enumerate() {
+        assert_eq!(renumbered.nodes()[new_atom], original.nodes()[old_atom]);
+        for (new_neighbor, &old_neighbor) in order.iter().enumerate() {
+            assert_eq!(
+                renumbered.edge_for_node_pair((new_atom, new_neighbor)).map(|edge| edge.bond()),
+                original.edge_for_node_pair((old_atom, old_neighbor)).map(|edge| edge.bond())
+            );
+        }
+    }
+    assert_eq!(renumbered.render(), original.render());
+}
+
+#[test]
+fn renumber_rejects_non_permutations() {
+    let smiles = Smiles::from_str("CCO").unwrap();
+
+    assert_eq!(
+        smiles.renumber(&[0, 1]).unwrap_err(),
+        RenumberError::LengthMismatch { expected: 3, actual: 2 }
+    );
+    assert_eq!(smiles.renumber(&[0, 1, 3]).unwrap_err(), RenumberError::AtomOutOfRange(3));
+    assert_eq!(smiles.renumber(&[0, 1, 1]).unwrap_err(), RenumberError::DuplicateAtom(1));
+}
+
+#[test]
+fn canonical_renumber_is_independent_of_input_order() {
+    let original = Smiles::from_str("OC1CCN(C)CC1").unwrap();
+    let permuted = permute_smiles(&original, &[3, 7, 0, 5, 2, 6, 1, 4]);
+
+    same_canonicalization_state(&original.canonical_renumber(), &permuted.canonical_renumber());
+}
+
+#[test]
+fn canonicalize_with_provenance_maps_atoms_back_to_the_input() {
+    for source in ["OC1CCN(C)CC1", "c1ccncc1O", "N[C@H](F)C(=O)O", "[H]OC([H])([H])C.O"] {
+        let smiles = Smiles::from_str(source).unwrap();
+        let (canonical, provenance) = smiles.canonicalize_with_provenance();
+        assert_eq!(canonical, smiles.canonicalize(), "{source}");
+        assert_eq!(provenance.len(), canonical.nodes().len(), "{source}");
+
+        let mut sorted = provenance.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(sorted.len(), provenance.len(), "{source}");
+        for (canonical_id, &input_id) in provenance.iter().enumerate() {
+            assert_eq!(
+                canonical.nodes()[canonical_id].element(),
+                smiles.nodes()[input_id].element(),
+                "{source}"
+            );
+            let heavy_neighbors = |graph: &Smiles, id| {
+                let mut elements = graph
+                    .neighbors_with_bonds(id)
+                    .filter_map(|(neighbor, _)| graph.nodes()[neighbor].element())
+                    .map(u8::from)
+                    .filter(|&atomic_number| atomic_number > 1)
+                    .collect::<Vec<_>>();
+                elements.sort_unstable();
+                elements
+            };
+            assert_eq!(
+                heavy_neighbors(&canonical, canonical_id),
+                heavy_neighbors(&smiles, input_id),
+                "{source}"
+            );
+        }
+    }
+}
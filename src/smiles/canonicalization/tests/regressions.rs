@@ -181,3 +181,46 @@ fn canonicalization_step_does_not_hide_bond_order_drift_for_pubchem_cid_10151035
     assert_eq!(canonicalization_state_key(&entry), canonicalization_state_key(&direct_step));
     assert_eq!(hidden_bond_order_digest(&entry), hidden_bond_order_digest(&direct_step));
 }
+
+#[test]
+fn canonical_strings_are_pinned_to_the_algorithm_version() {
+    // Changing any of these strings changes stored canonical keys and needs a
+    // major bump of `CanonicalAlgorithmVersion::CURRENT`.
+    assert_eq!(super::super::CanonicalAlgorithmVersion::CURRENT.major(), 1);
+    for (source, canonical) in [
+        ("OC", "CO"),
+        ("C", "C"),
+        ("C=C", "C=C"),
+        ("N#N", "N#N"),
+        ("[13CH4]", "[13CH4]"),
+        ("[NH4+]", "[NH4+]"),
+        ("C1CCCCC1", "C1CCCCC1"),
+        // Aromatic rings are written in their Kekule form.
+        ("c1ccccc1", "C1C=CC=CC=1"),
+        // Stereo
+        ("I[C@](Br)(Cl)F", "F[C@](Cl)(Br)I"),
+        ("I[C@@](Br)(Cl)F", "F[C@@](Cl)(Br)I"),
+        ("F\\C=C\\F", "F/C=C/F"),
+        ("F\\C=C/F", "F/C=C\\F"),
+        // Fused rings
+        ("C1CCC2C(C1)CCCC2", "C1CCC2CCCCC2C1"),
+        // Charges
+        ("[O-][N+](=O)C", "C[N+]([O-])=O"),
+        ("[O-]C(=O)C[NH3+]", "[NH3+]CC([O-])=O"),
+        // Several components
+        ("[Na+].[Cl-]", "[Cl-].[Na+]"),
+        ("[Na+].OC(=O)C", "CC(O)=O.[Na+]"),
+    ] {
+        assert_eq!(
+            source.parse::<Smiles>().unwrap().canonicalize().render(),
+            canonical,
+            "{source}"
+        );
+    }
+
+    for source in ["OCC", "c1ccccc1O", "C[C@H](N)C(=O)O", "F/C=C/Cl", "[13CH3]O.[Na+]"] {
+        let canonical = source.parse::<Smiles>().unwrap().canonicalize().render();
+        let reparsed = canonical.parse::<Smiles>().unwrap().canonicalize().render();
+        assert_eq!(reparsed, canonical, "{source}");
+    }
+}
@@ -0,0 +1,956 @@
+//! Read-only packed graphs for large in-memory libraries.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{marker::PhantomData, mem::size_of};
+
+use elements_rs::Element;
+use geometric_traits::traits::SparseValuedMatrixRef;
+
+use super::{
+    ConcreteAtoms, Smiles, SmilesAtomPolicy, StereoNeighbor,
+    build_bond_matrix_from_known_simple_edges,
+};
+use crate::{
+    atom::{Atom, PACKED_ATOM_LEN},
+    bond::{
+        Bond, BondDescriptor,
+        bond_edge::{BondEdge, bond_edge_with_aromaticity},
+        ring_num::RingNum,
+    },
+};
+
+/// Indices stored as `u16` when every value fits, and as `u32` otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum PackedIndices {
+    Narrow(Box<[u16]>),
+    Wide(Box<[u32]>),
+}
+
+impl PackedIndices {
+    fn new(values: &[usize]) -> Self {
+        match values.iter().map(|&value| u16::try_from(value)).collect() {
+            Ok(narrow) => Self::Narrow(narrow),
+            Err(_) => {
+                Self::Wide(
+                    values
+                        .iter()
+                        .map(|&value| {
+                            u32::try_from(value).unwrap_or_else(|_| {
+                                panic!("graph too large for compact storage: index {value}")
+                            })
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> usize {
+        match self {
+            Self::Narrow(values) => usize::from(values[index]),
+            Self::Wide(values) => values[index] as usize,
+        }
+    }
+
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        match self {
+            Self::Narrow(values) => values.len() * size_of::<u16>(),
+            Self::Wide(values) => values.len() * size_of::<u32>(),
+        }
+    }
+}
+
+/// The value of a bond without its endpoints.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct PackedBond {
+    bond: Bond,
+    ring_num: Option<RingNum>,
+    aromatic: bool,
+}
+
+impl PackedBond {
+    #[inline]
+    fn edge(self, source: usize, target: usize) -> BondEdge {
+        bond_edge_with_aromaticity(source, target, self.bond, self.ring_num, self.aromatic)
+    }
+
+    #[inline]
+    fn descriptor(self) -> BondDescriptor {
+        if self.aromatic {
+            BondDescriptor::aromatic(self.bond)
+        } else {
+            BondDescriptor::new(self.bond)
+        }
+    }
+}
+
+/// A read-only [`Smiles`] graph packed into flat arrays.
+///
+/// Atoms, implicit hydrogen counts and bonds are stored as exact-size boxed
+/// slices, adjacency as a compressed sparse row table, and every index as a
+/// `u16` when the graph is small enough, falling back to `u32`. The graph
+/// offers the inspection methods of [`Smiles`] that only read atoms and bonds;
+/// perception, canonicalization and writing go through [`CompactSmiles::thaw`],
+/// which restores an equal [`Smiles`], including its parsed stereo neighbor
+/// order and, for aromaticized graphs, their Kekule source.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::{bond::Bond, prelude::Smiles};
+///
+/// let smiles: Smiles = "C[C@H](N)C(=O)O".parse()?;
+/// let compact = smiles.freeze();
+///
+/// assert_eq!(compact.nodes(), smiles.nodes());
+/// assert_eq!(compact.number_of_bonds(), 5);
+/// assert_eq!(compact.edge_for_node_pair((3, 4)).map(|edge| edge.bond()), Some(Bond::Double));
+/// assert!(compact.heap_bytes() < smiles.memory_footprint().total());
+/// assert_eq!(compact.thaw(), smiles);
+/// assert_eq!(compact.render(), smiles.render());
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompactSmiles<AtomPolicy = ConcreteAtoms> {
+    atoms: Box<[Atom]>,
+    implicit_hydrogens: Box<[u8]>,
+    /// Endpoints of every bond, two per bond, in bond insertion order.
+    bond_ends: PackedIndices,
+    bonds: Box<[PackedBond]>,
+    /// Start of the adjacency row of every atom, plus the total.
+    adjacency_offsets: PackedIndices,
+    /// Neighbors of every atom, in ascending order within a row.
+    adjacency_neighbors: PackedIndices,
+    /// Bond index of every adjacency entry.
+    adjacency_bonds: PackedIndices,
+    /// Start of the stereo neighbor row of every atom, plus the total.
+    stereo_offsets: PackedIndices,
+    /// Parsed stereo neighbors, with the atom count standing for an explicit
+    /// hydrogen.
+    stereo_neighbors: PackedIndices,
+    kekulization_source: Option<Box<Self>>,
+    atom_policy: PhantomData<fn() -> AtomPolicy>,
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns a read-only packed copy of the graph.
+    ///
+    /// See [`CompactSmiles`] for the layout and the available queries.
+    #[must_use]
+    pub fn freeze(&self) -> CompactSmiles<AtomPolicy> {
+        let mut ordered = self
+            .bond_matrix
+            .sparse_entries()
+            .filter(|&((row, column), _)| row < column)
+            .map(|((row, column), entry)| {
+                let packed = PackedBond {
+                    bond: entry.bond(),
+                    ring_num: entry.ring_num(),
+                    aromatic: entry.aromatic(),
+                };
+                (entry.order(), (row, column, packed))
+            })
+            .collect::<Vec<_>>();
+        ordered.sort_unstable_by_key(|&(order, _)| order);
+        let bonds = ordered.into_iter().map(|(_, bond)| bond).collect::<Vec<_>>();
+
+        let number_of_atoms = self.atom_nodes.len();
+        let mut stereo_offsets = Vec::with_capacity(number_of_atoms + 1);
+        let mut stereo_neighbors = Vec::new();
+        stereo_offsets.push(0);
+        for row in &self.parsed_stereo_neighbors {
+            stereo_neighbors.extend(row.iter().map(|neighbor| {
+                match neighbor {
+                    StereoNeighbor::Atom(atom_id) => *atom_id,
+                    StereoNeighbor::ExplicitHydrogen => number_of_atoms,
+                }
+            }));
+            stereo_offsets.push(stereo_neighbors.len());
+        }
+
+        CompactSmiles::from_parts(
+            self.atom_nodes.as_slice().into(),
+            self.implicit_hydrogen_cache.as_slice().into(),
+            &bonds,
+            &stereo_offsets,
+            &stereo_neighbors,
+            self.kekulization_source.as_ref().map(|source| Box::new(source.freeze())),
+        )
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> CompactSmiles<AtomPolicy> {
+    /// Packs validated parts, building the adjacency table from `bonds`,
+    /// which are given in insertion order with `row < column`.
+    fn from_parts(
+        atoms: Box<[Atom]>,
+        implicit_hydrogens: Box<[u8]>,
+        bonds: &[(usize, usize, PackedBond)],
+        stereo_offsets: &[usize],
+        stereo_neighbors: &[usize],
+        kekulization_source: Option<Box<Self>>,
+    ) -> Self {
+        let number_of_atoms = atoms.len();
+        let mut adjacency_offsets = vec![0; number_of_atoms + 1];
+        for &(row, column, _) in bonds {
+            adjacency_offsets[row + 1] += 1;
+            adjacency_offsets[column + 1] += 1;
+        }
+        for atom_id in 0..number_of_atoms {
+            adjacency_offsets[atom_id + 1] += adjacency_offsets[atom_id];
+        }
+        let mut next_slot = adjacency_offsets.clone();
+        let mut adjacency = vec![(0, 0); 2 * bonds.len()];
+        for (bond_id, &(row, column, _)) in bonds.iter().enumerate() {
+            adjacency[next_slot[row]] = (column, bond_id);
+            next_slot[row] += 1;
+            adjacency[next_slot[column]] = (row, bond_id);
+            next_slot[column] += 1;
+        }
+        for atom_id in 0..number_of_atoms {
+            adjacency[adjacency_offsets[atom_id]..adjacency_offsets[atom_id + 1]].sort_unstable();
+        }
+
+        let bond_ends =
+            bonds.iter().flat_map(|&(row, column, _)| [row, column]).collect::<Vec<_>>();
+        let (adjacency_neighbors, adjacency_bonds): (Vec<_>, Vec<_>) =
+            adjacency.into_iter().unzip();
+        Self {
+            atoms,
+            implicit_hydrogens,
+            bond_ends: PackedIndices::new(&bond_ends),
+            bonds: bonds.iter().map(|&(.., packed)| packed).collect(),
+            adjacency_offsets: PackedIndices::new(&adjacency_offsets),
+            adjacency_neighbors: PackedIndices::new(&adjacency_neighbors),
+            adjacency_bonds: PackedIndices::new(&adjacency_bonds),
+            stereo_offsets: PackedIndices::new(stereo_offsets),
+            stereo_neighbors: PackedIndices::new(stereo_neighbors),
+            kekulization_source,
+            atom_policy: PhantomData,
+        }
+    }
+
+    /// Appends the binary record of the graph to `out`.
+    ///
+    /// All integers are little-endian. The record holds the atom, bond and
+    /// stereo neighbor counts as `u32` and a Kekule source flag byte, then the
+    /// packed atoms, the implicit hydrogen counts, every bond as two `u32`
+    /// endpoints followed by its order, aromaticity and ring number bytes, the
+    /// stereo row offsets and neighbors as `u32`, and finally the record of
+    /// the Kekule source, if any.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        let push_u32 = |out: &mut Vec<u8>, value: usize| {
+            let value = u32::try_from(value)
+                .unwrap_or_else(|_| unreachable!("compact indices always fit in u32"));
+            out.extend_from_slice(&value.to_le_bytes());
+        };
+        let number_of_atoms = self.atoms.len();
+        let stereo_len = self.stereo_offsets.get(number_of_atoms);
+        push_u32(out, number_of_atoms);
+        push_u32(out, self.bonds.len());
+        push_u32(out, stereo_len);
+        out.push(u8::from(self.kekulization_source.is_some()));
+        for atom in &self.atoms {
+            out.extend_from_slice(&atom.to_packed());
+        }
+        out.extend_from_slice(&self.implicit_hydrogens);
+        for (bond_id, packed) in self.bonds.iter().enumerate() {
+            push_u32(out, self.bond_ends.get(2 * bond_id));
+            push_u32(out, self.bond_ends.get(2 * bond_id + 1));
+            out.push(bond_code(packed.bond));
+            out.push(u8::from(packed.aromatic));
+            out.push(packed.ring_num.map_or(u8::MAX, |ring_num| ring_num.get()));
+        }
+        for slot in 0..=number_of_atoms {
+            push_u32(out, self.stereo_offsets.get(slot));
+        }
+        for slot in 0..stereo_len {
+            push_u32(out, self.stereo_neighbors.get(slot));
+        }
+        if let Some(source) = &self.kekulization_source {
+            source.encode(out);
+        }
+    }
+
+    /// Decodes a record written by [`CompactSmiles::encode`] from the front
+    /// of `bytes`, advancing it past the record.
+    ///
+    /// Returns `None` when the bytes are truncated or describe an invalid
+    /// graph: out-of-range or repeated bonds, unknown atom or bond codes,
+    /// non-monotonic stereo rows, or wildcard atoms under a concrete policy.
+    #[must_use]
+    pub fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let record = Record::split(bytes)?;
+        record.is_valid::<AtomPolicy>().then(|| Self::from_record(record))
+    }
+
+    /// Copies a checked record into packed arrays.
+    fn from_record(record: Record<'_>) -> Self {
+        let number_of_atoms = record.number_of_atoms();
+        let bonds =
+            (0..record.number_of_bonds()).map(|bond_id| record.bond(bond_id)).collect::<Vec<_>>();
+        let stereo_offsets =
+            (0..=number_of_atoms).map(|slot| record.stereo_offset(slot)).collect::<Vec<_>>();
+        let stereo_neighbors = (0..record.stereo_offset(number_of_atoms))
+            .map(|slot| record.stereo_neighbor(slot))
+            .collect::<Vec<_>>();
+        Self::from_parts(
+            (0..number_of_atoms).map(|atom_id| record.atom(atom_id)).collect(),
+            record.implicit_hydrogens.into(),
+            &bonds,
+            &stereo_offsets,
+            &stereo_neighbors,
+            record.kekulization_source().map(|source| Box::new(Self::from_record(source))),
+        )
+    }
+
+    /// Returns the atoms in parse order.
+    #[inline]
+    #[must_use]
+    pub fn nodes(&self) -> &[Atom] {
+        &self.atoms
+    }
+
+    /// Returns the atom with the given positional index, if present.
+    #[inline]
+    #[must_use]
+    pub fn node_by_id(&self, id: usize) -> Option<&Atom> {
+        self.atoms.get(id)
+    }
+
+    /// Returns the number of unique chemical bonds in the graph.
+    #[inline]
+    #[must_use]
+    pub fn number_of_bonds(&self) -> usize {
+        self.bonds.len()
+    }
+
+    /// Returns the implicit hydrogen count of every atom.
+    #[inline]
+    #[must_use]
+    pub fn implicit_hydrogen_counts(&self) -> &[u8] {
+        &self.implicit_hydrogens
+    }
+
+    /// Returns the implicit hydrogen count of the provided atom id.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    #[inline]
+    #[must_use]
+    pub fn implicit_hydrogen_count(&self, id: usize) -> u8 {
+        self.implicit_hydrogens[id]
+    }
+
+    /// Returns the number of bonds incident to the provided atom id.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    #[inline]
+    #[must_use]
+    pub fn degree(&self, id: usize) -> usize {
+        self.adjacency_row(id).len()
+    }
+
+    /// Returns the number of non-hydrogen atoms bonded to the provided atom
+    /// id. Wildcard neighbors count as heavy atoms.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    #[must_use]
+    pub fn heavy_degree(&self, id: usize) -> usize {
+        self.adjacency_row(id)
+            .filter(|&slot| {
+                self.atoms[self.adjacency_neighbors.get(slot)].element() != Some(Element::H)
+            })
+            .count()
+    }
+
+    /// Iterates the bonds incident to the provided atom id, oriented from `id`
+    /// to each neighbor, in ascending neighbor order.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    pub fn edges_for_node(&self, id: usize) -> impl Iterator<Item = BondEdge> + '_ {
+        self.neighbors_with_bonds(id).map(|(_, edge)| edge)
+    }
+
+    /// Iterates the neighbors of the provided atom id together with the bond
+    /// reaching each of them, in ascending neighbor order.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    pub fn neighbors_with_bonds(&self, id: usize) -> impl Iterator<Item = (usize, BondEdge)> + '_ {
+        self.adjacency_row(id).map(move |slot| {
+            let neighbor = self.adjacency_neighbors.get(slot);
+            (neighbor, self.bond_edge(self.adjacency_bonds.get(slot), id, neighbor))
+        })
+    }
+
+    /// Returns the bond connecting the given pair of atom ids, oriented from
+    /// the smaller id, if present.
+    #[must_use]
+    pub fn edge_for_node_pair(&self, nodes: (usize, usize)) -> Option<BondEdge> {
+        let (row, column) = super::edge_key(nodes.0, nodes.1);
+        if column >= self.atoms.len() {
+            return None;
+        }
+        self.neighbors_with_bonds(row)
+            .find(|&(neighbor, _)| neighbor == column)
+            .map(|(_, edge)| edge)
+    }
+
+    /// Returns the bytes held on the heap by the packed arrays, including
+    /// those of a kept Kekule source.
+    #[must_use]
+    pub fn heap_bytes(&self) -> usize {
+        self.atoms.len() * size_of::<Atom>()
+            + self.implicit_hydrogens.len()
+            + self.bond_ends.heap_bytes()
+            + self.bonds.len() * size_of::<PackedBond>()
+            + self.adjacency_offsets.heap_bytes()
+            + self.adjacency_neighbors.heap_bytes()
+            + self.adjacency_bonds.heap_bytes()
+            + self.stereo_offsets.heap_bytes()
+            + self.stereo_neighbors.heap_bytes()
+            + self
+                .kekulization_source
+                .as_ref()
+                .map_or(0, |source| size_of::<Self>() + source.heap_bytes())
+    }
+
+    /// Rebuilds the equivalent [`Smiles`] graph.
+    #[must_use]
+    pub fn thaw(&self) -> Smiles<AtomPolicy> {
+        let number_of_atoms = self.atoms.len();
+        let bond_matrix = build_bond_matrix_from_known_simple_edges(
+            number_of_atoms,
+            self.bonds.iter().enumerate().map(|(bond_id, packed)| {
+                (
+                    self.bond_ends.get(2 * bond_id),
+                    self.bond_ends.get(2 * bond_id + 1),
+                    packed.descriptor(),
+                    packed.ring_num,
+                )
+            }),
+        );
+        let parsed_stereo_neighbors = (0..number_of_atoms)
+            .map(|atom_id| {
+                (self.stereo_offsets.get(atom_id)..self.stereo_offsets.get(atom_id + 1))
+                    .map(|slot| {
+                        match self.stereo_neighbors.get(slot) {
+                            neighbor if neighbor == number_of_atoms => {
+                                StereoNeighbor::ExplicitHydrogen
+                            }
+                            neighbor => StereoNeighbor::Atom(neighbor),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Smiles::from_bond_matrix_parts_with_sidecars(
+            self.atoms.to_vec(),
+            bond_matrix,
+            parsed_stereo_neighbors,
+            self.implicit_hydrogens.to_vec(),
+            self.kekulization_source.as_ref().map(|source| Box::new(source.thaw())),
+        )
+    }
+
+    /// Renders the graph as a SMILES string, see [`Smiles::render`].
+    #[must_use]
+    pub fn render(&self) -> String {
+        self.thaw().render()
+    }
+
+    #[inline]
+    fn adjacency_row(&self, id: usize) -> core::ops::Range<usize> {
+        assert!(
+            id < self.atoms.len(),
+            "invalid atom index {id} for graph with {} atoms",
+            self.atoms.len()
+        );
+        self.adjacency_offsets.get(id)..self.adjacency_offsets.get(id + 1)
+    }
+
+    #[inline]
+    fn bond_edge(&self, bond_id: usize, source: usize, target: usize) -> BondEdge {
+        self.bonds[bond_id].edge(source, target)
+    }
+}
+
+/// A borrowed view of a record written by [`CompactSmiles::encode`].
+///
+/// The view checks the record once when it is created, with the same rules
+/// as [`CompactSmiles::decode`], and then answers every query straight from
+/// the encoded bytes, so the records of a memory-mapped `.smb` container can
+/// be inspected without copying them, see [`SmbReader::get_ref`]. Atoms are
+/// unpacked on each access, hence [`CompactSmilesRef::nodes`] yields values
+/// instead of a slice. Records hold no adjacency table: the per-atom queries
+/// scan the bonds of the graph and list neighbors in bond order rather than
+/// in ascending neighbor order. [`CompactSmilesRef::to_compact`] copies the
+/// record into a [`CompactSmiles`] when many such queries are needed.
+///
+/// [`SmbReader::get_ref`]: crate::io::SmbReader::get_ref
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::{
+///     bond::Bond,
+///     prelude::{CompactSmilesRef, Smiles},
+/// };
+///
+/// let smiles: Smiles = "C[C@H](N)C(=O)O".parse()?;
+/// let mut bytes = Vec::new();
+/// smiles.freeze().encode(&mut bytes);
+///
+/// let view = CompactSmilesRef::decode(&mut bytes.as_slice()).expect("valid record");
+/// assert!(view.nodes().eq(smiles.nodes().iter().copied()));
+/// assert_eq!(view.number_of_bonds(), 5);
+/// assert_eq!(view.edge_for_node_pair((3, 4)).map(|edge| edge.bond()), Some(Bond::Double));
+/// assert_eq!(view.to_compact(), smiles.freeze());
+/// assert_eq!(view.thaw(), smiles);
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct CompactSmilesRef<'a, AtomPolicy = ConcreteAtoms> {
+    record: Record<'a>,
+    atom_policy: PhantomData<fn() -> AtomPolicy>,
+}
+
+impl<'a, AtomPolicy: SmilesAtomPolicy> CompactSmilesRef<'a, AtomPolicy> {
+    /// Checks the record written by [`CompactSmiles::encode`] at the front of
+    /// `bytes` and borrows it, advancing `bytes` past the record.
+    ///
+    /// Returns `None` for the records [`CompactSmiles::decode`] rejects.
+    #[must_use]
+    pub fn decode(bytes: &mut &'a [u8]) -> Option<Self> {
+        let record = Record::split(bytes)?;
+        record.is_valid::<AtomPolicy>().then_some(Self { record, atom_policy: PhantomData })
+    }
+
+    /// Iterates the atoms in parse order.
+    pub fn nodes(self) -> impl ExactSizeIterator<Item = Atom> + 'a {
+        let record = self.record;
+        (0..record.number_of_atoms()).map(move |atom_id| record.atom(atom_id))
+    }
+
+    /// Returns the atom with the given positional index, if present.
+    #[inline]
+    #[must_use]
+    pub fn node_by_id(&self, id: usize) -> Option<Atom> {
+        (id < self.record.number_of_atoms()).then(|| self.record.atom(id))
+    }
+
+    /// Returns the number of unique chemical bonds in the graph.
+    #[inline]
+    #[must_use]
+    pub fn number_of_bonds(&self) -> usize {
+        self.record.number_of_bonds()
+    }
+
+    /// Returns the implicit hydrogen count of every atom.
+    #[inline]
+    #[must_use]
+    pub fn implicit_hydrogen_counts(&self) -> &'a [u8] {
+        self.record.implicit_hydrogens
+    }
+
+    /// Returns the implicit hydrogen count of the provided atom id.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    #[inline]
+    #[must_use]
+    pub fn implicit_hydrogen_count(&self, id: usize) -> u8 {
+        self.record.implicit_hydrogens[id]
+    }
+
+    /// Returns the number of bonds incident to the provided atom id.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    #[must_use]
+    pub fn degree(&self, id: usize) -> usize {
+        self.neighbors_with_bonds(id).count()
+    }
+
+    /// Returns the number of non-hydrogen atoms bonded to the provided atom
+    /// id. Wildcard neighbors count as heavy atoms.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    #[must_use]
+    pub fn heavy_degree(&self, id: usize) -> usize {
+        self.neighbors_with_bonds(id)
+            .filter(|&(neighbor, _)| self.record.atom(neighbor).element() != Some(Element::H))
+            .count()
+    }
+
+    /// Iterates the bonds incident to the provided atom id, oriented from `id`
+    /// to each neighbor, in bond order.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    pub fn edges_for_node(self, id: usize) -> impl Iterator<Item = BondEdge> + 'a {
+        self.neighbors_with_bonds(id).map(|(_, edge)| edge)
+    }
+
+    /// Iterates the neighbors of the provided atom id together with the bond
+    /// reaching each of them, in bond order.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    pub fn neighbors_with_bonds(self, id: usize) -> impl Iterator<Item = (usize, BondEdge)> + 'a {
+        let record = self.record;
+        assert!(
+            id < record.number_of_atoms(),
+            "invalid atom index {id} for graph with {} atoms",
+            record.number_of_atoms()
+        );
+        (0..record.number_of_bonds()).filter_map(move |bond_id| {
+            let (row, column, packed) = record.bond(bond_id);
+            let neighbor = if id == row {
+                column
+            } else if id == column {
+                row
+            } else {
+                return None;
+            };
+            Some((neighbor, packed.edge(id, neighbor)))
+        })
+    }
+
+    /// Returns the bond connecting the given pair of atom ids, oriented from
+    /// the smaller id, if present.
+    #[must_use]
+    pub fn edge_for_node_pair(&self, nodes: (usize, usize)) -> Option<BondEdge> {
+        let (row, column) = super::edge_key(nodes.0, nodes.1);
+        (0..self.record.number_of_bonds()).find_map(|bond_id| {
+            let (bond_row, bond_column, packed) = self.record.bond(bond_id);
+            (bond_row == row && bond_column == column).then(|| packed.edge(row, column))
+        })
+    }
+
+    /// Copies the record into an owned [`CompactSmiles`].
+    #[must_use]
+    pub fn to_compact(&self) -> CompactSmiles<AtomPolicy> {
+        CompactSmiles::from_record(self.record)
+    }
+
+    /// Rebuilds the equivalent [`Smiles`] graph.
+    #[must_use]
+    pub fn thaw(&self) -> Smiles<AtomPolicy> {
+        self.to_compact().thaw()
+    }
+
+    /// Renders the graph as a SMILES string, see [`Smiles::render`].
+    #[must_use]
+    pub fn render(&self) -> String {
+        self.thaw().render()
+    }
+}
+
+/// Length of a bond in a record: two `u32` endpoints, then the order,
+/// aromaticity and ring number bytes.
+const RECORD_BOND_LEN: usize = 11;
+
+/// The sections of a record written by [`CompactSmiles::encode`], borrowed
+/// from the encoded bytes.
+#[derive(Debug, Clone, Copy)]
+struct Record<'a> {
+    atoms: &'a [u8],
+    implicit_hydrogens: &'a [u8],
+    bonds: &'a [u8],
+    stereo_offsets: &'a [u8],
+    stereo_neighbors: &'a [u8],
+    /// The whole record of the Kekule source, if any.
+    kekulization_source: Option<&'a [u8]>,
+}
+
+impl<'a> Record<'a> {
+    /// Splits the record at the front of `bytes` into its sections, advancing
+    /// `bytes` past it. Only the lengths are checked here, the contents by
+    /// [`Record::is_valid`].
+    fn split(bytes: &mut &'a [u8]) -> Option<Self> {
+        let number_of_atoms = take_u32(bytes)?;
+        let number_of_bonds = take_u32(bytes)?;
+        let stereo_len = take_u32(bytes)?;
+        let has_source = match take(bytes, 1)? {
+            [0] => false,
+            [1] => true,
+            _ => return None,
+        };
+        let atoms = take(bytes, number_of_atoms.checked_mul(PACKED_ATOM_LEN)?)?;
+        let implicit_hydrogens = take(bytes, number_of_atoms)?;
+        let bonds = take(bytes, number_of_bonds.checked_mul(RECORD_BOND_LEN)?)?;
+        let stereo_offsets = take(bytes, number_of_atoms.checked_add(1)?.checked_mul(4)?)?;
+        let stereo_neighbors = take(bytes, stereo_len.checked_mul(4)?)?;
+        let kekulization_source = if has_source {
+            let start = *bytes;
+            Self::split(bytes)?;
+            Some(&start[..start.len() - bytes.len()])
+        } else {
+            None
+        };
+        Some(Self {
+            atoms,
+            implicit_hydrogens,
+            bonds,
+            stereo_offsets,
+            stereo_neighbors,
+            kekulization_source,
+        })
+    }
+
+    /// Returns whether the record describes a valid graph: known atom and
+    /// bond codes, in-range and unrepeated bonds, monotonic stereo rows, and
+    /// no wildcard atoms under a concrete policy.
+    fn is_valid<AtomPolicy: SmilesAtomPolicy>(&self) -> bool {
+        let number_of_atoms = self.number_of_atoms();
+        let atoms_valid = self.atoms.chunks_exact(PACKED_ATOM_LEN).all(|chunk| {
+            chunk
+                .try_into()
+                .ok()
+                .and_then(Atom::from_packed)
+                .is_some_and(|atom| AtomPolicy::ALLOW_WILDCARDS || !atom.symbol().is_wildcard())
+        });
+        let mut seen = hashbrown::HashSet::with_capacity(self.number_of_bonds());
+        let bonds_valid = self.bonds.chunks_exact(RECORD_BOND_LEN).all(|chunk| {
+            let (row, column) = (read_u32(chunk, 0), read_u32(chunk, 4));
+            row < column
+                && column < number_of_atoms
+                && seen.insert((row, column))
+                && bond_from_code(chunk[8]).is_some()
+                && chunk[9] <= 1
+                && (chunk[10] == u8::MAX || RingNum::try_new(chunk[10]).is_ok())
+        });
+        let stereo_len = self.stereo_neighbors.len() / 4;
+        let stereo_valid = self.stereo_offset(0) == 0
+            && self.stereo_offset(number_of_atoms) == stereo_len
+            && (0..number_of_atoms)
+                .all(|atom_id| self.stereo_offset(atom_id) <= self.stereo_offset(atom_id + 1))
+            && (0..stereo_len).all(|slot| self.stereo_neighbor(slot) <= number_of_atoms);
+        atoms_valid
+            && bonds_valid
+            && stereo_valid
+            && self.kekulization_source().is_none_or(|source| source.is_valid::<AtomPolicy>())
+    }
+
+    #[inline]
+    fn number_of_atoms(&self) -> usize {
+        self.implicit_hydrogens.len()
+    }
+
+    #[inline]
+    fn number_of_bonds(&self) -> usize {
+        self.bonds.len() / RECORD_BOND_LEN
+    }
+
+    /// Unpacks an atom of a checked record.
+    #[inline]
+    fn atom(&self, atom_id: usize) -> Atom {
+        self.atoms[atom_id * PACKED_ATOM_LEN..][..PACKED_ATOM_LEN]
+            .try_into()
+            .ok()
+            .and_then(Atom::from_packed)
+            .unwrap_or_else(|| unreachable!("checked records hold valid atoms"))
+    }
+
+    /// Unpacks a bond of a checked record.
+    #[inline]
+    fn bond(&self, bond_id: usize) -> (usize, usize, PackedBond) {
+        let chunk = &self.bonds[bond_id * RECORD_BOND_LEN..][..RECORD_BOND_LEN];
+        let packed = PackedBond {
+            bond: bond_from_code(chunk[8])
+                .unwrap_or_else(|| unreachable!("checked records hold valid bonds")),
+            ring_num: RingNum::try_new(chunk[10]).ok(),
+            aromatic: chunk[9] == 1,
+        };
+        (read_u32(chunk, 0), read_u32(chunk, 4), packed)
+    }
+
+    #[inline]
+    fn stereo_offset(&self, slot: usize) -> usize {
+        read_u32(self.stereo_offsets, 4 * slot)
+    }
+
+    #[inline]
+    fn stereo_neighbor(&self, slot: usize) -> usize {
+        read_u32(self.stereo_neighbors, 4 * slot)
+    }
+
+    fn kekulization_source(&self) -> Option<Self> {
+        self.kekulization_source.map(|mut bytes| {
+            Self::split(&mut bytes)
+                .unwrap_or_else(|| unreachable!("the source record was split before"))
+        })
+    }
+}
+
+/// Splits the first `len` bytes off `bytes`.
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if bytes.len() < len {
+        return None;
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Some(head)
+}
+
+/// Reads a little-endian `u32` off the front of `bytes`.
+fn take_u32(bytes: &mut &[u8]) -> Option<usize> {
+    let value = u32::from_le_bytes(take(bytes, 4)?.try_into().ok()?);
+    usize::try_from(value).ok()
+}
+
+/// Reads the little-endian `u32` at `offset` of a split record section.
+#[inline]
+fn read_u32(bytes: &[u8], offset: usize) -> usize {
+    take_u32(&mut &bytes[offset..])
+        .unwrap_or_else(|| unreachable!("record sections hold whole u32 values"))
+}
+
+fn bond_code(bond: Bond) -> u8 {
+    match bond {
+        Bond::Single => 0,
+        Bond::Double => 1,
+        Bond::Triple => 2,
+        Bond::Quadruple => 3,
+        Bond::Up => 4,
+        Bond::Down => 5,
+    }
+}
+
+fn bond_from_code(code: u8) -> Option<Bond> {
+    Some(match code {
+        0 => Bond::Single,
+        1 => Bond::Double,
+        2 => Bond::Triple,
+        3 => Bond::Quadruple,
+        4 => Bond::Up,
+        5 => Bond::Down,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trip(input: &str) {
+        let smiles: Smiles = input.parse().unwrap();
+        let compact = smiles.freeze();
+        let thawed = compact.thaw();
+
+        assert_eq!(thawed, smiles, "{input}");
+        assert_eq!(thawed.parsed_stereo_neighbors, smiles.parsed_stereo_neighbors, "{input}");
+        assert_eq!(thawed.implicit_hydrogen_cache, smiles.implicit_hydrogen_cache, "{input}");
+        assert_eq!(thawed.render(), smiles.render(), "{input}");
+        assert_eq!(compact.number_of_bonds(), smiles.number_of_bonds(), "{input}");
+        for atom_id in 0..smiles.nodes().len() {
+            assert_eq!(
+                compact.edges_for_node(atom_id).collect::<Vec<_>>(),
+                smiles.edges_for_node(atom_id).collect::<Vec<_>>(),
+                "{input}"
+            );
+            assert_eq!(compact.heavy_degree(atom_id), smiles.heavy_degree(atom_id), "{input}");
+            assert_eq!(
+                compact.implicit_hydrogen_count(atom_id),
+                smiles.implicit_hydrogen_count(atom_id),
+                "{input}"
+            );
+        }
+
+        let mut bytes = Vec::new();
+        compact.encode(&mut bytes);
+        let mut remaining = bytes.as_slice();
+        let view = CompactSmilesRef::<ConcreteAtoms>::decode(&mut remaining).unwrap();
+        assert!(remaining.is_empty(), "{input}");
+        assert!(view.nodes().eq(smiles.nodes().iter().copied()), "{input}");
+        assert_eq!(view.implicit_hydrogen_counts(), compact.implicit_hydrogen_counts(), "{input}");
+        assert_eq!(view.number_of_bonds(), compact.number_of_bonds(), "{input}");
+        for atom_id in 0..smiles.nodes().len() {
+            let mut edges = view.edges_for_node(atom_id).collect::<Vec<_>>();
+            edges.sort_unstable_by_key(|edge| edge.target());
+            assert_eq!(edges, compact.edges_for_node(atom_id).collect::<Vec<_>>(), "{input}");
+            assert_eq!(view.degree(atom_id), compact.degree(atom_id), "{input}");
+            assert_eq!(view.heavy_degree(atom_id), compact.heavy_degree(atom_id), "{input}");
+            for other in 0..=smiles.nodes().len() {
+                assert_eq!(
+                    view.edge_for_node_pair((atom_id, other)),
+                    compact.edge_for_node_pair((atom_id, other)),
+                    "{input}"
+                );
+            }
+        }
+        assert_eq!(view.to_compact(), compact, "{input}");
+        assert_eq!(view.thaw(), smiles, "{input}");
+    }
+
+    #[test]
+    fn freezing_round_trips() {
+        for input in [
+            "C",
+            "[Na+].[Cl-]",
+            "F/C=C/F",
+            "C1CC[C@@H]2CCCC[C@H]2C1",
+            "[H][C@]1(O)CCCC1",
+            "c1ccc2ccccc2c1",
+            "CC(C)CC1CCC(CC1)C(C)C(=O)O",
+        ] {
+            assert_round_trip(input);
+        }
+    }
+
+    #[test]
+    fn compact_graphs_use_less_memory() {
+        let smiles: Smiles = "CN1CCC23C4C1CC5=C2C(=C(C=C5)OC)OC3C(=O)CC4".parse().unwrap();
+        let compact = smiles.freeze();
+        assert!(compact.heap_bytes() * 2 <= smiles.memory_footprint().total());
+
+        let aromaticized = "C1=CC=CC=C1".parse::<Smiles>().unwrap().perceive_aromaticity().unwrap();
+        let compact = aromaticized.aromaticized().freeze();
+        assert_eq!(&compact.thaw(), aromaticized.aromaticized());
+        assert_eq!(
+            compact.thaw().kekulize().unwrap(),
+            aromaticized.aromaticized().kekulize().unwrap()
+        );
+    }
+
+    #[test]
+    fn views_read_the_encoded_bytes() {
+        let perception = "c1ccccc1O".parse::<Smiles>().unwrap().perceive_aromaticity().unwrap();
+        let compact = perception.aromaticized().freeze();
+        let mut bytes = Vec::new();
+        compact.encode(&mut bytes);
+
+        let view = CompactSmilesRef::<ConcreteAtoms>::decode(&mut bytes.as_slice()).unwrap();
+        let counts = view.implicit_hydrogen_counts().as_ptr_range();
+        assert!(bytes.as_ptr_range().contains(&counts.start));
+        assert_eq!(view.to_compact(), compact);
+        assert_eq!(view.thaw().kekulize().unwrap(), perception.aromaticized().kekulize().unwrap());
+        assert_eq!(view.node_by_id(7), None);
+
+        for truncated in 0..bytes.len() {
+            assert!(CompactSmilesRef::<ConcreteAtoms>::decode(&mut &bytes[..truncated]).is_none());
+        }
+        let wildcard = "*C".parse::<super::super::WildcardSmiles>().unwrap().inner.freeze();
+        bytes.clear();
+        wildcard.encode(&mut bytes);
+        assert!(CompactSmilesRef::<ConcreteAtoms>::decode(&mut bytes.as_slice()).is_none());
+    }
+
+    #[test]
+    fn queries_reject_missing_pairs() {
+        let compact = "CCO".parse::<Smiles>().unwrap().freeze();
+        assert_eq!(compact.edge_for_node_pair((2, 1)).map(|edge| edge.bond()), Some(Bond::Single));
+        assert_eq!(compact.edge_for_node_pair((0, 2)), None);
+        assert_eq!(compact.edge_for_node_pair((0, 9)), None);
+        assert_eq!(compact.degree(1), 2);
+        assert!(compact.node_by_id(3).is_none());
+    }
+}
@@ -0,0 +1,401 @@
+//! Topological descriptors computed directly from the molecular graph.
+//!
+//! None of these descriptors need 3D coordinates. The graph-theoretical
+//! indices (Zagreb, Wiener and Balaban J) are evaluated on the
+//! hydrogen-suppressed graph, so explicit `[H]` atoms and hydrogen counts do
+//! not change them. The Labute approximate surface area instead accounts for
+//! every hydrogen, whether it is stored as an atom, a bracket count or an
+//! implicit hydrogen.
+
+use alloc::{collections::VecDeque, vec::Vec};
+
+use elements_rs::Element;
+use geometric_traits::traits::{SparseMatrix2D, SparseValuedMatrix2DRef};
+
+use super::{BondEntry, Smiles, SmilesAtomPolicy, embedding::sqrt};
+use crate::bond::Bond;
+
+/// Bond radius of hydrogen used by the Labute surface area, in angstrom.
+const HYDROGEN_BOND_RADIUS: f64 = 0.33;
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns the first Zagreb index, the sum of squared heavy-atom degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let smiles: Smiles = "CC(C)C".parse()?;
+    /// assert_eq!(smiles.first_zagreb_index(), 12);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn first_zagreb_index(&self) -> usize {
+        (0..self.atom_nodes.len())
+            .filter(|&atom_id| !self.is_hydrogen_atom(atom_id))
+            .map(|atom_id| {
+                let degree = self.heavy_degree(atom_id);
+                degree * degree
+            })
+            .sum()
+    }
+
+    /// Returns the second Zagreb index, the sum over heavy-atom bonds of the
+    /// product of the endpoint degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let smiles: Smiles = "CC(C)C".parse()?;
+    /// assert_eq!(smiles.second_zagreb_index(), 9);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn second_zagreb_index(&self) -> usize {
+        self.heavy_bonds()
+            .map(|(left, right)| self.heavy_degree(left) * self.heavy_degree(right))
+            .sum()
+    }
+
+    /// Returns the Wiener index, the sum of shortest-path lengths between
+    /// every unordered pair of heavy atoms.
+    ///
+    /// Pairs of atoms in different connected components contribute nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let smiles: Smiles = "CCCC".parse()?;
+    /// assert_eq!(smiles.wiener_index(), 10);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn wiener_index(&self) -> usize {
+        self.heavy_distance_sums().into_iter().sum::<usize>() / 2
+    }
+
+    /// Returns the Balaban J index of the hydrogen-suppressed graph.
+    ///
+    /// The index is `m / (mu + 1) * sum((s_i * s_j)^-1/2)` over every bond,
+    /// where `m` is the number of bonds, `mu` the cyclomatic number and `s_i`
+    /// the sum of topological distances from atom `i` to every other atom of
+    /// its component. Graphs without heavy-atom bonds return `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let benzene: Smiles = "c1ccccc1".parse()?;
+    /// assert!((benzene.balaban_j() - 2.0).abs() < 1.0e-12);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn balaban_j(&self) -> f64 {
+        let heavy_atom_count =
+            (0..self.atom_nodes.len()).filter(|&atom_id| !self.is_hydrogen_atom(atom_id)).count();
+        let bond_count = self.heavy_bonds().count();
+        if bond_count == 0 {
+            return 0.0;
+        }
+        let component_count = self.heavy_component_count();
+        let cyclomatic_number = bond_count + component_count - heavy_atom_count;
+
+        let distance_sums = self.heavy_distance_sums();
+        let bond_sum: f64 = self
+            .heavy_bonds()
+            .map(|(left, right)| {
+                let product = to_f64(distance_sums[left]) * to_f64(distance_sums[right]);
+                1.0 / sqrt(product)
+            })
+            .sum();
+        to_f64(bond_count) / to_f64(cyclomatic_number + 1) * bond_sum
+    }
+
+    /// Returns Labute's approximate surface area (ASA), in square angstrom.
+    ///
+    /// Every atom is modelled as a sphere with a tabulated bond radius, and
+    /// the area hidden by each bonded neighbour is removed analytically, with
+    /// bond lengths shortened for multiple and aromatic bonds. Hydrogens are
+    /// included whether they are graph atoms, bracket counts or implicit.
+    ///
+    /// Returns `None` if the graph contains a wildcard atom, an element for
+    /// which no bond radius is tabulated, or a quadruple bond, for which the
+    /// method has no bond length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let methane: Smiles = "C".parse()?;
+    /// let ethane: Smiles = "CC".parse()?;
+    /// assert!(methane.labute_asa().unwrap() < ethane.labute_asa().unwrap());
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn labute_asa(&self) -> Option<f64> {
+        if self.bond_triples().any(|(_, _, entry)| entry.bond() == Bond::Quadruple) {
+            return None;
+        }
+        let radii = self
+            .atom_nodes
+            .iter()
+            .map(|atom| atom.element().and_then(bond_radius))
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut covered = vec![0.0; radii.len()];
+        for (row, column, entry) in self.bond_triples() {
+            let (row_radius, column_radius) = (radii[row], radii[column]);
+            let length = bond_distance(row_radius, column_radius, entry);
+            covered[row] += hidden_area(column_radius, row_radius, length);
+            covered[column] += hidden_area(row_radius, column_radius, length);
+        }
+
+        let mut area = 0.0;
+        for (atom_id, (&radius, &hidden)) in radii.iter().zip(&covered).enumerate() {
+            let hydrogens = f64::from(self.atom_nodes[atom_id].hydrogen_count())
+                + f64::from(self.implicit_hydrogen_count(atom_id));
+            let length =
+                bond_distance(radius, HYDROGEN_BOND_RADIUS, BondEntry::new(Bond::Single, None, 0));
+            let hidden_by_hydrogens = hydrogens * hidden_area(HYDROGEN_BOND_RADIUS, radius, length);
+            area += sphere_contribution(radius, hidden + hidden_by_hydrogens);
+            area += hydrogens
+                * sphere_contribution(
+                    HYDROGEN_BOND_RADIUS,
+                    hidden_area(radius, HYDROGEN_BOND_RADIUS, length),
+                );
+        }
+        Some(area)
+    }
+
+    fn is_hydrogen_atom(&self, atom_id: usize) -> bool {
+        self.atom_nodes[atom_id].element() == Some(Element::H)
+    }
+
+    fn heavy_neighbors(&self, atom_id: usize) -> impl Iterator<Item = usize> + '_ {
+        self.bond_matrix
+            .sparse_row(atom_id)
+            .filter(move |&neighbor| !self.is_hydrogen_atom(neighbor))
+    }
+
+    /// Iterates every bond between two heavy atoms once, as `(row, column)`
+    /// pairs with `row < column`.
+    fn heavy_bonds(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.atom_nodes.len()).filter(move |&atom_id| !self.is_hydrogen_atom(atom_id)).flat_map(
+            move |row| {
+                self.heavy_neighbors(row)
+                    .filter(move |&column| row < column)
+                    .map(move |column| (row, column))
+            },
+        )
+    }
+
+    /// Iterates every bond once together with its stored entry.
+    fn bond_triples(&self) -> impl Iterator<Item = (usize, usize, BondEntry)> + '_ {
+        (0..self.atom_nodes.len()).flat_map(move |row| {
+            self.bond_matrix
+                .sparse_row(row)
+                .zip(self.bond_matrix.sparse_row_values_ref(row))
+                .filter(move |&(column, _)| row < column)
+                .map(move |(column, entry)| (row, column, *entry))
+        })
+    }
+
+    fn heavy_component_count(&self) -> usize {
+        let mut visited = vec![false; self.atom_nodes.len()];
+        let mut component_count = 0;
+        let mut stack = Vec::new();
+        for start in 0..self.atom_nodes.len() {
+            if visited[start] || self.is_hydrogen_atom(start) {
+                continue;
+            }
+            component_count += 1;
+            visited[start] = true;
+            stack.push(start);
+            while let Some(atom_id) = stack.pop() {
+                for neighbor in self.heavy_neighbors(atom_id) {
+                    if !visited[neighbor] {
+                        visited[neighbor] = true;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+        component_count
+    }
+
+    /// Returns, for every atom, the sum of shortest-path lengths to the other
+    /// heavy atoms of its component. Hydrogen atoms get a zero sum.
+    fn heavy_distance_sums(&self) -> Vec<usize> {
+        let atom_count = self.atom_nodes.len();
+        let mut sums = vec![0; atom_count];
+        let mut distances = vec![usize::MAX; atom_count];
+        let mut queue = VecDeque::new();
+        for (source, sum) in sums.iter_mut().enumerate() {
+            if self.is_hydrogen_atom(source) {
+                continue;
+            }
+            distances.fill(usize::MAX);
+            distances[source] = 0;
+            queue.push_back(source);
+            while let Some(atom_id) = queue.pop_front() {
+                *sum += distances[atom_id];
+                for neighbor in self.heavy_neighbors(atom_id) {
+                    if distances[neighbor] == usize::MAX {
+                        distances[neighbor] = distances[atom_id] + 1;
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+        sums
+    }
+}
+
+/// Returns the bond radius used by the Labute surface area, in angstrom.
+fn bond_radius(element: Element) -> Option<f64> {
+    Some(match element {
+        Element::H => HYDROGEN_BOND_RADIUS,
+        Element::Li => 1.23,
+        Element::B => 0.82,
+        Element::C => 0.77,
+        Element::N => 0.70,
+        Element::O => 0.66,
+        Element::F => 0.611,
+        Element::Na => 1.54,
+        Element::Mg => 1.36,
+        Element::Al => 1.18,
+        Element::Si => 0.937,
+        Element::P => 0.89,
+        Element::S => 1.04,
+        Element::Cl => 0.997,
+        Element::K => 2.03,
+        Element::Ca => 1.74,
+        Element::Zn => 1.25,
+        Element::Ge => 1.22,
+        Element::As => 1.21,
+        Element::Se => 1.17,
+        Element::Br => 1.14,
+        Element::Sn => 1.4,
+        Element::Sb => 1.41,
+        Element::Te => 1.37,
+        Element::I => 1.33,
+        _ => return None,
+    })
+}
+
+/// Returns the bond length Labute assigns to a pair of radii: their sum,
+/// shortened for aromatic and multiple bonds, clamped to the geometrically
+/// meaningful range.
+fn bond_distance(left_radius: f64, right_radius: f64, entry: BondEntry) -> f64 {
+    let shortening = if entry.aromatic() {
+        0.1
+    } else {
+        match entry.bond() {
+            Bond::Single | Bond::Up | Bond::Down => 0.0,
+            Bond::Quadruple => unreachable!("Labute ASA excludes quadruple bonds"),
+            Bond::Double => 0.2,
+            Bond::Triple => 0.3,
+        }
+    };
+    let length = left_radius + right_radius - shortening;
+    length.max((left_radius - right_radius).abs()).min(left_radius + right_radius)
+}
+
+/// Returns the (scaled) area of the sphere of radius `own_radius` covered by a
+/// neighbour of radius `neighbor_radius` at the given distance.
+fn hidden_area(neighbor_radius: f64, own_radius: f64, length: f64) -> f64 {
+    neighbor_radius * neighbor_radius - (own_radius - length) * (own_radius - length) / length
+}
+
+fn sphere_contribution(radius: f64, hidden: f64) -> f64 {
+    core::f64::consts::PI * radius * (4.0 * radius - hidden)
+}
+
+fn to_f64(value: usize) -> f64 {
+    f64::from(u32::try_from(value).unwrap_or(u32::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1.0e-9, "expected {expected}, got {actual}");
+    }
+
+    #[test]
+    fn zagreb_indices_of_isobutane() {
+        let smiles: Smiles = "CC(C)C".parse().unwrap();
+
+        assert_eq!(smiles.first_zagreb_index(), 12);
+        assert_eq!(smiles.second_zagreb_index(), 9);
+    }
+
+    #[test]
+    fn graph_indices_ignore_hydrogen_spelling() {
+        let implicit: Smiles = "CCO".parse().unwrap();
+        let explicit = implicit.with_explicit_hydrogens();
+
+        assert_eq!(explicit.first_zagreb_index(), implicit.first_zagreb_index());
+        assert_eq!(explicit.second_zagreb_index(), implicit.second_zagreb_index());
+        assert_eq!(explicit.wiener_index(), implicit.wiener_index());
+        assert_close(explicit.balaban_j(), implicit.balaban_j());
+    }
+
+    #[test]
+    fn wiener_index_ignores_pairs_across_components() {
+        let smiles: Smiles = "CCC.CC".parse().unwrap();
+
+        assert_eq!(smiles.wiener_index(), 5);
+    }
+
+    #[test]
+    fn balaban_j_matches_reference_values() {
+        let propane: Smiles = "CCC".parse().unwrap();
+        let cyclohexane: Smiles = "C1CCCCC1".parse().unwrap();
+        let methane: Smiles = "C".parse().unwrap();
+
+        assert_close(propane.balaban_j(), 2.0 / sqrt(6.0) * 2.0);
+        assert_close(cyclohexane.balaban_j(), 2.0);
+        assert_close(methane.balaban_j(), 0.0);
+    }
+
+    #[test]
+    fn labute_asa_of_methane_matches_hand_computation() {
+        let smiles: Smiles = "C".parse().unwrap();
+
+        assert_close(smiles.labute_asa().unwrap(), 12.605_200_699_557_543);
+    }
+
+    #[test]
+    fn labute_asa_is_independent_of_hydrogen_spelling() {
+        let implicit: Smiles = "OCC=O".parse().unwrap();
+        let bracket: Smiles = "[OH][CH2][CH]=O".parse().unwrap();
+        let explicit = implicit.with_explicit_hydrogens();
+        let expected = implicit.labute_asa().unwrap();
+
+        assert_close(bracket.labute_asa().unwrap(), expected);
+        assert_close(explicit.labute_asa().unwrap(), expected);
+    }
+
+    #[test]
+    fn labute_asa_rejects_untabulated_elements() {
+        let smiles: Smiles = "[U]".parse().unwrap();
+
+        assert_eq!(smiles.labute_asa(), None);
+    }
+
+    #[test]
+    fn labute_asa_rejects_quadruple_bonds() {
+        let smiles: Smiles = "C$C".parse().unwrap();
+
+        assert_eq!(smiles.labute_asa(), None);
+    }
+}
@@ -0,0 +1,450 @@
+//! Atom- and bond-level differences between two [`Smiles`] graphs.
+
+use alloc::vec::Vec;
+
+use geometric_traits::traits::SparseValuedMatrixRef;
+
+use super::{
+    BondMatrixBuilder, Smiles, SmilesAtomPolicy, StereoNeighbor, WildcardSmiles,
+    fragment::skeleton_atom, stereo::normalized_tetrahedral_chirality,
+};
+use crate::{bond::Bond, errors::RenumberError};
+
+/// An atom attribute compared by [`Smiles::diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AtomAttribute {
+    /// The element or wildcard symbol.
+    Symbol,
+    /// The aromatic flag.
+    Aromaticity,
+    /// The formal charge.
+    Charge,
+    /// The isotope mass number.
+    Isotope,
+    /// The total hydrogen count, bracket and implicit hydrogens combined.
+    Hydrogens,
+    /// The tetrahedral or other chirality, compared in the frame of the
+    /// mapped neighbors rather than as a raw `@`/`@@` tag.
+    Chirality,
+    /// The atom class.
+    Class,
+}
+
+/// A bond attribute compared by [`Smiles::diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BondAttribute {
+    /// The bond order, ignoring the `/` and `\` directions.
+    Order,
+    /// The `E`/`Z` configuration of a double bond.
+    Stereo,
+}
+
+/// The differences between a left and a right graph, as reported by
+/// [`Smiles::diff`] and [`Smiles::diff_with_mapping`].
+///
+/// Removed atoms and bonds, and changed attributes, use the atom ids of the
+/// left graph; added atoms and bonds use those of the right graph. Bonds are
+/// reported as `(smaller, larger)` id pairs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmilesDiff {
+    mapping: Vec<Option<usize>>,
+    removed_atoms: Vec<usize>,
+    added_atoms: Vec<usize>,
+    removed_bonds: Vec<(usize, usize)>,
+    added_bonds: Vec<(usize, usize)>,
+    changed_atoms: Vec<(usize, AtomAttribute)>,
+    changed_bonds: Vec<((usize, usize), BondAttribute)>,
+}
+
+impl SmilesDiff {
+    /// Returns, for each left atom, the right atom it was aligned with.
+    #[inline]
+    #[must_use]
+    pub fn mapping(&self) -> &[Option<usize>] {
+        &self.mapping
+    }
+
+    /// Returns the left atoms without a counterpart in the right graph.
+    #[inline]
+    #[must_use]
+    pub fn removed_atoms(&self) -> &[usize] {
+        &self.removed_atoms
+    }
+
+    /// Returns the right atoms without a counterpart in the left graph.
+    #[inline]
+    #[must_use]
+    pub fn added_atoms(&self) -> &[usize] {
+        &self.added_atoms
+    }
+
+    /// Returns the left bonds without a counterpart in the right graph.
+    #[inline]
+    #[must_use]
+    pub fn removed_bonds(&self) -> &[(usize, usize)] {
+        &self.removed_bonds
+    }
+
+    /// Returns the right bonds without a counterpart in the left graph.
+    #[inline]
+    #[must_use]
+    pub fn added_bonds(&self) -> &[(usize, usize)] {
+        &self.added_bonds
+    }
+
+    /// Returns the attributes that differ between aligned atoms, keyed by the
+    /// left atom id.
+    #[inline]
+    #[must_use]
+    pub fn changed_atoms(&self) -> &[(usize, AtomAttribute)] {
+        &self.changed_atoms
+    }
+
+    /// Returns the attributes that differ between aligned bonds, keyed by the
+    /// left bond.
+    #[inline]
+    #[must_use]
+    pub fn changed_bonds(&self) -> &[((usize, usize), BondAttribute)] {
+        &self.changed_bonds
+    }
+
+    /// Returns whether the two graphs are identical under the mapping.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.removed_atoms.is_empty()
+            && self.added_atoms.is_empty()
+            && self.removed_bonds.is_empty()
+            && self.added_bonds.is_empty()
+            && self.changed_atoms.is_empty()
+            && self.changed_bonds.is_empty()
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Compares the graph with `other` after aligning their atoms by
+    /// canonical rank.
+    ///
+    /// Both graphs are labeled by the canonical order of their skeleton, i.e.
+    /// symbols, aromatic flags, bond orders and connectivity alone, so edits
+    /// to charges, isotopes, hydrogens or stereo keep the alignment exact.
+    /// When atoms or bonds are added, removed or reordered the rank alignment
+    /// is only a heuristic; pass an explicit mapping to
+    /// [`Smiles::diff_with_mapping`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::{AtomAttribute, Smiles};
+    ///
+    /// let acid: Smiles = "CC(=O)O".parse()?;
+    /// let acetate: Smiles = "[O-]C(=O)C".parse()?;
+    /// let diff = acid.diff(&acetate);
+    ///
+    /// assert_eq!(diff.changed_atoms(), [(3, AtomAttribute::Charge), (3, AtomAttribute::Hydrogens)]);
+    /// assert!(diff.added_atoms().is_empty());
+    /// assert!(acid.diff(&acid).is_empty());
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> SmilesDiff {
+        let left_order = self.skeleton_order();
+        let right_order = other.skeleton_order();
+        let mut mapping = vec![None; self.atom_nodes.len()];
+        for (&left, &right) in left_order.iter().zip(&right_order) {
+            mapping[left] = Some(right);
+        }
+        self.diff_with_mapping(other, &mapping)
+            .unwrap_or_else(|_| unreachable!("canonical ranks align atoms one to one"))
+    }
+
+    /// Compares the graph with `other` under an explicit atom mapping, where
+    /// `mapping[i]` is the right atom aligned with left atom `i`, or `None`
+    /// when that atom was removed.
+    ///
+    /// # Errors
+    /// Returns a [`RenumberError`] if `mapping` does not have one entry per
+    /// left atom, points outside `other`, or maps two atoms to the same
+    /// right atom.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let ethanol: Smiles = "CCO".parse()?;
+    /// let ethylamine: Smiles = "CCN".parse()?;
+    /// let diff = ethanol.diff_with_mapping(&ethylamine, &[Some(0), Some(1), None]).unwrap();
+    ///
+    /// assert_eq!(diff.removed_atoms(), [2]);
+    /// assert_eq!(diff.added_atoms(), [2]);
+    /// assert_eq!(diff.removed_bonds(), [(1, 2)]);
+    /// assert_eq!(diff.added_bonds(), [(1, 2)]);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    pub fn diff_with_mapping(
+        &self,
+        other: &Self,
+        mapping: &[Option<usize>],
+    ) -> Result<SmilesDiff, RenumberError> {
+        if mapping.len() != self.atom_nodes.len() {
+            return Err(RenumberError::LengthMismatch {
+                expected: self.atom_nodes.len(),
+                actual: mapping.len(),
+            });
+        }
+        let mut inverse = vec![None; other.atom_nodes.len()];
+        for (left, &right) in mapping.iter().enumerate() {
+            let Some(right) = right else {
+                continue;
+            };
+            let slot = inverse.get_mut(right).ok_or(RenumberError::AtomOutOfRange(right))?;
+            if slot.is_some() {
+                return Err(RenumberError::DuplicateAtom(right));
+            }
+            *slot = Some(left);
+        }
+
+        let mut changed_atoms = Vec::new();
+        for (left, &right) in mapping.iter().enumerate() {
+            if let Some(right) = right {
+                changed_atoms.extend(
+                    self.changed_atom_attributes(left, other, right, mapping)
+                        .into_iter()
+                        .map(|attribute| (left, attribute)),
+                );
+            }
+        }
+
+        let mut removed_bonds = Vec::new();
+        let mut changed_bonds = Vec::new();
+        for ((left_row, left_column), entry) in self.bond_matrix.sparse_entries() {
+            if left_row >= left_column {
+                continue;
+            }
+            let bond = (left_row, left_column);
+            let Some(right_bond) = mapping[left_row].zip(mapping[left_column]) else {
+                removed_bonds.push(bond);
+                continue;
+            };
+            let Some(right_entry) = other.bond_for_node_pair(right_bond) else {
+                removed_bonds.push(bond);
+                continue;
+            };
+            if entry.bond().without_direction() != right_entry.without_direction() {
+                changed_bonds.push((bond, BondAttribute::Order));
+            } else if entry.bond() == Bond::Double
+                && self.double_bond_stereo_config(left_row, left_column)
+                    != other.double_bond_stereo_config(right_bond.0, right_bond.1)
+            {
+                changed_bonds.push((bond, BondAttribute::Stereo));
+            }
+        }
+        let added_bonds = other
+            .bond_matrix
+            .sparse_entries()
+            .map(|(bond, _)| bond)
+            .filter(|&(row, column)| {
+                row < column
+                    && inverse[row]
+                        .zip(inverse[column])
+                        .is_none_or(|left_bond| self.bond_for_node_pair(left_bond).is_none())
+            })
+            .collect();
+
+        Ok(SmilesDiff {
+            mapping: mapping.to_vec(),
+            removed_atoms: (0..mapping.len()).filter(|&left| mapping[left].is_none()).collect(),
+            added_atoms: (0..inverse.len()).filter(|&right| inverse[right].is_none()).collect(),
+            removed_bonds,
+            added_bonds,
+            changed_atoms,
+            changed_bonds,
+        })
+    }
+
+    /// Returns the atom ids in the canonical order of the graph's skeleton.
+    fn skeleton_order(&self) -> Vec<usize> {
+        let atom_nodes: Vec<_> = self.atom_nodes.iter().map(|&atom| skeleton_atom(atom)).collect();
+        let mut builder = BondMatrixBuilder::default();
+        for ((row, column), entry) in self.bond_matrix.sparse_entries() {
+            if row < column {
+                let descriptor = entry.descriptor();
+                let descriptor = descriptor.with_bond(descriptor.bond().without_direction());
+                builder
+                    .push_edge_with_descriptor(row, column, descriptor, None)
+                    .unwrap_or_else(|_| unreachable!("the skeleton preserves a simple graph"));
+            }
+        }
+        let atom_count = atom_nodes.len();
+        Self::from_bond_matrix_parts_with_parsed_stereo_and_source(
+            atom_nodes,
+            builder.finish(atom_count),
+            vec![Vec::new(); atom_count],
+            None,
+        )
+        .exact_canonical_labeling()
+        .order()
+        .to_vec()
+    }
+
+    fn changed_atom_attributes(
+        &self,
+        left: usize,
+        other: &Self,
+        right: usize,
+        mapping: &[Option<usize>],
+    ) -> Vec<AtomAttribute> {
+        let left_atom = self.atom_nodes[left];
+        let right_atom = other.atom_nodes[right];
+        let left_hydrogens = left_atom.hydrogen_count() + self.implicit_hydrogen_count(left);
+        let right_hydrogens = right_atom.hydrogen_count() + other.implicit_hydrogen_count(right);
+
+        // Re-express the left chirality in the neighbor order of the right
+        // atom, so that respellings such as `F[C@H](Cl)Br` and
+        // `Cl[C@@H](F)Br` of the same centre compare equal.
+        let mapped_neighbors = self
+            .parsed_stereo_neighbors_row(left)
+            .iter()
+            .map(|&neighbor| {
+                match neighbor {
+                    StereoNeighbor::Atom(atom_id) => {
+                        mapping[atom_id]
+                            .map_or(StereoNeighbor::Atom(usize::MAX), StereoNeighbor::Atom)
+                    }
+                    StereoNeighbor::ExplicitHydrogen => StereoNeighbor::ExplicitHydrogen,
+                }
+            })
+            .collect::<Vec<_>>();
+        let left_chirality = normalized_tetrahedral_chirality(
+            left_atom.chirality(),
+            &mapped_neighbors,
+            other.parsed_stereo_neighbors_row(right),
+        );
+        let same_chirality = match (left_chirality, right_atom.chirality()) {
+            (Some(left), Some(right)) => left.is_equivalent(right),
+            (left, right) => left == right,
+        };
+
+        [
+            (AtomAttribute::Symbol, left_atom.symbol() == right_atom.symbol()),
+            (AtomAttribute::Aromaticity, left_atom.aromatic() == right_atom.aromatic()),
+            (AtomAttribute::Charge, left_atom.charge_value() == right_atom.charge_value()),
+            (
+                AtomAttribute::Isotope,
+                left_atom.isotope_mass_number() == right_atom.isotope_mass_number(),
+            ),
+            (AtomAttribute::Hydrogens, left_hydrogens == right_hydrogens),
+            (AtomAttribute::Chirality, same_chirality),
+            (AtomAttribute::Class, left_atom.class() == right_atom.class()),
+        ]
+        .into_iter()
+        .filter_map(|(attribute, same)| (!same).then_some(attribute))
+        .collect()
+    }
+}
+
+impl WildcardSmiles {
+    /// Compares the graph with `other` after aligning their atoms by
+    /// canonical rank.
+    #[inline]
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> SmilesDiff {
+        self.inner().diff(other.inner())
+    }
+
+    /// Compares the graph with `other` under an explicit atom mapping.
+    ///
+    /// # Errors
+    /// Returns a [`RenumberError`] if `mapping` is not a valid partial
+    /// injection from the atoms of this graph into those of `other`.
+    #[inline]
+    pub fn diff_with_mapping(
+        &self,
+        other: &Self,
+        mapping: &[Option<usize>],
+    ) -> Result<SmilesDiff, RenumberError> {
+        self.inner().diff_with_mapping(other, mapping)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn smiles(input: &str) -> Smiles {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn respellings_have_an_empty_diff() {
+        for (left, right) in [
+            ("CCO", "OCC"),
+            ("c1ccccc1O", "Oc1ccccc1"),
+            ("F[C@H](Cl)Br", "Cl[C@@H](F)Br"),
+            ("F/C=C/F", "F\\C=C\\F"),
+            ("[13CH3]C", "C[13CH3]"),
+        ] {
+            let diff = smiles(left).diff(&smiles(right));
+            assert!(diff.is_empty(), "{left} vs {right}: {diff:?}");
+        }
+    }
+
+    #[test]
+    fn attribute_edits_are_reported_on_aligned_atoms() {
+        let diff = smiles("F[C@H](Cl)Br").diff(&smiles("F[C@@H](Cl)Br"));
+        assert_eq!(diff.changed_atoms(), [(1, AtomAttribute::Chirality)]);
+
+        let diff = smiles("CC").diff(&smiles("[13CH3]C"));
+        assert_eq!(diff.changed_atoms().len(), 1);
+        assert_eq!(diff.changed_atoms()[0].1, AtomAttribute::Isotope);
+
+        let diff = smiles("F/C=C/F").diff(&smiles("F/C=C\\F"));
+        assert_eq!(diff.changed_bonds(), [((1, 2), BondAttribute::Stereo)]);
+
+        let diff = smiles("CCC").diff(&smiles("C=CC"));
+        assert_eq!(diff.changed_bonds().len(), 1);
+        assert_eq!(diff.changed_bonds()[0].1, BondAttribute::Order);
+        assert!(
+            diff.changed_atoms()
+                .iter()
+                .all(|&(_, attribute)| attribute == AtomAttribute::Hydrogens)
+        );
+    }
+
+    #[test]
+    fn mapped_diffs_report_added_and_removed_atoms() {
+        let diff = smiles("CCO")
+            .diff_with_mapping(&smiles("CC(O)Cl"), &[Some(0), Some(1), Some(2)])
+            .unwrap();
+        assert!(diff.removed_atoms().is_empty());
+        assert_eq!(diff.added_atoms(), [3]);
+        assert_eq!(diff.added_bonds(), [(1, 3)]);
+        assert_eq!(diff.changed_atoms(), [(1, AtomAttribute::Hydrogens)]);
+        assert_eq!(diff.mapping(), [Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn invalid_mappings_are_rejected() {
+        let left = smiles("CO");
+        let right = smiles("CN");
+        assert_eq!(
+            left.diff_with_mapping(&right, &[Some(0)]),
+            Err(RenumberError::LengthMismatch { expected: 2, actual: 1 })
+        );
+        assert_eq!(
+            left.diff_with_mapping(&right, &[Some(0), Some(5)]),
+            Err(RenumberError::AtomOutOfRange(5))
+        );
+        assert_eq!(
+            left.diff_with_mapping(&right, &[Some(1), Some(1)]),
+            Err(RenumberError::DuplicateAtom(1))
+        );
+    }
+
+    #[test]
+    fn wildcard_graphs_delegate() {
+        let left: WildcardSmiles = "*CO".parse().unwrap();
+        let right: WildcardSmiles = "*C[O-]".parse().unwrap();
+        assert_eq!(left.diff(&right).changed_atoms().len(), 2);
+    }
+}
@@ -480,7 +480,7 @@ fn substituent_priority_key(
     let atom = smiles.node_by_id(neighbor).unwrap_or_else(|| unreachable!());
     let atomic_number = atom.element().map_or(0, u8::from);
     let edge = smiles.edge_for_node_pair((endpoint, neighbor)).unwrap_or_else(|| unreachable!());
-    let bond_order_to_endpoint = if edge.is_aromatic() { 1 } else { bond_priority(edge.bond()) };
+    let bond_order_to_endpoint = edge.descriptor().order().unwrap_or(1);
 
     SubstituentPriorityKey {
         atomic_number,
@@ -493,15 +493,6 @@ fn substituent_priority_key(
     }
 }
 
-fn bond_priority(bond: Bond) -> u8 {
-    match bond {
-        Bond::Single | Bond::Up | Bond::Down => 1,
-        Bond::Double => 2,
-        Bond::Triple => 3,
-        Bond::Quadruple => 4,
-    }
-}
-
 #[inline]
 fn non_aromatic_double_bond(entry: super::BondEntry) -> bool {
     entry.bond() == Bond::Double && !entry.aromatic()
@@ -697,6 +688,29 @@ mod tests {
         assert!(records.iter().all(|record| record.config() == DoubleBondStereoConfig::E));
     }
 
+    #[test]
+    fn ring_closure_bond_directions_carry_double_bond_stereo() {
+        for (closure, chain, config) in [
+            ("F/C=C/1.Cl1", "F/C=C/Cl", DoubleBondStereoConfig::E),
+            ("F/C=C\\1.Cl1", "F/C=C\\Cl", DoubleBondStereoConfig::Z),
+            ("Cl1.F/C=C/1", "F/C=C/Cl", DoubleBondStereoConfig::E),
+            ("Cl1.F/C=C\\1", "F/C=C\\Cl", DoubleBondStereoConfig::Z),
+        ] {
+            let closed = parse(closure);
+            let records = closed.double_bond_stereo_records();
+            assert_eq!(records.len(), 1, "{closure}");
+            assert_eq!(records[0].config(), config, "{closure}");
+            assert_eq!(parse(chain).double_bond_stereo_records()[0].config(), config, "{chain}");
+
+            let reparsed = parse(&closed.to_string());
+            assert_eq!(
+                semantic_double_bond_stereo_signature(&closed),
+                semantic_double_bond_stereo_signature(&reparsed),
+                "{closure}"
+            );
+        }
+    }
+
     #[test]
     fn render_roundtrip_preserves_semantic_double_bond_stereo() {
         for input in [
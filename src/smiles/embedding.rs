@@ -0,0 +1,450 @@
+//! Experimental 3D coordinate embedding for [`Smiles`] graphs.
+//!
+//! The embedding is a distance-geometry sketch: every pair of atoms in a
+//! connected component receives a target distance derived from its bond
+//! length (for bonded pairs) or its topological distance (for everything
+//! else), and the coordinates are fitted to those targets by stress
+//! majorization followed by a short locally weighted refinement. The result is
+//! a rough, deterministic geometry meant as a starting point for an external
+//! force-field optimization, not as a conformer in its own right: stereo
+//! descriptors are not enforced and only the atoms present in the graph are
+//! placed.
+
+use alloc::{collections::VecDeque, vec::Vec};
+
+use elements_rs::Element;
+use geometric_traits::traits::{SparseMatrix2D, SparseValuedMatrix2DRef};
+
+use super::{BondEntry, Smiles, SmilesAtomPolicy};
+use crate::bond::Bond;
+
+/// Number of unweighted stress-majorization iterations per component.
+const MAJORIZATION_ITERATIONS: usize = 300;
+/// Number of locally weighted gradient iterations run after majorization.
+const REFINEMENT_ITERATIONS: usize = 200;
+/// Gradient step used by the weighted refinement.
+const REFINEMENT_STEP: f64 = 0.5;
+/// Target distance between atoms separated by two bonds, in angstrom.
+const ONE_THREE_DISTANCE: f64 = 2.5;
+/// Additional target distance per bond beyond the first two, in angstrom.
+const DISTANCE_PER_EXTRA_BOND: f64 = 1.25;
+/// Spacing left between the bounding boxes of disconnected components.
+const COMPONENT_GAP: f64 = 4.0;
+/// Seed for the deterministic initial coordinates.
+const INITIAL_SEED: u64 = 0x5EED;
+
+/// Rough 3D coordinates produced by [`Smiles::embed_3d`].
+///
+/// Positions are indexed by atom id and expressed in angstrom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conformer {
+    positions: Vec<[f64; 3]>,
+}
+
+impl Conformer {
+    /// Returns the position of every atom, indexed by atom id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let conformer = "CCO".parse::<Smiles>()?.embed_3d();
+    /// assert_eq!(conformer.positions().len(), 3);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn positions(&self) -> &[[f64; 3]] {
+        &self.positions
+    }
+
+    /// Returns the position of the provided atom id.
+    ///
+    /// # Panics
+    /// Panics if `atom_id` is not a valid atom index in this conformer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let conformer = "C".parse::<Smiles>()?.embed_3d();
+    /// assert_eq!(conformer.position(0), [0.0, 0.0, 0.0]);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn position(&self, atom_id: usize) -> [f64; 3] {
+        assert!(
+            atom_id < self.positions.len(),
+            "invalid atom index {atom_id} for conformer with {} atoms",
+            self.positions.len()
+        );
+        self.positions[atom_id]
+    }
+
+    /// Returns the euclidean distance between two atoms, in angstrom.
+    ///
+    /// # Panics
+    /// Panics if either atom id is not a valid atom index in this conformer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let conformer = "CC".parse::<Smiles>()?.embed_3d();
+    /// assert!((conformer.distance(0, 1) - 1.5).abs() < 0.1);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn distance(&self, atom_a: usize, atom_b: usize) -> f64 {
+        distance(self.position(atom_a), self.position(atom_b))
+    }
+
+    /// Consumes the conformer and returns the per-atom positions.
+    #[inline]
+    #[must_use]
+    pub fn into_positions(self) -> Vec<[f64; 3]> {
+        self.positions
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Generates rough 3D coordinates for every atom in the graph.
+    ///
+    /// This is an experimental distance-geometry embedding: it reproduces
+    /// typical bond lengths and 1-3 distances but does not enforce
+    /// chirality, double-bond geometry or ring planarity, so the output should
+    /// be refined with a force field before any quantitative use. Implicit
+    /// hydrogens are not placed; call [`Smiles::with_explicit_hydrogens`]
+    /// first to embed them as well. Disconnected components are embedded
+    /// independently and laid out side by side along the x axis.
+    ///
+    /// The embedding is deterministic and costs `O(n^2)` time and memory per
+    /// iteration in the size of the largest component.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let smiles: Smiles = "C1CCCCC1".parse()?;
+    /// let conformer = smiles.embed_3d();
+    ///
+    /// assert_eq!(conformer.positions().len(), 6);
+    /// assert!((conformer.distance(0, 1) - 1.5).abs() < 0.2);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn embed_3d(&self) -> Conformer {
+        let atom_count = self.atom_nodes.len();
+        let mut positions = vec![[0.0; 3]; atom_count];
+        let mut visited = vec![false; atom_count];
+        let mut seed = INITIAL_SEED;
+        let mut offset = 0.0_f64;
+
+        for start in 0..atom_count {
+            if visited[start] {
+                continue;
+            }
+            let members = self.component_members(start, &mut visited);
+            let targets = self.target_distances(&members);
+            let mut component = initial_positions(members.len(), &mut seed);
+            majorize(&mut component, &targets);
+            refine(&mut component, &targets);
+
+            let (lowest_x, highest_x) =
+                component.iter().fold((f64::MAX, f64::MIN), |(low, high), point| {
+                    (low.min(point[0]), high.max(point[0]))
+                });
+            let center = centroid(&component);
+            for (&atom_id, point) in members.iter().zip(&component) {
+                positions[atom_id] =
+                    [point[0] - lowest_x + offset, point[1] - center[1], point[2] - center[2]];
+            }
+            offset += highest_x - lowest_x + COMPONENT_GAP;
+        }
+
+        Conformer { positions }
+    }
+
+    /// Collects the atoms reachable from `start` in breadth-first order.
+    fn component_members(&self, start: usize, visited: &mut [bool]) -> Vec<usize> {
+        let mut members = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+        while let Some(atom_id) = queue.pop_front() {
+            members.push(atom_id);
+            for neighbor in self.bond_matrix.sparse_row(atom_id) {
+                if !visited[neighbor] {
+                    visited[neighbor] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        members
+    }
+
+    /// Builds the dense target-distance matrix for one connected component.
+    fn target_distances(&self, members: &[usize]) -> Vec<Vec<f64>> {
+        let mut local_of_atom = vec![usize::MAX; self.atom_nodes.len()];
+        for (local, &atom_id) in members.iter().enumerate() {
+            local_of_atom[atom_id] = local;
+        }
+
+        let mut targets = vec![vec![0.0; members.len()]; members.len()];
+        let mut hops = vec![usize::MAX; members.len()];
+        let mut queue = VecDeque::new();
+        for (source, &source_atom) in members.iter().enumerate() {
+            hops.fill(usize::MAX);
+            hops[source] = 0;
+            queue.push_back(source_atom);
+            while let Some(atom_id) = queue.pop_front() {
+                let current_hops = hops[local_of_atom[atom_id]];
+                for (neighbor, entry) in self
+                    .bond_matrix
+                    .sparse_row(atom_id)
+                    .zip(self.bond_matrix.sparse_row_values_ref(atom_id))
+                {
+                    let local = local_of_atom[neighbor];
+                    if hops[local] != usize::MAX {
+                        continue;
+                    }
+                    hops[local] = current_hops + 1;
+                    targets[source][local] = if atom_id == source_atom {
+                        self.bond_length(source_atom, neighbor, *entry)
+                    } else {
+                        ONE_THREE_DISTANCE
+                            + DISTANCE_PER_EXTRA_BOND * usize_to_f64(current_hops - 1)
+                    };
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        targets
+    }
+
+    /// Returns a typical length for the bond between two atoms, in angstrom.
+    fn bond_length(&self, atom_a: usize, atom_b: usize, entry: BondEntry) -> f64 {
+        let involves_hydrogen = [atom_a, atom_b]
+            .into_iter()
+            .any(|atom_id| self.atom_nodes[atom_id].element() == Some(Element::H));
+        if involves_hydrogen {
+            return 1.09;
+        }
+        if entry.aromatic() {
+            return 1.40;
+        }
+        match entry.bond() {
+            Bond::Single | Bond::Up | Bond::Down => 1.50,
+            Bond::Double => 1.34,
+            Bond::Triple => 1.20,
+            Bond::Quadruple => 1.10,
+        }
+    }
+}
+
+/// Spreads the atoms of a component over a cube with deterministic
+/// pseudo-random coordinates, so that no two atoms start on top of each other.
+fn initial_positions(atom_count: usize, seed: &mut u64) -> Vec<[f64; 3]> {
+    let half_side = usize_to_f64(atom_count).max(4.0) * 0.375;
+    (0..atom_count)
+        .map(|_| {
+            let mut point = [0.0; 3];
+            for coordinate in &mut point {
+                *coordinate = (next_unit(seed) * 2.0 - 1.0) * half_side;
+            }
+            point
+        })
+        .collect()
+}
+
+/// Runs unweighted stress majorization (SMACOF) towards the target distances.
+fn majorize(positions: &mut [[f64; 3]], targets: &[Vec<f64>]) {
+    let atom_count = positions.len();
+    if atom_count < 2 {
+        return;
+    }
+    let inverse_count = 1.0 / usize_to_f64(atom_count);
+    let mut next = vec![[0.0; 3]; atom_count];
+    for _ in 0..MAJORIZATION_ITERATIONS {
+        for (left, updated) in next.iter_mut().enumerate() {
+            let mut accumulator = [0.0; 3];
+            for right in 0..atom_count {
+                if left == right {
+                    continue;
+                }
+                let current = distance(positions[left], positions[right]);
+                if current < f64::EPSILON {
+                    continue;
+                }
+                let ratio = targets[left][right] / current;
+                let delta = difference(positions[left], positions[right]);
+                for (total, component) in accumulator.iter_mut().zip(delta) {
+                    *total += ratio * component;
+                }
+            }
+            *updated = accumulator.map(|value| value * inverse_count);
+        }
+        positions.copy_from_slice(&next);
+    }
+}
+
+/// Refines the layout by gradient descent on the stress weighted by the
+/// inverse squared target distance, which favours short-range geometry.
+fn refine(positions: &mut [[f64; 3]], targets: &[Vec<f64>]) {
+    let atom_count = positions.len();
+    let mut gradient = vec![[0.0; 3]; atom_count];
+    for _ in 0..REFINEMENT_ITERATIONS {
+        gradient.fill([0.0; 3]);
+        for left in 0..atom_count {
+            for right in left + 1..atom_count {
+                let target = targets[left][right];
+                let current = distance(positions[left], positions[right]);
+                if current < f64::EPSILON {
+                    continue;
+                }
+                let coefficient = (current - target) / (current * target * target);
+                let force = difference(positions[left], positions[right])
+                    .map(|component| coefficient * component);
+                for (total, component) in gradient[left].iter_mut().zip(force) {
+                    *total += component;
+                }
+                for (total, component) in gradient[right].iter_mut().zip(force) {
+                    *total -= component;
+                }
+            }
+        }
+        for (point, step) in positions.iter_mut().zip(&gradient) {
+            for (coordinate, component) in point.iter_mut().zip(step) {
+                *coordinate -= REFINEMENT_STEP * component;
+            }
+        }
+    }
+}
+
+fn centroid(positions: &[[f64; 3]]) -> [f64; 3] {
+    let inverse_count = 1.0 / usize_to_f64(positions.len().max(1));
+    let mut sum = [0.0; 3];
+    for point in positions {
+        for (total, coordinate) in sum.iter_mut().zip(point) {
+            *total += coordinate;
+        }
+    }
+    sum.map(|value| value * inverse_count)
+}
+
+fn difference(left: [f64; 3], right: [f64; 3]) -> [f64; 3] {
+    [left[0] - right[0], left[1] - right[1], left[2] - right[2]]
+}
+
+fn distance(left: [f64; 3], right: [f64; 3]) -> f64 {
+    sqrt(difference(left, right).iter().map(|component| component * component).sum())
+}
+
+/// Square root by Newton iteration, since `f64::sqrt` is not available in
+/// `core`.
+pub(super) fn sqrt(value: f64) -> f64 {
+    if value <= 0.0 || !value.is_finite() {
+        return value.max(0.0);
+    }
+    // Halving the biased exponent gives an estimate within a factor of two,
+    // which six Newton steps refine well past `f64` precision.
+    let mut estimate = f64::from_bits((value.to_bits() >> 1) + (1023_u64 << 51));
+    for _ in 0..6 {
+        estimate = 0.5 * (estimate + value / estimate);
+    }
+    estimate
+}
+
+/// Returns the next value of a splitmix64 sequence mapped into `[0, 1)`.
+fn next_unit(state: &mut u64) -> f64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut mixed = *state;
+    mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    mixed ^= mixed >> 31;
+    let high = u32::try_from(mixed >> 32).unwrap_or_else(|_| unreachable!("shifted into u32"));
+    f64::from(high) / 4_294_967_296.0
+}
+
+fn usize_to_f64(value: usize) -> f64 {
+    f64::from(u32::try_from(value).unwrap_or(u32::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "expected {expected} +/- {tolerance}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn sqrt_matches_std() {
+        for value in [0.0, 1.0e-12, 0.25, 2.0, 9.0, 1.0e6, 1.0e300] {
+            assert_close(sqrt(value), value.sqrt(), value.sqrt() * 1.0e-12 + 1.0e-300);
+        }
+    }
+
+    #[test]
+    fn chain_reproduces_bond_lengths_and_one_three_distances() {
+        let smiles: Smiles = "CCCCCC".parse().unwrap();
+        let conformer = smiles.embed_3d();
+
+        for atom_id in 0..5 {
+            assert_close(conformer.distance(atom_id, atom_id + 1), 1.5, 0.05);
+        }
+        for atom_id in 0..4 {
+            assert_close(conformer.distance(atom_id, atom_id + 2), 2.5, 0.1);
+        }
+    }
+
+    #[test]
+    fn bond_order_shortens_target_length() {
+        let smiles: Smiles = "C#CC".parse().unwrap();
+        let conformer = smiles.embed_3d();
+
+        assert!(conformer.distance(0, 1) < conformer.distance(1, 2));
+    }
+
+    #[test]
+    fn ring_bonds_stay_close_to_target() {
+        let smiles: Smiles = "c1ccccc1".parse().unwrap();
+        let conformer = smiles.embed_3d();
+
+        for atom_id in 0..6 {
+            assert_close(conformer.distance(atom_id, (atom_id + 1) % 6), 1.4, 0.2);
+        }
+    }
+
+    #[test]
+    fn embedding_is_deterministic() {
+        let smiles: Smiles = "CC(C)(C)O".parse().unwrap();
+
+        assert_eq!(smiles.embed_3d(), smiles.embed_3d());
+    }
+
+    #[test]
+    fn disconnected_components_do_not_overlap() {
+        let smiles: Smiles = "CC.O".parse().unwrap();
+        let conformer = smiles.embed_3d();
+
+        assert_close(conformer.distance(0, 1), 1.5, 0.05);
+        assert!(conformer.distance(1, 2) >= COMPONENT_GAP);
+        assert!(conformer.distance(0, 2) >= COMPONENT_GAP);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid atom index 3 for conformer with 1 atoms")]
+    fn position_panics_for_invalid_atom_id() {
+        let smiles: Smiles = "C".parse().unwrap();
+        let _ = smiles.embed_3d().position(3);
+    }
+}
@@ -1,7 +1,10 @@
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 use core::fmt::Write;
 
-use super::{Smiles, SmilesAtomPolicy, render_plan::RenderPlan};
+use super::{
+    Smiles, SmilesAtomPolicy,
+    render_plan::{ChildRenderPlan, RenderPlan},
+};
 
 /// Renders a [`Smiles`] graph by first building a [`RenderPlan`] and then
 /// emitting text from that plan.
@@ -25,6 +28,22 @@ pub(crate) fn emit_rooted<AtomPolicy: SmilesAtomPolicy>(
     emit_with_plan(smiles, &plan)
 }
 
+/// Renders `smiles` and returns the atom ids in the order they were written.
+#[must_use]
+pub(crate) fn emit_with_traversal_order<AtomPolicy: SmilesAtomPolicy>(
+    smiles: &Smiles<AtomPolicy>,
+) -> (String, Vec<usize>) {
+    let plan = smiles.render_plan();
+    (emit_with_plan(smiles, &plan), plan.traversal_order())
+}
+
+/// Renders `smiles` from its pretty plan, see [`Smiles::pretty_render_plan`].
+#[must_use]
+pub(crate) fn emit_pretty<AtomPolicy: SmilesAtomPolicy>(smiles: &Smiles<AtomPolicy>) -> String {
+    let plan = smiles.pretty_render_plan();
+    emit_with_plan(smiles, &plan)
+}
+
 /// Emits a SMILES string from a completed render plan.
 ///
 /// No graph search or ordering work happens here. The emitter only walks
@@ -46,57 +65,84 @@ fn emit_with_plan<AtomPolicy: SmilesAtomPolicy>(
         if index != 0 {
             rendered.push('.');
         }
-        emit_node(smiles, plan, component.root(), &mut rendered);
+        emit_component(smiles, plan, component.root(), &mut rendered);
     }
 
     rendered
 }
 
-/// Emits one planned node recursively.
+/// One pending write of [`emit_component`].
+#[derive(Debug, Clone, Copy)]
+enum EmitStep {
+    /// Atom text and closures of a node, then its children.
+    Node(usize),
+    /// Bond text of a tree edge, opening a parenthesized branch if `branch`.
+    Child { parent: usize, child: ChildRenderPlan, branch: bool },
+    /// The closing parenthesis of a branch.
+    CloseBranch,
+}
+
+/// Emits one planned component, starting from `root`.
 ///
-/// The write order mirrors SMILES surface syntax:
+/// The write order mirrors SMILES surface syntax, for every node:
 ///
 /// 1. atom text
 /// 2. attached closures
 /// 3. parenthesized branch children
 /// 4. continuation child, if any
-fn emit_node<AtomPolicy: SmilesAtomPolicy>(
+///
+/// Pending writes are kept on an explicit stack rather than the call stack,
+/// so long chains and deeply nested branches cannot overflow it.
+fn emit_component<AtomPolicy: SmilesAtomPolicy>(
     smiles: &Smiles<AtomPolicy>,
     plan: &RenderPlan,
-    node_id: usize,
+    root: usize,
     target: &mut String,
 ) {
-    let node_plan = plan.node(node_id).unwrap_or_else(|| unreachable!());
-    let atom = smiles.node_by_id(node_id).unwrap_or_else(|| unreachable!());
-    atom.write_smiles_with_chirality_to_string(target, node_plan.normalized_chirality());
+    let mut stack = vec![EmitStep::Node(root)];
+    while let Some(step) = stack.pop() {
+        match step {
+            EmitStep::Node(node_id) => {
+                let node_plan = plan.node(node_id).unwrap_or_else(|| unreachable!());
+                let atom = smiles.node_by_id(node_id).unwrap_or_else(|| unreachable!());
+                atom.write_smiles_with_chirality_to_string(
+                    target,
+                    node_plan.normalized_chirality(),
+                );
 
-    for closure in node_plan.closures() {
-        if closure.emit_bond_symbol() {
-            target.push_str(rendered_bond_text(smiles, node_id, closure.partner(), closure.bond()));
-        }
-        write_ring_label(target, closure.label());
-    }
+                for closure in node_plan.closures() {
+                    if closure.emit_bond_symbol() {
+                        target.push_str(rendered_bond_text(
+                            smiles,
+                            node_id,
+                            closure.partner(),
+                            closure.bond(),
+                        ));
+                    }
+                    write_ring_label(target, closure.label());
+                }
 
-    for branch_child in node_plan.branch_children() {
-        target.push('(');
-        target.push_str(rendered_bond_text(
-            smiles,
-            node_id,
-            branch_child.child(),
-            branch_child.bond(),
-        ));
-        emit_node(smiles, plan, branch_child.child(), target);
-        target.push(')');
-    }
-
-    if let Some(continuation_child) = node_plan.continuation_child() {
-        target.push_str(rendered_bond_text(
-            smiles,
-            node_id,
-            continuation_child.child(),
-            continuation_child.bond(),
-        ));
-        emit_node(smiles, plan, continuation_child.child(), target);
+                if let Some(child) = node_plan.continuation_child() {
+                    stack.push(EmitStep::Child { parent: node_id, child, branch: false });
+                }
+                stack.extend(
+                    node_plan
+                        .branch_children()
+                        .iter()
+                        .rev()
+                        .map(|&child| EmitStep::Child { parent: node_id, child, branch: true }),
+                );
+            }
+            EmitStep::Child { parent, child, branch } => {
+                if branch {
+                    target.push('(');
+                    stack.push(EmitStep::CloseBranch);
+                }
+                target.push_str(rendered_bond_text(smiles, parent, child.child(), child.bond()));
+                stack.push(EmitStep::Node(child.child()));
+            }
+            EmitStep::CloseBranch => target.push(')'),
+        }
     }
 }
 
@@ -132,6 +178,7 @@ fn rendered_bond_text(
         }
         (crate::bond::Bond::Single | crate::bond::Bond::Double, true) => ":",
         (crate::bond::Bond::Single, false) if from_aromatic && to_aromatic => "-",
+        (crate::bond::Bond::Single, false) if smiles.is_explicit_single_bond(from, to) => "-",
         (crate::bond::Bond::Single, false) => "",
         (other, _) => other.smiles_symbol(),
     }
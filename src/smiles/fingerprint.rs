@@ -0,0 +1,285 @@
+//! Hashed path fingerprints for substructure screening and similarity.
+//!
+//! The fingerprint of a graph sets one bit for every simple path of up to
+//! [`MAX_PATH_BONDS`] bonds, labelled by the element and aromaticity of its
+//! atoms and the order of its bonds. Charges, hydrogen counts, isotopes and
+//! stereochemistry are ignored, and paths stop at wildcard atoms, so every bit
+//! of a query graph is also set in any graph containing it as a subgraph: a
+//! graph whose fingerprint is not a superset of the query fingerprint can be
+//! discarded without an exact match. Aromaticity is perceived with the
+//! default model before paths are hashed, so Kekulé and aromatic spellings of
+//! a ring fingerprint alike; graphs whose perception fails keep their written
+//! aromatic flags. Bonds between two aromatic atoms are labelled aromatic even
+//! when written as single bonds, matching the SMARTS reading of an implicit
+//! bond between aromatic atoms.
+//!
+//! Path labels are hashed with a fixed function, so fingerprints are stable
+//! across runs and platforms and can be persisted.
+
+use alloc::vec::Vec;
+
+use super::{Smiles, SmilesAtomPolicy, WildcardSmiles};
+use crate::{atom::atom_symbol::AtomSymbol, bond::Bond};
+
+/// The number of bits in a [`Fingerprint`].
+pub const FINGERPRINT_BITS: usize = 2048;
+/// The largest number of bonds in a fingerprinted path.
+pub const MAX_PATH_BONDS: usize = 6;
+const WORDS: usize = FINGERPRINT_BITS / 64;
+
+/// A fixed-width hashed path fingerprint.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::prelude::Smiles;
+///
+/// let phenol = "Oc1ccccc1".parse::<Smiles>()?.fingerprint();
+/// let benzene = "c1ccccc1".parse::<Smiles>()?.fingerprint();
+/// let ethanol = "CCO".parse::<Smiles>()?.fingerprint();
+///
+/// assert!(phenol.is_superset_of(&benzene));
+/// assert!(!benzene.is_superset_of(&phenol));
+/// assert!(phenol.tanimoto(&benzene) > phenol.tanimoto(&ethanol));
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Fingerprint {
+    words: [u64; WORDS],
+}
+
+impl Default for Fingerprint {
+    #[inline]
+    fn default() -> Self {
+        Self { words: [0; WORDS] }
+    }
+}
+
+impl Fingerprint {
+    /// Creates a fingerprint from its little-endian bit words, as returned by
+    /// [`Fingerprint::words`].
+    #[inline]
+    #[must_use]
+    pub const fn from_words(words: [u64; WORDS]) -> Self {
+        Self { words }
+    }
+
+    /// Returns the bits as words, bit `i` being bit `i % 64` of word `i / 64`.
+    #[inline]
+    #[must_use]
+    pub const fn words(&self) -> &[u64; WORDS] {
+        &self.words
+    }
+
+    /// Returns whether bit `bit` is set.
+    ///
+    /// # Panics
+    /// Panics if `bit` is not below [`FINGERPRINT_BITS`].
+    #[inline]
+    #[must_use]
+    pub fn contains_bit(&self, bit: usize) -> bool {
+        self.words[bit / 64] & (1 << (bit % 64)) != 0
+    }
+
+    /// Returns the number of set bits.
+    #[must_use]
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Iterates the set bits in increasing order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, &word)| {
+            (0..64).filter(move |bit| word & (1 << bit) != 0).map(move |bit| 64 * index + bit)
+        })
+    }
+
+    /// Returns whether every bit of `other` is also set in `self`, the
+    /// necessary condition for `other` to be a subgraph of `self`.
+    #[must_use]
+    pub fn is_superset_of(&self, other: &Self) -> bool {
+        self.words.iter().zip(&other.words).all(|(word, other)| word & other == *other)
+    }
+
+    /// Returns the Tanimoto coefficient, the number of bits set in both
+    /// fingerprints over the number of bits set in either, or `0.0` when
+    /// both are empty.
+    #[must_use]
+    pub fn tanimoto(&self, other: &Self) -> f64 {
+        let (common, union) =
+            self.words.iter().zip(&other.words).fold((0, 0), |(common, union), (a, b)| {
+                (common + (a & b).count_ones(), union + (a | b).count_ones())
+            });
+        if union == 0 { 0.0 } else { f64::from(common) / f64::from(union) }
+    }
+
+    /// Sets the bit of a path given as alternating atom and bond labels,
+    /// reading it in the direction with the smaller label sequence so both
+    /// ends of a path hash alike.
+    fn insert_path(&mut self, labels: &[u8]) {
+        let hash = if labels.iter().cmp(labels.iter().rev()).is_gt() {
+            fnv1a(labels.iter().rev())
+        } else {
+            fnv1a(labels.iter())
+        };
+        let bit = usize::try_from((hash ^ (hash >> 32)) % FINGERPRINT_BITS as u64)
+            .unwrap_or_else(|_| unreachable!("bit indices are below FINGERPRINT_BITS"));
+        self.words[bit / 64] |= 1 << (bit % 64);
+    }
+}
+
+/// 64-bit FNV-1a over the labels of a path.
+fn fnv1a<'a>(labels: impl Iterator<Item = &'a u8>) -> u64 {
+    labels.fold(0xcbf2_9ce4_8422_2325, |hash, &label| {
+        (hash ^ u64::from(label)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns the hashed path fingerprint of the graph, after perceiving
+    /// its aromaticity.
+    ///
+    /// See [`Fingerprint`] for an example.
+    #[must_use]
+    pub fn fingerprint(&self) -> Fingerprint {
+        match self.perceive_aromaticity() {
+            Ok(perception) => perception.aromaticized().fingerprint_as_written(),
+            Err(_) => self.fingerprint_as_written(),
+        }
+    }
+
+    /// Hashes the paths of the graph with its aromatic flags as written.
+    fn fingerprint_as_written(&self) -> Fingerprint {
+        let mut fingerprint = Fingerprint::default();
+        let mut on_path = vec![false; self.atom_nodes.len()];
+        let mut labels = Vec::with_capacity(2 * MAX_PATH_BONDS + 1);
+        for start in 0..self.atom_nodes.len() {
+            let Some(label) = self.fingerprint_atom_label(start) else {
+                continue;
+            };
+            labels.push(label);
+            on_path[start] = true;
+            self.extend_fingerprint_paths(start, &mut on_path, &mut labels, &mut fingerprint);
+            on_path[start] = false;
+            labels.clear();
+        }
+        fingerprint
+    }
+
+    fn extend_fingerprint_paths(
+        &self,
+        atom: usize,
+        on_path: &mut [bool],
+        labels: &mut Vec<u8>,
+        fingerprint: &mut Fingerprint,
+    ) {
+        fingerprint.insert_path(labels);
+        if labels.len() > 2 * MAX_PATH_BONDS {
+            return;
+        }
+        for (neighbor, edge) in self.neighbors_with_bonds(atom) {
+            if on_path[neighbor] {
+                continue;
+            }
+            let Some(label) = self.fingerprint_atom_label(neighbor) else {
+                continue;
+            };
+            let aromatic = edge.is_aromatic()
+                || (self.atom_nodes[atom].aromatic()
+                    && self.atom_nodes[neighbor].aromatic()
+                    && matches!(edge.bond(), Bond::Single | Bond::Up | Bond::Down));
+            labels.push(match edge.bond() {
+                _ if aromatic => 4,
+                Bond::Single | Bond::Up | Bond::Down => 1,
+                Bond::Double => 2,
+                Bond::Triple => 3,
+                Bond::Quadruple => 5,
+            });
+            labels.push(label);
+            on_path[neighbor] = true;
+            self.extend_fingerprint_paths(neighbor, on_path, labels, fingerprint);
+            on_path[neighbor] = false;
+            labels.truncate(labels.len() - 2);
+        }
+    }
+
+    /// Returns the path label of an atom, or `None` for wildcard atoms.
+    fn fingerprint_atom_label(&self, id: usize) -> Option<u8> {
+        let atom = &self.atom_nodes[id];
+        match atom.symbol() {
+            AtomSymbol::WildCard => None,
+            AtomSymbol::Element(element) => Some(2 * u8::from(element) + u8::from(atom.aromatic())),
+        }
+    }
+}
+
+impl WildcardSmiles {
+    /// Returns the hashed path fingerprint of the graph, whose paths stop at
+    /// wildcard atoms.
+    #[inline]
+    #[must_use]
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.inner().fingerprint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(smiles: &str) -> Fingerprint {
+        smiles.parse::<Smiles>().unwrap().fingerprint()
+    }
+
+    #[test]
+    fn subgraphs_have_subset_fingerprints() {
+        for (query, target) in [
+            ("CC", "CCO"),
+            ("c1ccccc1", "Oc1ccccc1"),
+            ("C=O", "CC(=O)O"),
+            ("N", "[NH4+]"),
+            ("c1ccccc1", "c1ccccc1-c2ccccc2"),
+            ("CCCCCCCCC", "CCCCCCCCCC"),
+        ] {
+            assert!(fingerprint(target).is_superset_of(&fingerprint(query)), "{query} in {target}");
+        }
+        assert!(!fingerprint("CCO").is_superset_of(&fingerprint("CCN")));
+        assert!(!fingerprint("CC").is_superset_of(&fingerprint("C=C")));
+    }
+
+    #[test]
+    fn fingerprints_ignore_atom_order_and_charges() {
+        assert_eq!(fingerprint("OCC"), fingerprint("CCO"));
+        assert_eq!(fingerprint("C[NH3+]"), fingerprint("CN"));
+        assert_eq!(fingerprint("F/C=C/F"), fingerprint("FC=CF"));
+        assert_ne!(fingerprint("CCO"), fingerprint("CC=O"));
+        assert_eq!(Fingerprint::default().count_ones(), 0);
+        assert_eq!(fingerprint("C").count_ones(), 1);
+    }
+
+    #[test]
+    fn kekule_and_aromatic_spellings_fingerprint_alike() {
+        assert_eq!(fingerprint("C1=CC=CC=C1"), fingerprint("c1ccccc1"));
+        assert_eq!(fingerprint("OC1=CC=CC=C1"), fingerprint("Oc1ccccc1"));
+        assert!(fingerprint("OC1=CC=CC=C1").is_superset_of(&fingerprint("c1ccccc1")));
+        assert!(fingerprint("Oc1ccccc1").is_superset_of(&fingerprint("C1=CC=CC=C1")));
+        assert_eq!(fingerprint("CO").ones().count(), 3);
+    }
+
+    #[test]
+    fn tanimoto_is_bounded_and_symmetric() {
+        let phenol = fingerprint("Oc1ccccc1");
+        let toluene = fingerprint("Cc1ccccc1");
+        assert!((phenol.tanimoto(&phenol) - 1.0).abs() < f64::EPSILON);
+        assert!((phenol.tanimoto(&toluene) - toluene.tanimoto(&phenol)).abs() < f64::EPSILON);
+        assert!(phenol.tanimoto(&toluene) > 0.0 && phenol.tanimoto(&toluene) < 1.0);
+        assert!(Fingerprint::default().tanimoto(&Fingerprint::default()).abs() < f64::EPSILON);
+        assert_eq!(Fingerprint::from_words(*phenol.words()), phenol);
+    }
+
+    #[test]
+    fn paths_stop_at_wildcards() {
+        let wildcard: WildcardSmiles = "C*C".parse().unwrap();
+        assert_eq!(wildcard.fingerprint(), fingerprint("C"));
+    }
+}
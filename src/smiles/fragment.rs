@@ -5,14 +5,17 @@
 //! connectivity, so an atom that becomes terminal gains the hydrogens its
 //! reduced valence implies. Atom ids are remapped to a compact local numbering,
 //! with the parent correspondence kept private behind [`Fragment::local_id`]
-//! and [`Fragment::parent_id`].
+//! and [`Fragment::parent_id`]. [`Smiles::fragment_key`] reduces a graph to
+//! its heavy-atom skeleton.
 
 use alloc::{string::String, vec::Vec};
 
+use elements_rs::Element;
 use geometric_traits::traits::SparseValuedMatrixRef;
 
 use super::{BondMatrixBuilder, ConcreteAtoms, Smiles, SmilesAtomPolicy};
 use crate::{
+    atom::{Atom, can_write_unbracketed_aromatic, is_organic_subset_symbol},
     bond::bond_edge::BondEdge,
     errors::{RootError, SubgraphError},
 };
@@ -163,6 +166,60 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
         Ok(self.finish_fragment(parent_of_local, local_of_parent, builder))
     }
 
+    /// Returns the canonical SMILES of the heavy-atom skeleton, ignoring
+    /// hydrogens, charges, isotopes, stereochemistry and atom classes.
+    ///
+    /// Protonation states and isotopologues of one skeleton share a key, which
+    /// makes it suitable for grouping MS features. Bond orders and aromatic
+    /// flags are kept, so Kekulé and aromatic spellings of a ring differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let acid: Smiles = "CC(=O)O".parse()?;
+    /// let acetate: Smiles = "CC(=O)[O-]".parse()?;
+    /// let labelled: Smiles = "[13CH3]C(=O)O[2H]".parse()?;
+    /// assert_eq!(acid.fragment_key(), acetate.fragment_key());
+    /// assert_eq!(acid.fragment_key(), labelled.fragment_key());
+    /// assert_ne!(acid.fragment_key(), "CCO".parse::<Smiles>()?.fragment_key());
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn fragment_key(&self) -> String {
+        let heavy_atoms = (0..self.nodes().len())
+            .filter(|&atom| self.atom_nodes[atom].element() != Some(Element::H));
+        let skeleton = self
+            .fragment_from_atoms(heavy_atoms)
+            .unwrap_or_else(|_| unreachable!("every atom id is in range"))
+            .into_smiles();
+
+        let atom_nodes: Vec<_> =
+            skeleton.atom_nodes.iter().map(|&atom| skeleton_atom(atom)).collect();
+        let mut builder = BondMatrixBuilder::default();
+        for ((row, column), entry) in skeleton.bond_matrix.sparse_entries() {
+            if row >= column {
+                continue;
+            }
+            let descriptor = entry.descriptor();
+            let flattened = descriptor.with_bond(descriptor.bond().without_direction());
+            builder
+                .push_edge_with_descriptor(row, column, flattened, None)
+                .unwrap_or_else(|_| unreachable!("the skeleton preserves a simple graph"));
+        }
+        let parsed_stereo_neighbors = vec![Vec::new(); atom_nodes.len()];
+        let atom_count = atom_nodes.len();
+        Self::from_bond_matrix_parts_with_parsed_stereo_and_source(
+            atom_nodes,
+            builder.finish(atom_count),
+            parsed_stereo_neighbors,
+            None,
+        )
+        .canonicalize()
+        .render()
+    }
+
     fn finish_fragment(
         &self,
         parent_of_local: Vec<usize>,
@@ -183,6 +240,20 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
     }
 }
 
+/// Returns the atom with only its symbol and aromatic flag, written without
+/// brackets whenever the organic subset allows it.
+pub(super) fn skeleton_atom(atom: Atom) -> Atom {
+    let symbol = atom.symbol();
+    let aromatic = atom.aromatic();
+    let unbracketed = is_organic_subset_symbol(symbol)
+        && (!aromatic || atom.element().is_none_or(can_write_unbracketed_aromatic));
+    if unbracketed {
+        Atom::new_organic_subset(symbol, aromatic)
+    } else {
+        Atom::builder().with_symbol(symbol).with_aromatic(aromatic).build()
+    }
+}
+
 /// Assigns (or returns) the local id of a parent atom, recording it on first
 /// sight.
 fn register_local(
@@ -1,11 +1,243 @@
+use alloc::vec::Vec;
 use core::str::FromStr;
 
-use super::{Smiles, SmilesAtomPolicy, WildcardSmiles};
+use geometric_traits::traits::SparseValuedMatrixRef;
+use hashbrown::HashSet;
+
+use super::{BondMatrix, ElementProfile, Smiles, SmilesAtomPolicy, WildcardSmiles};
 use crate::{
+    bond::Bond,
     errors::SmilesErrorWithSpan,
-    parser::smiles_parser::{parse_smiles, parse_smiles_with_policy, parse_wildcard_smiles},
+    parser::{
+        ring_table::RingTable,
+        smiles_parser::{
+            BranchStack, parse_smiles, parse_smiles_from_tokens, parse_smiles_with_options,
+            parse_smiles_with_policy, parse_smiles_with_scratch, parse_wildcard_smiles,
+        },
+    },
+    token::TokenWithSpan,
 };
 
+/// Settings that relax or restrict how [`Smiles::parse_with_options`] reads
+/// its input.
+///
+/// The default is the behavior of [`FromStr`].
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::prelude::{ParserOptions, Smiles, SmilesError};
+///
+/// let error = "  ".parse::<Smiles>().unwrap_err();
+/// assert_eq!(error.smiles_error(), SmilesError::EmptyInput);
+///
+/// let options = ParserOptions { allow_empty: true, ..ParserOptions::default() };
+/// let smiles = Smiles::parse_with_options("  ", &options)?;
+/// assert!(smiles.nodes().is_empty());
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ParserOptions {
+    /// Parse empty and whitespace-only input as a graph without atoms instead
+    /// of failing with
+    /// [`SmilesError::EmptyInput`](crate::SmilesError::EmptyInput).
+    pub allow_empty: bool,
+    /// The elements atoms may have; atoms of other elements fail with
+    /// [`SmilesError::ElementOutsideProfile`](crate::SmilesError::ElementOutsideProfile).
+    pub profile: ElementProfile,
+    /// Treat the text after the SMILES string as an annotation, such as a
+    /// trailing name or comment, instead of failing. The string ends at the
+    /// first whitespace or [`ParserOptions::comment_delimiter`], and the
+    /// annotation, trimmed and without a leading delimiter, becomes the
+    /// [`Smiles::name`] of the graph. Error spans still refer to the whole
+    /// input.
+    pub annotations: bool,
+    /// A character that also ends the SMILES string outside bracket atoms
+    /// when [`ParserOptions::annotations`] is set, such as `;` or `|`, and is
+    /// stripped from the start of the annotation. A character with a meaning
+    /// in SMILES, such as the `#` of triple bonds, only starts an annotation
+    /// at the beginning of the input or after whitespace, so `C#N # nitrile`
+    /// is still hydrogen cyanide.
+    pub comment_delimiter: Option<char>,
+    /// Mark wildcard atoms whose ring bonds join them to aromatic atoms as
+    /// aromatic, together with those ring bonds, as in `c1cc*cc1`. Such
+    /// inputs come from combinatorial libraries where `*` stands for an
+    /// unspecified aromatic ring member; strictly, `*` has no aromatic form
+    /// and the ring would contain non-aromatic single bonds. Only affects
+    /// [`WildcardSmiles`], since [`Smiles`] rejects wildcards.
+    pub aromatic_wildcards: bool,
+    /// Read `se` and `as` written outside brackets, as some toolkits emit
+    /// them, as the aromatic bracket atoms `[se]` and `[as]` instead of
+    /// failing with
+    /// [`SmilesError::UnbracketedAromaticElement`](crate::SmilesError::UnbracketedAromaticElement).
+    /// The specification only allows the two-letter aromatic symbols inside
+    /// brackets, and rendering always writes them bracketed.
+    pub unbracketed_se_as: bool,
+    /// Record which single bonds the input writes explicitly as `-`, see
+    /// [`Smiles::is_explicit_single_bond`]. Rendering writes the recorded
+    /// bonds with their `-`, so token-level rewriting tools can round-trip
+    /// the input without the `-` tokens disappearing. The record does not
+    /// take part in graph equality and is not kept by derived graphs such as
+    /// [`Smiles::canonicalize`].
+    pub explicit_single_bonds: bool,
+    /// Reject quadruple bonds unless both ends are wildcards or transition
+    /// metals of groups 5 to 9, which are the elements known to form them,
+    /// with [`SmilesError::ImplausibleQuadrupleBond`](crate::SmilesError::ImplausibleQuadrupleBond).
+    /// Without it `C$C` parses and its quadruple bond counts for four in
+    /// valence and hydrogen computations.
+    pub strict_quadruple_bonds: bool,
+    /// Accept the chirality, hydrogen count, charge and class of bracket
+    /// atoms in any order, as some producers write `[O-H]` for `[OH-]`.
+    /// The specification order is chirality, hydrogens, charge, class; out
+    /// of order fields otherwise fail with
+    /// [`SmilesError::UnclosedBracket`](crate::SmilesError::UnclosedBracket).
+    /// [`lint`](crate::lint::lint) accepts such atoms and reports them.
+    pub lenient_bracket_order: bool,
+    /// Accept bracket hydrogen counts of 10 to
+    /// [`HydrogenCount::MAX_MULTI_DIGIT`](crate::atom::bracketed::hydrogen_count::HydrogenCount::MAX_MULTI_DIGIT),
+    /// as in `[CH12]`. The specification allows a single digit after `H`;
+    /// larger counts otherwise fail with
+    /// [`SmilesError::HydrogenCountOverflow`](crate::SmilesError::HydrogenCountOverflow).
+    /// Rendering writes such counts as they are, so the output only parses
+    /// again with this option.
+    pub multi_digit_hydrogen_counts: bool,
+}
+
+impl ParserOptions {
+    /// Splits `input` into the SMILES string and the annotation, which is
+    /// empty when annotations are disabled or absent.
+    pub(crate) fn split_annotation<'a>(&self, input: &'a str) -> (&'a str, &'a str) {
+        if !self.annotations {
+            return (input, "");
+        }
+        // A delimiter that is also SMILES syntax cannot end the string in the
+        // middle, only whitespace can; after whitespace it is stripped below.
+        let inline_delimiter =
+            self.comment_delimiter.filter(|&delimiter| !is_smiles_syntax(delimiter));
+        let mut in_bracket = false;
+        let end = if self.comment_delimiter.is_some_and(|delimiter| input.starts_with(delimiter)) {
+            0
+        } else {
+            input
+                .char_indices()
+                .find(|&(_, character)| {
+                    match character {
+                        '[' => in_bracket = true,
+                        ']' => in_bracket = false,
+                        _ => {}
+                    }
+                    character.is_whitespace()
+                        || (!in_bracket && Some(character) == inline_delimiter)
+                })
+                .map_or(input.len(), |(end, _)| end)
+        };
+        let annotation = input[end..].trim_start();
+        let annotation = self
+            .comment_delimiter
+            .and_then(|delimiter| annotation.strip_prefix(delimiter))
+            .unwrap_or(annotation);
+        (&input[..end], annotation.trim())
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Applies [`ParserOptions::aromatic_wildcards`]: a wildcard in a ring
+    /// becomes aromatic when at least two of its ring neighbors are aromatic
+    /// or wildcards, one of them aromatic, and its single ring bonds to
+    /// aromatic atoms become aromatic bonds.
+    pub(crate) fn with_ring_wildcards_aromatic(self) -> Self {
+        if !self.atom_nodes.iter().any(|atom| atom.symbol().is_wildcard()) {
+            return self;
+        }
+        let rings = &self.ring_membership();
+        let graph = &self;
+        let ring_neighbors = |id: usize| {
+            graph
+                .edges_for_node(id)
+                .filter(move |edge| rings.contains_edge(id, edge.target()))
+                .map(move |edge| graph.atom_nodes[edge.target()])
+        };
+        let aromatized = (0..self.atom_nodes.len())
+            .filter(|&id| {
+                let atom = self.atom_nodes[id];
+                atom.symbol().is_wildcard()
+                    && !atom.aromatic()
+                    && ring_neighbors(id).any(|neighbor| neighbor.aromatic())
+                    && ring_neighbors(id)
+                        .filter(|neighbor| neighbor.aromatic() || neighbor.symbol().is_wildcard())
+                        .count()
+                        >= 2
+            })
+            .collect::<Vec<_>>();
+        if aromatized.is_empty() {
+            return self;
+        }
+
+        let mut atom_nodes = self.atom_nodes.clone();
+        for &id in &aromatized {
+            atom_nodes[id] = atom_nodes[id].with_aromatic(true);
+        }
+        let bond_matrix = BondMatrix::from_sorted_upper_triangular_entries(
+            atom_nodes.len(),
+            self.bond_matrix.sparse_entries().filter_map(|((row, column), entry)| {
+                let joins_aromatized = (aromatized.binary_search(&row).is_ok()
+                    || aromatized.binary_search(&column).is_ok())
+                    && atom_nodes[row].aromatic()
+                    && atom_nodes[column].aromatic()
+                    && entry.bond() == Bond::Single
+                    && !entry.aromatic()
+                    && rings.contains_edge(row, column);
+                (row < column).then_some((
+                    row,
+                    column,
+                    if joins_aromatized { entry.with_aromatic(true) } else { *entry },
+                ))
+            }),
+        )
+        .unwrap_or_else(|_| unreachable!("existing bond matrix entries are already valid"));
+        Self::from_bond_matrix_parts_with_parsed_stereo(
+            atom_nodes,
+            bond_matrix,
+            self.parsed_stereo_neighbors,
+        )
+    }
+}
+
+/// Reusable working memory for [`Smiles::parse_with_scratch`].
+///
+/// The atoms and bonds of a parse become the returned graph, but the branch
+/// stack, the table of open ring closures and the hash set that rejects
+/// duplicate bonds are working memory only. A scratch keeps their allocations
+/// between parses, so a worker parsing many inputs, such as one task of a bulk
+/// ingestion, allocates them once instead of once per input.
+///
+/// Small molecules need none of it: the parser keeps shallow branch stacks
+/// and the first few open ring numbers in fixed-size arrays and finds
+/// duplicate bonds among the first few dozen by a linear scan, so the scratch
+/// only grows for deeply nested or large inputs. The atoms, bonds and stereo
+/// neighbor lists of the returned graph are still allocated on every parse,
+/// with or without a scratch.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::prelude::{ParserOptions, ParserScratch, Smiles};
+///
+/// let mut scratch = ParserScratch::default();
+/// let options = ParserOptions::default();
+/// for input in ["CCO", "c1ccccc1", "C1CC(C)CC1"] {
+///     let smiles = Smiles::parse_with_scratch(input, &options, &mut scratch)?;
+///     assert_eq!(smiles, input.parse::<Smiles>()?);
+/// }
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParserScratch {
+    pub(crate) branch_stack: BranchStack,
+    pub(crate) seen_edges: HashSet<(usize, usize)>,
+    pub(crate) ring_table: RingTable,
+}
+
 impl Smiles {
     /// Parses a strict [`Smiles`] graph from text.
     ///
@@ -20,6 +252,97 @@ impl Smiles {
     pub fn from_str(s: &str) -> Result<Self, SmilesErrorWithSpan> {
         parse_smiles(s)
     }
+
+    /// Parses a strict [`Smiles`] graph from text with the given
+    /// [`ParserOptions`].
+    ///
+    /// # Errors
+    /// Returns a spanned parse error when tokenization or graph construction
+    /// fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::{ParserOptions, Smiles};
+    ///
+    /// let options = ParserOptions { annotations: true, ..ParserOptions::default() };
+    /// let smiles = Smiles::parse_with_options("C#N hydrogen cyanide", &options)?;
+    /// assert_eq!(smiles.render(), "C#N");
+    /// assert_eq!(smiles.name(), Some("hydrogen cyanide"));
+    ///
+    /// let options = ParserOptions { comment_delimiter: Some('#'), ..options };
+    /// let smiles = Smiles::parse_with_options("CCO  # ethanol", &options)?;
+    /// assert_eq!(smiles.name(), Some("ethanol"));
+    ///
+    /// let error = Smiles::parse_with_options("C(C name", &options).unwrap_err();
+    /// assert_eq!((error.start(), error.end()), (1, 2));
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    pub fn parse_with_options(
+        s: &str,
+        options: &ParserOptions,
+    ) -> Result<Self, SmilesErrorWithSpan> {
+        parse_smiles_with_options(s, options)
+    }
+
+    /// Parses a strict [`Smiles`] graph like [`Smiles::parse_with_options`],
+    /// reusing the working memory of `scratch`.
+    ///
+    /// # Errors
+    /// Returns a spanned parse error when tokenization or graph construction
+    /// fails.
+    pub fn parse_with_scratch(
+        s: &str,
+        options: &ParserOptions,
+        scratch: &mut ParserScratch,
+    ) -> Result<Self, SmilesErrorWithSpan> {
+        parse_smiles_with_scratch(s, options, scratch)
+    }
+
+    /// Builds a strict [`Smiles`] graph from a token stream, such as one
+    /// edited at the token level, without writing it back to text first.
+    ///
+    /// The tokens are read with the default [`ParserOptions`], and their
+    /// spans are only used to locate errors.
+    ///
+    /// # Errors
+    /// Returns a spanned parse error when the tokens do not form a valid
+    /// graph, [`SmilesError::EmptyInput`](crate::SmilesError::EmptyInput)
+    /// for an empty slice, and
+    /// [`SmilesError::UnknownBracket`](crate::SmilesError::UnknownBracket)
+    /// for a [`Token::UnknownBracket`](crate::token::Token::UnknownBracket).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{
+    ///     prelude::Smiles,
+    ///     token::{Token, TokenWithSpan, tokenize_permissive},
+    /// };
+    ///
+    /// let tokens = tokenize_permissive("[13CH3]CO")
+    ///     .map(|token| {
+    ///         let token = token?;
+    ///         Ok(match token.token() {
+    ///             Token::Atom(atom) => {
+    ///                 TokenWithSpan::new(
+    ///                     Token::Atom(atom.without_isotope()),
+    ///                     token.start(),
+    ///                     token.end(),
+    ///                 )
+    ///             }
+    ///             _ => token,
+    ///         })
+    ///     })
+    ///     .collect::<Result<Vec<_>, smiles_parser::SmilesErrorWithSpan>>()?;
+    /// let smiles = Smiles::from_tokens(&tokens)?;
+    /// assert_eq!(smiles.nodes().len(), 3);
+    /// assert_eq!(smiles.nodes()[0].isotope_mass_number(), None);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    pub fn from_tokens(tokens: &[TokenWithSpan]) -> Result<Self, SmilesErrorWithSpan> {
+        parse_smiles_from_tokens(tokens)
+    }
 }
 
 impl<AtomPolicy: SmilesAtomPolicy> FromStr for Smiles<AtomPolicy> {
@@ -40,6 +363,44 @@ impl WildcardSmiles {
     pub fn from_str(s: &str) -> Result<Self, SmilesErrorWithSpan> {
         parse_wildcard_smiles(s).map(Self::from_inner)
     }
+
+    /// Parses a wildcard-capable [`WildcardSmiles`] graph from text with the
+    /// given [`ParserOptions`].
+    ///
+    /// # Errors
+    /// Returns a spanned parse error when tokenization or graph construction
+    /// fails.
+    pub fn parse_with_options(
+        s: &str,
+        options: &ParserOptions,
+    ) -> Result<Self, SmilesErrorWithSpan> {
+        parse_smiles_with_options(s, options).map(Self::from_inner)
+    }
+
+    /// Parses a wildcard-capable [`WildcardSmiles`] graph like
+    /// [`WildcardSmiles::parse_with_options`], reusing the working memory of
+    /// `scratch`.
+    ///
+    /// # Errors
+    /// Returns a spanned parse error when tokenization or graph construction
+    /// fails.
+    pub fn parse_with_scratch(
+        s: &str,
+        options: &ParserOptions,
+        scratch: &mut ParserScratch,
+    ) -> Result<Self, SmilesErrorWithSpan> {
+        parse_smiles_with_scratch(s, options, scratch).map(Self::from_inner)
+    }
+
+    /// Builds a wildcard-capable [`WildcardSmiles`] graph from a token
+    /// stream, like [`Smiles::from_tokens`].
+    ///
+    /// # Errors
+    /// Returns a spanned parse error when the tokens do not form a valid
+    /// graph.
+    pub fn from_tokens(tokens: &[TokenWithSpan]) -> Result<Self, SmilesErrorWithSpan> {
+        parse_smiles_from_tokens(tokens).map(Self::from_inner)
+    }
 }
 
 impl FromStr for WildcardSmiles {
@@ -50,6 +411,12 @@ impl FromStr for WildcardSmiles {
     }
 }
 
+/// Returns whether `character` can be part of a SMILES string outside
+/// bracket atoms.
+fn is_smiles_syntax(character: char) -> bool {
+    character.is_ascii_alphanumeric() || "[]()=#$:/\\%.+-@*".contains(character)
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::string::ToString;
@@ -63,7 +430,7 @@ mod tests {
             bond_edge::{bond_edge, bond_edge_ring_num_val},
             ring_num::RingNum,
         },
-        smiles::{Smiles, WildcardSmiles},
+        smiles::{ParserOptions, Smiles, WildcardSmiles},
     };
 
     #[test]
@@ -115,13 +482,195 @@ mod tests {
 
     #[test]
     fn empty_input_is_not_a_valid_smiles() {
-        let err = Smiles::from_str("").expect_err("empty input should not parse");
-        assert_eq!(err.smiles_error(), crate::errors::SmilesError::MissingElement);
-        assert_eq!((err.start(), err.end()), (0, 0));
+        for source in ["", " ", "\t\n"] {
+            let err = Smiles::from_str(source).expect_err("empty input should not parse");
+            assert_eq!(err.smiles_error(), crate::errors::SmilesError::EmptyInput);
+            assert_eq!((err.start(), err.end()), (0, source.len()));
+
+            let err = WildcardSmiles::from_str(source).expect_err("empty input should not parse");
+            assert_eq!(err.smiles_error(), crate::errors::SmilesError::EmptyInput);
+        }
+    }
+
+    #[test]
+    fn empty_input_can_be_allowed_through_parser_options() {
+        let options = ParserOptions { allow_empty: true, ..ParserOptions::default() };
+        for source in ["", "   "] {
+            let smiles = Smiles::parse_with_options(source, &options).unwrap();
+            assert!(smiles.nodes().is_empty());
+            assert_eq!(smiles.number_of_bonds(), 0);
+
+            let wildcard = WildcardSmiles::parse_with_options(source, &options).unwrap();
+            assert!(wildcard.nodes().is_empty());
+        }
+
+        let smiles = Smiles::parse_with_options("CCO", &options).unwrap();
+        assert_eq!(smiles.nodes().len(), 3);
+        assert!(Smiles::parse_with_options(" C", &options).is_err());
+    }
+
+    #[test]
+    fn annotations_are_split_off_when_enabled() {
+        assert!(Smiles::from_str("CCO ethanol").is_err());
+
+        let options = ParserOptions {
+            annotations: true,
+            comment_delimiter: Some(';'),
+            ..ParserOptions::default()
+        };
+        for (source, rendered, name) in [
+            ("CCO\tethanol", "CCO", Some("ethanol")),
+            ("[Na+].[Cl-];salt ", "[Na+].[Cl-]", Some("salt")),
+            ("c1ccccc1 ; benzene", "c1ccccc1", Some("benzene")),
+            ("N", "N", None),
+        ] {
+            let smiles = Smiles::parse_with_options(source, &options).unwrap();
+            assert_eq!(smiles.render(), rendered, "{source}");
+            assert_eq!(smiles.name(), name, "{source}");
+            let wildcard = WildcardSmiles::parse_with_options(source, &options).unwrap();
+            assert_eq!(wildcard.name(), name, "{source}");
+        }
 
-        let err = WildcardSmiles::from_str("").expect_err("empty input should not parse");
-        assert_eq!(err.smiles_error(), crate::errors::SmilesError::MissingElement);
-        assert_eq!((err.start(), err.end()), (0, 0));
+        let err = Smiles::parse_with_options(" comment", &options).unwrap_err();
+        assert_eq!(err.smiles_error(), crate::errors::SmilesError::EmptyInput);
+
+        let options = ParserOptions { comment_delimiter: Some('#'), ..options };
+        for (source, rendered, name) in [
+            ("C#N", "C#N", None),
+            ("C#N # hydrogen cyanide", "C#N", Some("hydrogen cyanide")),
+            ("CC#CC\t#butyne", "CC#CC", Some("butyne")),
+            ("# no structure", "", Some("no structure")),
+        ] {
+            let options = ParserOptions { allow_empty: true, ..options };
+            let smiles = Smiles::parse_with_options(source, &options).unwrap();
+            assert_eq!(smiles.render(), rendered, "{source}");
+            assert_eq!(smiles.name(), name, "{source}");
+        }
+
+        let options = ParserOptions { comment_delimiter: Some(';'), ..options };
+        let err = Smiles::parse_with_options("CC(=O; acid", &options).unwrap_err();
+        assert_eq!(err.smiles_error(), crate::errors::SmilesError::UnclosedBranch);
+        assert_eq!((err.start(), err.end()), (2, 3));
+    }
+
+    #[test]
+    fn scratch_buffers_survive_failed_parses() {
+        let mut scratch = super::ParserScratch::default();
+        let options = ParserOptions::default();
+        // Deep enough to spill the inline branch stack and large enough to
+        // index edges in the hash set.
+        let nested = format!("{}C{}", "C(".repeat(80), ")".repeat(80));
+        for source in ["C1CC(CC)CC1", "C(C", "CC1CC1C1", "*C", "c1ccccc1", &nested, "C1CCC1C1"] {
+            let with_scratch = Smiles::parse_with_scratch(source, &options, &mut scratch);
+            assert_eq!(with_scratch, Smiles::from_str(source), "{source}");
+        }
+        assert!(scratch.seen_edges.capacity() > 0);
+        assert!(scratch.branch_stack.spilled());
+
+        let wildcard = WildcardSmiles::parse_with_scratch("*C", &options, &mut scratch).unwrap();
+        assert_eq!(wildcard, WildcardSmiles::from_str("*C").unwrap());
+    }
+
+    #[test]
+    fn wildcard_attributes_and_aromatic_ring_wildcards() {
+        for source in ["[*+2]", "[*H2]", "[13*:1]", "C[*-]C"] {
+            assert_eq!(WildcardSmiles::from_str(source).unwrap().render(), source);
+        }
+
+        let options = ParserOptions { aromatic_wildcards: true, ..ParserOptions::default() };
+        let strict = WildcardSmiles::from_str("c1cc*cc1").unwrap();
+        assert!(!strict.nodes()[3].aromatic());
+        let lenient = WildcardSmiles::parse_with_options("c1cc*cc1", &options).unwrap();
+        assert!(lenient.nodes()[3].aromatic());
+        assert!(lenient.edges_for_node(3).all(|edge| edge.is_aromatic()));
+        assert_eq!(lenient.render(), strict.render());
+        assert_eq!(WildcardSmiles::parse_with_options(&lenient.render(), &options), Ok(lenient));
+
+        let substituent = WildcardSmiles::parse_with_options("c1ccccc1*", &options).unwrap();
+        assert!(!substituent.nodes()[6].aromatic());
+        let chain = WildcardSmiles::parse_with_options("c1ccc(*C)cc1", &options).unwrap();
+        assert!(!chain.nodes()[4].aromatic());
+    }
+
+    #[test]
+    fn selenium_and_arsenic_aromatics_need_brackets_unless_allowed() {
+        for source in ["c1cc[se]c1", "c1cc[as]c1", "c1cc[te]c1", "[se]1cccc1"] {
+            let smiles = Smiles::from_str(source).unwrap();
+            assert!(smiles.nodes().iter().all(Atom::aromatic));
+            assert_eq!(smiles.render(), source);
+        }
+
+        for (source, element, span) in
+            [("c1ccsec1", Element::Se, (4, 6)), ("c1ccasc1", Element::As, (4, 6))]
+        {
+            let error = Smiles::from_str(source).unwrap_err();
+            assert_eq!(
+                error.smiles_error(),
+                crate::errors::SmilesError::UnbracketedAromaticElement(element)
+            );
+            assert_eq!((error.start(), error.end()), span);
+        }
+
+        let options = ParserOptions { unbracketed_se_as: true, ..ParserOptions::default() };
+        for (source, bracketed) in [("c1ccsec1", "c1cc[se]c1"), ("c1ccasc1", "c1cc[as]c1")] {
+            let smiles = Smiles::parse_with_options(source, &options).unwrap();
+            assert_eq!(smiles, Smiles::from_str(bracketed).unwrap());
+            assert_eq!(smiles.render(), bracketed);
+        }
+        assert_eq!(Smiles::parse_with_options("CSC", &options).unwrap().render(), "CSC");
+    }
+
+    #[test]
+    fn graphs_built_from_tokens_match_parsed_graphs() {
+        use alloc::vec::Vec;
+
+        use crate::{
+            errors::SmilesError,
+            token::{Token, TokenWithSpan, tokenize_permissive},
+        };
+
+        let tokens_of = |input| tokenize_permissive(input).collect::<Result<Vec<_>, _>>().unwrap();
+        for input in ["CCO", "c1ccccc1C(=O)[O-]", "F/C=C/F", "[NH4+].[Cl-]", "C1CC1"] {
+            let smiles = Smiles::from_tokens(&tokens_of(input)).unwrap();
+            assert_eq!(smiles, Smiles::from_str(input).unwrap(), "{input}");
+            assert_eq!(smiles.render(), input);
+        }
+
+        let error = Smiles::from_tokens(&[]).unwrap_err();
+        assert_eq!(error.smiles_error(), SmilesError::EmptyInput);
+        let error = Smiles::from_tokens(&tokens_of("C(C")).unwrap_err();
+        assert_eq!((error.start(), error.end()), (1, 2));
+        let tokens = tokens_of("C[R1]C");
+        assert_eq!(tokens[1].token(), Token::UnknownBracket("[R1]".into(), 1..5));
+        let error = Smiles::from_tokens(&tokens).unwrap_err();
+        assert_eq!(error.smiles_error(), SmilesError::UnknownBracket);
+        assert_eq!((error.start(), error.end()), (1, 5));
+
+        assert!(Smiles::from_tokens(&tokens_of("C*")).is_err());
+        assert_eq!(WildcardSmiles::from_tokens(&tokens_of("C*")).unwrap().nodes().len(), 2);
+
+        let far_spans = tokens_of("CCO")
+            .into_iter()
+            .map(|token| TokenWithSpan::new(token.token(), usize::MAX - 1, usize::MAX))
+            .collect::<Vec<_>>();
+        assert_eq!(Smiles::from_tokens(&far_spans).unwrap(), Smiles::from_str("CCO").unwrap());
+    }
+
+    #[test]
+    fn lenient_bracket_order_accepts_fields_in_any_order() {
+        let options = ParserOptions { lenient_bracket_order: true, ..ParserOptions::default() };
+        for (source, ordered) in [
+            ("[O-H]", "[OH-]"),
+            ("[NH3:1+]", "[NH3+:1]"),
+            ("F[CH+@](Cl)Br", "F[C@H+](Cl)Br"),
+            ("[Fe2+H]", "[FeH+2]"),
+        ] {
+            assert!(Smiles::from_str(source).is_err());
+            let smiles = Smiles::parse_with_options(source, &options).unwrap();
+            assert_eq!(smiles, Smiles::from_str(ordered).unwrap());
+            assert_eq!(smiles.render(), ordered);
+        }
+        assert!(Smiles::parse_with_options("[CH2H3]", &options).is_err());
     }
 
     #[test]
@@ -159,4 +708,17 @@ mod tests {
         assert_eq!(err.smiles_error(), crate::errors::SmilesError::InvalidIsotope);
         assert_eq!((err.start(), err.end()), (0, 6));
     }
+
+    #[test]
+    fn implausible_mass_numbers_are_rejected_per_element() {
+        for (source, span) in [("[5C]", (0, 4)), ("C[1O]", (1, 5)), ("CC[300U]", (2, 8))] {
+            let err = Smiles::from_str(source).expect_err("implausible isotope should be invalid");
+            assert_eq!(err.smiles_error(), crate::errors::SmilesError::InvalidIsotope, "{source}");
+            assert_eq!((err.start(), err.end()), span, "{source}");
+        }
+        for source in ["[12C]", "[13CH4]", "[18OH2]", "[235U]"] {
+            Smiles::from_str(source)
+                .unwrap_or_else(|error| panic!("failed to parse {source}: {error}"));
+        }
+    }
 }
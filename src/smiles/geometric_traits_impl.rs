@@ -187,24 +187,55 @@ impl Hash for BondEntry {
 #[derive(Debug, Default)]
 pub(crate) struct BondMatrixBuilder {
     entries: Vec<PendingBond>,
+    /// Index of the pushed edges, filled only once there are more than
+    /// [`LINEAR_EDGE_SCAN_LIMIT`] of them.
     seen_edges: HashSet<(usize, usize)>,
 }
 
+/// Number of edges up to which duplicates are found by scanning the pending
+/// entries, which is faster than hashing for small molecules and leaves the
+/// hash set unallocated.
+const LINEAR_EDGE_SCAN_LIMIT: usize = 64;
+
 impl BondMatrixBuilder {
     #[inline]
     #[must_use]
     pub(crate) fn with_capacity(number_of_edges: usize) -> Self {
-        Self {
-            entries: Vec::with_capacity(number_of_edges),
-            seen_edges: HashSet::with_capacity(number_of_edges),
-        }
+        Self::with_seen_edges(number_of_edges, HashSet::new())
+    }
+
+    /// Creates a builder that reuses the allocation of `seen_edges`.
+    #[inline]
+    #[must_use]
+    pub(crate) fn with_seen_edges(
+        number_of_edges: usize,
+        mut seen_edges: HashSet<(usize, usize)>,
+    ) -> Self {
+        seen_edges.clear();
+        Self { entries: Vec::with_capacity(number_of_edges), seen_edges }
+    }
+
+    /// Takes the duplicate-edge set out of the builder, so that its
+    /// allocation can be reused by the next parse.
+    #[inline]
+    pub(crate) fn take_seen_edges(&mut self) -> HashSet<(usize, usize)> {
+        core::mem::take(&mut self.seen_edges)
     }
 
     #[inline]
     #[must_use]
     pub(crate) fn contains_edge(&self, node_a: usize, node_b: usize) -> bool {
         let (row, column) = crate::smiles::edge_key(node_a, node_b);
-        self.seen_edges.contains(&(row, column))
+        self.contains_key(row, column)
+    }
+
+    #[inline]
+    fn contains_key(&self, row: usize, column: usize) -> bool {
+        if self.entries.len() <= LINEAR_EDGE_SCAN_LIMIT {
+            self.entries.iter().any(|bond| bond.row_major_key() == (row, column))
+        } else {
+            self.seen_edges.contains(&(row, column))
+        }
     }
 
     #[inline]
@@ -219,7 +250,7 @@ impl BondMatrixBuilder {
         if row == column {
             return Err(SmilesError::SelfLoopEdge(row));
         }
-        if !self.seen_edges.insert((row, column)) {
+        if self.contains_key(row, column) {
             return Err(SmilesError::DuplicateEdge(row, column));
         }
 
@@ -229,6 +260,15 @@ impl BondMatrixBuilder {
             column,
             BondEntry::from_descriptor(descriptor, ring_num, order),
         ));
+        match self.entries.len().cmp(&(LINEAR_EDGE_SCAN_LIMIT + 1)) {
+            core::cmp::Ordering::Less => {}
+            core::cmp::Ordering::Equal => {
+                self.seen_edges.extend(self.entries.iter().map(|bond| bond.row_major_key()));
+            }
+            core::cmp::Ordering::Greater => {
+                self.seen_edges.insert((row, column));
+            }
+        }
         Ok(())
     }
 
@@ -358,6 +398,7 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
             parsed_stereo_neighbors,
             implicit_hydrogen_cache: Vec::new(),
             kekulization_source,
+            metadata: None,
             atom_policy: PhantomData,
         };
         smiles.implicit_hydrogen_cache = smiles.recompute_implicit_hydrogen_counts();
@@ -380,6 +421,7 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
             parsed_stereo_neighbors,
             implicit_hydrogen_cache,
             kekulization_source,
+            metadata: None,
             atom_policy: PhantomData,
         };
         assert_eq!(
@@ -807,4 +849,20 @@ mod tests {
         assert!(collapsed.matched_edges().len() > raw.matched_edges().len());
         assert!(collapsed.johnson_similarity() > raw.johnson_similarity());
     }
+
+    #[test]
+    fn duplicate_edges_are_found_on_both_sides_of_the_scan_limit() {
+        let mut builder = BondMatrixBuilder::with_capacity(0);
+        for node in 0..LINEAR_EDGE_SCAN_LIMIT + 8 {
+            assert!(!builder.contains_edge(node + 1, node));
+            builder.push_edge_with_descriptor(node, node + 1, Bond::Single.into(), None).unwrap();
+            assert_eq!(
+                builder.push_edge_with_descriptor(node + 1, node, Bond::Double.into(), None),
+                Err(SmilesError::DuplicateEdge(node, node + 1))
+            );
+        }
+        assert!(builder.contains_edge(1, 0));
+        assert!(builder.contains_edge(LINEAR_EDGE_SCAN_LIMIT, LINEAR_EDGE_SCAN_LIMIT + 1));
+        assert_eq!(builder.take_seen_edges().len(), LINEAR_EDGE_SCAN_LIMIT + 8);
+    }
 }
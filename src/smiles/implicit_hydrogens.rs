@@ -29,10 +29,7 @@ use elements_rs::{AllowedValences, ChargedValences, Element};
 use geometric_traits::traits::SparseValuedMatrix2DRef;
 
 use super::{Smiles, SmilesAtomPolicy};
-use crate::{
-    atom::{Atom, AtomSyntax, atom_symbol::AtomSymbol},
-    bond::Bond,
-};
+use crate::atom::{Atom, AtomSyntax, atom_symbol::AtomSymbol};
 
 impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
     #[inline]
@@ -176,7 +173,7 @@ pub(crate) fn explicit_valence(smiles: &Smiles<impl SmilesAtomPolicy>, node_id:
     smiles
         .bond_matrix()
         .sparse_row_values_ref(node_id)
-        .map(|entry| u16::from(bond_order(entry.bond())))
+        .map(|entry| u16::from(entry.bond().order()))
         .sum()
 }
 
@@ -192,23 +189,6 @@ fn saturated_explicit_valence(smiles: &Smiles<impl SmilesAtomPolicy>, node_id: u
     u8::try_from(explicit_valence(smiles, node_id)).unwrap_or(u8::MAX)
 }
 
-/// Maps parsed bond syntax to the local bond-order contribution used for raw
-/// implicit-hydrogen counting.
-///
-/// Aromatic bonds contribute `1` here because this module works on the parsed
-/// aromatic graph directly instead of first assigning a Kekule form.
-/// That choice matches raw `RDKit` on unsanitized molecules before any aromatic
-/// normalization pass is applied.
-#[inline]
-pub(crate) fn bond_order(bond: Bond) -> u8 {
-    match bond {
-        Bond::Single | Bond::Up | Bond::Down => 1,
-        Bond::Double => 2,
-        Bond::Triple => 3,
-        Bond::Quadruple => 4,
-    }
-}
-
 /// Applies normal-valence completion for an unbracketed aliphatic atom.
 #[inline]
 fn aliphatic_implicit_hydrogens(element: Element, explicit_valence: u8) -> u8 {
@@ -265,10 +245,10 @@ mod tests {
     use elements_rs::{AllowedValences, ChargedValences, Element};
 
     use super::{
-        Smiles, aromatic_implicit_hydrogens, bond_order, explicit_valence,
-        implicit_hydrogens_for_node, target_valence,
+        Smiles, aromatic_implicit_hydrogens, explicit_valence, implicit_hydrogens_for_node,
+        target_valence,
     };
-    use crate::{bond::Bond, smiles::WildcardSmiles};
+    use crate::smiles::WildcardSmiles;
 
     #[test]
     fn bracket_atoms_never_gain_implicit_hydrogens() {
@@ -340,12 +320,6 @@ mod tests {
         assert_eq!(explicit_valence(&smiles, 0), 300);
     }
 
-    #[test]
-    fn bond_order_maps_double_and_triple_bonds() {
-        assert_eq!(bond_order(Bond::Double), 2);
-        assert_eq!(bond_order(Bond::Triple), 3);
-    }
-
     #[test]
     fn target_valence_uses_neutral_iodine_progression() {
         assert_eq!(target_valence(Element::I, 2), Some(3));
@@ -0,0 +1,396 @@
+//! Isomorphism checks that do not rely on canonical forms alone.
+
+use alloc::{collections::VecDeque, vec::Vec};
+
+use geometric_traits::traits::SparseMatrix2D;
+
+use super::{Smiles, SmilesAtomPolicy, StereoNeighbor, stereo::reorder_tetrahedral_chirality};
+use crate::atom::bracketed::chirality::Chirality;
+
+/// The largest number of candidate atom assignments the mapping search tries
+/// before giving up.
+const MAX_MAPPING_STEPS: usize = 1_000_000;
+
+/// The outcome of a bounded search for an atom mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AtomMappingSearch {
+    /// The atom of the other graph every atom maps to.
+    Found(Vec<usize>),
+    /// The search ran to completion without finding a mapping.
+    NotIsomorphic,
+    /// The search ran out of candidate assignments before settling.
+    Undecided,
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns whether `self` and `other` are the same graph up to atom
+    /// order, or [`None`] if that could not be settled.
+    ///
+    /// The canonical forms of the two graphs are compared first, which
+    /// settles almost every pair. Highly symmetric graphs such as cages,
+    /// fullerenes and porphyrins leave many ties for the canonical labeling
+    /// to break, so when the canonical forms differ the answer comes from an
+    /// explicit search for an atom mapping that keeps every atom, bond and
+    /// stereo configuration. Chirality is checked as soon as a chiral atom
+    /// and its neighbors are mapped, pruning mirror-image branches early. A
+    /// tetrahedral tag matches its image when the parity of the permutation
+    /// between the two neighbor lists agrees with whether the tags are
+    /// inverted; other chirality classes must list their neighbors in the
+    /// same order.
+    ///
+    /// The budget only applies to that search: after a million candidate
+    /// assignments it gives up and this returns [`None`] rather than
+    /// reporting the graphs as different.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let cubane: Smiles = "C12C3C4C1C5C2C3C45".parse()?;
+    /// let rewritten: Smiles = "C1(C2C3C14)C5C2C3C45".parse()?;
+    /// assert_eq!(cubane.is_isomorphic_to(&rewritten), Some(true));
+    /// assert_eq!(cubane.is_isomorphic_to(&"C1CCCCCCC1".parse::<Smiles>()?), Some(false));
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn is_isomorphic_to<OtherPolicy: SmilesAtomPolicy>(
+        &self,
+        other: &Smiles<OtherPolicy>,
+    ) -> Option<bool> {
+        let left = self.canonicalize();
+        let right = other.canonicalize();
+        if left == right {
+            return Some(true);
+        }
+        match left.atom_mapping_to(&right, MAX_MAPPING_STEPS) {
+            AtomMappingSearch::Found(_) => Some(true),
+            AtomMappingSearch::NotIsomorphic => Some(false),
+            AtomMappingSearch::Undecided => None,
+        }
+    }
+
+    /// Searches for the atom of `other` every atom of `self` maps to under an
+    /// isomorphism that keeps atoms, bonds and the configuration of chiral
+    /// atoms, trying at most `max_steps` candidate assignments.
+    fn atom_mapping_to<OtherPolicy: SmilesAtomPolicy>(
+        &self,
+        other: &Smiles<OtherPolicy>,
+        max_steps: usize,
+    ) -> AtomMappingSearch {
+        let node_count = self.atom_nodes.len();
+        if node_count != other.atom_nodes.len() || self.number_of_bonds() != other.number_of_bonds()
+        {
+            return AtomMappingSearch::NotIsomorphic;
+        }
+        if node_count == 0 {
+            return AtomMappingSearch::Found(Vec::new());
+        }
+
+        // Atoms are mapped in breadth-first order, so every atom after the
+        // first of its component only has to be tried against the unmapped
+        // neighbors of an already mapped neighbor's image.
+        let visit_order = self.breadth_first_order();
+        let mut mapping = vec![usize::MAX; node_count];
+        let mut used = vec![false; node_count];
+        let mut candidates = vec![self.mapping_candidates(other, visit_order[0], &mapping, &used)];
+        let mut steps = 0;
+        loop {
+            let depth = candidates.len() - 1;
+            let atom_id = visit_order[depth];
+            if mapping[atom_id] != usize::MAX {
+                used[mapping[atom_id]] = false;
+                mapping[atom_id] = usize::MAX;
+            }
+            let Some(image) = candidates[depth].pop() else {
+                candidates.pop();
+                if candidates.is_empty() {
+                    return AtomMappingSearch::NotIsomorphic;
+                }
+                continue;
+            };
+            steps += 1;
+            if steps > max_steps {
+                return AtomMappingSearch::Undecided;
+            }
+            mapping[atom_id] = image;
+            used[image] = true;
+            if !self.stereo_configurations_match_around(other, atom_id, &mapping) {
+                continue;
+            }
+            if depth + 1 == node_count {
+                return AtomMappingSearch::Found(mapping);
+            }
+            candidates.push(self.mapping_candidates(
+                other,
+                visit_order[depth + 1],
+                &mapping,
+                &used,
+            ));
+        }
+    }
+
+    /// Returns the atoms in breadth-first order, one component after the
+    /// other.
+    fn breadth_first_order(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.atom_nodes.len());
+        let mut visited = vec![false; self.atom_nodes.len()];
+        let mut queue = VecDeque::new();
+        for root in 0..self.atom_nodes.len() {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            queue.push_back(root);
+            while let Some(atom_id) = queue.pop_front() {
+                order.push(atom_id);
+                for neighbor in self.bond_matrix.sparse_row(atom_id) {
+                    if !visited[neighbor] {
+                        visited[neighbor] = true;
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    /// Returns the unused atoms of `other` that `atom_id` can map to given
+    /// the atoms mapped so far.
+    fn mapping_candidates<OtherPolicy: SmilesAtomPolicy>(
+        &self,
+        other: &Smiles<OtherPolicy>,
+        atom_id: usize,
+        mapping: &[usize],
+        used: &[bool],
+    ) -> Vec<usize> {
+        let pool = match self
+            .bond_matrix
+            .sparse_row(atom_id)
+            .find(|&neighbor| mapping[neighbor] != usize::MAX)
+        {
+            Some(neighbor) => other.bond_matrix.sparse_row(mapping[neighbor]).collect::<Vec<_>>(),
+            None => (0..other.atom_nodes.len()).collect(),
+        };
+        pool.into_iter()
+            .filter(|&image| !used[image] && self.can_map(other, atom_id, image, mapping))
+            .collect()
+    }
+
+    /// Returns whether `atom_id` can map to `image` given the atoms mapped so
+    /// far.
+    fn can_map<OtherPolicy: SmilesAtomPolicy>(
+        &self,
+        other: &Smiles<OtherPolicy>,
+        atom_id: usize,
+        image: usize,
+        mapping: &[usize],
+    ) -> bool {
+        // Chirality tags are compared once the stereo neighbors are mapped,
+        // since the same configuration may be spelled with either tag.
+        self.atom_nodes[atom_id].without_chirality() == other.atom_nodes[image].without_chirality()
+            && self.atom_nodes[atom_id].chirality().is_some()
+                == other.atom_nodes[image].chirality().is_some()
+            && self.bond_matrix.sparse_row(atom_id).count()
+                == other.bond_matrix.sparse_row(image).count()
+            && self.bond_matrix.sparse_row(atom_id).all(|neighbor| {
+                mapping[neighbor] == usize::MAX
+                    || match (
+                        self.bond_entry_for_node_pair((atom_id, neighbor)),
+                        other.bond_entry_for_node_pair((image, mapping[neighbor])),
+                    ) {
+                        (Some(left), Some(right)) => {
+                            left.bond() == right.bond() && left.aromatic() == right.aromatic()
+                        }
+                        _ => false,
+                    }
+            })
+    }
+
+    /// Returns whether `atom_id` and its neighbors, among those chiral atoms
+    /// whose stereo neighbors are now all mapped, have the same configuration
+    /// as their images under `mapping`.
+    ///
+    /// Every chiral atom is checked once the last of itself and its neighbors
+    /// is mapped, so a complete mapping passing every step keeps all stereo.
+    fn stereo_configurations_match_around<OtherPolicy: SmilesAtomPolicy>(
+        &self,
+        other: &Smiles<OtherPolicy>,
+        atom_id: usize,
+        mapping: &[usize],
+    ) -> bool {
+        core::iter::once(atom_id).chain(self.bond_matrix.sparse_row(atom_id)).all(|center| {
+            let left = self.parsed_stereo_neighbors_row(center);
+            let ready = self.atom_nodes[center].chirality().is_some()
+                && mapping[center] != usize::MAX
+                && left.iter().all(|&neighbor| {
+                    match neighbor {
+                        StereoNeighbor::Atom(neighbor) => mapping[neighbor] != usize::MAX,
+                        StereoNeighbor::ExplicitHydrogen => true,
+                    }
+                });
+            if !ready {
+                return true;
+            }
+            let mapped = left
+                .iter()
+                .map(|&neighbor| {
+                    match neighbor {
+                        StereoNeighbor::Atom(neighbor) => StereoNeighbor::Atom(mapping[neighbor]),
+                        StereoNeighbor::ExplicitHydrogen => StereoNeighbor::ExplicitHydrogen,
+                    }
+                })
+                .collect::<Vec<_>>();
+            chirality_matches(
+                self.atom_nodes[center].chirality(),
+                &mapped,
+                other.atom_nodes[mapping[center]].chirality(),
+                other.parsed_stereo_neighbors_row(mapping[center]),
+            )
+        })
+    }
+}
+
+/// Returns whether `left`, around neighbors already renamed into the other
+/// graph's atom ids, spells the same configuration as `right` around its own
+/// neighbors.
+///
+/// Tetrahedral and allene-like tags are inverted when the neighbor lists
+/// differ by an odd permutation; the other chirality classes have no such
+/// rule here and must list their neighbors in the same order.
+fn chirality_matches(
+    left: Option<Chirality>,
+    left_neighbors: &[StereoNeighbor],
+    right: Option<Chirality>,
+    right_neighbors: &[StereoNeighbor],
+) -> bool {
+    let (Some(left), Some(right)) = (left, right) else {
+        return left == right;
+    };
+    if left_neighbors.len() != right_neighbors.len() {
+        return false;
+    }
+    match left {
+        Chirality::At | Chirality::AtAt | Chirality::TH(_) | Chirality::AL(_) => {
+            reorder_tetrahedral_chirality(left, left_neighbors, right_neighbors)
+                .is_some_and(|reordered| reordered.is_equivalent(right))
+        }
+        _ => left_neighbors == right_neighbors && left == right,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geometric_traits::traits::SparseValuedMatrixRef;
+
+    use super::*;
+
+    const FULLERENE: &str = "C12=C3C4=C5C6=C1C7=C8C9=C1C%10=C%11C(=C29)C3=C2C3=C4C4=C5C5=C9C6=C7C6=C7C8=C1C1=C8C%10=C%10C%11=C2C2=C3C3=C4C4=C5C5=C%11C%12=C(C6=C95)C7=C1C1=C%12C5=C%11C4=C3C3=C5C(=C81)C%10=C23";
+    const PORPHYRIN: &str = "C1=CC2=NC1=CC3=CC=C(N3)C=C4C=CC(=N4)C=C5C=CC(=C2)N5";
+
+    fn parse(smiles: &str) -> Smiles {
+        smiles.parse().unwrap()
+    }
+
+    /// Asserts that `mapping` carries every bond of `left` onto a bond of
+    /// `right`.
+    fn assert_mapping_keeps_bonds(left: &Smiles, right: &Smiles, mapping: &[usize]) {
+        for ((row, column), _) in left.bond_matrix().sparse_entries() {
+            assert!(right.bond_for_node_pair((mapping[row], mapping[column])).is_some());
+        }
+    }
+
+    /// Returns the atom order visiting every `stride`-th atom, which must be
+    /// coprime with the atom count.
+    fn strided_order(atom_count: usize, stride: usize) -> Vec<usize> {
+        (0..atom_count).map(|index| index * stride % atom_count).collect()
+    }
+
+    #[test]
+    fn atom_mapping_search_rejects_graphs_with_the_same_degrees() {
+        let cubane = parse("C12C3C4C1C5C2C3C45");
+        let relabeled = cubane.renumber(&[7, 3, 0, 5, 2, 6, 1, 4]).unwrap();
+        let AtomMappingSearch::Found(mapping) =
+            cubane.atom_mapping_to(&relabeled, MAX_MAPPING_STEPS)
+        else {
+            panic!("cubane maps onto its relabeling");
+        };
+        assert_mapping_keeps_bonds(&cubane, &relabeled, &mapping);
+
+        assert_eq!(
+            cubane.atom_mapping_to(&parse("C12C3C4C1C5C3C2C45"), MAX_MAPPING_STEPS),
+            AtomMappingSearch::NotIsomorphic
+        );
+        assert_eq!(
+            cubane.atom_mapping_to(&parse("C1CCCCCCC1"), MAX_MAPPING_STEPS),
+            AtomMappingSearch::NotIsomorphic
+        );
+        assert_eq!(cubane.is_isomorphic_to(&parse("C12C3C4C1C5C3C2C45")), Some(false));
+    }
+
+    #[test]
+    fn atom_mapping_search_maps_fullerene_and_porphyrin_relabelings() {
+        for source in [FULLERENE, PORPHYRIN] {
+            let original = parse(source);
+            let atom_count = original.nodes().len();
+            let reversed = (0..atom_count).rev().collect::<Vec<_>>();
+            for order in [reversed, strided_order(atom_count, 7)] {
+                let relabeled = original.renumber(&order).unwrap();
+                let AtomMappingSearch::Found(mapping) =
+                    original.atom_mapping_to(&relabeled, MAX_MAPPING_STEPS)
+                else {
+                    panic!("{source} maps onto its relabeling {order:?}");
+                };
+                assert_mapping_keeps_bonds(&original, &relabeled, &mapping);
+                assert_eq!(relabeled.is_isomorphic_to(&original), Some(true), "{source}");
+            }
+        }
+    }
+
+    #[test]
+    fn atom_mapping_search_keeps_chirality() {
+        let chiral = parse("N[C@@H](C)C(=O)O");
+        let relabeled = chiral.renumber(&[5, 4, 3, 2, 1, 0]).unwrap();
+        assert!(matches!(
+            chiral.atom_mapping_to(&relabeled, MAX_MAPPING_STEPS),
+            AtomMappingSearch::Found(_)
+        ));
+        assert_eq!(chiral.is_isomorphic_to(&relabeled), Some(true));
+
+        let mirrored = parse("N[C@H](C)C(=O)O");
+        assert_eq!(
+            chiral.atom_mapping_to(&mirrored, MAX_MAPPING_STEPS),
+            AtomMappingSearch::NotIsomorphic
+        );
+        assert_eq!(chiral.is_isomorphic_to(&mirrored), Some(false));
+    }
+
+    #[test]
+    fn atom_mapping_search_compares_chirality_by_neighbor_permutation_parity() {
+        let chiral = parse("N[C@@H](C)C(=O)O");
+        // The same enantiomer written from the methyl group lists the
+        // neighbors of the stereocenter in an odd permutation, with the
+        // opposite tag.
+        assert!(matches!(
+            chiral.atom_mapping_to(&parse("C[C@H](N)C(=O)O"), MAX_MAPPING_STEPS),
+            AtomMappingSearch::Found(_)
+        ));
+        assert_eq!(
+            chiral.atom_mapping_to(&parse("C[C@@H](N)C(=O)O"), MAX_MAPPING_STEPS),
+            AtomMappingSearch::NotIsomorphic
+        );
+        // An even permutation keeps the tag.
+        assert!(matches!(
+            chiral.atom_mapping_to(&parse("[C@@H](C)(N)C(=O)O"), MAX_MAPPING_STEPS),
+            AtomMappingSearch::Found(_)
+        ));
+    }
+
+    #[test]
+    fn atom_mapping_search_is_undecided_after_its_step_budget() {
+        let cubane = parse("C12C3C4C1C5C2C3C45");
+        let relabeled = cubane.renumber(&[7, 3, 0, 5, 2, 6, 1, 4]).unwrap();
+        assert!(matches!(cubane.atom_mapping_to(&relabeled, 8), AtomMappingSearch::Found(_)));
+        assert_eq!(cubane.atom_mapping_to(&relabeled, 7), AtomMappingSearch::Undecided);
+    }
+}
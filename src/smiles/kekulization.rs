@@ -17,11 +17,15 @@ use crate::bond::Bond;
 pub enum KekulizationError {
     /// No perfect matching exists for the aromatic candidate graph.
     #[error(
-        "cannot assign a Kekule form for the aromatic candidate graph with {candidate_atom_count} candidate atoms"
+        "cannot assign a Kekule form to the aromatic system of atoms {ring_atoms:?} with {candidate_atom_count} candidate atoms"
     )]
     NoPerfectMatching {
-        /// Number of candidate atoms that required localization.
+        /// Number of candidate atoms that required localization in the
+        /// systems that could not be localized.
         candidate_atom_count: usize,
+        /// Sorted ids of the atoms of the aromatic systems that cannot be
+        /// assigned alternating single and double bonds.
+        ring_atoms: Vec<usize>,
     },
     /// Standalone localization succeeded structurally, but re-perceiving the
     /// result under the original named policy changed the aromatic assignment.
@@ -104,14 +108,7 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
     /// [`AromaticityPerception::kekulize_with`](super::AromaticityPerception::kekulize_with)
     /// or [`AromaticityPerception::kekulize_standalone`](super::AromaticityPerception::kekulize_standalone).
     pub fn kekulize_with(&self, mode: KekulizationMode) -> Result<Self, KekulizationError> {
-        let aromatic_bonds = self
-            .bond_matrix()
-            .sparse_entries()
-            .filter_map(|((row, column), entry)| {
-                (row < column && entry.aromatic() && localizable_aromatic_bond(entry.bond()))
-                    .then_some(AromaticBond { node_a: row, node_b: column })
-            })
-            .collect::<Vec<_>>();
+        let aromatic_bonds = localizable_aromatic_bonds(self);
 
         let has_aromatic_flags = self.atom_nodes.iter().any(crate::atom::Atom::aromatic)
             || self
@@ -132,28 +129,87 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
             return Ok(clear_aromatic_flags(self));
         }
 
+        match self.localize(&aromatic_bonds) {
+            (kekulized, None) => Ok(kekulized),
+            (_, Some(error)) => Err(error),
+        }
+    }
+
+    /// Returns a localized Kekule form of the current graph, leaving the
+    /// aromatic systems that cannot be localized as they are.
+    ///
+    /// Where [`Smiles::kekulize_with`] fails as a whole, this method still
+    /// localizes every other aromatic system and keeps the aromatic atoms and
+    /// bonds of the failing ones. The error strict kekulization would have
+    /// returned comes back alongside the graph as a warning.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use smiles_parser::prelude::{KekulizationError, KekulizationMode, Smiles};
+    ///
+    /// let smiles = Smiles::from_str("c1ccccc1.c1cccc1").expect("valid aromatic rings");
+    /// let (kekulized, warning) = smiles.kekulize_lenient(KekulizationMode::Standalone);
+    ///
+    /// assert!(kekulized.nodes()[..6].iter().all(|atom| !atom.aromatic()));
+    /// assert!(kekulized.nodes()[6..].iter().all(|atom| atom.aromatic()));
+    /// assert_eq!(
+    ///     warning,
+    ///     Some(KekulizationError::NoPerfectMatching {
+    ///         candidate_atom_count: 5,
+    ///         ring_atoms: vec![6, 7, 8, 9, 10],
+    ///     })
+    /// );
+    /// ```
+    #[must_use]
+    pub fn kekulize_lenient(&self, mode: KekulizationMode) -> (Self, Option<KekulizationError>) {
+        match self.kekulize_with(mode) {
+            Ok(kekulized) => (kekulized, None),
+            Err(_) => {
+                let aromatic_bonds = localizable_aromatic_bonds(self);
+                self.localize(&aromatic_bonds)
+            }
+        }
+    }
+
+    /// Localizes every aromatic system that admits a Kekule form and reports
+    /// the ones that do not, which keep their aromatic atoms and bonds.
+    fn localize(&self, aromatic_bonds: &[AromaticBond]) -> (Self, Option<KekulizationError>) {
         let candidate_atom_ids = candidate_atom_ids(self);
-        if has_unlocalizable_aromatic_component(
-            self.nodes().len(),
-            &aromatic_bonds,
-            &candidate_atom_ids,
-        ) {
-            return Err(KekulizationError::NoPerfectMatching { candidate_atom_count: 0 });
+        let components = aromatic_components(self.nodes().len(), aromatic_bonds);
+        let mut has_candidate = vec![false; self.nodes().len()];
+        for &atom_id in &candidate_atom_ids {
+            has_candidate[components[atom_id]] = true;
+        }
+        // Systems without any candidate atom can only be localized from a
+        // preserved source graph.
+        let mut failed_components = vec![false; self.nodes().len()];
+        for bond in aromatic_bonds {
+            let component = components[bond.node_a];
+            failed_components[component] = !has_candidate[component];
         }
 
         let candidate_graph =
-            KekulizationCandidateGraph::new(self, &aromatic_bonds, &candidate_atom_ids);
-        if candidate_graph.graph.number_of_rows() == 0 {
-            return Err(KekulizationError::NoPerfectMatching { candidate_atom_count: 0 });
+            KekulizationCandidateGraph::new(self, aromatic_bonds, &candidate_atom_ids);
+        let (matched_edges, unmatched_atom_ids) = candidate_graph.matched_original_edges();
+        for atom_id in unmatched_atom_ids {
+            failed_components[components[atom_id]] = true;
         }
-        let matched_edges = candidate_graph.matched_original_edges()?;
-
-        let atom_nodes = self
-            .atom_nodes
-            .iter()
-            .copied()
-            .map(|atom| if atom.aromatic() { atom.with_aromatic(false) } else { atom })
-            .collect::<Vec<_>>();
+        let failed = |atom_id: usize| failed_components[components[atom_id]];
+
+        let atom_nodes =
+            self.atom_nodes
+                .iter()
+                .copied()
+                .enumerate()
+                .map(|(atom_id, atom)| {
+                    if atom.aromatic() && !failed(atom_id) {
+                        atom.with_aromatic(false)
+                    } else {
+                        atom
+                    }
+                })
+                .collect::<Vec<_>>();
 
         let bond_matrix = BondMatrix::from_sorted_upper_triangular_entries(
             atom_nodes.len(),
@@ -161,7 +217,7 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
                 (row < column).then_some((
                     row,
                     column,
-                    if entry.aromatic() {
+                    if entry.aromatic() && !failed(row) {
                         if !localizable_aromatic_bond(entry.bond()) {
                             entry.with_aromatic(false)
                         } else if matched_edges.contains(&(row, column)) {
@@ -182,7 +238,19 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
             bond_matrix,
             self.parsed_stereo_neighbors.clone(),
         );
-        Ok(kekulized)
+
+        let ring_atoms =
+            (0..self.nodes().len()).filter(|&atom_id| failed(atom_id)).collect::<Vec<_>>();
+        let error = (!ring_atoms.is_empty()).then(|| {
+            KekulizationError::NoPerfectMatching {
+                candidate_atom_count: candidate_atom_ids
+                    .iter()
+                    .filter(|&&atom_id| failed(atom_id))
+                    .count(),
+                ring_atoms,
+            }
+        });
+        (kekulized, error)
     }
 
     /// Returns a localized Kekule form by solving from the current aromatic
@@ -222,6 +290,7 @@ struct AromaticBond {
 struct KekulizationCandidateGraph {
     graph: BondMatrix,
     original_edge_keys_by_order: Vec<(usize, usize)>,
+    original_atom_ids: Vec<usize>,
 }
 
 impl KekulizationCandidateGraph {
@@ -264,26 +333,24 @@ impl KekulizationCandidateGraph {
             unreachable!("candidate graph only adds unique non-self upper-triangular edges")
         });
 
-        Self { graph, original_edge_keys_by_order }
+        Self { graph, original_edge_keys_by_order, original_atom_ids: local_atom_ids.to_vec() }
     }
 
-    fn matched_original_edges(&self) -> Result<Vec<(usize, usize)>, KekulizationError> {
-        let candidate_atom_count = self.graph.number_of_rows();
-        if candidate_atom_count == 0 {
-            return Ok(Vec::new());
-        }
-        if !candidate_atom_count.is_multiple_of(2) {
-            return Err(KekulizationError::NoPerfectMatching { candidate_atom_count });
+    /// Returns the aromatic bonds of a maximum matching of the candidate
+    /// graph, which become double bonds, and the candidate atoms it leaves
+    /// unmatched.
+    fn matched_original_edges(&self) -> (Vec<(usize, usize)>, Vec<usize>) {
+        if self.graph.number_of_rows() == 0 {
+            return (Vec::new(), Vec::new());
         }
 
         let matches = self.graph.gabow_1976();
-        if matches.len() * 2 != candidate_atom_count {
-            return Err(KekulizationError::NoPerfectMatching { candidate_atom_count });
-        }
-
-        Ok(matches
+        let mut matched_atoms = vec![false; self.original_atom_ids.len()];
+        let edges = matches
             .into_iter()
             .map(|(left, right)| {
+                matched_atoms[left] = true;
+                matched_atoms[right] = true;
                 let rank =
                     self.graph.try_rank(left.min(right), left.max(right)).unwrap_or_else(|| {
                         unreachable!("matching edges always come from the candidate graph")
@@ -291,10 +358,29 @@ impl KekulizationCandidateGraph {
                 let original_edge_order = self.graph.select_value_ref(rank).order();
                 self.original_edge_keys_by_order[original_edge_order]
             })
-            .collect())
+            .collect();
+        let unmatched_atom_ids = self
+            .original_atom_ids
+            .iter()
+            .zip(matched_atoms)
+            .filter_map(|(&atom_id, matched)| (!matched).then_some(atom_id))
+            .collect();
+
+        (edges, unmatched_atom_ids)
     }
 }
 
+fn localizable_aromatic_bonds(smiles: &Smiles<impl SmilesAtomPolicy>) -> Vec<AromaticBond> {
+    smiles
+        .bond_matrix()
+        .sparse_entries()
+        .filter_map(|((row, column), entry)| {
+            (row < column && entry.aromatic() && localizable_aromatic_bond(entry.bond()))
+                .then_some(AromaticBond { node_a: row, node_b: column })
+        })
+        .collect()
+}
+
 #[inline]
 fn localizable_aromatic_bond(bond: Bond) -> bool {
     !matches!(bond.without_direction(), Bond::Triple | Bond::Quadruple)
@@ -325,50 +411,37 @@ fn clear_aromatic_flags<AtomPolicy: SmilesAtomPolicy>(
     )
 }
 
-fn has_unlocalizable_aromatic_component(
-    atom_count: usize,
-    aromatic_bonds: &[AromaticBond],
-    candidate_atom_ids: &[usize],
-) -> bool {
+/// Labels every atom with the id of the aromatic system it belongs to, which
+/// is the smallest atom id connected to it through aromatic bonds.
+fn aromatic_components(atom_count: usize, aromatic_bonds: &[AromaticBond]) -> Vec<usize> {
     let mut adjacency = vec![Vec::new(); atom_count];
     for bond in aromatic_bonds {
         adjacency[bond.node_a].push(bond.node_b);
         adjacency[bond.node_b].push(bond.node_a);
     }
 
-    let mut candidate_flags = vec![false; atom_count];
-    for atom_id in candidate_atom_ids {
-        candidate_flags[*atom_id] = true;
-    }
-
-    let mut visited = vec![false; atom_count];
+    let mut components = vec![usize::MAX; atom_count];
     let mut stack = Vec::new();
 
     for start in 0..atom_count {
-        if visited[start] || adjacency[start].is_empty() {
+        if components[start] != usize::MAX {
             continue;
         }
 
-        visited[start] = true;
+        components[start] = start;
         stack.push(start);
-        let mut component_candidate_count = 0usize;
 
         while let Some(node) = stack.pop() {
-            component_candidate_count += usize::from(candidate_flags[node]);
             for &neighbor in &adjacency[node] {
-                if !visited[neighbor] {
-                    visited[neighbor] = true;
+                if components[neighbor] == usize::MAX {
+                    components[neighbor] = start;
                     stack.push(neighbor);
                 }
             }
         }
-
-        if component_candidate_count == 0 {
-            return true;
-        }
     }
 
-    false
+    components
 }
 
 fn candidate_atom_ids(smiles: &Smiles<impl SmilesAtomPolicy>) -> Vec<usize> {
@@ -461,10 +534,7 @@ mod tests {
     use elements_rs::Element;
     use geometric_traits::traits::SparseValuedMatrixRef;
 
-    use super::{
-        AromaticBond, KekulizationCandidateGraph, KekulizationError, KekulizationMode, Smiles,
-        candidate_atom_ids,
-    };
+    use super::{KekulizationError, KekulizationMode, Smiles, candidate_atom_ids};
     use crate::{
         atom::{Atom, atom_symbol::AtomSymbol},
         bond::{
@@ -722,7 +792,10 @@ mod tests {
 
         assert_eq!(
             smiles.kekulize(),
-            Err(KekulizationError::NoPerfectMatching { candidate_atom_count: 5 })
+            Err(KekulizationError::NoPerfectMatching {
+                candidate_atom_count: 5,
+                ring_atoms: vec![0, 1, 2, 3, 4]
+            })
         );
     }
 
@@ -736,21 +809,44 @@ mod tests {
                 bond_edge_with_aromaticity(2, 0, Bond::Single, None, true),
             ],
         );
-        let aromatic_bonds = smiles
-            .bond_matrix()
-            .sparse_entries()
-            .filter_map(|((row, column), entry)| {
-                (row < column && entry.aromatic())
-                    .then_some(AromaticBond { node_a: row, node_b: column })
+
+        assert_eq!(
+            smiles.kekulize_standalone(),
+            Err(KekulizationError::NoPerfectMatching {
+                candidate_atom_count: 3,
+                ring_atoms: vec![0, 1, 2]
             })
+        );
+    }
+
+    #[test]
+    fn lenient_kekulization_keeps_only_the_failing_systems_aromatic() {
+        let smiles = Smiles::from_str("c1ccccc1-c1cccc1.c1ccc[nH]1").expect("valid aromatic rings");
+        let error = smiles.kekulize().expect_err("the five-carbon ring has no Kekule form");
+        assert_eq!(
+            error,
+            KekulizationError::NoPerfectMatching {
+                candidate_atom_count: 5,
+                ring_atoms: vec![6, 7, 8, 9, 10]
+            }
+        );
+
+        let (kekulized, warning) = smiles.kekulize_lenient(KekulizationMode::PreserveSource);
+        assert_eq!(warning, Some(error));
+        let aromatic_atoms = kekulized
+            .nodes()
+            .iter()
+            .enumerate()
+            .filter_map(|(atom_id, atom)| atom.aromatic().then_some(atom_id))
             .collect::<Vec<_>>();
-        let candidate_atom_ids = candidate_atom_ids(&smiles);
-        let candidate_graph =
-            KekulizationCandidateGraph::new(&smiles, &aromatic_bonds, &candidate_atom_ids);
+        assert_eq!(aromatic_atoms, vec![6, 7, 8, 9, 10]);
+        assert_eq!(count_aromatic_bonds(&kekulized), 5);
+        assert_eq!(count_bonds(&kekulized, Bond::Double), 5);
 
+        let benzene = Smiles::from_str("c1ccccc1").expect("valid aromatic benzene");
         assert_eq!(
-            candidate_graph.matched_original_edges(),
-            Err(KekulizationError::NoPerfectMatching { candidate_atom_count: 3 })
+            benzene.kekulize_lenient(KekulizationMode::Standalone),
+            (benzene.kekulize_standalone().expect("benzene should kekulize"), None)
         );
     }
 
@@ -836,7 +932,7 @@ mod tests {
     fn is_expected_fuzz_standalone_error(error: &KekulizationError) -> bool {
         matches!(
             error,
-            KekulizationError::NoPerfectMatching { candidate_atom_count: 0 }
+            KekulizationError::NoPerfectMatching { candidate_atom_count: 0, .. }
                 | KekulizationError::StandaloneRoundtripMismatch
         )
     }
@@ -943,10 +1039,11 @@ mod tests {
         let reparsed =
             Smiles::from_str(&aromatic_smiles).expect("rendered aromatic smiles should parse");
 
-        assert_eq!(
+        assert!(matches!(
             reparsed.kekulize_standalone(),
-            Err(KekulizationError::NoPerfectMatching { candidate_atom_count: 0 })
-        );
+            Err(KekulizationError::NoPerfectMatching { candidate_atom_count: 0, ref ring_atoms })
+                if !ring_atoms.is_empty()
+        ));
     }
 
     #[test]
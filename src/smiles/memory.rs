@@ -0,0 +1,192 @@
+//! Heap usage estimates for capacity planning.
+
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use super::{
+    BondEntry, Smiles, SmilesAtomPolicy, StereoNeighbor, WildcardSmiles, metadata::SmilesMetadata,
+};
+use crate::atom::Atom;
+
+/// Estimated heap bytes held by a [`Smiles`] graph, broken down by storage.
+///
+/// Vectors are counted by capacity, so the figures include slack left over
+/// by parsing. The bond matrix is a compressed sparse row matrix storing both
+/// directions of every bond; its size is derived from the atom and bond
+/// counts, since its buffers belong to `geometric-traits`. The inline size of
+/// the [`Smiles`] value itself is not included.
+///
+/// A graph carrying a name, properties or a record of explicit single bonds
+/// also counts its [metadata](MemoryFootprint::metadata).
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::prelude::Smiles;
+///
+/// let smiles: Smiles = "C[C@H](N)C(=O)O".parse()?;
+/// let footprint = smiles.memory_footprint();
+///
+/// assert!(footprint.atoms() >= 6 * size_of::<smiles_parser::atom::Atom>());
+/// assert!(footprint.stereo_neighbors() > 0);
+/// assert_eq!(
+///     footprint.total(),
+///     footprint.atoms()
+///         + footprint.bonds()
+///         + footprint.stereo_neighbors()
+///         + footprint.implicit_hydrogens()
+///         + footprint.kekulization_source()
+///         + footprint.metadata()
+/// );
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MemoryFootprint {
+    atoms: usize,
+    bonds: usize,
+    stereo_neighbors: usize,
+    implicit_hydrogens: usize,
+    kekulization_source: usize,
+    metadata: usize,
+}
+
+impl MemoryFootprint {
+    /// Returns the bytes of the atom vector.
+    #[inline]
+    #[must_use]
+    pub fn atoms(&self) -> usize {
+        self.atoms
+    }
+
+    /// Returns the estimated bytes of the bond matrix: row offsets, column
+    /// indices and one [`BondEntry`] per bond direction.
+    #[inline]
+    #[must_use]
+    pub fn bonds(&self) -> usize {
+        self.bonds
+    }
+
+    /// Returns the bytes of the per-atom stereo neighbor lists, including the
+    /// outer vector.
+    #[inline]
+    #[must_use]
+    pub fn stereo_neighbors(&self) -> usize {
+        self.stereo_neighbors
+    }
+
+    /// Returns the bytes of the implicit hydrogen cache.
+    #[inline]
+    #[must_use]
+    pub fn implicit_hydrogens(&self) -> usize {
+        self.implicit_hydrogens
+    }
+
+    /// Returns the bytes of the Kekule source graph kept by aromaticized
+    /// graphs, or zero.
+    #[inline]
+    #[must_use]
+    pub fn kekulization_source(&self) -> usize {
+        self.kekulization_source
+    }
+
+    /// Returns the estimated bytes of the name, properties and explicit
+    /// single bond record, or zero for a graph without metadata.
+    #[inline]
+    #[must_use]
+    pub fn metadata(&self) -> usize {
+        self.metadata
+    }
+
+    /// Returns the sum of all parts.
+    #[inline]
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.atoms
+            + self.bonds
+            + self.stereo_neighbors
+            + self.implicit_hydrogens
+            + self.kekulization_source
+            + self.metadata
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns an estimate of the heap bytes held by the graph.
+    ///
+    /// See [`MemoryFootprint`] for what is counted.
+    #[must_use]
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        let directed_bonds = 2 * self.number_of_bonds();
+        MemoryFootprint {
+            atoms: self.atom_nodes.capacity() * size_of::<Atom>(),
+            bonds: (self.atom_nodes.len() + 1) * size_of::<usize>()
+                + directed_bonds * (size_of::<usize>() + size_of::<BondEntry>()),
+            stereo_neighbors: self.parsed_stereo_neighbors.capacity()
+                * size_of::<Vec<StereoNeighbor>>()
+                + self
+                    .parsed_stereo_neighbors
+                    .iter()
+                    .map(|row| row.capacity() * size_of::<StereoNeighbor>())
+                    .sum::<usize>(),
+            implicit_hydrogens: self.implicit_hydrogen_cache.capacity(),
+            kekulization_source: self
+                .kekulization_source
+                .as_ref()
+                .map_or(0, |source| size_of::<Self>() + source.memory_footprint().total()),
+            metadata: self.metadata.as_deref().map_or(0, SmilesMetadata::heap_bytes),
+        }
+    }
+}
+
+impl WildcardSmiles {
+    /// Returns an estimate of the heap bytes held by the graph.
+    #[inline]
+    #[must_use]
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        self.inner().memory_footprint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn footprint_grows_with_the_graph() {
+        let small: Smiles = "CC".parse().unwrap();
+        let large: Smiles = "CC(C)CC1CCC(CC1)C(C)C(=O)O".parse().unwrap();
+
+        assert!(large.memory_footprint().atoms() > small.memory_footprint().atoms());
+        assert!(large.memory_footprint().bonds() > small.memory_footprint().bonds());
+        assert!(large.memory_footprint().total() > small.memory_footprint().total());
+        assert_eq!(small.memory_footprint().kekulization_source(), 0);
+        assert_eq!(small.memory_footprint().metadata(), 0);
+    }
+
+    #[test]
+    fn metadata_is_counted() {
+        let mut smiles: Smiles = "CCO".parse().unwrap();
+        let plain = smiles.memory_footprint();
+
+        smiles.set_name("ethanol");
+        let named = smiles.memory_footprint();
+        assert!(named.metadata() >= "ethanol".len());
+        assert_eq!(named.total(), plain.total() + named.metadata());
+
+        smiles.set_property("source", "ChEBI:16236");
+        assert!(smiles.memory_footprint().metadata() > named.metadata());
+    }
+
+    #[test]
+    fn aromaticized_graphs_count_their_kekule_source() {
+        let benzene: Smiles = "C1=CC=CC=C1".parse().unwrap();
+        let perception = benzene.perceive_aromaticity().unwrap();
+
+        let footprint = perception.aromaticized().memory_footprint();
+        assert!(footprint.kekulization_source() > benzene.memory_footprint().atoms());
+        assert!(footprint.total() > footprint.kekulization_source());
+
+        let wildcard: WildcardSmiles = "*c1ccccc1".parse().unwrap();
+        assert!(wildcard.memory_footprint().total() > 0);
+    }
+}
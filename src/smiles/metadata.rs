@@ -0,0 +1,313 @@
+//! Names, key-value properties and written-form details attached to a graph.
+//!
+//! File formats pair structures with identifiers and data columns, so a
+//! [`Smiles`] graph can carry a name and string properties itself instead of
+//! in a parallel collection. A graph parsed with
+//! [`ParserOptions::explicit_single_bonds`](super::ParserOptions::explicit_single_bonds)
+//! also records which single bonds its input wrote as `-`. The metadata is
+//! stored behind a single optional pointer, so graphs without metadata pay one
+//! word for it. It belongs to the graph value: clones keep it, but graphs
+//! derived by transformations such as [`Smiles::canonicalize`] start without
+//! metadata, and graph equality ignores it.
+
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+use core::mem::size_of;
+
+use super::{Smiles, SmilesAtomPolicy, WildcardSmiles, edge_key};
+
+/// Name and properties of a graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct SmilesMetadata {
+    name: Option<String>,
+    properties: BTreeMap<String, String>,
+    /// Sorted edge keys of the single bonds written as `-` in the input.
+    explicit_single_bonds: Vec<(usize, usize)>,
+}
+
+impl SmilesMetadata {
+    /// Returns an estimate of the heap bytes held by the boxed metadata:
+    /// the box itself, the string buffers, one key and value per property
+    /// entry and the explicit single bond list. The tree nodes of the
+    /// property map are not counted beyond their entries.
+    pub(crate) fn heap_bytes(&self) -> usize {
+        size_of::<Self>()
+            + self.name.as_ref().map_or(0, String::capacity)
+            + self
+                .properties
+                .iter()
+                .map(|(key, value)| 2 * size_of::<String>() + key.capacity() + value.capacity())
+                .sum::<usize>()
+            + self.explicit_single_bonds.capacity() * size_of::<(usize, usize)>()
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns the name attached to the graph, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let mut smiles: Smiles = "CCO".parse()?;
+    /// assert_eq!(smiles.name(), None);
+    ///
+    /// smiles.set_name("ethanol");
+    /// assert_eq!(smiles.name(), Some("ethanol"));
+    /// assert_eq!(smiles.clone().name(), Some("ethanol"));
+    /// assert_eq!(smiles.canonicalize().name(), None);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.metadata.as_ref()?.name.as_deref()
+    }
+
+    /// Attaches a name to the graph, replacing the previous one.
+    #[inline]
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.metadata_mut().name = Some(name.into());
+    }
+
+    /// Removes the name attached to the graph and returns it.
+    #[inline]
+    pub fn take_name(&mut self) -> Option<String> {
+        let name = self.metadata.as_mut()?.name.take();
+        self.drop_empty_metadata();
+        name
+    }
+
+    /// Returns the value of the property `key`, if set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let mut smiles: Smiles = "OC(=O)c1ccccc1O".parse()?;
+    /// smiles.set_property("CAS", "69-72-7");
+    /// smiles.set_property("source", "ChEBI");
+    ///
+    /// assert_eq!(smiles.property("CAS"), Some("69-72-7"));
+    /// assert_eq!(smiles.properties().map(|(key, _)| key).collect::<Vec<_>>(), ["CAS", "source"]);
+    /// assert_eq!(smiles.remove_property("source").as_deref(), Some("ChEBI"));
+    /// assert_eq!(smiles.property("source"), None);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.metadata.as_ref()?.properties.get(key).map(String::as_str)
+    }
+
+    /// Sets the property `key` and returns its previous value.
+    #[inline]
+    pub fn set_property(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Option<String> {
+        self.metadata_mut().properties.insert(key.into(), value.into())
+    }
+
+    /// Removes the property `key` and returns its value.
+    #[inline]
+    pub fn remove_property(&mut self, key: &str) -> Option<String> {
+        let value = self.metadata.as_mut()?.properties.remove(key);
+        self.drop_empty_metadata();
+        value
+    }
+
+    /// Returns the properties as `(key, value)` pairs, in key order.
+    #[inline]
+    pub fn properties(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.metadata
+            .iter()
+            .flat_map(|metadata| &metadata.properties)
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Returns whether the bond between `node_a` and `node_b` is a single
+    /// bond the input wrote explicitly as `-`.
+    ///
+    /// Only graphs parsed with
+    /// [`ParserOptions::explicit_single_bonds`](super::ParserOptions::explicit_single_bonds)
+    /// keep this record; rendering such a graph writes the recorded bonds
+    /// with their `-`, so the input round-trips token for token.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::{ParserOptions, Smiles};
+    ///
+    /// let options = ParserOptions { explicit_single_bonds: true, ..ParserOptions::default() };
+    /// let smiles = Smiles::parse_with_options("C-CO", &options)?;
+    /// assert!(smiles.is_explicit_single_bond(1, 0));
+    /// assert!(!smiles.is_explicit_single_bond(1, 2));
+    /// assert_eq!(smiles.render(), "C-CO");
+    ///
+    /// let plain: Smiles = "C-CO".parse()?;
+    /// assert!(!plain.is_explicit_single_bond(0, 1));
+    /// assert_eq!(plain.render(), "CCO");
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_explicit_single_bond(&self, node_a: usize, node_b: usize) -> bool {
+        self.metadata.as_ref().is_some_and(|metadata| {
+            metadata.explicit_single_bonds.binary_search(&edge_key(node_a, node_b)).is_ok()
+        })
+    }
+
+    /// Returns the edge keys of the single bonds the input wrote as `-`, in
+    /// ascending order; see [`Smiles::is_explicit_single_bond`].
+    #[inline]
+    pub fn explicit_single_bonds(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.metadata.iter().flat_map(|metadata| metadata.explicit_single_bonds.iter().copied())
+    }
+
+    /// Records the single bonds written as `-`, given as edge keys.
+    pub(crate) fn set_explicit_single_bonds(&mut self, mut edges: Vec<(usize, usize)>) {
+        edges.sort_unstable();
+        self.metadata_mut().explicit_single_bonds = edges;
+        self.drop_empty_metadata();
+    }
+
+    /// Moves the name and properties to `derived`, a graph computed from this
+    /// one. The record of explicit single bonds refers to the atom ids of
+    /// this graph and is dropped.
+    pub(crate) fn move_metadata_to(&mut self, derived: &mut Self) {
+        derived.metadata = self.metadata.take();
+        if let Some(metadata) = &mut derived.metadata {
+            metadata.explicit_single_bonds.clear();
+        }
+        derived.drop_empty_metadata();
+    }
+
+    fn metadata_mut(&mut self) -> &mut SmilesMetadata {
+        self.metadata.get_or_insert_with(Box::default)
+    }
+
+    fn drop_empty_metadata(&mut self) {
+        if self.metadata.as_ref().is_some_and(|metadata| {
+            metadata.name.is_none()
+                && metadata.properties.is_empty()
+                && metadata.explicit_single_bonds.is_empty()
+        }) {
+            self.metadata = None;
+        }
+    }
+}
+
+impl WildcardSmiles {
+    /// Returns the name attached to the graph, if any.
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.inner.name()
+    }
+
+    /// Attaches a name to the graph, replacing the previous one.
+    #[inline]
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.inner.set_name(name);
+    }
+
+    /// Removes the name attached to the graph and returns it.
+    #[inline]
+    pub fn take_name(&mut self) -> Option<String> {
+        self.inner.take_name()
+    }
+
+    /// Returns the value of the property `key`, if set.
+    #[inline]
+    #[must_use]
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.inner.property(key)
+    }
+
+    /// Sets the property `key` and returns its previous value.
+    #[inline]
+    pub fn set_property(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Option<String> {
+        self.inner.set_property(key, value)
+    }
+
+    /// Removes the property `key` and returns its value.
+    #[inline]
+    pub fn remove_property(&mut self, key: &str) -> Option<String> {
+        self.inner.remove_property(key)
+    }
+
+    /// Returns the properties as `(key, value)` pairs, in key order.
+    #[inline]
+    pub fn properties(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.inner.properties()
+    }
+
+    /// Returns whether the bond between `node_a` and `node_b` is a single
+    /// bond the input wrote explicitly as `-`.
+    #[inline]
+    #[must_use]
+    pub fn is_explicit_single_bond(&self, node_a: usize, node_b: usize) -> bool {
+        self.inner.is_explicit_single_bond(node_a, node_b)
+    }
+
+    /// Returns the edge keys of the single bonds the input wrote as `-`, in
+    /// ascending order.
+    #[inline]
+    pub fn explicit_single_bonds(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.inner.explicit_single_bonds()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_is_kept_by_clones_and_conversions_only() {
+        let mut smiles: Smiles = "CC".parse().unwrap();
+        smiles.set_name("ethane");
+        assert_eq!(smiles.set_property("id", "1"), None);
+        assert_eq!(smiles.set_property("id", "2").as_deref(), Some("1"));
+
+        let wildcard = WildcardSmiles::from(smiles.clone());
+        assert_eq!(wildcard.name(), Some("ethane"));
+        assert_eq!(wildcard.property("id"), Some("2"));
+        assert_eq!(smiles, "CC".parse::<Smiles>().unwrap());
+
+        assert_eq!(smiles.take_name().as_deref(), Some("ethane"));
+        assert_eq!(smiles.remove_property("id").as_deref(), Some("2"));
+        assert!(smiles.metadata.is_none());
+        assert_eq!(smiles.properties().count(), 0);
+    }
+
+    #[test]
+    fn explicit_single_bonds_are_recorded_and_written_back() {
+        let options = super::super::ParserOptions {
+            explicit_single_bonds: true,
+            ..super::super::ParserOptions::default()
+        };
+        for source in ["C-C(-O)C", "C1CC-1", "c1ccccc1-c1ccccc1", "C-C.N-O", "CC=CC"] {
+            let smiles = Smiles::parse_with_options(source, &options).unwrap();
+            assert_eq!(smiles.render(), source);
+            assert_eq!(smiles, source.parse::<Smiles>().unwrap());
+        }
+
+        let mut smiles = Smiles::parse_with_options("C-CC-O", &options).unwrap();
+        assert_eq!(smiles.explicit_single_bonds().collect::<Vec<_>>(), [(0, 1), (2, 3)]);
+        assert!(smiles.clone().is_explicit_single_bond(3, 2));
+        assert_eq!(smiles.canonicalize().explicit_single_bonds().count(), 0);
+
+        smiles.set_name("propanol");
+        let mut derived: Smiles = "OCCC".parse().unwrap();
+        smiles.move_metadata_to(&mut derived);
+        assert_eq!(derived.name(), Some("propanol"));
+        assert_eq!(derived.explicit_single_bonds().count(), 0);
+    }
+}
@@ -21,7 +21,7 @@
 //! # Ok::<(), smiles_parser::errors::SmilesErrorWithSpan>(())
 //! ```
 use alloc::{boxed::Box, string::String, vec::Vec};
-use core::{fmt, marker::PhantomData};
+use core::{fmt, marker::PhantomData, ops::AddAssign};
 
 use elements_rs::Element;
 use geometric_traits::traits::{
@@ -39,25 +39,44 @@ mod aromaticity;
 mod atom_environment;
 mod branches;
 mod canonicalization;
+mod compact;
 mod connected_components;
+mod descriptors;
+mod diff;
 mod double_bond_stereo;
+mod embedding;
 mod emitter;
+mod fingerprint;
 mod fragment;
 mod from_str;
 mod geometric_traits_impl;
 mod implicit_hydrogens;
 mod invariants;
+mod isomorphism;
 mod kekulization;
 mod mces;
+mod memory;
+mod metadata;
 mod molecular_formula;
 mod neighbors;
+mod perception;
+mod profile;
+mod protonation;
 mod rdkit_symm_sssr;
 mod refinement;
 mod render_plan;
+mod ring_replacement;
 mod roots;
+mod shape;
+mod shared;
 mod spanning_tree;
 mod stereo;
+mod strip;
 mod symmetry;
+mod tautomer;
+mod transaction;
+mod valence;
+mod writer;
 
 use self::{aromaticity::rdkit_smarts_total_valence, implicit_hydrogens::explicit_valence};
 pub use self::{
@@ -68,20 +87,34 @@ pub use self::{
         WildcardAromaticityPerception,
     },
     atom_environment::AtomEnvironment,
-    canonicalization::SmilesCanonicalLabeling,
+    canonicalization::{CanonicalAlgorithmVersion, SmilesCanonicalLabeling},
+    compact::{CompactSmiles, CompactSmilesRef},
     connected_components::{SmilesComponents, WildcardSmilesComponents},
+    diff::{AtomAttribute, BondAttribute, SmilesDiff},
     double_bond_stereo::DoubleBondStereoConfig,
+    embedding::Conformer,
+    fingerprint::{FINGERPRINT_BITS, Fingerprint, MAX_PATH_BONDS},
     fragment::Fragment,
+    from_str::{ParserOptions, ParserScratch},
     geometric_traits_impl::{BondEntry, BondMatrix},
     kekulization::{KekulizationError, KekulizationMode},
     mces::{
         GraphSimilarities, InitialProductVertexOrdering, LargestFragmentMetric, McesBuilder,
         McesResult, McesSearchMode, SmilesMces,
     },
+    memory::MemoryFootprint,
     molecular_formula::WildcardMolecularFormulaConversionError,
+    perception::{AtomPerception, Hybridization},
+    profile::ElementProfile,
+    protonation::PhModel,
+    shared::SharedSmiles,
+    transaction::{Transaction, TransactionError},
+    valence::{ValenceError, ValenceModel},
+    writer::{HighlightStyle, WriterOptions},
 };
 pub(crate) use self::{
     geometric_traits_impl::{BondMatrixBuilder, build_bond_matrix_from_known_simple_edges},
+    molecular_formula::element_counts,
     stereo::StereoNeighbor,
 };
 
@@ -365,6 +398,23 @@ impl SmilesAtomPolicy for WildcardAtoms {
 }
 
 /// Represents a parsed SMILES graph.
+///
+/// Atom ids are plain positional indices in parse order, and bonds are
+/// addressed by the pair of atom ids they connect. Ids are not stable
+/// handles: they only mean something against the graph value they were read
+/// from. Methods taking `&self` that add, remove or reorder atoms return a new
+/// graph whose ids must not be mixed with those of the original.
+///
+/// A graph only changes in place through `&mut` access. The name and property
+/// setters leave atoms and bonds untouched, but a committed
+/// [`Smiles::transaction`] replaces the whole graph with the result of its
+/// edits, so ids read before it are invalid afterwards whenever an edit
+/// renumbered atoms, as [`Smiles::canonicalize`] does. The same holds for any
+/// graph assigned through [`SharedSmiles::make_mut`] or another `&mut`
+/// reference.
+///
+/// Cloning copies every atom and bond; wrap a graph in [`SharedSmiles`] to
+/// pass it between tasks and caches by reference count instead.
 #[derive(Debug, Clone)]
 pub struct Smiles<AtomPolicy = ConcreteAtoms> {
     atom_nodes: Vec<Atom>,
@@ -372,6 +422,7 @@ pub struct Smiles<AtomPolicy = ConcreteAtoms> {
     parsed_stereo_neighbors: Vec<Vec<StereoNeighbor>>,
     implicit_hydrogen_cache: Vec<u8>,
     kekulization_source: Option<Box<Self>>,
+    metadata: Option<Box<metadata::SmilesMetadata>>,
     atom_policy: PhantomData<fn() -> AtomPolicy>,
 }
 
@@ -419,6 +470,7 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
             parsed_stereo_neighbors: Vec::new(),
             implicit_hydrogen_cache: Vec::new(),
             kekulization_source: None,
+            metadata: None,
             atom_policy: PhantomData,
         }
     }
@@ -474,6 +526,7 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
             parsed_stereo_neighbors,
             implicit_hydrogen_cache,
             kekulization_source,
+            metadata,
             atom_policy: _,
         } = self;
         Smiles {
@@ -483,6 +536,7 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
             implicit_hydrogen_cache,
             kekulization_source: kekulization_source
                 .map(|source| Box::new((*source).into_atom_policy())),
+            metadata,
             atom_policy: PhantomData,
         }
     }
@@ -551,6 +605,69 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
         self.bond_matrix.sparse_row_values_ref(id).count()
     }
 
+    /// Iterates the neighbors of the provided atom id together with the bond
+    /// reaching each of them, in ascending neighbor order.
+    ///
+    /// Each [`BondEdge`] is materialized by value, oriented from `id` to the
+    /// neighbor, exactly as [`Smiles::edges_for_node`] yields it.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{bond::Bond, prelude::Smiles};
+    ///
+    /// let smiles: Smiles = "C=CO".parse()?;
+    /// let neighbors = smiles
+    ///     .neighbors_with_bonds(1)
+    ///     .map(|(neighbor, edge)| (neighbor, edge.bond()))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(neighbors, [(0, Bond::Double), (2, Bond::Single)]);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[inline]
+    pub fn neighbors_with_bonds(&self, id: usize) -> impl Iterator<Item = (usize, BondEdge)> + '_ {
+        self.edges_for_node(id).map(|edge| (edge.target(), edge))
+    }
+
+    /// Returns the number of atoms bonded to the provided atom id.
+    ///
+    /// Implicit and bracket hydrogens are not counted; explicit hydrogen atoms
+    /// are.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    #[inline]
+    #[must_use]
+    pub fn degree(&self, id: usize) -> usize {
+        self.edge_count_for_node(id)
+    }
+
+    /// Returns the number of non-hydrogen atoms bonded to the provided atom
+    /// id. Wildcard neighbors count as heavy atoms.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in this graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let smiles: Smiles = "[H]C([H])(O)C".parse()?;
+    /// assert_eq!(smiles.degree(1), 4);
+    /// assert_eq!(smiles.heavy_degree(1), 2);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn heavy_degree(&self, id: usize) -> usize {
+        self.neighbors_with_bonds(id)
+            .filter(|&(neighbor, _)| self.atom_nodes[neighbor].element() != Some(Element::H))
+            .count()
+    }
+
     /// Returns the connectivity count for the provided atom id.
     ///
     /// This is the local RDKit-style count used by this crate:
@@ -782,7 +899,6 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
             if discovery_order[start_atom_id] == 0 {
                 self.find_bridge_edges_depth_first(
                     start_atom_id,
-                    None,
                     &mut time,
                     &mut discovery_order,
                     &mut lowlink,
@@ -898,9 +1014,8 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
 
             for start_atom_id in 0..atom_count {
                 if scratch.discovery_order_u32[start_atom_id] == 0 {
-                    self.mark_ring_atom_flags_depth_first_u32(
+                    self.mark_ring_atom_flags_depth_first(
                         start_atom_id,
-                        None,
                         &mut time,
                         &mut scratch.discovery_order_u32,
                         &mut scratch.lowlink_u32,
@@ -919,7 +1034,6 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
                 if scratch.discovery_order[start_atom_id] == 0 {
                     self.mark_ring_atom_flags_depth_first(
                         start_atom_id,
-                        None,
                         &mut time,
                         &mut scratch.discovery_order,
                         &mut scratch.lowlink,
@@ -949,6 +1063,7 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
     /// ```
     #[must_use]
     pub fn symm_sssr_result(&self) -> SymmSssrResult {
+        stage_span!(DEBUG, "smiles.symm_sssr", atoms = self.atom_nodes.len());
         let ring_membership = self.ring_membership();
         rdkit_symm_sssr::symmetrize_sssr_with_ring_membership(self, &ring_membership)
     }
@@ -993,6 +1108,7 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
             parsed_stereo_neighbors: self.parsed_stereo_neighbors.clone(),
             implicit_hydrogen_cache: self.implicit_hydrogen_cache.clone(),
             kekulization_source: self.kekulization_source.clone(),
+            metadata: None,
             atom_policy: PhantomData,
         }
     }
@@ -1115,6 +1231,7 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
             parsed_stereo_neighbors: self.parsed_stereo_neighbors.clone(),
             implicit_hydrogen_cache: self.implicit_hydrogen_cache.clone(),
             kekulization_source: None,
+            metadata: None,
             atom_policy: PhantomData,
         }
     }
@@ -1268,7 +1385,6 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
             if discovery_order[start_atom_id] == 0 {
                 self.find_bridge_keys_depth_first(
                     start_atom_id,
-                    None,
                     &mut time,
                     &mut discovery_order,
                     &mut lowlink,
@@ -1355,156 +1471,148 @@ impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
         (u64::from(node_a) << 32) | u64::from(node_b)
     }
 
-    fn find_bridge_keys_depth_first(
+    /// Walks the component of `root_atom_id` depth first, numbering atoms in
+    /// `discovery_order` and computing their `lowlink`.
+    ///
+    /// `on_edge(edge, atom, neighbor, discovery_order, lowlink)` runs for a
+    /// tree edge once the subtree of `neighbor` is finished, and for every
+    /// other edge reaching an atom that was already visited. The walk keeps
+    /// its own stack, so chains of any length cannot overflow the call stack.
+    fn lowlink_depth_first<T>(
         &self,
-        atom_id: usize,
-        parent_atom_id: Option<usize>,
-        time: &mut usize,
-        discovery_order: &mut [usize],
-        lowlink: &mut [usize],
-        bridge_keys: &mut Vec<u64>,
-    ) {
-        *time += 1;
-        discovery_order[atom_id] = *time;
-        lowlink[atom_id] = *time;
-
-        for neighbor_atom_id in self.bond_matrix.sparse_row(atom_id) {
-            if discovery_order[neighbor_atom_id] == 0 {
-                self.find_bridge_keys_depth_first(
+        root_atom_id: usize,
+        time: &mut T,
+        discovery_order: &mut [T],
+        lowlink: &mut [T],
+        mut on_edge: impl FnMut(DepthFirstEdge, usize, usize, &[T], &[T]),
+    ) where
+        T: Copy + Ord + Default + AddAssign + From<u8>,
+    {
+        *time += T::from(1);
+        discovery_order[root_atom_id] = *time;
+        lowlink[root_atom_id] = *time;
+        let mut stack = vec![(root_atom_id, None, self.bond_matrix.sparse_row(root_atom_id))];
+
+        while let Some((atom_id, parent_atom_id, neighbors)) = stack.last_mut() {
+            let (atom_id, parent_atom_id) = (*atom_id, *parent_atom_id);
+            let Some(neighbor_atom_id) = neighbors.next() else {
+                stack.pop();
+                if let Some(parent_atom_id) = parent_atom_id {
+                    lowlink[parent_atom_id] = lowlink[parent_atom_id].min(lowlink[atom_id]);
+                    on_edge(
+                        DepthFirstEdge::Tree,
+                        parent_atom_id,
+                        atom_id,
+                        discovery_order,
+                        lowlink,
+                    );
+                }
+                continue;
+            };
+            if discovery_order[neighbor_atom_id] == T::default() {
+                *time += T::from(1);
+                discovery_order[neighbor_atom_id] = *time;
+                lowlink[neighbor_atom_id] = *time;
+                stack.push((
                     neighbor_atom_id,
                     Some(atom_id),
-                    time,
-                    discovery_order,
-                    lowlink,
-                    bridge_keys,
-                );
-                lowlink[atom_id] = lowlink[atom_id].min(lowlink[neighbor_atom_id]);
-                if lowlink[neighbor_atom_id] > discovery_order[atom_id] {
-                    let (row, column) = edge_key(atom_id, neighbor_atom_id);
-                    bridge_keys.push(Self::packed_edge_key(row, column));
-                }
+                    self.bond_matrix.sparse_row(neighbor_atom_id),
+                ));
             } else if parent_atom_id != Some(neighbor_atom_id) {
                 lowlink[atom_id] = lowlink[atom_id].min(discovery_order[neighbor_atom_id]);
+                on_edge(DepthFirstEdge::Back, atom_id, neighbor_atom_id, discovery_order, lowlink);
             }
         }
     }
 
-    fn find_bridge_edges_depth_first(
+    fn find_bridge_keys_depth_first(
         &self,
-        atom_id: usize,
-        parent_atom_id: Option<usize>,
+        root_atom_id: usize,
         time: &mut usize,
         discovery_order: &mut [usize],
         lowlink: &mut [usize],
-        bridge_edges: &mut Vec<[usize; 2]>,
+        bridge_keys: &mut Vec<u64>,
     ) {
-        *time += 1;
-        discovery_order[atom_id] = *time;
-        lowlink[atom_id] = *time;
-
-        for neighbor_atom_id in self.bond_matrix.sparse_row(atom_id) {
-            if discovery_order[neighbor_atom_id] == 0 {
-                self.find_bridge_edges_depth_first(
-                    neighbor_atom_id,
-                    Some(atom_id),
-                    time,
-                    discovery_order,
-                    lowlink,
-                    bridge_edges,
-                );
-                lowlink[atom_id] = lowlink[atom_id].min(lowlink[neighbor_atom_id]);
-                if lowlink[neighbor_atom_id] > discovery_order[atom_id] {
-                    bridge_edges.push(if atom_id < neighbor_atom_id {
-                        [atom_id, neighbor_atom_id]
-                    } else {
-                        [neighbor_atom_id, atom_id]
-                    });
+        self.lowlink_depth_first(
+            root_atom_id,
+            time,
+            discovery_order,
+            lowlink,
+            |edge, atom_id, neighbor_atom_id, discovery_order, lowlink| {
+                if edge == DepthFirstEdge::Tree
+                    && lowlink[neighbor_atom_id] > discovery_order[atom_id]
+                {
+                    let (row, column) = edge_key(atom_id, neighbor_atom_id);
+                    bridge_keys.push(Self::packed_edge_key(row, column));
                 }
-            } else if parent_atom_id != Some(neighbor_atom_id) {
-                lowlink[atom_id] = lowlink[atom_id].min(discovery_order[neighbor_atom_id]);
-            }
-        }
+            },
+        );
     }
 
-    fn mark_ring_atom_flags_depth_first(
+    fn find_bridge_edges_depth_first(
         &self,
-        atom_id: usize,
-        parent_atom_id: Option<usize>,
+        root_atom_id: usize,
         time: &mut usize,
         discovery_order: &mut [usize],
         lowlink: &mut [usize],
-        atom_flags: &mut [bool],
+        bridge_edges: &mut Vec<[usize; 2]>,
     ) {
-        *time += 1;
-        discovery_order[atom_id] = *time;
-        lowlink[atom_id] = *time;
-
-        for neighbor_atom_id in self.bond_matrix.sparse_row(atom_id) {
-            if discovery_order[neighbor_atom_id] == 0 {
-                self.mark_ring_atom_flags_depth_first(
-                    neighbor_atom_id,
-                    Some(atom_id),
-                    time,
-                    discovery_order,
-                    lowlink,
-                    atom_flags,
-                );
-                lowlink[atom_id] = lowlink[atom_id].min(lowlink[neighbor_atom_id]);
-                if lowlink[neighbor_atom_id] <= discovery_order[atom_id] {
-                    atom_flags[atom_id] = true;
-                    atom_flags[neighbor_atom_id] = true;
-                }
-            } else if parent_atom_id != Some(neighbor_atom_id) {
-                let neighbor_discovery_order = discovery_order[neighbor_atom_id];
-                lowlink[atom_id] = lowlink[atom_id].min(neighbor_discovery_order);
-                if neighbor_discovery_order < discovery_order[atom_id] {
-                    atom_flags[atom_id] = true;
-                    atom_flags[neighbor_atom_id] = true;
+        self.lowlink_depth_first(
+            root_atom_id,
+            time,
+            discovery_order,
+            lowlink,
+            |edge, atom_id, neighbor_atom_id, discovery_order, lowlink| {
+                if edge == DepthFirstEdge::Tree
+                    && lowlink[neighbor_atom_id] > discovery_order[atom_id]
+                {
+                    let (row, column) = edge_key(atom_id, neighbor_atom_id);
+                    bridge_edges.push([row, column]);
                 }
-            }
-        }
+            },
+        );
     }
 
-    fn mark_ring_atom_flags_depth_first_u32(
+    fn mark_ring_atom_flags_depth_first<T>(
         &self,
-        atom_id: usize,
-        parent_atom_id: Option<usize>,
-        time: &mut u32,
-        discovery_order: &mut [u32],
-        lowlink: &mut [u32],
+        root_atom_id: usize,
+        time: &mut T,
+        discovery_order: &mut [T],
+        lowlink: &mut [T],
         atom_flags: &mut [bool],
-    ) {
-        *time += 1;
-        discovery_order[atom_id] = *time;
-        lowlink[atom_id] = *time;
-
-        for neighbor_atom_id in self.bond_matrix.sparse_row(atom_id) {
-            if discovery_order[neighbor_atom_id] == 0 {
-                self.mark_ring_atom_flags_depth_first_u32(
-                    neighbor_atom_id,
-                    Some(atom_id),
-                    time,
-                    discovery_order,
-                    lowlink,
-                    atom_flags,
-                );
-                lowlink[atom_id] = lowlink[atom_id].min(lowlink[neighbor_atom_id]);
-                if lowlink[neighbor_atom_id] <= discovery_order[atom_id] {
-                    atom_flags[atom_id] = true;
-                    atom_flags[neighbor_atom_id] = true;
-                }
-            } else if parent_atom_id != Some(neighbor_atom_id) {
-                let neighbor_discovery_order = discovery_order[neighbor_atom_id];
-                lowlink[atom_id] = lowlink[atom_id].min(neighbor_discovery_order);
-                if neighbor_discovery_order < discovery_order[atom_id] {
+    ) where
+        T: Copy + Ord + Default + AddAssign + From<u8>,
+    {
+        self.lowlink_depth_first(
+            root_atom_id,
+            time,
+            discovery_order,
+            lowlink,
+            |edge, atom_id, neighbor_atom_id, discovery_order, lowlink| {
+                let in_ring = match edge {
+                    DepthFirstEdge::Tree => lowlink[neighbor_atom_id] <= discovery_order[atom_id],
+                    DepthFirstEdge::Back => {
+                        discovery_order[neighbor_atom_id] < discovery_order[atom_id]
+                    }
+                };
+                if in_ring {
                     atom_flags[atom_id] = true;
                     atom_flags[neighbor_atom_id] = true;
                 }
-            }
-        }
+            },
+        );
     }
 }
 
+/// How [`Smiles::lowlink_depth_first`] reached an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DepthFirstEdge {
+    /// The edge leads to an atom first discovered through it.
+    Tree,
+    /// The edge leads to an atom that was already visited.
+    Back,
+}
+
 fn explicit_hydrogen_atom() -> Atom {
     Atom::builder().with_symbol(AtomSymbol::Element(Element::H)).build()
 }
@@ -1607,6 +1715,28 @@ impl WildcardSmiles {
         self.inner.edge_count_for_node(id)
     }
 
+    /// Iterates the neighbors of the provided atom id together with the bond
+    /// reaching each of them.
+    #[inline]
+    pub fn neighbors_with_bonds(&self, id: usize) -> impl Iterator<Item = (usize, BondEdge)> + '_ {
+        self.inner.neighbors_with_bonds(id)
+    }
+
+    /// Returns the number of atoms bonded to the provided atom id.
+    #[inline]
+    #[must_use]
+    pub fn degree(&self, id: usize) -> usize {
+        self.inner.degree(id)
+    }
+
+    /// Returns the number of non-hydrogen atoms bonded to the provided atom
+    /// id.
+    #[inline]
+    #[must_use]
+    pub fn heavy_degree(&self, id: usize) -> usize {
+        self.inner.heavy_degree(id)
+    }
+
     /// Returns the connectivity count for the provided atom id.
     #[inline]
     #[must_use]
@@ -1621,6 +1751,57 @@ impl WildcardSmiles {
         self.inner.total_valence(id)
     }
 
+    /// Returns a copy without chirality tags or directional bonds.
+    #[inline]
+    #[must_use]
+    pub fn strip_stereo(&self) -> Self {
+        Self::from_inner(self.inner.strip_stereo())
+    }
+
+    /// Returns a copy without isotope mass numbers.
+    #[inline]
+    #[must_use]
+    pub fn strip_isotopes(&self) -> Self {
+        Self::from_inner(self.inner.strip_isotopes())
+    }
+
+    /// Returns whether `self` and `other` are the same structure up to
+    /// isotope labels, atom classes and atom order.
+    #[inline]
+    #[must_use]
+    pub fn is_same_ignoring_isotopes(&self, other: &Self) -> bool {
+        self.inner.is_same_ignoring_isotopes(&other.inner)
+    }
+
+    /// Returns whether `self` and `other` have the same constitution,
+    /// whatever their stereochemistry, atom classes or atom order.
+    #[inline]
+    #[must_use]
+    pub fn is_same_constitution(&self, other: &Self) -> bool {
+        self.inner.is_same_constitution(&other.inner)
+    }
+
+    /// Returns whether `self` and `other` are the same graph up to atom
+    /// order, even where canonical labeling breaks symmetric ties
+    /// differently, or [`None`] if the atom mapping search ran out of budget,
+    /// see [`Smiles::is_isomorphic_to`].
+    #[inline]
+    #[must_use]
+    pub fn is_isomorphic_to(&self, other: &Self) -> Option<bool> {
+        self.inner.is_isomorphic_to(&other.inner)
+    }
+
+    /// Checks that no atom exceeds the largest valence `model` allows for its
+    /// element at its formal charge.
+    ///
+    /// # Errors
+    /// Returns a [`ValenceError`] for the first atom above its maximum
+    /// valence.
+    #[inline]
+    pub fn validate_valences(&self, model: &ValenceModel) -> Result<(), ValenceError> {
+        self.inner.validate_valences(model)
+    }
+
     /// Returns the RDKit-style SMARTS total valence for the provided atom id
     /// under the supplied aromaticity assignment.
     #[inline]
@@ -1737,6 +1918,15 @@ impl WildcardSmiles {
         Self::from_inner(self.inner.canonicalize())
     }
 
+    /// Returns the graph rewritten into canonical node order, together with
+    /// the id in `self` of each of its atoms.
+    #[inline]
+    #[must_use]
+    pub fn canonicalize_with_provenance(&self) -> (Self, Vec<usize>) {
+        let (canonical, provenance) = self.inner.canonicalize_with_provenance();
+        (Self::from_inner(canonical), provenance)
+    }
+
     /// Returns a graph with directional single bonds collapsed to ordinary
     /// single bonds.
     #[inline]
@@ -1789,6 +1979,16 @@ impl WildcardSmiles {
         self.inner.kekulize_standalone().map(Self::from_inner)
     }
 
+    /// Returns a localized Kekule form of the current graph, leaving the
+    /// aromatic systems that cannot be localized as they are and reporting
+    /// them as a warning.
+    #[inline]
+    #[must_use]
+    pub fn kekulize_lenient(&self, mode: KekulizationMode) -> (Self, Option<KekulizationError>) {
+        let (kekulized, warning) = self.inner.kekulize_lenient(mode);
+        (Self::from_inner(kekulized), warning)
+    }
+
     /// Returns a new graph with the provided aromaticity assignment applied.
     ///
     /// # Errors
@@ -2010,6 +2210,36 @@ mod tests {
         let _ = smiles.edge_count_for_node(99);
     }
 
+    #[test]
+    fn neighbors_with_bonds_and_degrees_agree_with_edges() {
+        let smiles: Smiles = "[H]C([H])(O)C=C".parse().expect("valid SMILES");
+        let neighbors = smiles.neighbors_with_bonds(1).collect::<Vec<_>>();
+        assert_eq!(
+            neighbors.iter().map(|&(neighbor, _)| neighbor).collect::<Vec<_>>(),
+            vec![0, 2, 3, 4]
+        );
+        for (neighbor, edge) in neighbors {
+            assert_eq!(edge.source(), 1);
+            assert_eq!(edge.target(), neighbor);
+            assert_eq!(
+                Some(edge.bond()),
+                smiles.edge_for_node_pair((1, neighbor)).map(BondEdge::bond)
+            );
+        }
+        assert_eq!(smiles.degree(1), 4);
+        assert_eq!(smiles.heavy_degree(1), 2);
+        assert_eq!(smiles.heavy_degree(0), 1);
+        assert_eq!(
+            smiles.neighbors_with_bonds(5).map(|(_, edge)| edge.bond()).collect::<Vec<_>>(),
+            vec![Bond::Double]
+        );
+
+        let wildcard: WildcardSmiles = "*C(*)[H]".parse().expect("valid SMILES");
+        assert_eq!(wildcard.degree(1), 3);
+        assert_eq!(wildcard.heavy_degree(1), 2);
+        assert_eq!(wildcard.neighbors_with_bonds(0).count(), 1);
+    }
+
     #[test]
     fn connectivity_count_and_total_valence_follow_rdkit_style_local_counts() {
         let alcohol: Smiles = "CO".parse().expect("valid SMILES");
@@ -3249,6 +3479,52 @@ mod tests {
         assert!(!assignment.contains_edge(4, 8));
     }
 
+    const ALL_AROMATICITY_POLICIES: [AromaticityPolicy; 3] = [
+        AromaticityPolicy::RdkitDefault,
+        AromaticityPolicy::RdkitSimple,
+        AromaticityPolicy::RdkitMdl,
+    ];
+
+    #[test]
+    fn exocyclic_double_bonds_donate_by_their_partner_atom() {
+        for source in ["O=C1C=CC(=O)C=C1", "O=C1C=CC=CC1=O"] {
+            let smiles: Smiles = source.parse().unwrap();
+            for policy in ALL_AROMATICITY_POLICIES {
+                let assignment = smiles.aromaticity_assignment_for(policy);
+                assert_eq!(assignment.status(), AromaticityStatus::Complete, "{source}");
+                assert!(assignment.atom_ids().is_empty(), "{source} {policy:?}: {assignment:?}");
+            }
+        }
+
+        let xylylene: Smiles = "C=C1C=CC(=C)C=C1".parse().unwrap();
+        for policy in ALL_AROMATICITY_POLICIES {
+            let assignment = xylylene.aromaticity_assignment_for(policy);
+            let expected: &[usize] =
+                if policy == AromaticityPolicy::RdkitMdl { &[] } else { &[1, 2, 3, 4, 6, 7] };
+            assert_eq!(assignment.status(), AromaticityStatus::Complete, "{policy:?}");
+            assert_eq!(assignment.atom_ids(), expected, "{policy:?}: {assignment:?}");
+        }
+
+        let pyridone: Smiles = "O=C1C=CC=CN1".parse().unwrap();
+        assert_eq!(pyridone.aromaticity_assignment().atom_ids(), &[1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn kekule_azulene_perimeter_follows_each_policy() {
+        let smiles: Smiles = "C1=CC=C2C=CC=C2C=C1".parse().unwrap();
+        for policy in ALL_AROMATICITY_POLICIES {
+            let assignment = smiles.aromaticity_assignment_for(policy);
+            assert_eq!(assignment.status(), AromaticityStatus::Complete, "{policy:?}");
+            if policy == AromaticityPolicy::RdkitSimple {
+                assert!(assignment.atom_ids().is_empty(), "{policy:?}: {assignment:?}");
+            } else {
+                assert_eq!(assignment.atom_ids(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9], "{policy:?}");
+                assert_eq!(assignment.bond_edges().len(), 10, "{policy:?}");
+                assert!(!assignment.contains_edge(3, 7), "{policy:?}");
+            }
+        }
+    }
+
     #[test]
     fn aromaticity_assignment_perceives_kekule_indole() {
         let smiles: Smiles = "C1=CC=C2[NH]C=CC2=C1".parse().unwrap();
@@ -5,7 +5,9 @@ use alloc::{
 use core::str::FromStr;
 
 use elements_rs::{Element, Isotope};
-use molecular_formulas::{ChargeLike, ChemicalFormula, CountLike};
+use molecular_formulas::{
+    ChargeLike, ChargedMolecularFormula, ChemicalFormula, CountLike, MolecularFormula,
+};
 use thiserror::Error;
 
 use super::{Smiles, SmilesAtomPolicy, WildcardSmiles};
@@ -143,6 +145,67 @@ where
     }
 }
 
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns whether the graph has the elemental composition and net charge
+    /// of `formula`, such as one read from a formula column next to the
+    /// SMILES; a mismatch usually means the SMILES was truncated or
+    /// corrupted.
+    ///
+    /// Only element counts are compared, so isotope labels count towards
+    /// their element and the split of `formula` into components is ignored.
+    /// Graphs with wildcard atoms never match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use molecular_formulas::prelude::ChemicalFormula;
+    /// use smiles_parser::Smiles;
+    ///
+    /// let salt = ChemicalFormula::<u32, i32>::try_from("C2H3NaO2")?;
+    /// assert!("CC(=O)[O-].[Na+]".parse::<Smiles>()?.matches_formula(&salt));
+    /// assert!(!"CC(=O)O.[Na+]".parse::<Smiles>()?.matches_formula(&salt));
+    ///
+    /// let acetate = ChemicalFormula::<u32, i32>::try_from("C2H3O2-")?;
+    /// assert!("CC(=O)[O-]".parse::<Smiles>()?.matches_formula(&acetate));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn matches_formula(&self, formula: &ChemicalFormula<u32, i32>) -> bool {
+        let mut counts = Vec::<(Element, u32)>::new();
+        let mut charge = 0_i32;
+        for (atom_id, atom) in self.nodes().iter().enumerate() {
+            let Some(element) = atom.element() else {
+                return false;
+            };
+            let hydrogens =
+                u32::from(atom.hydrogen_count()) + u32::from(self.implicit_hydrogen_count(atom_id));
+            for (element, count) in [(element, 1), (Element::H, hydrogens)] {
+                match counts.iter_mut().find(|(counted, _)| *counted == element) {
+                    Some((_, total)) => *total += count,
+                    None => counts.push((element, count)),
+                }
+            }
+            charge += i32::from(atom.charge_value());
+        }
+        counts.retain(|&(_, count)| count != 0);
+        counts.sort_unstable_by_key(|&(element, _)| u8::from(element));
+        counts == element_counts(formula)
+            && (formula.charge() - f64::from(charge)).abs() < f64::EPSILON
+    }
+}
+
+/// Returns the elements of `formula` with a non-zero count, isotopes
+/// included, ordered by atomic number.
+pub(crate) fn element_counts(formula: &ChemicalFormula<u32, i32>) -> Vec<(Element, u32)> {
+    (1..=u8::MAX)
+        .map_while(|atomic_number| Element::try_from(atomic_number).ok())
+        .filter_map(|element| {
+            let count = formula.count_of_element::<u32>(element).ok()?;
+            (count != 0).then_some((element, count))
+        })
+        .collect()
+}
+
 fn strict_smiles_formula_string(smiles: &Smiles) -> String {
     smiles_formula_string(smiles).unwrap_or_else(|error| {
         match error {
@@ -264,8 +327,6 @@ fn write_charge(target: &mut String, charge: i32) {
 mod tests {
     use alloc::string::ToString;
 
-    use molecular_formulas::{ChargedMolecularFormula, MolecularFormula};
-
     use super::*;
 
     type TestFormula = ChemicalFormula<u32, i32>;
@@ -317,6 +378,33 @@ mod tests {
         assert_eq!(formula, rdkit_formula);
     }
 
+    #[test]
+    fn graphs_match_their_formula() {
+        for (smiles, formula) in [
+            ("c1ccccc1", "C6H6"),
+            ("[13CH3][NH3+]", "CH6N+"),
+            ("[Na+].[Cl-]", "ClNa"),
+            ("[Na+].[Cl-]", "Na+.Cl-"),
+            ("[2H]C([2H])([2H])[2H]", "CH4"),
+            ("C[N+](C)(C)C.[Br-]", "C4H12BrN"),
+            ("[O-]S(=O)(=O)[O-]", "O4S-2"),
+        ] {
+            let graph: Smiles = smiles.parse().unwrap();
+            let formula = TestFormula::try_from(formula).unwrap();
+            assert!(graph.matches_formula(&formula), "{smiles} vs {formula}");
+        }
+        let ethanol: Smiles = "CCO".parse().unwrap();
+        assert!(!ethanol.matches_formula(&TestFormula::try_from("C2H5O").unwrap()));
+        assert!(!ethanol.matches_formula(&TestFormula::try_from("C2H6O+").unwrap()));
+        assert!(!ethanol.matches_formula(&TestFormula::try_from("C2H6OS").unwrap()));
+    }
+
+    #[test]
+    fn wildcard_graphs_never_match_a_formula() {
+        let graph: WildcardSmiles = "*C".parse().unwrap();
+        assert!(!graph.inner().matches_formula(&TestFormula::try_from("CH3").unwrap()));
+    }
+
     #[test]
     fn formula_conversion_rejects_wildcards() {
         let smiles: WildcardSmiles = "*".parse().unwrap();
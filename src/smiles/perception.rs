@@ -0,0 +1,224 @@
+//! Per-atom perception results behind one lazily filled view.
+
+use core::cell::OnceCell;
+
+use elements_rs::{ChargedValences, Element};
+
+use super::{AromaticityAssignment, ConcreteAtoms, RingAtomMembership, Smiles, SmilesAtomPolicy};
+
+/// Hybridization of an atom, inferred from its bonds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Hybridization {
+    /// Two or more pi bonds, as in alkynes, nitriles and the center of allenes
+    Sp,
+    /// One pi bond, or membership in an aromatic system
+    Sp2,
+    /// Single bonds only
+    Sp3,
+}
+
+/// Perception results for the atoms of a [`Smiles`] graph, read through
+/// accessors instead of parallel per-atom arrays.
+///
+/// Each result is computed for the whole graph the first time one of its
+/// accessors is called and reused afterwards, so a view only pays for what
+/// it is asked. Build one with [`Smiles::perception`].
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::prelude::{Hybridization, Smiles};
+///
+/// let smiles: Smiles = "C1=CC=CC=C1C#N".parse()?;
+/// let perception = smiles.perception();
+/// assert!(perception.aromatic(0));
+/// assert!(perception.in_ring(5) && !perception.in_ring(6));
+/// assert_eq!(perception.hybridization(6), Some(Hybridization::Sp));
+/// assert_eq!(perception.total_hydrogens(0), 1);
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[derive(Debug)]
+pub struct AtomPerception<'mol, AtomPolicy = ConcreteAtoms> {
+    smiles: &'mol Smiles<AtomPolicy>,
+    aromaticity: OnceCell<AromaticityAssignment>,
+    rings: OnceCell<RingAtomMembership>,
+}
+
+impl<'mol, AtomPolicy: SmilesAtomPolicy> AtomPerception<'mol, AtomPolicy> {
+    /// Returns the graph the results are perceived on.
+    #[inline]
+    #[must_use]
+    pub fn smiles(&self) -> &'mol Smiles<AtomPolicy> {
+        self.smiles
+    }
+
+    /// Returns the number of implicit hydrogens on atom `id`.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in the graph.
+    #[inline]
+    #[must_use]
+    pub fn implicit_hydrogens(&self, id: usize) -> u8 {
+        self.smiles.implicit_hydrogen_count(id)
+    }
+
+    /// Returns the number of hydrogens on atom `id`, written in its bracket
+    /// or implicit, not counting hydrogen atoms of the graph.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in the graph.
+    #[inline]
+    #[must_use]
+    pub fn total_hydrogens(&self, id: usize) -> u8 {
+        self.smiles.nodes()[id].hydrogen_count().saturating_add(self.implicit_hydrogens(id))
+    }
+
+    /// Returns whether atom `id` is aromatic under the default aromaticity
+    /// model, whatever case it was written in.
+    #[inline]
+    #[must_use]
+    pub fn aromatic(&self, id: usize) -> bool {
+        self.aromaticity.get_or_init(|| self.smiles.aromaticity_assignment()).contains_atom(id)
+    }
+
+    /// Returns whether atom `id` belongs to at least one ring.
+    #[inline]
+    #[must_use]
+    pub fn in_ring(&self, id: usize) -> bool {
+        self.rings.get_or_init(|| self.smiles.ring_atom_membership()).contains_atom(id)
+    }
+
+    /// Returns the hybridization of atom `id`, or `None` for hydrogen,
+    /// wildcard atoms and hypervalent centers.
+    ///
+    /// Aromatic atoms are [`Hybridization::Sp2`]; other atoms are classified
+    /// by their number of pi bonds. Lone pairs conjugated with a neighbor, as
+    /// on an amide nitrogen, are not taken into account. Counting pi bonds
+    /// only holds for atoms at the lowest valence of their element and
+    /// charge, so hypervalent centers such as the sulfur of a sulfone, the
+    /// phosphorus of a phosphate or a nitro nitrogen written `N(=O)=O` have
+    /// no hybridization.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in the graph.
+    #[must_use]
+    pub fn hybridization(&self, id: usize) -> Option<Hybridization> {
+        if matches!(self.smiles.nodes()[id].element(), None | Some(Element::H)) {
+            return None;
+        }
+        if self.aromatic(id) {
+            return Some(Hybridization::Sp2);
+        }
+        if self.is_hypervalent(id) {
+            return None;
+        }
+        let pi_bonds = self
+            .smiles
+            .edges_for_node(id)
+            .map(|edge| edge.descriptor().order().unwrap_or(1).saturating_sub(1))
+            .fold(0_u8, u8::saturating_add);
+        Some(match pi_bonds {
+            0 => Hybridization::Sp3,
+            1 => Hybridization::Sp2,
+            _ => Hybridization::Sp,
+        })
+    }
+
+    /// Returns whether atom `id` is above the lowest valence of its element
+    /// at its charge.
+    fn is_hypervalent(&self, id: usize) -> bool {
+        let atom = self.smiles.nodes()[id];
+        atom.element()
+            .and_then(|element| {
+                element.valences_at_charge(atom.charge_value()).iter().min().copied()
+            })
+            .is_some_and(|lowest| self.smiles.total_valence(id) > lowest)
+    }
+
+    /// Returns the formal charge of atom `id`.
+    ///
+    /// # Panics
+    /// Panics if `id` is not a valid atom index in the graph.
+    #[inline]
+    #[must_use]
+    pub fn formal_charge(&self, id: usize) -> i8 {
+        self.smiles.nodes()[id].formal_charge()
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns a view of the perception results for the atoms of the graph,
+    /// each computed on first use.
+    #[inline]
+    #[must_use]
+    pub fn perception(&self) -> AtomPerception<'_, AtomPolicy> {
+        AtomPerception { smiles: self, aromaticity: OnceCell::new(), rings: OnceCell::new() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perception_classifies_each_atom() {
+        let smiles: Smiles = "[NH3+]CC(=O)[O-].C=C=C.c1ccncc1".parse().unwrap();
+        let perception = smiles.perception();
+
+        assert_eq!(perception.formal_charge(0), 1);
+        assert_eq!(perception.total_hydrogens(0), 3);
+        assert_eq!(perception.implicit_hydrogens(0), 0);
+        assert_eq!(perception.implicit_hydrogens(1), 2);
+        assert_eq!(perception.formal_charge(4), -1);
+
+        let hybridizations = (0..smiles.nodes().len())
+            .map(|id| perception.hybridization(id).unwrap())
+            .collect::<alloc::vec::Vec<_>>();
+        assert_eq!(
+            &hybridizations[..5],
+            [
+                Hybridization::Sp3,
+                Hybridization::Sp3,
+                Hybridization::Sp2,
+                Hybridization::Sp2,
+                Hybridization::Sp3
+            ]
+        );
+        assert_eq!(
+            &hybridizations[5..8],
+            [Hybridization::Sp2, Hybridization::Sp, Hybridization::Sp2]
+        );
+        assert!(
+            hybridizations[8..].iter().all(|&hybridization| hybridization == Hybridization::Sp2)
+        );
+        assert!((8..14).all(|id| perception.aromatic(id) && perception.in_ring(id)));
+        assert!((0..8).all(|id| !perception.aromatic(id) && !perception.in_ring(id)));
+
+        let hydrogen: Smiles = "[H][H]".parse().unwrap();
+        assert_eq!(hydrogen.perception().hybridization(0), None);
+    }
+
+    #[test]
+    fn hypervalent_centers_have_no_hybridization() {
+        for (source, center) in [
+            ("CS(=O)(=O)C", 1),
+            ("CS(=O)(=O)Cl", 1),
+            ("OP(=O)(O)O", 1),
+            ("CN(=O)=O", 1),
+            ("CS(=O)C", 1),
+        ] {
+            let smiles: Smiles = source.parse().unwrap();
+            assert_eq!(smiles.perception().hybridization(center), None, "{source}");
+        }
+
+        for (source, center, expected) in [
+            ("C[N+](=O)[O-]", 1, Hybridization::Sp2),
+            ("CC(=S)C", 2, Hybridization::Sp2),
+            ("CSC", 1, Hybridization::Sp3),
+            ("CC#N", 2, Hybridization::Sp),
+        ] {
+            let smiles: Smiles = source.parse().unwrap();
+            assert_eq!(smiles.perception().hybridization(center), Some(expected), "{source}");
+        }
+    }
+}
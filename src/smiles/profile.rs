@@ -0,0 +1,198 @@
+//! Element profiles for filtering corpora by composition.
+
+use alloc::vec::Vec;
+
+use elements_rs::Element;
+
+use super::{Smiles, SmilesAtomPolicy};
+use crate::{atom::Atom, errors::SmilesError};
+
+/// Returns the bit of the element with the given atomic number.
+const fn bit(atomic_number: u8) -> u128 {
+    1 << atomic_number
+}
+
+/// A set of allowed elements.
+///
+/// Profiles are checked during parsing through
+/// [`ParserOptions::profile`](crate::ParserOptions::profile), which rejects
+/// out-of-profile atoms with
+/// [`SmilesError::ElementOutsideProfile`], or after parsing with
+/// [`ElementProfile::violations`], which only flags them. Wildcard atoms are
+/// allowed by every profile.
+///
+/// # Examples
+///
+/// ```
+/// use elements_rs::Element;
+/// use smiles_parser::prelude::{ElementProfile, ParserOptions, Smiles, SmilesError};
+///
+/// let options = ParserOptions { profile: ElementProfile::CHNOPS, ..ParserOptions::default() };
+/// assert!(Smiles::parse_with_options("NCC(=O)O", &options).is_ok());
+/// let error = Smiles::parse_with_options("CC(=O)[O-].[Na+]", &options).unwrap_err();
+/// assert_eq!(error.smiles_error(), SmilesError::ElementOutsideProfile(Element::Na));
+/// assert_eq!(error.span(), 11..16);
+///
+/// let halogenated = ElementProfile::CHNOPS.with(Element::Cl);
+/// let graph: Smiles = "ClCCBr".parse()?;
+/// assert_eq!(halogenated.violations(&graph), [3]);
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ElementProfile {
+    allowed: u128,
+}
+
+impl ElementProfile {
+    /// Allows every element.
+    pub const ALL: Self = Self { allowed: u128::MAX };
+    /// Allows no element.
+    pub const NONE: Self = Self { allowed: 0 };
+    /// Allows carbon, hydrogen, nitrogen, oxygen, phosphorus and sulfur.
+    pub const CHNOPS: Self =
+        Self { allowed: bit(1) | bit(6) | bit(7) | bit(8) | bit(15) | bit(16) };
+    /// Allows the elements found in drug-like organic molecules: CHNOPS plus
+    /// boron, fluorine, silicon, chlorine, selenium, bromine and iodine.
+    pub const DRUGLIKE_ORGANIC: Self = Self {
+        allowed: Self::CHNOPS.allowed
+            | bit(5)
+            | bit(9)
+            | bit(14)
+            | bit(17)
+            | bit(34)
+            | bit(35)
+            | bit(53),
+    };
+
+    /// Returns whether the profile allows `element`.
+    #[inline]
+    #[must_use]
+    pub fn allows(self, element: Element) -> bool {
+        self.allowed & bit(u8::from(element)) != 0
+    }
+
+    /// Returns the profile that also allows `element`.
+    #[inline]
+    #[must_use]
+    pub fn with(self, element: Element) -> Self {
+        Self { allowed: self.allowed | bit(u8::from(element)) }
+    }
+
+    /// Returns the profile that no longer allows `element`.
+    #[inline]
+    #[must_use]
+    pub fn without(self, element: Element) -> Self {
+        Self { allowed: self.allowed & !bit(u8::from(element)) }
+    }
+
+    /// Returns the profile allowing the elements allowed by either profile.
+    #[inline]
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self { allowed: self.allowed | other.allowed }
+    }
+
+    /// Returns the profile allowing the elements allowed by both profiles.
+    #[inline]
+    #[must_use]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self { allowed: self.allowed & other.allowed }
+    }
+
+    /// Returns the ids of the atoms of `smiles` whose element the profile does
+    /// not allow.
+    #[must_use]
+    pub fn violations<AtomPolicy: SmilesAtomPolicy>(
+        self,
+        smiles: &Smiles<AtomPolicy>,
+    ) -> Vec<usize> {
+        smiles
+            .nodes()
+            .iter()
+            .enumerate()
+            .filter(|(_, atom)| self.check_atom(atom).is_err())
+            .map(|(atom_id, _)| atom_id)
+            .collect()
+    }
+
+    /// Returns an error if the profile does not allow the element of `atom`.
+    pub(crate) fn check_atom(self, atom: &Atom) -> Result<(), SmilesError> {
+        match atom.element() {
+            Some(element) if !self.allows(element) => {
+                Err(SmilesError::ElementOutsideProfile(element))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for ElementProfile {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl FromIterator<Element> for ElementProfile {
+    fn from_iter<I: IntoIterator<Item = Element>>(elements: I) -> Self {
+        elements.into_iter().fold(Self::NONE, Self::with)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smiles::{ParserOptions, WildcardSmiles};
+
+    #[test]
+    fn builtin_profiles_allow_their_elements() {
+        for element in [Element::C, Element::H, Element::N, Element::O, Element::P, Element::S] {
+            assert!(ElementProfile::CHNOPS.allows(element), "{element}");
+            assert!(ElementProfile::DRUGLIKE_ORGANIC.allows(element), "{element}");
+        }
+        for element in
+            [Element::B, Element::F, Element::Si, Element::Cl, Element::Se, Element::Br, Element::I]
+        {
+            assert!(!ElementProfile::CHNOPS.allows(element), "{element}");
+            assert!(ElementProfile::DRUGLIKE_ORGANIC.allows(element), "{element}");
+        }
+        for element in [Element::Na, Element::Fe, Element::Og] {
+            assert!(!ElementProfile::DRUGLIKE_ORGANIC.allows(element), "{element}");
+            assert!(ElementProfile::ALL.allows(element), "{element}");
+            assert!(!ElementProfile::NONE.allows(element), "{element}");
+        }
+        assert_eq!(ElementProfile::default(), ElementProfile::ALL);
+    }
+
+    #[test]
+    fn profiles_compose() {
+        let chno = [Element::C, Element::H, Element::N, Element::O]
+            .into_iter()
+            .collect::<ElementProfile>();
+        assert_eq!(ElementProfile::CHNOPS.without(Element::P).without(Element::S), chno);
+        assert_eq!(chno.union(ElementProfile::CHNOPS), ElementProfile::CHNOPS);
+        assert_eq!(chno.intersection(ElementProfile::CHNOPS), chno);
+        assert_eq!(ElementProfile::CHNOPS.intersection(ElementProfile::NONE), ElementProfile::NONE);
+    }
+
+    #[test]
+    fn parsing_rejects_and_violations_flag_out_of_profile_atoms() {
+        let options =
+            ParserOptions { profile: ElementProfile::DRUGLIKE_ORGANIC, ..ParserOptions::default() };
+        assert!(Smiles::parse_with_options("FC(F)(F)c1ccc(Br)cc1", &options).is_ok());
+        let error = Smiles::parse_with_options("C[Fe]C", &options).unwrap_err();
+        assert_eq!(error.smiles_error(), SmilesError::ElementOutsideProfile(Element::Fe));
+        assert_eq!(error.span(), 1..5);
+
+        let graph: Smiles = "[Na+].[Cl-].O".parse().unwrap();
+        assert_eq!(ElementProfile::CHNOPS.violations(&graph), [0, 1]);
+        assert_eq!(ElementProfile::DRUGLIKE_ORGANIC.violations(&graph), [0]);
+        assert!(ElementProfile::ALL.violations(&graph).is_empty());
+    }
+
+    #[test]
+    fn wildcards_are_always_allowed() {
+        let options = ParserOptions { profile: ElementProfile::NONE, ..ParserOptions::default() };
+        let graph = WildcardSmiles::parse_with_options("*.*", &options).unwrap();
+        assert_eq!(graph.nodes().len(), 2);
+    }
+}
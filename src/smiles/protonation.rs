@@ -0,0 +1,301 @@
+//! Rule-based enumeration of protonation states.
+
+use alloc::vec::Vec;
+
+use elements_rs::Element;
+use geometric_traits::traits::SparseMatrix2D;
+
+use super::{Smiles, SmilesAtomPolicy};
+use crate::{
+    atom::{Atom, bracketed::charge::Charge},
+    bond::Bond,
+};
+
+/// Approximate pKa of aliphatic carboxylic acids.
+const CARBOXYLIC_ACID_PKA: f64 = 4.2;
+/// Approximate pKa of phenols.
+const PHENOL_PKA: f64 = 10.0;
+/// Approximate pKa of the conjugate acids of aliphatic amines.
+const AMINE_PKA: f64 = 10.6;
+
+/// The pH and tolerances used by [`Smiles::enumerate_protonation_states`].
+///
+/// A site whose pKa lies further than `window` from `ph` takes the state that
+/// dominates at that pH; a site within the window is enumerated in both
+/// states.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhModel {
+    /// The pH of the solution.
+    pub ph: f64,
+    /// How close the pH may lie to a site's pKa for both states to be kept.
+    pub window: f64,
+    /// The maximum number of states returned. Zero is read as one, so the
+    /// first state is always returned.
+    pub max_states: usize,
+}
+
+impl Default for PhModel {
+    fn default() -> Self {
+        Self { ph: 7.0, window: 1.0, max_states: 64 }
+    }
+}
+
+/// An ionizable atom and the pKa class it belongs to.
+#[derive(Debug, Clone, Copy)]
+struct ProtonationSite {
+    atom_id: usize,
+    /// Hydrogens of the atom in its conjugate-acid state.
+    acid_hydrogens: u8,
+    /// Charge of the atom in its conjugate-acid state.
+    acid_charge: i8,
+    pka: f64,
+}
+
+impl ProtonationSite {
+    /// Returns the atom in its conjugate-acid state (`protonated`) or in its
+    /// conjugate-base state.
+    fn atom(self, atom: Atom, protonated: bool) -> Atom {
+        let (hydrogens, charge) = if protonated {
+            (self.acid_hydrogens, self.acid_charge)
+        } else {
+            (self.acid_hydrogens - 1, self.acid_charge - 1)
+        };
+        Atom::new_bracket(
+            atom.symbol(),
+            atom.isotope_mass_number(),
+            atom.aromatic(),
+            hydrogens,
+            Charge::try_new(charge)
+                .unwrap_or_else(|_| unreachable!("protonation sites carry charges of at most one")),
+            atom.class(),
+            atom.chirality(),
+        )
+    }
+
+    /// Returns the states the site may take at the model's pH, most
+    /// protonated first.
+    fn states(self, model: &PhModel) -> &'static [bool] {
+        if (model.ph - self.pka).abs() <= model.window {
+            &[true, false]
+        } else if model.ph < self.pka {
+            &[true]
+        } else {
+            &[false]
+        }
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns the likely protonation states of the graph at the pH of
+    /// `model`.
+    ///
+    /// Three site classes are recognised: carboxylic acids (pKa 4.2), phenols
+    /// (pKa 10.0) and aliphatic amines (conjugate acid pKa 10.6). Sites are
+    /// recognised in either state, so charged inputs are neutralised where
+    /// the pH calls for it. Amides, anilines and other conjugated nitrogens
+    /// are left untouched, and a graph without sites yields itself. At most
+    /// [`PhModel::max_states`] states are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::{PhModel, Smiles};
+    ///
+    /// let glycine: Smiles = "NCC(=O)O".parse()?;
+    /// let states = glycine.enumerate_protonation_states(&PhModel::default());
+    /// assert_eq!(states.len(), 1);
+    /// assert_eq!(states[0].render(), "[NH3+]CC([O-])=O");
+    ///
+    /// let acidic = PhModel { ph: 4.0, ..PhModel::default() };
+    /// assert_eq!(glycine.enumerate_protonation_states(&acidic).len(), 2);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn enumerate_protonation_states(&self, model: &PhModel) -> Vec<Self> {
+        let sites = (0..self.atom_nodes.len())
+            .filter_map(|atom_id| self.protonation_site(atom_id))
+            .collect::<Vec<_>>();
+        let choices = sites.iter().map(|site| site.states(model)).collect::<Vec<_>>();
+
+        let mut states = Vec::new();
+        let mut selection = vec![0_usize; sites.len()];
+        while states.len() < model.max_states.max(1) {
+            let mut atom_nodes = self.atom_nodes.clone();
+            for ((site, site_states), &choice) in sites.iter().zip(&choices).zip(&selection) {
+                atom_nodes[site.atom_id] =
+                    site.atom(self.atom_nodes[site.atom_id], site_states[choice]);
+            }
+            states.push(
+                Self::from_bond_matrix_parts_with_parsed_stereo_and_source(
+                    atom_nodes,
+                    self.bond_matrix.clone(),
+                    self.parsed_stereo_neighbors.clone(),
+                    None,
+                )
+                .canonicalization_spelling_normal_form(),
+            );
+
+            let Some(position) = (0..selection.len())
+                .rev()
+                .find(|&index| selection[index] + 1 < choices[index].len())
+            else {
+                break;
+            };
+            selection[position] += 1;
+            selection[position + 1..].fill(0);
+        }
+        states
+    }
+
+    pub(super) fn total_hydrogens(&self, atom_id: usize) -> u8 {
+        self.atom_nodes[atom_id].hydrogen_count() + self.implicit_hydrogen_count(atom_id)
+    }
+
+    fn protonation_site(&self, atom_id: usize) -> Option<ProtonationSite> {
+        let atom = self.atom_nodes[atom_id];
+        if atom.aromatic() {
+            return None;
+        }
+        let neighbors = self.bond_matrix.sparse_row(atom_id).collect::<Vec<_>>();
+        if neighbors.iter().any(|&neighbor| {
+            self.atom_nodes[neighbor].element() == Some(Element::H)
+                || !self.is_single_bond(atom_id, neighbor)
+        }) {
+            return None;
+        }
+        let hydrogens = self.total_hydrogens(atom_id);
+        match (atom.element()?, atom.charge_value()) {
+            (Element::O, charge @ (0 | -1)) => {
+                let acid_hydrogens = hydrogens + u8::from(charge == -1);
+                let [neighbor] = neighbors[..] else {
+                    return None;
+                };
+                let pka = if self.is_carboxyl_carbon(neighbor, atom_id) {
+                    CARBOXYLIC_ACID_PKA
+                } else if self.is_phenol_carbon(neighbor) {
+                    PHENOL_PKA
+                } else {
+                    return None;
+                };
+                (acid_hydrogens == 1).then_some(ProtonationSite {
+                    atom_id,
+                    acid_hydrogens,
+                    acid_charge: 0,
+                    pka,
+                })
+            }
+            (Element::N, charge @ (0 | 1)) => {
+                let acid_hydrogens = hydrogens + u8::from(charge == 0);
+                let aliphatic =
+                    neighbors.iter().all(|&neighbor| self.is_saturated_carbon(neighbor));
+                (aliphatic
+                    && acid_hydrogens > 0
+                    && usize::from(acid_hydrogens) + neighbors.len() == 4)
+                    .then_some(ProtonationSite {
+                        atom_id,
+                        acid_hydrogens,
+                        acid_charge: 1,
+                        pka: AMINE_PKA,
+                    })
+            }
+            _ => None,
+        }
+    }
+
+    fn is_single_bond(&self, left: usize, right: usize) -> bool {
+        self.bond_for_node_pair((left, right))
+            .is_some_and(|bond| bond.without_direction() == Bond::Single)
+    }
+
+    /// Returns whether `carbon` is double-bonded to an oxygen other than
+    /// `hydroxyl`.
+    fn is_carboxyl_carbon(&self, carbon: usize, hydroxyl: usize) -> bool {
+        let atom = self.atom_nodes[carbon];
+        atom.element() == Some(Element::C)
+            && !atom.aromatic()
+            && self.bond_matrix.sparse_row(carbon).any(|neighbor| {
+                neighbor != hydroxyl
+                    && self.atom_nodes[neighbor].element() == Some(Element::O)
+                    && self.bond_for_node_pair((carbon, neighbor)) == Some(Bond::Double)
+            })
+    }
+
+    fn is_phenol_carbon(&self, carbon: usize) -> bool {
+        let atom = self.atom_nodes[carbon];
+        atom.element() == Some(Element::C) && atom.aromatic()
+    }
+
+    fn is_saturated_carbon(&self, carbon: usize) -> bool {
+        let atom = self.atom_nodes[carbon];
+        atom.element() == Some(Element::C)
+            && !atom.aromatic()
+            && self
+                .bond_matrix
+                .sparse_row(carbon)
+                .all(|neighbor| self.is_single_bond(carbon, neighbor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+
+    fn states(smiles: &str, ph: f64) -> Vec<String> {
+        let model = PhModel { ph, ..PhModel::default() };
+        let mut rendered = smiles
+            .parse::<Smiles>()
+            .unwrap()
+            .enumerate_protonation_states(&model)
+            .iter()
+            .map(Smiles::render)
+            .collect::<Vec<_>>();
+        rendered.sort_unstable();
+        rendered
+    }
+
+    #[test]
+    fn sites_take_the_dominant_state_away_from_their_pka() {
+        assert_eq!(states("NCC(=O)O", 7.0), ["[NH3+]CC([O-])=O"]);
+        assert_eq!(states("NCC(=O)O", 1.0), ["[NH3+]CC(O)=O"]);
+        assert_eq!(states("NCC(=O)O", 13.0), ["NCC([O-])=O"]);
+        assert_eq!(states("CN(C)C", 7.0), ["C[NH+](C)C"]);
+        assert_eq!(states("Oc1ccccc1", 7.0), ["Oc1ccccc1"]);
+    }
+
+    #[test]
+    fn sites_near_their_pka_are_enumerated_in_both_states() {
+        assert_eq!(states("NCC(=O)O", 4.0), ["[NH3+]CC(O)=O", "[NH3+]CC([O-])=O"]);
+        assert_eq!(states("Oc1ccccc1", 10.0), ["Oc1ccccc1", "[O-]c1ccccc1"]);
+        assert_eq!(states("OC(=O)CCC(=O)O", 4.5).len(), 4);
+    }
+
+    #[test]
+    fn charged_inputs_are_recognised() {
+        assert_eq!(states("CC(=O)[O-]", 1.0), ["CC(O)=O"]);
+        assert_eq!(states("C[NH3+]", 13.0), ["CN"]);
+    }
+
+    #[test]
+    fn conjugated_and_quaternary_nitrogens_are_not_sites() {
+        for smiles in ["CC(=O)N", "Nc1ccccc1", "C[N+](C)(C)C", "CC=NC", "c1ccncc1", "CO", "NN"] {
+            let graph: Smiles = smiles.parse().unwrap();
+            let rendered = graph.enumerate_protonation_states(&PhModel::default());
+            assert_eq!(rendered.len(), 1, "{smiles}");
+            assert_eq!(rendered[0].render(), graph.render(), "{smiles}");
+        }
+    }
+
+    #[test]
+    fn max_states_caps_the_enumeration() {
+        let model = PhModel { ph: 4.2, max_states: 3, ..PhModel::default() };
+        let graph: Smiles = "OC(=O)CC(=O)O.OC(=O)C".parse().unwrap();
+        assert_eq!(graph.enumerate_protonation_states(&model).len(), 3);
+
+        let model = PhModel { max_states: 0, ..PhModel::default() };
+        let states = graph.enumerate_protonation_states(&model);
+        assert_eq!(states.len(), 1);
+        assert_eq!(states, graph.enumerate_protonation_states(&PhModel { max_states: 1, ..model }));
+    }
+}
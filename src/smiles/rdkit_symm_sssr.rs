@@ -91,27 +91,28 @@ struct SimpleCycleBlockSearch<'a> {
 }
 
 impl SimpleCycleBlockSearch<'_> {
-    fn collect_depth_first(&mut self, atom_id: usize) -> Option<()> {
-        self.time += 1;
-        self.discovery_order[atom_id] = self.time;
-        self.lowlink[atom_id] = self.time;
+    /// Walks the component of `root_atom_id` depth first with an explicit
+    /// stack of atoms and the index of the next neighbor each one visits.
+    fn collect_depth_first(&mut self, root_atom_id: usize) -> Option<()> {
+        self.discover(root_atom_id);
+        let mut stack = vec![(root_atom_id, 0_usize)];
+
+        while let Some((atom_id, next_neighbor)) = stack.last_mut() {
+            let atom_id = *atom_id;
+            let Some(&neighbor_atom_id) = self.ring_neighbors[atom_id].get(*next_neighbor) else {
+                stack.pop();
+                if let Some(&(parent_atom_id, _)) = stack.last() {
+                    self.finish_tree_edge(parent_atom_id, atom_id)?;
+                }
+                continue;
+            };
+            *next_neighbor += 1;
 
-        for &neighbor_atom_id in &self.ring_neighbors[atom_id] {
             if self.discovery_order[neighbor_atom_id] == 0 {
                 self.parent[neighbor_atom_id] = Some(atom_id);
                 self.edge_stack.push(edge_key(atom_id, neighbor_atom_id));
-                self.collect_depth_first(neighbor_atom_id)?;
-                self.lowlink[atom_id] = self.lowlink[atom_id].min(self.lowlink[neighbor_atom_id]);
-                if self.lowlink[neighbor_atom_id] >= self.discovery_order[atom_id] {
-                    let mut component_edges = Vec::<[usize; 2]>::new();
-                    while let Some(edge) = self.edge_stack.pop() {
-                        component_edges.push(edge);
-                        if edge == edge_key(atom_id, neighbor_atom_id) {
-                            break;
-                        }
-                    }
-                    self.cycles.push(simple_cycle_from_block_edges(&component_edges)?);
-                }
+                self.discover(neighbor_atom_id);
+                stack.push((neighbor_atom_id, 0));
             } else if self.parent[atom_id] != Some(neighbor_atom_id)
                 && self.discovery_order[neighbor_atom_id] < self.discovery_order[atom_id]
             {
@@ -123,6 +124,29 @@ impl SimpleCycleBlockSearch<'_> {
 
         Some(())
     }
+
+    fn discover(&mut self, atom_id: usize) {
+        self.time += 1;
+        self.discovery_order[atom_id] = self.time;
+        self.lowlink[atom_id] = self.time;
+    }
+
+    /// Folds the finished subtree of `atom_id` into `parent_atom_id` and
+    /// emits the cycle of the block it closes, if any.
+    fn finish_tree_edge(&mut self, parent_atom_id: usize, atom_id: usize) -> Option<()> {
+        self.lowlink[parent_atom_id] = self.lowlink[parent_atom_id].min(self.lowlink[atom_id]);
+        if self.lowlink[atom_id] >= self.discovery_order[parent_atom_id] {
+            let mut component_edges = Vec::<[usize; 2]>::new();
+            while let Some(edge) = self.edge_stack.pop() {
+                component_edges.push(edge);
+                if edge == edge_key(parent_atom_id, atom_id) {
+                    break;
+                }
+            }
+            self.cycles.push(simple_cycle_from_block_edges(&component_edges)?);
+        }
+        Some(())
+    }
 }
 
 fn simple_cycle_from_block_edges(component_edges: &[[usize; 2]]) -> Option<Vec<usize>> {
@@ -518,24 +542,20 @@ impl<'a, AtomPolicy: SmilesAtomPolicy> RingSearchState<'a, AtomPolicy> {
         generation: u32,
         active_edges: &HashSet<[usize; 2]>,
     ) {
-        for edge in &ordered_incident_bonds[root] {
-            let key = edge_key(edge.source(), edge.target());
-            if !active_edges.contains(&key) {
-                continue;
-            }
-            let Some(other) = bond_edge_other(*edge, root) else {
-                continue;
-            };
-            if forb[other] != generation && atom_degrees[other] == 2 {
-                forb[other] = generation;
-                Self::mark_useless_d2s(
-                    ordered_incident_bonds,
-                    atom_degrees,
-                    other,
-                    forb,
-                    generation,
-                    active_edges,
-                );
+        let mut pending = vec![root];
+        while let Some(atom_id) = pending.pop() {
+            for edge in &ordered_incident_bonds[atom_id] {
+                let key = edge_key(edge.source(), edge.target());
+                if !active_edges.contains(&key) {
+                    continue;
+                }
+                let Some(other) = bond_edge_other(*edge, atom_id) else {
+                    continue;
+                };
+                if forb[other] != generation && atom_degrees[other] == 2 {
+                    forb[other] = generation;
+                    pending.push(other);
+                }
             }
         }
     }
@@ -1368,7 +1388,6 @@ fn fast_find_rings(
         dfs_fast_find_rings(
             ordered_incident_bonds,
             start,
-            None,
             &mut colors,
             &mut traversal,
             &mut rings,
@@ -1379,19 +1398,31 @@ fn fast_find_rings(
     rings
 }
 
+/// Walks the atoms reachable from `root` depth first, in the same order as
+/// the recursive search it mirrors, keeping an explicit stack of atoms, the
+/// atom each was reached from and the index of its next incident bond.
 fn dfs_fast_find_rings(
     ordered_incident_bonds: &[Vec<BondEdge>],
-    atom: usize,
-    from_atom: Option<usize>,
+    root: usize,
     colors: &mut [u8],
     traversal: &mut Vec<usize>,
     rings: &mut Vec<Vec<usize>>,
     seen: &mut HashSet<Vec<usize>>,
 ) {
-    colors[atom] = GRAY;
-    traversal.push(atom);
+    colors[root] = GRAY;
+    traversal.push(root);
+    let mut stack = vec![(root, None::<usize>, 0_usize)];
+
+    while let Some((atom, from_atom, next_edge)) = stack.last_mut() {
+        let (atom, from_atom) = (*atom, *from_atom);
+        let Some(&edge) = ordered_incident_bonds[atom].get(*next_edge) else {
+            colors[atom] = BLACK;
+            traversal.pop();
+            stack.pop();
+            continue;
+        };
+        *next_edge += 1;
 
-    for &edge in &ordered_incident_bonds[atom] {
         let Some(nbr) = bond_edge_other(edge, atom) else {
             continue;
         };
@@ -1399,15 +1430,9 @@ fn dfs_fast_find_rings(
             if ordered_incident_bonds[nbr].len() < 2 {
                 colors[nbr] = BLACK;
             } else {
-                dfs_fast_find_rings(
-                    ordered_incident_bonds,
-                    nbr,
-                    Some(atom),
-                    colors,
-                    traversal,
-                    rings,
-                    seen,
-                );
+                colors[nbr] = GRAY;
+                traversal.push(nbr);
+                stack.push((nbr, Some(atom), 0));
             }
         } else if colors[nbr] == GRAY
             && from_atom != Some(nbr)
@@ -1421,9 +1446,6 @@ fn dfs_fast_find_rings(
             }
         }
     }
-
-    colors[atom] = BLACK;
-    traversal.pop();
 }
 
 #[cfg(test)]
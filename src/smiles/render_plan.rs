@@ -1,6 +1,8 @@
 use alloc::vec::Vec;
+use core::cmp::Reverse;
 
-use geometric_traits::traits::SparseValuedMatrix2DRef;
+use elements_rs::Element;
+use geometric_traits::traits::{SparseMatrix2D, SparseValuedMatrix2DRef};
 
 use super::{
     Smiles, SmilesAtomPolicy,
@@ -300,6 +302,54 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
     /// component to start at `forced_root`. Other components keep their
     /// deterministically chosen roots.
     pub(crate) fn render_plan_with_root(&self, forced_root: Option<usize>) -> RenderPlan {
+        self.render_plan_with_strategy(forced_root, false)
+    }
+
+    /// Builds the plan behind `WriterOptions::pretty` output: each component
+    /// starts at its most heteroatom-rich terminal atom and branches are
+    /// written smallest first.
+    pub(crate) fn pretty_render_plan(&self) -> RenderPlan {
+        self.render_plan_with_strategy(None, true)
+    }
+
+    /// Picks the start atom of `root`'s component for pretty output.
+    ///
+    /// Terminal atoms are preferred, then heteroatoms, then atoms with more
+    /// heteroatom neighbors. Remaining ties go to the lowest refined class so
+    /// the choice does not depend on input atom order.
+    fn pretty_component_root(
+        &self,
+        root: usize,
+        components: &super::SmilesComponents<'_, AtomPolicy>,
+        refined_classes: &[usize],
+    ) -> usize {
+        let is_heteroatom = |node_id: usize| {
+            self.atom_nodes[node_id]
+                .element()
+                .is_some_and(|element| !matches!(element, Element::C | Element::H))
+        };
+        let component = components.component_of_node(root);
+        (0..self.atom_nodes.len())
+            .filter(|&node_id| components.component_of_node(node_id) == component)
+            .max_by_key(|&node_id| {
+                let hetero_neighbors = self
+                    .bond_matrix
+                    .sparse_row(node_id)
+                    .filter(|&neighbor| is_heteroatom(neighbor))
+                    .count();
+                (
+                    self.edge_count_for_node(node_id) <= 1,
+                    is_heteroatom(node_id),
+                    hetero_neighbors,
+                    Reverse(refined_classes[node_id]),
+                    Reverse(node_id),
+                )
+            })
+            .unwrap_or(root)
+    }
+
+    /// Shared body of the structural and pretty render plans.
+    fn render_plan_with_strategy(&self, forced_root: Option<usize>, pretty: bool) -> RenderPlan {
         let node_count = self.nodes().len();
         let invariants = self.atom_invariants();
         let refined = self.refined_atom_classes_from_invariants(&invariants);
@@ -309,6 +359,12 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
         if let Some(forced) = forced_root {
             self.force_component_root(&mut roots, forced);
         }
+        if pretty {
+            let components = self.connected_components();
+            for root in &mut roots {
+                *root = self.pretty_component_root(*root, &components, &refined_classes);
+            }
+        }
         let primary = build_render_ordering(
             self,
             self.spanning_forest_with_planning(
@@ -321,6 +377,7 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
             &refined_classes,
             &rooted_classes,
             node_count,
+            pretty,
         );
         let ordering = if primary.max_assigned_label > 99 {
             let fallback = build_render_ordering(
@@ -330,6 +387,7 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
                 &refined_classes,
                 &rooted_classes,
                 node_count,
+                pretty,
             );
             if fallback.max_assigned_label < primary.max_assigned_label {
                 fallback
@@ -414,9 +472,13 @@ fn build_render_ordering<AtomPolicy: SmilesAtomPolicy>(
     refined_classes: &[usize],
     rooted_classes: &[usize],
     node_count: usize,
+    smallest_branches_first: bool,
 ) -> RenderOrdering {
-    let branch_plan =
+    let mut branch_plan =
         smiles.branch_plan_with_planning(&forest, invariants, refined_classes, rooted_classes);
+    if smallest_branches_first {
+        branch_plan.order_children_smallest_first();
+    }
     let (components, preorder_indices, global_preorder) =
         build_component_preorders(&forest, &branch_plan, node_count);
     let directional_overrides = smiles.projected_directional_bond_overrides_with_classes(
@@ -473,34 +535,30 @@ fn build_component_preorders(
     (components, preorder_indices, global_preorder)
 }
 
-/// Recursively assigns preorder indices by walking children in final branch
-/// order.
+/// Assigns preorder indices by walking children in final branch order.
+///
+/// Children are pushed on an explicit stack in reverse, so they are still
+/// visited in branch order without recursing once per tree level.
 fn assign_component_preorder(
-    node_id: usize,
+    root: usize,
     branch_plan: &BranchPlan,
     preorder_indices: &mut [usize],
     next_index: &mut usize,
     component_preorder: &mut Vec<usize>,
     global_preorder: &mut Vec<usize>,
 ) {
-    if preorder_indices[node_id] != usize::MAX {
-        return;
-    }
+    let mut stack = vec![root];
+    while let Some(node_id) = stack.pop() {
+        if preorder_indices[node_id] != usize::MAX {
+            continue;
+        }
 
-    preorder_indices[node_id] = *next_index;
-    *next_index += 1;
-    component_preorder.push(node_id);
-    global_preorder.push(node_id);
+        preorder_indices[node_id] = *next_index;
+        *next_index += 1;
+        component_preorder.push(node_id);
+        global_preorder.push(node_id);
 
-    for &child in branch_plan.ordered_children(node_id) {
-        assign_component_preorder(
-            child,
-            branch_plan,
-            preorder_indices,
-            next_index,
-            component_preorder,
-            global_preorder,
-        );
+        stack.extend(branch_plan.ordered_children(node_id).iter().rev());
     }
 }
 
@@ -802,7 +860,10 @@ fn rendered_bond_text_len<AtomPolicy: SmilesAtomPolicy>(
     let elided = (descriptor.is_aromatic()
         && both_aromatic
         && matches!(descriptor.bond(), Bond::Single | Bond::Double))
-        || (descriptor.bond() == Bond::Single && !descriptor.is_aromatic() && !both_aromatic);
+        || (descriptor.bond() == Bond::Single
+            && !descriptor.is_aromatic()
+            && !both_aromatic
+            && !smiles.is_explicit_single_bond(from, to));
     usize::from(!elided)
 }
 
@@ -0,0 +1,437 @@
+//! Scaffold hopping by replacing ring systems with bioisosteric rings.
+
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use geometric_traits::traits::SparseValuedMatrixRef;
+
+use super::{
+    BondMatrixBuilder, Smiles, SmilesAtomPolicy, StereoNeighbor, WildcardAtoms, WildcardSmiles,
+};
+use crate::{
+    atom::atom_symbol::AtomSymbol,
+    bond::{Bond, bond_edge::BondEdge},
+};
+
+/// Largest number of substituent assignments tried per ring system and
+/// library ring, the `6!` assignments of six attachments of one bond order.
+const MAX_ASSIGNMENTS: usize = 720;
+
+/// A bond leaving a ring system, oriented from the ring atom to the
+/// substituent atom.
+#[derive(Debug, Clone, Copy)]
+struct Attachment {
+    inside: usize,
+    outside: usize,
+    edge: BondEdge,
+}
+
+/// A wildcard of a replacement ring, marking where a substituent attaches.
+#[derive(Debug, Clone, Copy)]
+struct ExitVector {
+    wildcard: usize,
+    anchor: usize,
+    bond: Bond,
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns the ring systems of the graph: maximal sets of ring atoms
+    /// joined by ring bonds, so fused and spiro rings form one system.
+    ///
+    /// Each system is sorted, and systems are ordered by their smallest atom
+    /// id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let smiles: Smiles = "c1ccccc1Cc1ccc2ccccc2c1".parse()?;
+    /// let systems = smiles.ring_systems();
+    /// assert_eq!(systems.len(), 2);
+    /// assert_eq!(systems[0], [0, 1, 2, 3, 4, 5]);
+    /// assert_eq!(systems[1].len(), 10);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn ring_systems(&self) -> Vec<Vec<usize>> {
+        let membership = self.ring_membership();
+        let mut parent = (0..self.atom_nodes.len()).collect::<Vec<_>>();
+        for &[left, right] in membership.bond_edges() {
+            let (left, right) = (find_root(&mut parent, left), find_root(&mut parent, right));
+            parent[left.max(right)] = left.min(right);
+        }
+
+        let mut systems: Vec<Vec<usize>> = Vec::new();
+        let mut system_of_root = vec![usize::MAX; self.atom_nodes.len()];
+        for &atom_id in membership.atom_ids() {
+            let root = find_root(&mut parent, atom_id);
+            if system_of_root[root] == usize::MAX {
+                system_of_root[root] = systems.len();
+                systems.push(Vec::new());
+            }
+            systems[system_of_root[root]].push(atom_id);
+        }
+        systems
+    }
+}
+
+impl WildcardSmiles {
+    /// Returns the ring systems of the graph: maximal sets of ring atoms
+    /// joined by ring bonds.
+    #[inline]
+    #[must_use]
+    pub fn ring_systems(&self) -> Vec<Vec<usize>> {
+        self.inner().ring_systems()
+    }
+}
+
+impl Smiles {
+    /// Returns the variants obtained by replacing one ring system of the
+    /// graph with a ring from `library`.
+    ///
+    /// Library rings mark their exit vectors with wildcard atoms, e.g.
+    /// `*c1ccncc1` for a 4-pyridyl. A ring replaces a system when it has one
+    /// wildcard per bond leaving the system and each wildcard bond has the
+    /// order of the bond it takes over. Substituents are only assigned to
+    /// exit vectors of their bond order, and every such assignment yields a
+    /// variant, up to 720 assignments per ring system and library ring so
+    /// heavily substituted systems stay tractable. Library entries whose
+    /// wildcards are not terminal atoms on a non-wildcard neighbor are
+    /// ignored.
+    ///
+    /// Variants are canonicalized, deduplicated and returned in discovery
+    /// order; the graph itself is never among them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::{Smiles, WildcardSmiles};
+    ///
+    /// let toluene: Smiles = "Cc1ccccc1".parse()?;
+    /// let library = ["*c1ccncc1".parse::<WildcardSmiles>()?, "*C1CC1".parse()?];
+    /// let variants = toluene.replace_ring_systems(&library);
+    ///
+    /// assert_eq!(variants.len(), 2);
+    /// assert_eq!(variants[0], "Cc1ccncc1".parse::<Smiles>()?.canonicalize());
+    /// assert_eq!(variants[1], "CC1CC1".parse::<Smiles>()?.canonicalize());
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn replace_ring_systems(&self, library: &[WildcardSmiles]) -> Vec<Self> {
+        let mut seen = BTreeSet::from([self.canonicalize().render()]);
+        let mut variants = Vec::new();
+        for system in self.ring_systems() {
+            let attachments = self.ring_system_attachments(&system);
+            for replacement in library {
+                let replacement = replacement.inner();
+                let Some(exit_vectors) = exit_vectors(replacement) else {
+                    continue;
+                };
+                for assignment in compatible_assignments(&attachments, &exit_vectors) {
+                    let variant = self
+                        .with_ring_system_replaced(
+                            &system,
+                            &attachments,
+                            replacement,
+                            &exit_vectors,
+                            &assignment,
+                        )
+                        .canonicalize();
+                    if seen.insert(variant.render()) {
+                        variants.push(variant);
+                    }
+                }
+            }
+        }
+        variants
+    }
+
+    fn ring_system_attachments(&self, system: &[usize]) -> Vec<Attachment> {
+        system
+            .iter()
+            .flat_map(|&inside| {
+                self.neighbors_with_bonds(inside)
+                    .filter(|(outside, _)| system.binary_search(outside).is_err())
+                    .map(move |(outside, edge)| Attachment { inside, outside, edge })
+            })
+            .collect()
+    }
+
+    /// Builds the graph with `system` removed and `replacement` bonded in its
+    /// place, attachment `i` taking exit vector `assignment[i]`.
+    fn with_ring_system_replaced(
+        &self,
+        system: &[usize],
+        attachments: &[Attachment],
+        replacement: &Smiles<WildcardAtoms>,
+        exit_vectors: &[ExitVector],
+        assignment: &[usize],
+    ) -> Self {
+        let mut atom_nodes = Vec::new();
+        let mut new_of_old = vec![usize::MAX; self.atom_nodes.len()];
+        for (atom_id, &atom) in self.atom_nodes.iter().enumerate() {
+            if system.binary_search(&atom_id).is_err() {
+                new_of_old[atom_id] = atom_nodes.len();
+                atom_nodes.push(atom);
+            }
+        }
+        let mut new_of_replacement = vec![usize::MAX; replacement.atom_nodes.len()];
+        for (atom_id, &atom) in replacement.atom_nodes.iter().enumerate() {
+            if atom.symbol() != AtomSymbol::WildCard {
+                new_of_replacement[atom_id] = atom_nodes.len();
+                atom_nodes.push(atom);
+            }
+        }
+        let mut attachment_of_exit = vec![0; exit_vectors.len()];
+        for (attachment, &exit) in assignment.iter().enumerate() {
+            attachment_of_exit[exit] = attachment;
+        }
+
+        let mut builder = BondMatrixBuilder::default();
+        push_kept_bonds(&mut builder, self, &new_of_old);
+        push_kept_bonds(&mut builder, replacement, &new_of_replacement);
+        for (attachment, &exit) in attachments.iter().zip(assignment) {
+            builder
+                .push_edge_with_descriptor(
+                    new_of_replacement[exit_vectors[exit].anchor],
+                    new_of_old[attachment.outside],
+                    attachment.edge.descriptor(),
+                    None,
+                )
+                .unwrap_or_else(|_| unreachable!("every attachment bonds a new pair of atoms"));
+        }
+
+        // Stereo neighbors that pointed across a replaced bond now point at
+        // the atom on the other side of the new bond.
+        let mut parsed_stereo_neighbors = Vec::with_capacity(atom_nodes.len());
+        for atom_id in
+            (0..self.atom_nodes.len()).filter(|&atom_id| new_of_old[atom_id] != usize::MAX)
+        {
+            parsed_stereo_neighbors.push(remap_stereo_row(
+                self.parsed_stereo_neighbors_row(atom_id),
+                |neighbor| {
+                    if new_of_old[neighbor] != usize::MAX {
+                        return new_of_old[neighbor];
+                    }
+                    let attachment = attachments
+                        .iter()
+                        .position(|attachment| {
+                            attachment.outside == atom_id && attachment.inside == neighbor
+                        })
+                        .unwrap_or_else(|| unreachable!("stereo neighbors are bonded"));
+                    new_of_replacement[exit_vectors[assignment[attachment]].anchor]
+                },
+            ));
+        }
+        for atom_id in (0..replacement.atom_nodes.len())
+            .filter(|&atom_id| new_of_replacement[atom_id] != usize::MAX)
+        {
+            parsed_stereo_neighbors.push(remap_stereo_row(
+                replacement.parsed_stereo_neighbors_row(atom_id),
+                |neighbor| {
+                    if new_of_replacement[neighbor] != usize::MAX {
+                        return new_of_replacement[neighbor];
+                    }
+                    let exit = exit_vectors
+                        .iter()
+                        .position(|exit| exit.wildcard == neighbor)
+                        .unwrap_or_else(|| unreachable!("wildcards are exit vectors"));
+                    new_of_old[attachments[attachment_of_exit[exit]].outside]
+                },
+            ));
+        }
+
+        let atom_count = atom_nodes.len();
+        Self::from_bond_matrix_parts_with_parsed_stereo_and_source(
+            atom_nodes,
+            builder.finish(atom_count),
+            parsed_stereo_neighbors,
+            None,
+        )
+    }
+}
+
+/// Returns the exit vectors of a library ring, or `None` when a wildcard is
+/// not a terminal atom on a non-wildcard neighbor.
+fn exit_vectors(replacement: &Smiles<WildcardAtoms>) -> Option<Vec<ExitVector>> {
+    (0..replacement.atom_nodes.len())
+        .filter(|&atom_id| replacement.atom_nodes[atom_id].symbol() == AtomSymbol::WildCard)
+        .map(|wildcard| {
+            let mut neighbors = replacement.neighbors_with_bonds(wildcard);
+            let (anchor, edge) = neighbors.next()?;
+            let terminal = neighbors.next().is_none()
+                && replacement.atom_nodes[anchor].symbol() != AtomSymbol::WildCard;
+            terminal.then_some(ExitVector { wildcard, anchor, bond: edge.bond() })
+        })
+        .collect()
+}
+
+/// Returns up to [`MAX_ASSIGNMENTS`] assignments of attachments to exit
+/// vectors of the same bond order, attachment `i` taking exit vector
+/// `assignment[i]`, in lexicographic order.
+fn compatible_assignments(
+    attachments: &[Attachment],
+    exit_vectors: &[ExitVector],
+) -> Vec<Vec<usize>> {
+    let matches = |attachment: &Attachment, exit: &ExitVector| {
+        attachment.edge.bond().without_direction() == exit.bond.without_direction()
+    };
+    // With equal counts per bond order every partial assignment extends to a
+    // complete one, so the search below never backtracks out of a dead end.
+    let counts_match = exit_vectors.len() == attachments.len()
+        && attachments.iter().all(|attachment| {
+            let bond = attachment.edge.bond().without_direction();
+            attachments.iter().filter(|other| other.edge.bond().without_direction() == bond).count()
+                == exit_vectors.iter().filter(|exit| matches(attachment, exit)).count()
+        });
+    if !counts_match {
+        return Vec::new();
+    }
+
+    let mut assignments = Vec::new();
+    let mut assignment = Vec::with_capacity(attachments.len());
+    let mut used = vec![false; exit_vectors.len()];
+    let mut next_exit = vec![0; attachments.len() + 1];
+    while assignments.len() < MAX_ASSIGNMENTS {
+        let depth = assignment.len();
+        if depth == attachments.len() {
+            assignments.push(assignment.clone());
+        } else if let Some(exit) = (next_exit[depth]..exit_vectors.len())
+            .find(|&exit| !used[exit] && matches(&attachments[depth], &exit_vectors[exit]))
+        {
+            next_exit[depth] = exit + 1;
+            next_exit[depth + 1] = 0;
+            used[exit] = true;
+            assignment.push(exit);
+            continue;
+        }
+        let Some(exit) = assignment.pop() else {
+            break;
+        };
+        used[exit] = false;
+    }
+    assignments
+}
+
+fn remap_stereo_row(
+    row: &[StereoNeighbor],
+    new_id: impl Fn(usize) -> usize,
+) -> Vec<StereoNeighbor> {
+    row.iter()
+        .map(|&neighbor| {
+            match neighbor {
+                StereoNeighbor::Atom(atom_id) => StereoNeighbor::Atom(new_id(atom_id)),
+                StereoNeighbor::ExplicitHydrogen => StereoNeighbor::ExplicitHydrogen,
+            }
+        })
+        .collect()
+}
+
+/// Copies the bonds of `graph` whose endpoints both have a new id.
+fn push_kept_bonds<AtomPolicy: SmilesAtomPolicy>(
+    builder: &mut BondMatrixBuilder,
+    graph: &Smiles<AtomPolicy>,
+    new_ids: &[usize],
+) {
+    for ((row, column), entry) in graph.bond_matrix.sparse_entries() {
+        if row < column && new_ids[row] != usize::MAX && new_ids[column] != usize::MAX {
+            builder
+                .push_edge_with_descriptor(new_ids[row], new_ids[column], entry.descriptor(), None)
+                .unwrap_or_else(|_| unreachable!("kept bonds join distinct new atoms"));
+        }
+    }
+}
+
+fn find_root(parent: &mut [usize], mut atom_id: usize) -> usize {
+    while parent[atom_id] != atom_id {
+        parent[atom_id] = parent[parent[atom_id]];
+        atom_id = parent[atom_id];
+    }
+    atom_id
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+
+    use super::*;
+
+    fn variants(smiles: &str, library: &[&str]) -> Vec<String> {
+        let library = library.iter().map(|ring| ring.parse().unwrap()).collect::<Vec<_>>();
+        smiles
+            .parse::<Smiles>()
+            .unwrap()
+            .replace_ring_systems(&library)
+            .iter()
+            .map(Smiles::render)
+            .collect()
+    }
+
+    fn canonical(smiles: &str) -> String {
+        smiles.parse::<Smiles>().unwrap().canonicalize().render()
+    }
+
+    #[test]
+    fn ring_systems_merge_fused_and_spiro_rings() {
+        let smiles: Smiles = "C1CCC12CC2.c1ccccc1".parse().unwrap();
+        assert_eq!(smiles.ring_systems(), [vec![0, 1, 2, 3, 4, 5], vec![6, 7, 8, 9, 10, 11]]);
+        assert!("CCO".parse::<Smiles>().unwrap().ring_systems().is_empty());
+        let wildcard: WildcardSmiles = "*C1CC1".parse().unwrap();
+        assert_eq!(wildcard.ring_systems(), [vec![1, 2, 3]]);
+    }
+
+    #[test]
+    fn every_assignment_of_substituents_is_enumerated() {
+        let mut rendered = variants("Cc1ccc(O)cc1", &["*c1ccc(*)nc1"]);
+        rendered.sort_unstable();
+        let mut expected = [canonical("Cc1ccc(O)nc1"), canonical("Oc1ccc(C)nc1")];
+        expected.sort_unstable();
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn rings_must_match_the_attachment_count_and_bond_orders() {
+        assert!(variants("Cc1ccc(O)cc1", &["*c1ccncc1"]).is_empty());
+        assert!(variants("O=C1CCCCC1", &["*C1CCOCC1"]).is_empty());
+        assert_eq!(variants("O=C1CCCCC1", &["*=C1CCOCC1"]), [canonical("O=C1CCOCC1")]);
+        assert_eq!(variants("c1ccccc1", &["c1ccncc1"]), [canonical("c1ccncc1")]);
+    }
+
+    #[test]
+    fn assignments_are_capped_and_matched_by_bond_order() {
+        assert_eq!(
+            variants("Cc1c(C)c(C)c(C)c(C)c1C", &["*C1(*)C(*)(*)C1(*)*"]),
+            [canonical("CC1(C)C(C)(C)C1(C)C")]
+        );
+        // Eight attachments have 40320 assignments, of which only the first
+        // 720 are tried.
+        assert_eq!(
+            variants("Cc1c(C)c(C)c2c(C)c(C)c(C)c(C)c2c1C", &["*C1(*)C(*)(*)C(*)(*)C1(*)*"]),
+            [canonical("CC1(C)C(C)(C)C(C)(C)C1(C)C")]
+        );
+    }
+
+    #[test]
+    fn malformed_and_identical_rings_yield_nothing() {
+        assert!(variants("Cc1ccccc1", &["**c1ccccc1", "*1CC1", "*"]).is_empty());
+        assert!(variants("Cc1ccccc1", &["*c1ccccc1"]).is_empty());
+    }
+
+    #[test]
+    fn each_ring_system_is_replaced_on_its_own() {
+        let mut rendered = variants("c1ccccc1C1CC1", &["*C1CCC1"]);
+        rendered.sort_unstable();
+        let mut expected = [canonical("C1CCC1C1CC1"), canonical("c1ccccc1C1CCC1")];
+        expected.sort_unstable();
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn stereocentres_outside_the_ring_are_kept() {
+        assert_eq!(variants("C[C@H](N)c1ccccc1", &["*c1ccncc1"]), [canonical("C[C@H](N)c1ccncc1")]);
+        assert_eq!(
+            variants("C[C@@H](N)c1ccccc1", &["*c1ccncc1"]),
+            [canonical("C[C@@H](N)c1ccncc1")]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Cheap structural predicates for filtering graphs.
+
+use super::{Smiles, SmilesAtomPolicy, WildcardSmiles};
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns whether the graph has no atoms.
+    ///
+    /// Only graphs parsed with
+    /// [`ParserOptions::allow_empty`](crate::ParserOptions::allow_empty) can be
+    /// empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.atom_nodes.is_empty()
+    }
+
+    /// Returns whether the graph has exactly one atom, e.g. `[Na+]` or `C`.
+    #[inline]
+    #[must_use]
+    pub fn is_single_atom(&self) -> bool {
+        self.atom_nodes.len() == 1
+    }
+
+    /// Returns whether the graph has exactly one connected component. An
+    /// empty graph is not connected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// assert!("CCO".parse::<Smiles>()?.is_connected());
+    /// assert!(!"[Na+].[Cl-]".parse::<Smiles>()?.is_connected());
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        !self.is_empty() && self.connected_components().number_of_components() == 1
+    }
+
+    /// Returns whether the graph has no ring.
+    ///
+    /// A graph is acyclic exactly when it is a forest, i.e. when its bonds
+    /// plus its components equal its atoms, so no ring perception is needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// assert!("CC(C)O.N".parse::<Smiles>()?.is_acyclic());
+    /// assert!(!"C1CC1".parse::<Smiles>()?.is_acyclic());
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn is_acyclic(&self) -> bool {
+        self.number_of_bonds() + self.connected_components().number_of_components()
+            == self.atom_nodes.len()
+    }
+
+    /// Returns the size of the largest ring of the symmetrized SSSR, or
+    /// `None` when the graph is acyclic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// assert_eq!("C1CC1c1ccccc1".parse::<Smiles>()?.max_ring_size(), Some(6));
+    /// assert_eq!("CCO".parse::<Smiles>()?.max_ring_size(), None);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn max_ring_size(&self) -> Option<usize> {
+        if self.is_acyclic() {
+            return None;
+        }
+        self.symm_sssr_result().cycles().iter().map(alloc::vec::Vec::len).max()
+    }
+}
+
+impl WildcardSmiles {
+    /// Returns whether the graph has no atoms.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner().is_empty()
+    }
+
+    /// Returns whether the graph has exactly one atom.
+    #[inline]
+    #[must_use]
+    pub fn is_single_atom(&self) -> bool {
+        self.inner().is_single_atom()
+    }
+
+    /// Returns whether the graph has exactly one connected component.
+    #[inline]
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.inner().is_connected()
+    }
+
+    /// Returns whether the graph has no ring.
+    #[inline]
+    #[must_use]
+    pub fn is_acyclic(&self) -> bool {
+        self.inner().is_acyclic()
+    }
+
+    /// Returns the size of the largest ring of the symmetrized SSSR.
+    #[inline]
+    #[must_use]
+    pub fn max_ring_size(&self) -> Option<usize> {
+        self.inner().max_ring_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smiles::ParserOptions;
+
+    fn smiles(input: &str) -> Smiles {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn predicates_describe_simple_shapes() {
+        let empty = Smiles::parse_with_options(
+            "",
+            &ParserOptions { allow_empty: true, ..ParserOptions::default() },
+        )
+        .unwrap();
+        assert!(empty.is_empty());
+        assert!(!empty.is_single_atom());
+        assert!(!empty.is_connected());
+        assert!(empty.is_acyclic());
+        assert_eq!(empty.max_ring_size(), None);
+
+        let sodium = smiles("[Na+]");
+        assert!(!sodium.is_empty());
+        assert!(sodium.is_single_atom());
+        assert!(sodium.is_connected());
+        assert!(sodium.is_acyclic());
+    }
+
+    #[test]
+    fn rings_are_detected_across_components() {
+        assert!(!smiles("CC.C1CCCCCCC1").is_acyclic());
+        assert!(!smiles("CC.C1CCCCCCC1").is_connected());
+        assert_eq!(smiles("CC.C1CCCCCCC1").max_ring_size(), Some(8));
+        assert_eq!(smiles("c1ccc2ccccc2c1").max_ring_size(), Some(6));
+        assert_eq!(smiles("C12CC1C2").max_ring_size(), Some(3));
+        assert!(smiles("CC(C)(C)CC(=O)O").is_acyclic());
+    }
+
+    #[test]
+    fn wildcard_graphs_delegate() {
+        let graph: WildcardSmiles = "*C1CC1".parse().unwrap();
+        assert!(graph.is_connected());
+        assert!(!graph.is_acyclic());
+        assert_eq!(graph.max_ring_size(), Some(3));
+        assert!(!graph.is_single_atom());
+        assert!(!graph.is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+//! Reference-counted graphs for sharing between tasks and caches.
+
+use alloc::sync::Arc;
+use core::{fmt, ops::Deref};
+
+use super::{ConcreteAtoms, Smiles, SmilesAtomPolicy};
+
+/// A [`Smiles`] graph behind an atomic reference count.
+///
+/// Cloning a `SharedSmiles` copies a pointer rather than the atoms and bonds,
+/// so a parsed graph can be handed to task queues, caches and worker threads
+/// at no cost. Reads go through [`Deref`] to the shared graph; edits go
+/// through [`SharedSmiles::make_mut`], which copies the graph first only when
+/// other handles still point to it.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::prelude::{SharedSmiles, Smiles};
+///
+/// let shared = SharedSmiles::from("c1ccccc1O".parse::<Smiles>()?);
+/// let mut copy = shared.clone();
+/// assert!(SharedSmiles::ptr_eq(&shared, &copy));
+/// assert_eq!(copy.nodes().len(), 7);
+///
+/// copy.make_mut().set_name("phenol");
+/// assert!(!SharedSmiles::ptr_eq(&shared, &copy));
+/// assert_eq!(shared.name(), None);
+/// assert_eq!(copy.name(), Some("phenol"));
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+pub struct SharedSmiles<AtomPolicy = ConcreteAtoms> {
+    inner: Arc<Smiles<AtomPolicy>>,
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> SharedSmiles<AtomPolicy> {
+    /// Moves `smiles` behind a new reference count.
+    #[inline]
+    #[must_use]
+    pub fn new(smiles: Smiles<AtomPolicy>) -> Self {
+        Self { inner: Arc::new(smiles) }
+    }
+
+    /// Returns a mutable reference to the graph, first copying it when other
+    /// handles share it.
+    #[inline]
+    pub fn make_mut(&mut self) -> &mut Smiles<AtomPolicy> {
+        Arc::make_mut(&mut self.inner)
+    }
+
+    /// Returns the graph, copying it only when other handles share it.
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> Smiles<AtomPolicy> {
+        Arc::unwrap_or_clone(self.inner)
+    }
+
+    /// Returns whether `left` and `right` point to the same graph, rather
+    /// than to equal copies.
+    #[inline]
+    #[must_use]
+    pub fn ptr_eq(left: &Self, right: &Self) -> bool {
+        Arc::ptr_eq(&left.inner, &right.inner)
+    }
+
+    /// Returns the number of handles sharing the graph.
+    #[inline]
+    #[must_use]
+    pub fn handle_count(this: &Self) -> usize {
+        Arc::strong_count(&this.inner)
+    }
+}
+
+impl<AtomPolicy> Clone for SharedSmiles<AtomPolicy> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+impl<AtomPolicy> Deref for SharedSmiles<AtomPolicy> {
+    type Target = Smiles<AtomPolicy>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<AtomPolicy> AsRef<Smiles<AtomPolicy>> for SharedSmiles<AtomPolicy> {
+    #[inline]
+    fn as_ref(&self) -> &Smiles<AtomPolicy> {
+        &self.inner
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> From<Smiles<AtomPolicy>> for SharedSmiles<AtomPolicy> {
+    #[inline]
+    fn from(smiles: Smiles<AtomPolicy>) -> Self {
+        Self::new(smiles)
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> From<SharedSmiles<AtomPolicy>> for Smiles<AtomPolicy> {
+    #[inline]
+    fn from(shared: SharedSmiles<AtomPolicy>) -> Self {
+        shared.into_inner()
+    }
+}
+
+impl<LeftPolicy: SmilesAtomPolicy, RightPolicy: SmilesAtomPolicy>
+    PartialEq<SharedSmiles<RightPolicy>> for SharedSmiles<LeftPolicy>
+{
+    fn eq(&self, other: &SharedSmiles<RightPolicy>) -> bool {
+        *self.inner == *other.inner
+    }
+}
+
+impl<AtomPolicy: fmt::Debug> fmt::Debug for SharedSmiles<AtomPolicy> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> fmt::Display for SharedSmiles<AtomPolicy> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_copy_only_shared_graphs() {
+        let mut shared = SharedSmiles::new("CCO".parse::<Smiles>().unwrap());
+        let address = core::ptr::from_ref::<Smiles>(&shared);
+        shared.make_mut().set_name("ethanol");
+        assert_eq!(core::ptr::from_ref::<Smiles>(&shared), address);
+
+        let copy = shared.clone();
+        assert_eq!(SharedSmiles::handle_count(&shared), 2);
+        assert_eq!(copy, shared);
+        assert_eq!(copy.to_string(), "CCO");
+
+        let owned = shared.into_inner();
+        assert_eq!(SharedSmiles::handle_count(&copy), 1);
+        assert_eq!(owned.name(), Some("ethanol"));
+        assert_eq!(Smiles::from(copy), owned);
+    }
+}
@@ -132,18 +132,10 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
         &self,
         roots: &[usize],
     ) -> SpanningForest {
-        let node_count = self.nodes().len();
-        let mut state = ForestBuildState::new(node_count);
-
-        for &root in roots {
-            if root >= node_count || state.visited[root] {
-                continue;
-            }
-            state.visited[root] = true;
-            self.build_spanning_tree_from_parser_neighbor_order(root, &mut state);
-        }
-
-        state.into_forest(roots, self)
+        let ordered_neighbors: Vec<Vec<BondEdge>> = (0..self.nodes().len())
+            .map(|node_id| self.parser_ordered_neighbor_edges(node_id))
+            .collect();
+        self.spanning_forest_with_ordered_neighbors(roots, &ordered_neighbors)
     }
 
     fn spanning_forest_with_ordered_neighbors(
@@ -159,48 +151,12 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
                 continue;
             }
             state.visited[root] = true;
-            self.build_spanning_tree_from(root, ordered_neighbors, &mut state);
+            build_spanning_tree_from(root, ordered_neighbors, &mut state);
         }
 
         state.into_forest(roots, self)
     }
 
-    fn build_spanning_tree_from(
-        &self,
-        node_id: usize,
-        ordered_neighbors: &[Vec<BondEdge>],
-        state: &mut ForestBuildState,
-    ) {
-        let edges = &ordered_neighbors[node_id];
-        for edge in edges.iter().copied() {
-            self.visit_spanning_edge(node_id, edge, ordered_neighbors, state);
-        }
-    }
-
-    fn visit_spanning_edge(
-        &self,
-        node_id: usize,
-        edge: BondEdge,
-        ordered_neighbors: &[Vec<BondEdge>],
-        state: &mut ForestBuildState,
-    ) {
-        let neighbor_id = bond_edge_other(edge, node_id).unwrap_or_else(|| unreachable!());
-        if state.parents[node_id] == Some(neighbor_id) {
-            return;
-        }
-
-        if state.visited[neighbor_id] {
-            state.closure_pairs.push(crate::smiles::edge_key(node_id, neighbor_id));
-        } else {
-            state.visited[neighbor_id] = true;
-            state.parents[neighbor_id] = Some(node_id);
-            state.parent_bonds[neighbor_id] = Some(edge_descriptor(edge));
-            state.children[node_id].push(neighbor_id);
-            state.child_bonds[node_id].push(edge_descriptor(edge));
-            self.build_spanning_tree_from(neighbor_id, ordered_neighbors, state);
-        }
-    }
-
     fn parser_ordered_neighbor_edges(&self, node_id: usize) -> Vec<BondEdge> {
         let mut ordered_neighbors: Vec<(usize, usize, BondEdge)> = self
             .bond_matrix
@@ -215,28 +171,42 @@ impl<AtomPolicy: crate::smiles::SmilesAtomPolicy> Smiles<AtomPolicy> {
         });
         ordered_neighbors.into_iter().map(|(_order, _neighbor_id, edge)| edge).collect()
     }
+}
 
-    fn build_spanning_tree_from_parser_neighbor_order(
-        &self,
-        node_id: usize,
-        state: &mut ForestBuildState,
-    ) {
-        for edge in self.parser_ordered_neighbor_edges(node_id) {
-            let neighbor_id = bond_edge_other(edge, node_id).unwrap_or_else(|| unreachable!());
-            if state.parents[node_id] == Some(neighbor_id) {
-                continue;
-            }
+/// Grows the tree of `root` depth first, taking the edges of every node in
+/// their listed order.
+///
+/// The walk keeps its own stack of nodes and next edge positions instead of
+/// recursing, so long chains and deeply nested branches cannot overflow the
+/// call stack.
+fn build_spanning_tree_from(
+    root: usize,
+    ordered_neighbors: &[Vec<BondEdge>],
+    state: &mut ForestBuildState,
+) {
+    let mut stack = vec![(root, 0_usize)];
+    while let Some(&(node_id, next_edge)) = stack.last() {
+        let Some(&edge) = ordered_neighbors[node_id].get(next_edge) else {
+            stack.pop();
+            continue;
+        };
+        let top = stack.len() - 1;
+        stack[top].1 += 1;
 
-            if state.visited[neighbor_id] {
-                state.closure_pairs.push(crate::smiles::edge_key(node_id, neighbor_id));
-            } else {
-                state.visited[neighbor_id] = true;
-                state.parents[neighbor_id] = Some(node_id);
-                state.parent_bonds[neighbor_id] = Some(edge_descriptor(edge));
-                state.children[node_id].push(neighbor_id);
-                state.child_bonds[node_id].push(edge_descriptor(edge));
-                self.build_spanning_tree_from_parser_neighbor_order(neighbor_id, state);
-            }
+        let neighbor_id = bond_edge_other(edge, node_id).unwrap_or_else(|| unreachable!());
+        if state.parents[node_id] == Some(neighbor_id) {
+            continue;
+        }
+
+        if state.visited[neighbor_id] {
+            state.closure_pairs.push(crate::smiles::edge_key(node_id, neighbor_id));
+        } else {
+            state.visited[neighbor_id] = true;
+            state.parents[neighbor_id] = Some(node_id);
+            state.parent_bonds[neighbor_id] = Some(edge_descriptor(edge));
+            state.children[node_id].push(neighbor_id);
+            state.child_bonds[node_id].push(edge_descriptor(edge));
+            stack.push((neighbor_id, 0));
         }
     }
 }
@@ -263,7 +263,7 @@ fn permutation_from(
     Some(permutation)
 }
 
-fn reorder_tetrahedral_chirality(
+pub(super) fn reorder_tetrahedral_chirality(
     chirality: Chirality,
     from_neighbors: &[StereoNeighbor],
     to_neighbors: &[StereoNeighbor],
@@ -0,0 +1,138 @@
+//! Removal of stereo and isotope annotations, which not every source records.
+
+use super::{Smiles, SmilesAtomPolicy, WriterOptions};
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns a copy without chirality tags or directional bonds.
+    ///
+    /// Atoms that only needed brackets for their chirality are written in
+    /// the organic subset again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let smiles: Smiles = "F/C=C/[C@@H](N)O".parse()?;
+    /// assert_eq!(smiles.strip_stereo().to_string(), "NC(C=CF)O");
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn strip_stereo(&self) -> Self {
+        self.with_writer_features_removed(&WriterOptions {
+            stereo: false,
+            ..WriterOptions::default()
+        })
+    }
+
+    /// Returns a copy without isotope mass numbers.
+    ///
+    /// Atoms that only needed brackets for their isotope are written in the
+    /// organic subset again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let smiles: Smiles = "[13CH3][13C](=O)[18OH]".parse()?;
+    /// assert_eq!(smiles.strip_isotopes().to_string(), "CC(O)=O");
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn strip_isotopes(&self) -> Self {
+        self.with_writer_features_removed(&WriterOptions {
+            isotopes: false,
+            ..WriterOptions::default()
+        })
+    }
+
+    /// Returns whether `self` and `other` are the same structure up to
+    /// isotope labels, atom classes and atom order, so tracer variants match
+    /// their unlabeled parent.
+    ///
+    /// Stereochemistry still has to match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let tracer: Smiles = "[13CH3][C@H](N)C(=O)O".parse()?;
+    /// assert!(tracer.is_same_ignoring_isotopes(&"C[C@H](N)C(=O)O".parse::<Smiles>()?));
+    /// assert!(!tracer.is_same_ignoring_isotopes(&"C[C@@H](N)C(=O)O".parse::<Smiles>()?));
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn is_same_ignoring_isotopes<OtherPolicy: SmilesAtomPolicy>(
+        &self,
+        other: &Smiles<OtherPolicy>,
+    ) -> bool {
+        let options =
+            WriterOptions { isotopes: false, atom_classes: false, ..WriterOptions::default() };
+        self.with_writer_features_removed(&options).canonicalize()
+            == other.with_writer_features_removed(&options).canonicalize()
+    }
+
+    /// Returns whether `self` and `other` have the same constitution: the
+    /// same atoms joined by the same bonds, whatever their stereochemistry,
+    /// atom classes or atom order.
+    ///
+    /// Isotopes, charges and hydrogen counts still have to match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let annotated: Smiles = "N[C@@H](C)C(=O)O".parse()?;
+    /// let flat: Smiles = "OC(=O)C(C)N".parse()?;
+    /// assert!(annotated.is_same_constitution(&flat));
+    /// assert!(!annotated.is_same_constitution(&"NCCC(=O)O".parse::<Smiles>()?));
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn is_same_constitution<OtherPolicy: SmilesAtomPolicy>(
+        &self,
+        other: &Smiles<OtherPolicy>,
+    ) -> bool {
+        let options =
+            WriterOptions { stereo: false, atom_classes: false, ..WriterOptions::default() };
+        self.with_writer_features_removed(&options).canonicalize()
+            == other.with_writer_features_removed(&options).canonicalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stereo_is_stripped_and_constitution_compared() {
+        let smiles: Smiles = "C[C@H](N)/C=C\\[13CH3]".parse().unwrap();
+        let stripped = smiles.strip_stereo();
+
+        assert!(stripped.nodes().iter().all(|atom| atom.chirality().is_none()));
+        assert!(!stripped.to_string().contains(['/', '\\', '@']));
+        assert!(stripped.to_string().contains("[13CH3]"));
+        assert_eq!(stripped.strip_stereo(), stripped);
+
+        assert!(smiles.is_same_constitution(&"[13CH3]C=CC(C)N".parse::<Smiles>().unwrap()));
+        assert!(
+            smiles.is_same_constitution(&"C[C@@H](N)/C=C/[13CH3:4]".parse::<Smiles>().unwrap())
+        );
+        assert!(!smiles.is_same_constitution(&"CC(N)C=CC".parse::<Smiles>().unwrap()));
+    }
+
+    #[test]
+    fn isotopes_are_stripped_and_ignored_in_comparison() {
+        let tracer: Smiles = "[13CH3][18OH].[15NH4+]".parse().unwrap();
+        let stripped = tracer.strip_isotopes();
+
+        assert!(stripped.nodes().iter().all(|atom| atom.isotope_mass_number().is_none()));
+        assert_eq!(stripped.to_string(), "CO.[NH4+]");
+        assert!(tracer.is_same_ignoring_isotopes(&"[NH4+].OC".parse::<Smiles>().unwrap()));
+        assert!(!tracer.is_same_ignoring_isotopes(&"CO.[NH3]".parse::<Smiles>().unwrap()));
+        assert!(!tracer.is_same_constitution(&stripped));
+    }
+}
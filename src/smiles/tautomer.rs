@@ -0,0 +1,327 @@
+//! Normalization of mobile hydrogens to one representative tautomer.
+
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use elements_rs::Element;
+use geometric_traits::traits::{SparseMatrix2D, SparseValuedMatrixRef};
+
+use super::{BondMatrix, Smiles, SmilesAtomPolicy};
+use crate::{
+    atom::{Atom, bracketed::charge::Charge},
+    bond::Bond,
+};
+
+/// A carbon double bonded to one heteroatom of a mobile-hydrogen group and
+/// single bonded to the others.
+#[derive(Debug, Clone)]
+struct MobileHydrogenGroup {
+    carbon: usize,
+    /// Heteroatoms of the group, in neighbor order.
+    heteroatoms: Vec<usize>,
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Returns the graph with the hydrogens of its mobile-hydrogen groups
+    /// moved to one representative position.
+    ///
+    /// A mobile-hydrogen group is a non-aromatic carbon with a double bond to
+    /// a nitrogen, oxygen or sulfur and single bonds to other such
+    /// heteroatoms carrying a hydrogen, as in carboxylic acids, amides,
+    /// amidines and guanidines. The double bond is moved to the oxygen of the
+    /// group if there is one, then to a sulfur, then to a nitrogen, and the
+    /// hydrogen moves the other way, so every tautomer of such a group is
+    /// written the same way. Ties between heteroatoms of the same element are
+    /// broken by a canonical ranking that does not depend on where the
+    /// hydrogens sit. Charged atoms, aromatic rings and hydrogens written as
+    /// their own atoms are left alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::Smiles;
+    ///
+    /// let imidic_acid: Smiles = "CC(O)=N".parse()?;
+    /// let amide: Smiles = "CC(N)=O".parse()?;
+    /// assert_eq!(
+    ///     imidic_acid.normalize_mobile_hydrogens().canonicalize(),
+    ///     amide.normalize_mobile_hydrogens().canonicalize()
+    /// );
+    ///
+    /// let left: Smiles = "CNC(C)=NCC".parse()?;
+    /// let right: Smiles = "CN=C(C)NCC".parse()?;
+    /// assert_eq!(
+    ///     left.normalize_mobile_hydrogens().canonicalize(),
+    ///     right.normalize_mobile_hydrogens().canonicalize()
+    /// );
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn normalize_mobile_hydrogens(&self) -> Self {
+        let groups = (0..self.atom_nodes.len())
+            .filter_map(|carbon| self.mobile_hydrogen_group(carbon))
+            .collect::<Vec<_>>();
+        if groups.is_empty() {
+            return self.clone();
+        }
+
+        let mut hydrogens = (0..self.atom_nodes.len())
+            .map(|atom_id| self.total_hydrogens(atom_id))
+            .collect::<Vec<_>>();
+        let mut double_bonded = vec![false; self.atom_nodes.len()];
+        let mut partners = groups
+            .iter()
+            .map(|group| {
+                let partner = group
+                    .heteroatoms
+                    .iter()
+                    .copied()
+                    .find(|&atom_id| {
+                        self.bond_for_node_pair((group.carbon, atom_id)) == Some(Bond::Double)
+                    })
+                    .unwrap_or_else(|| unreachable!("every group has one double bond"));
+                double_bonded[partner] = true;
+                partner
+            })
+            .collect::<Vec<_>>();
+
+        let ranks = self.mobile_hydrogen_skeleton(&groups).exact_canonical_labeling();
+        let ranks = ranks.new_index_of_old_node();
+        let mut group_order = (0..groups.len()).collect::<Vec<_>>();
+        group_order.sort_unstable_by_key(|&index| ranks[groups[index].carbon]);
+
+        for index in group_order {
+            let partner = partners[index];
+            let best = groups[index]
+                .heteroatoms
+                .iter()
+                .copied()
+                .filter(|&atom_id| {
+                    atom_id == partner || (hydrogens[atom_id] > 0 && !double_bonded[atom_id])
+                })
+                .max_by_key(|&atom_id| {
+                    (double_bond_priority(self.atom_nodes[atom_id]), Reverse(ranks[atom_id]))
+                })
+                .unwrap_or(partner);
+            if best != partner {
+                hydrogens[best] -= 1;
+                hydrogens[partner] += 1;
+                double_bonded[best] = true;
+                double_bonded[partner] = false;
+                partners[index] = best;
+            }
+        }
+
+        let mut atom_nodes = self.atom_nodes.clone();
+        for group in &groups {
+            for &atom_id in &group.heteroatoms {
+                if hydrogens[atom_id] != self.total_hydrogens(atom_id) {
+                    atom_nodes[atom_id] =
+                        with_hydrogen_count(self.atom_nodes[atom_id], hydrogens[atom_id]);
+                }
+            }
+        }
+        let double_bonds = groups
+            .iter()
+            .zip(&partners)
+            .map(|(group, &partner)| (group.carbon.min(partner), group.carbon.max(partner)))
+            .collect::<Vec<_>>();
+        let bond_matrix = BondMatrix::from_sorted_upper_triangular_entries(
+            atom_nodes.len(),
+            self.bond_matrix.sparse_entries().filter_map(|((row, column), entry)| {
+                (row < column).then_some((
+                    row,
+                    column,
+                    if !is_group_bond(&groups, row, column) {
+                        *entry
+                    } else if double_bonds.contains(&(row, column)) {
+                        entry.with_bond(Bond::Double)
+                    } else {
+                        entry.with_bond(Bond::Single)
+                    },
+                ))
+            }),
+        )
+        .unwrap_or_else(|_| unreachable!("existing bond matrix entries are already valid"));
+
+        Self::from_bond_matrix_parts_with_parsed_stereo_and_source(
+            atom_nodes,
+            bond_matrix,
+            self.parsed_stereo_neighbors.clone(),
+            None,
+        )
+        .canonicalization_spelling_normal_form()
+    }
+
+    /// Returns the mobile-hydrogen group centered on `carbon`, if any.
+    fn mobile_hydrogen_group(&self, carbon: usize) -> Option<MobileHydrogenGroup> {
+        let atom = self.atom_nodes[carbon];
+        if atom.element() != Some(Element::C) || atom.aromatic() || atom.charge_value() != 0 {
+            return None;
+        }
+
+        let mut heteroatoms = Vec::new();
+        let mut has_double_bond = false;
+        let mut has_donor = false;
+        for neighbor in self.bond_matrix.sparse_row(carbon) {
+            let entry = self.bond_entry_for_node_pair((carbon, neighbor))?;
+            if entry.aromatic() {
+                return None;
+            }
+            match entry.bond().without_direction() {
+                Bond::Double
+                    if self.is_mobile_hydrogen_heteroatom(neighbor) && !has_double_bond =>
+                {
+                    has_double_bond = true;
+                    heteroatoms.push(neighbor);
+                }
+                Bond::Single => {
+                    if self.is_mobile_hydrogen_heteroatom(neighbor)
+                        && self.total_hydrogens(neighbor) > 0
+                    {
+                        has_donor = true;
+                        heteroatoms.push(neighbor);
+                    }
+                }
+                _ => return None,
+            }
+        }
+        (has_double_bond && has_donor).then_some(MobileHydrogenGroup { carbon, heteroatoms })
+    }
+
+    /// Returns whether `atom_id` is a neutral, non-aromatic nitrogen, oxygen
+    /// or sulfur at its lowest valence with no hydrogen atom neighbors.
+    fn is_mobile_hydrogen_heteroatom(&self, atom_id: usize) -> bool {
+        let atom = self.atom_nodes[atom_id];
+        let normal_valence = match atom.element() {
+            Some(Element::N) => 3,
+            Some(Element::O | Element::S) => 2,
+            _ => return false,
+        };
+        !atom.aromatic()
+            && atom.charge_value() == 0
+            && self.total_valence(atom_id) == normal_valence
+            && self
+                .bond_matrix
+                .sparse_row(atom_id)
+                .all(|neighbor| self.atom_nodes[neighbor].element() != Some(Element::H))
+    }
+
+    /// Returns a copy where every bond of a group is single and every
+    /// heteroatom of a group counts its double bond as a hydrogen, which is
+    /// the same graph for every tautomer of the groups.
+    fn mobile_hydrogen_skeleton(&self, groups: &[MobileHydrogenGroup]) -> Self {
+        let mut hydrogens = (0..self.atom_nodes.len())
+            .map(|atom_id| self.total_hydrogens(atom_id))
+            .collect::<Vec<_>>();
+        for group in groups {
+            for &atom_id in &group.heteroatoms {
+                if self.bond_for_node_pair((group.carbon, atom_id)) == Some(Bond::Double) {
+                    hydrogens[atom_id] += 1;
+                }
+            }
+        }
+        let mut atom_nodes = self.atom_nodes.clone();
+        for group in groups {
+            for &atom_id in &group.heteroatoms {
+                atom_nodes[atom_id] =
+                    with_hydrogen_count(self.atom_nodes[atom_id], hydrogens[atom_id]);
+            }
+        }
+        let bond_matrix = BondMatrix::from_sorted_upper_triangular_entries(
+            atom_nodes.len(),
+            self.bond_matrix.sparse_entries().filter_map(|((row, column), entry)| {
+                (row < column).then_some((
+                    row,
+                    column,
+                    if is_group_bond(groups, row, column) {
+                        entry.with_bond(Bond::Single)
+                    } else {
+                        *entry
+                    },
+                ))
+            }),
+        )
+        .unwrap_or_else(|_| unreachable!("existing bond matrix entries are already valid"));
+
+        Self::from_bond_matrix_parts_with_parsed_stereo_and_source(
+            atom_nodes,
+            bond_matrix,
+            self.parsed_stereo_neighbors.clone(),
+            None,
+        )
+    }
+}
+
+/// Returns whether the bond between `row` and `column` joins the carbon of a
+/// group to one of its heteroatoms.
+fn is_group_bond(groups: &[MobileHydrogenGroup], row: usize, column: usize) -> bool {
+    groups.iter().any(|group| {
+        (group.carbon == row && group.heteroatoms.contains(&column))
+            || (group.carbon == column && group.heteroatoms.contains(&row))
+    })
+}
+
+/// Returns how strongly a heteroatom attracts the double bond of its group:
+/// oxygen before sulfur before nitrogen.
+fn double_bond_priority(atom: Atom) -> u8 {
+    match atom.element() {
+        Some(Element::O) => 2,
+        Some(Element::S) => 1,
+        _ => 0,
+    }
+}
+
+/// Returns `atom` written as a bracket atom carrying `hydrogens` hydrogens.
+fn with_hydrogen_count(atom: Atom, hydrogens: u8) -> Atom {
+    Atom::new_bracket(
+        atom.symbol(),
+        atom.isotope_mass_number(),
+        atom.aromatic(),
+        hydrogens,
+        Charge::try_new(atom.charge_value())
+            .unwrap_or_else(|_| unreachable!("group heteroatoms are neutral")),
+        atom.class(),
+        atom.chirality(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+
+    fn normalized(smiles: &str) -> String {
+        smiles.parse::<Smiles>().unwrap().normalize_mobile_hydrogens().canonicalize().render()
+    }
+
+    fn canonical(smiles: &str) -> String {
+        smiles.parse::<Smiles>().unwrap().canonicalize().render()
+    }
+
+    #[test]
+    fn tautomers_share_one_representative() {
+        for (left, right) in [
+            ("CC(O)=N", "CC(N)=O"),
+            ("CC(S)=N", "CC(N)=S"),
+            ("CC(=O)S", "CC(O)=S"),
+            ("CNC(C)=NCC", "CN=C(C)NCC"),
+            ("CNC(=N)NC", "CN=C(N)NC"),
+            ("OC(O)=NC", "O=C(O)NC"),
+        ] {
+            assert_eq!(normalized(left), normalized(right), "{left} {right}");
+        }
+        assert_eq!(normalized("CC(O)=N"), canonical("CC(N)=O"));
+        assert_eq!(normalized("CC(S)=N"), canonical("CC(N)=S"));
+        assert_eq!(normalized("CC(O)=S"), canonical("CC(=O)S"));
+    }
+
+    #[test]
+    fn groups_without_mobile_hydrogens_are_kept() {
+        for input in ["CC(=O)OC", "CC(=O)N(C)C", "CC(=O)[O-]", "c1ccncc1O", "CC(=O)O[H]"] {
+            let smiles: Smiles = input.parse().unwrap();
+            assert_eq!(smiles.normalize_mobile_hydrogens(), smiles, "{input}");
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! All-or-nothing application of several graph edits.
+
+use elements_rs::ChargedValences;
+use thiserror::Error;
+
+use super::{Smiles, SmilesAtomPolicy};
+
+/// Error returned by [`Smiles::transaction`]; the graph is left unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TransactionError<E> {
+    /// The transaction closure returned an error.
+    #[error("transaction aborted: {0}")]
+    Aborted(E),
+    /// The edited graph has an atom whose total valence exceeds the largest
+    /// valence its element allows at its formal charge.
+    #[error("atom {atom} has valence {valence}, above the maximum of {max_valence}")]
+    InvalidValence {
+        /// Id of the first offending atom in the edited graph.
+        atom: usize,
+        /// Total valence of the atom, implicit hydrogens included.
+        valence: u8,
+        /// Largest valence allowed for the element at the atom's charge.
+        max_valence: u8,
+    },
+}
+
+/// Working copy of a graph edited inside [`Smiles::transaction`].
+///
+/// Each edit replaces the working copy; the graph the transaction was opened
+/// on only sees the result once every edit succeeded.
+#[derive(Debug)]
+pub struct Transaction<AtomPolicy: SmilesAtomPolicy> {
+    working: Smiles<AtomPolicy>,
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Transaction<AtomPolicy> {
+    /// Returns the working copy with every edit applied so far.
+    #[inline]
+    #[must_use]
+    pub fn smiles(&self) -> &Smiles<AtomPolicy> {
+        &self.working
+    }
+
+    /// Returns the working copy for in-place edits, such as metadata changes.
+    #[inline]
+    pub fn smiles_mut(&mut self) -> &mut Smiles<AtomPolicy> {
+        &mut self.working
+    }
+
+    /// Replaces the working copy by the graph `edit` derives from it.
+    ///
+    /// The name and properties of the working copy carry over to the derived
+    /// graph.
+    #[inline]
+    pub fn apply(&mut self, edit: impl FnOnce(&Smiles<AtomPolicy>) -> Smiles<AtomPolicy>) {
+        let mut edited = edit(&self.working);
+        self.working.move_metadata_to(&mut edited);
+        self.working = edited;
+    }
+
+    /// Replaces the working copy by the graph `edit` derives from it, or
+    /// leaves it unchanged when `edit` fails.
+    ///
+    /// The name and properties of the working copy carry over to the derived
+    /// graph.
+    ///
+    /// # Errors
+    /// Returns the error of `edit`.
+    #[inline]
+    pub fn try_apply<E>(
+        &mut self,
+        edit: impl FnOnce(&Smiles<AtomPolicy>) -> Result<Smiles<AtomPolicy>, E>,
+    ) -> Result<(), E> {
+        let mut edited = edit(&self.working)?;
+        self.working.move_metadata_to(&mut edited);
+        self.working = edited;
+        Ok(())
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Applies the edits of `edits` to the graph atomically.
+    ///
+    /// `edits` works on a [`Transaction`] holding a copy of the graph. The
+    /// graph is replaced by the edited copy only if `edits` returns `Ok` and
+    /// no atom of the copy exceeds the largest valence its element allows at
+    /// its formal charge; otherwise it is left untouched, so a multi-step
+    /// standardization never stops half-way. Atoms whose element has no
+    /// valence table at their charge, and wildcard atoms, are not checked.
+    ///
+    /// # Errors
+    /// Returns [`TransactionError::Aborted`] with the error of `edits`, or
+    /// [`TransactionError::InvalidValence`] for the first atom above its
+    /// maximum valence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::{Smiles, TransactionError};
+    ///
+    /// let mut smiles: Smiles = "c1ccccc1C(=O)O".parse()?;
+    /// smiles.set_name("benzoic acid");
+    /// smiles.transaction(|tx| {
+    ///     tx.try_apply(|smiles| smiles.kekulize())?;
+    ///     tx.apply(|smiles| smiles.canonicalize());
+    ///     Ok::<_, smiles_parser::prelude::KekulizationError>(())
+    /// })?;
+    /// assert!(smiles.nodes().iter().all(|atom| !atom.aromatic()));
+    /// assert_eq!(smiles.name(), Some("benzoic acid"));
+    ///
+    /// let before = smiles.clone();
+    /// let error = smiles
+    ///     .transaction(|tx| {
+    ///         tx.smiles_mut().set_name("pentavalent carbon");
+    ///         tx.apply(|_| "CC(C)(C)(C)C".parse().unwrap());
+    ///         Ok::<_, core::convert::Infallible>(())
+    ///     })
+    ///     .unwrap_err();
+    /// assert!(matches!(error, TransactionError::InvalidValence { atom: 1, valence: 5, .. }));
+    /// assert_eq!(smiles, before);
+    /// assert_eq!(smiles.name(), Some("benzoic acid"));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn transaction<E>(
+        &mut self,
+        edits: impl FnOnce(&mut Transaction<AtomPolicy>) -> Result<(), E>,
+    ) -> Result<(), TransactionError<E>> {
+        let mut transaction = Transaction { working: self.clone() };
+        edits(&mut transaction).map_err(TransactionError::Aborted)?;
+        transaction.working.check_valences()?;
+        *self = transaction.working;
+        Ok(())
+    }
+
+    fn check_valences<E>(&self) -> Result<(), TransactionError<E>> {
+        for (atom, node) in self.nodes().iter().enumerate() {
+            let Some(element) = node.element() else {
+                continue;
+            };
+            let Some(&max_valence) = element.valences_at_charge(node.charge_value()).iter().max()
+            else {
+                continue;
+            };
+            let valence = self.total_valence(atom);
+            if valence > max_valence {
+                return Err(TransactionError::InvalidValence { atom, valence, max_valence });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_transactions_leave_the_graph_untouched() {
+        let mut smiles: Smiles = "CCO".parse().unwrap();
+        let result = smiles.transaction(|tx| {
+            tx.smiles_mut().set_property("step", "1");
+            tx.apply(Smiles::canonicalize);
+            Err("second step failed")
+        });
+        assert_eq!(result, Err(TransactionError::Aborted("second step failed")));
+        assert_eq!(smiles.render(), "CCO");
+        assert_eq!(smiles.property("step"), None);
+
+        smiles
+            .transaction(|tx| {
+                tx.smiles_mut().set_property("step", "1");
+                tx.apply(|_| "[N+](=O)[O-]".parse().unwrap());
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        assert_eq!(smiles.property("step"), Some("1"));
+        assert_eq!(smiles.nodes().len(), 3);
+    }
+}
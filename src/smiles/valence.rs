@@ -0,0 +1,185 @@
+//! Per-element valence sets used to validate parsed graphs.
+
+use alloc::vec::Vec;
+
+use elements_rs::{ChargedValences, Element};
+use thiserror::Error;
+
+use super::{Smiles, SmilesAtomPolicy};
+
+/// Neutral valences of the standard model, following the organic subset of
+/// the specification with the hypervalent states of phosphorus, sulfur and
+/// iodine.
+const STANDARD_VALENCES: [(Element, &[u8]); 10] = [
+    (Element::B, &[3]),
+    (Element::C, &[4]),
+    (Element::N, &[3]),
+    (Element::O, &[2]),
+    (Element::P, &[3, 5]),
+    (Element::S, &[2, 4, 6]),
+    (Element::F, &[1]),
+    (Element::Cl, &[1]),
+    (Element::Br, &[1]),
+    (Element::I, &[1, 3, 5]),
+];
+
+/// Error returned by [`Smiles::validate_valences`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("atom {atom} has valence {valence}, above the maximum of {max_valence}")]
+pub struct ValenceError {
+    /// Id of the first offending atom.
+    pub atom: usize,
+    /// Total valence of the atom, implicit hydrogens included.
+    pub valence: u8,
+    /// Largest valence the model allows for the element at the atom's charge.
+    pub max_valence: u8,
+}
+
+/// The valences each element may take, used by [`Smiles::validate_valences`].
+///
+/// The default model allows B 3, C 4, N 3, O 2, P 3 and 5, S 2, 4 and 6, I 1,
+/// 3 and 5, and 1 for the other halogens, so sulfoxides, sulfones and
+/// phosphates validate while pentavalent carbon does not. Any element can be
+/// given its own set with [`ValenceModel::with_valences`]. Elements the model
+/// does not list, and charged atoms, use the valences of `elements-rs`.
+///
+/// # Examples
+///
+/// ```
+/// use elements_rs::Element;
+/// use smiles_parser::prelude::{Smiles, ValenceModel};
+///
+/// let nitro: Smiles = "CN(=O)=O".parse()?;
+/// assert!(nitro.validate_valences(&ValenceModel::default()).is_err());
+///
+/// let model = ValenceModel::default().with_valences(Element::N, &[3, 5]);
+/// assert_eq!(model.valences(Element::N, 0), &[3, 5]);
+/// assert!(nitro.validate_valences(&model).is_ok());
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValenceModel {
+    overrides: Vec<(Element, Vec<u8>)>,
+}
+
+impl Default for ValenceModel {
+    fn default() -> Self {
+        Self {
+            overrides: STANDARD_VALENCES
+                .iter()
+                .map(|&(element, valences)| (element, valences.to_vec()))
+                .collect(),
+        }
+    }
+}
+
+impl ValenceModel {
+    /// Returns the model with the neutral valences of `element` replaced by
+    /// `valences`.
+    #[must_use]
+    pub fn with_valences(mut self, element: Element, valences: &[u8]) -> Self {
+        let mut valences = valences.to_vec();
+        valences.sort_unstable();
+        valences.dedup();
+        match self.overrides.iter_mut().find(|(candidate, _)| *candidate == element) {
+            Some((_, existing)) => *existing = valences,
+            None => self.overrides.push((element, valences)),
+        }
+        self
+    }
+
+    /// Returns the valences `element` may take at `charge`.
+    #[must_use]
+    pub fn valences(&self, element: Element, charge: i8) -> &[u8] {
+        if charge == 0
+            && let Some((_, valences)) =
+                self.overrides.iter().find(|(candidate, _)| *candidate == element)
+        {
+            return valences;
+        }
+        element.valences_at_charge(charge)
+    }
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Checks that no atom exceeds the largest valence `model` allows for its
+    /// element at its formal charge.
+    ///
+    /// Valences are counted as in [`Smiles::total_valence`], with aromatic
+    /// bonds as single bonds. Valences below the largest allowed one, as in
+    /// radicals, are accepted. Wildcard atoms and atoms whose element has no
+    /// valences at their charge are not checked.
+    ///
+    /// # Errors
+    /// Returns a [`ValenceError`] for the first atom above its maximum
+    /// valence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::{Smiles, ValenceError, ValenceModel};
+    ///
+    /// let model = ValenceModel::default();
+    /// for source in ["CS(=O)C", "CS(=O)(=O)C", "OP(=O)(O)O"] {
+    ///     assert!(source.parse::<Smiles>()?.validate_valences(&model).is_ok());
+    /// }
+    /// assert_eq!(
+    ///     "CC(C)(C)(C)C".parse::<Smiles>()?.validate_valences(&model),
+    ///     Err(ValenceError { atom: 1, valence: 5, max_valence: 4 })
+    /// );
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    pub fn validate_valences(&self, model: &ValenceModel) -> Result<(), ValenceError> {
+        for (atom, node) in self.nodes().iter().enumerate() {
+            let Some(element) = node.element() else {
+                continue;
+            };
+            let Some(&max_valence) = model.valences(element, node.charge_value()).iter().max()
+            else {
+                continue;
+            };
+            let valence = self.total_valence(atom);
+            if valence > max_valence {
+                return Err(ValenceError { atom, valence, max_valence });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_sets_accept_hypervalent_sulfur_and_phosphorus() {
+        let model = ValenceModel::default();
+        for source in ["CS(=O)C", "CS(=O)(=O)C", "FS(F)(F)(F)(F)F", "ClP(Cl)(Cl)(Cl)Cl", "c1ccsc1"]
+        {
+            let smiles: Smiles = source.parse().unwrap();
+            assert_eq!(smiles.validate_valences(&model), Ok(()), "{source}");
+        }
+
+        let hypervalent_nitrogen: Smiles = "CN(=O)=O".parse().unwrap();
+        assert_eq!(
+            hypervalent_nitrogen.validate_valences(&model),
+            Err(ValenceError { atom: 1, valence: 5, max_valence: 3 })
+        );
+        let charge_separated: Smiles = "C[N+](=O)[O-]".parse().unwrap();
+        assert_eq!(charge_separated.validate_valences(&model), Ok(()));
+    }
+
+    #[test]
+    fn element_sets_can_be_replaced() {
+        let divalent_sulfur = ValenceModel::default().with_valences(Element::S, &[2]);
+        let sulfone: Smiles = "CS(=O)(=O)C".parse().unwrap();
+        assert_eq!(
+            sulfone.validate_valences(&divalent_sulfur),
+            Err(ValenceError { atom: 1, valence: 6, max_valence: 2 })
+        );
+
+        let model = ValenceModel::default().with_valences(Element::Se, &[6, 2, 4, 2]);
+        assert_eq!(model.valences(Element::Se, 0), &[2, 4, 6]);
+        assert_eq!(model.valences(Element::C, 0), &[4]);
+    }
+}
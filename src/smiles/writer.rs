@@ -0,0 +1,402 @@
+//! Output switches for rendering a [`Smiles`] graph, mirroring `RDKit`'s
+//! `isomericSmiles`, `kekuleSmiles` and `allHsExplicit`-style options, and
+//! highlighting of atom subsets in the written string.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+use geometric_traits::traits::SparseValuedMatrixRef;
+
+use super::{BondMatrixBuilder, KekulizationError, Smiles, SmilesAtomPolicy, emitter};
+use crate::atom::Atom;
+
+/// Controls which features [`Smiles::render_with_options`] writes.
+///
+/// The default writes everything the graph carries in aromatic form, which is
+/// exactly what [`Smiles::render`] produces.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::prelude::{Smiles, WriterOptions};
+///
+/// let smiles: Smiles = "[13CH3][C@H](O)c1ccccc1".parse()?;
+/// let options = WriterOptions { stereo: false, isotopes: false, ..WriterOptions::default() };
+///
+/// let rendered = smiles.render_with_options(&options).unwrap();
+/// assert!(!rendered.contains('@'));
+/// assert!(!rendered.contains("13"));
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct WriterOptions {
+    /// Write tetrahedral chirality and directional `/` `\` bonds.
+    pub stereo: bool,
+    /// Write isotope mass numbers.
+    pub isotopes: bool,
+    /// Write atom classes such as the `:1` in `[CH3:1]`.
+    pub atom_classes: bool,
+    /// Write the localized Kekule form instead of lowercase aromatic atoms.
+    pub kekule: bool,
+    /// Write every hydrogen as its own `[H]` atom.
+    pub explicit_hydrogens: bool,
+    /// Start each component at its most heteroatom-rich terminal atom and
+    /// write smaller branches before larger ones. The output is deterministic
+    /// for a given graph but is not canonical.
+    pub pretty: bool,
+}
+
+impl WriterOptions {
+    /// Options equivalent to `RDKit`'s `isomericSmiles=False`: no stereo and
+    /// no isotopes, everything else as in the default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::{Smiles, WriterOptions};
+    ///
+    /// let smiles: Smiles = "C/C=C/[13CH3]".parse()?;
+    /// assert_eq!(smiles.render_with_options(&WriterOptions::non_isomeric()).unwrap(), "CC=CC");
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn non_isomeric() -> Self {
+        Self {
+            stereo: false,
+            isotopes: false,
+            atom_classes: true,
+            kekule: false,
+            explicit_hydrogens: false,
+            pretty: false,
+        }
+    }
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        Self {
+            stereo: true,
+            isotopes: true,
+            atom_classes: true,
+            kekule: false,
+            explicit_hydrogens: false,
+            pretty: false,
+        }
+    }
+}
+
+/// How [`Smiles::render_highlighted`] marks the highlighted atoms.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HighlightStyle {
+    /// Write every highlighted atom in brackets with the given atom class,
+    /// replacing any class it already had. Viewers that color atom maps show
+    /// the highlight, and the class survives parsing the string back.
+    AtomClass(u16),
+    /// Leave the SMILES untouched and append a CXSMILES `atomProp` block
+    /// that sets `highlight` to `1` on every highlighted atom, using the
+    /// atom positions of the written string.
+    CxSmilesAtomProperty,
+}
+
+impl<AtomPolicy: SmilesAtomPolicy> Smiles<AtomPolicy> {
+    /// Renders the graph as SMILES with the features selected by `options`.
+    ///
+    /// Features that are switched off are removed from a copy of the graph
+    /// before rendering, so atoms that only needed brackets for a dropped
+    /// isotope, class or chirality tag are written in the organic subset.
+    ///
+    /// # Errors
+    /// Returns a [`KekulizationError`] when [`WriterOptions::kekule`] is set
+    /// and the aromatic system has no Kekule form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::{Smiles, WriterOptions};
+    ///
+    /// let smiles: Smiles = "c1ccccc1[CH2:7]O".parse()?;
+    /// let options = WriterOptions { kekule: true, atom_classes: false, ..WriterOptions::default() };
+    ///
+    /// let rendered = smiles.render_with_options(&options).unwrap();
+    /// assert!(!rendered.contains('c'));
+    /// assert!(!rendered.contains(':'));
+    /// assert_eq!(smiles.render_with_options(&WriterOptions::default()).unwrap(), smiles.render());
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    pub fn render_with_options(
+        &self,
+        options: &WriterOptions,
+    ) -> Result<String, KekulizationError> {
+        let mut smiles = if options.kekule { self.kekulize()? } else { self.clone() };
+        if !(options.stereo && options.isotopes && options.atom_classes) {
+            smiles = smiles.with_writer_features_removed(options);
+        }
+        if options.explicit_hydrogens {
+            smiles = smiles.with_explicit_hydrogens();
+        }
+        Ok(if options.pretty { emitter::emit_pretty(&smiles) } else { smiles.render() })
+    }
+
+    /// Renders the graph as SMILES with the atoms in `atom_ids` marked as
+    /// described by `style`.
+    ///
+    /// # Panics
+    /// Panics if any id in `atom_ids` is not a valid atom index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::prelude::{HighlightStyle, Smiles};
+    ///
+    /// let smiles: Smiles = "CCO".parse()?;
+    /// assert!(smiles.render_highlighted(&[2], HighlightStyle::AtomClass(1)).contains("[OH:1]"));
+    ///
+    /// let methanol: Smiles = "CO".parse()?;
+    /// assert_eq!(
+    ///     methanol.render_highlighted(&[0, 1], HighlightStyle::CxSmilesAtomProperty),
+    ///     format!("{} |atomProp:0.highlight.1:1.highlight.1|", methanol.render())
+    /// );
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn render_highlighted(&self, atom_ids: &[usize], style: HighlightStyle) -> String {
+        let mut highlighted = vec![false; self.atom_nodes.len()];
+        for &atom_id in atom_ids {
+            assert!(
+                atom_id < self.atom_nodes.len(),
+                "invalid atom index {atom_id} for graph with {} atoms",
+                self.atom_nodes.len()
+            );
+            highlighted[atom_id] = true;
+        }
+
+        match style {
+            HighlightStyle::AtomClass(class) => {
+                self.with_highlight_class(&highlighted, class).render()
+            }
+            HighlightStyle::CxSmilesAtomProperty => {
+                let (mut rendered, order) = emitter::emit_with_traversal_order(self);
+                let mut separator = " |atomProp:";
+                for (position, atom_id) in order.into_iter().enumerate() {
+                    if highlighted[atom_id] {
+                        write!(rendered, "{separator}{position}.highlight.1")
+                            .unwrap_or_else(|_| unreachable!("writing to String cannot fail"));
+                        separator = ":";
+                    }
+                }
+                if separator == ":" {
+                    rendered.push('|');
+                }
+                rendered
+            }
+        }
+    }
+
+    /// Returns a copy where every highlighted atom is a bracket atom carrying
+    /// `class`, with its implicit hydrogens written explicitly.
+    fn with_highlight_class(&self, highlighted: &[bool], class: u16) -> Self {
+        let mut implicit_hydrogen_cache = self.implicit_hydrogen_cache.clone();
+        let atom_nodes = self
+            .atom_nodes
+            .iter()
+            .zip(highlighted)
+            .enumerate()
+            .map(|(atom_id, (&atom, &is_highlighted))| {
+                if !is_highlighted {
+                    return atom;
+                }
+                let hydrogens = atom.hydrogen_count() + implicit_hydrogen_cache[atom_id];
+                implicit_hydrogen_cache[atom_id] = 0;
+                Atom::new_bracket(
+                    atom.symbol(),
+                    atom.isotope_mass_number(),
+                    atom.aromatic(),
+                    hydrogens,
+                    atom.charge(),
+                    class,
+                    atom.chirality(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        Self::from_bond_matrix_parts_with_sidecars(
+            atom_nodes,
+            self.bond_matrix.clone(),
+            self.parsed_stereo_neighbors.clone(),
+            implicit_hydrogen_cache,
+            None,
+        )
+    }
+
+    /// Returns a copy without the atom and bond features that `options`
+    /// switches off.
+    pub(crate) fn with_writer_features_removed(&self, options: &WriterOptions) -> Self {
+        let atom_nodes = self
+            .atom_nodes
+            .iter()
+            .map(|&atom| {
+                let atom = if options.stereo { atom } else { atom.without_chirality() };
+                let atom = if options.isotopes { atom } else { atom.without_isotope() };
+                if options.atom_classes { atom } else { atom.without_class() }
+            })
+            .collect::<Vec<_>>();
+
+        let (bond_matrix, parsed_stereo_neighbors) = if options.stereo {
+            (self.bond_matrix.clone(), self.parsed_stereo_neighbors.clone())
+        } else {
+            let mut builder = BondMatrixBuilder::default();
+            for ((row, column), entry) in self.bond_matrix.sparse_entries() {
+                if row >= column {
+                    continue;
+                }
+                let descriptor = entry.descriptor();
+                let flattened = descriptor.with_bond(descriptor.bond().without_direction());
+                builder
+                    .push_edge_with_descriptor(row, column, flattened, None)
+                    .unwrap_or_else(|_| unreachable!("flattening bonds preserves a simple graph"));
+            }
+            (builder.finish(atom_nodes.len()), vec![Vec::new(); atom_nodes.len()])
+        };
+
+        Self::from_bond_matrix_parts_with_sidecars(
+            atom_nodes,
+            bond_matrix,
+            parsed_stereo_neighbors,
+            self.implicit_hydrogen_cache.clone(),
+            None,
+        )
+        .canonicalization_spelling_normal_form()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(smiles: &str, options: WriterOptions) -> String {
+        smiles.parse::<Smiles>().unwrap().render_with_options(&options).unwrap()
+    }
+
+    #[test]
+    fn default_options_match_plain_render() {
+        for input in ["C[C@H](N)O", "C/C=C\\C", "[13CH4]", "[CH3:2]O", "c1ccccc1"] {
+            let smiles: Smiles = input.parse().unwrap();
+            assert_eq!(
+                smiles.render_with_options(&WriterOptions::default()).unwrap(),
+                smiles.render(),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn stereo_can_be_dropped_independently_of_isotopes() {
+        let options = WriterOptions { stereo: false, ..WriterOptions::default() };
+        let rendered = render("[13CH3][C@H](N)/C=C/C", options);
+
+        assert!(!rendered.contains('@'));
+        assert!(!rendered.contains('/'));
+        assert!(rendered.contains("[13CH3]"));
+    }
+
+    #[test]
+    fn isotopes_can_be_dropped_independently_of_stereo() {
+        let options = WriterOptions { isotopes: false, ..WriterOptions::default() };
+        let rendered = render("[13CH3][C@H](N)O", options);
+
+        assert!(rendered.contains('@'));
+        assert!(!rendered.contains("13"));
+    }
+
+    #[test]
+    fn dropping_classes_collapses_brackets() {
+        let options = WriterOptions { atom_classes: false, ..WriterOptions::default() };
+
+        assert_eq!(render("[CH3:1][OH:2]", options), "CO");
+        assert_eq!(render("[NH4+:3]", options), "[NH4+]");
+    }
+
+    #[test]
+    fn kekule_output_has_no_aromatic_atoms() {
+        let options = WriterOptions { kekule: true, ..WriterOptions::default() };
+        let rendered = render("c1ccncc1", options);
+
+        assert!(rendered.chars().all(|character| !character.is_ascii_lowercase()));
+        assert_eq!(rendered.matches('=').count(), 3);
+    }
+
+    #[test]
+    fn kekule_output_reports_impossible_localization() {
+        let smiles: Smiles = "c1cccc1".parse().unwrap();
+        let options = WriterOptions { kekule: true, ..WriterOptions::default() };
+
+        assert!(smiles.render_with_options(&options).is_err());
+    }
+
+    #[test]
+    fn pretty_output_starts_at_heteroatom_terminal() {
+        let options = WriterOptions { pretty: true, ..WriterOptions::default() };
+
+        assert_eq!(render("CCO", options), "OCC");
+        assert_eq!(render("CC(C)(C)CCN", options), "NCCC(C)(C)C");
+    }
+
+    #[test]
+    fn pretty_output_writes_smaller_branches_first() {
+        let options = WriterOptions { pretty: true, ..WriterOptions::default() };
+        let rendered = render("OC(CCCCC)C", options);
+
+        assert!(rendered.starts_with("OC(C)"), "{rendered}");
+        assert_eq!(render(&rendered, options), rendered);
+    }
+
+    #[test]
+    fn highlight_classes_bracket_organic_atoms_with_their_hydrogens() {
+        let smiles: Smiles = "c1ccccc1CN".parse().unwrap();
+        let rendered = smiles.render_highlighted(&[6, 7], HighlightStyle::AtomClass(5));
+
+        assert!(rendered.contains("[CH2:5]"), "{rendered}");
+        assert!(rendered.contains("[NH2:5]"), "{rendered}");
+        let reparsed: Smiles = rendered.parse().unwrap();
+        assert_eq!(reparsed.nodes().iter().filter(|atom| atom.class() == 5).count(), 2);
+    }
+
+    #[test]
+    fn highlight_properties_use_written_atom_positions() {
+        let smiles: Smiles = "Cl[13CH2]C(Br)[NH3+]".parse().unwrap();
+        let rendered = smiles.render_highlighted(&[4], HighlightStyle::CxSmilesAtomProperty);
+        let (body, extension) = rendered.split_once(' ').unwrap();
+
+        // Atoms are numbered in the order they are written, so the index of
+        // the nitrogen in the reparsed body is its written position, which
+        // the bracket before it keeps apart from its byte offset.
+        let written: Smiles = body.parse().unwrap();
+        let position = written
+            .nodes()
+            .iter()
+            .position(|atom| atom.element() == Some(elements_rs::Element::N))
+            .unwrap();
+        assert_ne!(body.find('N'), Some(position));
+        assert_eq!(extension, format!("|atomProp:{position}.highlight.1|"));
+        assert_eq!(
+            smiles.render_highlighted(&[], HighlightStyle::CxSmilesAtomProperty),
+            smiles.render()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid atom index 3 for graph with 3 atoms")]
+    fn highlight_rejects_invalid_atom_ids() {
+        let smiles: Smiles = "CCO".parse().unwrap();
+        let _ = smiles.render_highlighted(&[3], HighlightStyle::AtomClass(1));
+    }
+
+    #[test]
+    fn explicit_hydrogens_are_written_as_atoms() {
+        let options = WriterOptions { explicit_hydrogens: true, ..WriterOptions::default() };
+
+        assert_eq!(render("C", options).matches("[H]").count(), 4);
+    }
+}
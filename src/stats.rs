@@ -0,0 +1,205 @@
+//! Counters for locating pathological inputs in large corpora.
+//!
+//! [`parse_with_stats`] parses one SMILES like [`str::parse`] and also returns
+//! a [`ParseStats`] with the tokens seen by kind, the atoms seen by element and
+//! the time spent. Statistics of many parses are aggregated with
+//! [`ParseStats::merge`], and [`ParseStats::slowest`] points at the input that
+//! took longest.
+//!
+//! # Examples
+//!
+//! ```
+//! use elements_rs::Element;
+//! use smiles_parser::{
+//!     stats::{ParseStats, parse_with_stats},
+//!     token::TokenKind,
+//! };
+//!
+//! let mut total = ParseStats::default();
+//! for input in ["CCO", "c1ccccc1", "C(C"] {
+//!     let (_result, stats) = parse_with_stats(input);
+//!     total.merge(&stats);
+//! }
+//!
+//! assert_eq!(total.parses(), 3);
+//! assert_eq!(total.failures(), 1);
+//! assert_eq!(total.atoms(Some(Element::C)), 10);
+//! assert_eq!(total.tokens(TokenKind::RingClosure), 2);
+//! ```
+
+use core::time::Duration;
+use std::time::Instant;
+
+use elements_rs::Element;
+use hashbrown::HashMap;
+
+use crate::{
+    errors::SmilesErrorWithSpan,
+    parser::smiles_parser::parse_smiles_with_hook,
+    smiles::{ParserOptions, Smiles},
+    token::{Token, TokenKind},
+};
+
+/// Token, atom and timing counters of one or more parses.
+///
+/// Counts cover the tokens the parser consumed, so a failed parse contributes
+/// the tokens up to and including the one that failed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    tokens: HashMap<TokenKind, usize>,
+    atoms: HashMap<Option<Element>, usize>,
+    parses: usize,
+    failures: usize,
+    elapsed: Duration,
+    slowest: Option<(usize, Duration)>,
+}
+
+impl ParseStats {
+    /// Returns the number of tokens of the given kind.
+    #[inline]
+    #[must_use]
+    pub fn tokens(&self, kind: TokenKind) -> usize {
+        self.tokens.get(&kind).copied().unwrap_or_default()
+    }
+
+    /// Returns the number of atoms of the given element, with `None` counting
+    /// wildcard atoms.
+    #[inline]
+    #[must_use]
+    pub fn atoms(&self, element: Option<Element>) -> usize {
+        self.atoms.get(&element).copied().unwrap_or_default()
+    }
+
+    /// Returns an iterator over the elements seen and their atom counts, in
+    /// no particular order.
+    pub fn atoms_by_element(&self) -> impl Iterator<Item = (Option<Element>, usize)> + '_ {
+        self.atoms.iter().map(|(&element, &count)| (element, count))
+    }
+
+    /// Returns the number of parses counted.
+    #[inline]
+    #[must_use]
+    pub fn parses(&self) -> usize {
+        self.parses
+    }
+
+    /// Returns the number of parses that failed.
+    #[inline]
+    #[must_use]
+    pub fn failures(&self) -> usize {
+        self.failures
+    }
+
+    /// Returns the total time spent parsing.
+    #[inline]
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the position among the merged parses of the slowest one,
+    /// starting at zero, together with its duration.
+    #[inline]
+    #[must_use]
+    pub fn slowest(&self) -> Option<(usize, Duration)> {
+        self.slowest
+    }
+
+    /// Adds the counters of `other`, whose parses are numbered after the ones
+    /// already counted.
+    pub fn merge(&mut self, other: &Self) {
+        for (&kind, &count) in &other.tokens {
+            *self.tokens.entry(kind).or_default() += count;
+        }
+        for (&element, &count) in &other.atoms {
+            *self.atoms.entry(element).or_default() += count;
+        }
+        if let Some((index, elapsed)) = other.slowest
+            && self.slowest.is_none_or(|(_, slowest)| elapsed > slowest)
+        {
+            self.slowest = Some((self.parses + index, elapsed));
+        }
+        self.parses += other.parses;
+        self.failures += other.failures;
+        self.elapsed += other.elapsed;
+    }
+
+    fn count_token(&mut self, token: &Token) {
+        *self.tokens.entry(token.kind()).or_default() += 1;
+        if let Some(atom) = token.as_atom() {
+            *self.atoms.entry(atom.element()).or_default() += 1;
+        }
+    }
+}
+
+/// Parses `input` as a [`Smiles`] and returns the result together with the
+/// statistics of the parse.
+pub fn parse_with_stats(input: &str) -> (Result<Smiles, SmilesErrorWithSpan>, ParseStats) {
+    let mut stats = ParseStats::default();
+    let started = Instant::now();
+    let result = parse_smiles_with_hook(input, &ParserOptions::default(), |token| {
+        stats.count_token(&token.token());
+    });
+    let elapsed = started.elapsed();
+    stats.parses = 1;
+    stats.failures = usize::from(result.is_err());
+    stats.elapsed = elapsed;
+    stats.slowest = Some((0, elapsed));
+    (result, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::SmilesError;
+
+    #[test]
+    fn single_parse_counts_tokens_and_atoms() {
+        let (result, stats) = parse_with_stats("[13CH3]C(=O)[O-].[Na+]");
+
+        assert!(result.is_ok());
+        assert_eq!(stats.tokens(TokenKind::Atom), 5);
+        assert_eq!(stats.tokens(TokenKind::Bond), 1);
+        assert_eq!(stats.tokens(TokenKind::LeftParentheses), 1);
+        assert_eq!(stats.tokens(TokenKind::RightParentheses), 1);
+        assert_eq!(stats.tokens(TokenKind::NonBond), 1);
+        assert_eq!(stats.tokens(TokenKind::RingClosure), 0);
+        assert_eq!(stats.atoms(Some(Element::C)), 2);
+        assert_eq!(stats.atoms(Some(Element::O)), 2);
+        assert_eq!(stats.atoms(Some(Element::Na)), 1);
+        assert_eq!(stats.atoms(None), 0);
+        assert_eq!(stats.atoms_by_element().map(|(_, count)| count).sum::<usize>(), 5);
+        assert_eq!((stats.parses(), stats.failures()), (1, 0));
+        assert_eq!(stats.slowest(), Some((0, stats.elapsed())));
+    }
+
+    #[test]
+    fn failed_parses_count_tokens_up_to_the_error() {
+        let (result, stats) = parse_with_stats("CC)CCC");
+
+        assert_eq!(
+            result.map_err(|error| error.smiles_error()),
+            Err(SmilesError::UnexpectedRightParentheses)
+        );
+        assert_eq!(stats.tokens(TokenKind::Atom), 2);
+        assert_eq!(stats.tokens(TokenKind::RightParentheses), 1);
+        assert_eq!(stats.failures(), 1);
+    }
+
+    #[test]
+    fn merging_renumbers_the_slowest_parse() {
+        let mut first = ParseStats::default();
+        first.merge(&parse_with_stats("CC").1);
+        first.merge(&parse_with_stats("CCC").1);
+        let mut second = parse_with_stats("C").1;
+        second.slowest = Some((0, Duration::from_secs(1)));
+        second.elapsed = Duration::from_secs(1);
+
+        first.merge(&second);
+
+        assert_eq!(first.parses(), 3);
+        assert_eq!(first.slowest(), Some((2, Duration::from_secs(1))));
+        assert_eq!(first.tokens(TokenKind::Atom), 6);
+        assert!(first.elapsed() >= Duration::from_secs(1));
+    }
+}
@@ -1,10 +1,19 @@
 //! Represents tokens used in parsing SMILES strings.
 
-use core::ops::Range;
+use alloc::{boxed::Box, string::String};
+use core::{
+    fmt::{self, Write},
+    ops::Range,
+    str::from_utf8,
+};
+
+use smallvec::SmallVec;
 
 use crate::{
     atom::Atom,
     bond::{BondDescriptor, ring_num::RingNum},
+    errors::SmilesErrorWithSpan,
+    parser::token_iter::TokenIter,
 };
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -22,9 +31,14 @@ pub enum TokenKind {
     RightParentheses,
     /// Ring closure token
     RingClosure,
+    /// Bracket-delimited text that is not a valid bracket atom
+    UnknownBracket,
+    /// Text that starts no token, or an unclosed bracket; only produced by
+    /// [`lex`]
+    Invalid,
 }
 
-#[derive(Copy, Debug, PartialEq, Clone, Eq, Hash)]
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
 /// Represents a token in a molecular formula.
 pub enum Token {
     /// Represented with a `.`
@@ -41,9 +55,26 @@ pub enum Token {
     RightParentheses,
     /// Ring number markers occur outside of `[]` and may be of type `%` and
     /// `0-99`, and `%` may be omitted.
-    RingClosure(RingNum),
+    RingClosure {
+        /// The ring label.
+        ring_num: RingNum,
+        /// The bond written right before the label, as the `=` of `C=1`,
+        /// which is also yielded as its own [`Token::Bond`].
+        bond: Option<BondDescriptor>,
+        /// On the label closing a ring, the index in the token stream of the
+        /// label that opened it; `None` on the opening label.
+        partner: Option<usize>,
+    },
+    /// Bracket-delimited text that is not a valid bracket atom, e.g. a vendor
+    /// extension such as `[R1]`, with the text, brackets included, and its
+    /// span in the input. Only [`tokenize_permissive`] produces it.
+    UnknownBracket(String, Range<usize>),
 }
 
+/// Inline storage for the text of a token, spilling to the heap only for
+/// bracket atoms longer than any common spelling.
+type TokenText = SmallVec<[u8; 16]>;
+
 #[derive(Debug, PartialEq, Clone, Eq, Hash)]
 /// A parsed token and its relative location in the string
 pub struct TokenWithSpan {
@@ -51,6 +82,11 @@ pub struct TokenWithSpan {
     token: Token,
     /// The starting to ending points of the token in the string
     span: Range<usize>,
+    /// The text the token was read from, empty for tokens built with
+    /// [`TokenWithSpan::new`]
+    text: TokenText,
+    /// The field spans of a bracket atom, recorded by the tokenizer
+    bracket_field_spans: Option<RecordedFieldSpans>,
 }
 
 impl TokenWithSpan {
@@ -69,7 +105,47 @@ impl TokenWithSpan {
     /// ```
     #[must_use]
     pub fn new(token: Token, start: usize, end: usize) -> Self {
-        Self { token, span: start..end }
+        Self { token, span: start..end, text: TokenText::new(), bracket_field_spans: None }
+    }
+
+    /// Creates the token read from `text`, which starts at `start` in the
+    /// input.
+    pub(crate) fn read(token: Token, start: usize, text: &[u8]) -> Self {
+        Self {
+            token,
+            span: start..start + text.len(),
+            text: TokenText::from_slice(text),
+            bracket_field_spans: None,
+        }
+    }
+
+    /// Attaches the field spans the tokenizer read a bracket atom with.
+    #[must_use]
+    pub(crate) fn with_bracket_field_spans(mut self, spans: Option<BracketFieldSpans>) -> Self {
+        self.bracket_field_spans =
+            spans.map(|spans| RecordedFieldSpans::new(spans, self.span.start));
+        self
+    }
+
+    /// Returns the text the token was read from, or `None` for a token built
+    /// with [`TokenWithSpan::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::token::{Token, TokenWithSpan, tokenize_permissive};
+    ///
+    /// let tokens = tokenize_permissive("[CH1+1]C").collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(tokens[0].text(), Some("[CH1+1]"));
+    /// assert_eq!(TokenWithSpan::new(Token::NonBond, 0, 1).text(), None);
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn text(&self) -> Option<&str> {
+        (!self.text.is_empty()).then(|| {
+            from_utf8(&self.text)
+                .unwrap_or_else(|_| unreachable!("tokens end at ASCII character boundaries"))
+        })
     }
     /// Returns the token
     ///
@@ -86,7 +162,7 @@ impl TokenWithSpan {
     /// ```
     #[must_use]
     pub fn token(&self) -> Token {
-        self.token
+        self.token.clone()
     }
     /// Returns the token kind without the payload.
     ///
@@ -164,6 +240,114 @@ impl TokenWithSpan {
     pub fn is_bond(&self) -> bool {
         self.token_kind() == TokenKind::Bond
     }
+
+    /// Returns where each field of a bracket atom token is written in the
+    /// string the token was read from, or `None` for any other token and for
+    /// tokens built with [`TokenWithSpan::new`].
+    ///
+    /// The tokenizer records the spans while it reads the atom, so this does
+    /// not parse the token again. Fields written out of the specification
+    /// order, as accepted by
+    /// [`ParserOptions::lenient_bracket_order`](crate::ParserOptions::lenient_bracket_order),
+    /// get their spans too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::token::tokenize_permissive;
+    ///
+    /// let input = "C[13C@@H2+:5]";
+    /// let tokens = tokenize_permissive(input).collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(tokens[0].bracket_field_spans(), None);
+    ///
+    /// let spans = tokens[1].bracket_field_spans().unwrap();
+    /// assert_eq!(spans.isotope, Some(2..4));
+    /// assert_eq!(&input[spans.symbol], "C");
+    /// assert_eq!(spans.chirality, Some(5..7));
+    /// assert_eq!(spans.hydrogens, Some(7..9));
+    /// assert_eq!(spans.charge, Some(9..10));
+    /// assert_eq!(spans.class, Some(10..12));
+    /// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+    /// ```
+    #[must_use]
+    pub fn bracket_field_spans(&self) -> Option<BracketFieldSpans> {
+        self.bracket_field_spans.as_ref().map(|spans| spans.spans(self.span.start))
+    }
+}
+
+/// The field spans of a bracket atom token as kept in [`TokenWithSpan`].
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+enum RecordedFieldSpans {
+    /// The isotope, symbol, chirality, hydrogen count, charge and class
+    /// spans as offsets from the `[`, with `[0, 0]` for an absent field.
+    Offsets([[u16; 2]; 6]),
+    /// The spans of an atom too long for `u16` offsets, which only thousands
+    /// of leading zeros in its class can make.
+    Spans(Box<BracketFieldSpans>),
+}
+
+impl RecordedFieldSpans {
+    /// Records the spans of the bracket atom starting at `start`.
+    fn new(spans: BracketFieldSpans, start: usize) -> Self {
+        let offsets = |range: &Range<usize>| {
+            Some([u16::try_from(range.start - start).ok()?, u16::try_from(range.end - start).ok()?])
+        };
+        let field = |range: &Option<Range<usize>>| range.as_ref().map_or(Some([0, 0]), offsets);
+        let recorded = || {
+            Some([
+                field(&spans.isotope)?,
+                offsets(&spans.symbol)?,
+                field(&spans.chirality)?,
+                field(&spans.hydrogens)?,
+                field(&spans.charge)?,
+                field(&spans.class)?,
+            ])
+        };
+        match recorded() {
+            Some(offsets) => Self::Offsets(offsets),
+            None => Self::Spans(Box::new(spans)),
+        }
+    }
+
+    /// Returns the spans of the bracket atom starting at `start`.
+    fn spans(&self, start: usize) -> BracketFieldSpans {
+        match self {
+            Self::Offsets([isotope, symbol, chirality, hydrogens, charge, class]) => {
+                let span =
+                    |[from, to]: [u16; 2]| start + usize::from(from)..start + usize::from(to);
+                let field = |offsets: [u16; 2]| (offsets != [0, 0]).then(|| span(offsets));
+                BracketFieldSpans {
+                    isotope: field(*isotope),
+                    symbol: span(*symbol),
+                    chirality: field(*chirality),
+                    hydrogens: field(*hydrogens),
+                    charge: field(*charge),
+                    class: field(*class),
+                }
+            }
+            Self::Spans(spans) => (**spans).clone(),
+        }
+    }
+}
+
+/// Spans of the fields of a bracket atom, as offsets into the string it was
+/// read from; see [`TokenWithSpan::bracket_field_spans`].
+///
+/// Each optional field is `None` when the atom does not write it.
+#[derive(Debug, Default, PartialEq, Clone, Eq, Hash)]
+pub struct BracketFieldSpans {
+    /// The isotope mass number, e.g. `13` in `[13C@@H2+:5]`
+    pub isotope: Option<Range<usize>>,
+    /// The element symbol or `*`, e.g. `C` in `[13C@@H2+:5]`
+    pub symbol: Range<usize>,
+    /// The chirality, e.g. `@@` in `[13C@@H2+:5]`
+    pub chirality: Option<Range<usize>>,
+    /// The hydrogen count, e.g. `H2` in `[13C@@H2+:5]`
+    pub hydrogens: Option<Range<usize>>,
+    /// The charge, e.g. `+` in `[13C@@H2+:5]`
+    pub charge: Option<Range<usize>>,
+    /// The atom class, e.g. `:5` in `[13C@@H2+:5]`
+    pub class: Option<Range<usize>>,
 }
 
 impl Token {
@@ -182,28 +366,596 @@ impl Token {
     /// ```
     #[inline]
     #[must_use]
-    pub fn kind(self) -> TokenKind {
+    pub fn kind(&self) -> TokenKind {
         match self {
             Self::NonBond => TokenKind::NonBond,
             Self::Atom(_) => TokenKind::Atom,
             Self::Bond(_) => TokenKind::Bond,
             Self::LeftParentheses => TokenKind::LeftParentheses,
             Self::RightParentheses => TokenKind::RightParentheses,
-            Self::RingClosure(_) => TokenKind::RingClosure,
+            Self::RingClosure { .. } => TokenKind::RingClosure,
+            Self::UnknownBracket(..) => TokenKind::UnknownBracket,
+        }
+    }
+
+    /// Returns a ring closure token for `ring_num` with no bond or partner
+    /// recorded, as built by token-level rewrites.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{bond::ring_num::RingNum, token::Token};
+    ///
+    /// let ring_num = RingNum::try_new(3)?;
+    /// assert_eq!(
+    ///     Token::ring_closure(ring_num),
+    ///     Token::RingClosure { ring_num, bond: None, partner: None }
+    /// );
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn ring_closure(ring_num: RingNum) -> Self {
+        Self::RingClosure { ring_num, bond: None, partner: None }
+    }
+
+    /// Returns the atom of an atom token, or `None` for any other token.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{atom::Atom, token::Token};
+    ///
+    /// let atom = Atom::parse_bracket("[NH4+]")?.0;
+    /// assert_eq!(Token::from(atom).as_atom(), Some(atom));
+    /// assert_eq!(Token::NonBond.as_atom(), None);
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_atom(&self) -> Option<Atom> {
+        match self {
+            Self::Atom(atom) => Some(*atom),
+            _ => None,
+        }
+    }
+
+    /// Writes the SMILES spelling of the token to `target`.
+    ///
+    /// Bracket atoms are written in their conventional form, so spellings
+    /// that carry no information, such as the `1` of `[CH1]` or `[C+1]`, are
+    /// not reproduced. [`Token::UnknownBracket`] writes its text unchanged.
+    ///
+    /// # Errors
+    /// Returns the error raised by `target`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use smiles_parser::{bond::ring_num::RingNum, token::Token};
+    ///
+    /// let mut written = String::new();
+    /// Token::ring_closure(RingNum::try_new(12)?).write(&mut written).unwrap();
+    /// Token::LeftParentheses.write(&mut written).unwrap();
+    /// assert_eq!(written, "%12(");
+    /// # Ok::<(), smiles_parser::SmilesError>(())
+    /// ```
+    pub fn write<W: Write>(&self, target: &mut W) -> fmt::Result {
+        match self {
+            Self::NonBond => target.write_char('.'),
+            Self::Atom(atom) => atom.write_smiles(target),
+            Self::Bond(bond) => write!(target, "{bond}"),
+            Self::LeftParentheses => target.write_char('('),
+            Self::RightParentheses => target.write_char(')'),
+            Self::RingClosure { ring_num, .. } => write!(target, "{ring_num}"),
+            Self::UnknownBracket(text, _) => target.write_str(text),
+        }
+    }
+}
+
+impl From<Atom> for Token {
+    #[inline]
+    fn from(atom: Atom) -> Self {
+        Self::Atom(atom)
+    }
+}
+
+impl TryFrom<Token> for Atom {
+    /// The token, returned unchanged when it is not an atom.
+    type Error = Token;
+
+    #[inline]
+    fn try_from(token: Token) -> Result<Self, Self::Error> {
+        token.as_atom().ok_or(token)
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f)
+    }
+}
+
+/// Concatenates the spellings of `tokens`, reconstructing the exact text
+/// they were read from.
+///
+/// Every token read by the tokenizer is written back as its
+/// [`TokenWithSpan::text`], so redundant spellings such as `[CH1+1]` and
+/// `[Fe--]` survive. Tokens built with [`TokenWithSpan::new`] have no text
+/// and are written as by [`Token::write`].
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::{
+///     bond::Bond,
+///     token::{Token, TokenWithSpan, tokens_to_string},
+/// };
+///
+/// let tokens = [
+///     TokenWithSpan::new(Token::LeftParentheses, 0, 1),
+///     TokenWithSpan::new(Token::Bond(Bond::Double.into()), 1, 2),
+///     TokenWithSpan::new(Token::RightParentheses, 2, 3),
+/// ];
+/// assert_eq!(tokens_to_string(&tokens), "(=)");
+/// ```
+#[must_use]
+pub fn tokens_to_string(tokens: &[TokenWithSpan]) -> String {
+    let mut smiles = String::with_capacity(tokens.last().map_or(0, TokenWithSpan::end));
+    for token in tokens {
+        match token.text() {
+            Some(text) => smiles.push_str(text),
+            None => {
+                token
+                    .token
+                    .write(&mut smiles)
+                    .unwrap_or_else(|_| unreachable!("writing to String cannot fail"));
+            }
+        }
+    }
+    smiles
+}
+
+/// Checks that `tokens` tile `input`: the first span starts at `0`, each span
+/// ends where the next one starts, and the last one ends at `input.len()`.
+///
+/// Editor tooling maps tokens back onto the text through their spans, so the
+/// tokenizer guarantees this for every successfully tokenized input.
+///
+/// # Errors
+/// Returns the first byte offset at which the tiling breaks: a gap, an
+/// overlap, an empty span or an uncovered tail.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::token::{Token, TokenWithSpan, tokenize_permissive, verify_spans};
+///
+/// let input = "C[13CH2:1]%12.O";
+/// let tokens = tokenize_permissive(input).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(verify_spans(input, &tokens), Ok(()));
+///
+/// let gap = [TokenWithSpan::new(Token::NonBond, 1, 2)];
+/// assert_eq!(verify_spans("..", &gap), Err(0));
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+pub fn verify_spans(input: &str, tokens: &[TokenWithSpan]) -> Result<(), usize> {
+    let mut expected_start = 0;
+    for token in tokens {
+        if token.start() != expected_start || token.end() <= token.start() {
+            return Err(expected_start);
+        }
+        expected_start = token.end();
+    }
+    if expected_start == input.len() { Ok(()) } else { Err(expected_start) }
+}
+
+/// Tokenizes `input`, passes every token through `rewrite` and returns the
+/// rewritten string, without building the molecular graph.
+///
+/// Returning `None` drops the token. Tokens returned unchanged keep their
+/// original spelling; changed tokens are written as by [`Token::write`]. The
+/// result is not validated, so a rewrite that drops atoms or bonds may produce
+/// an invalid SMILES string.
+///
+/// # Errors
+/// Returns the tokenizer error if `input` contains an invalid token.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::token::{Token, rewrite_tokens};
+///
+/// let stripped = rewrite_tokens("[2H][CH2:1]O", |token| {
+///     Some(match token {
+///         Token::Atom(atom) => Token::Atom(atom.without_isotope().without_class()),
+///         other => other,
+///     })
+/// })?;
+/// assert_eq!(stripped, "[H][CH2]O");
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+pub fn rewrite_tokens<F>(input: &str, mut rewrite: F) -> Result<String, SmilesErrorWithSpan>
+where
+    F: FnMut(Token) -> Option<Token>,
+{
+    let mut output = String::with_capacity(input.len());
+    for token in TokenIter::from(input) {
+        let token = token?;
+        match rewrite(token.token()) {
+            Some(rewritten) if rewritten == token.token() => output.push_str(&input[token.span()]),
+            Some(rewritten) => {
+                rewritten
+                    .write(&mut output)
+                    .unwrap_or_else(|_| unreachable!("writing to String cannot fail"));
+            }
+            None => {}
         }
     }
+    Ok(output)
+}
+
+/// Tokenizes `input`, capturing closed bracket atoms that fail to parse as
+/// [`Token::UnknownBracket`] instead of stopping at an error.
+///
+/// Tooling that scans files with vendor extensions can log and skip such
+/// tokens. Every other error, including a `[` that is never closed, is
+/// reported as by the strict parser.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::token::{Token, tokenize_permissive};
+///
+/// let input = "C[R1]C";
+/// let tokens = tokenize_permissive(input).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(tokens[1].token(), Token::UnknownBracket("[R1]".into(), 1..5));
+/// assert_eq!(tokens[1].span(), 1..5);
+/// assert_eq!(tokens.len(), 3);
+/// # Ok::<(), smiles_parser::SmilesErrorWithSpan>(())
+/// ```
+pub fn tokenize_permissive(
+    input: &str,
+) -> impl Iterator<Item = Result<TokenWithSpan, SmilesErrorWithSpan>> + '_ {
+    TokenIter::permissive(input)
+}
+
+/// Splits `input` into token kinds and byte spans without validating it.
+///
+/// Unlike [`tokenize_permissive`], no element is looked up and no number is
+/// range-checked: every bracketed text is an atom, every letter outside
+/// brackets is an atom (`Cl` and `Br` spanning two letters), a `%` takes up
+/// to two following digits as a ring closure, and characters that start no
+/// token, including whitespace, become single-character
+/// [`TokenKind::Invalid`] tokens, as does an unclosed `[` together with the
+/// rest of the input. The spans always tile `input`, so the lexer suits
+/// syntax highlighting and cheap pre-screening of inputs that the full
+/// tokenizer would reject.
+///
+/// # Examples
+///
+/// ```
+/// use smiles_parser::token::{TokenKind, lex};
+///
+/// let kinds = lex("Cl[Xx]%1 (").map(|(kind, _)| kind).collect::<Vec<_>>();
+/// assert_eq!(
+///     kinds,
+///     [
+///         TokenKind::Atom,
+///         TokenKind::Atom,
+///         TokenKind::RingClosure,
+///         TokenKind::Invalid,
+///         TokenKind::LeftParentheses,
+///     ]
+/// );
+/// assert_eq!(lex("C[C").nth(1), Some((TokenKind::Invalid, 1..3)));
+/// ```
+pub fn lex(input: &str) -> impl Iterator<Item = (TokenKind, Range<usize>)> + '_ {
+    let bytes = input.as_bytes();
+    let mut position = 0;
+    core::iter::from_fn(move || {
+        let start = position;
+        let first = *bytes.get(start)?;
+        let (kind, len) = match first {
+            b'.' => (TokenKind::NonBond, 1),
+            b'(' => (TokenKind::LeftParentheses, 1),
+            b')' => (TokenKind::RightParentheses, 1),
+            b'-' | b'=' | b'#' | b'$' | b':' | b'/' | b'\\' => (TokenKind::Bond, 1),
+            b'0'..=b'9' => (TokenKind::RingClosure, 1),
+            b'%' => {
+                let digits = bytes[start + 1..]
+                    .iter()
+                    .take(2)
+                    .take_while(|byte| byte.is_ascii_digit())
+                    .count();
+                (if digits == 0 { TokenKind::Invalid } else { TokenKind::RingClosure }, 1 + digits)
+            }
+            b'[' => {
+                match bytes[start..].iter().position(|&byte| byte == b']') {
+                    Some(close) => (TokenKind::Atom, close + 1),
+                    None => (TokenKind::Invalid, bytes.len() - start),
+                }
+            }
+            b'C' if bytes.get(start + 1) == Some(&b'l') => (TokenKind::Atom, 2),
+            b'B' if bytes.get(start + 1) == Some(&b'r') => (TokenKind::Atom, 2),
+            b'*' | b'A'..=b'Z' | b'a'..=b'z' => (TokenKind::Atom, 1),
+            _ => {
+                let len = input[start..].chars().next().map_or(1, char::len_utf8);
+                (TokenKind::Invalid, len)
+            }
+        };
+        position = start + len;
+        Some((kind, start..position))
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+
     use elements_rs::Element;
 
-    use super::{Token, TokenKind, TokenWithSpan};
+    use super::{
+        Token, TokenKind, TokenWithSpan, lex, rewrite_tokens, tokenize_permissive,
+        tokens_to_string, verify_spans,
+    };
     use crate::{
         atom::{Atom, atom_symbol::AtomSymbol},
-        bond::{Bond, ring_num::RingNum},
+        bond::{Bond, BondDescriptor, ring_num::RingNum},
+        errors::SmilesError,
+        parser::token_iter::TokenIter,
     };
 
+    fn round_trip(input: &str) -> String {
+        round_trip_with(TokenIter::from(input))
+    }
+
+    fn round_trip_with(tokens: TokenIter<'_>) -> String {
+        tokens_to_string(&tokens.collect::<Result<Vec<_>, _>>().unwrap())
+    }
+
+    #[test]
+    fn lex_agrees_with_the_tokenizer_on_valid_input() {
+        for input in ["C[13CH2:1]%12.O/C=C\\Br", "c1ccccc1Cl", "C(=O)[O-].[Na+]", "C[R1]C"] {
+            let tokens = tokenize_permissive(input).collect::<Result<Vec<_>, _>>().unwrap();
+            let lexed = lex(input).collect::<Vec<_>>();
+            let expected = tokens
+                .iter()
+                .map(|token| {
+                    match token.token_kind() {
+                        TokenKind::UnknownBracket => (TokenKind::Atom, token.span()),
+                        kind => (kind, token.span()),
+                    }
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(lexed, expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn lex_tiles_invalid_input() {
+        for input in ["", "C C", "Cé%", "[C", "%123", "]]"] {
+            let tokens = lex(input)
+                .map(|(_, span)| TokenWithSpan::new(Token::NonBond, span.start, span.end))
+                .collect::<Vec<_>>();
+            assert_eq!(verify_spans(input, &tokens), Ok(()), "{input}");
+        }
+        assert_eq!(lex("%123").map(|(_, span)| span).collect::<Vec<_>>(), [0..3, 3..4]);
+    }
+
+    #[test]
+    fn tokens_round_trip_to_the_original_text() {
+        for input in [
+            "CC(=O)O",
+            "c1ccccc1",
+            "C%12CC%12",
+            "[13C@@H](Cl)(Br)F",
+            "[NH4+].[Cl-]",
+            "F/C=C\\F",
+            "C$C",
+            "c1ccccc1:c1ccccc1",
+            "[2H][C@H2+2:12]",
+            "*C[*:3]",
+        ] {
+            assert_eq!(round_trip(input), input);
+        }
+    }
+
+    #[test]
+    fn redundant_spellings_round_trip_verbatim() {
+        // Every field spelling the tokenizer accepts that has a shorter
+        // conventional form.
+        for input in [
+            "[CH1+1]",
+            "[Fe--]",
+            "[Fe++]",
+            "[Cu+2]",
+            "[Cu2+]",
+            "[C+0]",
+            "[CH0]",
+            "[013CH4]",
+            "[C@TH1](F)(Cl)(Br)I",
+            "[C@TH2](F)(Cl)(Br)I",
+            "[CH3:007]",
+            "[CH3:0]",
+            "[Co@OH01](F)(F)(F)(F)(F)F",
+            "[*H0-0:1]",
+        ] {
+            assert_eq!(round_trip(input), input);
+        }
+        assert_eq!(
+            round_trip_with(TokenIter::from("[O-H]").with_lenient_bracket_order(true)),
+            "[O-H]"
+        );
+        assert_eq!(
+            round_trip_with(TokenIter::from("[CH12]").with_multi_digit_hydrogen_counts(true)),
+            "[CH12]"
+        );
+        assert_eq!(
+            round_trip_with(TokenIter::from("c1ccsec1").with_unbracketed_se_as(true)),
+            "c1ccsec1"
+        );
+
+        let edited = TokenIter::from("[CH1+1][Fe--]")
+            .map(|token| {
+                let token = token.unwrap();
+                TokenWithSpan::new(token.token(), token.start(), token.end())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tokens_to_string(&edited), "[CH+][Fe-2]");
+    }
+
+    #[test]
+    fn identity_rewrite_keeps_the_original_spelling() {
+        let input = "[CH1+1]C%12CC%12";
+
+        assert_eq!(rewrite_tokens(input, Some).unwrap(), input);
+    }
+
+    #[test]
+    fn rewrites_can_drop_and_replace_tokens() {
+        let without_bonds =
+            rewrite_tokens("C=C-C#N", |token| (token.kind() != TokenKind::Bond).then_some(token));
+        assert_eq!(without_bonds.unwrap(), "CCCN");
+
+        let ring = RingNum::try_new(42).unwrap();
+        let relabelled = rewrite_tokens("C1CC1", |token| {
+            Some(if token.kind() == TokenKind::RingClosure {
+                Token::ring_closure(ring)
+            } else {
+                token
+            })
+        });
+        assert_eq!(relabelled.unwrap(), "C%42CC%42");
+    }
+
+    #[test]
+    fn ring_closures_carry_their_bond_and_partner() {
+        let tokens = TokenIter::from("C=1CC-%12CC1CC%12").collect::<Result<Vec<_>, _>>().unwrap();
+        let closures = tokens
+            .iter()
+            .enumerate()
+            .filter_map(|(index, token)| {
+                match token.token() {
+                    Token::RingClosure { ring_num, bond, partner } => {
+                        Some((index, ring_num.get(), bond, partner))
+                    }
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            closures,
+            [
+                (2, 1, Some(Bond::Double.into()), None),
+                (6, 12, Some(Bond::Single.into()), None),
+                (9, 1, None, Some(2)),
+                (12, 12, None, Some(6)),
+            ]
+        );
+
+        let reopened = TokenIter::from("C1CC1C1CC1").map(|token| token.unwrap().token());
+        let partners = reopened
+            .filter_map(|token| {
+                match token {
+                    Token::RingClosure { partner, .. } => Some(partner),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(partners, [None, Some(1), None, Some(6)]);
+    }
+
+    #[test]
+    fn rewriting_reports_tokenizer_errors() {
+        let error = rewrite_tokens("CC?", Some).unwrap_err();
+
+        assert_eq!(error.smiles_error(), SmilesError::UnexpectedCharacter('?'));
+        assert_eq!(error.span(), 2..3);
+    }
+
+    #[test]
+    fn permissive_tokenizer_captures_unknown_brackets() {
+        let input = "[Xx]C[C@XYZ].[Na+]";
+        let tokens = tokenize_permissive(input).collect::<Result<Vec<_>, _>>().unwrap();
+        let kinds = tokens.iter().map(TokenWithSpan::token_kind).collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::UnknownBracket,
+                TokenKind::Atom,
+                TokenKind::UnknownBracket,
+                TokenKind::NonBond,
+                TokenKind::Atom
+            ]
+        );
+        assert_eq!(tokens[2].token(), Token::UnknownBracket("[C@XYZ]".to_string(), 5..12));
+        assert_eq!(tokens_to_string(&tokens), input);
+    }
+
+    #[test]
+    fn permissive_tokenizer_still_reports_unclosed_brackets() {
+        let errors = tokenize_permissive("C[Xx").filter_map(Result::err).collect::<Vec<_>>();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].smiles_error(), SmilesError::UnclosedBracket);
+        assert_eq!(errors[0].span(), 1..4);
+
+        let error = tokenize_permissive("[Xx[C]").find_map(Result::err).unwrap();
+        assert_eq!(error.smiles_error(), SmilesError::UnclosedBracket);
+    }
+
+    #[test]
+    fn tokenizer_spans_tile_the_input() {
+        for input in [
+            "C",
+            "[13C@@H2+2:12]",
+            "C1=CC=CC=C1",
+            "c1ccc2ccccc2c1",
+            "C%12CC%12",
+            "F/C=C\\Cl",
+            "[Na+].[Cl-]",
+            "CC(=O)O[C@H](Br)I",
+            "[Fe--]Cl",
+            "*C([*:1])*",
+        ] {
+            let tokens = TokenIter::from(input).collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(verify_spans(input, &tokens), Ok(()), "{input}");
+        }
+
+        let input = "C[R1]C";
+        let tokens = tokenize_permissive(input).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(verify_spans(input, &tokens), Ok(()));
+    }
+
+    #[test]
+    fn verify_spans_reports_where_tiling_breaks() {
+        let token = |start, end| TokenWithSpan::new(Token::NonBond, start, end);
+
+        assert_eq!(verify_spans("", &[]), Ok(()));
+        assert_eq!(verify_spans("..", &[token(0, 1), token(1, 2)]), Ok(()));
+        assert_eq!(verify_spans("...", &[token(0, 1), token(2, 3)]), Err(1));
+        assert_eq!(verify_spans("..", &[token(0, 2), token(1, 2)]), Err(2));
+        assert_eq!(verify_spans("..", &[token(0, 0)]), Err(0));
+        assert_eq!(verify_spans("...", &[token(0, 1), token(1, 2)]), Err(2));
+    }
+
+    #[test]
+    fn tokens_display_their_spelling() {
+        assert_eq!(Token::NonBond.to_string(), ".");
+        assert_eq!(Token::Bond(BondDescriptor::aromatic(Bond::Single)).to_string(), ":");
+        assert_eq!(Token::ring_closure(RingNum::try_new(7).unwrap()).to_string(), "7");
+        assert_eq!(
+            Token::Atom(Atom::new_organic_subset(AtomSymbol::Element(Element::N), true))
+                .to_string(),
+            "n"
+        );
+    }
+
     #[test]
     fn token_variants_can_be_constructed_and_compared() {
         let bracket_atom = Atom::builder().with_symbol(AtomSymbol::Element(Element::C)).build();
@@ -217,7 +969,7 @@ mod tests {
             Token::Bond(Bond::Double.into()),
             Token::LeftParentheses,
             Token::RightParentheses,
-            Token::RingClosure(ring_num),
+            Token::ring_closure(ring_num),
         ];
 
         assert_eq!(cases[0], Token::NonBond);
@@ -226,7 +978,16 @@ mod tests {
         assert_eq!(cases[3], Token::Bond(Bond::Double.into()));
         assert_eq!(cases[4], Token::LeftParentheses);
         assert_eq!(cases[5], Token::RightParentheses);
-        assert_eq!(cases[6], Token::RingClosure(ring_num));
+        assert_eq!(cases[6], Token::ring_closure(ring_num));
+    }
+
+    #[test]
+    fn atoms_convert_to_and_from_tokens() {
+        let atom = Atom::new_organic_subset(AtomSymbol::Element(Element::O), false);
+        assert_eq!(Token::from(atom), Token::Atom(atom));
+        assert_eq!(Atom::try_from(Token::from(atom)), Ok(atom));
+        assert_eq!(Atom::try_from(Token::LeftParentheses), Err(Token::LeftParentheses));
+        assert_eq!(Token::Bond(Bond::Double.into()).as_atom(), None);
     }
 
     #[test]
@@ -246,13 +1007,13 @@ mod tests {
         let ring_num = RingNum::try_new(9).unwrap();
 
         let bracketed = TokenWithSpan::new(Token::Atom(bracket_atom), 0, 3);
-        let ring = TokenWithSpan::new(Token::RingClosure(ring_num), 5, 6);
+        let ring = TokenWithSpan::new(Token::ring_closure(ring_num), 5, 6);
         let non_bond = TokenWithSpan::new(Token::NonBond, 10, 11);
 
         assert_eq!(bracketed.token(), Token::Atom(bracket_atom));
         assert_eq!(bracketed.span(), 0..3);
 
-        assert_eq!(ring.token(), Token::RingClosure(ring_num));
+        assert_eq!(ring.token(), Token::ring_closure(ring_num));
         assert_eq!(ring.start(), 5);
         assert_eq!(ring.end(), 6);
 
@@ -260,6 +1021,32 @@ mod tests {
         assert_eq!(non_bond.span(), 10..11);
     }
 
+    #[test]
+    fn bracket_field_spans_cover_fields_in_any_order() {
+        let mut lenient = TokenIter::from("C[O-H]").with_lenient_bracket_order(true);
+        let spans = lenient.nth(1).unwrap().unwrap().bracket_field_spans().unwrap();
+        assert_eq!((spans.symbol, spans.charge, spans.hydrogens), (2..3, Some(3..4), Some(4..5)));
+        assert_eq!((spans.isotope, spans.chirality, spans.class), (None, None, None));
+
+        let input = "[Fe+2].[*:1]C";
+        let tokens = tokenize_permissive(input).collect::<Result<Vec<_>, _>>().unwrap();
+        let spans = tokens[0].bracket_field_spans().unwrap();
+        assert_eq!((spans.symbol, spans.charge), (1..3, Some(3..5)));
+        let spans = tokens[2].bracket_field_spans().unwrap();
+        assert_eq!((spans.symbol, spans.class), (8..9, Some(9..11)));
+        assert_eq!(tokens[3].bracket_field_spans(), None);
+        assert_eq!(tokens[1].bracket_field_spans(), None);
+        let rebuilt = TokenWithSpan::new(tokens[0].token(), 0, 6);
+        assert_eq!(rebuilt.bracket_field_spans(), None);
+
+        // Leading zeros push the class end past a `u16` offset.
+        let input = format!("CC[13CH3:{}7]", "0".repeat(70_000));
+        let tokens = tokenize_permissive(&input).collect::<Result<Vec<_>, _>>().unwrap();
+        let spans = tokens[2].bracket_field_spans().unwrap();
+        assert_eq!((spans.isotope, spans.symbol, spans.hydrogens), (Some(3..5), 5..6, Some(6..8)));
+        assert_eq!(spans.class, Some(8..input.len() - 1));
+    }
+
     #[test]
     fn token_with_span_clone_and_eq_behave_as_expected() {
         let original = TokenWithSpan::new(Token::LeftParentheses, 2, 3);
@@ -292,6 +1079,9 @@ mod tests {
         assert_eq!(Token::Bond(Bond::Double.into()).kind(), TokenKind::Bond);
         assert_eq!(Token::LeftParentheses.kind(), TokenKind::LeftParentheses);
         assert_eq!(Token::RightParentheses.kind(), TokenKind::RightParentheses);
-        assert_eq!(Token::RingClosure(RingNum::try_new(1).unwrap()).kind(), TokenKind::RingClosure);
+        assert_eq!(
+            Token::ring_closure(RingNum::try_new(1).unwrap()).kind(),
+            TokenKind::RingClosure
+        );
     }
 }
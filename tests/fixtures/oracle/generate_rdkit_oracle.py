@@ -0,0 +1,67 @@
+"""Regenerate the RDKit oracle fixture used by `tests/test_rdkit_oracle.rs`.
+
+Reads the molecules of `../rdkit_molecular_formula.csv.gz` and writes
+`rdkit_oracle.tsv.gz` next to this script, with the RDKit canonical SMILES,
+molecular formula, ring count and aromatic atom count of every molecule RDKit
+can sanitize.
+
+The fixture is tab-separated without any quoting, so that it can be read by
+splitting lines on tabs; a field containing a tab or newline is an error.
+
+Usage: python generate_rdkit_oracle.py
+"""
+
+import csv
+import gzip
+from pathlib import Path
+
+from rdkit import Chem, RDLogger, rdBase
+from rdkit.Chem.rdMolDescriptors import CalcMolFormula
+
+HERE = Path(__file__).resolve().parent
+SOURCE = HERE.parent / "rdkit_molecular_formula.csv.gz"
+TARGET = HERE / "rdkit_oracle.tsv.gz"
+HEADER = [
+    "name",
+    "smiles",
+    "rdkit_canonical_smiles",
+    "rdkit_formula",
+    "rdkit_ring_count",
+    "rdkit_aromatic_atoms",
+]
+
+
+def main() -> None:
+    RDLogger.DisableLog("rdApp.*")
+    written = skipped = 0
+    with gzip.open(SOURCE, "rt", newline="") as source, gzip.open(
+        TARGET, "wt", newline=""
+    ) as target:
+        writer = csv.writer(
+            target, delimiter="\t", quoting=csv.QUOTE_NONE, lineterminator="\n"
+        )
+        writer.writerow(HEADER)
+        for row in csv.DictReader(source):
+            molecule = Chem.MolFromSmiles(row["smiles"])
+            if molecule is None or "*" in row["smiles"]:
+                skipped += 1
+                continue
+            writer.writerow(
+                [
+                    row["name"],
+                    row["smiles"],
+                    Chem.MolToSmiles(molecule),
+                    CalcMolFormula(molecule),
+                    molecule.GetRingInfo().NumRings(),
+                    sum(atom.GetIsAromatic() for atom in molecule.GetAtoms()),
+                ]
+            )
+            written += 1
+    print(
+        f"wrote {written} molecules to {TARGET.name} with RDKit "
+        f"{rdBase.rdkitVersion}, skipped {skipped}"
+    )
+
+
+if __name__ == "__main__":
+    main()
@@ -3,6 +3,7 @@
 mod canonicalization_common;
 
 use canonicalization_common::{assert_same_canonical_group, canonical_string};
+use smiles_parser::prelude::Smiles;
 
 #[test]
 fn canonicalize_preserves_directional_bond_distinction() {
@@ -126,3 +127,23 @@ fn canonicalize_preserves_semantic_stereo_distinctions() {
     assert_ne!(canonical_string("N[C@](Br)(O)C"), canonical_string("N[C@@](Br)(O)C"));
     assert_ne!(canonical_string("OC(Cl)=[C@]=C(C)F"), canonical_string("OC(Cl)=[C@@]=C(C)F"));
 }
+
+#[test]
+fn canonical_output_rederives_stereo_for_the_canonical_atom_order() {
+    for source in [
+        "N[C@](Br)(O)C",
+        "N[C@@](Br)(O)C",
+        "C[C@H]1CCCCO1",
+        "O1CCCC[C@@H]1C",
+        "C1CC[C@H]2CCCC[C@@H]2C1",
+        "F/C=C/F",
+        "F/C=C\\F",
+        "Cl/C=C/C=C\\Br",
+        "C/C=C/[C@H](N)O",
+    ] {
+        let original: Smiles = source.parse().unwrap();
+        let canonical: Smiles = canonical_string(source).parse().unwrap();
+        let diff = original.diff(&canonical);
+        assert!(diff.is_empty(), "{source} -> {}: {diff:?}", canonical_string(source));
+    }
+}
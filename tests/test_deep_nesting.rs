@@ -0,0 +1,68 @@
+//! Fuzz regression tests for adversarially deep inputs: branches nested ten
+//! thousand levels deep and long chains must parse, render, canonicalize and
+//! go through ring perception without overflowing the stack.
+
+use smiles_parser::prelude::Smiles;
+
+/// A chain of `depth + 1` carbons written as `depth` nested branches.
+fn nested_branches(depth: usize) -> String {
+    format!("{}C{}", "C(".repeat(depth), ")".repeat(depth))
+}
+
+/// A chain of `depth` cyclopropanes, each one written in a branch of the
+/// previous ring and every ring closed with the number 1.
+fn nested_rings(depth: usize) -> String {
+    format!("{}C{}", "C1CC1(".repeat(depth), ")".repeat(depth))
+}
+
+fn parse(source: &str) -> Smiles {
+    source.parse().unwrap_or_else(|err| panic!("deep input must parse, got {err:?}"))
+}
+
+#[test]
+fn ten_thousand_nested_branches_do_not_overflow_the_stack() {
+    let depth = 10_000;
+    let smiles = parse(&nested_branches(depth));
+    assert_eq!(smiles.nodes().len(), depth + 1);
+
+    let chain = "C".repeat(depth + 1);
+    assert_eq!(smiles.render(), chain);
+    assert_eq!(smiles.canonicalize().render(), chain);
+    assert!(smiles.symm_sssr_result().cycles().is_empty());
+    assert!(!smiles.ring_atom_membership().atom_flags().contains(&true));
+    assert_eq!(parse(&smiles.render()), smiles);
+}
+
+#[test]
+fn long_chains_do_not_overflow_the_stack() {
+    let chain = "C".repeat(20_000);
+    let smiles = parse(&chain);
+    assert_eq!(smiles.render(), chain);
+    assert_eq!(smiles.canonicalize().render(), chain);
+    assert!(smiles.ring_membership().atom_ids().is_empty());
+}
+
+#[test]
+fn deeply_nested_rings_do_not_overflow_the_stack() {
+    let depth = 2_000;
+    let smiles = parse(&nested_rings(depth));
+    assert_eq!(smiles.nodes().len(), 3 * depth + 1);
+    assert_eq!(smiles.symm_sssr_result().cycles().len(), depth);
+
+    let ring_atoms = smiles.ring_atom_membership();
+    assert_eq!(ring_atoms.atom_flags().iter().filter(|&&in_ring| in_ring).count(), 3 * depth);
+    assert!(!ring_atoms.atom_flags()[3 * depth]);
+
+    let canonical = smiles.canonicalize().render();
+    assert_eq!(parse(&canonical).canonicalize().render(), canonical);
+}
+
+#[test]
+fn unbalanced_deep_nesting_is_rejected() {
+    let depth = 10_000;
+    let unclosed = format!("{}C", "C(".repeat(depth));
+    assert!(unclosed.parse::<Smiles>().is_err());
+
+    let unopened = format!("C{}", ")".repeat(depth));
+    assert!(unopened.parse::<Smiles>().is_err());
+}
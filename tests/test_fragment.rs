@@ -114,3 +114,39 @@ fn from_bonds_non_edge_errors() {
     let err = m.fragment_from_bonds([bond_edge(0, 2, Bond::Single, None)]);
     assert_eq!(err.unwrap_err(), SubgraphError::BondReferencesUnknownAtom(2));
 }
+
+fn fragment_key(smiles: &str) -> String {
+    smiles.parse::<Smiles>().unwrap().fragment_key()
+}
+
+#[test]
+fn fragment_key_groups_protonation_states_and_isotopologues() {
+    let key = fragment_key("NCC(=O)O");
+    for variant in [
+        "[NH3+]CC(=O)[O-]",
+        "NCC(=O)[O-]",
+        "[15NH2][13CH2]C(=O)O",
+        "[2H]OC(=O)CN",
+        "[H]N([H])CC(O)=O",
+    ] {
+        assert_eq!(fragment_key(variant), key, "{variant}");
+    }
+    assert_eq!(key, "NCC(=O)O".parse::<Smiles>().unwrap().canonicalize().render());
+}
+
+#[test]
+fn fragment_key_ignores_stereo_but_keeps_the_skeleton() {
+    assert_eq!(fragment_key("C[C@H](N)O"), fragment_key("C[C@@H](N)O"));
+    assert_eq!(fragment_key("C/C=C/C"), fragment_key("C/C=C\\C"));
+    assert_eq!(fragment_key("[NH4+].[Cl-]"), fragment_key("Cl.N"));
+    assert_ne!(fragment_key("CCO"), fragment_key("COC"));
+    assert_ne!(fragment_key("C=CC"), fragment_key("CCC"));
+}
+
+#[test]
+fn fragment_key_keeps_non_organic_atoms_bracketed() {
+    assert_eq!(fragment_key("[Na+].[O-]C(=O)C"), fragment_key("CC(=O)O.[Na]"));
+    assert_ne!(fragment_key("[Na+].[O-]C(=O)C"), fragment_key("CC(=O)O[Na]"));
+    assert!(fragment_key("[Na+]").contains("[Na]"));
+    assert_eq!(fragment_key("[H][H]"), "");
+}
@@ -0,0 +1,95 @@
+//! Allocation counts of parsing: the working memory of a parse (branch stack,
+//! ring table and duplicate-bond set) must not allocate for small molecules,
+//! nor, with a warmed [`ParserScratch`], for large or deeply nested ones.
+//!
+//! The returned graph always allocates, so the tests compare spellings of the
+//! same graph that only differ in the working memory they need. The counting
+//! allocator is global, hence this file holds its own test binary.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use smiles_parser::prelude::{ParserOptions, ParserScratch, Smiles};
+
+/// The system allocator, counting the allocations of the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+// SAFETY: every call is forwarded to the system allocator unchanged.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns the allocations `parse` made on this thread, with its result.
+fn allocations_of(parse: impl FnOnce() -> Smiles) -> (usize, Smiles) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let smiles = parse();
+    (ALLOCATIONS.with(Cell::get) - before, smiles)
+}
+
+/// Asserts that every spelling parses to the atoms and bond count of the
+/// first one, with as many allocations.
+fn assert_same_allocations(spellings: &[&str], mut parse: impl FnMut(&str) -> Smiles) {
+    let (expected, graph) = allocations_of(|| parse(spellings[0]));
+    for spelling in &spellings[1..] {
+        let (allocations, smiles) = allocations_of(|| parse(spelling));
+        assert_eq!(smiles.nodes(), graph.nodes(), "{spelling}");
+        assert_eq!(smiles.number_of_bonds(), graph.number_of_bonds(), "{spelling}");
+        assert_eq!(
+            allocations, expected,
+            "{spelling} allocated {allocations} times, {} {expected} times",
+            spellings[0]
+        );
+    }
+}
+
+#[test]
+fn small_molecules_need_no_working_memory() {
+    let parse = |source: &str| source.parse().unwrap();
+    assert_same_allocations(&["CC", "C(C)", "C1.C1", "C%10.C%10"], parse);
+    assert_same_allocations(&["CC.CC.CC.CC.CC", "C1.C2.C3.C4.C5.C1.C2.C3.C4.C5"], parse);
+}
+
+#[test]
+fn warmed_scratch_covers_large_and_nested_molecules() {
+    let chain = "C".repeat(100);
+    let nested = format!("{}C{}", "C(".repeat(99), ")".repeat(99));
+    let looped = format!("C1{}C1", "C".repeat(98));
+    let spellings = [chain.as_str(), nested.as_str()];
+    let options = ParserOptions::default();
+    let mut scratch = ParserScratch::default();
+    let mut parse =
+        |source: &str| Smiles::parse_with_scratch(source, &options, &mut scratch).unwrap();
+    for source in [&chain, &nested, &looped] {
+        parse(source);
+    }
+
+    assert_same_allocations(&spellings, &mut parse);
+    let (first, _) = allocations_of(|| parse(&looped));
+    let (second, _) = allocations_of(|| parse(&looped));
+    assert_eq!(first, second);
+}
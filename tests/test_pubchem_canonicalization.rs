@@ -4,19 +4,21 @@
 
 use std::{
     env,
-    fs::File,
-    io::{self, BufRead, BufReader, Read},
+    io::{self, BufRead},
     path::{Path, PathBuf},
     sync::atomic::{AtomicUsize, Ordering},
     time::Instant,
 };
 
-use flate2::read::GzDecoder;
 use indicatif::{ProgressBar, ProgressStyle};
 use molecular_formulas::prelude::ChemicalFormula;
 use rayon::prelude::*;
-use smiles_parser::prelude::{
-    CacheMode, DatasetFetchOptions, DatasetSource, GzipMode, PUBCHEM_SMILES, Smiles, WildcardSmiles,
+use smiles_parser::{
+    io::{SmilesTableReader, SmilesTableRecord, TableError, TableOptions, open_text_reader},
+    prelude::{
+        CacheMode, DatasetFetchOptions, DatasetSource, GzipMode, PUBCHEM_SMILES, Smiles,
+        WildcardSmiles,
+    },
 };
 
 #[test]
@@ -45,16 +47,16 @@ fn validate_pubchem_canonicalization_corpus() -> Result<(), Box<dyn std::error::
     let started = Instant::now();
     let validated = AtomicUsize::new(0);
 
-    let validation_result =
-        open_pubchem_corpus(&corpus_path)?.lines().take(record_count).par_bridge().try_for_each(
-            |line| -> Result<(), String> {
-                let line = line.map_err(|error| error.to_string())?;
-                validate_record_line(&line)?;
-                validated.fetch_add(1, Ordering::Relaxed);
-                progress_bar.inc(1);
-                Ok(())
-            },
-        );
+    let validation_result = pubchem_records(&corpus_path)?
+        .take(record_count)
+        .par_bridge()
+        .try_for_each(|record| -> Result<(), String> {
+            let record = record.map_err(|error| error.to_string())?;
+            validate_record(&record)?;
+            validated.fetch_add(1, Ordering::Relaxed);
+            progress_bar.inc(1);
+            Ok(())
+        });
 
     if let Err(error) = validation_result {
         progress_bar.abandon_with_message("canonicalization failed");
@@ -100,22 +102,22 @@ fn validate_pubchem_molecular_formula_corpus() -> Result<(), Box<dyn std::error:
     let formulas = AtomicUsize::new(0);
     let wildcards = AtomicUsize::new(0);
 
-    let validation_result =
-        open_pubchem_corpus(&corpus_path)?.lines().take(record_count).par_bridge().try_for_each(
-            |line| -> Result<(), String> {
-                let line = line.map_err(|error| error.to_string())?;
-                match validate_formula_record_line(&line)? {
-                    FormulaRecordKind::Formula => {
-                        formulas.fetch_add(1, Ordering::Relaxed);
-                    }
-                    FormulaRecordKind::Wildcard => {
-                        wildcards.fetch_add(1, Ordering::Relaxed);
-                    }
+    let validation_result = pubchem_records(&corpus_path)?
+        .take(record_count)
+        .par_bridge()
+        .try_for_each(|record| -> Result<(), String> {
+            let record = record.map_err(|error| error.to_string())?;
+            match validate_formula_record(&record)? {
+                FormulaRecordKind::Formula => {
+                    formulas.fetch_add(1, Ordering::Relaxed);
                 }
-                progress_bar.inc(1);
-                Ok(())
-            },
-        );
+                FormulaRecordKind::Wildcard => {
+                    wildcards.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            progress_bar.inc(1);
+            Ok(())
+        });
 
     if let Err(error) = validation_result {
         progress_bar.abandon_with_message("molecular formula validation failed");
@@ -136,7 +138,15 @@ fn validate_pubchem_molecular_formula_corpus() -> Result<(), Box<dyn std::error:
 }
 
 fn pubchem_record_count(path: &Path) -> Result<usize, io::Error> {
-    Ok(open_pubchem_corpus(path)?.lines().count())
+    Ok(open_text_reader(path)?.lines().count())
+}
+
+/// Streams the `CID<TAB>SMILES` rows of the corpus at `path`, parsed as
+/// strict SMILES.
+fn pubchem_records(
+    path: &Path,
+) -> Result<impl Iterator<Item = Result<SmilesTableRecord, TableError>> + Send, TableError> {
+    SmilesTableReader::open(path, &TableOptions::pubchem())
 }
 
 fn pubchem_corpus_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
@@ -160,10 +170,8 @@ fn pubchem_formula_corpus_path() -> Result<PathBuf, Box<dyn std::error::Error>>
     pubchem_corpus_path()
 }
 
-fn validate_record_line(line: &str) -> Result<(), String> {
-    let (cid, smiles_text) = line
-        .split_once('\t')
-        .ok_or_else(|| format!("expected CID<TAB>SMILES record, got: {line}"))?;
+fn validate_record(record: &SmilesTableRecord) -> Result<(), String> {
+    let (cid, smiles_text) = (record.id().unwrap_or_default(), record.smiles());
 
     if smiles_text.contains('*') {
         let smiles = smiles_text.parse::<WildcardSmiles>().map_err(|error| {
@@ -171,9 +179,7 @@ fn validate_record_line(line: &str) -> Result<(), String> {
         })?;
         validate_canonicalization(cid, smiles_text, &smiles.canonicalize())?;
     } else {
-        let smiles = smiles_text.parse::<Smiles>().map_err(|error| {
-            format!("cid={cid} failed to parse SMILES:\n{}", error.render(smiles_text))
-        })?;
+        let smiles = parsed_strict(cid, record)?;
         validate_canonicalization(cid, smiles_text, &smiles.canonicalize())?;
     }
 
@@ -186,10 +192,8 @@ enum FormulaRecordKind {
     Wildcard,
 }
 
-fn validate_formula_record_line(line: &str) -> Result<FormulaRecordKind, String> {
-    let (cid, smiles_text) = line
-        .split_once('\t')
-        .ok_or_else(|| format!("expected CID<TAB>SMILES record, got: {line}"))?;
+fn validate_formula_record(record: &SmilesTableRecord) -> Result<FormulaRecordKind, String> {
+    let (cid, smiles_text) = (record.id().unwrap_or_default(), record.smiles());
 
     if smiles_text.contains('*') {
         smiles_text.parse::<WildcardSmiles>().map_err(|error| {
@@ -198,13 +202,21 @@ fn validate_formula_record_line(line: &str) -> Result<FormulaRecordKind, String>
         return Ok(FormulaRecordKind::Wildcard);
     }
 
-    let smiles = smiles_text.parse::<Smiles>().map_err(|error| {
-        format!("cid={cid} failed to parse SMILES:\n{}", error.render(smiles_text))
-    })?;
-    let _formula: ChemicalFormula<u32, i32> = ChemicalFormula::from(&smiles);
+    let smiles = parsed_strict(cid, record)?;
+    let _formula: ChemicalFormula<u32, i32> = ChemicalFormula::from(smiles);
     Ok(FormulaRecordKind::Formula)
 }
 
+/// Returns the strict graph the reader parsed for `record`.
+fn parsed_strict<'record>(
+    cid: &str,
+    record: &'record SmilesTableRecord,
+) -> Result<&'record Smiles, String> {
+    record.parsed().map_err(|error| {
+        format!("cid={cid} failed to parse SMILES:\n{}", error.render(record.smiles()))
+    })
+}
+
 fn validate_canonicalization(
     cid: &str,
     smiles_text: &str,
@@ -252,45 +264,6 @@ impl CanonicalizedSmiles for WildcardSmiles {
     }
 }
 
-enum PubchemCorpusReader {
-    Plain(BufReader<File>),
-    Gzip(BufReader<GzDecoder<File>>),
-}
-
-impl Read for PubchemCorpusReader {
-    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
-        match self {
-            Self::Plain(reader) => reader.read(buf),
-            Self::Gzip(reader) => reader.read(buf),
-        }
-    }
-}
-
-impl BufRead for PubchemCorpusReader {
-    fn fill_buf(&mut self) -> Result<&[u8], io::Error> {
-        match self {
-            Self::Plain(reader) => reader.fill_buf(),
-            Self::Gzip(reader) => reader.fill_buf(),
-        }
-    }
-
-    fn consume(&mut self, amt: usize) {
-        match self {
-            Self::Plain(reader) => reader.consume(amt),
-            Self::Gzip(reader) => reader.consume(amt),
-        }
-    }
-}
-
-fn open_pubchem_corpus(path: &Path) -> Result<PubchemCorpusReader, io::Error> {
-    let file = File::open(path)?;
-    if path.extension().is_some_and(|ext| ext == "gz") {
-        Ok(PubchemCorpusReader::Gzip(BufReader::new(GzDecoder::new(file))))
-    } else {
-        Ok(PubchemCorpusReader::Plain(BufReader::new(file)))
-    }
-}
-
 fn env_usize(name: &str) -> Option<usize> {
     env::var(name).ok().and_then(|raw| raw.parse().ok())
 }
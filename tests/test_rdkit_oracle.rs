@@ -0,0 +1,131 @@
+//! Oracle tests comparing this crate against RDKit on a fixture of a few
+//! thousand molecules.
+//!
+//! The fixture is a tab-separated file without quoting, written by
+//! `tests/fixtures/oracle/generate_rdkit_oracle.py`. It needs RDKit to be
+//! generated and is not checked in; run the script before
+//! `cargo test --features oracle-tests`.
+#![cfg(feature = "oracle-tests")]
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+use flate2::read::GzDecoder;
+use molecular_formulas::prelude::ChemicalFormula;
+use smiles_parser::prelude::Smiles;
+
+const RDKIT_ORACLE_FIXTURE: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/oracle/rdkit_oracle.tsv.gz");
+const RDKIT_ORACLE_HEADER: &str = "name\tsmiles\trdkit_canonical_smiles\trdkit_formula\t\
+                                   rdkit_ring_count\trdkit_aromatic_atoms";
+/// Mismatches listed per check when the oracle disagrees.
+const REPORTED_MISMATCHES: usize = 10;
+type OracleFormula = ChemicalFormula<u32, i32>;
+
+#[derive(Debug, Default)]
+struct OracleMismatches {
+    total: usize,
+    canonical: Vec<String>,
+    formula: Vec<String>,
+    ring_count: Vec<String>,
+    aromatic_atoms: Vec<String>,
+}
+
+impl OracleMismatches {
+    fn assert_none(&self) {
+        let checks = [
+            ("canonical SMILES", &self.canonical),
+            ("molecular formula", &self.formula),
+            ("ring count", &self.ring_count),
+            ("aromatic atom count", &self.aromatic_atoms),
+        ];
+        let report = checks
+            .iter()
+            .filter(|(_, mismatches)| !mismatches.is_empty())
+            .map(|(check, mismatches)| {
+                let listed =
+                    mismatches.iter().take(REPORTED_MISMATCHES).cloned().collect::<Vec<_>>();
+                format!("{} {check} mismatches, e.g.\n  {}", mismatches.len(), listed.join("\n  "))
+            })
+            .collect::<Vec<_>>();
+        assert!(report.is_empty(), "{} molecules checked:\n{}", self.total, report.join("\n"));
+    }
+}
+
+#[test]
+fn rdkit_oracle_fixture_matches() {
+    let file = File::open(RDKIT_ORACLE_FIXTURE).unwrap_or_else(|error| {
+        panic!(
+            "{RDKIT_ORACLE_FIXTURE} is missing ({error}); run \
+             tests/fixtures/oracle/generate_rdkit_oracle.py to create it"
+        )
+    });
+    let mut lines = BufReader::new(GzDecoder::new(file)).lines();
+    assert_eq!(
+        lines.next().transpose().expect("fixture header should be readable").as_deref(),
+        Some(RDKIT_ORACLE_HEADER)
+    );
+
+    let mut mismatches = OracleMismatches::default();
+    for (line_index, line) in lines.enumerate() {
+        let line =
+            line.unwrap_or_else(|error| panic!("fixture line {} failed: {error}", line_index + 2));
+        let [
+            name,
+            smiles_text,
+            rdkit_canonical,
+            rdkit_formula,
+            rdkit_ring_count,
+            rdkit_aromatic_atoms,
+        ] = line.split('\t').collect::<Vec<_>>()[..]
+        else {
+            panic!("fixture line {} has the wrong column count: {line}", line_index + 2);
+        };
+        mismatches.total += 1;
+
+        let smiles: Smiles = smiles_text
+            .parse()
+            .unwrap_or_else(|error| panic!("{name} failed to parse {smiles_text}: {error}"));
+        let rdkit_smiles: Smiles = rdkit_canonical
+            .parse()
+            .unwrap_or_else(|error| panic!("{name} failed to parse {rdkit_canonical}: {error}"));
+
+        // RDKit spells canonical SMILES differently, so its canonical string
+        // must describe the same molecule: both reach one canonical form here.
+        let canonical = smiles.canonicalize().render();
+        let rdkit_canonical_here = rdkit_smiles.canonicalize().render();
+        if canonical != rdkit_canonical_here {
+            mismatches.canonical.push(format!(
+                "{name}: {smiles_text} -> {canonical}, RDKit {rdkit_canonical} -> {rdkit_canonical_here}"
+            ));
+        }
+
+        let formula = OracleFormula::try_from(rdkit_formula).unwrap_or_else(|error| {
+            panic!("{name} has invalid RDKit formula {rdkit_formula}: {error}")
+        });
+        if !smiles.matches_formula(&formula) {
+            mismatches.formula.push(format!(
+                "{name}: {smiles_text} -> {}, RDKit {rdkit_formula}",
+                OracleFormula::from(&smiles)
+            ));
+        }
+
+        let ring_count = smiles.symm_sssr_result().cycles().len();
+        if rdkit_ring_count.parse() != Ok(ring_count) {
+            mismatches
+                .ring_count
+                .push(format!("{name}: {smiles_text} -> {ring_count}, RDKit {rdkit_ring_count}"));
+        }
+
+        let aromatic_atoms = smiles.aromaticity_assignment().atom_ids().len();
+        if rdkit_aromatic_atoms.parse() != Ok(aromatic_atoms) {
+            mismatches.aromatic_atoms.push(format!(
+                "{name}: {smiles_text} -> {aromatic_atoms}, RDKit {rdkit_aromatic_atoms}"
+            ));
+        }
+    }
+
+    mismatches.assert_none();
+}
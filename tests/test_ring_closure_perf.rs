@@ -0,0 +1,95 @@
+//! Perf regression tests for ring-closure pairing and ring perception on
+//! macrocycles and on polycyclic graphs with hundreds of rings written with
+//! reused ring-closure numbers.
+
+use std::time::{Duration, Instant};
+
+use smiles_parser::prelude::{ParserOptions, ParserScratch, Smiles};
+
+const RING_BUDGET: Duration =
+    if cfg!(debug_assertions) { Duration::from_secs(10) } else { Duration::from_millis(250) };
+
+/// A single ring of `atoms` carbons.
+fn macrocycle(atoms: usize) -> String {
+    format!("C1{}C1", "C".repeat(atoms - 2))
+}
+
+/// A ladder of `rings` fused four-membered rings, written as a zigzag path
+/// whose missing edges are closed with the numbers 1 and 2 in turn.
+fn ladder(rings: usize) -> String {
+    let path_atoms = 2 * (rings + 1);
+    let mut smiles = String::new();
+    for index in 0..path_atoms {
+        smiles.push('C');
+        if index >= 3 && index % 2 == 1 {
+            smiles.push_str(&(((index - 3) / 2) % 2 + 1).to_string());
+        }
+        if index % 2 == 0 && index + 3 < path_atoms {
+            smiles.push_str(&((index / 2) % 2 + 1).to_string());
+        }
+    }
+    smiles
+}
+
+/// A chain of `rings` six-membered rings joined at spiro atoms, each ring
+/// closed with the number its predecessor left free.
+fn spiro_chain(rings: usize) -> String {
+    let (mut closing, mut opening) = (1, 2);
+    let mut smiles = String::from("C1CC");
+    for _ in 1..rings {
+        smiles.push_str(&format!("C{opening}(CC{closing})CC"));
+        (closing, opening) = (opening, closing);
+    }
+    smiles.push_str(&format!("CC{closing}"));
+    smiles
+}
+
+fn assert_rings_within_budget(label: &str, source: &str, expected_rings: usize) {
+    let started = Instant::now();
+    let smiles: Smiles = source
+        .parse()
+        .unwrap_or_else(|err| panic!("{label}: source must parse, got {err:?}: {source}"));
+    let result = smiles.symm_sssr_result();
+    let elapsed = started.elapsed();
+
+    assert_eq!(result.cycles().len(), expected_rings, "{label}");
+    assert!(
+        elapsed <= RING_BUDGET,
+        "{label}: parsing and ring perception took {elapsed:?}, exceeds budget {RING_BUDGET:?}"
+    );
+}
+
+#[test]
+fn ring_budget_macrocycle() {
+    assert_rings_within_budget("2000-membered macrocycle", &macrocycle(2000), 1);
+}
+
+#[test]
+fn ring_budget_fused_ladder() {
+    let source = ladder(400);
+    assert_rings_within_budget("ladder of 400 four-membered rings", &source, 400);
+    assert!(source.starts_with("C1CC2C1C1C2"));
+}
+
+#[test]
+fn ring_budget_spiro_chain() {
+    let source = spiro_chain(300);
+    assert_rings_within_budget("chain of 300 spiro-fused rings", &source, 300);
+    assert!(source.starts_with("C1CCC2(CC1)CCC1(CC2)"));
+}
+
+#[test]
+fn reused_ring_numbers_pair_the_same_with_and_without_scratch() {
+    let mut scratch = ParserScratch::default();
+    let options = ParserOptions::default();
+    for source in [ladder(50), spiro_chain(50), macrocycle(120), ladder(3)] {
+        let with_scratch = Smiles::parse_with_scratch(&source, &options, &mut scratch).unwrap();
+        let without_scratch: Smiles = source.parse().unwrap();
+        assert_eq!(with_scratch, without_scratch, "{source}");
+    }
+    assert!(Smiles::parse_with_scratch("C1CC", &options, &mut scratch).is_err());
+    assert_eq!(
+        Smiles::parse_with_scratch("C1CC1", &options, &mut scratch).unwrap(),
+        "C1CC1".parse::<Smiles>().unwrap()
+    );
+}
@@ -1,7 +1,7 @@
 //! Test for tokenizing square brackets
 
 use elements_rs::Element;
-use smiles_parser::{errors::SmilesError, smiles::Smiles};
+use smiles_parser::{errors::SmilesError, prelude::ParserOptions, smiles::Smiles};
 
 /// const for testing square brackets
 const SMILES_WITH_BRACKETS: &[&str] = &[
@@ -116,7 +116,12 @@ fn test_hydrogen_hcount_gt_one_stays_invalid() {
 
 #[test]
 fn test_bracket_hydrogen_count_at_cap_parses() {
-    let smiles = Smiles::from_str("[CH15]").unwrap_or_else(|_| panic!("Failed to parse [CH15]"));
+    let smiles = Smiles::from_str("[CH9]").unwrap_or_else(|_| panic!("Failed to parse [CH9]"));
+    assert_eq!(smiles.nodes()[0].hydrogen_count(), 9);
+
+    let options = ParserOptions { multi_digit_hydrogen_counts: true, ..ParserOptions::default() };
+    let smiles = Smiles::parse_with_options("[CH15]", &options)
+        .unwrap_or_else(|_| panic!("Failed to parse [CH15]"));
 
     assert_eq!(smiles.nodes().len(), 1);
     assert_eq!(smiles.nodes()[0].hydrogen_count(), 15);
@@ -124,7 +129,11 @@ fn test_bracket_hydrogen_count_at_cap_parses() {
 
 #[test]
 fn test_bracket_hydrogen_count_just_over_cap_is_rejected() {
-    let err = Smiles::from_str("[CH16]").unwrap_err();
+    let err = Smiles::from_str("[CH10]").unwrap_err();
+    assert_eq!(err.smiles_error(), SmilesError::HydrogenCountOverflow(10));
+
+    let options = ParserOptions { multi_digit_hydrogen_counts: true, ..ParserOptions::default() };
+    let err = Smiles::parse_with_options("[CH16]", &options).unwrap_err();
 
     assert_eq!(err.smiles_error(), SmilesError::HydrogenCountOverflow(16));
 }
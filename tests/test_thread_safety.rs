@@ -0,0 +1,64 @@
+//! Compile-time checks that parsed graphs, tokens and errors can be shared
+//! across threads.
+#![allow(dead_code)]
+
+use smiles_parser::{
+    atom::Atom,
+    bond::BondDescriptor,
+    library::Library,
+    prelude::{
+        CompactSmiles, Fingerprint, KekulizationError, ParserOptions, ParserScratch, SharedSmiles,
+        Smiles, SmilesComponents, SmilesError, SmilesErrorWithSource, SmilesErrorWithSpan,
+        WildcardSmiles,
+    },
+    token::{Token, TokenKind, TokenWithSpan},
+};
+
+const fn assert_send_sync<T: Send + Sync>() {}
+
+const _: () = {
+    assert_send_sync::<Smiles>();
+    assert_send_sync::<WildcardSmiles>();
+    assert_send_sync::<SharedSmiles>();
+    assert_send_sync::<CompactSmiles>();
+    assert_send_sync::<SmilesComponents>();
+    assert_send_sync::<Atom>();
+    assert_send_sync::<BondDescriptor>();
+    assert_send_sync::<Fingerprint>();
+    assert_send_sync::<Library>();
+    assert_send_sync::<ParserOptions>();
+    assert_send_sync::<ParserScratch>();
+    assert_send_sync::<Token>();
+    assert_send_sync::<TokenKind>();
+    assert_send_sync::<TokenWithSpan>();
+    assert_send_sync::<SmilesError>();
+    assert_send_sync::<SmilesErrorWithSpan>();
+    assert_send_sync::<SmilesErrorWithSource<'static>>();
+    assert_send_sync::<KekulizationError>();
+};
+
+#[cfg(feature = "io")]
+const _: () = {
+    use smiles_parser::io::{SdfError, SmbError, TableError, ValidationReport};
+
+    assert_send_sync::<SdfError>();
+    assert_send_sync::<SmbError>();
+    assert_send_sync::<TableError>();
+    assert_send_sync::<ValidationReport>();
+};
+
+#[test]
+fn parsed_graphs_are_shared_between_threads() {
+    let shared = SharedSmiles::from("OC(=O)c1ccccc1O".parse::<Smiles>().unwrap());
+    let rendered = std::thread::scope(|scope| {
+        let handles = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                scope.spawn(move || shared.canonicalize().render())
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+    });
+    assert!(rendered.windows(2).all(|pair| pair[0] == pair[1]));
+    assert_eq!(SharedSmiles::handle_count(&shared), 1);
+}